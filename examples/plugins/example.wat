@@ -0,0 +1,99 @@
+;; An example plugin with a page showing the last player event, an action skipping
+;; to the next track and a visualization pulsing with the beats.
+;;
+;; Copy this file to the `plugins` folder of the application's config folder to use it.
+(module
+  (import "spotify_player" "send_request" (func $send_request (param i32 i32) (result i32)))
+  (import "spotify_player" "log" (func $log (param i32 i32) (result i32)))
+
+  ;; memory layout:
+  ;; - constant strings at offset 0
+  ;; - the last player event at offset 1024 (at most 8 KiB)
+  ;; - the function's argument at offset 16384 (at most 16 KiB)
+  ;; - the function's output at offset 32768
+  (memory (export "memory") 1)
+
+  (data (i32.const 0) "{\"description\":\"an example plugin\",\"pages\":[\"Last event\"],\"actions\":[\"Skip\"],\"visualizations\":[\"Pulse\"]}")
+  (data (i32.const 256) "{\"type\":\"next_track\"}")
+  (data (i32.const 320) "\"beat\":")
+  (data (i32.const 336) "\"kind\":\"page\"")
+  (data (i32.const 352) "Last event:\n")
+
+  (global $event_len (mut i32) (i32.const 0))
+
+  ;; pack a string into an `i64` as `(ptr << 32) | len`
+  (func $pack (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len))))
+
+  ;; find the position of a needle in a string, or -1
+  (func $find (param $ptr i32) (param $len i32) (param $needle i32) (param $needle_len i32) (result i32)
+    (local $i i32)
+    (local $j i32)
+    (block $not_found
+      (loop $next_position
+        (br_if $not_found
+          (i32.gt_s (i32.add (local.get $i) (local.get $needle_len)) (local.get $len)))
+        (local.set $j (i32.const 0))
+        (block $mismatch
+          (loop $next_byte
+            (if (i32.eq (local.get $j) (local.get $needle_len))
+              (then (return (local.get $i))))
+            (br_if $mismatch
+              (i32.ne
+                (i32.load8_u (i32.add (local.get $ptr) (i32.add (local.get $i) (local.get $j))))
+                (i32.load8_u (i32.add (local.get $needle) (local.get $j)))))
+            (local.set $j (i32.add (local.get $j) (i32.const 1)))
+            (br $next_byte)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next_position)))
+    (i32.const -1))
+
+  (func (export "spotify_player_alloc") (param $len i32) (result i32)
+    (if (i32.gt_u (local.get $len) (i32.const 16384))
+      (then unreachable))
+    (i32.const 16384))
+
+  (func (export "spotify_player_manifest") (result i64)
+    (call $pack (i32.const 0) (i32.const 104)))
+
+  (func (export "spotify_player_on_event") (param $ptr i32) (param $len i32)
+    (if (i32.gt_u (local.get $len) (i32.const 8192))
+      (then (local.set $len (i32.const 8192))))
+    (memory.copy (i32.const 1024) (local.get $ptr) (local.get $len))
+    (global.set $event_len (local.get $len))
+    (drop (call $log (local.get $ptr) (local.get $len))))
+
+  ;; the plugin has a single action
+  (func (export "spotify_player_run_action") (param $ptr i32) (param $len i32)
+    (drop (call $send_request (i32.const 256) (i32.const 21))))
+
+  (func (export "spotify_player_render") (param $ptr i32) (param $len i32) (result i64)
+    (local $i i32)
+    (local $beat i32)
+    (local $digit i32)
+
+    ;; the page: the heading followed by the last event
+    (if (i32.ge_s (call $find (local.get $ptr) (local.get $len) (i32.const 336) (i32.const 13)) (i32.const 0))
+      (then
+        (memory.copy (i32.const 32768) (i32.const 352) (i32.const 12))
+        (memory.copy (i32.const 32780) (i32.const 1024) (global.get $event_len))
+        (return (call $pack (i32.const 32768) (i32.add (i32.const 12) (global.get $event_len))))))
+
+    ;; the visualization: a bar growing with each beat of a 8-beat phrase
+    (local.set $i (call $find (local.get $ptr) (local.get $len) (i32.const 320) (i32.const 7)))
+    (if (i32.ge_s (local.get $i) (i32.const 0))
+      (then
+        (local.set $i (i32.add (local.get $ptr) (i32.add (local.get $i) (i32.const 7))))
+        (block $done
+          (loop $next_digit
+            (local.set $digit (i32.sub (i32.load8_u (local.get $i)) (i32.const 48)))
+            (br_if $done (i32.gt_u (local.get $digit) (i32.const 9)))
+            (local.set $beat
+              (i32.add (i32.mul (local.get $beat) (i32.const 10)) (local.get $digit)))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br $next_digit)))))
+    (local.set $len (i32.mul (i32.add (i32.rem_u (local.get $beat) (i32.const 8)) (i32.const 1)) (i32.const 4)))
+    (memory.fill (i32.const 32768) (i32.const 35) (local.get $len))
+    (call $pack (i32.const 32768) (local.get $len))))
@@ -0,0 +1,120 @@
+//! Benchmarks of the application's rendering hot paths, run with `cargo bench`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ratatui::{backend::TestBackend, style::Style, Terminal};
+use spotify_player::{
+    ui::{glyphs, visualizations},
+    utils,
+};
+
+/// The size of the visualization area, similar to a full-screen terminal
+const WIDTH: u16 = 200;
+const HEIGHT: u16 = 30;
+/// The number of items in the fuzzy search benchmark
+const N_SEARCH_ITEMS: usize = 50_000;
+
+fn bench_visualizations(c: &mut Criterion) {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).expect("test terminal");
+    let color = Some((200, 80, 40));
+
+    let mut group = c.benchmark_group("visualization");
+    group.bench_function("waves", |b| {
+        b.iter(|| {
+            terminal
+                .draw(|frame| {
+                    visualizations::render_concentric_waves(
+                        frame,
+                        frame.area(),
+                        black_box(12.5),
+                        color,
+                        &glyphs::UNICODE,
+                        true,
+                    );
+                })
+                .expect("draw");
+        });
+    });
+    group.bench_function("bars", |b| {
+        b.iter(|| {
+            terminal
+                .draw(|frame| {
+                    visualizations::render_spectrum_bars(
+                        frame,
+                        frame.area(),
+                        black_box(12.5),
+                        color,
+                        &glyphs::UNICODE,
+                        true,
+                    );
+                })
+                .expect("draw");
+        });
+    });
+    group.bench_function("particles", |b| {
+        b.iter(|| {
+            terminal
+                .draw(|frame| {
+                    visualizations::render_particles(
+                        frame,
+                        frame.area(),
+                        black_box(12.5),
+                        color,
+                        &glyphs::UNICODE,
+                        true,
+                    );
+                })
+                .expect("draw");
+        });
+    });
+    group.finish();
+}
+
+fn bench_coalesce_spans(c: &mut Criterion) {
+    // a row alternating between runs of two styles
+    let styles = [
+        Style::default(),
+        Style::default().fg(ratatui::style::Color::Red),
+    ];
+    let cells = (0..WIDTH)
+        .map(|x| ('█', styles[usize::from(x / 8) % 2]))
+        .collect::<Vec<_>>();
+
+    c.bench_function("coalesce_spans", |b| {
+        b.iter(|| visualizations::coalesce_spans(black_box(cells.iter().copied())));
+    });
+}
+
+fn bench_search(c: &mut Criterion) {
+    let items = (0..N_SEARCH_ITEMS)
+        .map(|i| format!("Track {i} • Artist {} • Album {}", i % 997, i % 113))
+        .collect::<Vec<_>>();
+
+    c.bench_function("search_50k_items", |b| {
+        b.iter(|| utils::filtered_items_from_query(black_box("artist 42 album"), &items));
+    });
+}
+
+#[cfg(feature = "image")]
+fn bench_color_extraction(c: &mut Criterion) {
+    let image = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(640, 640, |x, y| {
+        image::Rgb([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8])
+    }));
+
+    c.bench_function("extract_dominant_color", |b| {
+        b.iter(|| utils::extract_dominant_color(black_box(&image)));
+    });
+}
+
+#[cfg(not(feature = "image"))]
+fn bench_color_extraction(_: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    bench_visualizations,
+    bench_coalesce_spans,
+    bench_search,
+    bench_color_extraction
+);
+criterion_main!(benches);
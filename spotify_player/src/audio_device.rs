@@ -0,0 +1,127 @@
+//! Watching the audio output devices to pause the integrated player's playback when
+//! its audio output device goes away, e.g. when headphones are disconnected.
+//!
+//! - On Linux, the audio server's events are watched using `pactl subscribe`, which supports
+//!   both `PulseAudio` and `PipeWire` (with its `PulseAudio` server)
+//! - On macOS and Windows, the system's default audio output device is polled
+
+use anyhow::Result;
+
+use crate::{
+    client::{ClientRequest, PlayerRequest},
+    config,
+    state::SharedState,
+};
+
+/// Start the application's audio output device watcher, which blocks the current thread
+pub fn start_device_watcher(
+    state: &SharedState,
+    client_pub: &flume::Sender<ClientRequest>,
+) -> Result<()> {
+    tracing::info!("Initializing application's audio output device watcher...");
+    watch_device_changes(|change| handle_device_change(state, client_pub, change))
+}
+
+/// Pause the playback if it's played by the integrated player
+fn handle_device_change(
+    state: &SharedState,
+    client_pub: &flume::Sender<ClientRequest>,
+    change: &str,
+) {
+    let configs = config::get_config();
+    let is_playing_on_integrated_player = state
+        .player
+        .read()
+        .current_playback()
+        .is_some_and(|p| p.is_playing && p.device.name == configs.app_config.device.name);
+    if !is_playing_on_integrated_player {
+        return;
+    }
+
+    tracing::info!("Pausing the playback because {change}");
+    if let Err(err) = client_pub.send(ClientRequest::Player(PlayerRequest::Pause)) {
+        tracing::error!("Failed to pause the playback: {err:#}");
+        return;
+    }
+    state
+        .ui
+        .lock()
+        .show_toast(format!("Playback paused: {change}"));
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn watch_device_changes(mut on_change: impl FnMut(&str)) -> Result<()> {
+    use anyhow::Context;
+    use std::io::BufRead;
+
+    let mut child = std::process::Command::new("pactl")
+        .arg("subscribe")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .context("run `pactl subscribe`, is `pactl` (PulseAudio or PipeWire) installed?")?;
+    let stdout = child
+        .stdout
+        .take()
+        .context("get `pactl subscribe`'s output")?;
+
+    let mut default_sink = get_default_sink();
+    for line in std::io::BufReader::new(stdout).lines() {
+        let line = line?;
+        // the default sink is changed when it's removed, so a sink's removal
+        // is only reported if it isn't reported as a default sink's change
+        let is_sink_removed = line.starts_with("Event 'remove' on sink ");
+        if !is_sink_removed && !line.starts_with("Event 'change' on server") {
+            continue;
+        }
+
+        let sink = get_default_sink();
+        if sink != default_sink {
+            default_sink = sink;
+            on_change("the default audio output device changed");
+        } else if is_sink_removed {
+            on_change("an audio output device was removed");
+        }
+    }
+
+    child.wait().ok();
+    anyhow::bail!("`pactl subscribe` exited unexpectedly")
+}
+
+/// Get the name of the audio server's default sink
+#[cfg(all(unix, not(target_os = "macos")))]
+fn get_default_sink() -> Option<String> {
+    let output = std::process::Command::new("pactl")
+        .arg("get-default-sink")
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn watch_device_changes(mut on_change: impl FnMut(&str)) -> Result<()> {
+    use cpal::traits::{DeviceTrait, HostTrait};
+
+    /// the duration between two consecutive polls of the default audio output device
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+    let host = cpal::default_host();
+    let get_default_device = || host.default_output_device().and_then(|d| d.name().ok());
+
+    let mut default_device = get_default_device();
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let device = get_default_device();
+        if device != default_device {
+            on_change(if device.is_none() {
+                "the audio output device was removed"
+            } else {
+                "the default audio output device changed"
+            });
+            default_device = device;
+        }
+    }
+}
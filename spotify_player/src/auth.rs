@@ -56,17 +56,23 @@ impl AuthConfig {
     }
 
     pub fn new(configs: &config::Configs) -> Result<AuthConfig> {
-        let audio_cache_folder = if configs.app_config.device.audio_cache {
+        let device = &configs.app_config.device;
+        let audio_cache_folder = if device.audio_cache {
             Some(configs.cache_folder.join("audio"))
         } else {
             None
         };
+        // the least recently used audio files are removed when the audio cache exceeds its size limit
+        let audio_cache_size_limit = match device.audio_cache_size_limit_in_mb {
+            0 => None,
+            size_in_mb => Some(size_in_mb * 1024 * 1024),
+        };
 
         let cache = Cache::new(
             Some(configs.cache_folder.clone()),
             None,
             audio_cache_folder,
-            None,
+            audio_cache_size_limit,
         )?;
 
         Ok(AuthConfig {
@@ -0,0 +1,193 @@
+//! Detection of the terminal's capabilities.
+//!
+//! The terminal is probed once on startup (see `probe`), before the application's UI is initialized.
+//! Rendering code reads the detected capabilities with `get` instead of guessing them.
+//! Without a probe (e.g. for the daemon or the CLI), the capabilities of a modern terminal are assumed.
+
+use std::{io::Write, sync::OnceLock};
+
+static CAPABILITIES: OnceLock<Capabilities> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Capabilities {
+    /// whether the terminal supports 24-bit RGB colors
+    pub truecolor: bool,
+    /// whether the terminal's locale uses UTF-8, so unicode symbols can be drawn
+    pub unicode: bool,
+    /// whether the terminal renders emojis two columns wide, as the application's layout assumes
+    pub wide_emoji: bool,
+    /// the protocol used to draw images
+    #[cfg(feature = "image")]
+    pub image_protocol: ImageProtocol,
+    /// whether the terminal supports copying to the clipboard with the `OSC 52` escape sequence
+    pub osc52: bool,
+    /// whether the terminal reports mouse events
+    pub mouse: bool,
+    /// whether the terminal has a light background, queried with the `OSC 11` escape sequence
+    pub light_background: bool,
+}
+
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    Kitty,
+    Iterm,
+    #[cfg(feature = "sixel")]
+    Sixel,
+    /// half blocks colored with the image's pixels, supported by every terminal
+    Blocks,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            truecolor: true,
+            unicode: true,
+            wide_emoji: true,
+            #[cfg(feature = "image")]
+            image_protocol: ImageProtocol::Blocks,
+            osc52: false,
+            mouse: true,
+            light_background: false,
+        }
+    }
+}
+
+/// Get the terminal's capabilities
+pub fn get() -> &'static Capabilities {
+    CAPABILITIES.get_or_init(Capabilities::default)
+}
+
+/// Probe the terminal's capabilities, which must be done before the application's UI is initialized
+pub fn probe() {
+    use std::io::IsTerminal;
+
+    if !std::io::stdout().is_terminal() {
+        return;
+    }
+
+    let term = env("TERM");
+    let term_program = env("TERM_PROGRAM");
+    let unicode = is_utf8_locale();
+    let capabilities = Capabilities {
+        truecolor: is_truecolor(&term, &term_program),
+        unicode,
+        wide_emoji: unicode && emoji_width().is_none_or(|width| width == 2),
+        #[cfg(feature = "image")]
+        image_protocol: image_protocol(),
+        osc52: is_osc52_supported(&term, &term_program),
+        // the Linux console only reports mouse events with a separate daemon (`gpm`)
+        mouse: term != "linux" && term != "dumb",
+        light_background: is_light_background(),
+    };
+    tracing::info!("Terminal capabilities: {capabilities:?}");
+
+    if CAPABILITIES.set(capabilities).is_err() {
+        tracing::warn!("The terminal's capabilities were already probed");
+    }
+}
+
+fn env(name: &str) -> String {
+    std::env::var(name).unwrap_or_default()
+}
+
+fn is_truecolor(term: &str, term_program: &str) -> bool {
+    let colorterm = env("COLORTERM");
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        return true;
+    }
+    // terminals known to only support the 8/16/256 ANSI colors,
+    // other terminals are assumed to support RGB colors even without advertising it
+    !(term_program == "Apple_Terminal"
+        || matches!(
+            term,
+            "linux" | "dumb" | "xterm" | "screen" | "vt100" | "vt220"
+        )
+        || term.ends_with("-16color")
+        || term.ends_with("-8color"))
+}
+
+fn is_utf8_locale() -> bool {
+    if cfg!(target_os = "windows") {
+        return true;
+    }
+    // the first set variable decides the locale's character set
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .map(env)
+        .find(|value| !value.is_empty())
+        .is_none_or(|locale| {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+fn is_osc52_supported(term: &str, term_program: &str) -> bool {
+    matches!(term_program, "iTerm.app" | "WezTerm" | "ghostty")
+        || [
+            "kitty",
+            "alacritty",
+            "foot",
+            "wezterm",
+            "ghostty",
+            "contour",
+        ]
+        .iter()
+        .any(|name| term.contains(name))
+        || ["KITTY_WINDOW_ID", "ALACRITTY_WINDOW_ID", "WT_SESSION"]
+            .iter()
+            .any(|name| std::env::var_os(name).is_some())
+}
+
+fn is_light_background() -> bool {
+    match terminal_colorsaurus::theme_mode(terminal_colorsaurus::QueryOptions::default()) {
+        Ok(mode) => mode == terminal_colorsaurus::ThemeMode::Light,
+        Err(err) => {
+            tracing::info!("Failed to detect the terminal's background: {err:#}");
+            false
+        }
+    }
+}
+
+/// Measure the number of columns the terminal advances its cursor by when printing an emoji,
+/// which is done in the alternate screen to not leave the emoji in the terminal
+fn emoji_width() -> Option<u16> {
+    fn measure(stdout: &mut std::io::Stdout) -> std::io::Result<u16> {
+        crossterm::execute!(stdout, crossterm::cursor::MoveTo(0, 0))?;
+        write!(stdout, "🐱")?;
+        stdout.flush()?;
+        Ok(crossterm::cursor::position()?.0)
+    }
+
+    let mut stdout = std::io::stdout();
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let width = crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)
+        .and_then(|()| measure(&mut stdout));
+    let _ = crossterm::execute!(stdout, crossterm::terminal::LeaveAlternateScreen);
+    let _ = crossterm::terminal::disable_raw_mode();
+
+    match width {
+        Ok(width) => Some(width),
+        Err(err) => {
+            tracing::info!("Failed to measure the terminal's emoji width: {err:#}");
+            None
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+fn image_protocol() -> ImageProtocol {
+    // `viuer` caches the results of its checks, which are reused when rendering images
+    if viuer::get_kitty_support() != viuer::KittySupport::None {
+        return ImageProtocol::Kitty;
+    }
+    if viuer::is_iterm_supported() {
+        return ImageProtocol::Iterm;
+    }
+    #[cfg(feature = "sixel")]
+    if viuer::is_sixel_supported() {
+        return ImageProtocol::Sixel;
+    }
+    ImageProtocol::Blocks
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::Write as _,
     fs::{create_dir_all, remove_dir_all},
     io::Write,
@@ -14,7 +14,8 @@ use tracing::Instrument;
 use crate::{
     cli::Request,
     client::{AppClient, PlayerRequest},
-    config::get_cache_folder_path,
+    config::{self, get_cache_folder_path},
+    hooks,
     state::{
         AlbumId, ArtistId, Context, ContextId, Id, PlayableId, Playback, PlaybackMetadata,
         PlaylistId, SharedState, TrackId,
@@ -105,13 +106,25 @@ async fn current_playback(
     }
 }
 
-async fn handle_socket_request(
+pub async fn handle_socket_request(
     client: &AppClient,
     state: Option<&SharedState>,
     request: super::Request,
 ) -> Result<Vec<u8>> {
     if let Some(state) = state {
-        client.check_valid_session(state).await?;
+        // the current playback is read from the application's state, so getting it
+        // (e.g. by a status bar) doesn't wake up an idle daemon
+        #[cfg(feature = "daemon")]
+        let is_idle_read = state.player.read().is_idle
+            && matches!(
+                request,
+                Request::Get(GetRequest::Key(Key::Playback) | GetRequest::Current { .. })
+            );
+        #[cfg(not(feature = "daemon"))]
+        let is_idle_read = false;
+        if !is_idle_read {
+            client.check_valid_session(state).await?;
+        }
     }
 
     match request {
@@ -119,6 +132,9 @@ async fn handle_socket_request(
         Request::Get(GetRequest::Item(item_type, id_or_name)) => {
             handle_get_item_request(client, item_type, id_or_name).await
         }
+        Request::Get(GetRequest::Current { format }) => {
+            handle_get_current_request(client, state, &format).await
+        }
         Request::Playback(command) => {
             handle_playback_request(client, state, command).await?;
             Ok(Vec::new())
@@ -156,11 +172,17 @@ async fn handle_socket_request(
                 },
             };
 
-            if let Some(id) = track.and_then(|t| t.id.clone()) {
-                if unlike {
-                    client.current_user_saved_tracks_delete([id]).await?;
-                } else {
-                    client.current_user_saved_tracks_add([id]).await?;
+            if let Some(track) = track {
+                if let Some(id) = track.id.clone() {
+                    if unlike {
+                        client.current_user_saved_tracks_delete([id]).await?;
+                    } else {
+                        client.current_user_saved_tracks_add([id]).await?;
+                        hooks::trigger(
+                            hooks::HookEvent::Liked,
+                            &hooks::HookContext::from_full_track(track),
+                        );
+                    }
                 }
             }
 
@@ -174,9 +196,146 @@ async fn handle_socket_request(
             let resp = handle_search_request(client, query).await?;
             Ok(resp)
         }
+        Request::Wrapped { year, format } => {
+            let report = super::wrapped::generate(client, year, format).await?;
+            Ok(report.into_bytes())
+        }
+        Request::Open { link, play } => {
+            if let Some(state) = state {
+                client.open_spotify_link(state, &link, play).await?;
+            } else {
+                // without a running application, there is no page to open so the linked item is played
+                let uri = client.resolve_spotify_link(&link).await?;
+                let playback = Playback::from_uri(&uri)
+                    .with_context(|| format!("unsupported Spotify link {link}"))?;
+                let current_playback = client.current_playback2().await?;
+                client
+                    .handle_player_request(
+                        PlayerRequest::StartPlayback(playback, None),
+                        current_playback
+                            .as_ref()
+                            .map(PlaybackMetadata::from_playback),
+                    )
+                    .await?;
+            }
+            Ok(Vec::new())
+        }
+        Request::Share { format } => {
+            let Some(playback) = current_playback(client, state).await? else {
+                anyhow::bail!("nothing is playing");
+            };
+            let snippet = super::share::render(client, &playback, format).await?;
+            Ok(snippet.into_bytes())
+        }
     }
 }
 
+/// Handle a request to get the current playback formatted by a template
+async fn handle_get_current_request(
+    client: &AppClient,
+    state: Option<&SharedState>,
+    format: &str,
+) -> Result<Vec<u8>> {
+    let Some(playback) = current_playback(client, state).await? else {
+        return Ok(Vec::new());
+    };
+    let Some(ref item) = playback.item else {
+        return Ok(Vec::new());
+    };
+    let configs = config::get_config();
+
+    let (title, artist, album, uri, duration) = match item {
+        rspotify::model::PlayableItem::Track(track) => (
+            track.name.clone(),
+            crate::utils::map_join(&track.artists, |a| &a.name, ", "),
+            track.album.name.clone(),
+            track.id.as_ref().map(Id::uri).unwrap_or_default(),
+            track.duration,
+        ),
+        rspotify::model::PlayableItem::Episode(episode) => (
+            episode.name.clone(),
+            episode.show.publisher.clone(),
+            episode.show.name.clone(),
+            episode.id.uri(),
+            episode.duration,
+        ),
+        rspotify::model::PlayableItem::Unknown(_) => return Ok(Vec::new()),
+    };
+
+    let liked = match item {
+        rspotify::model::PlayableItem::Track(track) if format.contains("liked") => {
+            match (state, &track.id) {
+                (Some(state), Some(id)) => state
+                    .data
+                    .read()
+                    .user_data
+                    .saved_tracks
+                    .contains_key(&id.uri()),
+                (None, Some(id)) => client
+                    .current_user_saved_tracks_contains([id.as_ref()])
+                    .await?
+                    .first()
+                    .copied()
+                    .unwrap_or_default(),
+                _ => false,
+            }
+        }
+        _ => false,
+    };
+
+    let progress = playback.progress.unwrap_or_default().min(duration);
+    let values = HashMap::from([
+        ("title", title),
+        ("artist", artist),
+        ("album", album),
+        ("uri", uri),
+        ("progress", crate::utils::format_duration(&progress)),
+        ("duration", crate::utils::format_duration(&duration)),
+        (
+            "volume",
+            playback
+                .device
+                .volume_percent
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        ),
+        ("device", playback.device.name.clone()),
+        (
+            "status",
+            if playback.is_playing {
+                configs.app_config.play_icon.clone()
+            } else {
+                configs.app_config.pause_icon.clone()
+            },
+        ),
+        (
+            "liked",
+            if liked {
+                configs.app_config.liked_icon.clone()
+            } else {
+                String::new()
+            },
+        ),
+        (
+            "shuffle",
+            if playback.shuffle_state {
+                "shuffle".to_string()
+            } else {
+                String::new()
+            },
+        ),
+        (
+            "repeat",
+            match playback.repeat_state {
+                rspotify::model::RepeatState::Off => String::new(),
+                state => <&str>::from(state).to_string(),
+            },
+        ),
+    ]);
+
+    Ok(super::template::render(format, &values)?.into_bytes())
+}
+
 async fn handle_get_key_request(
     client: &AppClient,
     state: Option<&SharedState>,
@@ -310,9 +469,9 @@ async fn handle_get_item_request(
 ) -> Result<Vec<u8>> {
     let sid = get_spotify_id(client, item_type, id_or_name).await?;
     Ok(match sid {
-        ItemId::Playlist(id) => serde_json::to_vec(&client.playlist_context(id).await?)?,
-        ItemId::Album(id) => serde_json::to_vec(&client.album_context(id).await?)?,
-        ItemId::Artist(id) => serde_json::to_vec(&client.artist_context(id).await?)?,
+        ItemId::Playlist(id) => serde_json::to_vec(&client.playlist_context(id, None).await?)?,
+        ItemId::Album(id) => serde_json::to_vec(&client.album_context(id, None).await?)?,
+        ItemId::Artist(id) => serde_json::to_vec(&client.artist_context(id, None).await?)?,
         ItemId::Track(id) => serde_json::to_vec(&client.track(id).await?)?,
     })
 }
@@ -341,7 +500,14 @@ async fn handle_playback_request(
             let tracks = client.radio_tracks(sid.uri()).await?;
 
             PlayerRequest::StartPlayback(
-                Playback::URIs(tracks.into_iter().map(|t| t.id.into()).collect(), None),
+                Playback::URIs(
+                    tracks
+                        .into_iter()
+                        .filter(|t| !t.is_unavailable)
+                        .map(|t| t.id.into())
+                        .collect(),
+                    None,
+                ),
                 None,
             )
         }
@@ -354,6 +520,7 @@ async fn handle_playback_request(
                     .user_data
                     .saved_tracks
                     .values()
+                    .filter(|t| !t.is_unavailable)
                     .map(|t| t.id.clone().into())
                     .collect()
             } else {
@@ -361,6 +528,7 @@ async fn handle_playback_request(
                     .current_user_saved_tracks()
                     .await?
                     .into_iter()
+                    .filter(|t| !t.is_unavailable)
                     .map(|t| t.id.into())
                     .collect()
             };
@@ -708,7 +876,7 @@ async fn playlist_import(
         name: String,
     }
     // Get playlists' info
-    let (from_tracks, from_name) = match client.playlist_context(import_from.clone()).await? {
+    let (from_tracks, from_name) = match client.playlist_context(import_from.clone(), None).await? {
         Context::Playlist { tracks, playlist } => (
             tracks.into_iter().map(|t| TrackData {
                 id: t.id,
@@ -718,7 +886,7 @@ async fn playlist_import(
         ),
         _ => unreachable!(),
     };
-    let (to_tracks, to_name) = match client.playlist_context(import_to.clone()).await? {
+    let (to_tracks, to_name) = match client.playlist_context(import_to.clone(), None).await? {
         Context::Playlist { tracks, playlist } => (
             tracks.into_iter().map(|t| TrackData {
                 id: t.id,
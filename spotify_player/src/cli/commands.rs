@@ -3,7 +3,7 @@ use clap_complete::Shell;
 
 use crate::cli::EditAction;
 
-use super::{ContextType, ItemType, Key};
+use super::{ContextType, ItemType, Key, ReportFormat, ShareFormat};
 
 pub fn init_connect_subcommand() -> Command {
     add_id_or_name_group(Command::new("connect").about("Connect to a Spotify device"))
@@ -27,6 +27,17 @@ pub fn init_get_subcommand() -> Command {
                     .required(true),
             ),
         ))
+        .subcommand(
+            Command::new("current")
+                .about("Get the current playback formatted by a template")
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .default_value("{artist} – {title}")
+                        .help("The output's template. Available placeholders: title, artist, album, uri, progress, duration, volume, device, status, liked, shuffle, repeat"),
+                ),
+        )
 }
 
 fn init_playback_start_subcommand() -> Command {
@@ -134,6 +145,67 @@ pub fn init_search_command() -> Command {
         .arg(Arg::new("query").help("Search query").required(true))
 }
 
+pub fn init_wrapped_command() -> Command {
+    Command::new("wrapped")
+        .about("Generate a year-in-review report from the local listen history")
+        .arg(
+            Arg::new("year")
+                .short('y')
+                .long("year")
+                .value_parser(value_parser!(i32))
+                .help("Year of the report (defaults to the current year)"),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_parser(EnumValueParser::<ReportFormat>::new())
+                .default_value("terminal")
+                .help("Format of the report"),
+        )
+}
+
+pub fn init_open_command() -> Command {
+    Command::new("open")
+        .about("Open a Spotify link or URI in the running application, or play it if there is none")
+        .arg(
+            Arg::new("link")
+                .help("A Spotify link (`https://open.spotify.com/...` or `https://spotify.link/...`) or URI (`spotify:...`)")
+                .required(true),
+        )
+        .arg(
+            Arg::new("play")
+                .short('p')
+                .long("play")
+                .action(ArgAction::SetTrue)
+                .help("Play the linked item instead of opening its page"),
+        )
+}
+
+pub fn init_register_uri_handler_command() -> Command {
+    Command::new("register-uri-handler")
+        .about("Register the application as the handler for `spotify:` URIs, which are opened with the `open` command")
+        .arg(
+            Arg::new("unregister")
+                .long("unregister")
+                .action(ArgAction::SetTrue)
+                .help("Remove the registration instead"),
+        )
+}
+
+pub fn init_share_command() -> Command {
+    Command::new("share")
+        .about("Render the current playback as a text snippet for sharing")
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_parser(EnumValueParser::<ShareFormat>::new())
+                .default_value("text")
+                .help("Format of the snippet (`ansi` keeps colors for terminals)"),
+        )
+}
+
 pub fn init_like_command() -> Command {
     Command::new("like")
         .about("Like currently playing track")
@@ -150,6 +222,30 @@ pub fn init_authenticate_command() -> Command {
     Command::new("authenticate").about("Authenticate the application")
 }
 
+pub fn init_export_command() -> Command {
+    Command::new("export")
+        .about("Export the user's local data (listen history, ratings, tags, bookmarks and blocklist) as JSON")
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_parser(value_parser!(std::path::PathBuf))
+                .help("Output file (defaults to stdout)"),
+        )
+}
+
+pub fn init_import_history_command() -> Command {
+    Command::new("import-history")
+        .about("Import Spotify's \"Extended Streaming History\" data export into the local listen history")
+        .arg(
+            Arg::new("paths")
+                .num_args(1..)
+                .required(true)
+                .value_parser(value_parser!(std::path::PathBuf))
+                .help("History JSON files or directories containing them"),
+        )
+}
+
 pub fn init_generate_command() -> Command {
     Command::new("generate")
         .about("Generate shell completion for the application CLI")
@@ -2,8 +2,8 @@ use crate::{auth::AuthConfig, client};
 
 use super::{
     config, init_cli, start_socket, AlbumId, Command, ContextType, EditAction, GetRequest,
-    IdOrName, ItemType, Key, PlaylistCommand, PlaylistId, Request, Response, TrackId,
-    MAX_REQUEST_SIZE,
+    IdOrName, ItemType, Key, PlaylistCommand, PlaylistId, ReportFormat, Request, Response,
+    ShareFormat, TrackId, MAX_REQUEST_SIZE,
 };
 use anyhow::{Context, Result};
 use clap::{ArgMatches, Id};
@@ -66,6 +66,13 @@ fn handle_get_subcommand(args: &ArgMatches) -> Request {
             let id_or_name = get_id_or_name(args);
             Request::Get(GetRequest::Item(item_type, id_or_name))
         }
+        "current" => {
+            let format = args
+                .get_one::<String>("format")
+                .expect("format should have a default value")
+                .to_owned();
+            Request::Get(GetRequest::Current { format })
+        }
         _ => unreachable!(),
     };
 
@@ -195,6 +202,36 @@ pub fn handle_cli_subcommand(cmd: &str, args: &ArgMatches) -> Result<()> {
             generate(gen, &mut cmd, name, &mut std::io::stdout());
             std::process::exit(0);
         }
+        "import-history" => {
+            let paths = args
+                .get_many::<std::path::PathBuf>("paths")
+                .expect("paths argument is required")
+                .cloned()
+                .collect::<Vec<_>>();
+            println!(
+                "{}",
+                super::history_import::import(&configs.cache_folder, &paths)?
+            );
+            std::process::exit(0);
+        }
+        "register-uri-handler" => {
+            println!(
+                "{}",
+                super::uri_handler::register(args.get_flag("unregister"))?
+            );
+            std::process::exit(0);
+        }
+        "export" => {
+            let data = crate::db::export(&configs.cache_folder)?;
+            if let Some(path) = args.get_one::<std::path::PathBuf>("output") {
+                let f = std::io::BufWriter::new(std::fs::File::create(path)?);
+                serde_json::to_writer_pretty(f, &data)?;
+            } else {
+                serde_json::to_writer_pretty(std::io::stdout(), &data)?;
+                println!();
+            }
+            std::process::exit(0);
+        }
         _ => {}
     }
 
@@ -216,6 +253,27 @@ pub fn handle_cli_subcommand(cmd: &str, args: &ArgMatches) -> Result<()> {
                 .expect("query is required")
                 .to_owned(),
         },
+        "wrapped" => Request::Wrapped {
+            year: args
+                .get_one::<i32>("year")
+                .copied()
+                .unwrap_or_else(|| chrono::Datelike::year(&chrono::Local::now())),
+            format: *args
+                .get_one::<ReportFormat>("format")
+                .expect("format should have a default value"),
+        },
+        "share" => Request::Share {
+            format: *args
+                .get_one::<ShareFormat>("format")
+                .expect("format should have a default value"),
+        },
+        "open" => Request::Open {
+            link: args
+                .get_one::<String>("link")
+                .expect("link is required")
+                .to_owned(),
+            play: args.get_flag("play"),
+        },
         _ => unreachable!(),
     };
 
@@ -0,0 +1,111 @@
+//! An importer for Spotify's "Extended Streaming History" data export, which backfills
+//! the listen history stored in the local database.
+//!
+//! The export consists of JSON files (e.g. `Streaming_History_Audio_2019-2021_0.json`),
+//! each of which contains a list of streams. Streams of non-track items (podcast episodes, videos, etc)
+//! are ignored.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::db::Listen;
+
+#[derive(Debug, Deserialize)]
+/// A stream in the extended streaming history
+struct Stream {
+    /// the time the stream ended, in UTC
+    ts: String,
+    ms_played: i64,
+    master_metadata_track_name: Option<String>,
+    master_metadata_album_artist_name: Option<String>,
+    master_metadata_album_album_name: Option<String>,
+    spotify_track_uri: Option<String>,
+}
+
+impl Stream {
+    fn into_listen(self) -> Result<Option<Listen>> {
+        let (Some(track_uri), Some(track_name)) =
+            (self.spotify_track_uri, self.master_metadata_track_name)
+        else {
+            return Ok(None);
+        };
+        let ended_at = chrono::DateTime::parse_from_rfc3339(&self.ts)
+            .with_context(|| format!("invalid timestamp {}", self.ts))?;
+
+        Ok(Some(Listen {
+            track_uri,
+            track_name,
+            artists: self.master_metadata_album_artist_name.unwrap_or_default(),
+            album: self.master_metadata_album_album_name.unwrap_or_default(),
+            played_at: ended_at.timestamp() - self.ms_played / 1000,
+            ms_played: self.ms_played,
+        }))
+    }
+}
+
+/// Get the history files from the given paths, expanding directories into the JSON files inside them
+fn history_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            let mut dir_files = path
+                .read_dir()
+                .with_context(|| format!("read directory {}", path.display()))?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect::<Vec<_>>();
+            dir_files.sort();
+            files.extend(dir_files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn read_history_file(path: &Path) -> Result<Vec<Listen>> {
+    let data = std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let streams: Vec<Stream> =
+        serde_json::from_str(&data).with_context(|| format!("parse {}", path.display()))?;
+    Ok(streams
+        .into_iter()
+        .map(Stream::into_listen)
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+/// Import the extended streaming history files (or directories of files) into the local database,
+/// returning a summary of the import
+pub fn import(cache_folder: &Path, paths: &[PathBuf]) -> Result<String> {
+    let mut summary = String::new();
+    let (mut n_listens, mut n_imported) = (0, 0);
+
+    for file in history_files(paths)? {
+        let listens = read_history_file(&file)?;
+        let imported = crate::db::insert_listens(cache_folder, &listens)?;
+        writeln!(
+            summary,
+            "{}: imported {imported} of {} track streams",
+            file.display(),
+            listens.len()
+        )?;
+        n_listens += listens.len();
+        n_imported += imported;
+    }
+
+    write!(
+        summary,
+        "Imported {n_imported} track streams in total ({} were already in the listen history)",
+        n_listens - n_imported
+    )?;
+    Ok(summary)
+}
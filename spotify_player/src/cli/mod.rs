@@ -1,6 +1,11 @@
 mod client;
 mod commands;
 mod handlers;
+mod history_import;
+mod share;
+mod template;
+mod uri_handler;
+mod wrapped;
 
 use crate::config;
 use rspotify::model::{AlbumId, ArtistId, Id, PlaylistId, TrackId};
@@ -8,6 +13,8 @@ use serde::{Deserialize, Serialize};
 
 const MAX_REQUEST_SIZE: usize = 4096;
 
+#[cfg(any(feature = "remote", feature = "mqtt"))]
+pub use client::handle_socket_request;
 pub use client::start_socket;
 pub use handlers::handle_cli_subcommand;
 
@@ -50,6 +57,7 @@ enum ItemId {
 pub enum GetRequest {
     Key(Key),
     Item(ItemType, IdOrName),
+    Current { format: String },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -58,6 +66,19 @@ pub enum IdOrName {
     Name(String),
 }
 
+#[derive(Debug, Serialize, Deserialize, clap::ValueEnum, Clone, Copy)]
+pub enum ReportFormat {
+    Terminal,
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Serialize, Deserialize, clap::ValueEnum, Clone, Copy)]
+pub enum ShareFormat {
+    Text,
+    Ansi,
+}
+
 #[derive(Debug, Serialize, Deserialize, clap::ValueEnum, Clone, Copy)]
 pub enum EditAction {
     Add,
@@ -131,6 +152,9 @@ pub enum Request {
     Like { unlike: bool },
     Playlist(PlaylistCommand),
     Search { query: String },
+    Wrapped { year: i32, format: ReportFormat },
+    Share { format: ShareFormat },
+    Open { link: String, play: bool },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -175,7 +199,25 @@ pub fn init_cli() -> anyhow::Result<clap::Command> {
         .subcommand(commands::init_authenticate_command())
         .subcommand(commands::init_playlist_subcommand())
         .subcommand(commands::init_generate_command())
+        .subcommand(commands::init_export_command())
+        .subcommand(commands::init_import_history_command())
         .subcommand(commands::init_search_command())
+        .subcommand(commands::init_wrapped_command())
+        .subcommand(commands::init_share_command())
+        .subcommand(commands::init_open_command())
+        .subcommand(commands::init_register_uri_handler_command())
+        .arg(
+            clap::Arg::new("link")
+                .value_name("LINK")
+                .help("A Spotify link or URI (album, playlist, artist, show, track or episode) to open when the application starts"),
+        )
+        .arg(
+            clap::Arg::new("play")
+                .long("play")
+                .action(clap::ArgAction::SetTrue)
+                .requires("link")
+                .help("Play the item of the `LINK` argument instead of opening its page"),
+        )
         .arg(
             clap::Arg::new("theme")
                 .short('t')
@@ -183,6 +225,30 @@ pub fn init_cli() -> anyhow::Result<clap::Command> {
                 .value_name("THEME")
                 .help("Application theme"),
         )
+        .arg(
+            clap::Arg::new("compact")
+                .long("compact")
+                .action(clap::ArgAction::SetTrue)
+                .help("Running the application in the compact single-row UI mode"),
+        )
+        .arg(
+            clap::Arg::new("low-power")
+                .long("low-power")
+                .action(clap::ArgAction::SetTrue)
+                .help("Running the application in the low-power mode regardless of the power source"),
+        )
+        .arg(
+            clap::Arg::new("now-playing")
+                .long("now-playing")
+                .action(clap::ArgAction::SetTrue)
+                .help("Start the application on the now playing page"),
+        )
+        .arg(
+            clap::Arg::new("fresh")
+                .long("fresh")
+                .action(clap::ArgAction::SetTrue)
+                .help("Start the application without restoring the UI session of its previous run"),
+        )
         .arg(
             clap::Arg::new("config-folder")
                 .short('c')
@@ -200,6 +266,23 @@ pub fn init_cli() -> anyhow::Result<clap::Command> {
                 .help("Path to the application's cache folder"),
         );
 
+    #[cfg(feature = "streaming")]
+    let cmd = cmd.arg(
+        clap::Arg::new("no-ui")
+            .long("no-ui")
+            .action(clap::ArgAction::SetTrue)
+            .help("Running the application without the terminal UI, printing a playback status line to stdout"),
+    );
+
+    #[cfg(feature = "fx")]
+    let cmd = cmd.arg(
+        clap::Arg::new("animation-seed")
+            .long("animation-seed")
+            .value_name("SEED")
+            .value_parser(clap::value_parser!(u64))
+            .help("Render the animations deterministically from a seed, advancing them by a fixed step on each frame (e.g. for reproducible visualization recordings)"),
+    );
+
     #[cfg(feature = "daemon")]
     let cmd = cmd.arg(
         clap::Arg::new("daemon")
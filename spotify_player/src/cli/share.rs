@@ -0,0 +1,170 @@
+//! A shareable text snippet of the current playback (title, artists, album, progress bar
+//! and, with the `image` feature, a mini cover art drawn with unicode blocks) for posting in chats.
+
+use anyhow::Result;
+use rspotify::model::{CurrentPlaybackContext, PlayableItem};
+
+use crate::client::AppClient;
+
+use super::ShareFormat;
+
+/// The width of the mini cover art, in terminal cells
+#[cfg(feature = "image")]
+const ART_WIDTH: u32 = 16;
+/// The height of the mini cover art, in terminal cells
+#[cfg(feature = "image")]
+const ART_HEIGHT: u32 = 8;
+/// The width of the progress bar, in terminal cells
+const PROGRESS_BAR_WIDTH: usize = 24;
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Draw the cover art with unicode blocks, one string per row.
+///
+/// In the ANSI format, each cell is an upper half block (`▀`) with the colors of two pixels,
+/// otherwise, each cell is a shade block representing a pixel's luminance.
+#[cfg(feature = "image")]
+fn draw_cover_art(image: &image::DynamicImage, format: ShareFormat) -> Vec<String> {
+    use image::imageops::FilterType;
+    use std::fmt::Write;
+
+    match format {
+        ShareFormat::Ansi => {
+            let image = image
+                .resize_exact(ART_WIDTH, ART_HEIGHT * 2, FilterType::Triangle)
+                .to_rgb8();
+            (0..ART_HEIGHT)
+                .map(|y| {
+                    let mut row = String::new();
+                    for x in 0..ART_WIDTH {
+                        let [tr, tg, tb] = image.get_pixel(x, y * 2).0;
+                        let [br, bg, bb] = image.get_pixel(x, y * 2 + 1).0;
+                        // writing to a `String` never fails
+                        let _ = write!(row, "\x1b[38;2;{tr};{tg};{tb}m\x1b[48;2;{br};{bg};{bb}m▀");
+                    }
+                    row.push_str(RESET);
+                    row
+                })
+                .collect()
+        }
+        ShareFormat::Text => {
+            const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+            let image = image
+                .resize_exact(ART_WIDTH, ART_HEIGHT, FilterType::Triangle)
+                .to_luma8();
+            (0..ART_HEIGHT)
+                .map(|y| {
+                    (0..ART_WIDTH)
+                        .map(|x| {
+                            let luma = usize::from(image.get_pixel(x, y).0[0]);
+                            SHADES[luma * SHADES.len() / 256]
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+}
+
+/// Download and draw the cover art of the playing item, if any
+#[cfg(feature = "image")]
+async fn cover_art(client: &AppClient, item: &PlayableItem, format: ShareFormat) -> Vec<String> {
+    let url = match item {
+        PlayableItem::Track(track) => crate::utils::get_track_album_image_url(track),
+        PlayableItem::Episode(episode) => crate::utils::get_episode_show_image_url(episode),
+        PlayableItem::Unknown(_) => None,
+    };
+    let Some(url) = url else {
+        return vec![];
+    };
+    match client.download_image(url).await {
+        Ok(image) => draw_cover_art(&image, format),
+        Err(err) => {
+            tracing::warn!("Failed to get the cover art for sharing: {err:#}");
+            vec![]
+        }
+    }
+}
+
+fn progress_bar(progress: chrono::Duration, duration: chrono::Duration) -> String {
+    let ratio = if duration.num_milliseconds() > 0 {
+        progress.num_milliseconds() as f64 / duration.num_milliseconds() as f64
+    } else {
+        0.0
+    };
+    let filled = ((ratio * PROGRESS_BAR_WIDTH as f64).round() as usize).min(PROGRESS_BAR_WIDTH);
+    format!(
+        "{}{} {} / {}",
+        "━".repeat(filled),
+        "─".repeat(PROGRESS_BAR_WIDTH - filled),
+        crate::utils::format_duration(&progress),
+        crate::utils::format_duration(&duration)
+    )
+}
+
+/// Render the shareable snippet of a playback
+#[cfg_attr(not(feature = "image"), allow(clippy::unused_async))]
+pub async fn render(
+    client: &AppClient,
+    playback: &CurrentPlaybackContext,
+    format: ShareFormat,
+) -> Result<String> {
+    let Some(ref item) = playback.item else {
+        anyhow::bail!("nothing is playing");
+    };
+    let (title, artists, album, duration) = match item {
+        PlayableItem::Track(track) => (
+            track.name.clone(),
+            crate::utils::map_join(&track.artists, |a| &a.name, ", "),
+            track.album.name.clone(),
+            track.duration,
+        ),
+        PlayableItem::Episode(episode) => (
+            episode.name.clone(),
+            episode.show.publisher.clone(),
+            episode.show.name.clone(),
+            episode.duration,
+        ),
+        PlayableItem::Unknown(_) => anyhow::bail!("unknown playing item"),
+    };
+    let progress = playback.progress.unwrap_or_default().min(duration);
+
+    let icon = &crate::config::get_config().app_config.play_icon;
+    let mut info = match format {
+        ShareFormat::Ansi => vec![
+            format!("{BOLD}{icon} {title}{RESET}"),
+            artists,
+            format!("{DIM}{album}{RESET}"),
+        ],
+        ShareFormat::Text => vec![format!("{icon} {title}"), artists, album],
+    };
+    info.push(String::new());
+    info.push(progress_bar(progress, duration));
+
+    #[cfg(feature = "image")]
+    let art = cover_art(client, item, format).await;
+    #[cfg(not(feature = "image"))]
+    let art: Vec<String> = {
+        let _ = client;
+        vec![]
+    };
+    if art.is_empty() {
+        return Ok(info.join("\n"));
+    }
+
+    // put the information next to the cover art, vertically centered
+    let offset = art.len().saturating_sub(info.len()) / 2;
+    Ok(art
+        .into_iter()
+        .enumerate()
+        .map(
+            |(i, row)| match i.checked_sub(offset).and_then(|i| info.get(i)) {
+                Some(line) => format!("{row}  {line}"),
+                None => row,
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
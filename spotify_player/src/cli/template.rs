@@ -0,0 +1,93 @@
+//! A template mini-language used to format the current playback for external status bars
+//! (tmux, i3blocks, polybar, etc).
+//!
+//! Syntax:
+//! - `{name}`: a placeholder, replaced by the value of `name`
+//! - `{name:N}`: a placeholder truncated to at most `N` characters (with a trailing `…`)
+//! - `{?name:segment}`: a conditional segment, only rendered if `name` has a non-empty value.
+//!   `segment` is a template itself, so it can contain other placeholders.
+//! - `{{` and `}}`: literal `{` and `}`
+
+use anyhow::{Context, Result};
+use std::{collections::HashMap, iter::Peekable, str::Chars};
+
+/// Render a template with the given placeholder values
+pub fn render(template: &str, values: &HashMap<&str, String>) -> Result<String> {
+    let mut out = String::new();
+    render_into(&mut template.chars().peekable(), values, &mut out, false)?;
+    Ok(out)
+}
+
+/// Truncate a string to at most `width` characters
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        s.to_string()
+    } else if width == 0 {
+        String::new()
+    } else {
+        let mut s = s.chars().take(width - 1).collect::<String>();
+        s.push('…');
+        s
+    }
+}
+
+fn render_into(
+    chars: &mut Peekable<Chars>,
+    values: &HashMap<&str, String>,
+    out: &mut String,
+    is_segment: bool,
+) -> Result<()> {
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => out.push('{'),
+            '{' => {
+                let is_conditional = chars.next_if_eq(&'?').is_some();
+
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| *c != ':' && *c != '}') {
+                    name.push(c);
+                }
+                let value = values
+                    .get(name.as_str())
+                    .with_context(|| format!("unknown placeholder: {name}"))?;
+
+                match chars.next() {
+                    Some('}') => out.push_str(value),
+                    Some(':') if is_conditional => {
+                        let mut segment = String::new();
+                        render_into(chars, values, &mut segment, true)?;
+                        if !value.is_empty() {
+                            out.push_str(&segment);
+                        }
+                    }
+                    Some(':') => {
+                        let mut width = String::new();
+                        while let Some(c) = chars.next_if(|c| *c != '}') {
+                            width.push(c);
+                        }
+                        if chars.next().is_none() {
+                            anyhow::bail!("unclosed placeholder: {name}");
+                        }
+                        let width = width
+                            .parse::<usize>()
+                            .with_context(|| format!("invalid width for placeholder {name}"))?;
+                        out.push_str(&truncate(value, width));
+                    }
+                    _ => anyhow::bail!("unclosed placeholder: {name}"),
+                }
+            }
+            // a `}` inside a conditional segment always closes the segment
+            '}' if is_segment => return Ok(()),
+            '}' => {
+                chars.next_if_eq(&'}');
+                out.push('}');
+            }
+            c => out.push(c),
+        }
+    }
+
+    if is_segment {
+        anyhow::bail!("unclosed conditional segment");
+    }
+    Ok(())
+}
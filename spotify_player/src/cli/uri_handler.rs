@@ -0,0 +1,329 @@
+//! Registration of the application as the system's handler for `spotify:` URIs.
+//!
+//! An opened URI is passed to the `open` CLI command, which routes it into the running
+//! application's instance through the client socket (or plays it if there is no instance).
+//! The registration is done per user:
+//! - Linux: a desktop entry handling the `x-scheme-handler/spotify` MIME type, set as the default with `xdg-mime`
+//! - macOS: an `AppleScript` applet declaring the `spotify` URL scheme, registered to Launch Services
+//! - Windows: the `spotify` URL protocol in the current user's registry classes
+//!
+//! A handler registered before (e.g. the Spotify application's) is remembered on registration
+//! and restored on unregistration.
+
+use anyhow::{Context, Result};
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+use std::process::Command;
+
+#[cfg(target_os = "linux")]
+const DESKTOP_ENTRY_NAME: &str = "spotify_player-uri-handler.desktop";
+
+#[cfg(target_os = "linux")]
+const MIME_TYPE: &str = "x-scheme-handler/spotify";
+
+/// The desktop entry's key remembering the previous default handler
+#[cfg(target_os = "linux")]
+const PREVIOUS_HANDLER_KEY: &str = "X-Spotify-Player-Previous-Handler";
+
+#[cfg(target_os = "macos")]
+const APPLET_NAME: &str = "spotify_player URI handler.app";
+
+/// Run a system command, failing if the command doesn't exit successfully
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn run(cmd: &mut Command) -> Result<()> {
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to run {cmd:?}"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{cmd:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Register (or unregister) the application as the handler for `spotify:` URIs,
+/// returning a message describing the result
+pub fn register(unregister: bool) -> Result<String> {
+    let exe = std::env::current_exe().context("get the application's executable path")?;
+    platform::register(&exe, unregister)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{
+        run, Command, Context, Result, DESKTOP_ENTRY_NAME, MIME_TYPE, PREVIOUS_HANDLER_KEY,
+    };
+
+    /// Get the desktop entry currently set as the default handler for `spotify:` URIs
+    fn default_handler() -> Option<String> {
+        let output = Command::new("xdg-mime")
+            .args(["query", "default", MIME_TYPE])
+            .output()
+            .ok()?;
+        let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !name.is_empty()).then_some(name)
+    }
+
+    /// Get the previous default handler remembered in the application's desktop entry
+    fn previous_handler(path: &std::path::Path) -> Option<String> {
+        std::fs::read_to_string(path)
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{PREVIOUS_HANDLER_KEY}=")))
+            .map(str::to_string)
+    }
+
+    /// Remove the application's desktop entry from the user's default applications,
+    /// as `xdg-mime` has no command to unset a default
+    fn remove_default_association() -> Result<()> {
+        let path = dirs_next::config_dir()
+            .context("get the user's config directory")?
+            .join("mimeapps.list");
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Ok(());
+        };
+        let mut lines = content
+            .lines()
+            .filter(|line| {
+                !line.split_once('=').is_some_and(|(key, value)| {
+                    key.trim() == MIME_TYPE
+                        && value.trim().trim_end_matches(';') == DESKTOP_ENTRY_NAME
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        lines.push('\n');
+        std::fs::write(&path, lines)?;
+        Ok(())
+    }
+
+    pub fn register(exe: &std::path::Path, unregister: bool) -> Result<String> {
+        let apps_dir = dirs_next::data_dir()
+            .context("get the user's data directory")?
+            .join("applications");
+        let path = apps_dir.join(DESKTOP_ENTRY_NAME);
+
+        if unregister {
+            let previous = previous_handler(&path);
+            // the default is left alone if the user has changed it since the registration
+            let is_default = default_handler().as_deref() == Some(DESKTOP_ENTRY_NAME);
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            if is_default {
+                if let Some(previous) = previous {
+                    run(Command::new("xdg-mime").args(["default", &previous, MIME_TYPE]))?;
+                    return Ok(format!(
+                        "Removed the desktop entry {} and restored {previous} as the handler for spotify: URIs",
+                        path.display()
+                    ));
+                }
+                remove_default_association()?;
+            }
+            return Ok(format!("Removed the desktop entry {}", path.display()));
+        }
+
+        // when registering again, the handler remembered by the first registration is kept
+        let previous = match default_handler() {
+            Some(name) if name == DESKTOP_ENTRY_NAME => previous_handler(&path),
+            name => name,
+        };
+
+        std::fs::create_dir_all(&apps_dir)?;
+        std::fs::write(
+            &path,
+            format!(
+                "[Desktop Entry]\n\
+                 Type=Application\n\
+                 Name=spotify_player\n\
+                 Comment=Open Spotify URIs in spotify_player\n\
+                 Exec=\"{}\" open %u\n\
+                 Terminal=false\n\
+                 NoDisplay=true\n\
+                 MimeType={MIME_TYPE};\n{}",
+                exe.display(),
+                previous
+                    .as_ref()
+                    .map(|previous| format!("{PREVIOUS_HANDLER_KEY}={previous}\n"))
+                    .unwrap_or_default()
+            ),
+        )?;
+        run(Command::new("xdg-mime").args(["default", DESKTOP_ENTRY_NAME, MIME_TYPE]))?;
+        // refreshing the desktop database is optional, as not every system has the tool
+        if let Err(err) = run(Command::new("update-desktop-database").arg(&apps_dir)) {
+            eprintln!("Failed to update the desktop database: {err:#}");
+        }
+
+        Ok(format!(
+            "Registered {} as the handler for spotify: URIs (desktop entry: {}){}",
+            exe.display(),
+            path.display(),
+            previous
+                .map(|previous| format!(
+                    ". The previous handler {previous} is restored on unregistration."
+                ))
+                .unwrap_or_default()
+        ))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{run, Command, Context, Result, APPLET_NAME};
+
+    const LSREGISTER: &str = "/System/Library/Frameworks/CoreServices.framework/Frameworks/LaunchServices.framework/Support/lsregister";
+
+    pub fn register(exe: &std::path::Path, unregister: bool) -> Result<String> {
+        let path = dirs_next::home_dir()
+            .context("get the home directory")?
+            .join("Applications")
+            .join(APPLET_NAME);
+
+        if unregister {
+            if path.exists() {
+                run(Command::new(LSREGISTER).arg("-u").arg(&path))?;
+                std::fs::remove_dir_all(&path)?;
+            }
+            return Ok(format!("Removed the URI handler applet {}", path.display()));
+        }
+
+        // URIs are sent to an application as Apple events instead of arguments,
+        // so an applet receives them and forwards them to the `open` CLI command
+        let script = format!(
+            "on open location theURI\n\
+             do shell script quoted form of \"{}\" & \" open \" & quoted form of theURI\n\
+             end open location",
+            exe.display()
+        );
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        run(Command::new("osacompile")
+            .arg("-o")
+            .arg(&path)
+            .arg("-e")
+            .arg(script))?;
+        run(Command::new("plutil")
+            .args([
+                "-insert",
+                "CFBundleURLTypes",
+                "-json",
+                r#"[{"CFBundleURLName":"Spotify URI","CFBundleURLSchemes":["spotify"]}]"#,
+            ])
+            .arg(path.join("Contents/Info.plist")))?;
+        run(Command::new(LSREGISTER).arg("-f").arg(&path))?;
+
+        Ok(format!(
+            "Registered {} as a handler for spotify: URIs. If the Spotify application is installed, \
+             it may still be the default handler.",
+            path.display()
+        ))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{run, Command, Context, Result};
+
+    const KEY: &str = r"HKCU\Software\Classes\spotify";
+
+    /// The key remembering the handler registered before the application
+    const BACKUP_KEY: &str = r"HKCU\Software\spotify_player\UriHandler";
+
+    /// Get a registry key's value, or `None` if the key or the value doesn't exist
+    fn query(key: &str, value: Option<&str>) -> Result<Option<String>> {
+        let mut cmd = Command::new("reg");
+        cmd.args(["query", key]);
+        match value {
+            Some(value) => cmd.args(["/v", value]),
+            None => cmd.arg("/ve"),
+        };
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run {cmd:?}"))?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        // a value is listed as "<name>    REG_<type>    <data>"
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.split_once("    REG_"))
+            .and_then(|(_, typed_data)| typed_data.split_once("    "))
+            .map(|(_, data)| data.trim().to_string())
+            .filter(|data| data != "(value not set)"))
+    }
+
+    fn set(key: &str, value: Option<&str>, data: &str) -> Result<()> {
+        let mut cmd = Command::new("reg");
+        cmd.args(["add", key]);
+        match value {
+            Some(value) => cmd.args(["/v", value]),
+            None => cmd.arg("/ve"),
+        };
+        run(cmd.args(["/d", data, "/f"]))
+    }
+
+    pub fn register(exe: &std::path::Path, unregister: bool) -> Result<String> {
+        let command_key = format!(r"{KEY}\shell\open\command");
+        let command = format!("\"{}\" open \"%1\"", exe.display());
+
+        if unregister {
+            if query(&command_key, None)?.as_deref() != Some(command.as_str()) {
+                return Ok(format!(
+                    "spotify_player is not the registered handler of {KEY}, nothing is removed"
+                ));
+            }
+            let Some(previous_command) = query(BACKUP_KEY, Some("Command"))? else {
+                run(Command::new("reg").args(["delete", KEY, "/f"]))?;
+                return Ok(format!("Removed the registry key {KEY}"));
+            };
+            let previous_name = query(BACKUP_KEY, Some("Name"))?.unwrap_or_default();
+            set(KEY, None, &previous_name)?;
+            set(&command_key, None, &previous_command)?;
+            run(Command::new("reg").args(["delete", BACKUP_KEY, "/f"]))?;
+            return Ok(format!(
+                "Restored the previous handler of spotify: URIs ({previous_command})"
+            ));
+        }
+
+        // the handler registered before the application is remembered to be restored on
+        // unregistration, unless the application is already registered
+        let previous_command = query(&command_key, None)?;
+        if let Some(previous_command) = previous_command
+            .as_ref()
+            .filter(|previous| **previous != command)
+        {
+            set(BACKUP_KEY, Some("Command"), previous_command)?;
+            set(
+                BACKUP_KEY,
+                Some("Name"),
+                &query(KEY, None)?.unwrap_or_default(),
+            )?;
+        }
+
+        set(KEY, None, "URL:Spotify URI")?;
+        set(KEY, Some("URL Protocol"), "")?;
+        set(&command_key, None, &command)?;
+
+        Ok(format!(
+            "Registered {} as the handler for spotify: URIs (registry key: {KEY}){}",
+            exe.display(),
+            previous_command
+                .filter(|previous| *previous != command)
+                .map(|previous| format!(
+                    ". The previous handler ({previous}) is restored on unregistration."
+                ))
+                .unwrap_or_default()
+        ))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::Result;
+
+    pub fn register(_exe: &std::path::Path, _unregister: bool) -> Result<String> {
+        anyhow::bail!("registering a handler for spotify: URIs is not supported on this platform")
+    }
+}
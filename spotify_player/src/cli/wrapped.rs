@@ -0,0 +1,313 @@
+//! A year-in-review ("wrapped") report generated from the local listen history.
+//!
+//! Genres aren't stored in the listen history, so they are estimated from the artists
+//! of the year's most played tracks, which are fetched from the Spotify API.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Write,
+};
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, TimeZone};
+use rspotify::prelude::BaseClient;
+
+use crate::{
+    client::AppClient,
+    db::Listen,
+    state::{ArtistId, Id},
+};
+
+use super::ReportFormat;
+
+/// The number of entries in each "top" list of the report
+const TOP_N: usize = 5;
+/// The number of most played tracks used to estimate the year's top genres
+const N_GENRE_TRACKS: usize = 100;
+
+#[derive(Debug, Default)]
+/// A ranked item (track, artist or genre) of the report
+struct TopItem {
+    name: String,
+    plays: usize,
+}
+
+#[derive(Debug)]
+struct Report {
+    year: i32,
+    n_listens: usize,
+    minutes: i64,
+    top_tracks: Vec<TopItem>,
+    top_artists: Vec<TopItem>,
+    top_genres: Vec<TopItem>,
+    /// the first and last days of the longest streak of consecutive days with listens
+    longest_streak: Option<(NaiveDate, NaiveDate)>,
+    /// the number of tracks listened for the first time in the year
+    n_new_tracks: usize,
+    /// the number of artists listened for the first time in the year
+    n_new_artists: usize,
+}
+
+/// Split the artists of a listen, which are stored joined by commas
+fn listen_artists(listen: &Listen) -> impl Iterator<Item = &str> {
+    listen
+        .artists
+        .split(", ")
+        .filter(|artist| !artist.is_empty())
+}
+
+fn listen_date(listen: &Listen) -> Option<NaiveDate> {
+    chrono::Local
+        .timestamp_opt(listen.played_at, 0)
+        .single()
+        .map(|t| t.date_naive())
+}
+
+/// Get the `TOP_N` items with the most plays
+fn top_items(plays: HashMap<&str, usize>) -> Vec<TopItem> {
+    let mut items = plays
+        .into_iter()
+        .map(|(name, plays)| TopItem {
+            name: name.to_string(),
+            plays,
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| b.plays.cmp(&a.plays).then_with(|| a.name.cmp(&b.name)));
+    items.truncate(TOP_N);
+    items
+}
+
+/// Get the longest streak of consecutive days from a set of days
+fn longest_streak(days: &BTreeSet<NaiveDate>) -> Option<(NaiveDate, NaiveDate)> {
+    let mut longest: Option<(NaiveDate, NaiveDate)> = None;
+    let mut current: Option<(NaiveDate, NaiveDate)> = None;
+    for &day in days {
+        current = match current {
+            Some((start, end)) if end.succ_opt() == Some(day) => Some((start, day)),
+            _ => Some((day, day)),
+        };
+        if let Some((start, end)) = current {
+            if longest.is_none_or(|(l_start, l_end)| end - start > l_end - l_start) {
+                longest = current;
+            }
+        }
+    }
+    longest
+}
+
+/// Estimate the top genres of the given tracks, weighted by their plays
+async fn top_genres(client: &AppClient, track_plays: &[(&str, usize)]) -> Result<Vec<TopItem>> {
+    let uris = track_plays
+        .iter()
+        .map(|(uri, _)| (*uri).to_string())
+        .collect::<Vec<_>>();
+    let tracks = client.tracks_from_uris(&uris).await?;
+    let plays = track_plays.iter().copied().collect::<HashMap<_, _>>();
+
+    let mut artist_plays: HashMap<ArtistId<'static>, usize> = HashMap::new();
+    for track in &tracks {
+        let n = plays
+            .get(track.id.uri().as_str())
+            .copied()
+            .unwrap_or_default();
+        for artist in &track.artists {
+            *artist_plays.entry(artist.id.clone()).or_default() += n;
+        }
+    }
+
+    let artist_ids = artist_plays.keys().cloned().collect::<Vec<_>>();
+    let mut genre_plays: HashMap<String, usize> = HashMap::new();
+    // the API only allows getting at most 50 artists per request
+    for ids in artist_ids.chunks(50) {
+        for artist in client.artists(ids.to_vec()).await? {
+            let n = artist_plays.get(&artist.id).copied().unwrap_or_default();
+            for genre in artist.genres {
+                *genre_plays.entry(genre).or_default() += n;
+            }
+        }
+    }
+
+    Ok(top_items(
+        genre_plays.iter().map(|(g, n)| (g.as_str(), *n)).collect(),
+    ))
+}
+
+async fn build_report(client: &AppClient, listens: &[Listen], year: i32) -> Result<Report> {
+    let (before, during): (Vec<_>, Vec<_>) = listens
+        .iter()
+        .filter_map(|l| Some((l, listen_date(l)?)))
+        .filter(|(_, date)| date.year() <= year)
+        .partition(|(_, date)| date.year() < year);
+
+    let known_tracks = before
+        .iter()
+        .map(|(l, _)| l.track_uri.as_str())
+        .collect::<HashSet<_>>();
+    let known_artists = before
+        .iter()
+        .flat_map(|(l, _)| listen_artists(l))
+        .collect::<HashSet<_>>();
+
+    let mut track_plays: HashMap<&str, usize> = HashMap::new();
+    let mut track_names: HashMap<&str, String> = HashMap::new();
+    let mut artist_plays: HashMap<&str, usize> = HashMap::new();
+    for (listen, _) in &during {
+        *track_plays.entry(&listen.track_uri).or_default() += 1;
+        track_names
+            .entry(&listen.track_uri)
+            .or_insert_with(|| format!("{} - {}", listen.track_name, listen.artists));
+        for artist in listen_artists(listen) {
+            *artist_plays.entry(artist).or_default() += 1;
+        }
+    }
+
+    let mut most_played = track_plays
+        .iter()
+        .map(|(uri, n)| (*uri, *n))
+        .collect::<Vec<_>>();
+    most_played.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+    most_played.truncate(N_GENRE_TRACKS);
+    let top_genres = if most_played.is_empty() {
+        vec![]
+    } else {
+        top_genres(client, &most_played).await?
+    };
+
+    Ok(Report {
+        year,
+        n_listens: during.len(),
+        minutes: during.iter().map(|(l, _)| l.ms_played).sum::<i64>() / 60_000,
+        top_tracks: top_items(
+            track_plays
+                .iter()
+                .map(|(uri, n)| (track_names[uri].as_str(), *n))
+                .collect(),
+        ),
+        top_artists: top_items(artist_plays.clone()),
+        top_genres,
+        longest_streak: longest_streak(&during.iter().map(|(_, date)| *date).collect()),
+        n_new_tracks: track_plays
+            .keys()
+            .filter(|uri| !known_tracks.contains(*uri))
+            .count(),
+        n_new_artists: artist_plays
+            .keys()
+            .filter(|artist| !known_artists.contains(*artist))
+            .count(),
+    })
+}
+
+fn streak_description(streak: Option<(NaiveDate, NaiveDate)>) -> String {
+    match streak {
+        Some((start, end)) => format!(
+            "{} days ({} to {})",
+            (end - start).num_days() + 1,
+            start.format("%b %-d"),
+            end.format("%b %-d")
+        ),
+        None => "no listens".to_string(),
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Report {
+    fn sections(&self) -> [(&'static str, &[TopItem]); 3] {
+        [
+            ("Top tracks", &self.top_tracks),
+            ("Top artists", &self.top_artists),
+            ("Top genres", &self.top_genres),
+        ]
+    }
+
+    fn summary(&self) -> [(&'static str, String); 4] {
+        [
+            (
+                "Minutes listened",
+                format!("{} ({} plays)", self.minutes, self.n_listens),
+            ),
+            ("Longest streak", streak_description(self.longest_streak)),
+            ("New tracks discovered", self.n_new_tracks.to_string()),
+            ("New artists discovered", self.n_new_artists.to_string()),
+        ]
+    }
+
+    fn render_terminal(&self) -> Result<String> {
+        const BAR_WIDTH: usize = 20;
+
+        let mut out = format!("\x1b[1mYour {} in music\x1b[0m\n\n", self.year);
+        for (name, value) in self.summary() {
+            writeln!(out, "{name:<24}{value}")?;
+        }
+        for (title, items) in self.sections() {
+            writeln!(out, "\n\x1b[1m{title}\x1b[0m")?;
+            let max_plays = items.first().map_or(1, |item| item.plays.max(1));
+            for (i, item) in items.iter().enumerate() {
+                let bar = "█".repeat((item.plays * BAR_WIDTH).div_ceil(max_plays));
+                writeln!(
+                    out,
+                    "{:>2}. {bar:<BAR_WIDTH$} {} ({} plays)",
+                    i + 1,
+                    item.name,
+                    item.plays
+                )?;
+            }
+        }
+        Ok(out)
+    }
+
+    fn render_markdown(&self) -> Result<String> {
+        let mut out = format!("# Your {} in music\n\n", self.year);
+        for (name, value) in self.summary() {
+            writeln!(out, "- **{name}**: {value}")?;
+        }
+        for (title, items) in self.sections() {
+            writeln!(out, "\n## {title}\n")?;
+            for (i, item) in items.iter().enumerate() {
+                writeln!(out, "{}. {} ({} plays)", i + 1, item.name, item.plays)?;
+            }
+        }
+        Ok(out)
+    }
+
+    fn render_html(&self) -> Result<String> {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Your {0} in music</title></head>\n<body>\n<h1>Your {0} in music</h1>\n<ul>\n",
+            self.year
+        );
+        for (name, value) in self.summary() {
+            writeln!(out, "<li><b>{name}</b>: {}</li>", escape_html(&value))?;
+        }
+        out += "</ul>\n";
+        for (title, items) in self.sections() {
+            writeln!(out, "<h2>{title}</h2>\n<ol>")?;
+            for item in items {
+                writeln!(
+                    out,
+                    "<li>{} ({} plays)</li>",
+                    escape_html(&item.name),
+                    item.plays
+                )?;
+            }
+            out += "</ol>\n";
+        }
+        out += "</body>\n</html>";
+        Ok(out)
+    }
+}
+
+/// Generate the year-in-review report of the given year
+pub async fn generate(client: &AppClient, year: i32, format: ReportFormat) -> Result<String> {
+    let listens = crate::db::load_listen_history(&crate::config::get_config().cache_folder)?;
+    let report = build_report(client, &listens, year).await?;
+    match format {
+        ReportFormat::Terminal => report.render_terminal(),
+        ReportFormat::Markdown => report.render_markdown(),
+        ReportFormat::Html => report.render_html(),
+    }
+}
@@ -3,26 +3,62 @@ use rspotify::model::Id;
 use tracing::Instrument;
 
 use crate::{
-    config,
-    state::{ContextId, ContextPageType, ContextPageUIState, PageState, PlayableId, SharedState},
+    config, hooks, power,
+    state::{
+        ContextId, ContextPageType, ContextPageUIState, PageState, PlayableId, Playback,
+        SharedState, Track, USER_LIKED_TRACKS_ID,
+    },
 };
 
 use crate::utils::map_join;
 
-use super::ClientRequest;
+use super::{ClientRequest, PlayerRequest};
 
 struct PlayerEventHandlerState {
     add_track_to_queue_req_timer: std::time::Instant,
     get_context_timer: std::time::Instant,
+    /// the last observed playing state and volume, used to trigger hooks
+    last_is_playing: Option<bool>,
+    last_volume: Option<u32>,
+    /// the last track list (a context's URI or a search query) whose tracks' saved status is checked
+    last_saved_tracks_check: Option<String>,
+    /// the last track whose audio analysis is requested
+    last_audio_analysis_request: Option<String>,
+    /// the playing context and the URI of its last track, set when the track is about to end
+    ending_context: Option<(ContextId, String)>,
+    /// whether the user data shown in the library page and the saved episodes page is requested,
+    /// which is delayed until the page is first opened to speed up the application's startup
+    is_library_requested: bool,
+    is_saved_episodes_requested: bool,
+    /// the keys of the page data still needed (see `ClientRequest::page_key`), whose requests are kept
+    /// when cancelling the requests of the left pages
+    page_request_keys: Vec<String>,
 }
 
+/// The delay after the last search request before the search is sent,
+/// to avoid searching for every keystroke of a query
+const SEARCH_DEBOUNCE_DURATION: std::time::Duration = std::time::Duration::from_millis(300);
+
 /// starts the client's request handler
 pub async fn start_client_handler(
     state: SharedState,
     client: super::AppClient,
     client_sub: flume::Receiver<ClientRequest>,
 ) {
+    // the latest search request, which is cancelled when a new search request arrives
+    let mut search_task: Option<tokio::task::AbortHandle> = None;
+
     while let Ok(request) = client_sub.recv_async().await {
+        // an idle daemon is woken up by the user's requests, not by the periodic playback
+        // refreshes or the background requests
+        #[cfg(feature = "daemon")]
+        if state.player.read().is_idle
+            && (matches!(request, ClientRequest::GetCurrentPlayback)
+                || request.priority() == super::rate_limit::Priority::Background)
+        {
+            continue;
+        }
+
         if let Err(err) = client.check_valid_session(&state).await {
             tracing::error!("{err:#}");
             continue;
@@ -31,15 +67,72 @@ pub async fn start_client_handler(
         let state = state.clone();
         let client = client.clone();
         let span = tracing::info_span!("client_request", request = ?request);
+        let is_search = matches!(request, ClientRequest::Search(_));
 
-        tokio::task::spawn(
+        let task = tokio::task::spawn(
             async move {
-                if let Err(err) = client.handle_request(&state, request).await {
-                    tracing::error!("Failed to handle client request: {err:#}");
+                if is_search {
+                    tokio::time::sleep(SEARCH_DEBOUNCE_DURATION).await;
+                }
+
+                let _stats = state.api_stats.track_request();
+                let in_flight = state.requests.register(request.page_key());
+                let timeout = request.timeout();
+                let context_uri = match &request {
+                    ClientRequest::GetContext(id) => Some(id.uri()),
+                    _ => None,
+                };
+                let loading_all_search_results = match &request {
+                    ClientRequest::LoadAllSearchResults { query, typ } => {
+                        Some((query.clone(), *typ))
+                    }
+                    _ => None,
+                };
+
+                let deadline = async {
+                    match timeout {
+                        Some(timeout) => tokio::time::sleep(timeout).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                // the request's future is dropped at its pending API call when the request
+                // is cancelled, which is safe as no state lock is held across API calls
+                let is_handled = tokio::select! {
+                    result = client.handle_request(&state, request) => {
+                        if let Err(err) = result {
+                            tracing::error!("Failed to handle client request: {err:#}");
+                        }
+                        true
+                    }
+                    () = in_flight.token().cancelled() => {
+                        tracing::info!("Client request cancelled");
+                        false
+                    }
+                    () = deadline => {
+                        tracing::error!("Client request timed out after {timeout:?}");
+                        false
+                    }
+                };
+
+                // a context whose request is cancelled is no longer loading
+                if let (false, Some(uri)) = (is_handled, context_uri) {
+                    state.data.write().caches.partial_contexts.remove(&uri);
+                }
+                // search results are no longer loading once the request finishes, fails or is cancelled
+                if let Some((query, typ)) = loading_all_search_results {
+                    if let Some(results) = state.data.write().caches.search.get_mut(&query) {
+                        results.page_mut(typ).loading_all = None;
+                    }
                 }
             }
             .instrument(span),
         );
+
+        if is_search {
+            if let Some(task) = search_task.replace(task.abort_handle()) {
+                task.abort();
+            }
+        }
     }
 }
 
@@ -68,6 +161,53 @@ fn handle_playback_change_event(
         _ => return Ok(()),
     };
 
+    // trigger hooks upon playing state or volume changes
+    if handler_state.last_is_playing == Some(true) && !playback.is_playing {
+        hooks::trigger(
+            hooks::HookEvent::Paused,
+            &hooks::HookContext::from_playable_item(
+                player.currently_playing().expect("non-empty playback"),
+            ),
+        );
+    }
+    if handler_state.last_volume.is_some() && handler_state.last_volume != playback.volume {
+        hooks::trigger(
+            hooks::HookEvent::VolumeChanged,
+            &hooks::HookContext {
+                volume: playback.volume,
+                ..hooks::HookContext::from_playable_item(
+                    player.currently_playing().expect("non-empty playback"),
+                )
+            },
+        );
+    }
+    handler_state.last_is_playing = Some(playback.is_playing);
+    handler_state.last_volume = playback.volume;
+
+    // request the current track's audio analysis for the beat grid features,
+    // the visualization's beat clock and the WebSocket stream's beat and segment events
+    let configs = config::get_config();
+    let needs_audio_analysis =
+        configs.app_config.enable_beat_grid || configs.app_config.snap_seek_to_bar;
+    #[cfg(feature = "fx")]
+    let needs_audio_analysis = needs_audio_analysis || configs.app_config.enable_visualization;
+    #[cfg(feature = "remote")]
+    let needs_audio_analysis = needs_audio_analysis || crate::remote::has_stream_clients();
+    // the analysis is prefetched in the background, which is paused in the low-power mode
+    if needs_audio_analysis && !state.is_low_power() {
+        if let PlayableId::Track(track_id) = &id {
+            let uri = track_id.uri();
+            if handler_state.last_audio_analysis_request.as_ref() != Some(&uri)
+                && !state.data.read().caches.audio_analysis.contains_key(&uri)
+            {
+                client_pub.send(ClientRequest::GetAudioAnalysis {
+                    track_id: track_id.clone_static(),
+                })?;
+                handler_state.last_audio_analysis_request = Some(uri);
+            }
+        }
+    }
+
     if let Some(progress) = player.playback_progress() {
         // update the playback when the current track ends
         if progress >= duration && playback.is_playing {
@@ -86,6 +226,8 @@ fn handle_playback_change_event(
         client_pub.send(ClientRequest::GetCurrentUserQueue)?;
     }
 
+    handle_context_end(state, client_pub, handler_state, &id)?;
+
     // handle fake track repeat mode
     if playback.fake_track_repeat_state {
         if let Some(progress) = player.playback_progress() {
@@ -109,6 +251,82 @@ fn handle_playback_change_event(
     Ok(())
 }
 
+/// Apply the playing context's end behavior (see `config::ContextEndBehavior`)
+/// once its last track is followed by a track outside of the context, e.g. from Spotify's autoplay
+fn handle_context_end(
+    state: &SharedState,
+    client_pub: &flume::Sender<ClientRequest>,
+    handler_state: &mut PlayerEventHandlerState,
+    id: &PlayableId,
+) -> anyhow::Result<()> {
+    let uri = id.uri();
+    let player = state.player.read();
+
+    if let Some((context_id, last_track_uri)) = handler_state.ending_context.as_ref() {
+        if uri == *last_track_uri {
+            return Ok(());
+        }
+        let in_context = state
+            .data
+            .read()
+            .context_tracks(context_id)
+            .is_some_and(|tracks| tracks.iter().any(|t| t.id.uri() == uri));
+        if !in_context {
+            let behavior = player.context_end_behavior(context_id);
+            tracing::info!(
+                "Context {} ended, applying its end behavior: {behavior:?}",
+                context_id.uri()
+            );
+            match behavior {
+                config::ContextEndBehavior::Autoplay => {}
+                config::ContextEndBehavior::Stop => {
+                    client_pub.send(ClientRequest::Player(PlayerRequest::Pause))?;
+                }
+                config::ContextEndBehavior::Repeat => {
+                    client_pub.send(ClientRequest::Player(PlayerRequest::StartPlayback(
+                        Playback::Context(context_id.clone(), None),
+                        None,
+                    )))?;
+                }
+            }
+        }
+        handler_state.ending_context = None;
+        return Ok(());
+    }
+
+    // the context's last track is only known when neither shuffle nor repeat is enabled
+    let (Some(playback), Some(context_id), Some(progress), Some(duration)) = (
+        player.buffered_playback.as_ref(),
+        player.playing_context_id(),
+        player.playback_progress(),
+        player.currently_playing().map(|item| match item {
+            rspotify::model::PlayableItem::Track(track) => track.duration,
+            rspotify::model::PlayableItem::Episode(episode) => episode.duration,
+            rspotify::model::PlayableItem::Unknown(_) => chrono::TimeDelta::zero(),
+        }),
+    ) else {
+        return Ok(());
+    };
+    if playback.shuffle_state || playback.repeat_state != rspotify::model::RepeatState::Off {
+        return Ok(());
+    }
+    // only watch the last track near its end, so that other playback changes
+    // (e.g. the user playing another context) aren't mistaken for the context's end
+    if progress + chrono::TimeDelta::seconds(5) < duration {
+        return Ok(());
+    }
+    let is_last_track = state
+        .data
+        .read()
+        .context_tracks(&context_id)
+        .and_then(|tracks| tracks.last())
+        .is_some_and(|t| t.id.uri() == uri);
+    if is_last_track {
+        handler_state.ending_context = Some((context_id, uri));
+    }
+    Ok(())
+}
+
 fn handle_page_change_event(
     state: &SharedState,
     client_pub: &flume::Sender<ClientRequest>,
@@ -155,8 +373,13 @@ fn handle_page_change_event(
             // To avoid making too many requests, only request if context id is changed
             // or it's been a while since the last request.
             if let Some(id) = id {
+                let is_cached = {
+                    let caches = &state.data.read().caches;
+                    caches.context.contains_key(&id.uri())
+                        && !caches.stale_contexts.contains(&id.uri())
+                };
                 if !matches!(id, ContextId::Tracks(_))
-                    && !state.data.read().caches.context.contains_key(&id.uri())
+                    && !is_cached
                     && (new_id
                         || handler_state.get_context_timer.elapsed()
                             > std::time::Duration::from_secs(5))
@@ -164,6 +387,24 @@ fn handle_page_change_event(
                     client_pub.send(ClientRequest::GetContext(id.clone()))?;
                     handler_state.get_context_timer = std::time::Instant::now();
                 }
+
+                // tracks in the liked tracks context are already known to be saved
+                if *id != ContextId::Tracks(USER_LIKED_TRACKS_ID.clone()) {
+                    if let Some(tracks) = state.data.read().context_tracks(id) {
+                        check_saved_tracks(id.uri(), tracks, client_pub, handler_state)?;
+                    }
+                }
+            }
+        }
+
+        PageState::Search { current_query, .. } => {
+            if let Some(results) = state.data.read().caches.search.get(current_query) {
+                check_saved_tracks(
+                    format!("search:{current_query}"),
+                    &results.tracks,
+                    client_pub,
+                    handler_state,
+                )?;
             }
         }
 
@@ -188,25 +429,250 @@ fn handle_page_change_event(
                 }
             }
         }
+
+        PageState::Library { .. } if !handler_state.is_library_requested => {
+            client_pub.send(ClientRequest::GetUserFollowedArtists)?;
+            client_pub.send(ClientRequest::GetUserSavedAlbums)?;
+            handler_state.is_library_requested = true;
+        }
+
+        PageState::SavedEpisodes { .. } if !handler_state.is_saved_episodes_requested => {
+            client_pub.send(ClientRequest::GetUserSavedShows)?;
+            client_pub.send(ClientRequest::GetUserSavedEpisodes)?;
+            handler_state.is_saved_episodes_requested = true;
+        }
         _ => {}
     }
 
     Ok(())
 }
 
+/// Cancel the in-flight requests loading data of the pages the user has left.
+///
+/// The data of the current page and the playing context is still needed, so its requests are kept.
+fn cancel_left_page_requests(state: &SharedState, handler_state: &mut PlayerEventHandlerState) {
+    let playing_context_id = state.player.read().playing_context_id();
+    let mut page_keys = match state.ui.lock().current_page() {
+        PageState::Context {
+            context_page_type: ContextPageType::Browsing(id),
+            ..
+        } => vec![id.uri()],
+        PageState::Search { current_query, .. } => vec![format!("search:{current_query}")],
+        _ => vec![],
+    };
+    if let Some(id) = playing_context_id {
+        page_keys.push(id.uri());
+    }
+
+    if page_keys != handler_state.page_request_keys {
+        state.requests.cancel_page_requests(&page_keys);
+        handler_state.page_request_keys = page_keys;
+    }
+}
+
+/// Request checking the saved status of tracks in a track list if the list hasn't been checked recently
+fn check_saved_tracks(
+    key: String,
+    tracks: &[Track],
+    client_pub: &flume::Sender<ClientRequest>,
+    handler_state: &mut PlayerEventHandlerState,
+) -> anyhow::Result<()> {
+    if handler_state.last_saved_tracks_check.as_ref() != Some(&key) {
+        client_pub.send(ClientRequest::CheckSavedTracks(
+            tracks.iter().map(|t| t.id.clone()).collect(),
+        ))?;
+        handler_state.last_saved_tracks_check = Some(key);
+    }
+    Ok(())
+}
+
+/// Revert the ongoing preview of the next queued track (if any) once its duration has passed
+fn handle_track_preview_event(
+    state: &SharedState,
+    client_pub: &flume::Sender<ClientRequest>,
+) -> anyhow::Result<()> {
+    let duration = std::time::Duration::from_secs(u64::from(
+        config::get_config().app_config.track_preview_duration_secs,
+    ));
+
+    let mut player = state.player.write();
+    if player
+        .track_preview
+        .as_ref()
+        .is_some_and(|preview| preview.started_at.elapsed() >= duration)
+    {
+        let preview = player
+            .track_preview
+            .take()
+            .expect("non-empty track preview");
+        tracing::info!("Track preview ended, going back to the previous track");
+        client_pub.send(ClientRequest::Player(PlayerRequest::RevertTrackPreview(
+            preview.previous,
+        )))?;
+    }
+
+    Ok(())
+}
+
+/// Seek back to the A point of the A-B loop (if any) when the playback reaches its B point
+fn handle_ab_loop_event(
+    state: &SharedState,
+    client_pub: &flume::Sender<ClientRequest>,
+    last_seek_time: &mut std::time::Instant,
+) -> anyhow::Result<()> {
+    let player = state.player.read();
+    let Some(ab_loop) = player.ab_loop.as_ref() else {
+        return Ok(());
+    };
+
+    // clear the loop when moving to another track, re-checking it under the write lock
+    // as the loop could have been replaced after the read lock was released
+    if player.currently_playing_uri().as_ref() != Some(&ab_loop.track_uri) {
+        drop(player);
+        let mut player = state.player.write();
+        if player.ab_loop.as_ref().is_some_and(|ab_loop| {
+            player.currently_playing_uri().as_ref() != Some(&ab_loop.track_uri)
+        }) {
+            player.ab_loop = None;
+        }
+        return Ok(());
+    }
+
+    // the playback's progress is only updated after the seek request is handled,
+    // so wait a bit before seeking again to avoid duplicated requests
+    if let (Some(b), Some(progress)) = (ab_loop.b, player.playback_progress()) {
+        if progress >= b && last_seek_time.elapsed() > std::time::Duration::from_secs(1) {
+            client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(ab_loop.a)))?;
+            *last_seek_time = std::time::Instant::now();
+        }
+    }
+
+    Ok(())
+}
+
+/// The position (as a percentage of the track's duration) of the part played by an audition,
+/// which skips the intro of most tracks
+const AUDITION_START_PERCENT: i32 = 35;
+/// The duration of the volume's fade-out at the end of an auditioned track's part
+const AUDITION_FADE_DURATION: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Play a part of each track back to back during an audition (if any): seek to the part
+/// of a new track, then fade out the volume and move to the next track after `audition_duration_secs`.
+/// The audition stops when the playback is paused (e.g. at the end of the context).
+fn handle_audition_event(
+    state: &SharedState,
+    client_pub: &flume::Sender<ClientRequest>,
+) -> anyhow::Result<()> {
+    let duration = std::time::Duration::from_secs(u64::from(
+        config::get_config().app_config.audition_duration_secs,
+    ));
+
+    let mut player = state.player.write();
+    let (Some(uri), Some(playback)) = (player.currently_playing_uri(), player.current_playback())
+    else {
+        return Ok(());
+    };
+    let Some(audition) = player.audition.as_mut() else {
+        return Ok(());
+    };
+
+    if audition.track_uri.as_ref() != Some(&uri) {
+        let track_duration = match playback.item {
+            Some(rspotify::model::PlayableItem::Track(track)) => track.duration,
+            Some(rspotify::model::PlayableItem::Episode(episode)) => episode.duration,
+            _ => return Ok(()),
+        };
+        audition.track_uri = Some(uri);
+        audition.started_at = Some(std::time::Instant::now());
+        audition.next_requested = false;
+        client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
+            track_duration * AUDITION_START_PERCENT / 100,
+        )))?;
+        if let Some(volume) = audition.faded_volume.take() {
+            client_pub.send(ClientRequest::Player(PlayerRequest::Volume(volume)))?;
+        }
+        return Ok(());
+    }
+
+    let elapsed = audition.started_at.map(|t| t.elapsed()).unwrap_or_default();
+    // the playback can be outdated right after seeking, so wait a bit before checking its state
+    if !playback.is_playing && elapsed > std::time::Duration::from_secs(2) {
+        tracing::info!("Playback paused, stopping the audition");
+        if let Some(volume) = audition.faded_volume {
+            client_pub.send(ClientRequest::Player(PlayerRequest::Volume(volume)))?;
+        }
+        player.audition = None;
+        return Ok(());
+    }
+
+    if elapsed + AUDITION_FADE_DURATION >= duration && audition.faded_volume.is_none() {
+        if let Some(volume) = playback.device.volume_percent.filter(|v| *v > 0) {
+            let volume = u8::try_from(volume).unwrap_or(100);
+            audition.faded_volume = Some(volume);
+            client_pub.send(ClientRequest::Player(PlayerRequest::Volume(volume / 4)))?;
+        }
+    }
+    if elapsed >= duration && !audition.next_requested {
+        audition.next_requested = true;
+        client_pub.send(ClientRequest::Player(PlayerRequest::NextTrack))?;
+    }
+
+    Ok(())
+}
+
 fn handle_player_event(
     state: &SharedState,
     client_pub: &flume::Sender<ClientRequest>,
     handler_state: &mut PlayerEventHandlerState,
 ) -> anyhow::Result<()> {
+    cancel_left_page_requests(state, handler_state);
     handle_page_change_event(state, client_pub, handler_state)
         .context("handle page change event")?;
     handle_playback_change_event(state, client_pub, handler_state)
         .context("handle playback change event")?;
+    handle_track_preview_event(state, client_pub).context("handle track preview event")?;
 
     Ok(())
 }
 
+/// The interval of checking whether the daemon is idle
+#[cfg(feature = "daemon")]
+const IDLE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Starts a watcher that shuts down the integrated player and its session once the playback
+/// has been paused (or stopped) for `daemon_idle_timeout_mins` minutes in daemon mode
+#[cfg(feature = "daemon")]
+pub async fn start_idle_shutdown_watcher(state: SharedState, client: super::AppClient) {
+    let timeout = std::time::Duration::from_secs(
+        config::get_config().app_config.daemon_idle_timeout_mins * 60,
+    );
+    let mut paused_since: Option<std::time::Instant> = None;
+
+    loop {
+        tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+
+        let (is_idle, is_playing) = {
+            let player = state.player.read();
+            (
+                player.is_idle,
+                player.playback.as_ref().is_some_and(|p| p.is_playing),
+            )
+        };
+        if is_idle || is_playing {
+            paused_since = None;
+            continue;
+        }
+
+        let paused_since = paused_since.get_or_insert_with(std::time::Instant::now);
+        if paused_since.elapsed() >= timeout {
+            tracing::info!(
+                "Playback has been paused for {timeout:?}, shutting down the integrated player..."
+            );
+            client.shutdown_idle_session(&state).await;
+        }
+    }
+}
+
 /// Starts multiple event watchers listening to events and
 /// notifying the client to make update requests if needed
 pub async fn start_player_event_watchers(
@@ -223,28 +689,64 @@ pub async fn start_player_event_watchers(
             let playback_refresh_duration = std::time::Duration::from_millis(
                 configs.app_config.playback_refresh_duration_in_ms,
             );
+            let state = state.clone();
             async move {
                 loop {
                     client_pub
                         .send_async(ClientRequest::GetCurrentPlayback)
                         .await
                         .unwrap_or_default();
-                    tokio::time::sleep(playback_refresh_duration).await;
+                    if state.is_low_power() {
+                        tokio::time::sleep(
+                            playback_refresh_duration * power::LOW_POWER_PLAYBACK_REFRESH_FACTOR,
+                        )
+                        .await;
+                    } else {
+                        tokio::time::sleep(playback_refresh_duration).await;
+                    }
                 }
             }
         });
     }
 
+    // Start a watcher task that handles the A-B loop and the audition, which need to be
+    // checked more frequently than other player events to seek precisely.
+    tokio::task::spawn({
+        let state = state.clone();
+        let client_pub = client_pub.clone();
+        async move {
+            let mut last_seek_time = std::time::Instant::now();
+            loop {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                if let Err(err) = handle_ab_loop_event(&state, &client_pub, &mut last_seek_time) {
+                    tracing::error!("Encounter error when handling A-B loop event: {err:#}");
+                }
+                if let Err(err) = handle_audition_event(&state, &client_pub) {
+                    tracing::error!("Encounter error when handling audition event: {err:#}");
+                }
+            }
+        }
+    });
+
     let refresh_duration = std::time::Duration::from_secs(1);
     let mut handler_state = PlayerEventHandlerState {
         add_track_to_queue_req_timer: std::time::Instant::now(),
         get_context_timer: std::time::Instant::now(),
+        last_is_playing: None,
+        last_volume: None,
+        last_saved_tracks_check: None,
+        last_audio_analysis_request: None,
+        ending_context: None,
+        is_library_requested: false,
+        is_saved_episodes_requested: false,
+        page_request_keys: vec![],
     };
 
     loop {
-        tokio::time::sleep(refresh_duration).await;
+        // the events are handled right away on startup to request the initial page's data
         if let Err(err) = handle_player_event(&state, &client_pub, &mut handler_state) {
             tracing::error!("Encounter error when handling player event: {err:#}");
         }
+        tokio::time::sleep(refresh_duration).await;
     }
 }
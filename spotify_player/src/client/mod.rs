@@ -2,15 +2,16 @@ use std::ops::Deref;
 use std::{borrow::Cow, collections::HashMap, sync::Arc};
 
 use crate::state::Lyrics;
-use crate::{auth, config};
+use crate::{auth, config, hooks};
 use crate::{
     auth::AuthConfig,
     state::{
-        store_data_into_file_cache, Album, AlbumId, Artist, ArtistId, Category, Context, ContextId,
-        Device, FileCacheKey, Item, ItemId, MemoryCaches, Playback, PlaybackMetadata, Playlist,
-        PlaylistFolderItem, PlaylistId, SearchResults, SharedState, Show, ShowId, Track, TrackId,
-        UserId, TTL_CACHE_DURATION, USER_LIKED_TRACKS_ID, USER_RECENTLY_PLAYED_TRACKS_ID,
-        USER_TOP_TRACKS_ID,
+        store_cache_data, Album, AlbumId, Artist, ArtistId, BlockedItem, CacheKey, Category,
+        ConnectionStatus, Context, ContextId, ContextPageType, Device, Episode, FeedItem, Item,
+        ItemId, MemoryCaches, PageState, PartialContext, Playback, PlaybackMetadata, Playlist,
+        PlaylistFolderItem, PlaylistId, PlaylistMergeOrder, SearchResults, SearchType,
+        SharedPlaylistAdd, SharedState, Show, ShowId, Track, TrackId, UserId, TTL_CACHE_DURATION,
+        USER_LIKED_TRACKS_ID, USER_RECENTLY_PLAYED_TRACKS_ID, USER_TOP_TRACKS_ID,
     },
 };
 
@@ -20,13 +21,13 @@ use anyhow::Context as _;
 use anyhow::Result;
 
 use librespot_core::SpotifyUri;
-#[cfg(feature = "streaming")]
 use parking_lot::Mutex;
 
 use reqwest::StatusCode;
-use rspotify::{http::Query, prelude::*};
+use rspotify::{http::Query, model::ItemPositions, prelude::*};
 
 mod handlers;
+mod rate_limit;
 mod request;
 mod spotify;
 
@@ -40,15 +41,66 @@ const PLAYBACK_TYPES: [&rspotify::model::AdditionalType; 2] = [
     &rspotify::model::AdditionalType::Episode,
 ];
 
+/// The timeout of an API call, matching the timeout of `rspotify`'s HTTP client
+const API_CALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// The timeout of an attempt to create a new session
+const RECONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// The delays between two failed attempts to create a new session
+const RECONNECT_MIN_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+/// The duration of fading out the integrated player's audio when the application quits
+#[cfg(feature = "streaming")]
+const FADE_OUT_DURATION: std::time::Duration = std::time::Duration::from_millis(300);
+/// The number of days in which releases and playlist adds are included in the artist feed
+const ARTIST_FEED_MAX_AGE_DAYS: i64 = 90;
+/// The number of days in which tracks added to the shared playlists are considered recent
+const SHARED_PLAYLIST_ADD_MAX_AGE_DAYS: i64 = 30;
+/// The maximum number of tracks liked or unliked in an API call
+const LIKED_TRACKS_CHUNK_SIZE: usize = 50;
+/// The number of search results of each type in a page, which is the maximum allowed by Spotify
+const SEARCH_PAGE_SIZE: usize = 50;
+/// The maximum number of search results of a type that can be loaded all at once,
+/// as Spotify doesn't return search results past this offset
+pub const MAX_LOAD_ALL_SEARCH_RESULTS: usize = 1000;
+
+#[derive(Debug, Default)]
+/// The state of the retries to create a new session after the current one is lost
+struct ReconnectBackoff {
+    n_failures: u32,
+    /// the time after which the next attempt can be made
+    retry_time: Option<std::time::Instant>,
+}
+
 /// The application's Spotify client
 #[derive(Clone)]
 pub struct AppClient {
     http: reqwest::Client,
+    /// the Spotify Web API's endpoint, without a trailing slash
+    api_endpoint: String,
     spotify: Arc<spotify::Spotify>,
     auth_config: AuthConfig,
     user_client: Option<rspotify::AuthCodePkceSpotify>,
+    rate_limiter: Arc<rate_limit::RateLimiter>,
+    reconnect_backoff: Arc<Mutex<ReconnectBackoff>>,
     #[cfg(feature = "streaming")]
     stream_conn: Arc<Mutex<Option<librespot_connect::Spirc>>>,
+    /// the channel of the cover images loaded in the background,
+    /// which are applied by [`AppClient::handle_loaded_cover_images`]
+    #[cfg(feature = "image")]
+    cover_images: (
+        flume::Sender<LoadedCoverImage>,
+        flume::Receiver<LoadedCoverImage>,
+    ),
+}
+
+/// A cover image loaded in the background
+#[cfg(feature = "image")]
+struct LoadedCoverImage {
+    url: String,
+    /// the decoded image, if it wasn't cached
+    image: Option<image::DynamicImage>,
+    /// the image's dominant color, if album colors are used
+    color: Option<(u8, u8, u8)>,
 }
 
 impl Deref for AppClient {
@@ -62,9 +114,85 @@ fn market_query() -> Query<'static> {
     Query::from([("market", "from_token")])
 }
 
+/// Get the URL of a playable item's cover image
+fn cover_image_url(item: &rspotify::model::PlayableItem) -> Option<String> {
+    match item {
+        rspotify::model::PlayableItem::Track(track) => {
+            crate::utils::get_track_album_image_url(track)
+        }
+        rspotify::model::PlayableItem::Episode(episode) => {
+            crate::utils::get_episode_show_image_url(episode)
+        }
+        rspotify::model::PlayableItem::Unknown(_) => None,
+    }
+    .map(str::to_string)
+}
+
+/// Get the playback starting at a last playback's item, in its context if any
+fn last_playback_to_playback(last_playback: &crate::state::LastPlayback) -> Result<Playback> {
+    if let Some(context_id) = last_playback
+        .context_uri
+        .as_deref()
+        .and_then(ContextId::from_uri)
+    {
+        return Ok(Playback::Context(
+            context_id,
+            Some(rspotify::model::Offset::Uri(last_playback.item_uri.clone())),
+        ));
+    }
+
+    let item_id = if last_playback.item_uri.starts_with("spotify:episode:") {
+        PlayableId::Episode(
+            rspotify::model::EpisodeId::from_uri(&last_playback.item_uri)?.into_static(),
+        )
+    } else {
+        PlayableId::Track(TrackId::from_uri(&last_playback.item_uri)?.into_static())
+    };
+    Ok(Playback::URIs(vec![item_id], None))
+}
+
+/// Cache a context. If the context is refreshed, the selected track of its page (if shown) stays selected.
+fn store_context(state: &SharedState, uri: &str, context: Context) {
+    let track_uris = context
+        .tracks()
+        .map(|tracks| tracks.iter().map(|t| t.id.uri()).collect::<Vec<_>>());
+    let previous_track_uris = state.data.write().cache_context(uri.to_owned(), context);
+    if let (Some(previous_track_uris), Some(track_uris)) = (previous_track_uris, track_uris) {
+        state
+            .ui
+            .lock()
+            .follow_refreshed_context(uri, &previous_track_uris, &track_uris);
+    }
+}
+
+/// Update the loaded sections of a context, if the application's state is specified
+fn update_partial_context(
+    state: Option<&SharedState>,
+    uri: &str,
+    update: impl FnOnce(&mut PartialContext),
+) {
+    if let Some(state) = state {
+        update(
+            state
+                .data
+                .write()
+                .caches
+                .partial_contexts
+                .entry(uri.to_owned())
+                .or_default(),
+        );
+    }
+}
+
 impl AppClient {
     /// Construct a new client
     pub async fn new() -> Result<Self> {
+        Self::with_api_endpoint(SPOTIFY_API_ENDPOINT).await
+    }
+
+    /// Construct a new client sending Spotify Web API requests to a given endpoint,
+    /// e.g. a mock server in tests
+    pub async fn with_api_endpoint(api_endpoint: &str) -> Result<Self> {
         let configs = config::get_config();
         let auth_config = AuthConfig::new(configs)?;
 
@@ -97,13 +225,21 @@ impl AppClient {
         }
 
         Ok(Self {
-            spotify: Arc::new(spotify::Spotify::new()),
-            http: reqwest::Client::new(),
+            spotify: Arc::new(spotify::Spotify::new(api_endpoint)),
+            http: reqwest::Client::builder()
+                .timeout(API_CALL_TIMEOUT)
+                .build()
+                .context("build HTTP client")?,
+            api_endpoint: api_endpoint.to_string(),
             auth_config,
             user_client,
+            rate_limiter: Arc::new(rate_limit::RateLimiter::default()),
+            reconnect_backoff: Arc::new(Mutex::new(ReconnectBackoff::default())),
 
             #[cfg(feature = "streaming")]
             stream_conn: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "image")]
+            cover_images: flume::unbounded(),
         })
     }
 
@@ -120,7 +256,7 @@ impl AppClient {
                 // a retry logic is implemented to ensure the application's state is properly initialized
                 let delay = std::time::Duration::from_secs(1);
 
-                for _ in 0..5 {
+                for i in 0..5 {
                     tokio::time::sleep(delay).await;
 
                     if let Err(err) = client.retrieve_current_playback(&state, false).await {
@@ -128,6 +264,11 @@ impl AppClient {
                         return;
                     }
 
+                    // offer to continue the last playback if there is no playback on startup
+                    if i == 0 {
+                        Self::offer_last_playback(&state);
+                    }
+
                     // if playback exists, don't connect to a new device
                     if state.player.read().playback.is_some() {
                         continue;
@@ -144,7 +285,24 @@ impl AppClient {
 
                     if let Some(id) = id {
                         tracing::info!("Trying to connect to device (id={id})");
-                        if let Err(err) = client.transfer_playback(&id, Some(false)).await {
+                        // start playing the `startup_context_uri` context (if any) on the device
+                        let startup_context = config::get_config()
+                            .app_config
+                            .startup_context_uri
+                            .as_deref()
+                            .and_then(ContextId::from_uri);
+                        let result = match startup_context {
+                            Some(context_id) => {
+                                client
+                                    .start_playback(Playback::Context(context_id, None), Some(&id))
+                                    .await
+                            }
+                            None => client
+                                .transfer_playback(&id, Some(false))
+                                .await
+                                .map_err(anyhow::Error::from),
+                        };
+                        if let Err(err) = result {
                             tracing::warn!("Connection failed (device_id={id}): {err:#}");
                         } else {
                             tracing::info!("Connection succeeded (device_id={id})!");
@@ -159,6 +317,34 @@ impl AppClient {
         });
     }
 
+    /// Show a toast offering to continue the last playback of the application's previous run,
+    /// if there is no playback and no `startup_context_uri` to play instead
+    fn offer_last_playback(state: &SharedState) {
+        let configs = config::get_config();
+        let player = state.player.read();
+        let Some(last_playback) = player.last_playback.as_ref() else {
+            return;
+        };
+        if player.playback.is_some() || configs.app_config.startup_context_uri.is_some() {
+            return;
+        }
+        let key = configs
+            .keymap_config
+            .keymaps
+            .iter()
+            .find(|k| k.command == crate::command::Command::ContinueLastPlayback)
+            .map_or_else(
+                || "run ContinueLastPlayback".to_string(),
+                |k| format!("press {}", k.key_sequence),
+            );
+        let message = format!(
+            "Welcome back! {key} to continue {} where you left off",
+            last_playback.item_name
+        );
+        drop(player);
+        state.ui.lock().show_toast(message);
+    }
+
     /// Create a new client session
     pub async fn new_session(&self, state: Option<&SharedState>, reauth: bool) -> Result<()> {
         let session = self.auth_config.session();
@@ -191,6 +377,10 @@ impl AppClient {
         self.refresh_token().await.context("refresh auth token")?;
 
         if let Some(state) = state {
+            #[cfg(feature = "daemon")]
+            {
+                state.player.write().is_idle = false;
+            }
             // reset the application's caches
             state.data.write().caches = MemoryCaches::new();
             self.initialize_playback(state);
@@ -200,14 +390,56 @@ impl AppClient {
     }
 
     /// Check if the current session is valid and if invalid, create a new session
+    ///
+    /// Failed attempts to create a new session (e.g. when the network connection is lost)
+    /// are retried with an exponential backoff, during which the check fails immediately.
     pub async fn check_valid_session(&self, state: &SharedState) -> Result<()> {
-        if self.session().await.is_invalid() {
-            tracing::info!("Client's current session is invalid, creating a new session...");
-            self.new_session(Some(state), false)
+        if !self.session().await.is_invalid() {
+            return Ok(());
+        }
+
+        if let Some(retry_time) = self.reconnect_backoff.lock().retry_time {
+            if std::time::Instant::now() < retry_time {
+                anyhow::bail!("Client's current session is invalid, waiting to reconnect");
+            }
+        }
+
+        tracing::info!("Client's current session is invalid, creating a new session...");
+        state.player.write().connection_status = ConnectionStatus::Reconnecting;
+        #[cfg(feature = "streaming")]
+        state.playback_stats.record_reconnect();
+        let result =
+            match tokio::time::timeout(RECONNECT_TIMEOUT, self.new_session(Some(state), false))
                 .await
-                .context("create new client session")?;
+            {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!("timed out")),
+            };
+
+        let mut backoff = self.reconnect_backoff.lock();
+        match result {
+            Ok(()) => {
+                *backoff = ReconnectBackoff::default();
+                state.player.write().connection_status = ConnectionStatus::Online;
+                Ok(())
+            }
+            Err(err) => {
+                let delay = RECONNECT_MIN_DELAY
+                    .saturating_mul(1 << backoff.n_failures.min(16))
+                    .min(RECONNECT_MAX_DELAY);
+                backoff.n_failures += 1;
+                backoff.retry_time = Some(std::time::Instant::now() + delay);
+                Err(err.context(format!(
+                    "create new client session, retrying in {}s",
+                    delay.as_secs()
+                )))
+            }
         }
-        Ok(())
+    }
+
+    #[cfg(feature = "zeroconf")]
+    pub fn auth_config(&self) -> &AuthConfig {
+        &self.auth_config
     }
 
     /// Create a new streaming connection
@@ -231,6 +463,136 @@ impl AppClient {
         Ok(())
     }
 
+    /// Shut down the streaming connection and the session while the daemon is idle,
+    /// releasing the audio device. A new session is created upon the next request
+    /// needing it (see [`Self::check_valid_session`]).
+    #[cfg(feature = "daemon")]
+    pub async fn shutdown_idle_session(&self, state: &SharedState) {
+        let conn = self.stream_conn.lock().take();
+        if let Some(conn) = conn {
+            if let Err(err) = conn.shutdown() {
+                tracing::error!("Failed to shutdown the streaming connection: {err:#}");
+            }
+        }
+        self.session().await.shutdown();
+        state.player.write().is_idle = true;
+    }
+
+    /// Stop the integrated player before the application quits, fading out its audio
+    /// if the player is playing. The device's volume is restored after the fade-out.
+    #[cfg(feature = "streaming")]
+    pub async fn stop_integrated_player(&self, state: &SharedState) {
+        const FADE_OUT_STEPS: u16 = 10;
+
+        let Some(conn) = self.stream_conn.lock().take() else {
+            return;
+        };
+        let volume = state
+            .player
+            .read()
+            .buffered_playback
+            .as_ref()
+            .and_then(|p| {
+                (p.is_playing && p.device_name == config::get_config().app_config.device.name)
+                    .then_some(p.volume)
+                    .flatten()
+            });
+
+        if let Some(volume) = volume {
+            // `librespot` volume ranges from 0 to 65535, see `streaming::new_connection`
+            let volume = (f64::from(volume.min(100)) / 100.0 * 65535.0).round() as u16;
+            for step in (0..FADE_OUT_STEPS).rev() {
+                if let Err(err) = conn.set_volume(volume / FADE_OUT_STEPS * step) {
+                    tracing::warn!("Failed to fade out the integrated player: {err:#}");
+                    break;
+                }
+                tokio::time::sleep(FADE_OUT_DURATION / u32::from(FADE_OUT_STEPS)).await;
+            }
+            if let Err(err) = conn.pause() {
+                tracing::warn!("Failed to pause the integrated player: {err:#}");
+            }
+            // restore the device's volume once paused, so that other Spotify Connect clients
+            // and the next session don't see the device muted
+            if let Err(err) = conn.set_volume(volume) {
+                tracing::warn!("Failed to restore the integrated player's volume: {err:#}");
+            }
+        }
+        if let Err(err) = conn.shutdown() {
+            tracing::error!("Failed to shutdown the streaming connection: {err:#}");
+        }
+    }
+
+    /// Resolve a Spotify link or URI, which can be a shortened link, into the linked item's URI
+    pub async fn resolve_spotify_link(&self, link: &str) -> Result<String> {
+        let uri = if crate::utils::is_short_spotify_link(link) {
+            // a shortened link redirects to the item's full link, either directly
+            // or through a web page embedding the full link
+            let response = self.http.get(link.trim()).send().await?;
+            match crate::utils::spotify_uri_from_link(response.url().as_str()) {
+                Some(uri) => Some(uri),
+                None => crate::utils::spotify_uri_from_link(&response.text().await?),
+            }
+        } else {
+            crate::utils::spotify_uri_from_link(link)
+        };
+        uri.with_context(|| format!("{link} is not a valid Spotify link"))
+    }
+
+    /// Open a Spotify link or URI: the linked context's page is opened unless `play` is true,
+    /// in which case the context is played. Tracks and episodes are always played.
+    pub async fn open_spotify_link(
+        &self,
+        state: &SharedState,
+        link: &str,
+        play: bool,
+    ) -> Result<()> {
+        let uri = self.resolve_spotify_link(link).await?;
+
+        if !play {
+            if let Some(id) = ContextId::from_uri(&uri) {
+                state.ui.lock().new_page(PageState::Context {
+                    id: None,
+                    context_page_type: ContextPageType::Browsing(id),
+                    state: None,
+                });
+                return Ok(());
+            }
+        }
+        let playback =
+            Playback::from_uri(&uri).with_context(|| format!("unsupported Spotify link {link}"))?;
+
+        let request = PlayerRequest::StartPlayback(playback, None);
+        let playback = state.player.read().buffered_playback.clone();
+        let playback = self.handle_player_request(request, playback).await?;
+        state.player.write().buffered_playback = playback;
+        self.update_playback(state);
+        Ok(())
+    }
+
+    /// Apply the playback's volume to the other devices in the device group.
+    ///
+    /// Spotify only accepts volume changes on devices that are currently reachable,
+    /// so a failure for one device is logged instead of failing the whole request.
+    async fn sync_device_group_volume(&self, state: &SharedState, playback: &PlaybackMetadata) {
+        let device_ids = state
+            .player
+            .read()
+            .device_group
+            .iter()
+            .filter(|id| Some(id.as_str()) != playback.device_id.as_deref())
+            .cloned()
+            .collect::<Vec<_>>();
+        let volume = match playback.mute_state {
+            Some(_) => 0,
+            None => playback.volume.unwrap_or_default(),
+        };
+        for id in device_ids {
+            if let Err(err) = self.volume(volume as u8, Some(&id)).await {
+                tracing::warn!("Failed to set volume of grouped device with id={id}: {err:#}");
+            }
+        }
+    }
+
     /// Handle a player request, return a new playback metadata on success
     pub async fn handle_player_request(
         &self,
@@ -293,6 +655,16 @@ impl AppClient {
             PlayerRequest::SeekTrack(position_ms) => {
                 self.seek_track(position_ms, device_id).await?;
             }
+            PlayerRequest::RevertTrackPreview(previous) => {
+                // restart the previous track in its context instead of going to the previous track,
+                // as the previewed track isn't necessarily the one after it (e.g. a queued track)
+                self.start_playback_at(
+                    last_playback_to_playback(&previous)?,
+                    device_id,
+                    Some(chrono::Duration::milliseconds(previous.position_ms)),
+                )
+                .await?;
+            }
             PlayerRequest::Repeat => {
                 let next_repeat_state = match playback.repeat_state {
                     rspotify::model::RepeatState::Off => rspotify::model::RepeatState::Track,
@@ -341,13 +713,13 @@ impl AppClient {
     }
 
     /// Handle a client request
-    pub(crate) async fn handle_request(
-        &self,
-        state: &SharedState,
-        request: ClientRequest,
-    ) -> Result<()> {
+    pub async fn handle_request(&self, state: &SharedState, request: ClientRequest) -> Result<()> {
         let timer = tokio::time::Instant::now();
 
+        self.rate_limiter
+            .acquire(request.rate_limit_weight(), request.priority())
+            .await;
+
         match request {
             ClientRequest::GetBrowseCategories => {
                 let categories = self.browse_categories().await?;
@@ -374,17 +746,41 @@ impl AppClient {
                         .insert(uri, lyrics, *TTL_CACHE_DURATION);
                 }
             }
+            ClientRequest::GetAudioAnalysis { track_id } => {
+                let uri = track_id.uri();
+                if !state.data.read().caches.audio_analysis.contains_key(&uri) {
+                    #[allow(deprecated)]
+                    let analysis = self.track_analysis(track_id).await?;
+                    state.data.write().caches.audio_analysis.insert(
+                        uri,
+                        analysis,
+                        *TTL_CACHE_DURATION,
+                    );
+                }
+            }
             #[cfg(feature = "streaming")]
             ClientRequest::RestartIntegratedClient => {
                 self.new_session(Some(state), false).await?;
             }
+            ClientRequest::OpenSpotifyLink { link, play } => {
+                self.open_spotify_link(state, &link, play).await?;
+            }
             ClientRequest::GetCurrentUser => {
                 let user = self.current_user().await?;
                 state.data.write().user_data.user = Some(user);
             }
             ClientRequest::Player(request) => {
+                let is_volume_request = matches!(
+                    request,
+                    PlayerRequest::Volume(_) | PlayerRequest::ToggleMute
+                );
                 let playback = state.player.read().buffered_playback.clone();
                 let playback = self.handle_player_request(request, playback).await?;
+                if is_volume_request {
+                    if let Some(ref playback) = playback {
+                        self.sync_device_group_volume(state, playback).await;
+                    }
+                }
                 state.player.write().buffered_playback = playback;
                 self.update_playback(state);
             }
@@ -409,44 +805,54 @@ impl AppClient {
                         .map(PlaylistFolderItem::Playlist)
                         .collect()
                 };
-                store_data_into_file_cache(
-                    FileCacheKey::Playlists,
+                store_cache_data(
+                    CacheKey::Playlists,
                     &config::get_config().cache_folder,
                     &playlists,
                 )
-                .context("store user's playlists into the cache folder")?;
+                .context("store user's playlists into the database")?;
                 state.data.write().user_data.playlists = playlists;
             }
             ClientRequest::GetUserFollowedArtists => {
                 let artists = self.current_user_followed_artists().await?;
-                store_data_into_file_cache(
-                    FileCacheKey::FollowedArtists,
+                store_cache_data(
+                    CacheKey::FollowedArtists,
                     &config::get_config().cache_folder,
                     &artists,
                 )
-                .context("store user's followed artists into the cache folder")?;
+                .context("store user's followed artists into the database")?;
                 state.data.write().user_data.followed_artists = artists;
             }
             ClientRequest::GetUserSavedAlbums => {
                 let albums = self.current_user_saved_albums().await?;
-                store_data_into_file_cache(
-                    FileCacheKey::SavedAlbums,
+                store_cache_data(
+                    CacheKey::SavedAlbums,
                     &config::get_config().cache_folder,
                     &albums,
                 )
-                .context("store user's saved albums into the cache folder")?;
+                .context("store user's saved albums into the database")?;
                 state.data.write().user_data.saved_albums = albums;
             }
             ClientRequest::GetUserSavedShows => {
                 let shows = self.current_user_saved_shows().await?;
-                store_data_into_file_cache(
-                    FileCacheKey::SavedShows,
+                store_cache_data(
+                    CacheKey::SavedShows,
                     &config::get_config().cache_folder,
                     &shows,
                 )
-                .context("store user's saved shows into the cache folder")?;
+                .context("store user's saved shows into the database")?;
                 state.data.write().user_data.saved_shows = shows;
             }
+            ClientRequest::GetUserSavedEpisodes => {
+                let episodes = self.current_user_saved_episodes().await?;
+                store_cache_data(
+                    CacheKey::SavedEpisodes,
+                    &config::get_config().cache_folder,
+                    &episodes,
+                )
+                .context("store user's saved episodes into the database")?;
+                state.data.write().user_data.saved_episodes = episodes;
+            }
             ClientRequest::GetUserTopTracks => {
                 let uri = &USER_TOP_TRACKS_ID.uri;
                 if !state.data.read().caches.context.contains_key(uri) {
@@ -467,24 +873,65 @@ impl AppClient {
                     .iter()
                     .map(|t| (t.id.uri(), t.clone()))
                     .collect::<HashMap<_, _>>();
-                store_data_into_file_cache(
-                    FileCacheKey::SavedTracks,
+                store_cache_data(
+                    CacheKey::SavedTracks,
                     &config::get_config().cache_folder,
                     &tracks_hm,
                 )
-                .context("store user's saved tracks into the cache folder")?;
+                .context("store user's saved tracks into the database")?;
 
-                let mut data = state.data.write();
-                data.user_data.saved_tracks = tracks_hm;
-                data.caches.context.insert(
-                    USER_LIKED_TRACKS_ID.uri.clone(),
+                state.data.write().user_data.saved_tracks = tracks_hm;
+                store_context(
+                    state,
+                    &USER_LIKED_TRACKS_ID.uri,
                     Context::Tracks {
                         tracks,
                         desc: "User's liked tracks".to_string(),
                     },
-                    *TTL_CACHE_DURATION,
                 );
             }
+            ClientRequest::GetSmartPlaylistTracks(playlist) => {
+                // always re-fetch the tracks as their annotations may have changed
+                let uris = state
+                    .data
+                    .read()
+                    .user_data
+                    .track_annotations
+                    .iter()
+                    .filter(|(_, a)| playlist.matches(a))
+                    .map(|(uri, _)| uri.clone())
+                    .collect::<Vec<_>>();
+                let tracks = self.tracks_from_uris(&uris).await?;
+                store_context(
+                    state,
+                    &playlist.tracks_id().uri,
+                    Context::Tracks {
+                        tracks,
+                        desc: format!("Smart playlist {}", playlist.name),
+                    },
+                );
+            }
+            ClientRequest::GetSavedTrackGenres => {
+                self.get_saved_track_genres(state).await?;
+            }
+            ClientRequest::GetTrackDetails(track_id) => {
+                let uri = track_id.uri();
+                if !state.data.read().caches.track_details.contains_key(&uri) {
+                    // the track is requested without a market, so that its available markets are included
+                    let details = self.spotify.track(track_id, None).await?.into();
+                    state.data.write().caches.track_details.insert(
+                        uri,
+                        details,
+                        *TTL_CACHE_DURATION,
+                    );
+                }
+            }
+            ClientRequest::GetArtistFeed => {
+                self.get_artist_feed(state).await?;
+            }
+            ClientRequest::GetSharedPlaylistAdds => {
+                self.get_shared_playlist_adds(state).await?;
+            }
             ClientRequest::GetUserRecentlyPlayedTracks => {
                 let uri = &USER_RECENTLY_PLAYED_TRACKS_ID.uri;
                 if !state.data.read().caches.context.contains_key(uri) {
@@ -500,30 +947,55 @@ impl AppClient {
                 }
             }
             ClientRequest::GetContext(context) => {
+                if matches!(context, ContextId::Tracks(_)) {
+                    anyhow::bail!("`GetContext` request for `tracks` context is not supported!");
+                }
+
                 let uri = context.uri();
-                if !state.data.read().caches.context.contains_key(&uri) {
+                let is_loaded_or_loading = {
+                    let mut data = state.data.write();
+                    let caches = &mut data.caches;
+                    // a stale context is still shown while it's refreshed
+                    if (caches.context.contains_key(&uri) && !caches.stale_contexts.contains(&uri))
+                        || caches.partial_contexts.contains_key(&uri)
+                    {
+                        true
+                    } else {
+                        caches
+                            .partial_contexts
+                            .insert(uri.clone(), PartialContext::default());
+                        false
+                    }
+                };
+                if !is_loaded_or_loading {
+                    // the context's sections are loaded concurrently and rendered as they arrive
                     let context = match context {
                         ContextId::Playlist(playlist_id) => {
-                            self.playlist_context(playlist_id).await?
+                            self.playlist_context(playlist_id, Some(state)).await
                         }
-                        ContextId::Album(album_id) => self.album_context(album_id).await?,
-                        ContextId::Artist(artist_id) => self.artist_context(artist_id).await?,
-                        ContextId::Tracks(_) => {
-                            anyhow::bail!(
-                                "`GetContext` request for `tracks` context is not supported!"
-                            );
+                        ContextId::Album(album_id) => {
+                            self.album_context(album_id, Some(state)).await
                         }
-                        ContextId::Show(show_id) => self.show_context(show_id).await?,
+                        ContextId::Artist(artist_id) => {
+                            self.artist_context(artist_id, Some(state)).await
+                        }
+                        ContextId::Show(show_id) => self.show_context(show_id, Some(state)).await,
+                        ContextId::Tracks(_) => unreachable!("checked above"),
                     };
 
-                    state
-                        .data
-                        .write()
-                        .caches
-                        .context
-                        .insert(uri, context, *TTL_CACHE_DURATION);
+                    let context = {
+                        let mut data = state.data.write();
+                        data.caches.partial_contexts.remove(&uri);
+                        let mut context = context?;
+                        data.user_data.sort_playlist_context(&mut context);
+                        context
+                    };
+                    store_context(state, &uri, context);
                 }
             }
+            ClientRequest::CheckSavedTracks(ids) => {
+                self.check_saved_tracks(state, ids).await?;
+            }
             ClientRequest::Search(query) => {
                 if !state.data.read().caches.search.contains_key(&query) {
                     let results = self.search(&query).await?;
@@ -536,6 +1008,13 @@ impl AppClient {
                         .insert(query, results, *TTL_CACHE_DURATION);
                 }
             }
+            ClientRequest::GetSearchResultPage { query, typ, offset } => {
+                self.get_search_result_page(state, &query, typ, offset)
+                    .await?;
+            }
+            ClientRequest::LoadAllSearchResults { query, typ } => {
+                self.load_all_search_results(state, &query, typ).await?;
+            }
             ClientRequest::GetRadioTracks {
                 seed_uri: uri,
                 seed_name: name,
@@ -556,13 +1035,50 @@ impl AppClient {
             }
             ClientRequest::AddPlayableToQueue(playable_id) => {
                 self.add_item_to_queue(playable_id, None).await?;
+                // refresh the queue, which is shown in the queue page and the playback window
+                let queue = self.current_user_queue().await?;
+                state.player.write().queue = Some(queue);
             }
             ClientRequest::AddPlayableToPlaylist(playlist_id, playable_id) => {
                 self.add_item_to_playlist(state, playlist_id, playable_id)
                     .await?;
             }
+            ClientRequest::SyncPlaylist {
+                playlist_id,
+                added,
+                removed,
+            } => {
+                self.sync_playlist(state, playlist_id, added, removed)
+                    .await?;
+            }
+            ClientRequest::CreatePlaylistFromTracks {
+                playlist_name,
+                track_ids,
+            } => {
+                let user_id = state
+                    .data
+                    .read()
+                    .user_data
+                    .user
+                    .as_ref()
+                    .map(|u| u.id.clone())
+                    .context("current user is not available")?;
+                let playlist = self
+                    .create_new_playlist(state, user_id, &playlist_name, false, false, "")
+                    .await?;
+                self.add_tracks_to_playlist(state, playlist.id, track_ids)
+                    .await?;
+            }
+            ClientRequest::MergePlaylists {
+                playlists,
+                order,
+                deduplicate,
+            } => {
+                self.merge_playlists(state, playlists, order, deduplicate)
+                    .await?;
+            }
             ClientRequest::AddAlbumToQueue(album_id) => {
-                let album_context = self.album_context(album_id).await?;
+                let album_context = self.album_context(album_id, None).await?;
 
                 if let Context::Album { album: _, tracks } = album_context {
                     for track in tracks {
@@ -571,6 +1087,13 @@ impl AppClient {
                     }
                 }
             }
+            ClientRequest::EditPlaylistTracks {
+                playlist,
+                track_ids,
+            } => {
+                self.edit_playlist_tracks(state, &playlist, track_ids)
+                    .await?;
+            }
             ClientRequest::DeleteTrackFromPlaylist(playlist_id, track_id) => {
                 self.delete_track_from_playlist(state, playlist_id, track_id)
                     .await?;
@@ -578,9 +1101,45 @@ impl AppClient {
             ClientRequest::AddToLibrary(item) => {
                 self.add_to_library(state, item).await?;
             }
+            ClientRequest::SetTracksLiked {
+                tracks,
+                liked,
+                undoable,
+            } => {
+                self.set_tracks_liked(state, tracks, liked, undoable)
+                    .await?;
+            }
             ClientRequest::DeleteFromLibrary(id) => {
                 self.delete_from_library(state, id).await?;
             }
+            ClientRequest::AddToBlocklist(item) => {
+                self.add_to_blocklist(state, item).await?;
+            }
+            #[cfg(feature = "fx")]
+            ClientRequest::UpdateSmartVisualizationMode => {
+                let track = match state.player.read().currently_playing() {
+                    Some(rspotify::model::PlayableItem::Track(track)) => Some(track.clone()),
+                    _ => None,
+                };
+                if let Some(track) = track {
+                    self.update_smart_visualization_mode(state, &track).await;
+                }
+            }
+            #[cfg(feature = "preview")]
+            ClientRequest::PeekTrack(track_id) => {
+                let message = match self.peek_track(track_id).await {
+                    Ok(message) => message,
+                    Err(err) => format!("Failed to play the track's preview: {err:#}"),
+                };
+                state.ui.lock().show_toast(message);
+            }
+            ClientRequest::ContinueLastPlayback => {
+                let message = match self.continue_last_playback(state).await {
+                    Ok(name) => format!("Continuing {name}"),
+                    Err(err) => format!("Failed to continue the last playback: {err:#}"),
+                };
+                state.ui.lock().show_toast(message);
+            }
             ClientRequest::GetCurrentUserQueue => {
                 let queue = self.current_user_queue().await?;
                 state.player.write().queue = Some(queue);
@@ -626,6 +1185,14 @@ impl AppClient {
                 )
                 .await?;
             }
+            ClientRequest::ChangePlaylistDetails {
+                playlist_id,
+                name,
+                desc,
+            } => {
+                self.change_playlist_details(state, playlist_id, name, desc)
+                    .await?;
+            }
         }
 
         tracing::info!(
@@ -811,7 +1378,7 @@ impl AppClient {
         // Fetch the first page of playlists
         let first_page = self
             .http_get::<rspotify::model::Page<rspotify::model::SimplifiedPlaylist>>(
-                &format!("{SPOTIFY_API_ENDPOINT}/me/playlists"),
+                &format!("{}/me/playlists", self.api_endpoint),
                 &Query::from([("limit", "50")]),
             )
             .await?;
@@ -875,6 +1442,29 @@ impl AppClient {
         Ok(shows.into_iter().map(|s| s.show.into()).collect())
     }
 
+    /// Get all saved episodes ("Your Episodes") of the current user
+    pub async fn current_user_saved_episodes(&self) -> Result<Vec<Episode>> {
+        // `rspotify` doesn't support the saved episodes API,
+        // so the API is called directly
+        #[derive(serde::Deserialize)]
+        struct SavedEpisode {
+            // an episode can be null if it's no longer available
+            episode: Option<rspotify::model::FullEpisode>,
+        }
+
+        let first_page = self
+            .http_get::<rspotify::model::Page<SavedEpisode>>(
+                &format!("{}/me/episodes", self.api_endpoint),
+                &Query::from([("market", "from_token"), ("limit", "50")]),
+            )
+            .await?;
+        let episodes = self.all_paging_items(first_page, &market_query()).await?;
+        Ok(episodes
+            .into_iter()
+            .filter_map(|e| e.episode.map(Episode::from))
+            .collect())
+    }
+
     /// Get all albums of an artist
     pub async fn artist_albums(&self, artist_id: ArtistId<'_>) -> Result<Vec<Album>> {
         let payload = market_query();
@@ -915,30 +1505,60 @@ impl AppClient {
 
     /// Start a playback
     async fn start_playback(&self, playback: Playback, device_id: Option<&str>) -> Result<()> {
+        self.start_playback_at(playback, device_id, None).await
+    }
+
+    /// Start a playback at a position of its first played item
+    async fn start_playback_at(
+        &self,
+        playback: Playback,
+        device_id: Option<&str>,
+        position: Option<chrono::Duration>,
+    ) -> Result<()> {
         match playback {
             Playback::Context(id, offset) => match id {
                 ContextId::Album(id) => {
-                    self.start_context_playback(PlayContextId::from(id), device_id, offset, None)
-                        .await?;
+                    self.start_context_playback(
+                        PlayContextId::from(id),
+                        device_id,
+                        offset,
+                        position,
+                    )
+                    .await?;
                 }
                 ContextId::Artist(id) => {
-                    self.start_context_playback(PlayContextId::from(id), device_id, offset, None)
-                        .await?;
+                    self.start_context_playback(
+                        PlayContextId::from(id),
+                        device_id,
+                        offset,
+                        position,
+                    )
+                    .await?;
                 }
                 ContextId::Playlist(id) => {
-                    self.start_context_playback(PlayContextId::from(id), device_id, offset, None)
-                        .await?;
+                    self.start_context_playback(
+                        PlayContextId::from(id),
+                        device_id,
+                        offset,
+                        position,
+                    )
+                    .await?;
                 }
                 ContextId::Show(id) => {
-                    self.start_context_playback(PlayContextId::from(id), device_id, offset, None)
-                        .await?;
+                    self.start_context_playback(
+                        PlayContextId::from(id),
+                        device_id,
+                        offset,
+                        position,
+                    )
+                    .await?;
                 }
                 ContextId::Tracks(_) => {
                     anyhow::bail!("`StartPlayback` request for `tracks` context is not supported")
                 }
             },
             Playback::URIs(ids, offset) => {
-                self.start_uris_playback(ids, device_id, offset, None)
+                self.start_uris_playback(ids, device_id, offset, position)
                     .await?;
             }
         }
@@ -946,6 +1566,37 @@ impl AppClient {
         Ok(())
     }
 
+    /// Continue the last playback of the application's previous run on the current device
+    /// (or an available device if there is no playback). Returns the name of the continued item.
+    async fn continue_last_playback(&self, state: &SharedState) -> Result<String> {
+        let (last_playback, device_id) = {
+            let player = state.player.read();
+            (
+                player
+                    .last_playback
+                    .clone()
+                    .context("no previous playback to continue")?,
+                player.playback.as_ref().and_then(|p| p.device.id.clone()),
+            )
+        };
+        let device_id = match device_id {
+            Some(id) => Some(id),
+            None => self.find_available_device().await?,
+        };
+
+        self.start_playback_at(
+            last_playback_to_playback(&last_playback)?,
+            device_id.as_deref(),
+            Some(chrono::Duration::milliseconds(last_playback.position_ms)),
+        )
+        .await?;
+
+        // upon starting a new playback, reset the buffered playback
+        state.player.write().buffered_playback = None;
+        self.update_playback(state);
+        Ok(last_playback.item_name)
+    }
+
     /// Get recommendation (radio) tracks based on a seed
     pub async fn radio_tracks(&self, seed_uri: String) -> Result<Vec<Track>> {
         #[derive(Debug, Deserialize)]
@@ -1007,82 +1658,52 @@ impl AppClient {
         Ok(tracks)
     }
 
+    /// Get tracks from their URIs
+    pub async fn tracks_from_uris(&self, uris: &[String]) -> Result<Vec<Track>> {
+        let ids = uris
+            .iter()
+            .filter_map(|uri| TrackId::from_uri(uri).ok())
+            .collect::<Vec<_>>();
+
+        let mut tracks = vec![];
+        // the API only allows getting at most 50 tracks per request
+        for ids in ids.chunks(50) {
+            tracks.extend(
+                self.tracks(ids.to_vec(), Some(rspotify::model::Market::FromToken))
+                    .await?
+                    .into_iter()
+                    .filter_map(Track::try_from_full_track),
+            );
+        }
+        Ok(tracks)
+    }
+
     /// Search for items (tracks, artists, albums, playlists) matching a given query
     pub async fn search(&self, query: &str) -> Result<SearchResults> {
-        let (
-            track_result,
-            artist_result,
-            album_result,
-            playlist_result,
-            show_result,
-            episode_result,
-        ) = tokio::try_join!(
-            self.search_specific_type(query, rspotify::model::SearchType::Track),
-            self.search_specific_type(query, rspotify::model::SearchType::Artist),
-            self.search_specific_type(query, rspotify::model::SearchType::Album),
-            self.search_specific_type(query, rspotify::model::SearchType::Playlist),
-            self.search_specific_type(query, rspotify::model::SearchType::Show),
-            self.search_specific_type(query, rspotify::model::SearchType::Episode)
+        let pages = tokio::try_join!(
+            self.search_page(query, SearchType::Track, 0),
+            self.search_page(query, SearchType::Artist, 0),
+            self.search_page(query, SearchType::Album, 0),
+            self.search_page(query, SearchType::Playlist, 0),
+            self.search_page(query, SearchType::Show, 0),
+            self.search_page(query, SearchType::Episode, 0)
         )?;
 
-        let (tracks, artists, albums, playlists, shows, episodes) = (
-            match track_result {
-                rspotify::model::SearchResult::Tracks(p) => p
-                    .items
-                    .into_iter()
-                    .filter_map(Track::try_from_full_track)
-                    .collect(),
-                _ => anyhow::bail!("expect a track search result"),
-            },
-            match artist_result {
-                rspotify::model::SearchResult::Artists(p) => {
-                    p.items.into_iter().map(std::convert::Into::into).collect()
-                }
-                _ => anyhow::bail!("expect an artist search result"),
-            },
-            match album_result {
-                rspotify::model::SearchResult::Albums(p) => p
-                    .items
-                    .into_iter()
-                    .filter_map(Album::try_from_simplified_album)
-                    .collect(),
-                _ => anyhow::bail!("expect an album search result"),
-            },
-            match playlist_result {
-                rspotify::model::SearchResult::Playlists(p) => {
-                    p.items.into_iter().map(std::convert::Into::into).collect()
-                }
-                _ => anyhow::bail!("expect a playlist search result"),
-            },
-            match show_result {
-                rspotify::model::SearchResult::Shows(p) => {
-                    p.items.into_iter().map(std::convert::Into::into).collect()
-                }
-                _ => anyhow::bail!("expect a show search result"),
-            },
-            match episode_result {
-                rspotify::model::SearchResult::Episodes(p) => {
-                    p.items.into_iter().map(std::convert::Into::into).collect()
-                }
-                _ => anyhow::bail!("expect a episode search result"),
-            },
-        );
-
-        Ok(SearchResults {
-            tracks,
-            artists,
-            albums,
-            playlists,
-            shows,
-            episodes,
-        })
+        let mut results = SearchResults::default();
+        results.append_page(pages.0);
+        results.append_page(pages.1);
+        results.append_page(pages.2);
+        results.append_page(pages.3);
+        results.append_page(pages.4);
+        results.append_page(pages.5);
+        Ok(results)
     }
 
     /// Search for items of a specific type matching a given query
     pub async fn search_specific_type(
         &self,
         query: &str,
-        typ: rspotify::model::SearchType,
+        typ: SearchType,
     ) -> Result<rspotify::model::SearchResult> {
         Ok(self
             .spotify
@@ -1090,15 +1711,100 @@ impl AppClient {
             .await?)
     }
 
-    /// Add a playable item to a playlist
-    pub async fn add_item_to_playlist(
+    /// Search for a page of items of a specific type matching a given query
+    pub async fn search_page(
         &self,
-        state: &SharedState,
-        playlist_id: PlaylistId<'_>,
-        playable_id: PlayableId<'_>,
-    ) -> Result<()> {
-        // remove all the occurrences of the track to ensure no duplication in the playlist
-        self.playlist_remove_all_occurrences_of_items(
+        query: &str,
+        typ: SearchType,
+        offset: usize,
+    ) -> Result<rspotify::model::SearchResult> {
+        Ok(self
+            .spotify
+            .search(
+                query,
+                typ,
+                None,
+                None,
+                Some(SEARCH_PAGE_SIZE as u32),
+                Some(offset as u32),
+            )
+            .await?)
+    }
+
+    /// Replace the cached search results of a specific type with the page of results at a given offset
+    async fn get_search_result_page(
+        &self,
+        state: &SharedState,
+        query: &str,
+        typ: SearchType,
+        offset: usize,
+    ) -> Result<()> {
+        let mut page = SearchResults::default();
+        page.append_page(self.search_page(query, typ, offset).await?);
+        page.page_mut(typ).offset = offset;
+
+        if let Some(results) = state.data.write().caches.search.get_mut(query) {
+            results.replace_results(typ, page);
+        }
+        Ok(())
+    }
+
+    /// Load all search results of a specific type page by page, updating the loading progress,
+    /// and replace the cached results of the type with them
+    async fn load_all_search_results(
+        &self,
+        state: &SharedState,
+        query: &str,
+        typ: SearchType,
+    ) -> Result<()> {
+        let Some(total) = state
+            .data
+            .read()
+            .caches
+            .search
+            .get(query)
+            .map(|results| results.page(typ).total)
+        else {
+            return Ok(());
+        };
+        if total > MAX_LOAD_ALL_SEARCH_RESULTS {
+            anyhow::bail!(
+                "cannot load all {total} search results, at most {MAX_LOAD_ALL_SEARCH_RESULTS} results can be loaded"
+            );
+        }
+
+        if let Some(results) = state.data.write().caches.search.get_mut(query) {
+            results.page_mut(typ).loading_all = Some(0);
+        }
+        let mut all = SearchResults::default();
+        let mut offset = 0;
+        while offset < total {
+            self.rate_limiter
+                .acquire(1, rate_limit::Priority::Interactive)
+                .await;
+            all.append_page(self.search_page(query, typ, offset).await?);
+            offset += SEARCH_PAGE_SIZE;
+
+            if let Some(results) = state.data.write().caches.search.get_mut(query) {
+                results.page_mut(typ).loading_all = Some(offset.min(total));
+            }
+        }
+
+        if let Some(results) = state.data.write().caches.search.get_mut(query) {
+            results.replace_results(typ, all);
+        }
+        Ok(())
+    }
+
+    /// Add a playable item to a playlist
+    pub async fn add_item_to_playlist(
+        &self,
+        state: &SharedState,
+        playlist_id: PlaylistId<'_>,
+        playable_id: PlayableId<'_>,
+    ) -> Result<()> {
+        // remove all the occurrences of the track to ensure no duplication in the playlist
+        self.playlist_remove_all_occurrences_of_items(
             playlist_id.as_ref(),
             [playable_id.as_ref()],
             None,
@@ -1108,12 +1814,115 @@ impl AppClient {
         self.playlist_add_items(playlist_id.as_ref(), [playable_id.as_ref()], None)
             .await?;
 
-        // After adding a new track to a playlist, remove the cache of that playlist to force refetching new data
-        state.data.write().caches.context.remove(&playlist_id.uri());
+        // After adding a new track to a playlist, mark the cache of that playlist as stale to force refetching new data
+        state
+            .data
+            .write()
+            .caches
+            .stale_contexts
+            .insert(playlist_id.uri());
 
         Ok(())
     }
 
+    /// Add tracks to a playlist
+    pub async fn add_tracks_to_playlist(
+        &self,
+        state: &SharedState,
+        playlist_id: PlaylistId<'static>,
+        track_ids: Vec<TrackId<'static>>,
+    ) -> Result<()> {
+        // the endpoint accepts at most 100 items per request
+        for chunk in track_ids.chunks(100) {
+            self.playlist_add_items(
+                playlist_id.as_ref(),
+                chunk.iter().map(|id| PlayableId::Track(id.as_ref())),
+                None,
+            )
+            .await?;
+        }
+
+        // refetch the playlist to update its in-memory data stored inside the app caches
+        let uri = playlist_id.uri();
+        let mut context = self.playlist_context(playlist_id, None).await?;
+        let mut data = state.data.write();
+        data.user_data.sort_playlist_context(&mut context);
+        data.caches
+            .context
+            .insert(uri, context, *TTL_CACHE_DURATION);
+
+        Ok(())
+    }
+
+    /// Sync a playlist with another playlist by removing all occurrences of the tracks
+    /// not in the other playlist, then adding the tracks missing from the synced playlist
+    async fn sync_playlist(
+        &self,
+        state: &SharedState,
+        playlist_id: PlaylistId<'static>,
+        added: Vec<TrackId<'static>>,
+        removed: Vec<TrackId<'static>>,
+    ) -> Result<()> {
+        // the endpoint accepts at most 100 items per request
+        for chunk in removed.chunks(100) {
+            self.playlist_remove_all_occurrences_of_items(
+                playlist_id.as_ref(),
+                chunk.iter().map(|id| PlayableId::Track(id.as_ref())),
+                None,
+            )
+            .await?;
+        }
+        // the playlist is refetched after adding the tracks
+        self.add_tracks_to_playlist(state, playlist_id, added).await
+    }
+
+    /// Merge playlists into a new playlist
+    async fn merge_playlists(
+        &self,
+        state: &SharedState,
+        playlists: Vec<Playlist>,
+        order: PlaylistMergeOrder,
+        deduplicate: bool,
+    ) -> Result<()> {
+        let mut playlist_tracks = vec![];
+        for playlist in &playlists {
+            let Context::Playlist { tracks, .. } =
+                self.playlist_context(playlist.id.as_ref(), None).await?
+            else {
+                anyhow::bail!("expect a playlist context");
+            };
+            playlist_tracks.push(tracks);
+        }
+        let tracks = order.merge(playlist_tracks, deduplicate);
+
+        let user_id = state
+            .data
+            .read()
+            .user_data
+            .user
+            .as_ref()
+            .map(|u| u.id.clone())
+            .context("current user is not available")?;
+        let name = crate::utils::map_join(&playlists, |p| &p.name, " + ");
+        let playlist = self
+            .create_new_playlist(
+                state,
+                user_id,
+                &name,
+                false,
+                false,
+                &format!("Merged from {name}"),
+            )
+            .await?;
+
+        self.add_tracks_to_playlist(
+            state,
+            playlist.id,
+            tracks.into_iter().map(|t| t.id).collect(),
+        )
+        .await
+    }
+
     /// Remove a track from a playlist
     pub async fn delete_track_from_playlist(
         &self,
@@ -1143,6 +1952,95 @@ impl AppClient {
         Ok(())
     }
 
+    /// Edit a playlist's tracks into the given tracks by removing the tracks that
+    /// aren't kept then reordering the remaining tracks
+    async fn edit_playlist_tracks(
+        &self,
+        state: &SharedState,
+        playlist: &Playlist,
+        track_ids: Vec<TrackId<'static>>,
+    ) -> Result<()> {
+        let Some(Context::Playlist { tracks, .. }) = state
+            .data
+            .read()
+            .caches
+            .context
+            .get(&playlist.id.uri())
+            .cloned()
+        else {
+            anyhow::bail!("tracks of playlist {} aren't loaded", playlist.name);
+        };
+
+        // the first occurrences of a track are kept, up to its number of occurrences in the edited tracks
+        let mut remaining_occurrences = HashMap::<&TrackId, usize>::new();
+        for id in &track_ids {
+            *remaining_occurrences.entry(id).or_default() += 1;
+        }
+        let mut kept_tracks = vec![];
+        let mut removed_positions = HashMap::<&TrackId, Vec<u32>>::new();
+        for (position, track) in tracks.iter().enumerate() {
+            match remaining_occurrences.get_mut(&track.id).filter(|n| **n > 0) {
+                Some(n) => {
+                    *n -= 1;
+                    kept_tracks.push(track.clone());
+                }
+                None => removed_positions
+                    .entry(&track.id)
+                    .or_default()
+                    .push(u32::try_from(position)?),
+            }
+        }
+
+        // the positions are relative to the playlist's snapshot before removing any tracks
+        let removed_positions = removed_positions.into_iter().collect::<Vec<_>>();
+        for chunk in removed_positions.chunks(100) {
+            self.playlist_remove_specific_occurrences_of_items(
+                playlist.id.as_ref(),
+                chunk.iter().map(|(id, positions)| ItemPositions {
+                    id: PlayableId::Track(id.as_ref()),
+                    positions,
+                }),
+                Some(&playlist.snapshot_id),
+            )
+            .await?;
+        }
+
+        // move the tracks into their edited positions one by one
+        for (i, id) in track_ids.iter().enumerate() {
+            if kept_tracks[i].id == *id {
+                continue;
+            }
+            let Some(j) = (i + 1..kept_tracks.len()).find(|j| kept_tracks[*j].id == *id) else {
+                anyhow::bail!("track {id} isn't in playlist {}", playlist.name);
+            };
+            self.playlist_reorder_items(
+                playlist.id.as_ref(),
+                Some(i32::try_from(j)?),
+                Some(i32::try_from(i)?),
+                None,
+                None,
+            )
+            .await?;
+            let track = kept_tracks.remove(j);
+            kept_tracks.insert(i, track);
+        }
+        tracing::info!(
+            "tracks of playlist (id={}) were successfully edited",
+            playlist.id
+        );
+
+        if let Some(Context::Playlist { tracks, .. }) = state
+            .data
+            .write()
+            .caches
+            .context
+            .get_mut(&playlist.id.uri())
+        {
+            *tracks = kept_tracks;
+        }
+        Ok(())
+    }
+
     /// Reorder items in a playlist
     async fn reorder_playlist_items(
         &self,
@@ -1183,6 +2081,245 @@ impl AppClient {
         Ok(())
     }
 
+    /// Check the saved (liked) status of tracks and store the results in the cache.
+    /// Tracks whose status has already been cached are not re-checked.
+    async fn check_saved_tracks(
+        &self,
+        state: &SharedState,
+        ids: Vec<TrackId<'static>>,
+    ) -> Result<()> {
+        let ids = {
+            let data = state.data.read();
+            ids.into_iter()
+                .filter(|id| !data.caches.saved_tracks_status.contains_key(&id.uri()))
+                .collect::<Vec<_>>()
+        };
+
+        // the `contains` endpoint accepts at most 50 tracks per request
+        for chunk in ids.chunks(50) {
+            self.rate_limiter
+                .acquire(1, rate_limit::Priority::Background)
+                .await;
+            let contains = self
+                .current_user_saved_tracks_contains(chunk.iter().map(TrackId::as_ref))
+                .await?;
+
+            let mut data = state.data.write();
+            for (id, saved) in chunk.iter().zip(contains) {
+                data.caches
+                    .saved_tracks_status
+                    .insert(id.uri(), saved, *TTL_CACHE_DURATION);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the genres of the saved tracks' artists whose genres are not cached yet
+    async fn get_saved_track_genres(&self, state: &SharedState) -> Result<()> {
+        let artist_ids = {
+            let data = state.data.read();
+            data.user_data
+                .saved_tracks
+                .values()
+                .flat_map(|t| &t.artists)
+                .filter(|a| !data.user_data.artist_genres.contains_key(&a.id.uri()))
+                .map(|a| a.id.clone())
+                .collect::<std::collections::HashSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        if artist_ids.is_empty() {
+            return Ok(());
+        }
+
+        // the API only allows getting at most 50 artists per request
+        for ids in artist_ids.chunks(50) {
+            self.rate_limiter
+                .acquire(1, rate_limit::Priority::Background)
+                .await;
+            let artists = self.artists(ids.to_vec()).await?;
+
+            let mut data = state.data.write();
+            for artist in artists {
+                data.user_data
+                    .artist_genres
+                    .insert(artist.id.uri(), artist.genres);
+            }
+        }
+
+        // clone the genres to avoid locking the application's data while writing into the database
+        let artist_genres = state.data.read().user_data.artist_genres.clone();
+        store_cache_data(
+            CacheKey::ArtistGenres,
+            &config::get_config().cache_folder,
+            &artist_genres,
+        )
+        .context("store artists' genres into the database")
+    }
+
+    /// Get the feed of the user's followed artists, which consists of
+    /// - albums and singles recently released by the artists
+    /// - albums the artists recently appeared on
+    /// - the artists' tracks recently added to the user's playlists whose tracks are cached
+    async fn get_artist_feed(&self, state: &SharedState) -> Result<()> {
+        let artists = state.data.read().user_data.followed_artists.clone();
+        let since = chrono::Utc::now() - chrono::Duration::days(ARTIST_FEED_MAX_AGE_DAYS);
+        let since_date = since.format("%Y-%m-%d").to_string();
+
+        let mut feed = vec![];
+        for artist in &artists {
+            // the artist's albums are returned grouped by their types,
+            // so appearances are queried separately to not be cut off by the artist's own releases
+            for groups in [
+                vec![
+                    rspotify::model::AlbumType::Album,
+                    rspotify::model::AlbumType::Single,
+                ],
+                vec![rspotify::model::AlbumType::AppearsOn],
+            ] {
+                self.rate_limiter
+                    .acquire(1, rate_limit::Priority::Background)
+                    .await;
+                let page = self
+                    .artist_albums_manual(
+                        artist.id.as_ref(),
+                        groups,
+                        Some(rspotify::model::Market::FromToken),
+                        Some(20),
+                        None,
+                    )
+                    .await?;
+                for album in page.items {
+                    let is_appearance = album.album_group.as_deref() == Some("appears_on");
+                    let Some(album) = Album::try_from_simplified_album(album) else {
+                        continue;
+                    };
+                    if album.release_date < since_date {
+                        continue;
+                    }
+                    let artist = artist.clone();
+                    feed.push(if is_appearance {
+                        FeedItem::AppearsOn { artist, album }
+                    } else {
+                        FeedItem::Release { artist, album }
+                    });
+                }
+            }
+        }
+
+        {
+            let data = state.data.read();
+            let artists = artists
+                .iter()
+                .map(|a| (a.id.uri(), a))
+                .collect::<HashMap<_, _>>();
+            let since = u64::try_from(since.timestamp()).unwrap_or_default();
+            for item in &data.user_data.playlists {
+                let PlaylistFolderItem::Playlist(playlist) = item else {
+                    continue;
+                };
+                let Some(Context::Playlist { tracks, .. }) =
+                    data.caches.context.get(&playlist.id.uri())
+                else {
+                    continue;
+                };
+                for track in tracks.iter().filter(|t| t.added_at >= since) {
+                    if let Some(artist) =
+                        track.artists.iter().find_map(|a| artists.get(&a.id.uri()))
+                    {
+                        feed.push(FeedItem::PlaylistAdd {
+                            artist: (*artist).clone(),
+                            track: Box::new(track.clone()),
+                            playlist: playlist.clone(),
+                            added_at: track.added_at,
+                        });
+                    }
+                }
+            }
+        }
+
+        // an album can be released by (or feature) multiple followed artists
+        let mut keys = std::collections::HashSet::new();
+        feed.retain(|item| keys.insert(item.key()));
+        feed.sort_by_key(|item| std::cmp::Reverse(item.date()));
+
+        store_cache_data(
+            CacheKey::ArtistFeed,
+            &config::get_config().cache_folder,
+            &feed,
+        )
+        .context("store the artist feed into the database")?;
+        state.data.write().user_data.artist_feed = feed;
+        Ok(())
+    }
+
+    /// Get the tracks recently added by other contributors to the shared playlists
+    /// (collaborative playlists and Blends) in the user's library.
+    ///
+    /// The shared playlists' contexts are cached along the way.
+    async fn get_shared_playlist_adds(&self, state: &SharedState) -> Result<()> {
+        let (user_id, playlists) = {
+            let data = state.data.read();
+            let Some(user) = data.user_data.user.as_ref() else {
+                return Ok(());
+            };
+            let playlists = data
+                .user_data
+                .shared_playlist_items()
+                .into_iter()
+                .filter_map(|item| match item {
+                    PlaylistFolderItem::Playlist(p) => Some(p.clone()),
+                    PlaylistFolderItem::Folder(_) => None,
+                })
+                .collect::<Vec<_>>();
+            (user.id.clone(), playlists)
+        };
+        let since = chrono::Utc::now() - chrono::Duration::days(SHARED_PLAYLIST_ADD_MAX_AGE_DAYS);
+
+        let mut adds = vec![];
+        for playlist in playlists {
+            self.rate_limiter
+                .acquire(3, rate_limit::Priority::Background)
+                .await;
+            let (full_playlist, items) = self.playlist_with_items(playlist.id.as_ref()).await?;
+
+            let mut tracks = vec![];
+            for item in items {
+                let added_by = item.added_by.as_ref().filter(|u| u.id != user_id).map(|u| {
+                    u.display_name
+                        .clone()
+                        .unwrap_or_else(|| u.id.id().to_string())
+                });
+                let is_recent = item.added_at.is_some_and(|t| t >= since);
+                let Some(track) = Track::try_from_playlist_item(item) else {
+                    continue;
+                };
+                if let Some(added_by) = added_by.filter(|_| is_recent) {
+                    adds.push(SharedPlaylistAdd {
+                        track: track.clone(),
+                        added_by,
+                        playlist: playlist.clone(),
+                    });
+                }
+                tracks.push(track);
+            }
+
+            store_context(
+                state,
+                &playlist.id.uri(),
+                Context::Playlist {
+                    playlist: full_playlist.into(),
+                    tracks,
+                },
+            );
+        }
+
+        adds.sort_by_key(|add| std::cmp::Reverse(add.track.added_at));
+        state.data.write().user_data.shared_playlist_adds = adds;
+        Ok(())
+    }
+
     /// Add a Spotify item to current user's library.
     async fn add_to_library(&self, state: &SharedState, item: Item) -> Result<()> {
         // Before adding new item, checks if that item already exists in the library to avoid adding a duplicated item.
@@ -1194,13 +2331,14 @@ impl AppClient {
                 if !contains[0] {
                     self.current_user_saved_tracks_add([track.id.as_ref()])
                         .await?;
+                    hooks::trigger(
+                        hooks::HookEvent::Liked,
+                        &hooks::HookContext::from_track(&track),
+                    );
                     // update the in-memory `user_data`
-                    state
-                        .data
-                        .write()
-                        .user_data
-                        .saved_tracks
-                        .insert(track.id.uri(), track);
+                    let mut data = state.data.write();
+                    data.caches.saved_tracks_status.remove(&track.id.uri());
+                    data.user_data.saved_tracks.insert(track.id.uri(), track);
                 }
             }
             Item::Album(album) => {
@@ -1260,17 +2398,137 @@ impl AppClient {
                     state.data.write().user_data.saved_shows.insert(0, show);
                 }
             }
+            Item::Episode(episode) => {
+                let url = format!("{}/me/episodes", self.api_endpoint);
+                let payload = Query::from([("ids", episode.id.id())]);
+                let contains = self
+                    .http_get::<Vec<bool>>(&format!("{url}/contains"), &payload)
+                    .await?;
+                if !contains.first().copied().unwrap_or_default() {
+                    self.http_send(reqwest::Method::PUT, &url, &payload).await?;
+                    // update the in-memory `user_data`
+                    state
+                        .data
+                        .write()
+                        .user_data
+                        .saved_episodes
+                        .insert(0, episode);
+                }
+            }
         }
         Ok(())
     }
 
+    /// Like (or unlike) tracks in chunks, showing the progress in a toast.
+    ///
+    /// Only the tracks whose liked status changes are remembered for undoing the change,
+    /// so undoing it doesn't unlike the tracks that were liked before (and vice versa).
+    async fn set_tracks_liked(
+        &self,
+        state: &SharedState,
+        mut tracks: Vec<Track>,
+        liked: bool,
+        undoable: bool,
+    ) -> Result<()> {
+        let mut uris = std::collections::HashSet::new();
+        tracks.retain(|t| uris.insert(t.id.uri()));
+
+        let total = tracks.len();
+        let mut changed = vec![];
+        let mut result = Ok(());
+        for (i, chunk) in tracks.chunks(LIKED_TRACKS_CHUNK_SIZE).enumerate() {
+            match self.set_track_chunk_liked(state, chunk, liked).await {
+                Ok(tracks) => changed.extend(tracks),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+            let progress = (i * LIKED_TRACKS_CHUNK_SIZE + chunk.len()).min(total);
+            let verb = if liked { "Liking" } else { "Unliking" };
+            state
+                .ui
+                .lock()
+                .show_toast(format!("{verb} tracks: {progress}/{total}"));
+        }
+
+        let message = match (liked, &result) {
+            (true, Ok(())) => format!("Liked {} tracks", changed.len()),
+            (false, Ok(())) => format!("Unliked {} tracks", changed.len()),
+            (true, Err(_)) => format!("Failed to like all tracks, liked {}", changed.len()),
+            (false, Err(_)) => format!("Failed to unlike all tracks, unliked {}", changed.len()),
+        };
+        let mut ui = state.ui.lock();
+        ui.show_toast(message);
+        // a partially applied change can still be undone
+        if undoable && !changed.is_empty() {
+            ui.last_liked_tracks_change = Some(crate::state::LikedTracksChange {
+                tracks: changed,
+                liked,
+            });
+        }
+        result
+    }
+
+    /// Like (or unlike) a chunk of tracks, returning the tracks whose liked status changed.
+    ///
+    /// Liking a chunk triggers the `liked` hook once for all of its newly liked tracks.
+    async fn set_track_chunk_liked(
+        &self,
+        state: &SharedState,
+        chunk: &[Track],
+        liked: bool,
+    ) -> Result<Vec<Track>> {
+        self.rate_limiter
+            .acquire(2, rate_limit::Priority::Interactive)
+            .await;
+        let contains = self
+            .current_user_saved_tracks_contains(chunk.iter().map(|t| t.id.as_ref()))
+            .await?;
+        let tracks = chunk
+            .iter()
+            .zip(contains)
+            .filter(|(_, contains)| *contains != liked)
+            .map(|(t, _)| t.clone())
+            .collect::<Vec<_>>();
+        if tracks.is_empty() {
+            return Ok(tracks);
+        }
+
+        let ids = tracks.iter().map(|t| t.id.as_ref());
+        if liked {
+            self.current_user_saved_tracks_add(ids).await?;
+            hooks::trigger(
+                hooks::HookEvent::Liked,
+                &hooks::HookContext::from_tracks(&tracks),
+            );
+        } else {
+            self.current_user_saved_tracks_delete(ids).await?;
+        }
+
+        // update the in-memory `user_data`
+        let mut data = state.data.write();
+        for track in &tracks {
+            let uri = track.id.uri();
+            data.caches.saved_tracks_status.remove(&uri);
+            if liked {
+                data.user_data.saved_tracks.insert(uri, track.clone());
+            } else {
+                data.user_data.saved_tracks.remove(&uri);
+            }
+        }
+        Ok(tracks)
+    }
+
     // Delete a Spotify item from user's library
     async fn delete_from_library(&self, state: &SharedState, id: ItemId) -> Result<()> {
         match id {
             ItemId::Track(id) => {
                 let uri = id.uri();
                 self.current_user_saved_tracks_delete([id]).await?;
-                state.data.write().user_data.saved_tracks.remove(&uri);
+                let mut data = state.data.write();
+                data.caches.saved_tracks_status.remove(&uri);
+                data.user_data.saved_tracks.remove(&uri);
             }
             ItemId::Album(id) => {
                 state
@@ -1312,6 +2570,20 @@ impl AppClient {
                 self.remove_users_saved_shows([id], Some(rspotify::model::Market::FromToken))
                     .await?;
             }
+            ItemId::Episode(id) => {
+                state
+                    .data
+                    .write()
+                    .user_data
+                    .saved_episodes
+                    .retain(|e| e.id != id);
+                self.http_send(
+                    reqwest::Method::DELETE,
+                    &format!("{}/me/episodes", self.api_endpoint),
+                    &Query::from([("ids", id.id())]),
+                )
+                .await?;
+            }
         }
         Ok(())
     }
@@ -1326,24 +2598,27 @@ impl AppClient {
         .context("convert FullTrack into Track")
     }
 
-    /// Get a playlist context data
-    pub async fn playlist_context(&self, playlist_id: PlaylistId<'_>) -> Result<Context> {
+    /// Get a playlist context data.
+    ///
+    /// If the application's state is specified, the context's sections are stored as they arrive.
+    pub async fn playlist_context(
+        &self,
+        playlist_id: PlaylistId<'_>,
+        state: Option<&SharedState>,
+    ) -> Result<Context> {
         let playlist_uri = playlist_id.uri();
         tracing::info!("Get playlist context: {}", playlist_uri);
 
-        // TODO: this should use `rspotify::playlist` API instead of `internal_call`
-        // See: https://github.com/ramsayleung/rspotify/issues/459
-        // let playlist = self
-        //     .playlist(playlist_id, None, Some(Market::FromToken))
-        //     .await?;
-        let playlist = self
-            .http_get::<rspotify::model::FullPlaylist>(
-                &format!("{SPOTIFY_API_ENDPOINT}/playlists/{}", playlist_id.id()),
-                &market_query(),
-            )
-            .await?;
+        let playlist = self.full_playlist(playlist_id).await?;
+        update_partial_context(state, &playlist_uri, |c| {
+            c.description = Some(format!(
+                "{} | {}",
+                playlist.name,
+                playlist.owner.display_name.as_deref().unwrap_or_default()
+            ));
+        });
 
-        // get the playlist's tracks
+        // get the playlist's items
         let first_page = playlist.tracks.clone();
         let tracks = self
             .all_paging_items(first_page, &market_query())
@@ -1358,8 +2633,47 @@ impl AppClient {
         })
     }
 
-    /// Get an album context data
-    pub async fn album_context(&self, album_id: AlbumId<'_>) -> Result<Context> {
+    /// Get a playlist without its items after the first page
+    async fn full_playlist(
+        &self,
+        playlist_id: PlaylistId<'_>,
+    ) -> Result<rspotify::model::FullPlaylist> {
+        // TODO: this should use `rspotify::playlist` API instead of `internal_call`
+        // See: https://github.com/ramsayleung/rspotify/issues/459
+        // let playlist = self
+        //     .playlist(playlist_id, None, Some(Market::FromToken))
+        //     .await?;
+        self.http_get::<rspotify::model::FullPlaylist>(
+            &format!("{}/playlists/{}", self.api_endpoint, playlist_id.id()),
+            &market_query(),
+        )
+        .await
+    }
+
+    /// Get a playlist along with all of its items
+    async fn playlist_with_items(
+        &self,
+        playlist_id: PlaylistId<'_>,
+    ) -> Result<(
+        rspotify::model::FullPlaylist,
+        Vec<rspotify::model::PlaylistItem>,
+    )> {
+        let playlist = self.full_playlist(playlist_id).await?;
+
+        // get the playlist's items
+        let first_page = playlist.tracks.clone();
+        let items = self.all_paging_items(first_page, &market_query()).await?;
+        Ok((playlist, items))
+    }
+
+    /// Get an album context data.
+    ///
+    /// If the application's state is specified, the context's sections are stored as they arrive.
+    pub async fn album_context(
+        &self,
+        album_id: AlbumId<'_>,
+        state: Option<&SharedState>,
+    ) -> Result<Context> {
         let album_uri = album_id.uri();
         tracing::info!("Get album context: {}", album_uri);
 
@@ -1370,6 +2684,9 @@ impl AppClient {
 
         // converts `rspotify::model::FullAlbum` into `state::Album`
         let album: Album = album.into();
+        update_partial_context(state, &album_uri, |c| {
+            c.description = Some(format!("{} | {}", album.name, album.release_date));
+        });
 
         // get the album's tracks
         let tracks = self
@@ -1390,42 +2707,69 @@ impl AppClient {
         Ok(Context::Album { album, tracks })
     }
 
-    /// Get an artist context data
-    pub async fn artist_context(&self, artist_id: ArtistId<'_>) -> Result<Context> {
+    /// Get an artist context data.
+    ///
+    /// If the application's state is specified, the context's sections are stored as they arrive.
+    pub async fn artist_context(
+        &self,
+        artist_id: ArtistId<'_>,
+        state: Option<&SharedState>,
+    ) -> Result<Context> {
         let artist_uri = artist_id.uri();
         tracing::info!("Get artist context: {}", artist_uri);
 
-        // get the artist's information, including top tracks, related artists, and albums
-
-        let artist = self
-            .artist(artist_id.as_ref())
-            .await
-            .context("get artist")?
-            .into();
-
-        let top_tracks = self
-            .artist_top_tracks(artist_id.as_ref(), Some(rspotify::model::Market::FromToken))
-            .await
-            .context("get artist's top tracks")?;
-        let top_tracks = top_tracks
-            .into_iter()
-            .filter_map(Track::try_from_full_track)
-            .collect::<Vec<_>>();
-
-        #[allow(deprecated)]
-        let related_artists = self
-            .artist_related_artists(artist_id.as_ref())
-            .await
-            .context("get related artists")?;
-        let related_artists = related_artists
-            .into_iter()
-            .map(std::convert::Into::into)
-            .collect::<Vec<_>>();
-
-        let albums = self
-            .artist_albums(artist_id.as_ref())
-            .await
-            .context("get artist's albums")?;
+        // get the artist's information, including top tracks, related artists, and albums,
+        // concurrently
+        let (artist, top_tracks, related_artists, albums) = tokio::try_join!(
+            async {
+                let artist: Artist = self
+                    .artist(artist_id.as_ref())
+                    .await
+                    .context("get artist")?
+                    .into();
+                update_partial_context(state, &artist_uri, |c| {
+                    c.description = Some(artist.name.clone());
+                });
+                anyhow::Ok(artist)
+            },
+            async {
+                let top_tracks = self
+                    .artist_top_tracks(artist_id.as_ref(), Some(rspotify::model::Market::FromToken))
+                    .await
+                    .context("get artist's top tracks")?
+                    .into_iter()
+                    .filter_map(Track::try_from_full_track)
+                    .collect::<Vec<_>>();
+                update_partial_context(state, &artist_uri, |c| {
+                    c.tracks = Some(top_tracks.clone());
+                });
+                anyhow::Ok(top_tracks)
+            },
+            async {
+                #[allow(deprecated)]
+                let related_artists = self
+                    .artist_related_artists(artist_id.as_ref())
+                    .await
+                    .context("get related artists")?
+                    .into_iter()
+                    .map(std::convert::Into::into)
+                    .collect::<Vec<Artist>>();
+                update_partial_context(state, &artist_uri, |c| {
+                    c.related_artists = Some(related_artists.clone());
+                });
+                anyhow::Ok(related_artists)
+            },
+            async {
+                let albums = self
+                    .artist_albums(artist_id.as_ref())
+                    .await
+                    .context("get artist's albums")?;
+                update_partial_context(state, &artist_uri, |c| {
+                    c.albums = Some(albums.clone());
+                });
+                anyhow::Ok(albums)
+            },
+        )?;
 
         Ok(Context::Artist {
             artist,
@@ -1435,8 +2779,14 @@ impl AppClient {
         })
     }
 
-    /// Get a show context data
-    pub async fn show_context(&self, show_id: ShowId<'_>) -> Result<Context> {
+    /// Get a show context data.
+    ///
+    /// If the application's state is specified, the context's sections are stored as they arrive.
+    pub async fn show_context(
+        &self,
+        show_id: ShowId<'_>,
+        state: Option<&SharedState>,
+    ) -> Result<Context> {
         let show_uri = show_id.uri();
         tracing::info!("Get show context: {}", show_uri);
 
@@ -1457,6 +2807,9 @@ impl AppClient {
 
         // converts `rspotify::model::FullShow` into `state::Show`
         let show: Show = show.into();
+        update_partial_context(state, &show_uri, |c| {
+            c.description = Some(show.name.clone());
+        });
 
         // get the show's episodes
         let episodes = self
@@ -1512,6 +2865,35 @@ impl AppClient {
         Ok(serde_json::from_str(&text)?)
     }
 
+    /// Make a HTTP request without a response body to the Spotify server
+    async fn http_send(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        payload: &Query<'_>,
+    ) -> Result<()> {
+        let access_token = self.access_token().await?;
+        tracing::debug!("{access_token} {method} {url}");
+
+        let response = self
+            .http
+            .request(method, url)
+            .query(payload)
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!("Bearer {access_token}"),
+            )
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await?;
+            anyhow::bail!("failed to send a Spotify API request {url}: {text}");
+        }
+        Ok(())
+    }
+
     /// Get all paging items starting from a pagination object of the first page
     async fn all_paging_items<T>(
         &self,
@@ -1571,8 +2953,15 @@ impl AppClient {
     ) -> Result<()> {
         let new_playback = {
             // update the playback state
-            let playback = self.current_playback2().await?;
+            let playback = match self.current_playback2().await {
+                Ok(playback) => playback,
+                Err(err) => {
+                    state.player.write().connection_status = ConnectionStatus::Offline;
+                    return Err(err);
+                }
+            };
             let mut player = state.player.write();
+            player.connection_status = ConnectionStatus::Online;
 
             let prev_item = player.currently_playing();
 
@@ -1629,7 +3018,200 @@ impl AppClient {
         Ok(())
     }
 
+    /// Add an item to the user's blocklist and skip the current track if it's blocked by the new item
+    async fn add_to_blocklist(&self, state: &SharedState, item: BlockedItem) -> Result<()> {
+        tracing::info!("Adding {item} to the blocklist");
+        let is_current_track_blocked = {
+            let current_track = match state.player.read().currently_playing() {
+                Some(rspotify::model::PlayableItem::Track(track)) => Some(track.clone()),
+                _ => None,
+            };
+            let mut data = state.data.write();
+            data.user_data
+                .add_to_blocklist(item, &config::get_config().cache_folder);
+            current_track.is_some_and(|track| data.user_data.is_blocked(&track))
+        };
+
+        if is_current_track_blocked {
+            let playback = state.player.read().buffered_playback.clone();
+            let playback = self
+                .handle_player_request(PlayerRequest::NextTrack, playback)
+                .await?;
+            state.player.write().buffered_playback = playback;
+            self.update_playback(state);
+        }
+        Ok(())
+    }
+
+    /// Skip a newly started track if it's in the user's blocklist, or skip the track
+    /// or its beginning if it matches any of the user-defined skip rules
+    async fn apply_skip_rules(
+        &self,
+        state: &SharedState,
+        track: &rspotify::model::FullTrack,
+    ) -> Result<()> {
+        let configs = config::get_config();
+        let request = if state.data.read().user_data.is_blocked(track) {
+            tracing::info!("Track \"{}\" is blocked, skipping it", track.name);
+            PlayerRequest::NextTrack
+        } else {
+            let Some(rule) = configs
+                .app_config
+                .skip_rules
+                .iter()
+                .find(|r| r.matches(track))
+            else {
+                return Ok(());
+            };
+            let request = match rule.skip_first_secs {
+                None => PlayerRequest::NextTrack,
+                Some(secs) => {
+                    let position = chrono::Duration::seconds(secs as i64);
+                    // only skip the track's beginning if it hasn't been passed yet
+                    if state.player.read().playback_progress() >= Some(position) {
+                        return Ok(());
+                    }
+                    PlayerRequest::SeekTrack(position)
+                }
+            };
+            tracing::info!(
+                "Track \"{}\" matches a skip rule {rule:?}, handling {request:?}",
+                track.name
+            );
+            request
+        };
+
+        let playback = state.player.read().buffered_playback.clone();
+        let playback = self.handle_player_request(request, playback).await?;
+        state.player.write().buffered_playback = playback;
+        self.update_playback(state);
+        Ok(())
+    }
+
     // Handle new track event
+    /// Apply the visualization remembered for the track's artists or genres, if any,
+    /// otherwise restore the visualization picked by the user
+    #[cfg(feature = "fx")]
+    fn apply_visualization_preference(
+        state: &SharedState,
+        track: &rspotify::model::FullTrack,
+    ) -> Result<()> {
+        use crate::ui::visualizations::{
+            visualization_preference_targets, VisualizationPreference,
+            VisualizationPreferenceTarget,
+        };
+
+        let genres = track
+            .artists
+            .first()
+            .and_then(|a| state.data.read().caches.genres.get(&a.name).cloned())
+            .unwrap_or_default();
+        let keys = visualization_preference_targets(track, &genres)
+            .iter()
+            .map(VisualizationPreferenceTarget::key)
+            .collect::<Vec<_>>();
+        let preference = crate::db::load_visualization_preference::<VisualizationPreference>(
+            &config::get_config().cache_folder,
+            &keys,
+        )?;
+
+        let mut ui = state.ui.lock();
+        match preference {
+            Some(preference) => {
+                if ui.visualization_fallback.is_none() {
+                    ui.visualization_fallback = Some(ui.visualization);
+                }
+                ui.visualization = preference;
+            }
+            None => {
+                if let Some(fallback) = ui.visualization_fallback.take() {
+                    ui.visualization = fallback;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Play the 30-second preview of a track through the local audio output, or stop it
+    /// if it's playing. Returns the message shown to the user.
+    #[cfg(feature = "preview")]
+    async fn peek_track(&self, track_id: TrackId<'static>) -> Result<String> {
+        if crate::preview::playing().as_deref() == Some(track_id.uri().as_str()) {
+            crate::preview::stop();
+            return Ok("Stopped the track's preview".to_string());
+        }
+
+        let track = self
+            .spotify
+            .track(track_id, Some(rspotify::model::Market::FromToken))
+            .await?;
+        let Some(url) = track.preview_url else {
+            return Ok(format!("No preview available for {}", track.name));
+        };
+        let data = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("get the preview from url {url}"))?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let uri = track.id.map(|id| id.uri()).unwrap_or_default();
+        crate::preview::play(uri, data.to_vec()).await?;
+        Ok(format!("Peeking at {}", track.name))
+    }
+
+    /// Pick the visualization mode of a track using the `visualization_rules` config option,
+    /// if the `Smart` visualization mode is used
+    #[cfg(feature = "fx")]
+    async fn update_smart_visualization_mode(
+        &self,
+        state: &SharedState,
+        track: &rspotify::model::FullTrack,
+    ) {
+        use crate::ui::visualizations::VisualizationMode;
+
+        if state.ui.lock().visualization.mode != VisualizationMode::Smart {
+            return;
+        }
+        let rules = &config::get_config().app_config.visualization_rules;
+
+        let features = match &track.id {
+            Some(id)
+                if rules
+                    .iter()
+                    .any(config::VisualizationRule::uses_audio_features) =>
+            {
+                #[allow(deprecated)]
+                let features = self.track_features(id.clone()).await;
+                match features {
+                    Ok(features) => Some(features),
+                    Err(err) => {
+                        tracing::warn!("Failed to get audio features of the track: {err:#}");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        let genres = track
+            .artists
+            .first()
+            .and_then(|a| state.data.read().caches.genres.get(&a.name).cloned())
+            .unwrap_or_default();
+
+        let mode = rules
+            .iter()
+            .find(|rule| rule.matches(features.as_ref(), &genres))
+            .map_or(VisualizationMode::Banner, |rule| rule.mode);
+        tracing::info!(
+            "Picked visualization mode {mode:?} for track {}",
+            track.name
+        );
+        state.ui.lock().smart_visualization_mode = mode;
+    }
+
     async fn handle_new_playback_event(&self, state: &SharedState) -> Result<()> {
         let configs = config::get_config();
 
@@ -1641,6 +3223,20 @@ impl AppClient {
             track_or_episode.clone()
         };
 
+        hooks::trigger(
+            hooks::HookEvent::TrackChanged,
+            &hooks::HookContext::from_playable_item(&curr_item),
+        );
+
+        if let rspotify::model::PlayableItem::Track(track) = &curr_item {
+            crate::db::record_track_start(
+                &configs.cache_folder,
+                track,
+                chrono::Utc::now().timestamp(),
+            );
+            self.apply_skip_rules(state, track).await?;
+        }
+
         // retrieve current artist for genres if not in cache
         let curr_artist = match &curr_item {
             rspotify::model::PlayableItem::Track(full_track) => {
@@ -1674,17 +3270,30 @@ impl AppClient {
             }
         }
 
-        let url = match curr_item {
-            rspotify::model::PlayableItem::Track(ref track) => {
-                crate::utils::get_track_album_image_url(track)
-                    .ok_or(anyhow::anyhow!("missing image"))?
-            }
-            rspotify::model::PlayableItem::Episode(ref episode) => {
-                crate::utils::get_episode_show_image_url(episode)
-                    .ok_or(anyhow::anyhow!("missing image"))?
+        #[cfg(feature = "fx")]
+        if !config::get_config().app_config.accessibility.reduce_motion {
+            // play the visualization's drop animation and crossfade from the previous track's color
+            let mut ui = state.ui.lock();
+            let now = ui.clock.now();
+            ui.visualization_drop_start = Some(now);
+            ui.effects_state.start_track_change(now);
+            ui.visualization_crossfade = ui
+                .visualization_color
+                .map(|color| crate::ui::visualizations::ColorCrossfade::new(color, now));
+        }
+
+        #[cfg(feature = "fx")]
+        if let rspotify::model::PlayableItem::Track(track) = &curr_item {
+            if let Err(err) = Self::apply_visualization_preference(state, track) {
+                tracing::error!("Failed to apply the visualization preference: {err:#}");
             }
-            rspotify::model::PlayableItem::Unknown(_) => return Ok(()),
-        };
+            self.update_smart_visualization_mode(state, track).await;
+        }
+
+        if matches!(curr_item, rspotify::model::PlayableItem::Unknown(_)) {
+            return Ok(());
+        }
+        let url = cover_image_url(&curr_item).ok_or(anyhow::anyhow!("missing image"))?;
 
         let filename = (match curr_item {
             rspotify::model::PlayableItem::Track(ref track) => {
@@ -1711,32 +3320,22 @@ impl AppClient {
         let path = configs.cache_folder.join("image").join(filename);
 
         if configs.app_config.enable_cover_image_cache {
-            self.retrieve_image(url, &path, true).await?;
+            self.retrieve_image(&url, &path, true).await?;
         }
 
+        // load the cover image in the background, so that a slow image never delays the playback's update
         #[cfg(feature = "image")]
-        if !state.data.read().caches.images.contains_key(url) {
-            let bytes = self.retrieve_image(url, &path, false).await?;
-
-            #[cfg(not(feature = "pixelate"))]
-            let image =
-                image::load_from_memory(&bytes).context("Failed to load image from memory")?;
-            #[cfg(feature = "pixelate")]
-            let mut image =
-                image::load_from_memory(&bytes).context("Failed to load image from memory")?;
-
-            #[cfg(feature = "pixelate")]
-            {
-                Self::pixelate_image(&mut image);
+        tokio::task::spawn({
+            let client = self.clone();
+            let state = state.clone();
+            let url = url.clone();
+            let path = path.clone();
+            async move {
+                if let Err(err) = client.load_cover_image(&state, url, &path).await {
+                    tracing::error!("Failed to load the cover image: {err:#}");
+                }
             }
-
-            state
-                .data
-                .write()
-                .caches
-                .images
-                .insert(url.to_owned(), image, *TTL_CACHE_DURATION);
-        }
+        });
 
         // notify user about the playback's change if any
         #[cfg(all(feature = "notify", feature = "streaming"))]
@@ -1763,7 +3362,7 @@ impl AppClient {
         public: bool,
         collab: bool,
         desc: &str,
-    ) -> Result<()> {
+    ) -> Result<Playlist> {
         let playlist: Playlist = self
             .user_playlist_create(
                 user_id,
@@ -1784,7 +3383,50 @@ impl AppClient {
             .write()
             .user_data
             .playlists
-            .insert(0, PlaylistFolderItem::Playlist(playlist));
+            .insert(0, PlaylistFolderItem::Playlist(playlist.clone()));
+        Ok(playlist)
+    }
+
+    /// Change the name and/or description of a playlist
+    async fn change_playlist_details(
+        &self,
+        state: &SharedState,
+        playlist_id: PlaylistId<'static>,
+        name: Option<String>,
+        desc: Option<String>,
+    ) -> Result<()> {
+        self.playlist_change_detail(
+            playlist_id.as_ref(),
+            name.as_deref(),
+            None,
+            desc.as_deref(),
+            None,
+        )
+        .await?;
+        tracing::info!("details of playlist (id={playlist_id}) were successfully changed");
+
+        // update the playlist's details stored in the application's data
+        let update = |playlist: &mut Playlist| {
+            if let Some(name) = &name {
+                playlist.name.clone_from(name);
+            }
+            if let Some(desc) = &desc {
+                playlist.desc.clone_from(desc);
+            }
+        };
+        let mut data = state.data.write();
+        for item in &mut data.user_data.playlists {
+            if let PlaylistFolderItem::Playlist(p) = item {
+                if p.id == playlist_id {
+                    update(p);
+                }
+            }
+        }
+        if let Some(Context::Playlist { playlist, .. }) =
+            data.caches.context.get_mut(&playlist_id.uri())
+        {
+            update(playlist);
+        }
         Ok(())
     }
 
@@ -1900,6 +3542,116 @@ impl AppClient {
         Ok(bytes.to_vec())
     }
 
+    /// Download an image from a given url without caching it
+    #[cfg(feature = "image")]
+    pub async fn download_image(&self, url: &str) -> Result<image::DynamicImage> {
+        let bytes = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("get image from url {url}"))?
+            .bytes()
+            .await?;
+        image::load_from_memory(&bytes).context("Failed to load image from memory")
+    }
+
+    /// Load a cover image and, if enabled, extract its dominant color,
+    /// sending the result to [`AppClient::handle_loaded_cover_images`].
+    ///
+    /// Decoding the image and extracting its color are CPU-bound, so they're done on a blocking thread,
+    /// whose result is awaited without blocking the async runtime.
+    #[cfg(feature = "image")]
+    async fn load_cover_image(
+        &self,
+        state: &SharedState,
+        url: String,
+        path: &std::path::Path,
+    ) -> Result<()> {
+        #[cfg(feature = "fx")]
+        let use_album_colors = config::get_config().app_config.use_album_colors;
+        #[cfg(not(feature = "fx"))]
+        let use_album_colors = false;
+
+        let cached = state.data.read().caches.images.contains_key(&url);
+        if cached && !use_album_colors {
+            return Ok(());
+        }
+        let bytes = if cached {
+            None
+        } else {
+            Some(self.retrieve_image(&url, path, false).await?)
+        };
+        let cached_image = state.data.read().caches.images.get(&url).cloned();
+
+        let (image, color) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let image = match (cached_image, bytes) {
+                (Some(image), _) => image,
+                (None, Some(bytes)) => {
+                    #[cfg(not(feature = "pixelate"))]
+                    let image = image::load_from_memory(&bytes)
+                        .context("Failed to load image from memory")?;
+                    #[cfg(feature = "pixelate")]
+                    let mut image = image::load_from_memory(&bytes)
+                        .context("Failed to load image from memory")?;
+
+                    #[cfg(feature = "pixelate")]
+                    {
+                        Self::pixelate_image(&mut image);
+                    }
+                    image
+                }
+                (None, None) => anyhow::bail!("the cover image was removed from the cache"),
+            };
+            let color = use_album_colors.then(|| crate::utils::extract_dominant_color(&image));
+            Ok((image, color))
+        })
+        .await??;
+
+        self.cover_images
+            .0
+            .send(LoadedCoverImage {
+                url,
+                image: (!cached).then_some(image),
+                color,
+            })
+            .context("send the loaded cover image")?;
+        Ok(())
+    }
+
+    /// Apply the cover images loaded in the background: cache them and use the dominant color
+    /// of the playing item's cover as the album color of the visualization.
+    ///
+    /// As the images may finish loading out of order, the color of a cover that no longer
+    /// belongs to the playing item is ignored.
+    #[cfg(feature = "image")]
+    pub async fn handle_loaded_cover_images(&self, state: &SharedState) {
+        while let Ok(loaded) = self.cover_images.1.recv_async().await {
+            if let Some(image) = loaded.image {
+                state.data.write().caches.images.insert(
+                    loaded.url.clone(),
+                    image,
+                    *TTL_CACHE_DURATION,
+                );
+            }
+
+            #[cfg(feature = "fx")]
+            if loaded.color.is_some() {
+                let is_current = state
+                    .player
+                    .read()
+                    .currently_playing()
+                    .and_then(cover_image_url)
+                    .is_some_and(|url| url == loaded.url);
+                if is_current {
+                    state.ui.lock().current_album_color = loaded.color;
+                }
+            }
+            #[cfg(not(feature = "fx"))]
+            let _ = loaded.color;
+        }
+    }
+
     #[cfg(feature = "pixelate")]
     fn pixelate_image(image: &mut image::DynamicImage) {
         let pixels = config::get_config().app_config.cover_img_pixels;
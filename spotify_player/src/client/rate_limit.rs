@@ -0,0 +1,86 @@
+//! A token-bucket rate limiter for the client's Spotify API calls.
+//!
+//! Spotify responds with `429 Too Many Requests` when an application makes too many calls
+//! in a short period of time, stalling all subsequent calls. To avoid that, every request
+//! takes tokens (its weight) from a shared bucket, waiting for the bucket to refill if needed.
+//! Background requests leave a reserve of tokens in the bucket, so they never delay
+//! the user's interactive actions.
+
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// The maximum number of tokens in the bucket, i.e. the maximum burst of API calls
+const BUCKET_CAPACITY: f64 = 20.0;
+/// The number of tokens added to the bucket per second
+const REFILL_RATE: f64 = 8.0;
+/// The number of tokens that background requests leave for interactive requests
+const BACKGROUND_RESERVE: f64 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The priority of a request
+pub enum Priority {
+    /// A request triggered by the user's action
+    Interactive,
+    /// A request made in the background, e.g. prefetching data
+    Background,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+#[derive(Debug)]
+/// A rate limiter shared by the client's API calls
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                tokens: BUCKET_CAPACITY,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Wait until `weight` tokens are available and take them from the bucket
+    pub async fn acquire(&self, weight: u32, priority: Priority) {
+        if weight == 0 {
+            return;
+        }
+
+        let reserve = match priority {
+            Priority::Interactive => 0.0,
+            Priority::Background => BACKGROUND_RESERVE,
+        };
+        // a request heavier than the bucket would wait forever
+        let weight = f64::from(weight).min(BUCKET_CAPACITY - reserve);
+
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock();
+                let now = Instant::now();
+                bucket.tokens = (bucket.tokens
+                    + (now - bucket.last_refill).as_secs_f64() * REFILL_RATE)
+                    .min(BUCKET_CAPACITY);
+                bucket.last_refill = now;
+
+                if bucket.tokens - weight >= reserve {
+                    bucket.tokens -= weight;
+                    return;
+                }
+                (weight + reserve - bucket.tokens) / REFILL_RATE
+            };
+
+            tracing::debug!("Rate limited, waiting {wait:.2}s for {weight} tokens ({priority:?})");
+            tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+        }
+    }
+}
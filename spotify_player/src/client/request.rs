@@ -1,7 +1,16 @@
+use std::time::Duration;
+
+use super::rate_limit::Priority;
 use crate::state::{
-    AlbumId, Category, ContextId, Item, ItemId, PlayableId, Playback, PlaylistId, TrackId,
+    AlbumId, BlockedItem, Category, ContextId, Item, ItemId, LastPlayback, PlayableId, Playback,
+    Playlist, PlaylistId, PlaylistMergeOrder, SearchType, Track, TrackId,
 };
 
+/// The maximum duration of handling a request making a few API calls
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// The maximum duration of handling a paginated request or a request making an API call per item
+const LONG_REQUEST_TIMEOUT: Duration = Duration::from_secs(180);
+
 #[derive(Clone, Debug)]
 /// A request that modifies the player's playback
 pub enum PlayerRequest {
@@ -11,6 +20,8 @@ pub enum PlayerRequest {
     Pause,
     ResumePause,
     SeekTrack(chrono::Duration),
+    /// Go back from a previewed track to the previous track (in its context) at its position
+    RevertTrackPreview(LastPlayback),
     Repeat,
     Shuffle,
     Volume(u8),
@@ -29,20 +40,61 @@ pub enum ClientRequest {
     GetUserPlaylists,
     GetUserSavedAlbums,
     GetUserSavedShows,
+    GetUserSavedEpisodes,
     GetUserFollowedArtists,
     GetUserSavedTracks,
     GetUserTopTracks,
     GetUserRecentlyPlayedTracks,
     GetContext(ContextId),
+    /// Check the saved (liked) status of tracks in the background
+    CheckSavedTracks(Vec<TrackId<'static>>),
+    GetSmartPlaylistTracks(crate::config::SmartPlaylist),
+    /// Get the genres of the saved tracks' artists whose genres are not cached yet
+    GetSavedTrackGenres,
+    /// Get the full metadata of a track if it's not cached yet
+    GetTrackDetails(TrackId<'static>),
+    /// Get the feed of the user's followed artists
+    GetArtistFeed,
+    /// Get the tracks recently added by other contributors to the shared playlists
+    GetSharedPlaylistAdds,
     GetCurrentPlayback,
     GetRadioTracks {
         seed_uri: String,
         seed_name: String,
     },
     Search(String),
+    /// Replace the search results of a type with the page of results at the given offset
+    GetSearchResultPage {
+        query: String,
+        typ: SearchType,
+        offset: usize,
+    },
+    /// Load all search results of a type, which are at most `MAX_LOAD_ALL_SEARCH_RESULTS` results
+    LoadAllSearchResults {
+        query: String,
+        typ: SearchType,
+    },
     AddPlayableToQueue(PlayableId<'static>),
     AddAlbumToQueue(AlbumId<'static>),
     AddPlayableToPlaylist(PlaylistId<'static>, PlayableId<'static>),
+    /// Sync a playlist with another playlist by adding the tracks only in the other playlist
+    /// and removing the tracks only in the synced playlist
+    SyncPlaylist {
+        playlist_id: PlaylistId<'static>,
+        added: Vec<TrackId<'static>>,
+        removed: Vec<TrackId<'static>>,
+    },
+    /// Create a new playlist with the given tracks
+    CreatePlaylistFromTracks {
+        playlist_name: String,
+        track_ids: Vec<TrackId<'static>>,
+    },
+    /// Merge playlists into a new playlist
+    MergePlaylists {
+        playlists: Vec<Playlist>,
+        order: PlaylistMergeOrder,
+        deduplicate: bool,
+    },
     DeleteTrackFromPlaylist(PlaylistId<'static>, TrackId<'static>),
     ReorderPlaylistItems {
         playlist_id: PlaylistId<'static>,
@@ -51,19 +103,165 @@ pub enum ClientRequest {
         range_length: Option<usize>,
         snapshot_id: Option<String>,
     },
+    /// Change a playlist's tracks into the given tracks, which are the playlist's tracks
+    /// with some tracks reordered or removed
+    EditPlaylistTracks {
+        playlist: Playlist,
+        track_ids: Vec<TrackId<'static>>,
+    },
     AddToLibrary(Item),
     DeleteFromLibrary(ItemId),
+    /// Like (or unlike) tracks in chunks, showing the progress.
+    /// The change is remembered to be undone later if `undoable` is true.
+    SetTracksLiked {
+        tracks: Vec<Track>,
+        liked: bool,
+        undoable: bool,
+    },
+    /// Add an item to the blocklist, skipping the current track if it becomes blocked
+    AddToBlocklist(BlockedItem),
+    /// Pick the visualization mode of the current track for the `Smart` visualization mode
+    #[cfg(feature = "fx")]
+    UpdateSmartVisualizationMode,
     Player(PlayerRequest),
     GetCurrentUserQueue,
+    /// Continue the last playback of the application's previous run
+    ContinueLastPlayback,
     GetLyrics {
         track_id: TrackId<'static>,
     },
+    GetAudioAnalysis {
+        track_id: TrackId<'static>,
+    },
+    /// Play a track's preview through the local audio output, or stop it if it's playing
+    #[cfg(feature = "preview")]
+    PeekTrack(TrackId<'static>),
     #[cfg(feature = "streaming")]
     RestartIntegratedClient,
+    /// Open a Spotify link or URI, playing the linked item if `play` is true
+    /// (tracks and episodes are always played)
+    OpenSpotifyLink {
+        link: String,
+        play: bool,
+    },
     CreatePlaylist {
         playlist_name: String,
         public: bool,
         collab: bool,
         desc: String,
     },
+    /// Change a playlist's name and/or description
+    ChangePlaylistDetails {
+        playlist_id: PlaylistId<'static>,
+        name: Option<String>,
+        desc: Option<String>,
+    },
+}
+
+impl ClientRequest {
+    /// The weight of the request in the client's rate limiter, roughly the number of API calls it makes
+    pub fn rate_limit_weight(&self) -> u32 {
+        match self {
+            // rate limited per API call (or per playlist) when checking the tracks,
+            // getting the artists (or their albums) or getting the shared playlists
+            Self::CheckSavedTracks(_)
+            | Self::GetSavedTrackGenres
+            | Self::GetArtistFeed
+            | Self::GetSharedPlaylistAdds
+            | Self::LoadAllSearchResults { .. }
+            | Self::SetTracksLiked { .. } => 0,
+            // paginated requests
+            Self::GetContext(_)
+            | Self::GetUserPlaylists
+            | Self::GetUserSavedAlbums
+            | Self::GetUserSavedShows
+            | Self::GetUserSavedEpisodes
+            | Self::GetUserFollowedArtists
+            | Self::GetUserSavedTracks
+            | Self::GetUserTopTracks
+            | Self::GetSmartPlaylistTracks(_) => 3,
+            Self::Search(_) | Self::GetRadioTracks { .. } => 2,
+            // removing and adding tracks in chunks of 100 tracks and refetching the playlist
+            Self::SyncPlaylist { added, removed, .. } => {
+                u32::try_from(added.len().div_ceil(100) + removed.len().div_ceil(100))
+                    .unwrap_or(u32::MAX)
+                    + 3
+            }
+            Self::CreatePlaylistFromTracks { track_ids, .. } => {
+                u32::try_from(track_ids.len().div_ceil(100)).unwrap_or(u32::MAX) + 4
+            }
+            // removing tracks in chunks of 100 tracks and reordering tracks one by one
+            Self::EditPlaylistTracks { track_ids, .. } => {
+                u32::try_from(track_ids.len()).unwrap_or(u32::MAX) + 1
+            }
+            // fetching the playlists, creating the new playlist and adding tracks to it
+            Self::MergePlaylists { playlists, .. } => {
+                u32::try_from(playlists.len() * 4).unwrap_or(u32::MAX) + 4
+            }
+            _ => 1,
+        }
+    }
+
+    /// The priority of the request in the client's rate limiter
+    pub fn priority(&self) -> Priority {
+        match self {
+            Self::CheckSavedTracks(_)
+            | Self::GetSavedTrackGenres
+            | Self::GetArtistFeed
+            | Self::GetSharedPlaylistAdds
+            | Self::GetLyrics { .. }
+            | Self::GetAudioAnalysis { .. }
+            | Self::GetCurrentUserQueue => Priority::Background,
+            _ => Priority::Interactive,
+        }
+    }
+
+    /// The key of the page data loaded by the request, which is cancelled when the user leaves the page
+    /// (see `InFlightRequests::cancel_page_requests`), or `None` if the request isn't bound to a page
+    pub fn page_key(&self) -> Option<String> {
+        match self {
+            Self::GetContext(id) => Some(id.uri()),
+            Self::Search(query)
+            | Self::GetSearchResultPage { query, .. }
+            | Self::LoadAllSearchResults { query, .. } => Some(format!("search:{query}")),
+            _ => None,
+        }
+    }
+
+    /// The maximum duration of handling the request, after which the request is cancelled,
+    /// or `None` if the request modifies the user's data and must not be left half-done
+    pub fn timeout(&self) -> Option<Duration> {
+        match self {
+            Self::AddPlayableToQueue(_)
+            | Self::AddAlbumToQueue(_)
+            | Self::AddPlayableToPlaylist(..)
+            | Self::SyncPlaylist { .. }
+            | Self::CreatePlaylistFromTracks { .. }
+            | Self::MergePlaylists { .. }
+            | Self::DeleteTrackFromPlaylist(..)
+            | Self::ReorderPlaylistItems { .. }
+            | Self::EditPlaylistTracks { .. }
+            | Self::AddToLibrary(_)
+            | Self::DeleteFromLibrary(_)
+            | Self::SetTracksLiked { .. }
+            | Self::AddToBlocklist(_)
+            | Self::CreatePlaylist { .. }
+            | Self::ChangePlaylistDetails { .. } => None,
+            Self::GetContext(_)
+            | Self::GetUserPlaylists
+            | Self::GetUserSavedAlbums
+            | Self::GetUserSavedShows
+            | Self::GetUserSavedEpisodes
+            | Self::GetUserFollowedArtists
+            | Self::GetUserSavedTracks
+            | Self::GetUserTopTracks
+            | Self::GetSmartPlaylistTracks(_)
+            | Self::CheckSavedTracks(_)
+            | Self::GetSavedTrackGenres
+            | Self::GetArtistFeed
+            | Self::GetSharedPlaylistAdds
+            | Self::LoadAllSearchResults { .. } => Some(LONG_REQUEST_TIMEOUT),
+            _ => Some(REQUEST_TIMEOUT),
+        }
+    }
 }
@@ -35,12 +35,13 @@ impl fmt::Debug for Spotify {
 }
 
 impl Spotify {
-    /// Create a new Spotify client
-    pub fn new() -> Spotify {
+    /// Create a new Spotify client sending Web API requests to a given endpoint
+    pub fn new(api_endpoint: &str) -> Spotify {
         Self {
             creds: Credentials::default(),
             oauth: OAuth::default(),
             config: Config {
+                api_base_url: format!("{api_endpoint}/"),
                 token_refreshing: true,
                 ..Default::default()
             },
@@ -27,6 +27,18 @@ pub enum Command {
         duration: Option<u16>,
     },
 
+    SetCuePoint,
+    JumpToCuePoint,
+    PreviewNextTrack,
+    CommitTrackPreview,
+    ToggleAudition,
+    #[cfg(feature = "preview")]
+    PeekTrack,
+    SetLoopPoint,
+    AddEpisodeBookmark,
+    ShowEpisodeBookmarks,
+    BlockCurrentTrack,
+
     Quit,
     OpenCommandHelp,
     ClosePopup,
@@ -42,15 +54,21 @@ pub enum Command {
     ChooseSelected,
 
     RefreshPlayback,
+    ContinueLastPlayback,
 
     #[cfg(feature = "streaming")]
     RestartIntegratedClient,
+    #[cfg(feature = "streaming")]
+    CycleStreamingBitrate,
 
     FocusNextWindow,
     FocusPreviousWindow,
 
     SwitchTheme,
     SwitchDevice,
+    ToggleDeviceGroupMember,
+    SwitchToNextGroupDevice,
+    CycleContextEndBehavior,
     Search,
     Queue,
 
@@ -62,23 +80,56 @@ pub enum Command {
     BrowseUserPlaylists,
     BrowseUserFollowedArtists,
     BrowseUserSavedAlbums,
+    BrowseSmartPlaylists,
+    BrowseLikedTracksByGenre,
+    BrowseTracksByDecade,
+    SaveTracksAsPlaylist,
+    LikeAllTracks,
+    UnlikeAllTracks,
+    UndoLikeAllTracks,
 
     CurrentlyPlayingContextPage,
     TopTrackPage,
     RecentlyPlayedTrackPage,
     LikedTrackPage,
     LyricsPage,
+    NowPlayingPage,
     LibraryPage,
     SearchPage,
     BrowsePage,
+    BlocklistPage,
+    ArtistFeedPage,
+    MarkFeedAsRead,
+    SharedPlaylistsPage,
+    SavedEpisodesPage,
+    PlaylistDiffPage,
+    SyncPlaylists,
+    #[cfg(feature = "plugins")]
+    PluginsPage,
     PreviousPage,
+    NextPage,
+    NextResultPage,
+    PreviousResultPage,
+    LoadAllResults,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
     OpenSpotifyLinkFromClipboard,
+    PlaySpotifyLinkFromClipboard,
+    TogglePerformanceOverlay,
+    TogglePrivateSession,
+    CycleColorBlindnessSimulation,
+    OpenCommandPalette,
+    RecordMacro,
+    ReplayMacro,
 
     SortTrackByTitle,
     SortTrackByArtists,
     SortTrackByAlbum,
     SortTrackByDuration,
     SortTrackByAddedDate,
+    ResetTrackOrder,
     ReverseTrackOrder,
 
     SortLibraryAlphabetically,
@@ -86,6 +137,7 @@ pub enum Command {
 
     MovePlaylistItemUp,
     MovePlaylistItemDown,
+    GrabPlaylistItem,
 
     CreatePlaylist,
 
@@ -95,9 +147,21 @@ pub enum Command {
     CycleProgressBarEffect,
     #[cfg(feature = "fx")]
     ToggleVisualization,
+    #[cfg(feature = "fx")]
+    SnapshotVisualization,
+    #[cfg(feature = "fx")]
+    ToggleVisualizationRecording,
+    #[cfg(feature = "fx")]
+    CycleVisualizationMode,
+    #[cfg(feature = "fx")]
+    CycleVisualizationColorScheme,
+    #[cfg(feature = "fx")]
+    RememberVisualization,
+    #[cfg(all(feature = "fx", feature = "image"))]
+    ExportAlbumTheme,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
 pub enum Action {
     GoToArtist,
     GoToAlbum,
@@ -117,6 +181,15 @@ pub enum Action {
     CopyLink,
     Follow,
     Unfollow,
+    AddToBlocklist,
+    SetRating,
+    EditTags,
+    ShowTrackDetails,
+    MergePlaylists,
+    RenamePlaylist,
+    EditPlaylistDescription,
+    EditPlaylistInEditor,
+    SearchForAlternative,
 }
 
 #[derive(Debug)]
@@ -139,11 +212,85 @@ pub enum ActionTarget {
     SelectedItem,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CommandOrAction {
     Command(Command),
     Action(Action, ActionTarget),
 }
 
+#[derive(Debug, Clone)]
+/// An entry of the command palette
+pub struct PaletteEntry {
+    pub command_or_action: CommandOrAction,
+    /// the key sequences mapped to the entry's command or action
+    pub key_sequences: Vec<String>,
+}
+
+impl std::fmt::Display for PaletteEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.command_or_action {
+            CommandOrAction::Command(command) => write!(f, "{command:?}: {}", command.desc())?,
+            CommandOrAction::Action(action, ActionTarget::PlayingTrack) => {
+                write!(f, "{action:?}: on the playing track")?;
+            }
+            CommandOrAction::Action(action, ActionTarget::SelectedItem) => {
+                write!(f, "{action:?}: on the selected item")?;
+            }
+        }
+        if !self.key_sequences.is_empty() {
+            write!(f, " [{}]", self.key_sequences.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// constructs the entries of the command palette, which consist of the commands and actions
+/// in the keymaps, and the available actions on the playing track (or episode)
+pub fn construct_palette_entries(playing_track_actions: Vec<Action>) -> Vec<PaletteEntry> {
+    let keymap_config = &crate::config::get_config().keymap_config;
+    let mut entries: Vec<PaletteEntry> = vec![];
+    let mut add_entry =
+        |command_or_action: CommandOrAction, key_sequence: Option<String>| match entries
+            .iter_mut()
+            .find(|e| e.command_or_action == command_or_action)
+        {
+            Some(entry) => entry.key_sequences.extend(key_sequence),
+            None => entries.push(PaletteEntry {
+                command_or_action,
+                key_sequences: key_sequence.into_iter().collect(),
+            }),
+        };
+
+    for keymap in &keymap_config.keymaps {
+        // macro commands depend on the pressed keys, so they can't be run from the palette
+        if !matches!(
+            keymap.command,
+            Command::None
+                | Command::OpenCommandPalette
+                | Command::RecordMacro
+                | Command::ReplayMacro
+        ) {
+            add_entry(
+                CommandOrAction::Command(keymap.command),
+                Some(keymap.key_sequence.to_string()),
+            );
+        }
+    }
+    for action in &keymap_config.actions {
+        add_entry(
+            CommandOrAction::Action(action.action, action.target),
+            Some(action.key_sequence.to_string()),
+        );
+    }
+    for action in playing_track_actions {
+        add_entry(
+            CommandOrAction::Action(action, ActionTarget::PlayingTrack),
+            None,
+        );
+    }
+    entries
+}
+
 impl From<Track> for ActionContext {
     fn from(v: Track) -> Self {
         Self::Track(v)
@@ -217,11 +364,20 @@ pub fn construct_track_actions(track: &Track, data: &DataReadGuard) -> Vec<Actio
         Action::AddToQueue,
     ];
 
-    if data.user_data.is_liked_track(track) {
+    if data.is_liked_track(track) {
         actions.push(Action::DeleteFromLiked);
     } else {
         actions.push(Action::AddToLiked);
     }
+    if track.is_unavailable {
+        actions.push(Action::SearchForAlternative);
+    }
+    actions.extend([
+        Action::SetRating,
+        Action::EditTags,
+        Action::AddToBlocklist,
+        Action::ShowTrackDetails,
+    ]);
 
     actions
 }
@@ -257,12 +413,13 @@ pub fn construct_artist_actions(artist: &Artist, data: &DataReadGuard) -> Vec<Ac
     } else {
         actions.push(Action::Follow);
     }
+    actions.push(Action::AddToBlocklist);
     actions
 }
 
 /// constructs a list of actions on an playlist
 pub fn construct_playlist_actions(playlist: &Playlist, data: &DataReadGuard) -> Vec<Action> {
-    let mut actions = vec![Action::GoToRadio, Action::CopyLink];
+    let mut actions = vec![Action::GoToRadio, Action::CopyLink, Action::MergePlaylists];
 
     if data
         .user_data
@@ -274,6 +431,16 @@ pub fn construct_playlist_actions(playlist: &Playlist, data: &DataReadGuard) ->
     } else {
         actions.push(Action::AddToLibrary);
     }
+    if data
+        .user_data
+        .user
+        .as_ref()
+        .is_some_and(|u| u.id == playlist.owner.1)
+    {
+        actions.push(Action::RenamePlaylist);
+        actions.push(Action::EditPlaylistDescription);
+        actions.push(Action::EditPlaylistInEditor);
+    }
     actions
 }
 
@@ -289,12 +456,22 @@ pub fn construct_show_actions(show: &Show, data: &DataReadGuard) -> Vec<Action>
 }
 
 /// constructs a list of actions on an episode
-pub fn construct_episode_actions(episode: &Episode, _data: &DataReadGuard) -> Vec<Action> {
+pub fn construct_episode_actions(episode: &Episode, data: &DataReadGuard) -> Vec<Action> {
     let mut actions = vec![Action::CopyLink, Action::AddToPlaylist, Action::AddToQueue];
     if episode.show.is_some() {
         actions.push(Action::ShowActionsOnShow);
         actions.push(Action::GoToShow);
     }
+    if data
+        .user_data
+        .saved_episodes
+        .iter()
+        .any(|e| e.id == episode.id)
+    {
+        actions.push(Action::DeleteFromLibrary);
+    } else {
+        actions.push(Action::AddToLibrary);
+    }
     actions
 }
 
@@ -316,10 +493,25 @@ impl Command {
             Self::Mute => "toggle playback volume between 0% and previous level",
             Self::SeekForward { duration } => { return format!("seek forward by {}s", duration.unwrap_or(5)) },
             Self::SeekBackward { duration } => { return format!("seek backward by {}s", duration.unwrap_or(5)) },
+            Self::SetCuePoint => "set a temporary cue point at the current position of the current track",
+            Self::JumpToCuePoint => "jump back to the cue point of the current track",
+            Self::PreviewNextTrack => "preview the next queued track for a few seconds before going back to the current track (or stop the ongoing preview)",
+            Self::CommitTrackPreview => "commit the ongoing preview, i.e. keep playing the previewed track",
+            Self::ToggleAudition => "audition the playing context by playing a few seconds of each track back to back (or stop the ongoing audition)",
+            #[cfg(feature = "preview")]
+            Self::PeekTrack => "play the selected track's 30-second preview through the local audio output without changing the playback (or stop the playing preview)",
+            Self::SetLoopPoint => "set the A (start) point, then the B (end) point of an A-B loop in the current track, or clear the loop if both are set",
+            Self::AddEpisodeBookmark => "add a bookmark with a note at the current position of the current episode",
+            Self::ShowEpisodeBookmarks => "open a popup showing bookmarks of the current episode",
+            Self::BlockCurrentTrack => "never play the current track again, i.e. add it to the blocklist and skip it",
             Self::Quit => "quit the application",
             Self::ClosePopup => "close a popup",
             #[cfg(feature = "streaming")]
             Self::RestartIntegratedClient => "restart the integrated client",
+            #[cfg(feature = "streaming")]
+            Self::CycleStreamingBitrate => {
+                "cycle the integrated client's streaming bitrate (96, 160 or 320 kbps), restarting the client"
+            }
             Self::SelectNextOrScrollDown => "select the next item in a list/table or scroll down (supports vim-style count: 5j)",
             Self::SelectPreviousOrScrollUp => {
                 "select the previous item in a list/table or scroll up (supports vim-style count: 10k)"
@@ -339,6 +531,7 @@ impl Command {
             Self::ChooseSelected => "choose the selected item and act on it",
             Self::JumpToCurrentTrackInContext => "jump to the current track in the context",
             Self::RefreshPlayback => "manually refresh the current playback",
+            Self::ContinueLastPlayback => "continue the last playback of the application's previous run where it left off",
             Self::ShowActionsOnSelectedItem => "open a popup showing actions on a selected item",
             Self::ShowActionsOnCurrentTrack => "open a popup showing actions on the current track",
             Self::AddSelectedItemToQueue => "add the selected item to queue",
@@ -347,27 +540,93 @@ impl Command {
             Self::FocusPreviousWindow => "focus the previous focusable window (if any)",
             Self::SwitchTheme => "open a popup for switching theme",
             Self::SwitchDevice => "open a popup for switching device",
+            Self::ToggleDeviceGroupMember => {
+                "add the selected device to (or remove it from) the device group in the devices popup"
+            }
+            Self::SwitchToNextGroupDevice => "move the playback to the next device in the device group",
+            Self::CycleContextEndBehavior => {
+                "cycle what happens when the playing context ends (autoplay, stop or repeat)"
+            }
             Self::Search => "open a popup for searching in the current page",
             Self::BrowseUserPlaylists => "open a popup for browsing user's playlists",
             Self::BrowseUserFollowedArtists => "open a popup for browsing user's followed artists",
             Self::BrowseUserSavedAlbums => "open a popup for browsing user's saved albums",
+            Self::BrowseSmartPlaylists => "open a popup for browsing smart playlists",
+            Self::BrowseLikedTracksByGenre => {
+                "open a popup for browsing the user liked tracks by genre"
+            }
+            Self::BrowseTracksByDecade => {
+                "open a popup for browsing the tracks of the current page (or the user liked tracks) by release decade"
+            }
+            Self::SaveTracksAsPlaylist => {
+                "save the tracks of the current tracks page (e.g. liked tracks of a genre) as a new playlist"
+            }
             Self::CurrentlyPlayingContextPage => "go to the currently playing context page",
             Self::TopTrackPage => "go to the user top track page",
             Self::RecentlyPlayedTrackPage => "go to the user recently played track page",
             Self::LikedTrackPage => "go to the user liked track page",
             Self::LyricsPage => "go to the lyrics page of the current track",
+            Self::NowPlayingPage => "go to/leave the full-screen now playing page",
             Self::LibraryPage => "go to the user library page",
             Self::SearchPage => "go to the search page",
             Self::BrowsePage => "go to the browse page",
+            Self::BlocklistPage => "go to the blocklist page",
+            Self::ArtistFeedPage => {
+                "go to the feed of the followed artists' new releases, appearances and playlist adds"
+            }
+            Self::MarkFeedAsRead => "mark all items in the artist feed as read",
+            Self::SharedPlaylistsPage => {
+                "go to the page of the collaborative playlists and Blends shared with the user"
+            }
+            Self::SavedEpisodesPage => {
+                "go to the user's saved episodes (\"Your Episodes\") with their played status"
+            }
+            Self::PlaylistDiffPage => "compare the current playlist with another playlist",
+            #[cfg(feature = "plugins")]
+            Self::PluginsPage => {
+                "go to the plugin manager page to enable/disable plugins and use their pages, actions and visualizations"
+            }
+            Self::SyncPlaylists => {
+                "make the other compared playlist (if owned) a mirror of the focused list's playlist"
+            }
             Self::Queue => "go to the queue page",
             Self::OpenCommandHelp => "go to the command help page",
             Self::PreviousPage => "go to the previous page",
+            Self::NextPage => "go to the next page (after going back)",
+            Self::LikeAllTracks => "like all tracks of the current album/playlist",
+            Self::UnlikeAllTracks => "unlike all tracks of the current album/playlist",
+            Self::UndoLikeAllTracks => {
+                "undo the last liking/unliking of all tracks of an album/playlist"
+            }
+            Self::NextResultPage => "show the next page of results in a paginated list (supports vim-style count: 3])",
+            Self::PreviousResultPage => "show the previous page of results in a paginated list (supports vim-style count: 3[)",
+            Self::LoadAllResults => "load all results of a paginated list (up to 1,000 results)",
+            Self::NewTab => "open the current page in a new tab",
+            Self::CloseTab => "close the current tab",
+            Self::NextTab => "go to the next tab",
+            Self::PreviousTab => "go to the previous tab",
             Self::OpenSpotifyLinkFromClipboard => "open a Spotify link from clipboard",
+            Self::PlaySpotifyLinkFromClipboard => "play the item of a Spotify link from clipboard",
             Self::SortTrackByTitle => "sort the track table (if any) by track's title",
+            Self::TogglePrivateSession => {
+                "toggle a private session, in which the played tracks aren't recorded in the listen history and the hooks aren't triggered"
+            }
+            Self::TogglePerformanceOverlay => {
+                "toggle an overlay showing the application's performance statistics"
+            }
+            Self::CycleColorBlindnessSimulation => {
+                "cycle through simulations of color blindness (protanopia, deuteranopia, tritanopia, off)"
+            }
+            Self::OpenCommandPalette => {
+                "open a palette to search and run commands and actions on the playing track"
+            }
+            Self::RecordMacro => "start/stop recording the pressed keys as a macro",
+            Self::ReplayMacro => "replay the last recorded macro (a count prefix replays it N times)",
             Self::SortTrackByArtists => "sort the track table (if any) by track's artists",
             Self::SortTrackByAlbum => "sort the track table (if any) by track's album",
             Self::SortTrackByDuration => "sort the track table (if any) by track's duration",
             Self::SortTrackByAddedDate => "sort the track table (if any) by track's added date",
+            Self::ResetTrackOrder => "reset the track table of a playlist to the playlist's own order",
             Self::ReverseTrackOrder => "reverse the order of the track table (if any)",
             Self::SortLibraryAlphabetically => "sort the library alphabetically",
             Self::SortLibraryByRecent => {
@@ -375,6 +634,9 @@ impl Command {
             }
             Self::MovePlaylistItemUp => "move playlist item up one position",
             Self::MovePlaylistItemDown => "move playlist item down one position",
+            Self::GrabPlaylistItem => {
+                "grab a playlist item to move it with navigation commands, then drop it (space/enter) or cancel the move (esc)"
+            }
             Self::CreatePlaylist => "create a new playlist",
             #[cfg(feature = "fx")]
             Self::ToggleEffects => "toggle visualization effects on/off",
@@ -382,6 +644,26 @@ impl Command {
             Self::CycleProgressBarEffect => "cycle through progress bar effect types",
             #[cfg(feature = "fx")]
             Self::ToggleVisualization => "toggle audio visualization on/off",
+            #[cfg(feature = "fx")]
+            Self::SnapshotVisualization => {
+                "save a snapshot of the visualization to the cache folder"
+            }
+            #[cfg(feature = "fx")]
+            Self::ToggleVisualizationRecording => {
+                "start/stop recording the visualization as an asciinema cast"
+            }
+            #[cfg(feature = "fx")]
+            Self::CycleVisualizationMode => "cycle through visualization modes",
+            #[cfg(feature = "fx")]
+            Self::CycleVisualizationColorScheme => "cycle through visualization color schemes",
+            #[cfg(feature = "fx")]
+            Self::RememberVisualization => {
+                "remember the current visualization for an artist or a genre of the current track"
+            }
+            #[cfg(all(feature = "fx", feature = "image"))]
+            Self::ExportAlbumTheme => {
+                "export the palette of the current album's cover as a theme (and a terminal color scheme)"
+            }
             Self::VolumeChange { offset: _ } => unreachable!(),
         }
         .to_string()
@@ -12,6 +12,9 @@ pub struct KeymapConfig {
     pub keymaps: Vec<Keymap>,
     #[serde(default)]
     pub actions: Vec<ActionMap>,
+    #[cfg(feature = "scripting")]
+    #[serde(default)]
+    pub scripts: Vec<ScriptMap>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -30,10 +33,21 @@ pub struct ActionMap {
     pub action: Action,
 }
 
+#[cfg(feature = "scripting")]
+#[derive(Clone, Debug, Deserialize)]
+/// A keymap that runs a user script when a key sequence is pressed
+pub struct ScriptMap {
+    pub key_sequence: KeySequence,
+    /// the script's name, which is the name of a `.rhai` file in the `scripts` config sub-folder
+    pub script: String,
+}
+
 impl Default for KeymapConfig {
     fn default() -> Self {
         KeymapConfig {
             actions: vec![],
+            #[cfg(feature = "scripting")]
+            scripts: vec![],
             keymaps: vec![
                 Keymap {
                     key_sequence: "n".into(),
@@ -83,6 +97,47 @@ impl Default for KeymapConfig {
                     key_sequence: "<".into(),
                     command: Command::SeekBackward { duration: None },
                 },
+                Keymap {
+                    key_sequence: "c s".into(),
+                    command: Command::SetCuePoint,
+                },
+                Keymap {
+                    key_sequence: "c j".into(),
+                    command: Command::JumpToCuePoint,
+                },
+                Keymap {
+                    key_sequence: "c p".into(),
+                    command: Command::PreviewNextTrack,
+                },
+                Keymap {
+                    key_sequence: "c c".into(),
+                    command: Command::CommitTrackPreview,
+                },
+                Keymap {
+                    key_sequence: "c a".into(),
+                    command: Command::ToggleAudition,
+                },
+                #[cfg(feature = "preview")]
+                Keymap {
+                    key_sequence: "c k".into(),
+                    command: Command::PeekTrack,
+                },
+                Keymap {
+                    key_sequence: "c l".into(),
+                    command: Command::SetLoopPoint,
+                },
+                Keymap {
+                    key_sequence: "b a".into(),
+                    command: Command::AddEpisodeBookmark,
+                },
+                Keymap {
+                    key_sequence: "b l".into(),
+                    command: Command::ShowEpisodeBookmarks,
+                },
+                Keymap {
+                    key_sequence: "X".into(),
+                    command: Command::BlockCurrentTrack,
+                },
                 Keymap {
                     key_sequence: "enter".into(),
                     command: Command::ChooseSelected,
@@ -91,6 +146,10 @@ impl Default for KeymapConfig {
                     key_sequence: "r".into(),
                     command: Command::RefreshPlayback,
                 },
+                Keymap {
+                    key_sequence: "C".into(),
+                    command: Command::ContinueLastPlayback,
+                },
                 Keymap {
                     key_sequence: "/".into(),
                     command: Command::Search,
@@ -128,6 +187,11 @@ impl Default for KeymapConfig {
                     key_sequence: "R".into(),
                     command: Command::RestartIntegratedClient,
                 },
+                #[cfg(feature = "streaming")]
+                Keymap {
+                    key_sequence: "g q".into(),
+                    command: Command::CycleStreamingBitrate,
+                },
                 Keymap {
                     key_sequence: "tab".into(),
                     command: Command::FocusNextWindow,
@@ -144,6 +208,18 @@ impl Default for KeymapConfig {
                     key_sequence: "D".into(),
                     command: Command::SwitchDevice,
                 },
+                Keymap {
+                    key_sequence: "x".into(),
+                    command: Command::ToggleDeviceGroupMember,
+                },
+                Keymap {
+                    key_sequence: "g D".into(),
+                    command: Command::SwitchToNextGroupDevice,
+                },
+                Keymap {
+                    key_sequence: "g A".into(),
+                    command: Command::CycleContextEndBehavior,
+                },
                 Keymap {
                     key_sequence: "u p".into(),
                     command: Command::BrowseUserPlaylists,
@@ -156,6 +232,22 @@ impl Default for KeymapConfig {
                     key_sequence: "u A".into(),
                     command: Command::BrowseUserSavedAlbums,
                 },
+                Keymap {
+                    key_sequence: "u s".into(),
+                    command: Command::BrowseSmartPlaylists,
+                },
+                Keymap {
+                    key_sequence: "u g".into(),
+                    command: Command::BrowseLikedTracksByGenre,
+                },
+                Keymap {
+                    key_sequence: "u d".into(),
+                    command: Command::BrowseTracksByDecade,
+                },
+                Keymap {
+                    key_sequence: "C-e".into(),
+                    command: Command::SaveTracksAsPlaylist,
+                },
                 Keymap {
                     key_sequence: "g space".into(),
                     command: Command::CurrentlyPlayingContextPage,
@@ -180,6 +272,50 @@ impl Default for KeymapConfig {
                     key_sequence: "l".into(),
                     command: Command::LyricsPage,
                 },
+                Keymap {
+                    key_sequence: "M-d".into(),
+                    command: Command::TogglePerformanceOverlay,
+                },
+                Keymap {
+                    key_sequence: "g p".into(),
+                    command: Command::TogglePrivateSession,
+                },
+                Keymap {
+                    key_sequence: "e b".into(),
+                    command: Command::CycleColorBlindnessSimulation,
+                },
+                Keymap {
+                    key_sequence: "C-t".into(),
+                    command: Command::NewTab,
+                },
+                Keymap {
+                    key_sequence: "C-w".into(),
+                    command: Command::CloseTab,
+                },
+                Keymap {
+                    key_sequence: "t n".into(),
+                    command: Command::NextTab,
+                },
+                Keymap {
+                    key_sequence: "t p".into(),
+                    command: Command::PreviousTab,
+                },
+                Keymap {
+                    key_sequence: ":".into(),
+                    command: Command::OpenCommandPalette,
+                },
+                Keymap {
+                    key_sequence: "Q".into(),
+                    command: Command::RecordMacro,
+                },
+                Keymap {
+                    key_sequence: "@".into(),
+                    command: Command::ReplayMacro,
+                },
+                Keymap {
+                    key_sequence: "g n".into(),
+                    command: Command::NowPlayingPage,
+                },
                 Keymap {
                     key_sequence: "g l".into(),
                     command: Command::LibraryPage,
@@ -192,6 +328,39 @@ impl Default for KeymapConfig {
                     key_sequence: "g b".into(),
                     command: Command::BrowsePage,
                 },
+                Keymap {
+                    key_sequence: "g x".into(),
+                    command: Command::BlocklistPage,
+                },
+                Keymap {
+                    key_sequence: "g f".into(),
+                    command: Command::ArtistFeedPage,
+                },
+                Keymap {
+                    key_sequence: "M".into(),
+                    command: Command::MarkFeedAsRead,
+                },
+                Keymap {
+                    key_sequence: "g w".into(),
+                    command: Command::SharedPlaylistsPage,
+                },
+                Keymap {
+                    key_sequence: "g e".into(),
+                    command: Command::SavedEpisodesPage,
+                },
+                Keymap {
+                    key_sequence: "g d".into(),
+                    command: Command::PlaylistDiffPage,
+                },
+                Keymap {
+                    key_sequence: "S".into(),
+                    command: Command::SyncPlaylists,
+                },
+                #[cfg(feature = "plugins")]
+                Keymap {
+                    key_sequence: "g P".into(),
+                    command: Command::PluginsPage,
+                },
                 Keymap {
                     key_sequence: "backspace".into(),
                     command: Command::PreviousPage,
@@ -200,10 +369,46 @@ impl Default for KeymapConfig {
                     key_sequence: "C-q".into(),
                     command: Command::PreviousPage,
                 },
+                Keymap {
+                    key_sequence: "C-o".into(),
+                    command: Command::PreviousPage,
+                },
+                Keymap {
+                    key_sequence: "C-y".into(),
+                    command: Command::NextPage,
+                },
+                Keymap {
+                    key_sequence: "g +".into(),
+                    command: Command::LikeAllTracks,
+                },
+                Keymap {
+                    key_sequence: "g -".into(),
+                    command: Command::UnlikeAllTracks,
+                },
+                Keymap {
+                    key_sequence: "g u".into(),
+                    command: Command::UndoLikeAllTracks,
+                },
+                Keymap {
+                    key_sequence: "]".into(),
+                    command: Command::NextResultPage,
+                },
+                Keymap {
+                    key_sequence: "[".into(),
+                    command: Command::PreviousResultPage,
+                },
+                Keymap {
+                    key_sequence: "L".into(),
+                    command: Command::LoadAllResults,
+                },
                 Keymap {
                     key_sequence: "O".into(),
                     command: Command::OpenSpotifyLinkFromClipboard,
                 },
+                Keymap {
+                    key_sequence: "P".into(),
+                    command: Command::PlaySpotifyLinkFromClipboard,
+                },
                 Keymap {
                     key_sequence: "?".into(),
                     command: Command::OpenCommandHelp,
@@ -304,6 +509,10 @@ impl Default for KeymapConfig {
                     key_sequence: "s r".into(),
                     command: Command::ReverseTrackOrder,
                 },
+                Keymap {
+                    key_sequence: "s R".into(),
+                    command: Command::ResetTrackOrder,
+                },
                 Keymap {
                     key_sequence: "s l a".into(),
                     command: Command::SortLibraryAlphabetically,
@@ -320,6 +529,10 @@ impl Default for KeymapConfig {
                     key_sequence: "C-j".into(),
                     command: Command::MovePlaylistItemDown,
                 },
+                Keymap {
+                    key_sequence: "m".into(),
+                    command: Command::GrabPlaylistItem,
+                },
                 Keymap {
                     key_sequence: "N".into(),
                     command: Command::CreatePlaylist,
@@ -338,6 +551,36 @@ impl Default for KeymapConfig {
                     key_sequence: "e v".into(),
                     command: Command::ToggleVisualization,
                 },
+                #[cfg(feature = "fx")]
+                Keymap {
+                    key_sequence: "e s".into(),
+                    command: Command::SnapshotVisualization,
+                },
+                #[cfg(feature = "fx")]
+                Keymap {
+                    key_sequence: "e r".into(),
+                    command: Command::ToggleVisualizationRecording,
+                },
+                #[cfg(feature = "fx")]
+                Keymap {
+                    key_sequence: "e m".into(),
+                    command: Command::CycleVisualizationMode,
+                },
+                #[cfg(feature = "fx")]
+                Keymap {
+                    key_sequence: "e c".into(),
+                    command: Command::CycleVisualizationColorScheme,
+                },
+                #[cfg(feature = "fx")]
+                Keymap {
+                    key_sequence: "e p".into(),
+                    command: Command::RememberVisualization,
+                },
+                #[cfg(all(feature = "fx", feature = "image"))]
+                Keymap {
+                    key_sequence: "e x".into(),
+                    command: Command::ExportAlbumTheme,
+                },
             ],
         }
     }
@@ -364,6 +607,10 @@ impl KeymapConfig {
                 let mut parsed = toml::from_str::<Self>(&content)?;
                 std::mem::swap(&mut self.keymaps, &mut parsed.keymaps);
                 std::mem::swap(&mut self.actions, &mut parsed.actions);
+                #[cfg(feature = "scripting")]
+                {
+                    self.scripts = parsed.scripts;
+                }
 
                 // a dumb approach (with quadratic complexity) to merge two different keymap arrays
                 // while keeping the invariant:
@@ -411,6 +658,14 @@ impl KeymapConfig {
     pub fn has_matched_prefix(&self, prefix: &KeySequence) -> bool {
         let keymaps = self.find_matched_prefix_keymaps(prefix);
         let actions = self.find_matched_prefix_actions(prefix);
+        #[cfg(feature = "scripting")]
+        if self
+            .scripts
+            .iter()
+            .any(|script| prefix.is_prefix(&script.key_sequence))
+        {
+            return true;
+        }
         !keymaps.is_empty() || !actions.is_empty()
     }
 
@@ -433,6 +688,15 @@ impl KeymapConfig {
             .map(|action| (action.action, action.target))
     }
 
+    #[cfg(feature = "scripting")]
+    /// finds a script from a mapped key sequence
+    pub fn find_script_from_key_sequence(&self, key_sequence: &KeySequence) -> Option<&str> {
+        self.scripts
+            .iter()
+            .find(|&script| script.key_sequence == *key_sequence)
+            .map(|script| script.script.as_str())
+    }
+
     /// finds a command or action from a mapped key sequence
     pub fn find_command_or_action_from_key_sequence(
         &self,
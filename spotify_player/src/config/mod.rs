@@ -1,5 +1,6 @@
 mod keymap;
 mod theme;
+mod theme_import;
 
 const DEFAULT_CONFIG_FOLDER: &str = ".config/spotify-player";
 const DEFAULT_CACHE_FOLDER: &str = ".cache/spotify-player";
@@ -18,9 +19,11 @@ use std::{
 };
 
 use keymap::KeymapConfig;
-use theme::ThemeConfig;
+pub use theme::{Theme, ThemeConfig};
 
-pub use theme::Theme;
+#[cfg(all(feature = "fx", feature = "image"))]
+pub use theme::{add_theme, terminal_scheme_content, unique_theme_name};
+pub use theme_import::import_theme;
 
 use crate::auth::SPOTIFY_CLIENT_ID;
 
@@ -32,6 +35,7 @@ pub struct Configs {
     pub keymap_config: KeymapConfig,
     pub theme_config: ThemeConfig,
     pub cache_folder: std::path::PathBuf,
+    pub config_folder: std::path::PathBuf,
 }
 
 impl Configs {
@@ -41,6 +45,7 @@ impl Configs {
             keymap_config: KeymapConfig::new(config_folder)?,
             theme_config: ThemeConfig::new(config_folder)?,
             cache_folder: cache_folder.to_path_buf(),
+            config_folder: config_folder.to_path_buf(),
         })
     }
 }
@@ -50,17 +55,27 @@ impl Configs {
 /// Application configurations
 pub struct AppConfig {
     pub theme: String,
+    pub terminal_background: TerminalBackground,
     pub client_id: Option<String>,
     pub client_id_command: Option<Command>,
 
     pub client_port: u16,
 
+    #[cfg(feature = "remote")]
+    pub remote_control_address: String,
+
     pub login_redirect_uri: String,
 
     pub log_folder: Option<PathBuf>,
 
     pub player_event_hook_command: Option<Command>,
 
+    pub hooks: HooksConfig,
+
+    pub skip_rules: Vec<SkipRule>,
+
+    pub smart_playlists: Vec<SmartPlaylist>,
+
     pub playback_format: String,
     pub playback_metadata_fields: Vec<String>,
     #[cfg(feature = "notify")]
@@ -69,6 +84,8 @@ pub struct AppConfig {
     pub notify_timeout_in_secs: u64,
 
     pub tracks_playback_limit: usize,
+    /// the default behavior when a playing context ends, which can be changed per context
+    pub context_end_behavior: ContextEndBehavior,
 
     // session configs
     pub proxy: Option<String>,
@@ -77,6 +94,8 @@ pub struct AppConfig {
     // duration configs
     pub app_refresh_duration_in_ms: u64,
     pub playback_refresh_duration_in_ms: u64,
+    /// reduce the refresh rates and background requests, e.g. when the system is on battery power
+    pub low_power_mode: LowPowerMode,
 
     pub page_size_in_rows: usize,
 
@@ -91,8 +110,13 @@ pub struct AppConfig {
     pub progress_bar_position: ProgressBarPosition,
 
     pub layout: LayoutConfig,
+    pub accessibility: AccessibilityConfig,
+    /// the ordered segments of the status bar, which is hidden if there is no segment
+    pub status_bar_segments: Vec<StatusBarSegment>,
 
     pub genre_num: u8,
+    /// the number of queued tracks shown in the playback window's "up next" strip (0 to hide the strip)
+    pub up_next_num: usize,
 
     #[cfg(feature = "image")]
     pub cover_img_length: usize,
@@ -102,7 +126,7 @@ pub struct AppConfig {
     pub cover_img_scale: f32,
     #[cfg(feature = "pixelate")]
     pub cover_img_pixels: u32,
-    
+
     // ASCII art album cover settings
     #[cfg(feature = "image")]
     pub enable_ascii_art: bool,
@@ -122,13 +146,31 @@ pub struct AppConfig {
     pub enable_cover_image_cache: bool,
 
     pub default_device: String,
+    pub startup_context_uri: Option<String>,
 
     pub device: DeviceConfig,
 
+    #[cfg(feature = "mqtt")]
+    pub mqtt: MqttConfig,
+
     #[cfg(all(feature = "streaming", feature = "notify"))]
     pub notify_streaming_only: bool,
 
+    #[cfg(feature = "streaming")]
+    pub pause_on_audio_device_change: bool,
+
+    #[cfg(feature = "daemon")]
+    pub daemon_idle_timeout_mins: u64,
+
+    /// the playback actions triggered by the `SIGUSR1` and `SIGUSR2` signals
+    pub sigusr1_action: SignalAction,
+    pub sigusr2_action: SignalAction,
+
     pub seek_duration_secs: u16,
+    pub track_preview_duration_secs: u16,
+    pub audition_duration_secs: u16,
+    pub enable_beat_grid: bool,
+    pub snap_seek_to_bar: bool,
 
     pub sort_artist_albums_by_type: bool,
 
@@ -136,14 +178,28 @@ pub struct AppConfig {
     pub enable_effects: bool,
     #[cfg(feature = "fx")]
     pub progress_bar_effect: ProgressBarEffect,
-    
+
     // Visualization settings
     #[cfg(feature = "fx")]
     pub enable_visualization: bool,
     #[cfg(feature = "fx")]
     pub visualization_height: usize,
+    /// the duration (in seconds) of a visualization recording, rounded to a whole number of beats
+    #[cfg(feature = "fx")]
+    pub visualization_recording_secs: u64,
+    #[cfg(feature = "fx")]
+    pub visualization_rules: Vec<VisualizationRule>,
+    /// the expression of the cells' intensity in the `Shader` visualization mode, see `ui::shader`
+    #[cfg(feature = "fx")]
+    pub visualization_shader: Option<String>,
+    /// the duration (in ms) of the visualization's color crossfade upon a track change
+    #[cfg(feature = "fx")]
+    pub visualization_crossfade_ms: u64,
     #[cfg(all(feature = "fx", feature = "image"))]
     pub use_album_colors: bool,
+    /// the format of the terminal color scheme exported along with an album's theme, if any
+    #[cfg(all(feature = "fx", feature = "image"))]
+    pub album_theme_terminal_scheme: Option<TerminalColorScheme>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -177,6 +233,103 @@ pub enum ProgressBarPosition {
 }
 config_parser_impl!(ProgressBarPosition);
 
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// What happens when the last track of a playing context ends
+pub enum ContextEndBehavior {
+    /// continue with the playing device's behavior, e.g. Spotify's autoplay if it's enabled
+    Autoplay,
+    /// stop the playback
+    Stop,
+    /// play the context again from its beginning
+    Repeat,
+}
+config_parser_impl!(ContextEndBehavior);
+
+impl ContextEndBehavior {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Autoplay => Self::Stop,
+            Self::Stop => Self::Repeat,
+            Self::Repeat => Self::Autoplay,
+        }
+    }
+
+    pub fn desc(self) -> &'static str {
+        match self {
+            Self::Autoplay => "autoplay",
+            Self::Stop => "stop",
+            Self::Repeat => "repeat",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// The brightness of the terminal's background
+pub enum TerminalBackground {
+    /// detect the background by querying the terminal's background color
+    Auto,
+    Dark,
+    Light,
+}
+config_parser_impl!(TerminalBackground);
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// When to enable the application's low-power mode
+pub enum LowPowerMode {
+    /// enable the low-power mode while the system is on battery power
+    Auto,
+    Always,
+    Never,
+}
+config_parser_impl!(LowPowerMode);
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// A playback action triggered by a Unix signal
+pub enum SignalAction {
+    ResumePause,
+    NextTrack,
+    PreviousTrack,
+    /// ignore the signal
+    None,
+}
+config_parser_impl!(SignalAction);
+
+#[cfg(all(feature = "fx", feature = "image"))]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// The format of a terminal's color scheme file
+pub enum TerminalColorScheme {
+    Alacritty,
+    Kitty,
+    Xresources,
+}
+#[cfg(all(feature = "fx", feature = "image"))]
+config_parser_impl!(TerminalColorScheme);
+
+#[cfg(all(feature = "fx", feature = "image"))]
+impl TerminalColorScheme {
+    /// the extension of the color scheme's file
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Alacritty => "toml",
+            Self::Kitty => "conf",
+            Self::Xresources => "Xresources",
+        }
+    }
+}
+
+impl TerminalBackground {
+    /// Check if the terminal's background is light.
+    ///
+    /// For `Auto`, the background probed on startup is used (see `capabilities::probe`).
+    pub fn is_light(self) -> bool {
+        match self {
+            Self::Dark => false,
+            Self::Light => true,
+            Self::Auto => crate::capabilities::get().light_background,
+        }
+    }
+}
+
 #[cfg(feature = "fx")]
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub enum ProgressBarEffect {
@@ -191,6 +344,7 @@ config_parser_impl!(ProgressBarEffect);
 #[cfg(feature = "fx")]
 impl ProgressBarEffect {
     /// Get animated color for the progress bar based on elapsed time
+    #[allow(clippy::unused_self)]
     pub fn get_animated_color(
         &self,
         _elapsed: std::time::Duration,
@@ -237,6 +391,120 @@ impl Command {
 }
 
 #[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
+/// Hook commands executed upon player events
+pub struct HooksConfig {
+    pub track_changed: Option<Command>,
+    pub paused: Option<Command>,
+    pub liked: Option<Command>,
+    pub volume_changed: Option<Command>,
+    /// The minimum duration between two executions of the same hook
+    pub rate_limit_in_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A rule to (partially) skip tracks upon starting.
+///
+/// A track is matched if it satisfies all the specified conditions.
+pub struct SkipRule {
+    /// match tracks from an artist (by name)
+    pub artist: Option<String>,
+    /// match tracks whose title matches a regex
+    pub title_regex: Option<TitleRegex>,
+    /// match tracks shorter than a duration (in seconds)
+    pub shorter_than_secs: Option<u64>,
+    /// skip the first seconds of matched tracks instead of skipping the whole tracks
+    pub skip_first_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+/// A skip rule's title regex, compiled when the config is loaded so that
+/// an invalid pattern is reported upon starting the application
+pub struct TitleRegex(regex::Regex);
+
+impl<'de> Deserialize<'de> for TitleRegex {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        regex::Regex::new(&pattern).map(Self).map_err(|err| {
+            serde::de::Error::custom(format!("invalid skip rule's title_regex {pattern}: {err}"))
+        })
+    }
+}
+
+impl Serialize for TitleRegex {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A smart playlist, which consists of tracks matching criteria on their local ratings and tags
+pub struct SmartPlaylist {
+    pub name: String,
+    /// match tracks rated at least `min_rating` stars
+    pub min_rating: Option<u8>,
+    /// match tracks tagged with all the tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum StatusBarSegmentType {
+    Clock,
+    Date,
+    Network,
+    Shuffle,
+    Repeat,
+    Volume,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A segment of the status bar
+pub struct StatusBarSegment {
+    #[serde(rename = "type")]
+    pub typ: StatusBarSegmentType,
+    /// the segment's format, which is a `strftime` format for the `Clock` and `Date` segments.
+    /// For other segments, `{}` in the format is replaced by the segment's value.
+    pub format: Option<String>,
+}
+
+impl StatusBarSegmentType {
+    pub fn default_format(self) -> &'static str {
+        match self {
+            Self::Clock => "%H:%M",
+            Self::Date => "%a %-d %b",
+            Self::Network => "{}",
+            Self::Shuffle => "shuffle: {}",
+            Self::Repeat => "repeat: {}",
+            Self::Volume => "volume: {}",
+        }
+    }
+}
+
+#[cfg(feature = "fx")]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+/// A rule to pick the visualization mode of tracks in the `Smart` visualization mode.
+///
+/// A track is matched if it satisfies all the specified conditions.
+pub struct VisualizationRule {
+    pub mode: crate::ui::visualizations::VisualizationMode,
+    /// match tracks whose energy (from 0.0 to 1.0) is at least `min_energy`
+    pub min_energy: Option<f32>,
+    /// match tracks whose energy (from 0.0 to 1.0) is at most `max_energy`
+    pub max_energy: Option<f32>,
+    /// match tracks whose tempo (in BPM) is at least `min_tempo`
+    pub min_tempo: Option<f32>,
+    /// match tracks whose tempo (in BPM) is at most `max_tempo`
+    pub max_tempo: Option<f32>,
+    /// match tracks whose acousticness (from 0.0 to 1.0) is at least `min_acousticness`
+    pub min_acousticness: Option<f32>,
+    /// match tracks whose acousticness (from 0.0 to 1.0) is at most `max_acousticness`
+    pub max_acousticness: Option<f32>,
+    /// match tracks whose first artist has a genre containing `genre` (case-insensitive)
+    pub genre: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 /// Application device configurations
 pub struct DeviceConfig {
     pub name: String,
@@ -244,8 +512,24 @@ pub struct DeviceConfig {
     pub volume: u8,
     pub bitrate: u16,
     pub audio_cache: bool,
+    pub audio_cache_size_limit_in_mb: u64,
     pub normalization: bool,
     pub autoplay: bool,
+    pub trim_silence: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
+#[cfg(feature = "mqtt")]
+/// MQTT publisher configurations
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub state_topic: String,
+    pub command_topic: String,
+    pub publish_interval_in_secs: u64,
 }
 
 #[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
@@ -261,6 +545,21 @@ pub struct LayoutConfig {
     pub library: LibraryLayoutConfig,
     pub playback_window_position: Position,
     pub playback_window_height: usize,
+    pub compact_mode_height_threshold: u16,
+}
+
+#[derive(Debug, Deserialize, Serialize, ConfigParse, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+// Accessibility configurations
+pub struct AccessibilityConfig {
+    /// disable animations and effects
+    pub reduce_motion: bool,
+    /// increase the contrast of the rendered colors
+    pub high_contrast: bool,
+    /// draw plain ASCII characters instead of unicode symbols
+    pub ascii_only: bool,
+    /// announce track changes in a dedicated line at the bottom of the application
+    pub announce_track_changes: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
@@ -324,16 +623,21 @@ impl Default for AppConfig {
     fn default() -> Self {
         Self {
             theme: "dracula".to_owned(),
+            terminal_background: TerminalBackground::Auto,
             client_id: None,
             client_id_command: None,
 
             client_port: 8080,
 
+            #[cfg(feature = "remote")]
+            remote_control_address: "127.0.0.1:8090".to_string(),
+
             login_redirect_uri: "http://127.0.0.1:8989/login".to_string(),
 
             log_folder: None,
 
             tracks_playback_limit: 50,
+            context_end_behavior: ContextEndBehavior::Autoplay,
 
             playback_format: String::from(
                 "{status} {track} • {artists} {liked}\n{album} • {genres}\n{metadata}",
@@ -343,6 +647,8 @@ impl Default for AppConfig {
                 "shuffle".to_string(),
                 "volume".to_string(),
                 "device".to_string(),
+                "quality".to_string(),
+                "context_end".to_string(),
             ],
             #[cfg(feature = "notify")]
             notify_format: NotifyFormat {
@@ -354,10 +660,15 @@ impl Default for AppConfig {
 
             player_event_hook_command: None,
 
+            hooks: HooksConfig::default(),
+            skip_rules: vec![],
+            smart_playlists: vec![],
+
             proxy: None,
             ap_port: None,
             app_refresh_duration_in_ms: 32,
             playback_refresh_duration_in_ms: 0,
+            low_power_mode: LowPowerMode::Auto,
 
             page_size_in_rows: 20,
 
@@ -370,8 +681,11 @@ impl Default for AppConfig {
             progress_bar_position: ProgressBarPosition::Bottom,
 
             layout: LayoutConfig::default(),
+            accessibility: AccessibilityConfig::default(),
+            status_bar_segments: vec![],
 
             genre_num: 2,
+            up_next_num: 3,
 
             #[cfg(feature = "image")]
             cover_img_length: 9,
@@ -381,7 +695,7 @@ impl Default for AppConfig {
             cover_img_scale: 1.0,
             #[cfg(feature = "pixelate")]
             cover_img_pixels: 16,
-            
+
             // ASCII art defaults
             #[cfg(feature = "image")]
             enable_ascii_art: false,
@@ -409,13 +723,30 @@ impl Default for AppConfig {
             enable_cover_image_cache: true,
 
             default_device: "spotify-player".to_string(),
+            startup_context_uri: None,
 
             device: DeviceConfig::default(),
 
+            #[cfg(feature = "mqtt")]
+            mqtt: MqttConfig::default(),
+
             #[cfg(all(feature = "streaming", feature = "notify"))]
             notify_streaming_only: false,
 
+            #[cfg(feature = "streaming")]
+            pause_on_audio_device_change: true,
+
+            #[cfg(feature = "daemon")]
+            daemon_idle_timeout_mins: 0,
+
+            sigusr1_action: SignalAction::ResumePause,
+            sigusr2_action: SignalAction::NextTrack,
+
             seek_duration_secs: 5,
+            track_preview_duration_secs: 10,
+            audition_duration_secs: 10,
+            enable_beat_grid: false,
+            snap_seek_to_bar: false,
 
             sort_artist_albums_by_type: false,
 
@@ -423,14 +754,24 @@ impl Default for AppConfig {
             enable_effects: true,
             #[cfg(feature = "fx")]
             progress_bar_effect: ProgressBarEffect::None,
-            
+
             // Visualization defaults
             #[cfg(feature = "fx")]
             enable_visualization: true,
             #[cfg(feature = "fx")]
             visualization_height: 30,
+            #[cfg(feature = "fx")]
+            visualization_recording_secs: 10,
+            #[cfg(feature = "fx")]
+            visualization_rules: VisualizationRule::defaults(),
+            #[cfg(feature = "fx")]
+            visualization_shader: None,
+            #[cfg(feature = "fx")]
+            visualization_crossfade_ms: 1500,
             #[cfg(all(feature = "fx", feature = "image"))]
             use_album_colors: true,
+            #[cfg(all(feature = "fx", feature = "image"))]
+            album_theme_terminal_scheme: None,
         }
     }
 }
@@ -443,8 +784,38 @@ impl Default for DeviceConfig {
             volume: 70,
             bitrate: 320,
             audio_cache: false,
+            audio_cache_size_limit_in_mb: 1024,
             normalization: false,
             autoplay: false,
+            trim_silence: false,
+        }
+    }
+}
+
+#[cfg(feature = "mqtt")]
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "spotify-player".to_string(),
+            username: None,
+            password: None,
+            state_topic: "spotify_player/state".to_string(),
+            command_topic: "spotify_player/command".to_string(),
+            publish_interval_in_secs: 5,
+        }
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            track_changed: None,
+            paused: None,
+            liked: None,
+            volume_changed: None,
+            rate_limit_in_ms: 1000,
         }
     }
 }
@@ -458,10 +829,137 @@ impl Default for LayoutConfig {
             },
             playback_window_position: Position::Top,
             playback_window_height: 6,
+            compact_mode_height_threshold: 5,
         }
     }
 }
 
+impl SkipRule {
+    /// Check if a track matches the rule
+    pub fn matches(&self, track: &rspotify::model::FullTrack) -> bool {
+        if self.artist.is_none() && self.title_regex.is_none() && self.shorter_than_secs.is_none() {
+            return false;
+        }
+        if let Some(artist) = &self.artist {
+            if !track.artists.iter().any(|a| a.name == *artist) {
+                return false;
+            }
+        }
+        if let Some(title_regex) = &self.title_regex {
+            if !title_regex.0.is_match(&track.name) {
+                return false;
+            }
+        }
+        if let Some(secs) = self.shorter_than_secs {
+            if track.duration >= chrono::Duration::seconds(secs as i64) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(feature = "fx")]
+impl VisualizationRule {
+    /// The default rules: particles for electronic tracks, spectrum bars for high-energy tracks
+    /// and slow waves for low-tempo or acoustic tracks
+    fn defaults() -> Vec<Self> {
+        use crate::ui::visualizations::VisualizationMode;
+
+        vec![
+            Self {
+                mode: VisualizationMode::Particles,
+                genre: Some("electronic".to_string()),
+                ..Self::default()
+            },
+            Self {
+                mode: VisualizationMode::Bars,
+                min_energy: Some(0.7),
+                ..Self::default()
+            },
+            Self {
+                mode: VisualizationMode::Waves,
+                max_tempo: Some(90.0),
+                ..Self::default()
+            },
+            Self {
+                mode: VisualizationMode::Waves,
+                min_acousticness: Some(0.6),
+                ..Self::default()
+            },
+        ]
+    }
+
+    /// Check if the rule has conditions on tracks' audio features
+    pub fn uses_audio_features(&self) -> bool {
+        [
+            self.min_energy,
+            self.max_energy,
+            self.min_tempo,
+            self.max_tempo,
+            self.min_acousticness,
+            self.max_acousticness,
+        ]
+        .iter()
+        .any(Option::is_some)
+    }
+
+    /// Check if a track matches the rule, given its audio features (if available)
+    /// and the genres of its first artist
+    pub fn matches(
+        &self,
+        features: Option<&rspotify::model::AudioFeatures>,
+        genres: &[String],
+    ) -> bool {
+        let conditions = [
+            (self.min_energy, self.max_energy, features.map(|f| f.energy)),
+            (self.min_tempo, self.max_tempo, features.map(|f| f.tempo)),
+            (
+                self.min_acousticness,
+                self.max_acousticness,
+                features.map(|f| f.acousticness),
+            ),
+        ];
+        for (min, max, value) in conditions {
+            if min.is_none() && max.is_none() {
+                continue;
+            }
+            let Some(value) = value else {
+                return false;
+            };
+            if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+                return false;
+            }
+        }
+        if let Some(genre) = &self.genre {
+            let genre = genre.to_lowercase();
+            if !genres.iter().any(|g| g.to_lowercase().contains(&genre)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl SmartPlaylist {
+    /// The ID of the playlist's tracks context
+    pub fn tracks_id(&self) -> crate::state::TracksId {
+        crate::state::TracksId::new(format!("tracks:smart-playlist:{}", self.name), &self.name)
+    }
+
+    /// Check if a track's annotation matches the playlist's criteria
+    pub fn matches(&self, annotation: &crate::state::TrackAnnotation) -> bool {
+        if let Some(min_rating) = self.min_rating {
+            if annotation.rating.is_none_or(|r| r < min_rating) {
+                return false;
+            }
+        }
+        self.tags
+            .iter()
+            .all(|tag| annotation.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+}
+
 impl LayoutConfig {
     fn check_values(&self) -> anyhow::Result<()> {
         if self.library.album_percent + self.library.playlist_percent > 99 {
@@ -479,6 +977,11 @@ impl AppConfig {
         }
 
         config.layout.check_values()?;
+        #[cfg(feature = "fx")]
+        if let Some(shader) = &config.visualization_shader {
+            crate::ui::shader::Shader::compile(shader)
+                .map_err(|err| anyhow!("Invalid visualization_shader: {err:#}"))?;
+        }
         Ok(config)
     }
 
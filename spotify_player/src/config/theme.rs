@@ -1,6 +1,6 @@
-use std::str::FromStr;
+use std::{fmt::Write, path::Path, str::FromStr};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use ratatui::style;
 use serde::Deserialize;
 
@@ -70,9 +70,12 @@ struct ComponentStyle {
     playback_album: Option<Style>,
     playback_genres: Option<Style>,
     playback_metadata: Option<Style>,
+    playback_up_next: Option<Style>,
     playback_progress_bar: Option<Style>,
     playback_progress_bar_unfilled: Option<Style>,
     current_playing: Option<Style>,
+    new_track: Option<Style>,
+    unavailable_track: Option<Style>,
     page_desc: Option<Style>,
     playlist_desc: Option<Style>,
     table_header: Option<Style>,
@@ -168,6 +171,126 @@ impl ThemeConfig {
     }
 }
 
+/// the palette's fields of a theme, in the order written in a theme file
+const PALETTE_FIELDS: [&str; 18] = [
+    "background",
+    "foreground",
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
+/// Construct the content of a theme file with a theme of the given name and palette's colors
+fn theme_file_content(name: &str, palette: &[(&str, String)]) -> String {
+    let mut content = format!("\n[[themes]]\nname = {name:?}\n[themes.palette]\n");
+    for field in PALETTE_FIELDS {
+        if let Some((_, color)) = palette.iter().find(|(f, _)| *f == field) {
+            let _ = writeln!(content, "{field} = \"{color}\"");
+        }
+    }
+    content
+}
+
+/// Add a theme of the given name and palette's colors (hex colors keyed by the palette's fields)
+/// to the theme config file in `config_folder`
+pub fn add_theme(config_folder: &Path, name: &str, palette: &[(&str, String)]) -> Result<Theme> {
+    let content = theme_file_content(name, palette);
+    let theme = toml::from_str::<ThemeConfig>(&content)?
+        .themes
+        .pop()
+        .context("no theme in the added theme's config")?;
+
+    let theme_file = config_folder.join(super::THEME_CONFIG_FILE);
+    let mut themes = std::fs::read_to_string(&theme_file).unwrap_or_default();
+    if !themes.is_empty() && !themes.ends_with('\n') {
+        themes.push('\n');
+    }
+    themes.push_str(&content);
+    std::fs::write(&theme_file, themes)
+        .with_context(|| format!("failed to write the theme file {}", theme_file.display()))?;
+
+    Ok(theme)
+}
+
+#[cfg(all(feature = "fx", feature = "image"))]
+/// Get a name based on `name` that isn't used by any theme in `config_folder`,
+/// by adding a number suffix if needed
+pub fn unique_theme_name(config_folder: &Path, name: &str) -> Result<String> {
+    let themes = ThemeConfig::new(config_folder)?;
+    let mut unique_name = name.to_string();
+    let mut i = 1;
+    while themes.find_theme(&unique_name).is_some() {
+        i += 1;
+        unique_name = format!("{name} ({i})");
+    }
+    Ok(unique_name)
+}
+
+#[cfg(all(feature = "fx", feature = "image"))]
+/// Construct the content of a terminal's color scheme file with a palette's colors
+/// (hex colors keyed by the palette's fields)
+pub fn terminal_scheme_content(
+    scheme: super::TerminalColorScheme,
+    palette: &[(&str, String)],
+) -> String {
+    let color = |field: &str| {
+        palette
+            .iter()
+            .find(|(f, _)| *f == field)
+            .map_or("", |(_, c)| c.as_str())
+    };
+    // the ANSI colors, from `color0` (black) to `color15` (bright white)
+    let ansi_colors = PALETTE_FIELDS[2..].iter().map(|f| color(f));
+
+    let mut content = String::new();
+    match scheme {
+        super::TerminalColorScheme::Alacritty => {
+            let _ = writeln!(
+                content,
+                "[colors.primary]\nbackground = \"{}\"\nforeground = \"{}\"",
+                color("background"),
+                color("foreground")
+            );
+            let fields = PALETTE_FIELDS[2..10].iter();
+            for (i, (field, color)) in fields.cycle().zip(ansi_colors).enumerate() {
+                if i % 8 == 0 {
+                    let table = if i == 0 { "normal" } else { "bright" };
+                    let _ = writeln!(content, "\n[colors.{table}]");
+                }
+                let _ = writeln!(content, "{field} = \"{color}\"");
+            }
+        }
+        super::TerminalColorScheme::Kitty => {
+            let _ = writeln!(content, "background {}", color("background"));
+            let _ = writeln!(content, "foreground {}", color("foreground"));
+            for (i, color) in ansi_colors.enumerate() {
+                let _ = writeln!(content, "color{i} {color}");
+            }
+        }
+        super::TerminalColorScheme::Xresources => {
+            let _ = writeln!(content, "*.background: {}", color("background"));
+            let _ = writeln!(content, "*.foreground: {}", color("foreground"));
+            for (i, color) in ansi_colors.enumerate() {
+                let _ = writeln!(content, "*.color{i}: {color}");
+            }
+        }
+    }
+    content
+}
+
 impl Theme {
     pub fn app(&self) -> style::Style {
         let mut style = style::Style::default();
@@ -274,6 +397,18 @@ impl Theme {
             .style(&self.palette)
     }
 
+    pub fn playback_up_next(&self) -> style::Style {
+        self.component_style
+            .playback_up_next
+            .as_ref()
+            .unwrap_or(
+                &Style::default()
+                    .fg(StyleColor::BrightBlack)
+                    .modifiers([StyleModifier::Dim]),
+            )
+            .style(&self.palette)
+    }
+
     pub fn playback_progress_bar(&self) -> style::Style {
         self.component_style
             .playback_progress_bar
@@ -306,6 +441,26 @@ impl Theme {
             .style(&self.palette)
     }
 
+    pub fn new_track(&self) -> style::Style {
+        self.component_style
+            .new_track
+            .as_ref()
+            .unwrap_or(&Style::default().fg(StyleColor::Yellow))
+            .style(&self.palette)
+    }
+
+    pub fn unavailable_track(&self) -> style::Style {
+        self.component_style
+            .unavailable_track
+            .as_ref()
+            .unwrap_or(
+                &Style::default()
+                    .fg(StyleColor::BrightBlack)
+                    .modifiers([StyleModifier::CrossedOut]),
+            )
+            .style(&self.palette)
+    }
+
     pub fn page_desc(&self) -> style::Style {
         self.component_style
             .page_desc
@@ -574,7 +729,13 @@ impl From<style::Color> for Color {
 impl Default for ThemeConfig {
     fn default() -> Self {
         Self {
-            themes: vec![Theme::default()],
+            themes: vec![
+                Theme::default(),
+                Theme::with_palette("default_light", Palette::light()),
+                Theme::with_palette("deuteranopia", Palette::deuteranopia()),
+                Theme::with_palette("protanopia", Palette::protanopia()),
+                Theme::with_palette("tritanopia", Palette::tritanopia()),
+            ],
         }
     }
 }
@@ -589,6 +750,96 @@ impl Default for Theme {
     }
 }
 
+impl Theme {
+    /// a theme with the predefined component styles and a given palette
+    fn with_palette(name: &str, palette: Palette) -> Self {
+        Self {
+            name: name.to_owned(),
+            palette,
+            component_style: ComponentStyle::default(),
+        }
+    }
+}
+
+impl Palette {
+    /// a palette based on the terminal's colors with the given (normal, bright) RGB colors
+    /// for red, green, yellow, blue, magenta and cyan
+    fn with_colors(colors: [(&str, &str); 6]) -> Self {
+        let [red, green, yellow, blue, magenta, cyan] = colors;
+        Self {
+            red: red.0.into(),
+            green: green.0.into(),
+            yellow: yellow.0.into(),
+            blue: blue.0.into(),
+            magenta: magenta.0.into(),
+            cyan: cyan.0.into(),
+            bright_red: red.1.into(),
+            bright_green: green.1.into(),
+            bright_yellow: yellow.1.into(),
+            bright_blue: blue.1.into(),
+            bright_magenta: magenta.1.into(),
+            bright_cyan: cyan.1.into(),
+            ..Self::default()
+        }
+    }
+
+    /// a palette based on the terminal's colors for light backgrounds,
+    /// in which the bright colors (usually pale on light backgrounds) are replaced by the normal ones
+    /// and white texts are replaced by black ones
+    fn light() -> Self {
+        Self {
+            white: Color::black(),
+            bright_white: Color::black(),
+            bright_red: Color::red(),
+            bright_green: Color::green(),
+            bright_yellow: Color::yellow(),
+            bright_blue: Color::blue(),
+            bright_magenta: Color::magenta(),
+            bright_cyan: Color::cyan(),
+            ..Self::default()
+        }
+    }
+
+    /// a palette for red-green color blindness (green-weak), based on the Okabe-Ito colors:
+    /// red and green are replaced by vermillion and blue
+    fn deuteranopia() -> Self {
+        Self::with_colors([
+            ("#D55E00", "#FF8C42"),
+            ("#0072B2", "#3399DD"),
+            ("#F0E442", "#F7F08A"),
+            ("#56B4E9", "#8FD0F5"),
+            ("#CC79A7", "#E0A3C8"),
+            ("#E69F00", "#FFC04D"),
+        ])
+    }
+
+    /// a palette for red-green color blindness (red-weak), based on the Okabe-Ito colors:
+    /// red, which looks dark, is replaced by orange and green by blue
+    fn protanopia() -> Self {
+        Self::with_colors([
+            ("#E69F00", "#FFC04D"),
+            ("#0072B2", "#3399DD"),
+            ("#F0E442", "#F7F08A"),
+            ("#56B4E9", "#8FD0F5"),
+            ("#CC79A7", "#E0A3C8"),
+            ("#009E73", "#33C9A0"),
+        ])
+    }
+
+    /// a palette for blue-yellow color blindness:
+    /// yellow and blue are replaced by pink and mauve, green and cyan by teals
+    fn tritanopia() -> Self {
+        Self::with_colors([
+            ("#D55E00", "#FF7F3F"),
+            ("#009E9E", "#33C4C4"),
+            ("#F28AB3", "#F7B6CF"),
+            ("#B07AA1", "#CFA3C3"),
+            ("#E04E8A", "#F07AAA"),
+            ("#4DD2D2", "#8AE6E6"),
+        ])
+    }
+}
+
 impl Default for Palette {
     fn default() -> Self {
         Self {
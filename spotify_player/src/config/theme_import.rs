@@ -0,0 +1,255 @@
+//! Importers of color scheme files from other theme collections (base16, Catppuccin and Gruvbox)
+//! into the application's theme format.
+//!
+//! A scheme file is read as a flat list of named colors, whose names decide the scheme's format:
+//! - base16 (and base24) schemes define `base00` to `base0F` (`base17` for base24)
+//! - Catppuccin flavors define `base`, `text`, `mauve`, `rosewater`, etc.
+//! - Gruvbox palettes define `dark0`, `light1`, `bright_red`, `neutral_red`, etc.
+//!
+//! The colors are read from YAML/TOML-like `key: value` (or `key = value`) lines,
+//! or from JSON files, in which a color can also be an object with a `hex` field.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use super::theme::{add_theme, Theme, ThemeConfig};
+
+/// Import a scheme file as a new theme, which is added to the theme config file in `config_folder`
+pub fn import_theme(file: &Path, config_folder: &Path) -> Result<Theme> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("failed to read the scheme file {}", file.display()))?;
+    let colors = parse_colors(&content);
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let palette = if colors.get("base0F").is_some() {
+        base16_palette(&colors)
+    } else if colors.get("mauve").is_some() {
+        catppuccin_palette(&colors)
+    } else if colors.get("dark0").is_some() {
+        gruvbox_palette(&colors, stem.to_lowercase().contains("light"))
+    } else {
+        bail!("unsupported scheme file: expected a base16, Catppuccin or Gruvbox scheme");
+    };
+    let name = colors
+        .name
+        .clone()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(stem);
+
+    let existing_themes = ThemeConfig::new(config_folder)?;
+    if existing_themes.find_theme(&name).is_some() {
+        bail!("a theme named \"{name}\" already exists");
+    }
+
+    add_theme(config_folder, &name, &palette)
+}
+
+/// the named colors of a scheme file
+#[derive(Default)]
+struct Colors {
+    name: Option<String>,
+    colors: Vec<(String, String)>,
+}
+
+impl Colors {
+    fn get(&self, key: &str) -> Option<String> {
+        self.colors
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, c)| c.clone())
+    }
+
+    /// get the first defined color of the given keys
+    fn first(&self, keys: &[&str]) -> Option<String> {
+        keys.iter().find_map(|k| self.get(k))
+    }
+
+    fn add(&mut self, key: &str, value: &str) {
+        let key = key.trim().trim_matches(['"', '\'']).replace('-', "_");
+        let value = value.trim().trim_matches(['"', '\'', ',']).trim();
+        if matches!(key.as_str(), "scheme" | "name") {
+            if self.name.is_none() {
+                self.name = Some(value.to_string());
+            }
+        } else if let Some(color) = normalize_hex_color(value) {
+            // the first definition wins, e.g. the first flavor of a multi-flavor file
+            if self.get(&key).is_none() {
+                self.colors.push((key, color));
+            }
+        }
+    }
+}
+
+/// Parse the named colors of a scheme file
+fn parse_colors(content: &str) -> Colors {
+    let mut colors = Colors::default();
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(content) {
+        add_json_colors(&mut colors, None, &json);
+        return colors;
+    }
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once([':', '=']) {
+            // remove the trailing comments
+            let value = value.trim();
+            let value = value.split(" #").next().unwrap_or(value);
+            colors.add(key, value);
+        }
+    }
+    colors
+}
+
+fn add_json_colors(colors: &mut Colors, key: Option<&str>, value: &serde_json::Value) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Some(key) = key {
+                colors.add(key, s);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            // a color defined as an object, e.g. Catppuccin's `"base": {"hex": "#1e1e2e", ...}`
+            if let (Some(key), Some(serde_json::Value::String(hex))) = (key, map.get("hex")) {
+                colors.add(key, hex);
+                return;
+            }
+            for (k, v) in map {
+                add_json_colors(colors, Some(k), v);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Normalize a hex color (with or without the leading `#`) into the `#rrggbb` format
+fn normalize_hex_color(value: &str) -> Option<String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(format!("#{}", hex.to_lowercase()))
+    } else {
+        None
+    }
+}
+
+/// Construct a palette from the named colors, given a list of (palette's field, scheme's color keys)
+fn palette(colors: &Colors, fields: &[(&'static str, &[&str])]) -> Vec<(&'static str, String)> {
+    fields
+        .iter()
+        .filter_map(|(field, keys)| Some((*field, colors.first(keys)?)))
+        .collect()
+}
+
+/// Map a base16 scheme to a palette following the base16 styling guidelines,
+/// with the bright colors of base24 schemes (or the normal colors for base16 schemes)
+fn base16_palette(colors: &Colors) -> Vec<(&'static str, String)> {
+    palette(
+        colors,
+        &[
+            ("background", &["base00"]),
+            ("foreground", &["base05"]),
+            ("black", &["base00"]),
+            ("red", &["base08"]),
+            ("green", &["base0B"]),
+            ("yellow", &["base0A"]),
+            ("blue", &["base0D"]),
+            ("magenta", &["base0E"]),
+            ("cyan", &["base0C"]),
+            ("white", &["base05"]),
+            ("bright_black", &["base03"]),
+            ("bright_red", &["base12", "base08"]),
+            ("bright_green", &["base14", "base0B"]),
+            ("bright_yellow", &["base13", "base0A"]),
+            ("bright_blue", &["base16", "base0D"]),
+            ("bright_magenta", &["base17", "base0E"]),
+            ("bright_cyan", &["base15", "base0C"]),
+            ("bright_white", &["base07"]),
+        ],
+    )
+}
+
+/// Map a Catppuccin flavor to a palette following Catppuccin's terminal ports
+fn catppuccin_palette(colors: &Colors) -> Vec<(&'static str, String)> {
+    palette(
+        colors,
+        &[
+            ("background", &["base"]),
+            ("foreground", &["text"]),
+            ("black", &["surface1"]),
+            ("red", &["red"]),
+            ("green", &["green"]),
+            ("yellow", &["yellow"]),
+            ("blue", &["blue"]),
+            ("magenta", &["pink"]),
+            ("cyan", &["teal"]),
+            ("white", &["subtext1"]),
+            ("bright_black", &["surface2"]),
+            ("bright_red", &["red"]),
+            ("bright_green", &["green"]),
+            ("bright_yellow", &["yellow"]),
+            ("bright_blue", &["blue"]),
+            ("bright_magenta", &["pink"]),
+            ("bright_cyan", &["teal"]),
+            ("bright_white", &["subtext0"]),
+        ],
+    )
+}
+
+/// Map a Gruvbox palette to its dark (or light) terminal colors
+fn gruvbox_palette(colors: &Colors, light: bool) -> Vec<(&'static str, String)> {
+    if light {
+        palette(
+            colors,
+            &[
+                ("background", &["light0"]),
+                ("foreground", &["dark1"]),
+                ("black", &["light0"]),
+                ("red", &["neutral_red"]),
+                ("green", &["neutral_green"]),
+                ("yellow", &["neutral_yellow"]),
+                ("blue", &["neutral_blue"]),
+                ("magenta", &["neutral_purple"]),
+                ("cyan", &["neutral_aqua"]),
+                ("white", &["dark4"]),
+                ("bright_black", &["gray_244", "gray"]),
+                ("bright_red", &["faded_red"]),
+                ("bright_green", &["faded_green"]),
+                ("bright_yellow", &["faded_yellow"]),
+                ("bright_blue", &["faded_blue"]),
+                ("bright_magenta", &["faded_purple"]),
+                ("bright_cyan", &["faded_aqua"]),
+                ("bright_white", &["dark1"]),
+            ],
+        )
+    } else {
+        palette(
+            colors,
+            &[
+                ("background", &["dark0"]),
+                ("foreground", &["light1"]),
+                ("black", &["dark0"]),
+                ("red", &["neutral_red"]),
+                ("green", &["neutral_green"]),
+                ("yellow", &["neutral_yellow"]),
+                ("blue", &["neutral_blue"]),
+                ("magenta", &["neutral_purple"]),
+                ("cyan", &["neutral_aqua"]),
+                ("white", &["light4"]),
+                ("bright_black", &["gray_245", "gray"]),
+                ("bright_red", &["bright_red"]),
+                ("bright_green", &["bright_green"]),
+                ("bright_yellow", &["bright_yellow"]),
+                ("bright_blue", &["bright_blue"]),
+                ("bright_magenta", &["bright_purple"]),
+                ("bright_cyan", &["bright_aqua"]),
+                ("bright_white", &["light1"]),
+            ],
+        )
+    }
+}
@@ -0,0 +1,715 @@
+//! A local `SQLite` database storing the application's persistent data, which includes
+//! - the listen history
+//! - track annotations (star ratings and freeform tags)
+//! - episode bookmarks
+//! - the blocklist
+//! - read markers of the artist feed
+//! - playlist preferences (track order and shuffle state)
+//! - visualization preferences
+//! - caches of the user's data (playlists, saved albums, etc)
+//!
+//! The database is located at `spotify_player.db` in the application's cache folder.
+//! Its schema is versioned by `SQLite`'s `user_version` and upgraded upon opening by running
+//! pending [`MIGRATIONS`].
+//!
+//! Writes done while handling the user's actions are sent to a dedicated writer thread
+//! (see [`write_in_background`]), so that a busy database doesn't stall the application
+//! while its state is locked.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use parking_lot::Mutex;
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, Transaction};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::state::{BlockedItem, EpisodeBookmark, PlaylistPreference, TrackAnnotation};
+
+const DB_FILE_NAME: &str = "spotify_player.db";
+
+/// A write into the database, described by a message logged if the write fails
+type WriteJob = (&'static str, Box<dyn FnOnce() -> Result<()> + Send>);
+
+/// The channel to the database writer thread, which runs the received writes in order
+static WRITER: LazyLock<flume::Sender<WriteJob>> = LazyLock::new(|| {
+    let (tx, rx) = flume::unbounded::<WriteJob>();
+    std::thread::Builder::new()
+        .name("db-writer".to_string())
+        .spawn(move || {
+            while let Ok((desc, write)) = rx.recv() {
+                if let Err(err) = write() {
+                    tracing::error!("Failed to {desc}: {err:#}");
+                }
+            }
+        })
+        .expect("spawn the database writer thread");
+    tx
+});
+
+/// The row of the listen history recorded for the track started last in this session, if any.
+///
+/// Only accessed by the writes recording the listen history, which run in the database writer thread.
+static LAST_RECORDED_LISTEN: Mutex<Option<i64>> = Mutex::new(None);
+
+type Migration = fn(&Transaction, &Path) -> Result<()>;
+
+/// Schema migrations. The `i`-th migration upgrades the schema from version `i` to `i + 1`.
+const MIGRATIONS: &[Migration] = &[
+    create_track_annotation_tables,
+    create_store_tables,
+    add_listen_history_unique_index,
+    create_visualization_preferences_table,
+    create_read_feed_items_table,
+    create_playlist_preferences_table,
+];
+
+/// JSON cache files used before the database was introduced, which are imported into the database
+const LEGACY_CACHE_FILES: [&str; 7] = [
+    "Playlists",
+    "FollowedArtists",
+    "SavedShows",
+    "SavedAlbums",
+    "SavedTracks",
+    "EpisodeBookmarks",
+    "Blocklist",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A listen (play) of a track
+pub struct Listen {
+    pub track_uri: String,
+    pub track_name: String,
+    pub artists: String,
+    pub album: String,
+    /// the time the track started playing, as a Unix timestamp in seconds
+    pub played_at: i64,
+    pub ms_played: i64,
+}
+
+#[derive(Debug, Serialize)]
+/// The user's data exported from the database
+pub struct Export {
+    pub listen_history: Vec<Listen>,
+    pub track_annotations: HashMap<String, TrackAnnotation>,
+    pub episode_bookmarks: HashMap<String, Vec<EpisodeBookmark>>,
+    pub blocklist: Vec<BlockedItem>,
+    pub playlist_preferences: HashMap<String, PlaylistPreference>,
+    /// visualization preferences, keyed by artists or genres
+    pub visualization_preferences: HashMap<String, serde_json::Value>,
+    pub read_feed_items: HashSet<String>,
+}
+
+/// Open the local database, running pending schema migrations if any
+fn open(cache_folder: &Path) -> Result<Connection> {
+    let path = cache_folder.join(DB_FILE_NAME);
+    let mut conn = Connection::open(&path).with_context(|| format!("open {}", path.display()))?;
+    // the database can be accessed by multiple threads (each with its own connection) at the same time
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+
+    let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if (version as usize) < MIGRATIONS.len() {
+        let mut tx = conn.transaction()?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            tracing::info!("Migrating the database schema to version {}...", i + 1);
+            migration(&tx, cache_folder)
+                .with_context(|| format!("migrate the database schema to version {}", i + 1))?;
+        }
+        // the legacy cache files are imported when the tables storing their data are created
+        // (by the second migration)
+        let imported_files = if version < 2 {
+            import_legacy_cache_files(&mut tx, cache_folder)
+        } else {
+            Vec::new()
+        };
+        tx.pragma_update(None, "user_version", MIGRATIONS.len() as u32)?;
+        tx.commit()?;
+
+        // the imported legacy cache files are now stored in the database, so it's safe to remove them
+        for path in imported_files {
+            if let Err(err) = std::fs::remove_file(&path) {
+                tracing::warn!("Failed to remove {}: {err:#}", path.display());
+            }
+        }
+    }
+
+    Ok(conn)
+}
+
+/// Run a write into the database in the database writer thread.
+///
+/// Writes are run in the order they are submitted. A failed write is logged with its description.
+pub fn write_in_background(
+    desc: &'static str,
+    write: impl FnOnce() -> Result<()> + Send + 'static,
+) {
+    if WRITER.send((desc, Box::new(write))).is_err() {
+        tracing::error!("Failed to {desc}: the database writer has stopped");
+    }
+}
+
+/// Wait until all writes submitted to the database writer thread have been run
+pub fn flush_background_writes() {
+    let (tx, rx) = flume::bounded(1);
+    write_in_background("flush the database writes", move || {
+        tx.send(()).ok();
+        Ok(())
+    });
+    rx.recv().ok();
+}
+
+fn create_track_annotation_tables(tx: &Transaction, _cache_folder: &Path) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS track_ratings (
+            uri TEXT PRIMARY KEY,
+            rating INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS track_tags (
+            uri TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (uri, tag)
+        );",
+    )?;
+    Ok(())
+}
+
+fn create_store_tables(tx: &Transaction, _cache_folder: &Path) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE listen_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            track_uri TEXT NOT NULL,
+            track_name TEXT NOT NULL,
+            artists TEXT NOT NULL,
+            album TEXT NOT NULL,
+            played_at INTEGER NOT NULL,
+            ms_played INTEGER NOT NULL
+        );
+        CREATE INDEX listen_history_played_at ON listen_history (played_at);
+        CREATE TABLE episode_bookmarks (
+            episode_uri TEXT NOT NULL,
+            position_ms INTEGER NOT NULL,
+            note TEXT NOT NULL
+        );
+        CREATE TABLE blocklist (
+            uri TEXT PRIMARY KEY,
+            item TEXT NOT NULL
+        );
+        CREATE TABLE cache_entries (
+            key TEXT PRIMARY KEY,
+            data TEXT NOT NULL
+        );",
+    )?;
+
+    Ok(())
+}
+
+/// Import the legacy JSON cache files into the database, returning the imported files.
+///
+/// A file that can't be read or imported is skipped (and kept), so that it doesn't prevent the database from opening.
+fn import_legacy_cache_files(tx: &mut Transaction, cache_folder: &Path) -> Vec<PathBuf> {
+    let mut imported_files = Vec::new();
+    for name in LEGACY_CACHE_FILES {
+        let path = cache_folder.join(format!("{name}_cache.json"));
+        if !path.exists() {
+            continue;
+        }
+        tracing::info!("Importing {} into the database...", path.display());
+        let data = match std::fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) => {
+                tracing::warn!("Failed to read {}, skipping it: {err:#}", path.display());
+                continue;
+            }
+        };
+        // a file is imported in a savepoint, so that it's either imported entirely or not at all
+        let result = tx.savepoint().map_err(anyhow::Error::from).and_then(|sp| {
+            import_legacy_cache_file(&sp, name, data)?;
+            sp.commit()?;
+            Ok(())
+        });
+        match result {
+            Ok(()) => imported_files.push(path),
+            Err(err) => {
+                tracing::warn!("Failed to import {}, skipping it: {err:#}", path.display());
+            }
+        }
+    }
+    imported_files
+}
+
+/// Import the data of a legacy JSON cache file, which is parsed before anything is inserted
+fn import_legacy_cache_file(conn: &Connection, name: &str, data: String) -> Result<()> {
+    match name {
+        "EpisodeBookmarks" => {
+            let bookmarks: HashMap<String, Vec<EpisodeBookmark>> = serde_json::from_str(&data)?;
+            for (episode_uri, bookmarks) in bookmarks {
+                for bookmark in bookmarks {
+                    insert_episode_bookmark(conn, &episode_uri, &bookmark)?;
+                }
+            }
+        }
+        "Blocklist" => {
+            let items: Vec<BlockedItem> = serde_json::from_str(&data)?;
+            for item in items {
+                insert_blocked_item(conn, &item)?;
+            }
+        }
+        _ => {
+            // the caches are parsed when loaded, an invalid one is only checked to be JSON
+            serde_json::from_str::<serde_json::Value>(&data)?;
+            conn.execute(
+                "INSERT OR REPLACE INTO cache_entries (key, data) VALUES (?1, ?2)",
+                (name, data),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Make listens unique by their tracks and start times, so the same listen can't be recorded
+/// (or imported) twice
+fn add_listen_history_unique_index(tx: &Transaction, _cache_folder: &Path) -> Result<()> {
+    tx.execute_batch(
+        "DELETE FROM listen_history WHERE id NOT IN (
+            SELECT MIN(id) FROM listen_history GROUP BY track_uri, played_at
+        );
+        CREATE UNIQUE INDEX listen_history_track_played_at
+            ON listen_history (track_uri, played_at);",
+    )?;
+    Ok(())
+}
+
+fn create_visualization_preferences_table(tx: &Transaction, _cache_folder: &Path) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE visualization_preferences (
+            key TEXT PRIMARY KEY,
+            preference TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+fn create_read_feed_items_table(tx: &Transaction, _cache_folder: &Path) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE read_feed_items (
+            key TEXT PRIMARY KEY
+        );",
+    )?;
+    Ok(())
+}
+
+fn create_playlist_preferences_table(tx: &Transaction, _cache_folder: &Path) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE playlist_preferences (
+            uri TEXT PRIMARY KEY,
+            preference TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+/// Load annotations of all annotated tracks, keyed by track URIs
+pub fn load_track_annotations(cache_folder: &Path) -> Result<HashMap<String, TrackAnnotation>> {
+    let conn = open(cache_folder)?;
+    let mut annotations = HashMap::<String, TrackAnnotation>::new();
+
+    let mut stmt = conn.prepare("SELECT uri, rating FROM track_ratings")?;
+    for row in stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, u8>(1)?))
+    })? {
+        let (uri, rating) = row?;
+        annotations.entry(uri).or_default().rating = Some(rating);
+    }
+
+    let mut stmt = conn.prepare("SELECT uri, tag FROM track_tags ORDER BY uri, tag")?;
+    for row in stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })? {
+        let (uri, tag) = row?;
+        annotations.entry(uri).or_default().tags.push(tag);
+    }
+
+    Ok(annotations)
+}
+
+/// Store the annotation of a track, replacing its existing annotation
+pub fn store_track_annotation(
+    cache_folder: &Path,
+    uri: &str,
+    annotation: &TrackAnnotation,
+) -> Result<()> {
+    let mut conn = open(cache_folder)?;
+    let tx = conn.transaction()?;
+    match annotation.rating {
+        Some(rating) => tx.execute(
+            "INSERT OR REPLACE INTO track_ratings (uri, rating) VALUES (?1, ?2)",
+            (uri, rating),
+        )?,
+        None => tx.execute("DELETE FROM track_ratings WHERE uri = ?1", [uri])?,
+    };
+    tx.execute("DELETE FROM track_tags WHERE uri = ?1", [uri])?;
+    for tag in &annotation.tags {
+        tx.execute(
+            "INSERT OR IGNORE INTO track_tags (uri, tag) VALUES (?1, ?2)",
+            (uri, tag),
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Load bookmarks of all episodes, keyed by episode URIs
+pub fn load_episode_bookmarks(
+    cache_folder: &Path,
+) -> Result<HashMap<String, Vec<EpisodeBookmark>>> {
+    let conn = open(cache_folder)?;
+    let mut bookmarks = HashMap::<String, Vec<EpisodeBookmark>>::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT episode_uri, position_ms, note FROM episode_bookmarks ORDER BY position_ms",
+    )?;
+    for row in stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            EpisodeBookmark {
+                position_ms: row.get(1)?,
+                note: row.get(2)?,
+            },
+        ))
+    })? {
+        let (episode_uri, bookmark) = row?;
+        bookmarks.entry(episode_uri).or_default().push(bookmark);
+    }
+
+    Ok(bookmarks)
+}
+
+fn insert_episode_bookmark(
+    conn: &Connection,
+    episode_uri: &str,
+    bookmark: &EpisodeBookmark,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO episode_bookmarks (episode_uri, position_ms, note) VALUES (?1, ?2, ?3)",
+        (episode_uri, bookmark.position_ms, &bookmark.note),
+    )?;
+    Ok(())
+}
+
+/// Store a bookmark of an episode
+pub fn store_episode_bookmark(
+    cache_folder: &Path,
+    episode_uri: &str,
+    bookmark: &EpisodeBookmark,
+) -> Result<()> {
+    insert_episode_bookmark(&open(cache_folder)?, episode_uri, bookmark)
+}
+
+/// Load the blocklist, ordered by the time items were added
+pub fn load_blocklist(cache_folder: &Path) -> Result<Vec<BlockedItem>> {
+    let conn = open(cache_folder)?;
+    let mut stmt = conn.prepare("SELECT item FROM blocklist ORDER BY rowid")?;
+    let items = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .map(|item| Ok(serde_json::from_str(&item?)?))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(items)
+}
+
+fn insert_blocked_item(conn: &Connection, item: &BlockedItem) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO blocklist (uri, item) VALUES (?1, ?2)",
+        (item.uri(), serde_json::to_string(item)?),
+    )?;
+    Ok(())
+}
+
+/// Add an item to the blocklist
+pub fn store_blocked_item(cache_folder: &Path, item: &BlockedItem) -> Result<()> {
+    insert_blocked_item(&open(cache_folder)?, item)
+}
+
+/// Remove an item from the blocklist
+pub fn delete_blocked_item(cache_folder: &Path, uri: &str) -> Result<()> {
+    open(cache_folder)?.execute("DELETE FROM blocklist WHERE uri = ?1", [uri])?;
+    Ok(())
+}
+
+/// Load keys of the artist feed's read items
+pub fn load_read_feed_items(cache_folder: &Path) -> Result<HashSet<String>> {
+    let conn = open(cache_folder)?;
+    let mut stmt = conn.prepare("SELECT key FROM read_feed_items")?;
+    let keys = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<HashSet<_>>>()?;
+    Ok(keys)
+}
+
+/// Mark items of the artist feed as read
+pub fn store_read_feed_items(cache_folder: &Path, keys: &[String]) -> Result<()> {
+    let mut conn = open(cache_folder)?;
+    let tx = conn.transaction()?;
+    for key in keys {
+        tx.execute(
+            "INSERT OR IGNORE INTO read_feed_items (key) VALUES (?1)",
+            [key],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Load preferences of all playlists, keyed by playlist URIs
+pub fn load_playlist_preferences(
+    cache_folder: &Path,
+) -> Result<HashMap<String, PlaylistPreference>> {
+    let conn = open(cache_folder)?;
+    let mut stmt = conn.prepare("SELECT uri, preference FROM playlist_preferences")?;
+    let preferences = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .map(|row| {
+            let (uri, preference) = row?;
+            Ok((uri, serde_json::from_str(&preference)?))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+    Ok(preferences)
+}
+
+/// Store the preference of a playlist, replacing its existing preference
+pub fn store_playlist_preference(
+    cache_folder: &Path,
+    uri: &str,
+    preference: PlaylistPreference,
+) -> Result<()> {
+    let conn = open(cache_folder)?;
+    if preference.is_empty() {
+        conn.execute("DELETE FROM playlist_preferences WHERE uri = ?1", [uri])?;
+    } else {
+        conn.execute(
+            "INSERT OR REPLACE INTO playlist_preferences (uri, preference) VALUES (?1, ?2)",
+            (uri, serde_json::to_string(&preference)?),
+        )?;
+    }
+    Ok(())
+}
+
+/// Load a cache entry
+pub fn load_cache_entry<T: DeserializeOwned>(cache_folder: &Path, key: &str) -> Result<Option<T>> {
+    let data = open(cache_folder)?
+        .query_row(
+            "SELECT data FROM cache_entries WHERE key = ?1",
+            [key],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?;
+    match data {
+        Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+        None => Ok(None),
+    }
+}
+
+/// Store a cache entry, replacing the existing entry with the same key
+pub fn store_cache_entry<T: Serialize>(cache_folder: &Path, key: &str, data: &T) -> Result<()> {
+    open(cache_folder)?.execute(
+        "INSERT OR REPLACE INTO cache_entries (key, data) VALUES (?1, ?2)",
+        (key, serde_json::to_string(data)?),
+    )?;
+    Ok(())
+}
+
+/// Load the visualization preference of the first key (e.g. an artist or a genre) having one
+#[cfg(feature = "fx")]
+pub fn load_visualization_preference<T: DeserializeOwned>(
+    cache_folder: &Path,
+    keys: &[String],
+) -> Result<Option<T>> {
+    let conn = open(cache_folder)?;
+    let mut stmt =
+        conn.prepare("SELECT preference FROM visualization_preferences WHERE key = ?1")?;
+    for key in keys {
+        if let Some(preference) = stmt
+            .query_row([key], |row| row.get::<_, String>(0))
+            .optional()?
+        {
+            return Ok(Some(serde_json::from_str(&preference)?));
+        }
+    }
+    Ok(None)
+}
+
+/// Store a visualization preference, replacing the existing preference with the same key
+#[cfg(feature = "fx")]
+pub fn store_visualization_preference<T: Serialize>(
+    cache_folder: &Path,
+    key: &str,
+    preference: &T,
+) -> Result<()> {
+    open(cache_folder)?.execute(
+        "INSERT OR REPLACE INTO visualization_preferences (key, preference) VALUES (?1, ?2)",
+        (key, serde_json::to_string(preference)?),
+    )?;
+    Ok(())
+}
+
+/// Record a newly started track in the listen history, given the time (as a Unix timestamp in seconds)
+/// it started playing.
+///
+/// The played duration of the previously recorded track is updated to the time
+/// until the new track started (capped by the previous track's duration).
+/// Nothing is recorded in a private session. The write is done in the database writer thread.
+pub fn record_track_start(
+    cache_folder: &Path,
+    track: &rspotify::model::FullTrack,
+    started_at: i64,
+) {
+    if crate::state::is_private_session() {
+        return;
+    }
+    let Some(id) = &track.id else {
+        return;
+    };
+
+    let cache_folder = cache_folder.to_path_buf();
+    let listen = (
+        rspotify::prelude::Id::uri(id),
+        track.name.clone(),
+        crate::utils::map_join(&track.artists, |a| &a.name, ", "),
+        track.album.name.clone(),
+        started_at,
+        // the track's duration, which is updated when the next track starts
+        track.duration.num_milliseconds(),
+    );
+    write_in_background("record the track in the listen history", move || {
+        let mut last_recorded_listen = LAST_RECORDED_LISTEN.lock();
+        let mut conn = open(&cache_folder)?;
+        let tx = conn.transaction()?;
+        if let Some(row) = *last_recorded_listen {
+            tx.execute(
+                "UPDATE listen_history SET ms_played = MIN(ms_played, (?1 - played_at) * 1000)
+                WHERE id = ?2",
+                (started_at, row),
+            )?;
+        }
+        let n_inserted = tx.execute(
+            "INSERT OR IGNORE INTO listen_history
+            (track_uri, track_name, artists, album, played_at, ms_played)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            listen,
+        )?;
+        let row = (n_inserted > 0).then(|| tx.last_insert_rowid());
+        tx.commit()?;
+        *last_recorded_listen = row;
+        Ok(())
+    });
+}
+
+/// Record the played duration of the last recorded track if it's the given track,
+/// e.g. when the application quits while the track is playing.
+///
+/// Nothing is recorded in a private session. The write is done in the database writer thread.
+pub fn record_listen_end(cache_folder: &Path, track_uri: String, ms_played: i64) {
+    if crate::state::is_private_session() {
+        return;
+    }
+
+    let cache_folder = cache_folder.to_path_buf();
+    write_in_background("record the listen's end into the database", move || {
+        let Some(row) = *LAST_RECORDED_LISTEN.lock() else {
+            return Ok(());
+        };
+        let conn = open(&cache_folder)?;
+        conn.execute(
+            "UPDATE listen_history SET ms_played = MIN(ms_played, ?2)
+            WHERE id = ?3 AND track_uri = ?1",
+            (track_uri, ms_played, row),
+        )?;
+        Ok(())
+    });
+}
+
+/// Insert listens into the listen history, ignoring already recorded listens.
+///
+/// Returns the number of inserted listens.
+pub fn insert_listens(cache_folder: &Path, listens: &[Listen]) -> Result<usize> {
+    let mut conn = open(cache_folder)?;
+    let tx = conn.transaction()?;
+    let mut n_inserted = 0;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT OR IGNORE INTO listen_history
+            (track_uri, track_name, artists, album, played_at, ms_played)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for listen in listens {
+            n_inserted += stmt.execute((
+                &listen.track_uri,
+                &listen.track_name,
+                &listen.artists,
+                &listen.album,
+                listen.played_at,
+                listen.ms_played,
+            ))?;
+        }
+    }
+    tx.commit()?;
+    Ok(n_inserted)
+}
+
+/// Load the listen history, ordered by the time tracks were played
+pub fn load_listen_history(cache_folder: &Path) -> Result<Vec<Listen>> {
+    let conn = open(cache_folder)?;
+    let mut stmt = conn.prepare(
+        "SELECT track_uri, track_name, artists, album, played_at, ms_played
+        FROM listen_history ORDER BY played_at",
+    )?;
+    let listens = stmt
+        .query_map([], |row| {
+            Ok(Listen {
+                track_uri: row.get(0)?,
+                track_name: row.get(1)?,
+                artists: row.get(2)?,
+                album: row.get(3)?,
+                played_at: row.get(4)?,
+                ms_played: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(listens)
+}
+
+/// Export the user's data (excluding caches) from the database
+pub fn export(cache_folder: &Path) -> Result<Export> {
+    Ok(Export {
+        listen_history: load_listen_history(cache_folder)?,
+        track_annotations: load_track_annotations(cache_folder)?,
+        episode_bookmarks: load_episode_bookmarks(cache_folder)?,
+        blocklist: load_blocklist(cache_folder)?,
+        playlist_preferences: load_playlist_preferences(cache_folder)?,
+        visualization_preferences: load_visualization_preferences(cache_folder)?,
+        read_feed_items: load_read_feed_items(cache_folder)?,
+    })
+}
+
+/// Load all visualization preferences, keyed by artists or genres
+fn load_visualization_preferences(
+    cache_folder: &Path,
+) -> Result<HashMap<String, serde_json::Value>> {
+    let conn = open(cache_folder)?;
+    let mut stmt = conn.prepare("SELECT key, preference FROM visualization_preferences")?;
+    let preferences = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .map(|row| {
+            let (key, preference) = row?;
+            Ok((key, serde_json::from_str(&preference)?))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+    Ok(preferences)
+}
@@ -19,6 +19,10 @@ struct CommandProvider {
 #[cfg(not(target_os = "windows"))]
 struct NopProvider {}
 
+/// A provider copying to the terminal's clipboard with the `OSC 52` escape sequence,
+/// which also works over SSH. Pasting is not supported.
+struct Osc52Provider {}
+
 #[cfg(target_os = "windows")]
 struct WindowsProvider {}
 
@@ -61,6 +65,21 @@ impl ClipboardProvider for NopProvider {
     }
 }
 
+impl ClipboardProvider for Osc52Provider {
+    fn get_contents(&self) -> Result<String> {
+        anyhow::bail!("pasting from the terminal's clipboard (OSC 52) is not supported!")
+    }
+    fn set_contents(&self, contents: String) -> Result<()> {
+        use base64::Engine;
+
+        let contents = base64::engine::general_purpose::STANDARD.encode(contents);
+        let mut stdout = std::io::stdout().lock();
+        write!(stdout, "\x1b]52;c;{contents}\x07")?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
 #[cfg(target_os = "windows")]
 impl ClipboardProvider for WindowsProvider {
     fn get_contents(&self) -> Result<String> {
@@ -79,7 +98,11 @@ impl ClipboardProvider for WindowsProvider {
 // The function's implementation is inspired by helix
 // (https://github.com/blaggacao/helix/blob/master/helix-view/src/clipboard.rs)
 pub fn get_clipboard_provider() -> Box<dyn ClipboardProvider> {
-    if binary_exists("pbcopy") && binary_exists("pbpaste") {
+    let osc52 = crate::capabilities::get().osc52;
+    // in a SSH session, the clipboard commands would copy to the remote machine's clipboard
+    if osc52 && env_var_is_set("SSH_TTY") {
+        Box::new(Osc52Provider {})
+    } else if binary_exists("pbcopy") && binary_exists("pbpaste") {
         Box::new(CommandProvider {
             paste_command: Command::new::<_, &str>("pbpaste", &[]),
             copy_command: Command::new::<_, &str>("pbcopy", &[]),
@@ -102,6 +125,8 @@ pub fn get_clipboard_provider() -> Box<dyn ClipboardProvider> {
             paste_command: Command::new("xsel", &["-o", "-b"]),
             copy_command: Command::new("xsel", &["--nodetach", "-i", "-b"]),
         })
+    } else if osc52 && !cfg!(target_os = "windows") {
+        Box::new(Osc52Provider {})
     } else {
         #[cfg(target_os = "windows")]
         return Box::new(WindowsProvider {});
@@ -0,0 +1,137 @@
+//! Editing a playlist's tracks in an external text editor, similar to `git rebase -i`.
+//!
+//! The playlist's tracks are written into a text file, one track per line, which is opened
+//! in the user's editor (`$VISUAL` or `$EDITOR`). After the editor exits, the file's lines
+//! determine the playlist's new track order, and the removed lines determine the deleted tracks.
+
+use std::{collections::HashMap, fmt::Write};
+
+use anyhow::{Context as _, Result};
+
+use crate::{
+    client::ClientRequest,
+    state::{Context, ContextId, Id, Playlist, SharedState, Track, TrackId},
+};
+
+/// Open a playlist's tracks in the user's editor and apply the edited tracks to the playlist.
+///
+/// The function must be called without holding the application's states' locks,
+/// as it blocks until the editor exits.
+pub fn edit_playlist_tracks(
+    playlist: &Playlist,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+) -> Result<()> {
+    let tracks = state
+        .data
+        .read()
+        .caches
+        .context
+        .get(&playlist.id.uri())
+        .and_then(|c| match c {
+            Context::Playlist { tracks, .. } => Some(tracks.clone()),
+            _ => None,
+        });
+    let Some(tracks) = tracks else {
+        client_pub.send(ClientRequest::GetContext(ContextId::Playlist(
+            playlist.id.clone(),
+        )))?;
+        anyhow::bail!(
+            "tracks of playlist {} aren't loaded yet, please try again",
+            playlist.name
+        );
+    };
+
+    let path =
+        std::env::temp_dir().join(format!("spotify_player_playlist_{}.txt", playlist.id.id()));
+    std::fs::write(&path, playlist_file_content(playlist, &tracks))
+        .context("write the playlist's tracks into a file")?;
+
+    // the terminal is handed over to the editor, during which the application's UI isn't rendered
+    state.ui.lock().is_terminal_suspended = true;
+    let result = crate::ui::suspend_terminal().and_then(|()| {
+        let status = run_editor(&path);
+        crate::ui::resume_terminal()?;
+        status
+    });
+    state.ui.lock().is_terminal_suspended = false;
+    result?;
+
+    let content = std::fs::read_to_string(&path).context("read the edited playlist's file")?;
+    std::fs::remove_file(&path).ok();
+
+    let track_ids = parse_playlist_file_content(&content, &tracks);
+    if !track_ids.is_empty() && !track_ids.iter().eq(tracks.iter().map(|t| &t.id)) {
+        client_pub.send(ClientRequest::EditPlaylistTracks {
+            playlist: playlist.clone(),
+            track_ids,
+        })?;
+    }
+    Ok(())
+}
+
+/// Run the user's editor on a file, waiting for the editor to exit
+fn run_editor(path: &std::path::Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // the editor can be specified with arguments, e.g. `code --wait`
+    let mut args = editor.split_whitespace();
+    let program = args.next().context("empty editor command")?;
+
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("run the editor `{editor}`"))?;
+    if !status.success() {
+        anyhow::bail!("the editor `{editor}` exited with {status}");
+    }
+    Ok(())
+}
+
+fn playlist_file_content(playlist: &Playlist, tracks: &[Track]) -> String {
+    let mut content = format!(
+        "# Editing the tracks of playlist \"{}\"\n\
+         #\n\
+         # Reorder the lines to reorder the tracks, or delete a line to remove its track.\n\
+         # Lines starting with '#' are ignored, so are added lines of unknown tracks.\n\
+         # Save the file without changes, or delete all the lines, to abort the editing.\n\n",
+        playlist.name
+    );
+    for track in tracks {
+        writeln!(
+            content,
+            "{}  {} - {}",
+            track.id.uri(),
+            track.name,
+            track.artists_info()
+        )
+        .expect("writing into a string should succeed");
+    }
+    content
+}
+
+/// Parse the tracks from an edited playlist's file. Only tracks of the original playlist are
+/// accepted, each track at most as many times as it occurs in the original playlist.
+fn parse_playlist_file_content(content: &str, tracks: &[Track]) -> Vec<TrackId<'static>> {
+    let mut remaining_occurrences = HashMap::<String, usize>::new();
+    for track in tracks {
+        *remaining_occurrences.entry(track.id.uri()).or_default() += 1;
+    }
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| {
+            let uri = l.split_whitespace().next()?;
+            let remaining = remaining_occurrences.get_mut(uri).filter(|n| **n > 0)?;
+            *remaining -= 1;
+            tracks
+                .iter()
+                .find(|t| t.id.uri() == uri)
+                .map(|t| t.id.clone())
+        })
+        .collect()
+}
@@ -7,15 +7,22 @@ use crate::{
     config,
     key::{Key, KeySequence},
     state::{
-        ActionListItem, Album, AlbumId, Artist, ArtistFocusState, ArtistId, ArtistPopupAction,
-        BrowsePageUIState, Context, ContextId, ContextPageType, ContextPageUIState, DataReadGuard,
-        Focusable, Id, Item, ItemId, LibraryFocusState, LibraryPageUIState, PageState, PageType,
-        PlayableId, Playback, PlaylistCreateCurrentField, PlaylistFolderItem, PlaylistId,
-        PlaylistPopupAction, PopupState, SearchFocusState, SearchPageUIState, SharedState, ShowId,
-        Track, TrackId, TrackOrder, UIStateGuard, USER_LIKED_TRACKS_ID,
+        ABLoop, ActionListItem, Album, AlbumId, Artist, ArtistFocusState, ArtistId,
+        ArtistPopupAction, Audition, BlockedItem, BrowsePageUIState, Context, ContextId,
+        ContextPageType, ContextPageUIState, DataReadGuard, EpisodeBookmark, FeedItem, Focusable,
+        Id, Item, ItemId, LibraryFocusState, LibraryPageUIState, PageState, PageType, PlayableId,
+        Playback, PlayerState, PlaylistCreateCurrentField, PlaylistDiffFocusState,
+        PlaylistDiffPageUIState, PlaylistFolderItem, PlaylistId, PlaylistMergeOrder,
+        PlaylistPopupAction, PlaylistPreference, PopupState, SearchFocusState, SearchPageUIState,
+        SharedPlaylistsFocusState, SharedPlaylistsPageUIState, SharedState, ShowId, Track,
+        TrackOrder, TrackPreview, TrackRemoval, UIStateGuard, USER_LIKED_TRACKS_ID,
         USER_RECENTLY_PLAYED_TRACKS_ID, USER_TOP_TRACKS_ID,
     },
-    ui::{single_line_input::LineInput, Orientation},
+    ui::{
+        multi_line_input::MultiLineInput,
+        single_line_input::{InputEffect, LineInput},
+        Orientation,
+    },
     utils::parse_uri,
 };
 
@@ -24,9 +31,10 @@ use anyhow::{Context as _, Result};
 use crossterm::event::KeyCode;
 
 use clipboard::{execute_copy_command, get_clipboard_content};
-use ratatui::widgets::ListState;
+use ratatui::widgets::{ListState, TableState};
 
 mod clipboard;
+mod editor;
 mod page;
 mod popup;
 mod window;
@@ -37,6 +45,7 @@ pub fn start_event_handler(state: &SharedState, client_pub: &flume::Sender<Clien
         let _enter = tracing::info_span!("terminal_event", event = ?event).entered();
         if let Err(err) = match event {
             crossterm::event::Event::Mouse(event) => handle_mouse_event(event, client_pub, state),
+            crossterm::event::Event::Paste(text) => handle_paste_event(&text, client_pub, state),
             crossterm::event::Event::Resize(columns, rows) => {
                 state.ui.lock().orientation = Orientation::from_size(columns, rows);
                 Ok(())
@@ -59,6 +68,51 @@ pub fn start_event_handler(state: &SharedState, client_pub: &flume::Sender<Clien
     }
 }
 
+/// Handle a pasted text, which is inserted into the focused text input (if any)
+fn handle_paste_event(
+    text: &str,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+) -> Result<()> {
+    let mut ui = state.ui.lock();
+    if let Some(PopupState::PlaylistDescriptionEdit { desc, .. }) = ui.popup.as_mut() {
+        desc.insert_str(text);
+    } else if let Some(popup) = ui.popup.as_mut() {
+        let Some(input) = popup.line_input_mut() else {
+            return Ok(());
+        };
+        input.insert_str(text);
+        // the items filtered by the popup's query are selected from the first item
+        if matches!(popup, PopupState::Search { .. }) {
+            ui.current_page_mut().select(0);
+        } else {
+            popup.list_select(Some(0));
+        }
+    } else if let PageState::Search {
+        state:
+            SearchPageUIState {
+                focus: SearchFocusState::Input,
+                ..
+            },
+        line_input,
+        current_query,
+    } = ui.current_page_mut()
+    {
+        line_input.insert_str(text);
+        if !line_input.is_empty() {
+            *current_query = line_input.get_text();
+            client_pub.send(ClientRequest::Search(line_input.get_text()))?;
+        }
+    } else if crate::utils::is_spotify_link(text) {
+        // a Spotify link pasted outside of any input is opened
+        client_pub.send(ClientRequest::OpenSpotifyLink {
+            link: text.to_string(),
+            play: false,
+        })?;
+    }
+    Ok(())
+}
+
 // Handle a terminal mouse event
 fn handle_mouse_event(
     event: crossterm::event::MouseEvent,
@@ -69,8 +123,33 @@ fn handle_mouse_event(
     if let crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left) = event.kind
     {
         tracing::debug!("Handling mouse event: {event:?}");
-        let rect = state.ui.lock().playback_progress_bar_rect;
-        if event.row == rect.y {
+        {
+            let mut ui = state.ui.lock();
+            let position = ratatui::layout::Position::new(event.column, event.row);
+            if let Some(id) = ui
+                .breadcrumb_rects
+                .iter()
+                .position(|rect| rect.contains(position))
+            {
+                ui.go_back_to_page(id);
+                return Ok(());
+            }
+        }
+        let (rect, controls_rect) = {
+            let ui = state.ui.lock();
+            (ui.playback_progress_bar_rect, ui.compact_controls_rect)
+        };
+        if event.row == controls_rect.y
+            && (controls_rect.left()..controls_rect.right()).contains(&event.column)
+        {
+            // the compact mode's controls are rendered as "⏮ ⏯ ⏭"
+            let request = match (event.column - controls_rect.x) / 2 {
+                0 => PlayerRequest::PreviousTrack,
+                1 => PlayerRequest::ResumePause,
+                _ => PlayerRequest::NextTrack,
+            };
+            client_pub.send(ClientRequest::Player(request))?;
+        } else if event.row == rect.y {
             // calculate the seek position (in ms) based on the mouse click position,
             // the progress bar's width and the track's duration (in ms)
             let player = state.player.read();
@@ -83,7 +162,11 @@ fn handle_mouse_event(
                 let position_ms =
                     (duration.num_milliseconds()) * i64::from(event.column) / i64::from(rect.width);
                 client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
-                    chrono::Duration::try_milliseconds(position_ms).unwrap(),
+                    snap_seek_position(
+                        state,
+                        &player,
+                        chrono::Duration::try_milliseconds(position_ms).unwrap(),
+                    ),
                 )))?;
             }
         }
@@ -100,6 +183,10 @@ fn handle_key_event(
     let key: Key = event.into();
     let mut ui = state.ui.lock();
 
+    if let Some(keys) = ui.macro_recording.as_mut() {
+        keys.push(event);
+    }
+
     let mut key_sequence = ui.input_key_sequence.clone();
     key_sequence.keys.push(key);
 
@@ -130,9 +217,25 @@ fn handle_key_event(
             Some(CommandOrAction::Action(action, target)) => {
                 handle_global_action(action, target, client_pub, state, &mut ui)?
             }
+            // macro commands are handled separately as they need to know the matched key sequence
+            Some(CommandOrAction::Command(
+                command @ (Command::RecordMacro | Command::ReplayMacro),
+            )) => {
+                handle_macro_command(command, key_sequence.keys.len(), &mut ui);
+                true
+            }
             Some(CommandOrAction::Command(command)) => {
                 handle_global_command(command, client_pub, state, &mut ui)?
             }
+            #[cfg(feature = "scripting")]
+            None => match keymap_config.find_script_from_key_sequence(&key_sequence) {
+                Some(script) => {
+                    crate::scripting::run_script(script, client_pub.clone(), state.clone());
+                    true
+                }
+                None => false,
+            },
+            #[cfg(not(feature = "scripting"))]
             None => false,
         }
     };
@@ -165,9 +268,57 @@ fn handle_key_event(
             }
         }
     }
+
+    let playlist_to_edit = ui.pending_playlist_edit.take();
+    let n_replays = std::mem::take(&mut ui.pending_macro_replays);
+    drop(ui);
+
+    if let Some(playlist) = playlist_to_edit {
+        editor::edit_playlist_tracks(&playlist, client_pub, state)?;
+    }
+    if n_replays > 0 {
+        let mut ui = state.ui.lock();
+        let events = ui.last_macro.clone();
+        ui.is_replaying_macro = true;
+        drop(ui);
+
+        let result = (0..n_replays)
+            .flat_map(|_| events.iter())
+            .try_for_each(|event| handle_key_event(*event, client_pub, state));
+        state.ui.lock().is_replaying_macro = false;
+        result?;
+    }
     Ok(())
 }
 
+/// Handle a command to record or replay a macro.
+///
+/// `n_keys` is the number of keys of the key sequence matching the command,
+/// which are excluded from the recorded macro.
+fn handle_macro_command(command: Command, n_keys: usize, ui: &mut UIStateGuard) {
+    let count = ui.count_prefix;
+    // drop the keys of the macro commands (and their count prefixes) from the macro being recorded,
+    // so that a macro never records or replays another macro
+    if let Some(keys) = ui.macro_recording.as_mut() {
+        let n_keys = n_keys + count.map_or(0, |c| c.to_string().len());
+        keys.truncate(keys.len().saturating_sub(n_keys));
+    }
+
+    match command {
+        Command::RecordMacro => {
+            if let Some(keys) = ui.macro_recording.take() {
+                ui.last_macro = keys;
+            } else if !ui.is_replaying_macro {
+                ui.macro_recording = Some(vec![]);
+            }
+        }
+        Command::ReplayMacro if ui.macro_recording.is_none() && !ui.is_replaying_macro => {
+            ui.pending_macro_replays = count.unwrap_or(1);
+        }
+        _ => {}
+    }
+}
+
 pub fn handle_action_in_context(
     action: Action,
     context: ActionContext,
@@ -212,14 +363,15 @@ pub fn handle_action_in_context(
                     PlaylistPopupAction::AddTrack {
                         folder_id: 0,
                         track_id: track.id,
-                        search_query: String::new(),
+                        search_query: LineInput::default(),
+                        confirmed_playlist: None,
                     },
                     ListState::default(),
                 ));
                 Ok(true)
             }
             Action::ToggleLiked => {
-                if data.user_data.is_liked_track(&track) {
+                if data.is_liked_track(&track) {
                     client_pub.send(ClientRequest::DeleteFromLibrary(ItemId::Track(track.id)))?;
                 } else {
                     client_pub.send(ClientRequest::AddToLibrary(Item::Track(track)))?;
@@ -232,11 +384,65 @@ pub fn handle_action_in_context(
                 ui.popup = None;
                 Ok(true)
             }
-            Action::DeleteFromLiked => {
-                client_pub.send(ClientRequest::DeleteFromLibrary(ItemId::Track(track.id)))?;
+            Action::SetRating => {
+                ui.popup = Some(PopupState::TrackRating(
+                    track.id.uri(),
+                    ListState::default(),
+                ));
+                Ok(true)
+            }
+            Action::ShowTrackDetails => {
+                client_pub.send(ClientRequest::GetTrackDetails(track.id.clone()))?;
+                ui.popup = Some(PopupState::TrackDetails(
+                    track.id.uri(),
+                    ListState::default(),
+                ));
+                Ok(true)
+            }
+            Action::EditTags => {
+                let track_uri = track.id.uri();
+                let tags = data
+                    .user_data
+                    .track_annotations
+                    .get(&track_uri)
+                    .map(|a| a.tags.join(", "))
+                    .unwrap_or_default();
+                ui.popup = Some(PopupState::TrackTagsEdit {
+                    track_uri,
+                    tags: LineInput::new(tags.chars().collect()),
+                });
+                Ok(true)
+            }
+            Action::AddToBlocklist => {
+                client_pub.send(ClientRequest::AddToBlocklist(BlockedItem::Track {
+                    uri: track.id.uri(),
+                    name: track.name,
+                }))?;
                 ui.popup = None;
                 Ok(true)
             }
+            Action::DeleteFromLiked => {
+                ui.popup = Some(PopupState::TrackRemovalConfirmation(
+                    track,
+                    TrackRemoval::Liked,
+                ));
+                Ok(true)
+            }
+            Action::SearchForAlternative => {
+                let query = match track.artists.first() {
+                    Some(artist) => format!("{} {}", track.name, artist.name),
+                    None => track.name,
+                };
+                let mut state = SearchPageUIState::new();
+                state.focus = SearchFocusState::Tracks;
+                ui.new_page(PageState::Search {
+                    line_input: LineInput::new(query.chars().collect()),
+                    current_query: query.clone(),
+                    state,
+                });
+                client_pub.send(ClientRequest::Search(query))?;
+                Ok(true)
+            }
             Action::GoToRadio => {
                 let uri = track.id.uri();
                 let name = track.name;
@@ -266,17 +472,22 @@ pub fn handle_action_in_context(
                 Ok(false)
             }
             Action::DeleteFromPlaylist => {
-                if let PageState::Context {
-                    id: Some(ContextId::Playlist(playlist_id)),
-                    ..
-                } = ui.current_page()
-                {
-                    client_pub.send(ClientRequest::DeleteTrackFromPlaylist(
-                        playlist_id.clone_static(),
-                        track.id,
-                    ))?;
-                }
-                ui.popup = None;
+                ui.popup = match ui.current_page() {
+                    PageState::Context {
+                        id: Some(id @ ContextId::Playlist(playlist_id)),
+                        ..
+                    } => {
+                        let name = match data.caches.context.get(&id.uri()) {
+                            Some(Context::Playlist { playlist, .. }) => playlist.name.clone(),
+                            _ => "this playlist".to_string(),
+                        };
+                        Some(PopupState::TrackRemovalConfirmation(
+                            track,
+                            TrackRemoval::Playlist(playlist_id.clone_static(), name),
+                        ))
+                    }
+                    _ => None,
+                };
                 Ok(true)
             }
             _ => Ok(false),
@@ -324,6 +535,14 @@ pub fn handle_action_in_context(
             _ => Ok(false),
         },
         ActionContext::Artist(artist) => match action {
+            Action::AddToBlocklist => {
+                client_pub.send(ClientRequest::AddToBlocklist(BlockedItem::Artist {
+                    uri: artist.id.uri(),
+                    name: artist.name,
+                }))?;
+                ui.popup = None;
+                Ok(true)
+            }
             Action::Follow => {
                 client_pub.send(ClientRequest::AddToLibrary(Item::Artist(artist)))?;
                 ui.popup = None;
@@ -382,6 +601,53 @@ pub fn handle_action_in_context(
                 ui.popup = None;
                 Ok(true)
             }
+            Action::RenamePlaylist => {
+                ui.popup = Some(PopupState::PlaylistRename {
+                    name: LineInput::new(playlist.name.chars().collect()),
+                    playlist_id: playlist.id,
+                });
+                Ok(true)
+            }
+            Action::EditPlaylistDescription => {
+                ui.popup = Some(PopupState::PlaylistDescriptionEdit {
+                    desc: MultiLineInput::new(&playlist.desc),
+                    playlist_id: playlist.id,
+                });
+                Ok(true)
+            }
+            Action::EditPlaylistInEditor => {
+                ui.popup = None;
+                ui.pending_playlist_edit = Some(playlist);
+                Ok(true)
+            }
+            Action::MergePlaylists => {
+                // the playlist is chosen to be merged with the user's other playlists
+                let playlists = std::iter::once(playlist.clone())
+                    .chain(
+                        data.user_data
+                            .playlists
+                            .iter()
+                            .filter_map(|item| match item {
+                                PlaylistFolderItem::Playlist(p) if p.id != playlist.id => {
+                                    Some(p.clone())
+                                }
+                                _ => None,
+                            }),
+                    )
+                    .collect::<Vec<_>>();
+                let mut chosen = vec![false; playlists.len()];
+                chosen[0] = true;
+
+                client_pub.send(ClientRequest::GetUserPlaylists)?;
+                ui.popup = Some(PopupState::PlaylistMerge {
+                    playlists,
+                    chosen,
+                    order: PlaylistMergeOrder::Concatenate,
+                    deduplicate: true,
+                    list_state: ListState::default(),
+                });
+                Ok(true)
+            }
             _ => Ok(false),
         },
         ActionContext::Show(show) => match action {
@@ -435,12 +701,24 @@ pub fn handle_action_in_context(
                     PlaylistPopupAction::AddEpisode {
                         folder_id: 0,
                         episode_id: episode.id,
-                        search_query: String::new(),
+                        search_query: LineInput::default(),
                     },
                     ListState::default(),
                 ));
                 Ok(true)
             }
+            Action::AddToLibrary => {
+                client_pub.send(ClientRequest::AddToLibrary(Item::Episode(episode)))?;
+                ui.popup = None;
+                Ok(true)
+            }
+            Action::DeleteFromLibrary => {
+                client_pub.send(ClientRequest::DeleteFromLibrary(ItemId::Episode(
+                    episode.id,
+                )))?;
+                ui.popup = None;
+                Ok(true)
+            }
             Action::ShowActionsOnShow => {
                 if let Some(show) = episode.show {
                     let context = ActionContext::Show(show.clone());
@@ -501,6 +779,40 @@ fn handle_show_actions_on_artist(
 
 /// Handle a global action, currently this is only used to target
 /// the currently playing item instead of the selection.
+/// Handle a command or an action, which is handled by the current page first, then globally
+pub fn handle_command_or_action(
+    command_or_action: CommandOrAction,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    if page::handle_command_or_action_for_page(command_or_action, client_pub, state, ui)? {
+        return Ok(true);
+    }
+    match command_or_action {
+        CommandOrAction::Action(action, target) => {
+            handle_global_action(action, target, client_pub, state, ui)
+        }
+        CommandOrAction::Command(command) => handle_global_command(command, client_pub, state, ui),
+    }
+}
+
+/// Get the available actions on the currently playing track (or episode)
+fn playing_track_actions(state: &SharedState) -> Vec<Action> {
+    let player = state.player.read();
+    let data = state.data.read();
+    let context = match player.currently_playing() {
+        Some(rspotify::model::PlayableItem::Track(track)) => {
+            Track::try_from_full_track(track.clone()).map(ActionContext::Track)
+        }
+        Some(rspotify::model::PlayableItem::Episode(episode)) => {
+            Some(ActionContext::Episode(episode.clone().into()))
+        }
+        _ => None,
+    };
+    context.map_or_else(Vec::new, |c| c.get_available_actions(&data))
+}
+
 fn handle_global_action(
     action: Action,
     target: ActionTarget,
@@ -544,6 +856,69 @@ fn handle_global_action(
     Ok(false)
 }
 
+/// Snap a seek position to the nearest bar of the current track if `snap_seek_to_bar` is enabled
+fn snap_seek_position(
+    state: &SharedState,
+    player: &PlayerState,
+    position: chrono::Duration,
+) -> chrono::Duration {
+    if !config::get_config().app_config.snap_seek_to_bar {
+        return position;
+    }
+    match player.currently_playing_uri() {
+        Some(uri) => state.data.read().snap_to_nearest_bar(&uri, position),
+        None => position,
+    }
+}
+
+/// Export the palette of the current album's cover as a new theme and, if configured, a terminal
+/// color scheme in the `schemes` folder of the config folder. Returns the message shown to the user.
+#[cfg(all(feature = "fx", feature = "image"))]
+fn export_album_theme(state: &SharedState, ui: &UIStateGuard) -> Result<String> {
+    use crate::ui::visualizations;
+
+    let Some(album_color) = ui.current_album_color else {
+        anyhow::bail!(
+            "no color extracted from the album's cover (see the `use_album_colors` config option)"
+        );
+    };
+    let album = match state.player.read().currently_playing() {
+        Some(rspotify::model::PlayableItem::Track(track)) => track.album.name.clone(),
+        Some(rspotify::model::PlayableItem::Episode(episode)) => episode.show.name.clone(),
+        _ => "album".to_string(),
+    };
+
+    let configs = config::get_config();
+    let colors = visualizations::album_theme_colors(
+        &visualizations::album_palette(Some(album_color)),
+        ui.is_light_background,
+    );
+    let name = config::unique_theme_name(&configs.config_folder, &album)?;
+    config::add_theme(&configs.config_folder, &name, &colors)?;
+
+    let Some(scheme) = configs.app_config.album_theme_terminal_scheme else {
+        return Ok(format!("Exported theme {name}"));
+    };
+    let folder = configs.config_folder.join("schemes");
+    std::fs::create_dir_all(&folder)?;
+    let file_name = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    let path = folder.join(format!("{file_name}.{}", scheme.extension()));
+    std::fs::write(&path, config::terminal_scheme_content(scheme, &colors))?;
+    Ok(format!(
+        "Exported theme {name} and the {scheme:?} color scheme {}",
+        path.display()
+    ))
+}
+
 /// Handle a global command that is not specific to any page/popup
 fn handle_global_command(
     command: Command,
@@ -575,6 +950,29 @@ fn handle_global_command(
         }
         Command::Shuffle => {
             client_pub.send(ClientRequest::Player(PlayerRequest::Shuffle))?;
+
+            // remember the shuffle state of the playing playlist
+            let playing = {
+                let player = state.player.read();
+                player.playing_context_id().zip(
+                    player
+                        .buffered_playback
+                        .as_ref()
+                        .map(|playback| playback.shuffle_state),
+                )
+            };
+            if let Some((ContextId::Playlist(id), shuffle_state)) = playing {
+                let mut data = state.data.write();
+                let preference = PlaylistPreference {
+                    shuffle: Some(!shuffle_state),
+                    ..data.user_data.playlist_preference(&id)
+                };
+                data.user_data.set_playlist_preference(
+                    &id,
+                    preference,
+                    &config::get_config().cache_folder,
+                );
+            }
         }
         Command::VolumeChange { offset } => {
             if let Some(ref playback) = state.player.read().buffered_playback {
@@ -588,32 +986,120 @@ fn handle_global_command(
             client_pub.send(ClientRequest::Player(PlayerRequest::ToggleMute))?;
         }
         Command::SeekForward { duration } => {
-            if let Some(progress) = state.player.read().playback_progress() {
+            let player = state.player.read();
+            if let Some(progress) = player.playback_progress() {
                 let duration =
                     duration.unwrap_or(config::get_config().app_config.seek_duration_secs);
                 client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
-                    progress + chrono::Duration::try_seconds(i64::from(duration)).unwrap(),
+                    snap_seek_position(
+                        state,
+                        &player,
+                        progress + chrono::Duration::try_seconds(i64::from(duration)).unwrap(),
+                    ),
                 )))?;
             }
         }
         Command::SeekBackward { duration } => {
-            if let Some(progress) = state.player.read().playback_progress() {
+            let player = state.player.read();
+            if let Some(progress) = player.playback_progress() {
                 let duration =
                     duration.unwrap_or(config::get_config().app_config.seek_duration_secs);
                 client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
-                    std::cmp::max(
-                        chrono::Duration::zero(),
-                        progress - chrono::Duration::try_seconds(i64::from(duration)).unwrap(),
+                    snap_seek_position(
+                        state,
+                        &player,
+                        std::cmp::max(
+                            chrono::Duration::zero(),
+                            progress - chrono::Duration::try_seconds(i64::from(duration)).unwrap(),
+                        ),
                     ),
                 )))?;
             }
         }
+        Command::SetCuePoint => {
+            let mut player = state.player.write();
+            if let (Some(uri), Some(progress)) =
+                (player.currently_playing_uri(), player.playback_progress())
+            {
+                player.cue_point = Some((uri, progress));
+            }
+        }
+        Command::JumpToCuePoint => {
+            let player = state.player.read();
+            if let Some((uri, position)) = &player.cue_point {
+                if player.currently_playing_uri().as_ref() == Some(uri) {
+                    client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(*position)))?;
+                }
+            }
+        }
+        Command::PreviewNextTrack => {
+            let mut player = state.player.write();
+            if let Some(preview) = player.track_preview.take() {
+                // stop the ongoing preview early
+                client_pub.send(ClientRequest::Player(PlayerRequest::RevertTrackPreview(
+                    preview.previous,
+                )))?;
+            } else if let Some(previous) = player.last_playback_snapshot() {
+                player.track_preview = Some(TrackPreview {
+                    previous,
+                    started_at: std::time::Instant::now(),
+                });
+                client_pub.send(ClientRequest::Player(PlayerRequest::NextTrack))?;
+            }
+        }
+        Command::CommitTrackPreview => {
+            state.player.write().track_preview = None;
+        }
+        Command::ToggleAudition => {
+            let mut player = state.player.write();
+            if let Some(audition) = player.audition.take() {
+                if let Some(volume) = audition.faded_volume {
+                    client_pub.send(ClientRequest::Player(PlayerRequest::Volume(volume)))?;
+                }
+                ui.show_toast("Audition stopped".to_string());
+            } else if player.currently_playing_uri().is_some() {
+                player.audition = Some(Audition::default());
+                ui.show_toast("Auditioning the playing context".to_string());
+            }
+        }
+        Command::SetLoopPoint => {
+            let mut player = state.player.write();
+            if let (Some(uri), Some(progress)) =
+                (player.currently_playing_uri(), player.playback_progress())
+            {
+                player.ab_loop = match player.ab_loop.take() {
+                    Some(ABLoop {
+                        track_uri,
+                        a,
+                        b: None,
+                    }) if track_uri == uri => Some(ABLoop {
+                        track_uri,
+                        a: std::cmp::min(a, progress),
+                        b: Some(std::cmp::max(a, progress)),
+                    }),
+                    // clear the loop if both points are set
+                    Some(ABLoop { track_uri, .. }) if track_uri == uri => None,
+                    _ => Some(ABLoop {
+                        track_uri: uri,
+                        a: progress,
+                        b: None,
+                    }),
+                };
+            }
+        }
         Command::OpenCommandHelp => {
             ui.new_page(PageState::CommandHelp { scroll_offset: 0 });
         }
         Command::RefreshPlayback => {
             client_pub.send(ClientRequest::GetCurrentPlayback)?;
         }
+        Command::ContinueLastPlayback => {
+            if state.player.read().last_playback.is_some() {
+                client_pub.send(ClientRequest::ContinueLastPlayback)?;
+            } else {
+                ui.show_toast("No previous playback to continue".to_string());
+            }
+        }
         Command::ShowActionsOnCurrentTrack => {
             if let Some(currently_playing) = state.player.read().currently_playing() {
                 match currently_playing {
@@ -652,7 +1138,31 @@ fn handle_global_command(
             ui.popup = Some(PopupState::UserPlaylistList(
                 PlaylistPopupAction::Browse {
                     folder_id: 0,
-                    search_query: String::new(),
+                    search_query: LineInput::default(),
+                },
+                ListState::default(),
+            ));
+        }
+        Command::PlaylistDiffPage => {
+            let PageState::Context {
+                id: Some(ContextId::Playlist(id)),
+                ..
+            } = ui.current_page()
+            else {
+                return Ok(false);
+            };
+            let Some(Context::Playlist { playlist, .. }) =
+                state.data.read().caches.context.get(&id.uri()).cloned()
+            else {
+                return Ok(false);
+            };
+
+            client_pub.send(ClientRequest::GetUserPlaylists)?;
+            ui.popup = Some(PopupState::UserPlaylistList(
+                PlaylistPopupAction::Diff {
+                    folder_id: 0,
+                    playlist,
+                    search_query: LineInput::default(),
                 },
                 ListState::default(),
             ));
@@ -665,6 +1175,76 @@ fn handle_global_command(
             client_pub.send(ClientRequest::GetUserSavedAlbums)?;
             ui.popup = Some(PopupState::UserSavedAlbumList(ListState::default()));
         }
+        Command::BrowseSmartPlaylists => {
+            ui.popup = Some(PopupState::SmartPlaylistList(ListState::default()));
+        }
+        Command::BrowseLikedTracksByGenre => {
+            client_pub.send(ClientRequest::GetSavedTrackGenres)?;
+            ui.popup = Some(PopupState::LikedTrackGenreList(ListState::default()));
+        }
+        Command::BrowseTracksByDecade => {
+            let data = state.data.read();
+            // browse the tracks of the current context page, or the user liked tracks otherwise
+            let source = match ui.current_page() {
+                PageState::Context { id: Some(id), .. } => {
+                    data.caches.context.get(&id.uri()).and_then(|c| match c {
+                        Context::Playlist { playlist, tracks } => {
+                            Some((playlist.name.clone(), tracks.clone()))
+                        }
+                        Context::Album { album, tracks } => {
+                            Some((album.name.clone(), tracks.clone()))
+                        }
+                        Context::Artist {
+                            artist, top_tracks, ..
+                        } => Some((artist.name.clone(), top_tracks.clone())),
+                        Context::Tracks { tracks, .. } => match id {
+                            ContextId::Tracks(id) => Some((id.kind.clone(), tracks.clone())),
+                            _ => None,
+                        },
+                        Context::Show { .. } => None,
+                    })
+                }
+                _ => None,
+            };
+            let (name, tracks) = source.unwrap_or_else(|| {
+                let mut tracks = data
+                    .user_data
+                    .saved_tracks
+                    .values()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                tracks.sort_by_key(|t| std::cmp::Reverse(t.added_at));
+                (USER_LIKED_TRACKS_ID.kind.clone(), tracks)
+            });
+
+            ui.popup = Some(PopupState::TrackDecadeList {
+                name,
+                tracks,
+                list_state: ListState::default(),
+            });
+        }
+        Command::SaveTracksAsPlaylist => {
+            let PageState::Context {
+                id: Some(ContextId::Tracks(id)),
+                ..
+            } = ui.current_page()
+            else {
+                return Ok(false);
+            };
+            let Some(tracks) = state
+                .data
+                .read()
+                .context_tracks(&ContextId::Tracks(id.clone()))
+                .map(|tracks| tracks.iter().map(|t| t.id.clone()).collect::<Vec<_>>())
+            else {
+                return Ok(false);
+            };
+
+            client_pub.send(ClientRequest::CreatePlaylistFromTracks {
+                playlist_name: id.kind.clone(),
+                track_ids: tracks,
+            })?;
+        }
         Command::TopTrackPage => {
             ui.new_page(PageState::Context {
                 id: None,
@@ -715,56 +1295,102 @@ fn handle_global_command(
             });
             client_pub.send(ClientRequest::GetBrowseCategories)?;
         }
+        Command::BlocklistPage => {
+            ui.new_page(PageState::Blocklist {
+                state: ListState::default(),
+            });
+        }
+        #[cfg(feature = "plugins")]
+        Command::PluginsPage => {
+            ui.new_page(PageState::Plugins {
+                state: ListState::default(),
+            });
+        }
+        Command::SharedPlaylistsPage => {
+            client_pub.send(ClientRequest::GetSharedPlaylistAdds)?;
+            ui.new_page(PageState::SharedPlaylists {
+                state: SharedPlaylistsPageUIState::new(),
+            });
+        }
+        Command::OpenCommandPalette => {
+            ui.popup = Some(PopupState::CommandPalette {
+                query: LineInput::default(),
+                entries: command::construct_palette_entries(playing_track_actions(state)),
+                list_state: ListState::default(),
+            });
+        }
+        Command::SavedEpisodesPage => {
+            client_pub.send(ClientRequest::GetUserSavedEpisodes)?;
+            ui.new_page(PageState::SavedEpisodes {
+                state: TableState::default(),
+            });
+        }
+        Command::ArtistFeedPage => {
+            client_pub.send(ClientRequest::GetArtistFeed)?;
+            ui.new_page(PageState::ArtistFeed {
+                state: ListState::default(),
+            });
+        }
+        Command::TogglePrivateSession => {
+            let is_private_session = !crate::state::is_private_session();
+            state.set_private_session(is_private_session);
+            ui.show_toast(
+                if is_private_session {
+                    "Private session on, the played tracks aren't recorded"
+                } else {
+                    "Private session off"
+                }
+                .to_string(),
+            );
+        }
+        Command::TogglePerformanceOverlay => {
+            ui.show_performance_overlay = !ui.show_performance_overlay;
+        }
+        Command::CycleColorBlindnessSimulation => {
+            ui.color_blindness_simulation =
+                crate::ui::utils::ColorBlindness::next(ui.color_blindness_simulation);
+            let message = match ui.color_blindness_simulation {
+                Some(color_blindness) => format!("Simulating {color_blindness}"),
+                None => "Color blindness simulation off".to_string(),
+            };
+            ui.show_toast(message);
+        }
         Command::PreviousPage => {
-            if ui.history.len() > 1 {
-                ui.history.pop();
-                ui.popup = None;
-            }
+            ui.previous_page();
+        }
+        Command::NextPage => {
+            ui.next_page();
+        }
+        Command::NewTab => {
+            let page = ui.current_page().clone();
+            ui.new_tab(page);
+        }
+        Command::CloseTab => {
+            ui.close_tab();
+        }
+        Command::NextTab => {
+            let id = (ui.current_tab + 1) % ui.tabs.len();
+            ui.switch_tab(id);
+        }
+        Command::PreviousTab => {
+            let id = (ui.current_tab + ui.tabs.len() - 1) % ui.tabs.len();
+            ui.switch_tab(id);
         }
-        Command::OpenSpotifyLinkFromClipboard => {
+        Command::UndoLikeAllTracks => match ui.last_liked_tracks_change.take() {
+            Some(change) => client_pub.send(ClientRequest::SetTracksLiked {
+                tracks: change.tracks,
+                liked: !change.liked,
+                undoable: false,
+            })?,
+            None => ui.show_toast("Nothing to undo".to_string()),
+        },
+        Command::OpenSpotifyLinkFromClipboard | Command::PlaySpotifyLinkFromClipboard => {
             let content = get_clipboard_content().context("get clipboard's content")?;
-            let re = regex::Regex::new(
-                r"https://open.spotify.com/(?P<type>.*?)/(?P<id>[[:alnum:]]*).*",
-            )?;
-            if let Some(cap) = re.captures(&content) {
-                let typ = cap.name("type").expect("valid capture").as_str();
-                let id = cap.name("id").expect("valid capture").as_str();
-                match typ {
-                    // for track link, play the song
-                    "track" => {
-                        let id = TrackId::from_id(id)?.into_static();
-                        client_pub.send(ClientRequest::Player(PlayerRequest::StartPlayback(
-                            Playback::URIs(vec![id.into()], None),
-                            None,
-                        )))?;
-                    }
-                    // for playlist/artist/album link, go to the corresponding context page
-                    "playlist" => {
-                        let id = PlaylistId::from_id(id)?.into_static();
-                        ui.new_page(PageState::Context {
-                            id: None,
-                            context_page_type: ContextPageType::Browsing(ContextId::Playlist(id)),
-                            state: None,
-                        });
-                    }
-                    "artist" => {
-                        let id = ArtistId::from_id(id)?.into_static();
-                        ui.new_page(PageState::Context {
-                            id: None,
-                            context_page_type: ContextPageType::Browsing(ContextId::Artist(id)),
-                            state: None,
-                        });
-                    }
-                    "album" => {
-                        let id = AlbumId::from_id(id)?.into_static();
-                        ui.new_page(PageState::Context {
-                            id: None,
-                            context_page_type: ContextPageType::Browsing(ContextId::Album(id)),
-                            state: None,
-                        });
-                    }
-                    e => anyhow::bail!("unsupported Spotify type {e}!"),
-                }
+            if crate::utils::is_spotify_link(&content) {
+                client_pub.send(ClientRequest::OpenSpotifyLink {
+                    link: content,
+                    play: command == Command::PlaySpotifyLinkFromClipboard,
+                })?;
             } else {
                 tracing::warn!("clipboard's content ({content}) is not a valid Spotify link!");
             }
@@ -787,13 +1413,71 @@ fn handle_global_command(
                 }
             }
         }
+        Command::NowPlayingPage => {
+            if ui.current_page().page_type() == PageType::NowPlaying {
+                ui.history.pop();
+            } else {
+                ui.new_page(PageState::NowPlaying);
+            }
+            #[cfg(feature = "image")]
+            {
+                // the cover image in the playback window needs to be redrawn after leaving the page
+                ui.last_cover_image_render_info = crate::state::ImageRenderInfo::default();
+            }
+        }
         Command::SwitchDevice => {
             ui.popup = Some(PopupState::DeviceList(ListState::default()));
             client_pub.send(ClientRequest::GetDevices)?;
         }
+        Command::SwitchToNextGroupDevice => {
+            let player = state.player.read();
+            if player.device_group.is_empty() {
+                ui.show_toast(
+                    "The device group is empty, add devices to it in the devices popup".to_string(),
+                );
+                return Ok(true);
+            }
+            let current_device_id = player
+                .playback
+                .as_ref()
+                .and_then(|p| p.device.id.as_deref());
+            // start from the group's first device if the current device isn't in the group
+            let next_id = match player
+                .device_group
+                .iter()
+                .position(|id| Some(id.as_str()) == current_device_id)
+            {
+                Some(i) => (i + 1) % player.device_group.len(),
+                None => 0,
+            };
+            let device_id = player.device_group[next_id].clone();
+            let is_playing = player.playback.as_ref().is_some_and(|p| p.is_playing);
+            let name = player
+                .devices
+                .iter()
+                .find(|d| d.id == device_id)
+                .map_or_else(|| device_id.clone(), |d| d.name.clone());
+            client_pub.send(ClientRequest::Player(PlayerRequest::TransferPlayback(
+                device_id, is_playing,
+            )))?;
+            ui.show_toast(format!("Moving playback to {name}"));
+        }
+        Command::CycleContextEndBehavior => {
+            let mut player = state.player.write();
+            let Some(id) = player.playing_context_id() else {
+                ui.show_toast("No context is playing".to_string());
+                return Ok(true);
+            };
+            let behavior = player.context_end_behavior(&id).next();
+            player.context_end_behaviors.insert(id.uri(), behavior);
+            ui.show_toast(format!("When the context ends: {}", behavior.desc()));
+        }
         Command::SwitchTheme => {
-            // get the available themes with the current theme moved to the first position
-            let mut themes = config::get_config().theme_config.themes.clone();
+            // get the available themes with the current theme moved to the first position.
+            // The theme config file is read again to include the themes imported or exported since the start.
+            let configs = config::get_config();
+            let mut themes = config::ThemeConfig::new(&configs.config_folder)
+                .map_or_else(|_| configs.theme_config.themes.clone(), |c| c.themes);
             let id = themes.iter().position(|t| t.name == ui.theme.name);
             if let Some(id) = id {
                 let theme = themes.remove(id);
@@ -806,6 +1490,21 @@ fn handle_global_command(
         Command::RestartIntegratedClient => {
             client_pub.send(ClientRequest::RestartIntegratedClient)?;
         }
+        #[cfg(feature = "streaming")]
+        Command::CycleStreamingBitrate => {
+            let bitrate = {
+                let mut player = state.player.write();
+                player.streaming_bitrate = match player.streaming_bitrate {
+                    0..=96 => 160,
+                    97..=160 => 320,
+                    _ => 96,
+                };
+                player.streaming_bitrate
+            };
+            // the bitrate is applied to the integrated client's new player
+            client_pub.send(ClientRequest::RestartIntegratedClient)?;
+            ui.show_toast(format!("Streaming bitrate: {bitrate} kbps"));
+        }
         Command::FocusNextWindow => {
             if !ui.has_focused_popup() {
                 ui.current_page_mut().next();
@@ -820,6 +1519,40 @@ fn handle_global_command(
             ui.new_page(PageState::Queue { scroll_offset: 0 });
             client_pub.send(ClientRequest::GetCurrentUserQueue)?;
         }
+        Command::AddEpisodeBookmark => {
+            let player = state.player.read();
+            if let (Some(rspotify::model::PlayableItem::Episode(episode)), Some(progress)) =
+                (player.currently_playing(), player.playback_progress())
+            {
+                ui.popup = Some(PopupState::EpisodeBookmarkCreate {
+                    episode_uri: episode.id.uri(),
+                    position: progress,
+                    note: LineInput::default(),
+                });
+            }
+        }
+        Command::ShowEpisodeBookmarks => {
+            if let Some(rspotify::model::PlayableItem::Episode(episode)) =
+                state.player.read().currently_playing()
+            {
+                ui.popup = Some(PopupState::EpisodeBookmarkList(
+                    episode.id.uri(),
+                    ListState::default(),
+                ));
+            }
+        }
+        Command::BlockCurrentTrack => {
+            if let Some(rspotify::model::PlayableItem::Track(track)) =
+                state.player.read().currently_playing()
+            {
+                if let Some(id) = &track.id {
+                    client_pub.send(ClientRequest::AddToBlocklist(BlockedItem::Track {
+                        uri: id.uri(),
+                        name: track.name.clone(),
+                    }))?;
+                }
+            }
+        }
         Command::CreatePlaylist => {
             ui.popup = Some(PopupState::PlaylistCreate {
                 name: LineInput::default(),
@@ -847,7 +1580,7 @@ fn handle_global_command(
                     .data
                     .read()
                     .context_tracks(context_id)
-                    .and_then(|tracks| tracks.iter().position(|t| t.id.uri() == track_id.uri()));
+                    .and_then(|tracks| tracks.iter().position(|t| t.has_uri(&track_id.uri())));
 
                 if let Some(p) = context_track_pos {
                     ui.current_page_mut().select(p);
@@ -882,6 +1615,74 @@ fn handle_global_command(
             ui.enable_visualization = !ui.enable_visualization;
             tracing::info!("Visualization toggled: {}", ui.enable_visualization);
         }
+        #[cfg(feature = "fx")]
+        Command::SnapshotVisualization => {
+            // the snapshot is taken from the visualization's next rendered frame
+            ui.visualization_snapshot_requested = true;
+        }
+        #[cfg(feature = "fx")]
+        Command::CycleVisualizationMode => {
+            let has_shader = config::get_config()
+                .app_config
+                .visualization_shader
+                .is_some();
+            ui.visualization.mode = ui.visualization.mode.next(has_shader);
+            ui.visualization_fallback = None;
+            if ui.visualization.mode == crate::ui::visualizations::VisualizationMode::Smart {
+                client_pub.send(ClientRequest::UpdateSmartVisualizationMode)?;
+            }
+        }
+        #[cfg(feature = "fx")]
+        Command::CycleVisualizationColorScheme => {
+            ui.visualization.color_scheme = ui.visualization.color_scheme.next();
+            ui.visualization_fallback = None;
+        }
+        #[cfg(all(feature = "fx", feature = "image"))]
+        Command::ExportAlbumTheme => {
+            let message = match export_album_theme(state, ui) {
+                Ok(message) => message,
+                Err(err) => format!("Failed to export the album's theme: {err:#}"),
+            };
+            ui.show_toast(message);
+        }
+        #[cfg(feature = "fx")]
+        Command::RememberVisualization => {
+            let targets = match state.player.read().currently_playing() {
+                Some(rspotify::model::PlayableItem::Track(track)) => {
+                    let genres = track
+                        .artists
+                        .first()
+                        .and_then(|a| state.data.read().caches.genres.get(&a.name).cloned())
+                        .unwrap_or_default();
+                    crate::ui::visualizations::visualization_preference_targets(track, &genres)
+                }
+                _ => vec![],
+            };
+            if !targets.is_empty() {
+                ui.popup = Some(PopupState::VisualizationPreferenceTargets(
+                    targets,
+                    ListState::default(),
+                ));
+            }
+        }
+        #[cfg(feature = "fx")]
+        Command::ToggleVisualizationRecording => {
+            let now = ui.clock.now();
+            if let Some(recording) = ui.visualization_recording.as_mut() {
+                recording.stop(now);
+            } else {
+                let duration = std::time::Duration::from_secs(
+                    config::get_config().app_config.visualization_recording_secs,
+                );
+                ui.visualization_recording =
+                    Some(crate::ui::visualizations::VisualizationRecording::new(
+                        ui.beat_clock.bpm(),
+                        ui.beat_clock.beat_origin(now),
+                        duration,
+                        now,
+                    ));
+            }
+        }
         _ => return Ok(false),
     }
     Ok(true)
@@ -1,3 +1,5 @@
+use crate::ui::single_line_input::InputEffect;
+use crate::{client::MAX_LOAD_ALL_SEARCH_RESULTS, utils::format_count};
 use anyhow::Context as _;
 use command::CommandOrAction;
 
@@ -20,26 +22,67 @@ pub fn handle_key_sequence_for_page(
         .keymap_config
         .find_command_or_action_from_key_sequence(key_sequence)
     {
-        Some(CommandOrAction::Command(command)) => match page_type {
-            PageType::Search => anyhow::bail!("page search type should already be handled!"),
+        Some(command_or_action) => {
+            handle_command_or_action_for_page(command_or_action, client_pub, state, ui)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Handle a command or an action on the selected item for the current page
+pub fn handle_command_or_action_for_page(
+    command_or_action: CommandOrAction,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let page_type = ui.current_page().page_type();
+    match command_or_action {
+        CommandOrAction::Command(command) => match page_type {
             PageType::Library => handle_command_for_library_page(command, client_pub, ui, state),
             PageType::Context => handle_command_for_context_page(command, client_pub, ui, state),
             PageType::Browse => handle_command_for_browse_page(command, client_pub, ui, state),
-            // lyrics page doesn't support any commands
-            PageType::Lyrics => Ok(false),
+            PageType::Blocklist => Ok(handle_command_for_blocklist_page(command, ui, state)),
+            PageType::ArtistFeed => Ok(handle_command_for_artist_feed_page(command, ui, state)),
+            PageType::SharedPlaylists => {
+                handle_command_for_shared_playlists_page(command, client_pub, ui, state)
+            }
+            PageType::SavedEpisodes => {
+                handle_command_for_saved_episodes_page(command, client_pub, ui, state)
+            }
+            PageType::PlaylistDiff => {
+                handle_command_for_playlist_diff_page(command, client_pub, ui, state)
+            }
+            // lyrics and now playing pages don't support any commands, and commands of
+            // the search page are only handled from its raw key sequences as they depend on
+            // the page's input focus
+            PageType::Lyrics | PageType::NowPlaying | PageType::Search => Ok(false),
             PageType::Queue => Ok(handle_command_for_queue_page(command, ui)),
             PageType::CommandHelp => Ok(handle_command_for_command_help_page(command, ui)),
+            #[cfg(feature = "plugins")]
+            PageType::Plugins => handle_command_for_plugins_page(command, ui),
+            #[cfg(feature = "plugins")]
+            PageType::PluginPage => Ok(handle_command_for_plugin_page(command, ui)),
         },
-        Some(CommandOrAction::Action(action, ActionTarget::SelectedItem)) => match page_type {
-            PageType::Search => anyhow::bail!("page search type should already be handled!"),
+        CommandOrAction::Action(action, ActionTarget::SelectedItem) => match page_type {
             PageType::Library => handle_action_for_library_page(action, client_pub, ui, state),
             PageType::Context => {
                 window::handle_action_for_focused_context_page(action, client_pub, ui, state)
             }
             PageType::Browse => handle_action_for_browse_page(action, client_pub, ui, state),
+            PageType::SavedEpisodes => {
+                let data = state.data.read();
+                window::handle_action_for_selected_item(
+                    action,
+                    &ui.search_filtered_items(&data.user_data.saved_episodes),
+                    &data,
+                    ui,
+                    client_pub,
+                )
+            }
             _ => Ok(false),
         },
-        _ => Ok(false),
+        CommandOrAction::Action(..) => Ok(false),
     }
 }
 
@@ -118,12 +161,12 @@ fn handle_command_for_library_page(
         // Sort albums alphabetically
         data.user_data
             .saved_albums
-            .sort_by(|x, y| x.name.to_lowercase().cmp(&y.name.to_lowercase()));
+            .sort_by_key(|x| x.name.to_lowercase());
 
         // Sort artists alphabetically
         data.user_data
             .followed_artists
-            .sort_by(|x, y| x.name.to_lowercase().cmp(&y.name.to_lowercase()));
+            .sort_by_key(|x| x.name.to_lowercase());
     }
 
     if command == Command::SortLibraryByRecent {
@@ -152,7 +195,7 @@ fn handle_command_for_library_page(
         // Sort albums by recent addition
         data.user_data
             .saved_albums
-            .sort_by(|a, b| b.added_at.cmp(&a.added_at));
+            .sort_by_key(|a| std::cmp::Reverse(a.added_at));
     }
 
     match focus_state {
@@ -198,34 +241,68 @@ fn handle_key_sequence_for_search_page(
     state: &SharedState,
     ui: &mut UIStateGuard,
 ) -> Result<bool> {
-    let (focus_state, current_query, line_input) = match ui.current_page_mut() {
-        PageState::Search {
-            state,
-            line_input,
-            current_query,
-        } => (state.focus, current_query, line_input),
+    let focus_state = match ui.current_page() {
+        PageState::Search { state, .. } => state.focus,
         _ => anyhow::bail!("expect a search page"),
     };
 
     // handle user's input
-    if let SearchFocusState::Input = focus_state {
-        if key_sequence.keys.len() == 1 {
-            return match &key_sequence.keys[0] {
-                Key::None(crossterm::event::KeyCode::Enter) => {
-                    if !line_input.is_empty() {
-                        *current_query = line_input.get_text();
-                        client_pub.send(ClientRequest::Search(line_input.get_text()))?;
-                    }
-                    Ok(true)
+    if let (SearchFocusState::Input, [key]) = (focus_state, &key_sequence.keys[..]) {
+        // browse the history of submitted queries with the up/down keys
+        let recalled_query = match key {
+            Key::None(crossterm::event::KeyCode::Up) => {
+                Some(ui.search_history.previous().map(String::from))
+            }
+            Key::None(crossterm::event::KeyCode::Down) => {
+                Some(ui.search_history.next().map(String::from))
+            }
+            _ => None,
+        };
+
+        let PageState::Search {
+            line_input,
+            current_query,
+            ..
+        } = ui.current_page_mut()
+        else {
+            anyhow::bail!("expect a search page");
+        };
+        let effect = match recalled_query {
+            Some(Some(query)) => {
+                line_input.set_text(&query);
+                Some(InputEffect::TextChanged)
+            }
+            Some(None) => Some(InputEffect::Ack),
+            None if *key == Key::None(crossterm::event::KeyCode::Enter) => {
+                if !line_input.is_empty() {
+                    let query = line_input.get_text();
+                    current_query.clone_from(&query);
+                    client_pub.send(ClientRequest::Search(query.clone()))?;
+                    ui.search_history.push(query);
                 }
-                k => match line_input.input(k) {
-                    None => Ok(false),
-                    _ => Ok(true),
-                },
-            };
-        }
+                return Ok(true);
+            }
+            None => line_input.input(key),
+        };
+        return match effect {
+            None => Ok(false),
+            Some(InputEffect::TextChanged) => {
+                // search as the user types, the client debounces the search requests
+                if !line_input.is_empty() {
+                    *current_query = line_input.get_text();
+                    client_pub.send(ClientRequest::Search(line_input.get_text()))?;
+                }
+                Ok(true)
+            }
+            Some(_) => Ok(true),
+        };
     }
 
+    let PageState::Search { current_query, .. } = ui.current_page() else {
+        anyhow::bail!("expect a search page");
+    };
+    let current_query = current_query.clone();
+
     let Some(found_keymap) = config::get_config()
         .keymap_config
         .find_command_or_action_from_key_sequence(key_sequence)
@@ -233,8 +310,21 @@ fn handle_key_sequence_for_search_page(
         return Ok(false);
     };
 
+    if let CommandOrAction::Command(command) = found_keymap {
+        if handle_result_page_command(
+            command,
+            client_pub,
+            state,
+            ui,
+            focus_state,
+            current_query.clone(),
+        )? {
+            return Ok(true);
+        }
+    }
+
     let data = state.data.read();
-    let search_results = data.caches.search.get(current_query);
+    let search_results = data.caches.search.get(&current_query);
 
     match focus_state {
         SearchFocusState::Input => anyhow::bail!("user's search input should be handled before"),
@@ -364,6 +454,20 @@ fn handle_command_for_context_page(
             ui.new_search_popup();
             Ok(true)
         }
+        Command::LikeAllTracks | Command::UnlikeAllTracks => {
+            let PageState::Context { id: Some(id), .. } = ui.current_page() else {
+                return Ok(false);
+            };
+            let Some(tracks) = state.data.read().context_tracks(id).cloned() else {
+                return Ok(false);
+            };
+            client_pub.send(ClientRequest::SetTracksLiked {
+                tracks,
+                liked: command == Command::LikeAllTracks,
+                undoable: true,
+            })?;
+            Ok(true)
+        }
         _ => window::handle_command_for_focused_context_window(command, client_pub, ui, state),
     }
 }
@@ -481,6 +585,271 @@ fn handle_command_for_browse_page(
     Ok(true)
 }
 
+fn handle_command_for_blocklist_page(
+    command: Command,
+    ui: &mut UIStateGuard,
+    state: &SharedState,
+) -> bool {
+    let len = state.data.read().user_data.blocklist.len();
+
+    let count = ui.count_prefix;
+    let page_state = ui.current_page_mut();
+    let selected = page_state.selected().unwrap_or_default();
+    if selected >= len {
+        return false;
+    }
+
+    if handle_navigation_command(command, page_state, selected, len, count) {
+        return true;
+    }
+    match command {
+        Command::ChooseSelected => {
+            state
+                .data
+                .write()
+                .user_data
+                .remove_from_blocklist(selected, &config::get_config().cache_folder);
+            if selected + 1 >= len {
+                page_state.select(selected.saturating_sub(1));
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+fn handle_command_for_saved_episodes_page(
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+    state: &SharedState,
+) -> Result<bool> {
+    if command == Command::Search {
+        ui.new_search_popup();
+        return Ok(true);
+    }
+
+    let data = state.data.read();
+    let episodes = ui.search_filtered_items(&data.user_data.saved_episodes);
+    window::handle_command_for_episode_list_window(command, client_pub, &episodes, &data, ui)
+}
+
+fn handle_command_for_artist_feed_page(
+    command: Command,
+    ui: &mut UIStateGuard,
+    state: &SharedState,
+) -> bool {
+    let mut data = state.data.write();
+    let len = data.user_data.artist_feed.len();
+
+    let count = ui.count_prefix;
+    let page_state = ui.current_page_mut();
+    let selected = page_state.selected().unwrap_or_default();
+    if selected >= len {
+        return false;
+    }
+
+    if handle_navigation_command(command, page_state, selected, len, count) {
+        return true;
+    }
+    let cache_folder = &config::get_config().cache_folder;
+    match command {
+        Command::ChooseSelected => {
+            let item = data.user_data.artist_feed[selected].clone();
+            data.user_data
+                .mark_feed_items_as_read(vec![item.key()], cache_folder);
+
+            let context_id = match item {
+                FeedItem::Release { album, .. } | FeedItem::AppearsOn { album, .. } => {
+                    ContextId::Album(album.id)
+                }
+                FeedItem::PlaylistAdd { playlist, .. } => ContextId::Playlist(playlist.id),
+            };
+            ui.new_page(PageState::Context {
+                id: None,
+                context_page_type: ContextPageType::Browsing(context_id),
+                state: None,
+            });
+        }
+        Command::MarkFeedAsRead => {
+            let keys = data
+                .user_data
+                .artist_feed
+                .iter()
+                .map(FeedItem::key)
+                .collect();
+            data.user_data.mark_feed_items_as_read(keys, cache_folder);
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Handle a command paginating the search results of the focused window
+fn handle_result_page_command(
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    focus_state: SearchFocusState,
+    query: String,
+) -> Result<bool> {
+    if !matches!(
+        command,
+        Command::NextResultPage | Command::PreviousResultPage | Command::LoadAllResults
+    ) {
+        return Ok(false);
+    }
+    let Some(typ) = focus_state.search_type() else {
+        return Ok(false);
+    };
+    let Some((page, n_items)) = state
+        .data
+        .read()
+        .caches
+        .search
+        .get(&query)
+        .map(|results| (*results.page(typ), results.len(typ)))
+    else {
+        return Ok(false);
+    };
+    if page.loading_all.is_some() {
+        return Ok(true);
+    }
+
+    let count = ui.count_prefix.unwrap_or(1);
+    let offset = match command {
+        Command::NextResultPage => page.offset + count * n_items.max(1),
+        Command::PreviousResultPage => page.offset.saturating_sub(count * n_items.max(1)),
+        _ => {
+            if page.is_fully_loaded(n_items) {
+                ui.show_toast("All results are loaded".to_string());
+            } else if page.total > MAX_LOAD_ALL_SEARCH_RESULTS {
+                ui.show_toast(format!(
+                    "Cannot load all {} results, at most {} results can be loaded",
+                    format_count(page.total),
+                    format_count(MAX_LOAD_ALL_SEARCH_RESULTS),
+                ));
+            } else {
+                client_pub.send(ClientRequest::LoadAllSearchResults { query, typ })?;
+            }
+            return Ok(true);
+        }
+    };
+    if offset != page.offset && offset < page.total.min(MAX_LOAD_ALL_SEARCH_RESULTS) {
+        client_pub.send(ClientRequest::GetSearchResultPage { query, typ, offset })?;
+        ui.current_page_mut().select(0);
+    }
+    Ok(true)
+}
+
+fn handle_command_for_shared_playlists_page(
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+    state: &SharedState,
+) -> Result<bool> {
+    let focus_state = match ui.current_page() {
+        PageState::SharedPlaylists { state } => state.focus,
+        _ => anyhow::bail!("expect a shared playlists page"),
+    };
+
+    let data = state.data.read();
+    match focus_state {
+        SharedPlaylistsFocusState::Playlists => {
+            Ok(window::handle_command_for_playlist_list_window(
+                command,
+                &data.user_data.shared_playlist_items(),
+                &data,
+                ui,
+            ))
+        }
+        SharedPlaylistsFocusState::RecentTracks => {
+            let tracks = data
+                .user_data
+                .shared_playlist_adds
+                .iter()
+                .map(|add| &add.track)
+                .collect::<Vec<_>>();
+            window::handle_command_for_track_list_window(command, client_pub, &tracks, &data, ui)
+        }
+    }
+}
+
+fn handle_command_for_playlist_diff_page(
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+    state: &SharedState,
+) -> Result<bool> {
+    let (first_id, second_id, focus_state) = match ui.current_page() {
+        PageState::PlaylistDiff {
+            first,
+            second,
+            state,
+        } => (first.id.clone(), second.id.clone(), state.focus),
+        _ => anyhow::bail!("expect a playlist diff page"),
+    };
+
+    let data = state.data.read();
+    let Some(diff) = data.playlist_diff(
+        &ContextId::Playlist(first_id.clone()),
+        &ContextId::Playlist(second_id.clone()),
+    ) else {
+        return Ok(false);
+    };
+
+    // the tracks of the focused list, the playlist to sync with them and the tracks only in that playlist
+    let (tracks, sync_target, extra_tracks) = match focus_state {
+        PlaylistDiffFocusState::OnlyInFirst => {
+            (diff.only_in_first, Some(second_id), diff.only_in_second)
+        }
+        PlaylistDiffFocusState::OnlyInSecond => {
+            (diff.only_in_second, Some(first_id), diff.only_in_first)
+        }
+        PlaylistDiffFocusState::InBoth => (diff.in_both, None, vec![]),
+    };
+
+    if command == Command::SyncPlaylists {
+        let Some(playlist_id) = sync_target else {
+            return Ok(true);
+        };
+        // syncing makes the playlist a mirror of the other playlist, so it must be the user's playlist
+        let owned = data.user_data.user.as_ref().is_some_and(|user| {
+            data.user_data.playlists.iter().any(|item| {
+                matches!(item, PlaylistFolderItem::Playlist(p) if p.id == playlist_id && p.owner.1 == user.id)
+            })
+        });
+        if !owned {
+            ui.show_toast("Only a playlist you own can be synced".to_string());
+            return Ok(true);
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let added = tracks
+            .iter()
+            .filter(|t| seen.insert(&t.id))
+            .map(|t| t.id.clone())
+            .collect::<Vec<_>>();
+        let mut seen = std::collections::HashSet::new();
+        let removed = extra_tracks
+            .iter()
+            .filter(|t| seen.insert(&t.id))
+            .map(|t| t.id.clone())
+            .collect::<Vec<_>>();
+        if !added.is_empty() || !removed.is_empty() {
+            client_pub.send(ClientRequest::SyncPlaylist {
+                playlist_id,
+                added,
+                removed,
+            })?;
+        }
+        return Ok(true);
+    }
+
+    window::handle_command_for_track_list_window(command, client_pub, &tracks, &data, ui)
+}
+
 fn handle_command_for_queue_page(command: Command, ui: &mut UIStateGuard) -> bool {
     let scroll_offset = match ui.current_page() {
         PageState::Queue { scroll_offset } => *scroll_offset,
@@ -503,6 +872,83 @@ fn handle_command_for_command_help_page(command: Command, ui: &mut UIStateGuard)
     handle_navigation_command(command, ui.current_page_mut(), scroll_offset, 10000, count)
 }
 
+#[cfg(feature = "plugins")]
+fn handle_command_for_plugins_page(command: Command, ui: &mut UIStateGuard) -> Result<bool> {
+    use crate::plugins::PluginItem;
+
+    let items = crate::plugins::items();
+
+    let count = ui.count_prefix;
+    let page_state = ui.current_page_mut();
+    let selected = page_state.selected().unwrap_or_default();
+    if selected >= items.len() {
+        return Ok(false);
+    }
+
+    if handle_navigation_command(command, page_state, selected, items.len(), count) {
+        return Ok(true);
+    }
+    if command != Command::ChooseSelected {
+        return Ok(false);
+    }
+    match items[selected] {
+        PluginItem::Plugin(plugin) => {
+            let enabled = !plugin.is_enabled();
+            plugin.set_enabled(enabled)?;
+            #[cfg(feature = "fx")]
+            if !enabled
+                && ui
+                    .plugin_visualization
+                    .as_ref()
+                    .is_some_and(|(name, _)| *name == plugin.name)
+            {
+                ui.plugin_visualization = None;
+            }
+        }
+        PluginItem::Page(plugin, page) => {
+            ui.new_page(PageState::PluginPage {
+                plugin: plugin.name.clone(),
+                page: page.to_string(),
+                scroll_offset: 0,
+            });
+        }
+        PluginItem::Action(plugin, action) => {
+            let result = plugin.run_action(action);
+            ui.show_toast(match result {
+                Ok(()) => format!("Ran {action} of plugin {}", plugin.name),
+                Err(err) => format!("Failed to run {action} of plugin {}: {err:#}", plugin.name),
+            });
+        }
+        PluginItem::Visualization(plugin, visualization) => {
+            #[cfg(feature = "fx")]
+            {
+                let item = (plugin.name.clone(), visualization.to_string());
+                ui.plugin_visualization = if ui.plugin_visualization.as_ref() == Some(&item) {
+                    None
+                } else {
+                    Some(item)
+                };
+            }
+            #[cfg(not(feature = "fx"))]
+            {
+                let _ = (plugin, visualization);
+                ui.show_toast("Visualizations require the `fx` feature".to_string());
+            }
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(feature = "plugins")]
+fn handle_command_for_plugin_page(command: Command, ui: &mut UIStateGuard) -> bool {
+    let scroll_offset = match ui.current_page() {
+        PageState::PluginPage { scroll_offset, .. } => *scroll_offset,
+        _ => return false,
+    };
+    let count = ui.count_prefix;
+    handle_navigation_command(command, ui.current_page_mut(), scroll_offset, 10000, count)
+}
+
 pub fn handle_navigation_command(
     command: Command,
     page: &mut PageState,
@@ -1,5 +1,9 @@
 use super::*;
-use crate::{command::construct_artist_actions, utils::filtered_items_from_query};
+use crate::{
+    command::construct_artist_actions,
+    state::{group_tracks_by_decade, PLAYLIST_MERGE_N_OPTIONS, TRACK_RATING_CHOICES},
+    utils::filtered_items_from_query,
+};
 use anyhow::Context;
 
 pub fn handle_key_sequence_for_popup(
@@ -16,6 +20,45 @@ pub fn handle_key_sequence_for_popup(
         PopupState::PlaylistCreate { .. } => {
             return handle_key_sequence_for_create_playlist_popup(key_sequence, client_pub, ui);
         }
+        PopupState::EpisodeBookmarkCreate { .. } => {
+            return Ok(handle_key_sequence_for_create_episode_bookmark_popup(
+                key_sequence,
+                state,
+                ui,
+            ));
+        }
+        PopupState::TrackTagsEdit { .. } => {
+            return Ok(handle_key_sequence_for_edit_track_tags_popup(
+                key_sequence,
+                state,
+                ui,
+            ));
+        }
+        PopupState::PlaylistRename { .. } => {
+            return handle_key_sequence_for_rename_playlist_popup(key_sequence, client_pub, ui);
+        }
+        PopupState::PlaylistDescriptionEdit { .. } => {
+            return handle_key_sequence_for_edit_playlist_description_popup(
+                key_sequence,
+                client_pub,
+                ui,
+            );
+        }
+        PopupState::TrackRemovalConfirmation(..) => {
+            return handle_key_sequence_for_track_removal_confirmation_popup(
+                key_sequence,
+                client_pub,
+                ui,
+            );
+        }
+        PopupState::CommandPalette { .. } => {
+            return handle_key_sequence_for_command_palette_popup(
+                key_sequence,
+                client_pub,
+                state,
+                ui,
+            );
+        }
         PopupState::ActionList(item, ..) => {
             return handle_key_sequence_for_action_list_popup(
                 item.n_actions(),
@@ -25,6 +68,8 @@ pub fn handle_key_sequence_for_popup(
                 ui,
             );
         }
+        // the popup's borrow prevents collapsing this check into a match guard
+        #[allow(clippy::collapsible_match)]
         PopupState::UserPlaylistList(..) => {
             if handle_key_sequence_for_playlist_search_popup(key_sequence, ui) {
                 return Ok(true);
@@ -45,9 +90,27 @@ pub fn handle_key_sequence_for_popup(
         PopupState::PlaylistCreate { .. } => {
             anyhow::bail!("create playlist popup should be handled before")
         }
+        PopupState::EpisodeBookmarkCreate { .. } => {
+            anyhow::bail!("create episode bookmark popup should be handled before")
+        }
+        PopupState::TrackTagsEdit { .. } => {
+            anyhow::bail!("edit track tags popup should be handled before")
+        }
+        PopupState::PlaylistRename { .. } => {
+            anyhow::bail!("rename playlist popup should be handled before")
+        }
+        PopupState::PlaylistDescriptionEdit { .. } => {
+            anyhow::bail!("edit playlist description popup should be handled before")
+        }
         PopupState::ActionList(..) => {
             anyhow::bail!("action list popup should be handled before")
         }
+        PopupState::CommandPalette { .. } => {
+            anyhow::bail!("command palette popup should be handled before")
+        }
+        PopupState::TrackRemovalConfirmation(..) => {
+            anyhow::bail!("track removal confirmation popup should be handled before")
+        }
         PopupState::ArtistList(_, artists, _) => {
             let n_items = artists.len();
 
@@ -97,7 +160,7 @@ pub fn handle_key_sequence_for_popup(
                 let search_query = search_query.clone();
                 let data = state.data.read();
                 let items = data.user_data.folder_playlists_items(*folder_id);
-                let filtered_items = filtered_items_from_query(&search_query, &items);
+                let filtered_items = filtered_items_from_query(&search_query.get_text(), &items);
 
                 handle_command_for_list_popup(
                     command,
@@ -134,16 +197,69 @@ pub fn handle_key_sequence_for_popup(
                     },
                 )
             }
+            PlaylistPopupAction::Diff {
+                folder_id,
+                playlist,
+                search_query,
+            } => {
+                let search_query = search_query.clone();
+                let playlist = playlist.clone();
+                let data = state.data.read();
+                let items = data.user_data.folder_playlists_items(*folder_id);
+                let filtered_items = filtered_items_from_query(&search_query.get_text(), &items);
+
+                handle_command_for_list_popup(
+                    command,
+                    ui,
+                    filtered_items.len(),
+                    |_, _| {},
+                    |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                        match filtered_items.get(id).expect("invalid index") {
+                            PlaylistFolderItem::Folder(f) => {
+                                ui.popup = Some(PopupState::UserPlaylistList(
+                                    PlaylistPopupAction::Diff {
+                                        folder_id: f.target_id,
+                                        playlist,
+                                        search_query: search_query.clone(),
+                                    },
+                                    ListState::default(),
+                                ));
+                            }
+                            // a playlist cannot be compared with itself
+                            PlaylistFolderItem::Playlist(p) if p.id == playlist.id => {}
+                            PlaylistFolderItem::Playlist(p) => {
+                                client_pub.send(ClientRequest::GetContext(ContextId::Playlist(
+                                    playlist.id.clone(),
+                                )))?;
+                                client_pub.send(ClientRequest::GetContext(ContextId::Playlist(
+                                    p.id.clone(),
+                                )))?;
+                                ui.new_page(PageState::PlaylistDiff {
+                                    first: playlist,
+                                    second: (*p).clone(),
+                                    state: PlaylistDiffPageUIState::new(),
+                                });
+                            }
+                        }
+                        Ok(())
+                    },
+                    |ui: &mut UIStateGuard| {
+                        ui.popup = None;
+                    },
+                )
+            }
             PlaylistPopupAction::AddTrack {
                 folder_id,
                 track_id,
                 search_query,
+                confirmed_playlist,
             } => {
                 let search_query = search_query.clone();
                 let track_id = track_id.clone();
+                let confirmed_playlist = confirmed_playlist.clone();
                 let data = state.data.read();
                 let items = data.user_data.modifiable_playlist_items(Some(*folder_id));
-                let filtered_items = filtered_items_from_query(&search_query, &items);
+                let filtered_items = filtered_items_from_query(&search_query.get_text(), &items);
 
                 handle_command_for_list_popup(
                     command,
@@ -151,23 +267,56 @@ pub fn handle_key_sequence_for_popup(
                     filtered_items.len(),
                     |_, _| {},
                     |ui: &mut UIStateGuard, id: usize| -> Result<()> {
-                        ui.popup = match filtered_items.get(id).expect("invalid index") {
-                            PlaylistFolderItem::Folder(f) => Some(PopupState::UserPlaylistList(
-                                PlaylistPopupAction::AddTrack {
-                                    folder_id: f.target_id,
-                                    track_id,
-                                    search_query: search_query.clone(),
-                                },
-                                ListState::default(),
-                            )),
+                        match filtered_items.get(id).expect("invalid index") {
+                            PlaylistFolderItem::Folder(f) => {
+                                ui.popup = Some(PopupState::UserPlaylistList(
+                                    PlaylistPopupAction::AddTrack {
+                                        folder_id: f.target_id,
+                                        track_id,
+                                        search_query: search_query.clone(),
+                                        confirmed_playlist: None,
+                                    },
+                                    ListState::default(),
+                                ));
+                            }
                             PlaylistFolderItem::Playlist(p) => {
-                                client_pub.send(ClientRequest::AddPlayableToPlaylist(
-                                    p.id.clone(),
-                                    track_id.into(),
-                                ))?;
-                                None
+                                let duplicate = data.find_playlist_duplicate(&p.id, &track_id);
+                                match duplicate {
+                                    // warn about the duplicate and wait for the playlist to be chosen again
+                                    Some((track, in_playlist, liked))
+                                        if confirmed_playlist.as_ref() != Some(&p.id) =>
+                                    {
+                                        let location = match (in_playlist, liked) {
+                                            (true, true) => "this playlist and your liked songs",
+                                            (true, false) => "this playlist",
+                                            _ => "your liked songs",
+                                        };
+                                        ui.show_toast(format!(
+                                            "{} is already in {location}, choose {} again to add it anyway",
+                                            track.name,
+                                            p.name
+                                        ));
+                                        if let Some(PopupState::UserPlaylistList(
+                                            PlaylistPopupAction::AddTrack {
+                                                confirmed_playlist,
+                                                ..
+                                            },
+                                            _,
+                                        )) = &mut ui.popup
+                                        {
+                                            *confirmed_playlist = Some(p.id.clone());
+                                        }
+                                    }
+                                    _ => {
+                                        client_pub.send(ClientRequest::AddPlayableToPlaylist(
+                                            p.id.clone(),
+                                            track_id.into(),
+                                        ))?;
+                                        ui.popup = None;
+                                    }
+                                }
                             }
-                        };
+                        }
                         Ok(())
                     },
                     |ui: &mut UIStateGuard| {
@@ -184,7 +333,7 @@ pub fn handle_key_sequence_for_popup(
                 let episode_id = episode_id.clone();
                 let data = state.data.read();
                 let items = data.user_data.modifiable_playlist_items(Some(*folder_id));
-                let filtered_items = filtered_items_from_query(&search_query, &items);
+                let filtered_items = filtered_items_from_query(&search_query.get_text(), &items);
 
                 handle_command_for_list_popup(
                     command,
@@ -277,7 +426,323 @@ pub fn handle_key_sequence_for_popup(
                 },
             )
         }
+        PopupState::EpisodeBookmarkList(episode_uri, _) => {
+            let positions = state
+                .data
+                .read()
+                .user_data
+                .episode_bookmarks
+                .get(episode_uri)
+                .map(|bookmarks| bookmarks.iter().map(|b| b.position_ms).collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                positions.len(),
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
+                        chrono::Duration::milliseconds(positions[id]),
+                    )))?;
+                    ui.popup = None;
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
+        PopupState::TrackRating(track_uri, _) => {
+            let track_uri = track_uri.clone();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                TRACK_RATING_CHOICES.len(),
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    let mut data = state.data.write();
+                    let mut annotation = data
+                        .user_data
+                        .track_annotations
+                        .get(&track_uri)
+                        .cloned()
+                        .unwrap_or_default();
+                    annotation.rating = TRACK_RATING_CHOICES[id];
+                    data.user_data.set_track_annotation(
+                        track_uri.clone(),
+                        annotation,
+                        &config::get_config().cache_folder,
+                    );
+                    ui.popup = None;
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
+        PopupState::PlaylistMerge { playlists, .. } => {
+            let n_items = PLAYLIST_MERGE_N_OPTIONS + playlists.len();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                n_items,
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    let Some(PopupState::PlaylistMerge {
+                        playlists,
+                        chosen,
+                        order,
+                        deduplicate,
+                        ..
+                    }) = &mut ui.popup
+                    else {
+                        return Ok(());
+                    };
+
+                    match id {
+                        0 => *order = order.next(),
+                        1 => *deduplicate = !*deduplicate,
+                        2 => {
+                            let playlists = playlists
+                                .iter()
+                                .zip(chosen.iter())
+                                .filter(|(_, chosen)| **chosen)
+                                .map(|(p, _)| p.clone())
+                                .collect::<Vec<_>>();
+                            // at least two playlists are needed for a merge
+                            if playlists.len() >= 2 {
+                                client_pub.send(ClientRequest::MergePlaylists {
+                                    playlists,
+                                    order: *order,
+                                    deduplicate: *deduplicate,
+                                })?;
+                                ui.popup = None;
+                            }
+                        }
+                        _ => {
+                            let chosen = &mut chosen[id - PLAYLIST_MERGE_N_OPTIONS];
+                            *chosen = !*chosen;
+                        }
+                    }
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
+        PopupState::LikedTrackGenreList(_) => {
+            let n_genres = state.data.read().user_data.saved_tracks_by_genre().len();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                n_genres,
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    let mut data = state.data.write();
+                    let (tracks_id, context) = {
+                        let (genre, tracks) = &data.user_data.saved_tracks_by_genre()[id];
+                        (
+                            crate::state::TracksId::new(
+                                format!("tracks:liked-genre:{genre}"),
+                                format!("Liked {genre}"),
+                            ),
+                            crate::state::Context::Tracks {
+                                tracks: tracks.iter().map(|t| (*t).clone()).collect(),
+                                desc: format!("User's liked tracks of genre {genre}"),
+                            },
+                        )
+                    };
+
+                    // the tracks are grouped from the (local) user data,
+                    // so the context is created without making any request
+                    data.caches.context.insert(
+                        tracks_id.uri.clone(),
+                        context,
+                        *crate::state::TTL_CACHE_DURATION,
+                    );
+                    ui.new_page(PageState::Context {
+                        id: None,
+                        context_page_type: ContextPageType::Browsing(ContextId::Tracks(tracks_id)),
+                        state: None,
+                    });
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
+        PopupState::TrackDetails(track_uri, _) => {
+            let artists = state
+                .data
+                .read()
+                .caches
+                .track_details
+                .get(track_uri)
+                .map(|details| details.artists.clone())
+                .unwrap_or_default();
+            let n_fields = if artists.is_empty() {
+                0
+            } else {
+                crate::state::TrackDetails::N_METADATA_FIELDS + artists.len()
+            };
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                n_fields,
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    // choosing an artist's field goes to the artist's page
+                    if let Some(artist) = id
+                        .checked_sub(crate::state::TrackDetails::N_METADATA_FIELDS)
+                        .and_then(|i| artists.get(i))
+                    {
+                        ui.new_page(PageState::Context {
+                            id: None,
+                            context_page_type: ContextPageType::Browsing(ContextId::Artist(
+                                artist.id.clone(),
+                            )),
+                            state: None,
+                        });
+                    }
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
+        PopupState::TrackDecadeList { name, tracks, .. } => {
+            let decades = group_tracks_by_decade(tracks)
+                .into_iter()
+                .map(|(decade, tracks)| (decade, tracks.into_iter().cloned().collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+            let name = name.clone();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                decades.len(),
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    let (decade, tracks) = decades.into_iter().nth(id).expect("invalid index");
+                    let tracks_id = crate::state::TracksId::new(
+                        format!("tracks:decade:{name}:{decade}"),
+                        format!("{name}: {decade}s"),
+                    );
+
+                    // the tracks are grouped from the (cached) source tracks,
+                    // so the context is created without making any request
+                    state.data.write().caches.context.insert(
+                        tracks_id.uri.clone(),
+                        crate::state::Context::Tracks {
+                            tracks,
+                            desc: format!("{name} released in the {decade}s"),
+                        },
+                        *crate::state::TTL_CACHE_DURATION,
+                    );
+                    ui.new_page(PageState::Context {
+                        id: None,
+                        context_page_type: ContextPageType::Browsing(ContextId::Tracks(tracks_id)),
+                        state: None,
+                    });
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
+        PopupState::SmartPlaylistList(_) => {
+            let playlists = &config::get_config().app_config.smart_playlists;
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                playlists.len(),
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    ui.new_page(PageState::Context {
+                        id: None,
+                        context_page_type: ContextPageType::Browsing(ContextId::Tracks(
+                            playlists[id].tracks_id(),
+                        )),
+                        state: None,
+                    });
+                    client_pub
+                        .send(ClientRequest::GetSmartPlaylistTracks(playlists[id].clone()))?;
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
+        #[cfg(feature = "fx")]
+        PopupState::VisualizationPreferenceTargets(targets, _) => {
+            let targets = targets.clone();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                targets.len(),
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    let cache_folder = config::get_config().cache_folder.clone();
+                    let key = targets[id].key();
+                    let preference = ui.visualization;
+                    crate::db::write_in_background(
+                        "store visualization preference into the database",
+                        move || {
+                            crate::db::store_visualization_preference(
+                                &cache_folder,
+                                &key,
+                                &preference,
+                            )
+                        },
+                    );
+                    ui.popup = None;
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
         PopupState::DeviceList(_) => {
+            if command == Command::ToggleDeviceGroupMember {
+                let mut player = state.player.write();
+                let Some(device) = ui
+                    .popup
+                    .as_ref()
+                    .and_then(PopupState::list_selected)
+                    .and_then(|id| player.devices.get(id))
+                    .cloned()
+                else {
+                    return Ok(true);
+                };
+                // Spotify plays on a single device per account, so grouped devices don't
+                // play simultaneously, they only share volume changes and playback moves
+                if let Some(pos) = player.device_group.iter().position(|id| *id == device.id) {
+                    player.device_group.remove(pos);
+                    ui.show_toast(format!("Removed {} from the device group", device.name));
+                } else {
+                    player.device_group.push(device.id);
+                    ui.show_toast(format!(
+                        "Added {} to the device group (playback stays on one device at a time)",
+                        device.name
+                    ));
+                }
+                return Ok(true);
+            }
+
             let player = state.player.read();
 
             handle_command_for_list_popup(
@@ -348,38 +813,211 @@ fn handle_key_sequence_for_create_playlist_popup(
     Ok(false)
 }
 
-fn handle_key_sequence_for_search_popup(
+fn handle_key_sequence_for_create_episode_bookmark_popup(
+    key_sequence: &KeySequence,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> bool {
+    let Some(PopupState::EpisodeBookmarkCreate {
+        episode_uri,
+        position,
+        note,
+    }) = &mut ui.popup
+    else {
+        return false;
+    };
+    if key_sequence.keys.len() == 1 {
+        match &key_sequence.keys[0] {
+            Key::None(crossterm::event::KeyCode::Enter) => {
+                state.data.write().user_data.add_episode_bookmark(
+                    episode_uri.clone(),
+                    EpisodeBookmark {
+                        position_ms: position.num_milliseconds(),
+                        note: note.get_text(),
+                    },
+                    &config::get_config().cache_folder,
+                );
+                ui.popup = None;
+                return true;
+            }
+            k => {
+                if note.input(k).is_some() {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn handle_key_sequence_for_edit_track_tags_popup(
     key_sequence: &KeySequence,
-    client_pub: &flume::Sender<ClientRequest>,
     state: &SharedState,
     ui: &mut UIStateGuard,
+) -> bool {
+    let Some(PopupState::TrackTagsEdit { track_uri, tags }) = &mut ui.popup else {
+        return false;
+    };
+    if key_sequence.keys.len() == 1 {
+        match &key_sequence.keys[0] {
+            Key::None(crossterm::event::KeyCode::Enter) => {
+                let mut data = state.data.write();
+                let mut annotation = data
+                    .user_data
+                    .track_annotations
+                    .get(track_uri)
+                    .cloned()
+                    .unwrap_or_default();
+                annotation.tags.clear();
+                for tag in tags.get_text().split(',').map(str::trim) {
+                    if !tag.is_empty() && !annotation.tags.iter().any(|t| t == tag) {
+                        annotation.tags.push(tag.to_string());
+                    }
+                }
+                data.user_data.set_track_annotation(
+                    track_uri.clone(),
+                    annotation,
+                    &config::get_config().cache_folder,
+                );
+                ui.popup = None;
+                return true;
+            }
+            k => {
+                if tags.input(k).is_some() {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn handle_key_sequence_for_rename_playlist_popup(
+    key_sequence: &KeySequence,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
 ) -> Result<bool> {
-    // handle user's input that updates the search query
-    let Some(PopupState::Search { ref mut query }) = &mut ui.popup else {
+    let Some(PopupState::PlaylistRename { playlist_id, name }) = &mut ui.popup else {
         return Ok(false);
     };
     if key_sequence.keys.len() == 1 {
-        if let Key::None(c) = key_sequence.keys[0] {
-            match c {
-                crossterm::event::KeyCode::Char(c) => {
-                    query.push(c);
-                    ui.current_page_mut().select(0);
+        match &key_sequence.keys[0] {
+            Key::None(crossterm::event::KeyCode::Enter) => {
+                if !name.is_empty() {
+                    client_pub.send(ClientRequest::ChangePlaylistDetails {
+                        playlist_id: playlist_id.clone(),
+                        name: Some(name.get_text()),
+                        desc: None,
+                    })?;
+                }
+                ui.popup = None;
+                return Ok(true);
+            }
+            k => {
+                if name.input(k).is_some() {
                     return Ok(true);
                 }
-                crossterm::event::KeyCode::Backspace => {
-                    if query.is_empty() {
-                        // close search popup when user presses backspace on empty search
-                        ui.popup = None;
-                    } else {
-                        query.pop().unwrap();
-                        ui.current_page_mut().select(0);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Handle key sequence for the track removal confirmation popup,
+/// in which `y` (or `enter`) confirms the removal and `n` cancels it
+fn handle_key_sequence_for_track_removal_confirmation_popup(
+    key_sequence: &KeySequence,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let [Key::None(key)] = key_sequence.keys[..] else {
+        return Ok(false);
+    };
+    match key {
+        crossterm::event::KeyCode::Char('y') | crossterm::event::KeyCode::Enter => {
+            if let Some(PopupState::TrackRemovalConfirmation(track, removal)) = ui.popup.take() {
+                client_pub.send(match removal {
+                    TrackRemoval::Playlist(playlist_id, _) => {
+                        ClientRequest::DeleteTrackFromPlaylist(playlist_id, track.id)
+                    }
+                    TrackRemoval::Liked => {
+                        ClientRequest::DeleteFromLibrary(ItemId::Track(track.id))
                     }
+                })?;
+            }
+            Ok(true)
+        }
+        crossterm::event::KeyCode::Char('n') => {
+            ui.popup = None;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+fn handle_key_sequence_for_edit_playlist_description_popup(
+    key_sequence: &KeySequence,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let Some(PopupState::PlaylistDescriptionEdit { playlist_id, desc }) = &mut ui.popup else {
+        return Ok(false);
+    };
+    if key_sequence.keys.len() == 1 {
+        match &key_sequence.keys[0] {
+            Key::Ctrl(crossterm::event::KeyCode::Char('s')) => {
+                // Spotify doesn't keep line breaks in playlist descriptions,
+                // so the description's lines are joined with spaces
+                let desc = desc
+                    .get_text()
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                client_pub.send(ClientRequest::ChangePlaylistDetails {
+                    playlist_id: playlist_id.clone(),
+                    name: None,
+                    desc: Some(desc),
+                })?;
+                ui.popup = None;
+                return Ok(true);
+            }
+            k => {
+                if desc.input(k).is_some() {
                     return Ok(true);
                 }
-                _ => {}
             }
         }
     }
+    Ok(false)
+}
+
+fn handle_key_sequence_for_search_popup(
+    key_sequence: &KeySequence,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    // handle user's input that updates the search query
+    let Some(PopupState::Search { ref mut query }) = &mut ui.popup else {
+        return Ok(false);
+    };
+    if let [key] = key_sequence.keys[..] {
+        if key == Key::None(crossterm::event::KeyCode::Backspace) && query.is_empty() {
+            // close search popup when user presses backspace on empty search
+            ui.popup = None;
+            return Ok(true);
+        }
+        match query.input(&key) {
+            Some(InputEffect::TextChanged) => {
+                ui.current_page_mut().select(0);
+                return Ok(true);
+            }
+            Some(_) => return Ok(true),
+            None => {}
+        }
+    }
 
     // key sequence not handle by the popup should be moved to the current page's event handler
     page::handle_key_sequence_for_page(key_sequence, client_pub, state, ui)
@@ -553,6 +1191,113 @@ pub fn handle_item_action(
     }
 }
 
+/// Handle key sequence for the command palette popup, in which typed characters update
+/// the search query and the chosen command or action is run after closing the palette
+fn handle_key_sequence_for_command_palette_popup(
+    key_sequence: &KeySequence,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let Some(PopupState::CommandPalette {
+        query, list_state, ..
+    }) = &mut ui.popup
+    else {
+        return Ok(false);
+    };
+
+    if let [key] = key_sequence.keys[..] {
+        if key == Key::None(crossterm::event::KeyCode::Backspace) && query.is_empty() {
+            ui.popup = None;
+            return Ok(true);
+        }
+        match query.input(&key) {
+            Some(InputEffect::TextChanged) => {
+                list_state.select(Some(0));
+                return Ok(true);
+            }
+            Some(_) => return Ok(true),
+            None => {}
+        }
+    }
+
+    let Some(command) = config::get_config()
+        .keymap_config
+        .find_command_from_key_sequence(key_sequence)
+    else {
+        return Ok(false);
+    };
+    let Some(PopupState::CommandPalette { query, entries, .. }) = &ui.popup else {
+        return Ok(false);
+    };
+    // a Spotify link typed or pasted into the palette is opened instead of running a command
+    let text = query.get_text();
+    if crate::utils::is_spotify_link(&text) {
+        if command == Command::ChooseSelected {
+            ui.popup = None;
+            client_pub.send(ClientRequest::OpenSpotifyLink {
+                link: text,
+                play: false,
+            })?;
+            return Ok(true);
+        }
+        return handle_command_for_list_popup(
+            command,
+            ui,
+            1,
+            |_, _| {},
+            |_, _| Ok(()),
+            |ui| {
+                ui.popup = None;
+            },
+        );
+    }
+    // a `theme import <file>` text imports the file's color scheme as a new theme
+    if let Some(file) = crate::utils::theme_import_path(&text) {
+        if command == Command::ChooseSelected {
+            ui.popup = None;
+            let configs = config::get_config();
+            match config::import_theme(&file, &configs.config_folder) {
+                Ok(theme) => {
+                    ui.show_toast(format!("Imported and applied theme {}", theme.name));
+                    ui.theme = theme;
+                }
+                Err(err) => ui.show_toast(format!("Failed to import theme: {err:#}")),
+            }
+            return Ok(true);
+        }
+        return handle_command_for_list_popup(
+            command,
+            ui,
+            1,
+            |_, _| {},
+            |_, _| Ok(()),
+            |ui| {
+                ui.popup = None;
+            },
+        );
+    }
+    let entries = filtered_items_from_query(&query.get_text(), entries)
+        .into_iter()
+        .map(|e| e.command_or_action)
+        .collect::<Vec<_>>();
+
+    handle_command_for_list_popup(
+        command,
+        ui,
+        entries.len(),
+        |_, _| {},
+        |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+            ui.popup = None;
+            handle_command_or_action(entries[id], client_pub, state, ui)?;
+            Ok(())
+        },
+        |ui: &mut UIStateGuard| {
+            ui.popup = None;
+        },
+    )
+}
+
 /// Handle key sequence for playlist search popup (AddTrack/AddEpisode)
 fn handle_key_sequence_for_playlist_search_popup(
     key_sequence: &KeySequence,
@@ -566,35 +1311,26 @@ fn handle_key_sequence_for_playlist_search_popup(
     let search_query = match action {
         PlaylistPopupAction::AddTrack { search_query, .. }
         | PlaylistPopupAction::AddEpisode { search_query, .. }
-        | PlaylistPopupAction::Browse { search_query, .. } => search_query,
+        | PlaylistPopupAction::Browse { search_query, .. }
+        | PlaylistPopupAction::Diff { search_query, .. } => search_query,
     };
 
-    if key_sequence.keys.len() == 1 {
-        if let Key::None(c) = key_sequence.keys[0] {
-            match c {
-                crossterm::event::KeyCode::Char(c) => {
-                    search_query.push(c);
-                    // Reset selection to first item when search query changes
-                    if let Some(popup) = &mut ui.popup {
-                        popup.list_select(Some(0));
-                    }
-                    return true;
-                }
-                crossterm::event::KeyCode::Backspace => {
-                    if search_query.is_empty() {
-                        // Close playlist popup when user presses backspace on empty search
-                        ui.popup = None;
-                    } else {
-                        search_query.pop();
-                        // Reset selection to first item when search query changes
-                        if let Some(popup) = &mut ui.popup {
-                            popup.list_select(Some(0));
-                        }
-                    }
-                    return true;
+    if let [key] = key_sequence.keys[..] {
+        if key == Key::None(crossterm::event::KeyCode::Backspace) && search_query.is_empty() {
+            // Close playlist popup when user presses backspace on empty search
+            ui.popup = None;
+            return true;
+        }
+        match search_query.input(&key) {
+            Some(InputEffect::TextChanged) => {
+                // Reset selection to first item when search query changes
+                if let Some(popup) = &mut ui.popup {
+                    popup.list_select(Some(0));
                 }
-                _ => {}
+                return true;
             }
+            Some(_) => return true,
+            None => {}
         }
     }
 
@@ -5,7 +5,7 @@ use crate::{
         construct_album_actions, construct_artist_actions, construct_playlist_actions,
         construct_show_actions,
     },
-    state::{Episode, MutableWindowState, Show, UIStateGuard},
+    state::{Episode, MutableWindowState, PlaylistPreference, Show, UIStateGuard},
 };
 use command::Action;
 use rand::Rng;
@@ -53,7 +53,7 @@ pub fn handle_action_for_focused_context_page(
                 ),
                 ArtistFocusState::TopTracks => handle_action_for_selected_item(
                     action,
-                    &ui.search_filtered_items(top_tracks),
+                    &ui.search_filtered_tracks(top_tracks, &data),
                     &data,
                     ui,
                     client_pub,
@@ -66,7 +66,7 @@ pub fn handle_action_for_focused_context_page(
             | Context::Playlist { tracks, .. },
         ) => handle_action_for_selected_item(
             action,
-            &ui.search_filtered_items(tracks),
+            &ui.search_filtered_tracks(tracks, &data),
             &data,
             ui,
             client_pub,
@@ -132,6 +132,19 @@ pub fn handle_command_for_focused_context_window(
             if let Some(tracks) = data.context_tracks_mut(context_id) {
                 tracks.sort_by(|x, y| order.compare(x, y));
             }
+            // remember the playlist's order
+            if let ContextId::Playlist(id) = context_id {
+                let preference = PlaylistPreference {
+                    track_order: Some(order),
+                    reversed: false,
+                    ..data.user_data.playlist_preference(id)
+                };
+                data.user_data.set_playlist_preference(
+                    id,
+                    preference,
+                    &config::get_config().cache_folder,
+                );
+            }
             return Ok(true);
         }
         // reverse ordering command
@@ -140,6 +153,35 @@ pub fn handle_command_for_focused_context_window(
             if let Some(tracks) = data.context_tracks_mut(context_id) {
                 tracks.reverse();
             }
+            if let ContextId::Playlist(id) = context_id {
+                let mut preference = data.user_data.playlist_preference(id);
+                preference.reversed = !preference.reversed;
+                data.user_data.set_playlist_preference(
+                    id,
+                    preference,
+                    &config::get_config().cache_folder,
+                );
+            }
+            return Ok(true);
+        }
+        // reset ordering command, which refetches the playlist to restore its own order
+        if command == Command::ResetTrackOrder {
+            let ContextId::Playlist(id) = context_id else {
+                return Ok(false);
+            };
+            let mut data = state.data.write();
+            let preference = PlaylistPreference {
+                track_order: None,
+                reversed: false,
+                ..data.user_data.playlist_preference(id)
+            };
+            data.user_data.set_playlist_preference(
+                id,
+                preference,
+                &config::get_config().cache_folder,
+            );
+            data.caches.stale_contexts.insert(context_id.uri());
+            client_pub.send(ClientRequest::GetContext(context_id.clone()))?;
             return Ok(true);
         }
     }
@@ -214,7 +256,43 @@ fn handle_playlist_modify_command(
     data: &DataReadGuard,
     ui: &mut UIStateGuard,
 ) -> Result<bool> {
+    // a grabbed track is moved with navigation commands until it's dropped or the move is cancelled
+    let grabbed_track = ui.current_page_mut().grabbed_track_mut().and_then(|t| *t);
+    if let Some(from) = grabbed_track {
+        match command {
+            Command::GrabPlaylistItem | Command::ResumePause | Command::ChooseSelected => {
+                if id != from {
+                    // the move is applied in a single reorder request
+                    client_pub.send(ClientRequest::ReorderPlaylistItems {
+                        playlist_id: playlist_id.clone_static(),
+                        insert_index: id,
+                        range_start: from,
+                        range_length: None,
+                        snapshot_id: None,
+                    })?;
+                }
+            }
+            Command::ClosePopup => {
+                ui.current_page_mut().select(from);
+            }
+            _ => return Ok(false),
+        }
+        if let Some(grabbed_track) = ui.current_page_mut().grabbed_track_mut() {
+            *grabbed_track = None;
+        }
+        return Ok(true);
+    }
+
     match command {
+        Command::GrabPlaylistItem => {
+            // the track's positions are unknown in a list filtered by a search query
+            if !matches!(ui.popup, Some(PopupState::Search { .. })) {
+                if let Some(grabbed_track) = ui.current_page_mut().grabbed_track_mut() {
+                    *grabbed_track = Some(id);
+                }
+            }
+            return Ok(true);
+        }
         Command::MovePlaylistItemUp => {
             if id > 0 {
                 client_pub.send(ClientRequest::ReorderPlaylistItems {
@@ -256,6 +334,23 @@ fn handle_playlist_modify_command(
     Ok(false)
 }
 
+/// The IDs of the tracks to play, skipping the tracks unavailable in the user's market
+fn playable_track_ids(tracks: &[Track]) -> Vec<PlayableId<'static>> {
+    tracks
+        .iter()
+        .filter(|t| !t.is_unavailable)
+        .map(|t| t.id.clone().into())
+        .collect()
+}
+
+/// Notify the user that a track can't be played in their market
+fn show_unavailable_track_toast(track: &Track, ui: &mut UIStateGuard) {
+    ui.show_toast(format!(
+        "{} is unavailable in your market, use the SearchForAlternative action to find another version",
+        track.name
+    ));
+}
+
 fn handle_command_for_track_table_window(
     command: Command,
     client_pub: &flume::Sender<ClientRequest>,
@@ -265,7 +360,7 @@ fn handle_command_for_track_table_window(
     ui: &mut UIStateGuard,
 ) -> Result<bool> {
     let id = ui.current_page_mut().selected().unwrap_or_default();
-    let filtered_tracks = ui.search_filtered_items(tracks);
+    let filtered_tracks = ui.search_filtered_tracks(tracks, data);
     if id >= filtered_tracks.len() {
         return Ok(false);
     }
@@ -302,16 +397,41 @@ fn handle_command_for_track_table_window(
     }
 
     match command {
+        Command::ChooseSelected | Command::AddSelectedItemToQueue
+            if filtered_tracks[id].is_unavailable =>
+        {
+            show_unavailable_track_toast(filtered_tracks[id], ui);
+        }
         Command::PlayRandom | Command::ChooseSelected => {
             let uri = if command == Command::PlayRandom {
-                tracks[rand::rng().random_range(0..tracks.len())].id.uri()
+                // the random track is picked among the tracks of the played list of URIs
+                let playable_tracks = tracks
+                    .iter()
+                    .filter(|t| !t.is_unavailable)
+                    .collect::<Vec<_>>();
+                if playable_tracks.is_empty() {
+                    ui.show_toast("No track is available in your market".to_string());
+                    return Ok(true);
+                }
+                playable_tracks[rand::rng().random_range(0..playable_tracks.len())]
+                    .id
+                    .uri()
             } else {
                 filtered_tracks[id].id.uri()
             };
 
+            let preference = match context_id {
+                Some(ContextId::Playlist(ref id)) => data.user_data.playlist_preference(id),
+                _ => PlaylistPreference::default(),
+            };
+
             let base_playback = match context_id {
                 None | Some(ContextId::Tracks(_)) => {
-                    Playback::URIs(tracks.iter().map(|t| t.id.clone().into()).collect(), None)
+                    Playback::URIs(playable_track_ids(tracks), None)
+                }
+                // a playlist with a preferred order is played in the table's (sorted) order
+                Some(ContextId::Playlist(_)) if preference.has_track_order() => {
+                    Playback::URIs(playable_track_ids(tracks), None)
                 }
                 Some(ContextId::Show(_)) => unreachable!(
                     "show context should be handled by handle_command_for_episode_table_window"
@@ -322,13 +442,13 @@ fn handle_command_for_track_table_window(
             client_pub.send(ClientRequest::Player(PlayerRequest::StartPlayback(
                 base_playback
                     .uri_offset(uri, config::get_config().app_config.tracks_playback_limit),
-                None,
+                preference.shuffle,
             )))?;
         }
         Command::ShowActionsOnSelectedItem => {
             let actions = command::construct_track_actions(filtered_tracks[id], data);
             ui.popup = Some(PopupState::ActionList(
-                Box::new(ActionListItem::Track(tracks[id].clone(), actions)),
+                Box::new(ActionListItem::Track(filtered_tracks[id].clone(), actions)),
                 ListState::default(),
             ));
         }
@@ -337,6 +457,10 @@ fn handle_command_for_track_table_window(
                 filtered_tracks[id].id.clone().into(),
             ))?;
         }
+        #[cfg(feature = "preview")]
+        Command::PeekTrack => {
+            client_pub.send(ClientRequest::PeekTrack(filtered_tracks[id].id.clone()))?;
+        }
         Command::JumpToHighlightTrackInContext => {
             ui.popup = None;
             let selected_track = filtered_tracks[id];
@@ -375,6 +499,9 @@ pub fn handle_command_for_track_list_window(
         return Ok(true);
     }
     match command {
+        Command::ChooseSelected | Command::AddSelectedItemToQueue if tracks[id].is_unavailable => {
+            show_unavailable_track_toast(tracks[id], ui);
+        }
         Command::ChooseSelected => {
             // for a track list, `ChooseSelected` on a track
             // will start a `URIs` playback containing only that track.
@@ -398,6 +525,10 @@ pub fn handle_command_for_track_list_window(
                 tracks[id].id.clone().into(),
             ))?;
         }
+        #[cfg(feature = "preview")]
+        Command::PeekTrack => {
+            client_pub.send(ClientRequest::PeekTrack(tracks[id].id.clone()))?;
+        }
         _ => return Ok(false),
     }
     Ok(true)
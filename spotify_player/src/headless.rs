@@ -0,0 +1,75 @@
+//! Headless (`--no-ui`) mode, which runs the application without its terminal UI
+//! and prints a single-line playback status to stdout instead.
+
+use crate::{config, state::SharedState};
+use std::io::Write;
+
+/// Construct the status line of the current playback, which includes
+/// the playing state, the track's name and artists, the playback's position and the volume
+pub fn construct_status_line(state: &SharedState) -> String {
+    let configs = config::get_config();
+    let player = state.player.read();
+
+    let (Some(item), Some(playback)) = (
+        player.currently_playing(),
+        player.buffered_playback.as_ref(),
+    ) else {
+        return "No playback found".to_string();
+    };
+
+    let (name, artists, duration) = match item {
+        rspotify::model::PlayableItem::Track(track) => (
+            track.name.clone(),
+            crate::utils::map_join(&track.artists, |a| &a.name, ", "),
+            track.duration,
+        ),
+        rspotify::model::PlayableItem::Episode(episode) => (
+            episode.name.clone(),
+            episode.show.publisher.clone(),
+            episode.duration,
+        ),
+        rspotify::model::PlayableItem::Unknown(_) => return "Unknown playback item".to_string(),
+    };
+
+    let progress = std::cmp::min(player.playback_progress().unwrap_or_default(), duration);
+    let status = if playback.is_playing {
+        &configs.app_config.play_icon
+    } else {
+        &configs.app_config.pause_icon
+    };
+    let volume = match playback.mute_state {
+        Some(volume) => format!("{volume}% (muted)"),
+        None => format!("{}%", playback.volume.unwrap_or_default()),
+    };
+
+    format!(
+        "{status} {name} • {artists} [{}/{}] volume: {volume}",
+        crate::utils::format_duration(&progress),
+        crate::utils::format_duration(&duration),
+    )
+}
+
+/// Run the headless mode's status line printer.
+///
+/// The status line is re-printed in place every `app_refresh_duration_in_ms` milliseconds.
+/// The playback can be controlled using CLI commands sent via the client socket.
+pub fn run(state: &SharedState) {
+    let refresh_duration = std::time::Duration::from_millis(
+        config::get_config().app_config.app_refresh_duration_in_ms,
+    );
+    let mut last_line = String::new();
+    let mut stdout = std::io::stdout();
+
+    loop {
+        let line = construct_status_line(state);
+        if line != last_line {
+            // move the cursor to the start of the line and clear it before printing the new status
+            if let Err(err) = write!(stdout, "\r\x1b[2K{line}").and_then(|()| stdout.flush()) {
+                tracing::error!("Failed to print the playback status line: {err:#}");
+            }
+            last_line = line;
+        }
+
+        std::thread::sleep(refresh_duration);
+    }
+}
@@ -0,0 +1,246 @@
+//! User-defined hook commands executed upon player events (the `[hooks]` config section)
+//!
+//! Events are handled by a dedicated thread (see [`trigger`]), so that neither the hook commands
+//! nor the plugins receiving the events delay the caller.
+
+use crate::{config, state::Track};
+use rspotify::model::Id;
+use std::{
+    collections::HashMap,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+/// The channel to the hooks thread, which handles the triggered events in order
+static EVENTS: LazyLock<flume::Sender<(HookEvent, HookContext)>> = LazyLock::new(|| {
+    let (tx, rx) = flume::unbounded();
+    std::thread::Builder::new()
+        .name("hooks".to_string())
+        .spawn(move || run(&rx))
+        .expect("spawn the hooks thread");
+    tx
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A player event that can trigger a hook
+pub enum HookEvent {
+    TrackChanged,
+    Paused,
+    Liked,
+    VolumeChanged,
+}
+
+#[derive(Debug, Default, Clone)]
+/// Data used to fill in a hook command's templated arguments
+pub struct HookContext {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub uri: String,
+    pub volume: Option<u32>,
+}
+
+impl HookContext {
+    pub fn from_full_track(track: &rspotify::model::FullTrack) -> Self {
+        Self {
+            title: track.name.clone(),
+            artist: crate::utils::map_join(&track.artists, |a| &a.name, ", "),
+            album: track.album.name.clone(),
+            uri: track.id.as_ref().map(Id::uri).unwrap_or_default(),
+            volume: None,
+        }
+    }
+
+    pub fn from_track(track: &Track) -> Self {
+        Self {
+            title: track.name.clone(),
+            artist: track.artists_info(),
+            album: track.album_info(),
+            uri: track.id.uri(),
+            volume: None,
+        }
+    }
+
+    /// A summary context of several tracks liked at once: the tracks' unique artists and albums
+    /// and their space-separated URIs.
+    pub fn from_tracks(tracks: &[Track]) -> Self {
+        if let [track] = tracks {
+            return Self::from_track(track);
+        }
+        let mut artists = vec![];
+        let mut albums = vec![];
+        for track in tracks {
+            let artist = track.artists_info();
+            if !artists.contains(&artist) {
+                artists.push(artist);
+            }
+            let album = track.album_info();
+            if !albums.contains(&album) {
+                albums.push(album);
+            }
+        }
+        Self {
+            title: format!("{} tracks", tracks.len()),
+            artist: artists.join(", "),
+            album: albums.join(", "),
+            uri: tracks
+                .iter()
+                .map(|t| t.id.uri())
+                .collect::<Vec<_>>()
+                .join(" "),
+            volume: None,
+        }
+    }
+
+    pub fn from_playable_item(item: &rspotify::model::PlayableItem) -> Self {
+        match item {
+            rspotify::model::PlayableItem::Track(track) => Self::from_full_track(track),
+            rspotify::model::PlayableItem::Episode(episode) => Self {
+                title: episode.name.clone(),
+                artist: episode.show.publisher.clone(),
+                album: episode.show.name.clone(),
+                uri: episode.id.uri(),
+                volume: None,
+            },
+            rspotify::model::PlayableItem::Unknown(_) => Self::default(),
+        }
+    }
+
+    /// Replace the template placeholders (`{title}`, `{artist}`, `{album}`, `{uri}`, `{volume}`) in an argument.
+    ///
+    /// The argument is scanned once, so placeholders inside the replaced values (e.g. a title containing `{uri}`)
+    /// are kept as is.
+    fn render(&self, arg: &str) -> String {
+        let mut rendered = String::with_capacity(arg.len());
+        let mut rest = arg;
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let Some(end) = rest.find('}') else {
+                break;
+            };
+            match &rest[1..end] {
+                "title" => rendered.push_str(&self.title),
+                "artist" => rendered.push_str(&self.artist),
+                "album" => rendered.push_str(&self.album),
+                "uri" => rendered.push_str(&self.uri),
+                "volume" => {
+                    if let Some(volume) = self.volume {
+                        rendered.push_str(&volume.to_string());
+                    }
+                }
+                // not a placeholder, so only its opening brace is consumed
+                _ => {
+                    rendered.push('{');
+                    rest = &rest[1..];
+                    continue;
+                }
+            }
+            rest = &rest[end + 1..];
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
+/// Trigger the hook command (if any) associated with an event, and send the event to the enabled plugins.
+///
+/// Nothing is triggered in a private session.
+///
+/// The event is handled in the hooks thread. A hook is executed at most once per `hooks.rate_limit_in_ms`
+/// milliseconds: the events triggered in between are debounced, and the latest of them executes the hook
+/// once the rate limit has passed, so the hook is always run with the latest data.
+pub fn trigger(event: HookEvent, context: &HookContext) {
+    if crate::state::is_private_session() {
+        return;
+    }
+    if EVENTS.send((event, context.clone())).is_err() {
+        tracing::error!("Failed to send {event:?} event to the hooks thread");
+    }
+}
+
+/// The execution state of a hook
+#[derive(Default)]
+struct HookState {
+    last_execution: Option<Instant>,
+    /// the latest event triggered within the rate limit, to be executed once the rate limit passes
+    pending: Option<HookContext>,
+}
+
+/// Run the hooks thread, which handles the events received from `rx`
+fn run(rx: &flume::Receiver<(HookEvent, HookContext)>) {
+    let mut states: HashMap<HookEvent, HookState> = HashMap::new();
+
+    loop {
+        let rate_limit =
+            Duration::from_millis(config::get_config().app_config.hooks.rate_limit_in_ms);
+        // wait for a new event, or until the earliest pending event is due
+        let deadline = states
+            .values()
+            .filter(|state| state.pending.is_some())
+            .filter_map(|state| Some(state.last_execution? + rate_limit))
+            .min();
+        let received = match deadline {
+            Some(deadline) => rx.recv_deadline(deadline),
+            None => rx.recv().map_err(|_| flume::RecvTimeoutError::Disconnected),
+        };
+
+        match received {
+            Ok((event, context)) => {
+                #[cfg(feature = "plugins")]
+                crate::plugins::dispatch_event(event, &context);
+
+                let state = states.entry(event).or_default();
+                if state
+                    .last_execution
+                    .is_some_and(|t| t.elapsed() < rate_limit)
+                {
+                    tracing::info!("Debounced {event:?} hook because of rate limiting");
+                    state.pending = Some(context);
+                } else {
+                    state.last_execution = Some(Instant::now());
+                    execute(event, &context);
+                }
+            }
+            Err(flume::RecvTimeoutError::Timeout) => {
+                for (event, state) in &mut states {
+                    if state
+                        .last_execution
+                        .is_some_and(|t| t.elapsed() >= rate_limit)
+                    {
+                        if let Some(context) = state.pending.take() {
+                            state.last_execution = Some(Instant::now());
+                            execute(*event, &context);
+                        }
+                    }
+                }
+            }
+            Err(flume::RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+/// Execute the hook command (if any) associated with an event in a separate thread
+fn execute(event: HookEvent, context: &HookContext) {
+    let hooks = &config::get_config().app_config.hooks;
+    let cmd = match event {
+        HookEvent::TrackChanged => &hooks.track_changed,
+        HookEvent::Paused => &hooks.paused,
+        HookEvent::Liked => &hooks.liked,
+        HookEvent::VolumeChanged => &hooks.volume_changed,
+    };
+    let Some(cmd) = cmd else {
+        return;
+    };
+
+    let cmd = config::Command {
+        command: cmd.command.clone(),
+        args: cmd.args.iter().map(|arg| context.render(arg)).collect(),
+    };
+    std::thread::spawn(move || {
+        tracing::info!("Executing {event:?} hook: {cmd:?}");
+        if let Err(err) = cmd.execute(None) {
+            tracing::warn!("Failed to execute {event:?} hook: {err:#}");
+        }
+    });
+}
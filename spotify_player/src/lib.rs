@@ -0,0 +1,531 @@
+//! A Spotify player in the terminal with full feature parity.
+//!
+//! The application's entry point is [`run`], called by the `spotify_player` binary.
+//! The modules used by the integration tests and the benchmarks are public.
+
+// the library only serves the binary, the integration tests and the benchmarks, so lints about a public API don't apply
+#![allow(
+    clippy::must_use_candidate,
+    clippy::return_self_not_must_use,
+    clippy::missing_panics_doc,
+    clippy::new_without_default,
+    clippy::should_implement_trait
+)]
+
+#[cfg(feature = "streaming")]
+mod audio_device;
+mod auth;
+mod capabilities;
+mod cli;
+pub mod client;
+mod command;
+pub mod config;
+mod db;
+mod event;
+#[cfg(feature = "streaming")]
+mod headless;
+mod hooks;
+mod key;
+#[cfg(feature = "media-control")]
+mod media_control;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod playlist_folders;
+#[cfg(feature = "plugins")]
+mod plugins;
+mod power;
+#[cfg(feature = "preview")]
+mod preview;
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod shutdown;
+#[cfg(unix)]
+mod signals;
+pub mod state;
+#[cfg(feature = "streaming")]
+mod streaming;
+mod token;
+pub mod ui;
+pub mod utils;
+#[cfg(feature = "zeroconf")]
+mod zeroconf;
+
+use anyhow::{Context, Result};
+use std::io::Write;
+
+fn init_spotify(
+    client_pub: &flume::Sender<client::ClientRequest>,
+    client: &client::AppClient,
+    state: &state::SharedState,
+) -> Result<()> {
+    client.initialize_playback(state);
+
+    // request user data shown across the pages, the other user data (saved albums, followed artists, etc)
+    // is requested when a page showing it is first opened (see `client::start_player_event_watchers`)
+    client_pub.send(client::ClientRequest::GetCurrentUser)?;
+    client_pub.send(client::ClientRequest::GetUserPlaylists)?;
+    client_pub.send(client::ClientRequest::GetUserSavedTracks)?;
+
+    // request the data of the page restored from the previous session
+    match state.ui.lock().current_page() {
+        state::PageState::Search { current_query, .. } if !current_query.is_empty() => {
+            client_pub.send(client::ClientRequest::Search(current_query.clone()))?;
+        }
+        state::PageState::Browse { .. } => {
+            client_pub.send(client::ClientRequest::GetBrowseCategories)?;
+        }
+        state::PageState::ArtistFeed { .. } => {
+            client_pub.send(client::ClientRequest::GetArtistFeed)?;
+        }
+        state::PageState::SharedPlaylists { .. } => {
+            client_pub.send(client::ClientRequest::GetSharedPlaylistAdds)?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn init_logging(log_folder: &std::path::Path) -> Result<()> {
+    if std::env::var_os("RUST_LOG").is_some_and(|x| x == "off") {
+        // Don't create log files if logging is disabled.
+        return Ok(());
+    }
+
+    let log_prefix = format!(
+        "spotify-player-{}",
+        chrono::Local::now().format("%y-%m-%d-%H-%M")
+    );
+
+    // initialize the application's logging
+    if std::env::var("RUST_LOG").is_err() {
+        // default to log the current crate and librespot crates
+        std::env::set_var("RUST_LOG", "spotify_player=info,librespot=info");
+    }
+    if !log_folder.exists() {
+        std::fs::create_dir_all(log_folder)?;
+    }
+    let log_file = std::fs::File::create(log_folder.join(format!("{log_prefix}.log")))
+        .context("failed to create log file")?;
+    tracing_subscriber::fmt::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_ansi(false)
+        .with_writer(std::sync::Mutex::new(log_file))
+        .init();
+
+    // initialize the application's panic backtrace
+    let backtrace_file = std::fs::File::create(log_folder.join(format!("{log_prefix}.backtrace")))
+        .context("failed to create backtrace file")?;
+    let backtrace_file = std::sync::Mutex::new(backtrace_file);
+    std::panic::set_hook(Box::new(move |info| {
+        let mut file = backtrace_file.lock().unwrap();
+        let backtrace = backtrace::Backtrace::new();
+        writeln!(&mut file, "Got a panic: {info:#?}\n").unwrap();
+        writeln!(&mut file, "Stack backtrace:\n{backtrace:?}").unwrap();
+    }));
+
+    Ok(())
+}
+
+/// Start the application, opening the given Spotify link (and whether to play it), if any
+#[tokio::main]
+async fn start_app(state: &state::SharedState, link: Option<(String, bool)>) -> Result<()> {
+    let configs = config::get_config();
+
+    // client channels
+    let (client_pub, client_sub) = flume::unbounded::<client::ClientRequest>();
+
+    #[cfg(feature = "pulseaudio-backend")]
+    {
+        // set environment variables for PulseAudio
+        if std::env::var("PULSE_PROP_application.name").is_err() {
+            std::env::set_var("PULSE_PROP_application.name", "spotify-player");
+        }
+        if std::env::var("PULSE_PROP_application.icon_name").is_err() {
+            std::env::set_var("PULSE_PROP_application.icon_name", "spotify");
+        }
+        if std::env::var("PULSE_PROP_stream.description").is_err() {
+            std::env::set_var(
+                "PULSE_PROP_stream.description",
+                format!(
+                    "Spotify Connect endpoint ({})",
+                    configs.app_config.device.name
+                ),
+            );
+        }
+        if std::env::var("PULSE_PROP_media.software").is_err() {
+            std::env::set_var("PULSE_PROP_media.software", "Spotify");
+        }
+        if std::env::var("PULSE_PROP_media.role").is_err() {
+            std::env::set_var("PULSE_PROP_media.role", "music");
+        }
+    }
+
+    // create a Spotify API client
+    let client = client::AppClient::new()
+        .await
+        .context("construct app client")?;
+
+    #[cfg(feature = "zeroconf")]
+    let discovery = if state.is_streaming_enabled() {
+        let mut discovery = zeroconf::start_discovery(&client)?;
+        // Instead of authenticating the application in the browser, a Spotify client
+        // connecting to the integrated player can send its user's credentials
+        if client.auth_config().cache.credentials().is_none() {
+            zeroconf::wait_for_credentials(&mut discovery, &client).await?;
+        }
+        Some(discovery)
+    } else {
+        None
+    };
+
+    client
+        .new_session(Some(state), true)
+        .await
+        .context("initialize new Spotify session")?;
+
+    // The UI is started right after the session is created, so that its first paint only waits
+    // for the authentication. The Spotify data is requested in the background afterwards.
+    let mut ui_task = None;
+    if !state.is_daemon && !state.is_headless {
+        // spawn tasks needed for running the application UI
+
+        // terminal event handler task
+        tokio::task::spawn_blocking({
+            let client_pub = client_pub.clone();
+            let state = state.clone();
+            move || {
+                event::start_event_handler(&state, &client_pub);
+            }
+        });
+
+        // application UI task
+        ui_task = Some(tokio::task::spawn_blocking({
+            let state = state.clone();
+            move || ui::run(&state)
+        }));
+    }
+
+    // initialize Spotify-related stuff
+    init_spotify(&client_pub, &client, state).context("Failed to initialize the Spotify data")?;
+    if let Some((link, play)) = link {
+        client_pub.send(client::ClientRequest::OpenSpotifyLink { link, play })?;
+    }
+
+    #[cfg(feature = "plugins")]
+    plugins::init(&configs.config_folder, &client_pub);
+
+    // Spawn application's tasks
+    let mut tasks = Vec::new();
+    // tasks accepting commands from other processes, which are stopped upon the application's shutdown
+    let mut ipc_tasks = Vec::new();
+
+    // client socket task (for handling CLI commands)
+    ipc_tasks.push(tokio::task::spawn({
+        let client = client.clone();
+        let state = state.clone();
+        async move {
+            let port = configs.app_config.client_port;
+            tracing::info!("Starting a client socket at 127.0.0.1:{port}");
+            match tokio::net::UdpSocket::bind(("127.0.0.1", port)).await {
+                Ok(socket) => cli::start_socket(client, socket, Some(state)).await,
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to create a client socket for handling CLI commands: {err:#}"
+                    );
+                }
+            }
+        }
+    }));
+
+    // remote control server task
+    #[cfg(feature = "remote")]
+    ipc_tasks.push(tokio::task::spawn({
+        let client = client.clone();
+        let state = state.clone();
+        async move {
+            if let Err(err) = remote::start_server(client, state).await {
+                tracing::warn!("Failed to start the remote control server: {err:#}");
+            }
+        }
+    }));
+
+    // MQTT client task
+    #[cfg(feature = "mqtt")]
+    ipc_tasks.push(tokio::task::spawn({
+        let client = client.clone();
+        let state = state.clone();
+        async move {
+            mqtt::start_client(client, state).await;
+        }
+    }));
+
+    // cover image task
+    #[cfg(feature = "image")]
+    tasks.push(tokio::task::spawn({
+        let client = client.clone();
+        let state = state.clone();
+        async move {
+            client.handle_loaded_cover_images(&state).await;
+        }
+    }));
+
+    #[cfg(feature = "zeroconf")]
+    if let Some(discovery) = discovery {
+        // zeroconf discovery task
+        tasks.push(tokio::task::spawn(zeroconf::handle_discovered_credentials(
+            discovery,
+            client.clone(),
+            state.clone(),
+        )));
+    }
+
+    #[cfg(feature = "daemon")]
+    if state.is_daemon
+        && state.is_streaming_enabled()
+        && configs.app_config.daemon_idle_timeout_mins > 0
+    {
+        // idle shutdown watcher task
+        tasks.push(tokio::task::spawn({
+            let state = state.clone();
+            let client = client.clone();
+            async move {
+                client::start_idle_shutdown_watcher(state, client).await;
+            }
+        }));
+    }
+
+    // shutdown watcher task
+    tasks.push(tokio::task::spawn(shutdown::start_shutdown_watcher(
+        state.clone(),
+        client.clone(),
+        ui_task,
+        ipc_tasks
+            .iter()
+            .map(tokio::task::JoinHandle::abort_handle)
+            .collect(),
+    )));
+    tasks.extend(ipc_tasks);
+
+    // playback signal handler task
+    #[cfg(unix)]
+    tasks.push(tokio::task::spawn(signals::start_signal_handler(
+        client_pub.clone(),
+    )));
+
+    // power source watcher task
+    tasks.push(tokio::task::spawn(power::start_power_watcher(
+        state.clone(),
+    )));
+
+    // client event handler task
+    tasks.push(tokio::task::spawn({
+        let state = state.clone();
+        async move {
+            client::start_client_handler(state, client, client_sub).await;
+        }
+    }));
+
+    // player event watcher task
+    tasks.push(tokio::task::spawn({
+        let state = state.clone();
+        let client_pub = client_pub.clone();
+        async move {
+            client::start_player_event_watchers(state, client_pub).await;
+        }
+    }));
+
+    #[cfg(feature = "streaming")]
+    if state.is_headless {
+        // headless status line task
+        tokio::task::spawn_blocking({
+            let state = state.clone();
+            move || headless::run(&state)
+        });
+    }
+
+    #[cfg(feature = "streaming")]
+    if configs.app_config.pause_on_audio_device_change && state.is_streaming_enabled() {
+        // audio output device watcher task
+        tokio::task::spawn_blocking({
+            let state = state.clone();
+            let client_pub = client_pub.clone();
+            move || {
+                if let Err(err) = audio_device::start_device_watcher(&state, &client_pub) {
+                    tracing::warn!(
+                        "Failed to start the application's audio output device watcher: {err:#}"
+                    );
+                }
+            }
+        });
+    }
+
+    #[cfg(feature = "media-control")]
+    if configs.app_config.enable_media_control {
+        // media control task
+        tokio::task::spawn_blocking({
+            let state = state.clone();
+            move || {
+                if let Err(err) = media_control::start_event_watcher(&state, client_pub) {
+                    tracing::error!(
+                        "Failed to start the application's media control event watcher: err={err:#?}"
+                    );
+                }
+            }
+        });
+
+        // the winit's event loop must be run in the main thread
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        {
+            // Start an event loop that listens to OS window events.
+            //
+            // MacOS and Windows require an open window to be able to listen to media
+            // control events. The below code will create an invisible window on startup
+            // to listen to such events.
+            let event_loop = winit::event_loop::EventLoop::new()?;
+            #[allow(deprecated)]
+            event_loop.run(move |_, _| {})?;
+        }
+    }
+
+    for task in tasks {
+        task.await?;
+    }
+
+    Ok(())
+}
+
+/// Run the application with the command line arguments
+pub fn run() -> Result<()> {
+    // librespot depends on hyper-rustls which requires a crypto provider to be set up.
+    // TODO: see if this can be fixed upstream
+    rustls::crypto::ring::default_provider()
+        .install_default()
+        .unwrap();
+
+    // parse command line arguments
+    let args = cli::init_cli()?.get_matches();
+
+    // initialize the application's cache and config folders
+    let config_folder: std::path::PathBuf = args
+        .get_one::<String>("config-folder")
+        .expect("config-folder should have default value")
+        .into();
+    if !config_folder.exists() {
+        std::fs::create_dir_all(&config_folder)?;
+    }
+
+    let cache_folder: std::path::PathBuf = args
+        .get_one::<String>("cache-folder")
+        .expect("cache-folder should have a default value")
+        .into();
+    let cache_audio_folder = cache_folder.join("audio");
+    if !cache_audio_folder.exists() {
+        std::fs::create_dir_all(&cache_audio_folder)?;
+    }
+    let cache_image_folder = cache_folder.join("image");
+    if !cache_image_folder.exists() {
+        std::fs::create_dir_all(&cache_image_folder)?;
+    }
+
+    // initialize the application configs
+    {
+        let mut configs = config::Configs::new(&config_folder, &cache_folder)?;
+        if configs.app_config.log_folder.is_none() {
+            // set the log folder to be the cache folder if it is not set
+            configs.app_config.log_folder = Some(cache_folder);
+        }
+        if let Some(theme) = args.get_one::<String>("theme") {
+            // override the theme config if user specifies a `theme` cli argument
+            theme.clone_into(&mut configs.app_config.theme);
+        }
+        config::set_config(configs);
+    }
+
+    match args.subcommand() {
+        None => {
+            // initialize the application's log
+            let log_folder = config::get_config()
+                .app_config
+                .log_folder
+                .as_deref()
+                .expect("log_folder is set");
+
+            init_logging(log_folder).context("failed to initialize application's logging")?;
+
+            // log the application's configurations
+            tracing::info!("Configurations: {:?}", config::get_config());
+
+            let is_daemon;
+
+            #[cfg(feature = "daemon")]
+            {
+                is_daemon = args.get_flag("daemon");
+                if is_daemon {
+                    if cfg!(any(target_os = "macos", target_os = "windows"))
+                        && cfg!(feature = "media-control")
+                    {
+                        eprintln!("Running the application as a daemon on windows/macos with `media-control` feature enabled is not supported!");
+                        std::process::exit(1);
+                    }
+
+                    tracing::info!("Starting the application as a daemon...");
+                    let daemonize = daemonize::Daemonize::new();
+                    daemonize.start()?;
+                }
+            }
+
+            #[cfg(not(feature = "daemon"))]
+            {
+                is_daemon = false;
+            }
+
+            #[cfg(feature = "streaming")]
+            let is_headless = args.get_flag("no-ui");
+            #[cfg(not(feature = "streaming"))]
+            let is_headless = false;
+            if is_headless {
+                tracing::info!("Starting the application in headless mode...");
+            }
+
+            if !is_daemon && !is_headless {
+                capabilities::probe();
+            }
+
+            let is_compact = args.get_flag("compact");
+            let state = std::sync::Arc::new(state::State::new(is_daemon, is_headless, is_compact));
+            if args.get_flag("low-power") {
+                state.set_low_power(true);
+            }
+            if !args.get_flag("fresh") {
+                if let Some(session) = state::load_cache_data(
+                    state::CacheKey::UISession,
+                    &config::get_config().cache_folder,
+                ) {
+                    state.ui.lock().restore_session(session);
+                }
+            }
+            state.player.write().last_playback = state::load_cache_data(
+                state::CacheKey::LastPlayback,
+                &config::get_config().cache_folder,
+            );
+            if args.get_flag("now-playing") {
+                state.ui.lock().new_page(state::PageState::NowPlaying);
+            }
+            #[cfg(feature = "fx")]
+            if let Some(&seed) = args.get_one::<u64>("animation-seed") {
+                state.ui.lock().set_clock(ui::clock::Clock::deterministic(
+                    seed,
+                    std::time::Duration::ZERO,
+                ));
+            }
+            let link = args
+                .get_one::<String>("link")
+                .map(|link| (link.clone(), args.get_flag("play")));
+            start_app(&state, link)
+        }
+        Some((cmd, args)) => cli::handle_cli_subcommand(cmd, args),
+    }
+}
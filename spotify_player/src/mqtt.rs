@@ -0,0 +1,133 @@
+//! MQTT integration (`mqtt` feature), which publishes the current playback state
+//! and accepts playback commands to enable smart-home automations (e.g. Home Assistant).
+
+use crate::{
+    cli::{self, Command, Request},
+    client::AppClient,
+    config,
+    state::{PlaybackSnapshot, SharedState},
+};
+use anyhow::Result;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+
+/// The delay before reconnecting to the broker after a connection error
+const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+/// The interval to check for playback changes
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Start a MQTT client connecting to the broker specified in `app_config.mqtt`
+pub async fn start_client(client: AppClient, state: SharedState) {
+    let configs = config::get_config();
+    let mqtt_config = &configs.app_config.mqtt;
+
+    let mut options = MqttOptions::new(&mqtt_config.client_id, &mqtt_config.host, mqtt_config.port);
+    options.set_keep_alive(std::time::Duration::from_secs(30));
+    if let Some(ref username) = mqtt_config.username {
+        options.set_credentials(username, mqtt_config.password.clone().unwrap_or_default());
+    }
+
+    let (mqtt, mut eventloop) = AsyncClient::new(options, 16);
+    tracing::info!(
+        "Starting a MQTT client connecting to {}:{}",
+        mqtt_config.host,
+        mqtt_config.port
+    );
+
+    tokio::task::spawn(publish_playback_state(mqtt.clone(), state.clone()));
+
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                // (re-)subscribe to the command topic upon a new connection
+                if let Err(err) = mqtt.try_subscribe(&mqtt_config.command_topic, QoS::AtLeastOnce) {
+                    tracing::warn!("Failed to subscribe to the MQTT command topic: {err:#}");
+                }
+            }
+            Ok(Event::Incoming(Packet::Publish(publish)))
+                if publish.topic == mqtt_config.command_topic =>
+            {
+                let client = client.clone();
+                let state = state.clone();
+                tokio::task::spawn(async move {
+                    if let Err(err) = handle_command(&client, &state, &publish.payload).await {
+                        tracing::error!("Failed to handle MQTT command: {err:#}");
+                    }
+                });
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!("MQTT connection error: {err:#}");
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Parse a playback command from a MQTT payload.
+///
+/// The payload has the same JSON format as the CLI's `playback` commands. For convenience,
+/// commands without arguments can also be specified as plain strings (e.g. `Next` instead of `"Next"`).
+fn parse_command(payload: &[u8]) -> Result<Command> {
+    if let Ok(command) = serde_json::from_slice(payload) {
+        return Ok(command);
+    }
+    let payload = std::str::from_utf8(payload)?.trim();
+    Ok(serde_json::from_value(serde_json::Value::String(
+        payload.to_string(),
+    ))?)
+}
+
+async fn handle_command(client: &AppClient, state: &SharedState, payload: &[u8]) -> Result<()> {
+    let command = parse_command(payload)?;
+    tracing::info!("Got a MQTT command: {command:?}");
+    cli::handle_socket_request(client, Some(state), Request::Playback(command)).await?;
+    Ok(())
+}
+
+/// Check if two playback snapshots have different states, ignoring their progress
+fn is_state_changed(old: Option<&PlaybackSnapshot>, new: Option<&PlaybackSnapshot>) -> bool {
+    match (old, new) {
+        (Some(old), Some(new)) => {
+            (&old.uri, old.is_playing, old.volume, &old.device)
+                != (&new.uri, new.is_playing, new.volume, &new.device)
+        }
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+/// Publish the playback state to the state topic upon a playback change
+/// and periodically (every `publish_interval_in_secs` seconds), except in a private session
+async fn publish_playback_state(mqtt: AsyncClient, state: SharedState) {
+    let mqtt_config = &config::get_config().app_config.mqtt;
+    let publish_interval = std::time::Duration::from_secs(mqtt_config.publish_interval_in_secs);
+
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut last_snapshot = None;
+    let mut last_published_time = None::<std::time::Instant>;
+
+    loop {
+        interval.tick().await;
+        // the playback isn't shared in a private session, and the last published state
+        // is kept to detect the change once the session ends
+        if crate::state::is_private_session() {
+            continue;
+        }
+
+        let snapshot = state.player.read().playback_snapshot();
+        let should_publish = last_published_time.is_none_or(|t| t.elapsed() >= publish_interval)
+            || is_state_changed(last_snapshot.as_ref(), snapshot.as_ref());
+
+        if should_publish {
+            let payload = serde_json::to_vec(&snapshot).expect("serializable snapshot");
+            if let Err(err) = mqtt
+                .publish(&mqtt_config.state_topic, QoS::AtLeastOnce, true, payload)
+                .await
+            {
+                tracing::warn!("Failed to publish the playback state: {err:#}");
+            }
+            last_published_time = Some(std::time::Instant::now());
+        }
+        last_snapshot = snapshot;
+    }
+}
@@ -0,0 +1,565 @@
+//! Third-party plugins (`plugins` feature) compiled to WebAssembly, which can add pages,
+//! visualizations and actions to the application.
+//!
+//! A plugin is loaded from a `.wasm` (or `.wat`) file in the `plugins` folder of the application's config folder.
+//! Plugins run sandboxed with a limited amount of fuel per call: they receive the player's events
+//! and can only affect the application by sending client requests.
+//!
+//! The events and the renderings of plugins' pages and visualizations are handled by a dedicated thread,
+//! so that a slow plugin never blocks the UI: the UI draws the latest rendering of a plugin (see [`Plugin::rendered`]).
+//!
+//! Data is exchanged with a plugin as UTF-8 strings in its memory. A string returned by
+//! a plugin's function is packed into an `i64` as `(ptr << 32) | len`.
+//! See the plugins documentation (`docs/config.md`) for the complete ABI.
+
+use crate::{
+    client::{ClientRequest, PlayerRequest},
+    config,
+    hooks::{HookContext, HookEvent},
+    state::{load_cache_data, store_cache_data, CacheKey, EpisodeId, PlayableId, TrackId},
+};
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, OnceLock,
+    },
+};
+use wasmi::{Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store};
+
+/// The amount of fuel (roughly, the number of executed instructions) a plugin can use per call
+const FUEL_PER_CALL: u64 = 500_000;
+
+/// The module of the functions imported by plugins from the application
+const HOST_MODULE: &str = "spotify_player";
+
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+/// The channel to the plugins thread, which handles the plugins' tasks in order
+static TASKS: LazyLock<flume::Sender<Task>> = LazyLock::new(|| {
+    let (tx, rx) = flume::unbounded();
+    std::thread::Builder::new()
+        .name("plugins".to_string())
+        .spawn(move || run(&rx))
+        .expect("spawn the plugins thread");
+    tx
+});
+
+/// A task handled by the plugins thread
+enum Task {
+    Event(HookEvent, HookContext),
+    Render {
+        plugin: &'static Plugin,
+        kind: &'static str,
+        name: String,
+        size: (u16, u16),
+        beat: f64,
+    },
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+/// The pages, visualizations and actions provided by a plugin
+pub struct Manifest {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub pages: Vec<String>,
+    #[serde(default)]
+    pub visualizations: Vec<String>,
+    #[serde(default)]
+    pub actions: Vec<String>,
+}
+
+/// A loaded plugin
+pub struct Plugin {
+    /// the plugin's name, which is its file name without the extension
+    pub name: String,
+    pub manifest: Manifest,
+    enabled: AtomicBool,
+    runtime: Mutex<Runtime>,
+    /// the latest renderings of the plugin's pages and visualizations, by kind and name
+    renderings: Mutex<HashMap<(&'static str, String), Rendering>>,
+}
+
+/// The latest rendering of a plugin's page or visualization
+#[derive(Default)]
+struct Rendering {
+    /// the rendered text lines, or the rendering's error
+    output: Option<Result<Vec<String>, String>>,
+    /// whether a new rendering is requested and not done yet
+    pending: bool,
+}
+
+/// The sandboxed instance of a plugin's module
+struct Runtime {
+    store: Store<HostState>,
+    instance: Instance,
+}
+
+/// Data of the application accessible to a plugin's imported functions
+struct HostState {
+    plugin: String,
+    client_pub: flume::Sender<ClientRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// A request sent by a plugin with the `send_request` function
+enum PluginRequest {
+    NextTrack,
+    PreviousTrack,
+    ResumePause,
+    Shuffle,
+    Repeat,
+    SetVolume { volume: u8 },
+    Seek { position_ms: i64 },
+    AddToQueue { uri: String },
+    OpenLink { link: String, play: bool },
+}
+
+/// An item listed in the plugin manager page
+pub enum PluginItem<'a> {
+    Plugin(&'a Plugin),
+    Page(&'a Plugin, &'a str),
+    Action(&'a Plugin, &'a str),
+    Visualization(&'a Plugin, &'a str),
+}
+
+/// Load the plugins from the `plugins` folder of the config folder.
+///
+/// Plugins failing to load are skipped, and plugins disabled in a previous session stay disabled.
+pub fn init(config_folder: &Path, client_pub: &flume::Sender<ClientRequest>) {
+    let folder = config_folder.join("plugins");
+    let mut paths = match std::fs::read_dir(&folder) {
+        Ok(entries) => entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "wasm" || ext == "wat")
+            })
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+
+    let disabled: Vec<String> = load_cache_data(
+        CacheKey::DisabledPlugins,
+        &config::get_config().cache_folder,
+    )
+    .unwrap_or_default();
+
+    let engine = Engine::new(Config::default().consume_fuel(true));
+    let plugins = paths
+        .into_iter()
+        .filter_map(
+            |path| match Plugin::load(&engine, &path, client_pub.clone()) {
+                Ok(plugin) => {
+                    tracing::info!("Loaded plugin {}: {:?}", plugin.name, plugin.manifest);
+                    plugin
+                        .enabled
+                        .store(!disabled.contains(&plugin.name), Ordering::Relaxed);
+                    Some(plugin)
+                }
+                Err(err) => {
+                    tracing::error!("Failed to load plugin {}: {err:#}", path.display());
+                    None
+                }
+            },
+        )
+        .collect();
+
+    let _ = PLUGINS.set(plugins);
+}
+
+/// Get the loaded plugins
+pub fn plugins() -> &'static [Plugin] {
+    PLUGINS.get().map_or(&[], Vec::as_slice)
+}
+
+/// Get a loaded plugin by its name
+pub fn get(name: &str) -> Option<&'static Plugin> {
+    plugins().iter().find(|p| p.name == name)
+}
+
+/// List the plugins with the pages, actions and visualizations of the enabled ones
+pub fn items() -> Vec<PluginItem<'static>> {
+    let mut items = Vec::new();
+    for plugin in plugins() {
+        items.push(PluginItem::Plugin(plugin));
+        if !plugin.is_enabled() {
+            continue;
+        }
+        let manifest = &plugin.manifest;
+        items.extend(manifest.pages.iter().map(|p| PluginItem::Page(plugin, p)));
+        items.extend(
+            manifest
+                .actions
+                .iter()
+                .map(|a| PluginItem::Action(plugin, a)),
+        );
+        items.extend(
+            manifest
+                .visualizations
+                .iter()
+                .map(|v| PluginItem::Visualization(plugin, v)),
+        );
+    }
+    items
+}
+
+/// Send a player event to the enabled plugins, which handle it in the plugins thread
+pub fn dispatch_event(event: HookEvent, context: &HookContext) {
+    if TASKS.send(Task::Event(event, context.clone())).is_err() {
+        tracing::error!("Failed to send {event:?} event to the plugins thread");
+    }
+}
+
+/// Run the plugins thread, which handles the tasks received from `rx`
+fn run(rx: &flume::Receiver<Task>) {
+    while let Ok(task) = rx.recv() {
+        match task {
+            Task::Event(event, context) => handle_event(event, &context),
+            Task::Render {
+                plugin,
+                kind,
+                name,
+                size,
+                beat,
+            } => {
+                let output = plugin
+                    .render(kind, &name, size, beat)
+                    .map_err(|err| format!("{err:#}"));
+                plugin.renderings.lock().insert(
+                    (kind, name),
+                    Rendering {
+                        output: Some(output),
+                        pending: false,
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn handle_event(event: HookEvent, context: &HookContext) {
+    let name = match event {
+        HookEvent::TrackChanged => "track_changed",
+        HookEvent::Paused => "paused",
+        HookEvent::Liked => "liked",
+        HookEvent::VolumeChanged => "volume_changed",
+    };
+    let event = serde_json::json!({
+        "event": name,
+        "title": context.title,
+        "artist": context.artist,
+        "album": context.album,
+        "uri": context.uri,
+        "volume": context.volume,
+    })
+    .to_string();
+
+    for plugin in plugins().iter().filter(|p| p.is_enabled()) {
+        if let Err(err) = plugin.call("spotify_player_on_event", &event, false) {
+            tracing::warn!(
+                "Failed to send {name} event to plugin {}: {err:#}",
+                plugin.name
+            );
+        }
+    }
+}
+
+impl Plugin {
+    fn load(
+        engine: &Engine,
+        path: &Path,
+        client_pub: flume::Sender<ClientRequest>,
+    ) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .context("plugin file without name")?
+            .to_string_lossy()
+            .to_string();
+        let bytes = std::fs::read(path).context("read plugin file")?;
+        let module = Module::new(engine, bytes).map_err(|err| anyhow::anyhow!("{err}"))?;
+
+        let mut store = Store::new(
+            engine,
+            HostState {
+                plugin: name.clone(),
+                client_pub,
+            },
+        );
+        store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|err| anyhow::anyhow!("{err}"))?;
+        let instance = new_linker(engine)?
+            .instantiate_and_start(&mut store, &module)
+            .map_err(|err| anyhow::anyhow!("instantiate plugin: {err}"))?;
+
+        let mut runtime = Runtime { store, instance };
+        let manifest = runtime.manifest()?;
+
+        Ok(Self {
+            name,
+            manifest,
+            enabled: AtomicBool::new(true),
+            runtime: Mutex::new(runtime),
+            renderings: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable the plugin, which is remembered across sessions
+    pub fn set_enabled(&self, enabled: bool) -> Result<()> {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        let disabled = plugins()
+            .iter()
+            .filter(|p| !p.is_enabled())
+            .map(|p| p.name.clone())
+            .collect::<Vec<_>>();
+        store_cache_data(
+            CacheKey::DisabledPlugins,
+            &config::get_config().cache_folder,
+            &disabled,
+        )
+    }
+
+    /// Run one of the plugin's actions
+    pub fn run_action(&self, action: &str) -> Result<()> {
+        self.call("spotify_player_run_action", action, false)?;
+        Ok(())
+    }
+
+    /// Get the latest rendering of one of the plugin's pages or visualizations (`kind` is `page`
+    /// or `visualization`), or `None` if the plugin hasn't rendered it yet.
+    ///
+    /// A new rendering for an area of the given size is requested from the plugins thread,
+    /// unless the previous one is still in progress.
+    pub fn rendered(
+        &'static self,
+        kind: &'static str,
+        name: &str,
+        size: (u16, u16),
+        beat: f64,
+    ) -> Option<Result<Vec<String>>> {
+        let mut renderings = self.renderings.lock();
+        let rendering = renderings.entry((kind, name.to_string())).or_default();
+        if !rendering.pending {
+            let task = Task::Render {
+                plugin: self,
+                kind,
+                name: name.to_string(),
+                size,
+                beat,
+            };
+            rendering.pending = TASKS.send(task).is_ok();
+        }
+        rendering
+            .output
+            .clone()
+            .map(|output| output.map_err(|err| anyhow::anyhow!(err)))
+    }
+
+    /// Render one of the plugin's pages or visualizations into the text lines
+    /// to be displayed in an area of the given size
+    fn render(
+        &self,
+        kind: &str,
+        name: &str,
+        (width, height): (u16, u16),
+        beat: f64,
+    ) -> Result<Vec<String>> {
+        let input = serde_json::json!({
+            "kind": kind,
+            "name": name,
+            "width": width,
+            "height": height,
+            "beat": beat,
+        })
+        .to_string();
+        let output = self.call("spotify_player_render", &input, true)?;
+        Ok(output
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Call an exported function of the plugin with a string argument.
+    ///
+    /// Optional functions not exported by the plugin are skipped and return `None`.
+    fn call(&self, func: &str, input: &str, has_output: bool) -> Result<Option<String>> {
+        let mut runtime = self.runtime.lock();
+        if runtime.instance.get_func(&runtime.store, func).is_none() {
+            return Ok(None);
+        }
+        runtime
+            .store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|err| anyhow::anyhow!("{err}"))?;
+        let (ptr, len) = runtime.write_input(input.as_bytes())?;
+        if has_output {
+            let f = runtime
+                .instance
+                .get_typed_func::<(i32, i32), i64>(&runtime.store, func)
+                .map_err(|err| anyhow::anyhow!("{func}: {err}"))?;
+            let packed = f
+                .call(&mut runtime.store, (ptr, len))
+                .map_err(|err| anyhow::anyhow!("{func}: {err}"))?;
+            runtime.read_output(packed).map(Some)
+        } else {
+            let f = runtime
+                .instance
+                .get_typed_func::<(i32, i32), ()>(&runtime.store, func)
+                .map_err(|err| anyhow::anyhow!("{func}: {err}"))?;
+            f.call(&mut runtime.store, (ptr, len))
+                .map_err(|err| anyhow::anyhow!("{func}: {err}"))?;
+            Ok(None)
+        }
+    }
+}
+
+impl Runtime {
+    fn memory(&self) -> Result<Memory> {
+        self.instance
+            .get_memory(&self.store, "memory")
+            .context("plugin doesn't export its memory")
+    }
+
+    /// Get the plugin's manifest from its `spotify_player_manifest` function
+    fn manifest(&mut self) -> Result<Manifest> {
+        let f = self
+            .instance
+            .get_typed_func::<(), i64>(&self.store, "spotify_player_manifest")
+            .map_err(|err| anyhow::anyhow!("spotify_player_manifest: {err}"))?;
+        let packed = f
+            .call(&mut self.store, ())
+            .map_err(|err| anyhow::anyhow!("spotify_player_manifest: {err}"))?;
+        serde_json::from_str(&self.read_output(packed)?).context("parse plugin manifest")
+    }
+
+    /// Copy an argument into a buffer allocated in the plugin's memory
+    fn write_input(&mut self, input: &[u8]) -> Result<(i32, i32)> {
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, "spotify_player_alloc")
+            .map_err(|err| anyhow::anyhow!("spotify_player_alloc: {err}"))?;
+        let len = i32::try_from(input.len()).context("input too large")?;
+        let ptr = alloc
+            .call(&mut self.store, len)
+            .map_err(|err| anyhow::anyhow!("spotify_player_alloc: {err}"))?;
+        self.memory()?
+            .write(&mut self.store, ptr as usize, input)
+            .map_err(|err| anyhow::anyhow!("write plugin memory: {err}"))?;
+        Ok((ptr, len))
+    }
+
+    /// Read a string returned by the plugin
+    fn read_output(&self, packed: i64) -> Result<String> {
+        let (ptr, len) = unpack(packed);
+        let mut buf = vec![0; len];
+        self.memory()?
+            .read(&self.store, ptr, &mut buf)
+            .map_err(|err| anyhow::anyhow!("read plugin memory: {err}"))?;
+        String::from_utf8(buf).context("plugin returned invalid UTF-8")
+    }
+}
+
+/// Unpack the pointer and the length of a string returned by a plugin
+fn unpack(packed: i64) -> (usize, usize) {
+    let packed = packed as u64;
+    ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize)
+}
+
+/// Read a string argument of an imported function from the calling plugin's memory
+fn read_string(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Result<String> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .context("plugin doesn't export its memory")?;
+    let mut buf = vec![0; len as usize];
+    memory
+        .read(caller, ptr as usize, &mut buf)
+        .map_err(|err| anyhow::anyhow!("read plugin memory: {err}"))?;
+    String::from_utf8(buf).context("invalid UTF-8 string")
+}
+
+/// Construct a linker defining the functions imported by plugins.
+///
+/// The functions return `0` on success and `-1` on failure.
+fn new_linker(engine: &Engine) -> Result<Linker<HostState>> {
+    let mut linker = Linker::new(engine);
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "send_request",
+            |caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i32 {
+                let result = read_string(&caller, ptr, len).and_then(|request| {
+                    let request = serde_json::from_str(&request)
+                        .with_context(|| format!("invalid request {request}"))?;
+                    caller.data().client_pub.send(client_request(request)?)?;
+                    Ok(())
+                });
+                match result {
+                    Ok(()) => 0,
+                    Err(err) => {
+                        tracing::warn!(
+                            "Failed to handle a request from plugin {}: {err:#}",
+                            caller.data().plugin
+                        );
+                        -1
+                    }
+                }
+            },
+        )
+        .map_err(|err| anyhow::anyhow!("{err}"))?;
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "log",
+            |caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i32 {
+                match read_string(&caller, ptr, len) {
+                    Ok(message) => {
+                        tracing::info!("[plugin {}] {message}", caller.data().plugin);
+                        0
+                    }
+                    Err(_) => -1,
+                }
+            },
+        )
+        .map_err(|err| anyhow::anyhow!("{err}"))?;
+    Ok(linker)
+}
+
+/// Convert a plugin's request into a client request
+fn client_request(request: PluginRequest) -> Result<ClientRequest> {
+    Ok(match request {
+        PluginRequest::NextTrack => ClientRequest::Player(PlayerRequest::NextTrack),
+        PluginRequest::PreviousTrack => ClientRequest::Player(PlayerRequest::PreviousTrack),
+        PluginRequest::ResumePause => ClientRequest::Player(PlayerRequest::ResumePause),
+        PluginRequest::Shuffle => ClientRequest::Player(PlayerRequest::Shuffle),
+        PluginRequest::Repeat => ClientRequest::Player(PlayerRequest::Repeat),
+        PluginRequest::SetVolume { volume } => {
+            ClientRequest::Player(PlayerRequest::Volume(volume.min(100)))
+        }
+        PluginRequest::Seek { position_ms } => ClientRequest::Player(PlayerRequest::SeekTrack(
+            chrono::Duration::milliseconds(position_ms),
+        )),
+        PluginRequest::AddToQueue { uri } => {
+            let id = match (TrackId::from_uri(&uri), EpisodeId::from_uri(&uri)) {
+                (Ok(id), _) => PlayableId::Track(id.into_static()),
+                (_, Ok(id)) => PlayableId::Episode(id.into_static()),
+                _ => anyhow::bail!("invalid track or episode uri: {uri}"),
+            };
+            ClientRequest::AddPlayableToQueue(id)
+        }
+        PluginRequest::OpenLink { link, play } => ClientRequest::OpenSpotifyLink { link, play },
+    })
+}
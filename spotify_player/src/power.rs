@@ -0,0 +1,114 @@
+//! Watching the system's power source to switch the application into its low-power mode,
+//! which reduces the application's refresh rates and background requests on battery power.
+//!
+//! - On Linux, the power supplies are read from `/sys/class/power_supply`
+//! - On macOS, the power source is read from the output of `pmset -g batt`
+//! - On other platforms, the power source can't be detected, so the low-power mode
+//!   is only enabled by the `low_power_mode` config option or the `--low-power` flag
+
+use crate::{
+    config::{self, LowPowerMode},
+    state::SharedState,
+};
+
+/// The duration between two consecutive checks of the system's power source
+const POWER_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// The minimum duration between two consecutive UI refreshes in the low-power mode
+pub const LOW_POWER_APP_REFRESH_DURATION: std::time::Duration =
+    std::time::Duration::from_millis(100);
+
+/// The factor by which the playback refresh duration is multiplied in the low-power mode
+pub const LOW_POWER_PLAYBACK_REFRESH_FACTOR: u32 = 4;
+
+/// Start the application's power source watcher, which updates the application's low-power mode.
+///
+/// The low-power mode enabled on startup by the `--low-power` flag is kept regardless of the power source.
+pub async fn start_power_watcher(state: SharedState) {
+    if state.is_low_power() {
+        tracing::info!("Running the application in the low-power mode...");
+        return;
+    }
+
+    match config::get_config().app_config.low_power_mode {
+        LowPowerMode::Never => {}
+        LowPowerMode::Always => {
+            tracing::info!("Running the application in the low-power mode...");
+            state.set_low_power(true);
+        }
+        LowPowerMode::Auto => {
+            tracing::info!("Initializing application's power source watcher...");
+            loop {
+                let is_low_power = is_on_battery_power().unwrap_or(false);
+                if is_low_power != state.is_low_power() {
+                    tracing::info!(
+                        "Power source changed, {} the low-power mode...",
+                        if is_low_power {
+                            "enabling"
+                        } else {
+                            "disabling"
+                        }
+                    );
+                    state.set_low_power(is_low_power);
+                    if !state.is_daemon && !state.is_headless {
+                        state.ui.lock().show_toast(if is_low_power {
+                            "On battery power, switched to the low-power mode".to_string()
+                        } else {
+                            "Back on AC power, left the low-power mode".to_string()
+                        });
+                    }
+                }
+                tokio::time::sleep(POWER_CHECK_INTERVAL).await;
+            }
+        }
+    }
+}
+
+/// Check if the system is running on battery power, or `None` if it can't be detected
+#[cfg(target_os = "linux")]
+fn is_on_battery_power() -> Option<bool> {
+    let read = |path: &std::path::Path, name: &str| {
+        std::fs::read_to_string(path.join(name))
+            .ok()
+            .map(|s| s.trim().to_owned())
+    };
+
+    let mut has_battery = false;
+    for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        match read(&path, "type").as_deref() {
+            // the system is on AC power if any of its external power supplies is online
+            Some("Mains" | "USB") if read(&path, "online").as_deref() == Some("1") => {
+                return Some(false);
+            }
+            // ignore the batteries of peripherals (e.g. a wireless mouse)
+            Some("Battery") if read(&path, "scope").as_deref() != Some("Device") => {
+                if read(&path, "status").as_deref() == Some("Discharging") {
+                    return Some(true);
+                }
+                has_battery = true;
+            }
+            _ => {}
+        }
+    }
+    has_battery.then_some(false)
+}
+
+/// Check if the system is running on battery power, or `None` if it can't be detected
+#[cfg(target_os = "macos")]
+fn is_on_battery_power() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    // the first line is either "Now drawing from 'Battery Power'" or "Now drawing from 'AC Power'"
+    let source = output.lines().next()?;
+    Some(source.contains("Battery Power"))
+}
+
+/// Check if the system is running on battery power, or `None` if it can't be detected
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn is_on_battery_power() -> Option<bool> {
+    None
+}
@@ -0,0 +1,104 @@
+//! Playback of tracks' 30-second previews (`preview` feature) through the local audio output,
+//! independently of the Spotify Connect playback.
+//!
+//! Previews are played by a dedicated thread, which opens the default audio output while
+//! a preview is playing and closes it afterwards.
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use std::{sync::OnceLock, time::Duration};
+
+/// The interval of checking whether the playing preview has finished
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+static PLAYER: OnceLock<flume::Sender<Request>> = OnceLock::new();
+
+/// The URI of the track whose preview is playing
+static PLAYING: Mutex<Option<String>> = Mutex::new(None);
+
+enum Request {
+    /// Play a track's preview, specified by the track's URI and the preview's audio data,
+    /// along with a sender of the result of starting the preview
+    Play(String, Vec<u8>, flume::Sender<Result<()>>),
+    Stop,
+}
+
+/// Get the URI of the track whose preview is playing, if any
+pub fn playing() -> Option<String> {
+    PLAYING.lock().clone()
+}
+
+/// Play a track's preview, given the preview's audio data (e.g. an MP3 file),
+/// replacing the playing preview
+pub async fn play(uri: String, data: Vec<u8>) -> Result<()> {
+    let player = PLAYER.get_or_init(|| {
+        let (tx, rx) = flume::unbounded();
+        std::thread::Builder::new()
+            .name("preview".to_string())
+            .spawn(move || run(&rx))
+            .expect("spawn the preview thread");
+        tx
+    });
+    let (tx, rx) = flume::bounded(1);
+    player
+        .send(Request::Play(uri, data, tx))
+        .context("send the preview to the preview thread")?;
+    rx.recv_async()
+        .await
+        .context("receive the result of playing the preview")?
+}
+
+/// Stop the playing preview
+pub fn stop() {
+    if let Some(player) = PLAYER.get() {
+        let _ = player.send(Request::Stop);
+    }
+}
+
+/// Run the preview thread, which plays the previews received from `rx`
+fn run(rx: &flume::Receiver<Request>) {
+    // the audio output and the sink of the playing preview
+    let mut output: Option<(rodio::OutputStream, rodio::Sink)> = None;
+
+    loop {
+        let request = match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(request) => Some(request),
+            Err(flume::RecvTimeoutError::Timeout) => None,
+            Err(flume::RecvTimeoutError::Disconnected) => return,
+        };
+        match request {
+            Some(Request::Play(uri, data, result_tx)) => {
+                output = None;
+                let result = open_output(data).map(|o| {
+                    output = Some(o);
+                });
+                *PLAYING.lock() = result.is_ok().then_some(uri);
+                let _ = result_tx.send(result);
+            }
+            Some(Request::Stop) => {
+                output = None;
+                *PLAYING.lock() = None;
+            }
+            None => {
+                if output.as_ref().is_some_and(|(_, sink)| sink.empty()) {
+                    output = None;
+                    *PLAYING.lock() = None;
+                }
+            }
+        }
+    }
+}
+
+/// Open the default audio output and start playing a preview's audio data
+fn open_output(data: Vec<u8>) -> Result<(rodio::OutputStream, rodio::Sink)> {
+    let mut stream = rodio::OutputStreamBuilder::open_default_stream()
+        .context("open the default audio output")?;
+    // the application's UI would be messed up by the message printed to stderr when dropping the stream
+    stream.log_on_drop(false);
+
+    let source = rodio::Decoder::try_from(std::io::Cursor::new(data))
+        .context("decode the preview's audio")?;
+    let sink = rodio::Sink::connect_new(stream.mixer());
+    sink.append(source);
+    Ok((stream, sink))
+}
@@ -0,0 +1,108 @@
+//! A lightweight HTTP server for controlling the application remotely (`remote` feature).
+//!
+//! The server exposes
+//! - `GET /`: a minimal web remote page
+//! - `GET /api/playback`, `GET /api/queue`, `GET /api/devices`: the application's current state
+//! - `POST /api/playback`: a playback command, which has the same JSON format as the CLI's `playback` commands
+//! - `POST /api/like`: like (or unlike with `{"unlike": true}`) the currently playing track
+//!
+//! The state-changing requests only accept JSON bodies (`application/json`), which web pages of other
+//! origins can't send without a CORS preflight, so they can't control the player from the user's browser.
+//! - `GET /ws`: a WebSocket stream of the current playback, its beats and its audio analysis
+
+use crate::{
+    cli::{self, Command, GetRequest, Key, Request},
+    client::AppClient,
+    config,
+    state::SharedState,
+};
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use tracing::Instrument;
+
+mod ws;
+
+pub use ws::has_clients as has_stream_clients;
+
+const INDEX_PAGE: &str = include_str!("index.html");
+
+#[derive(Clone)]
+struct RemoteState {
+    client: AppClient,
+    state: SharedState,
+}
+
+#[derive(Debug, Deserialize)]
+struct LikeParams {
+    #[serde(default)]
+    unlike: bool,
+}
+
+/// Start the remote control HTTP server listening on `app_config.remote_control_address`
+pub async fn start_server(client: AppClient, state: SharedState) -> Result<()> {
+    let addr = &config::get_config().app_config.remote_control_address;
+
+    let app = Router::new()
+        .route("/", get(|| async { Html(INDEX_PAGE) }))
+        .route(
+            "/api/playback",
+            get(|s| get_key(s, Key::Playback)).post(post_playback),
+        )
+        .route("/api/queue", get(|s| get_key(s, Key::Queue)))
+        .route("/api/devices", get(|s| get_key(s, Key::Devices)))
+        .route("/api/like", post(post_like))
+        .route("/ws", get(ws::handler))
+        .with_state(RemoteState { client, state });
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("bind remote control server to {addr}"))?;
+    tracing::info!("Starting a remote control server at http://{addr}");
+    axum::serve(listener, app)
+        .await
+        .context("run remote control server")?;
+
+    Ok(())
+}
+
+/// Handle a request the same way as a CLI request received from the client socket
+async fn handle_request(s: &RemoteState, request: Request) -> Response {
+    let span = tracing::info_span!("remote_request", request = ?request);
+
+    match cli::handle_socket_request(&s.client, Some(&s.state), request)
+        .instrument(span)
+        .await
+    {
+        Ok(data) if data.is_empty() => StatusCode::NO_CONTENT.into_response(),
+        Ok(data) => ([(header::CONTENT_TYPE, "application/json")], data).into_response(),
+        Err(err) => {
+            tracing::error!("Failed to handle remote request: {err:#}");
+            (StatusCode::BAD_REQUEST, format!("Bad request: {err:#}")).into_response()
+        }
+    }
+}
+
+async fn get_key(State(s): State<RemoteState>, key: Key) -> Response {
+    handle_request(&s, Request::Get(GetRequest::Key(key))).await
+}
+
+async fn post_playback(State(s): State<RemoteState>, Json(command): Json<Command>) -> Response {
+    handle_request(&s, Request::Playback(command)).await
+}
+
+async fn post_like(State(s): State<RemoteState>, Json(params): Json<LikeParams>) -> Response {
+    handle_request(
+        &s,
+        Request::Like {
+            unlike: params.unlike,
+        },
+    )
+    .await
+}
@@ -0,0 +1,214 @@
+//! WebSocket stream of the application's playback state, beats and audio analysis, which allows
+//! external tools (OBS overlays, LED controllers, etc) to sync to the music being played.
+
+use super::RemoteState;
+use crate::state::{PlaybackSnapshot, SharedState};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        State, WebSocketUpgrade,
+    },
+    response::Response,
+};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The interval to check for playback changes
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+/// The interval to re-send the playback state to keep clients' positions in sync
+const SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// The tempo used for the beats of a track without audio analysis if the visualization's tempo isn't available
+#[cfg(not(feature = "fx"))]
+const DEFAULT_BPM: f64 = 120.0;
+
+/// The number of connected WebSocket clients
+static N_CLIENTS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event")]
+/// An event sent to WebSocket clients
+enum StreamEvent<'a> {
+    /// The current playback, sent upon a playback change and periodically
+    Playback(&'a PlaybackSnapshot),
+    /// No playback found
+    NoPlayback,
+    /// A new beat of the playing track
+    Beat {
+        uri: &'a str,
+        /// the beat's index in the track
+        beat: u64,
+        /// the tempo at the beat
+        bpm: f64,
+        progress_ms: i64,
+    },
+    /// A new segment of the playing track's audio analysis (only for tracks whose analysis is available)
+    Segment {
+        uri: &'a str,
+        start_ms: i64,
+        duration_ms: i64,
+        /// the segment's peak loudness (in dB)
+        loudness: f32,
+        /// the relative dominance of the 12 pitch classes (C, C#, ..., B) in the segment
+        pitches: &'a [f32],
+        /// the segment's timbre coefficients
+        timbre: &'a [f32],
+    },
+}
+
+/// Check if there are connected WebSocket clients, which need the playing track's audio analysis
+pub fn has_clients() -> bool {
+    N_CLIENTS.load(Ordering::Relaxed) > 0
+}
+
+pub async fn handler(ws: WebSocketUpgrade, State(s): State<RemoteState>) -> Response {
+    ws.on_upgrade(move |socket| stream_events(socket, s.state))
+}
+
+/// Check if the playback has changed, ignoring progress changes caused by the playback being played
+fn is_playback_changed(old: Option<&PlaybackSnapshot>, new: Option<&PlaybackSnapshot>) -> bool {
+    match (old, new) {
+        (Some(old), Some(new)) => {
+            // allow a small drift in the progress between two polls
+            let expected_progress = if old.is_playing {
+                old.progress_ms + POLL_INTERVAL.as_millis() as i64
+            } else {
+                old.progress_ms
+            };
+            (&old.uri, old.is_playing, old.volume, &old.device)
+                != (&new.uri, new.is_playing, new.volume, &new.device)
+                || (new.progress_ms - expected_progress).abs() > 1000
+        }
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+/// Get the beat and analysis events of a playing track since the last poll.
+///
+/// `last_beat` and `last_segment` are the (track's URI, index) of the beat and the segment
+/// sent last, which are updated with the new ones.
+fn beat_events(
+    state: &SharedState,
+    snapshot: &PlaybackSnapshot,
+    last_beat: &mut Option<(String, u64)>,
+    last_segment: &mut Option<(String, usize)>,
+) -> Vec<String> {
+    #[cfg(feature = "fx")]
+    let bpm = state.ui.lock().current_bpm;
+    #[cfg(not(feature = "fx"))]
+    let bpm = DEFAULT_BPM;
+
+    let mut events = Vec::new();
+    let data = state.data.read();
+    let analysis = data.caches.audio_analysis.get(&snapshot.uri);
+    let position = snapshot.progress_ms as f64 / 1000.0;
+
+    let (n_beats, rate) = crate::utils::beats_at(analysis, position, bpm);
+    let beat = n_beats.max(0.0) as u64;
+    let new_beat = Some((snapshot.uri.clone(), beat));
+    if *last_beat != new_beat {
+        *last_beat = new_beat;
+        let event = StreamEvent::Beat {
+            uri: &snapshot.uri,
+            beat,
+            bpm: rate * 60.0,
+            progress_ms: snapshot.progress_ms,
+        };
+        events.push(serde_json::to_string(&event).expect("serializable event"));
+    }
+
+    let Some(analysis) = analysis else {
+        return events;
+    };
+    let segments = &analysis.segments;
+    let Some(i) = segments
+        .partition_point(|s| f64::from(s.time_interval.start) <= position)
+        .checked_sub(1)
+    else {
+        return events;
+    };
+    let new_segment = Some((snapshot.uri.clone(), i));
+    if *last_segment != new_segment {
+        *last_segment = new_segment;
+        let segment = &segments[i];
+        let event = StreamEvent::Segment {
+            uri: &snapshot.uri,
+            start_ms: (f64::from(segment.time_interval.start) * 1000.0) as i64,
+            duration_ms: (f64::from(segment.time_interval.duration) * 1000.0) as i64,
+            loudness: segment.loudness_max,
+            pitches: &segment.pitches,
+            timbre: &segment.timbre,
+        };
+        events.push(serde_json::to_string(&event).expect("serializable event"));
+    }
+    events
+}
+
+/// A guard counting a connected WebSocket client in `N_CLIENTS` until it's dropped,
+/// including when the client's task panics or is cancelled
+struct ClientGuard;
+
+impl ClientGuard {
+    fn new() -> Self {
+        N_CLIENTS.fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        N_CLIENTS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+async fn stream_events(socket: WebSocket, state: SharedState) {
+    let _guard = ClientGuard::new();
+    send_events(socket, state).await;
+}
+
+async fn send_events(mut socket: WebSocket, state: SharedState) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut last_snapshot = None;
+    let mut last_sent_time = None::<std::time::Instant>;
+    let mut last_beat = None;
+    let mut last_segment = None;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            msg = socket.recv() => match msg {
+                // incoming messages are ignored
+                Some(Ok(_)) => continue,
+                // the client has disconnected
+                None | Some(Err(_)) => break,
+            }
+        }
+
+        let snapshot = state.player.read().playback_snapshot();
+        let should_send = last_sent_time.is_none_or(|t| t.elapsed() >= SYNC_INTERVAL)
+            || is_playback_changed(last_snapshot.as_ref(), snapshot.as_ref());
+
+        if should_send {
+            let event = match snapshot {
+                Some(ref snapshot) => StreamEvent::Playback(snapshot),
+                None => StreamEvent::NoPlayback,
+            };
+            let msg = serde_json::to_string(&event).expect("serializable event");
+            if socket.send(Message::text(msg)).await.is_err() {
+                // the client has disconnected
+                break;
+            }
+            last_sent_time = Some(std::time::Instant::now());
+        }
+
+        if let Some(snapshot) = snapshot.as_ref().filter(|s| s.is_playing) {
+            for msg in beat_events(&state, snapshot, &mut last_beat, &mut last_segment) {
+                if socket.send(Message::text(msg)).await.is_err() {
+                    // the client has disconnected
+                    return;
+                }
+            }
+        }
+        last_snapshot = snapshot;
+    }
+}
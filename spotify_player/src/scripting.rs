@@ -0,0 +1,232 @@
+//! User scripts (`scripting` feature) written in [Rhai](https://rhai.rs), which can be bound to keys
+//! to define custom actions (e.g. "add the current track to my 'Gym' playlist and skip").
+//!
+//! A script named `name` is loaded from the `scripts/name.rhai` file in the application's config folder.
+
+use crate::{
+    client::{ClientRequest, PlayerRequest},
+    config,
+    state::{
+        EpisodeId, Id, Item, ItemId, PlayableId, PlaylistFolderItem, SharedState, Track, TrackId,
+    },
+};
+use anyhow::{Context, Result};
+use rhai::{Dynamic, Engine, EvalAltResult, Map};
+
+type ScriptResult<T> = std::result::Result<T, Box<EvalAltResult>>;
+
+/// Run a user script in a separate thread
+pub fn run_script(name: &str, client_pub: flume::Sender<ClientRequest>, state: SharedState) {
+    let name = name.to_string();
+    std::thread::spawn(move || {
+        tracing::info!("Running script {name}");
+        if let Err(err) = run(&name, client_pub, state) {
+            tracing::error!("Failed to run script {name}: {err:#}");
+        }
+    });
+}
+
+fn run(name: &str, client_pub: flume::Sender<ClientRequest>, state: SharedState) -> Result<()> {
+    let path = config::get_config()
+        .config_folder
+        .join("scripts")
+        .join(format!("{name}.rhai"));
+    let script = std::fs::read_to_string(&path)
+        .with_context(|| format!("read script {}", path.display()))?;
+
+    let engine = new_engine(client_pub, state);
+    engine
+        .run(&script)
+        .map_err(|err| anyhow::anyhow!("{err}"))?;
+    Ok(())
+}
+
+/// Get the currently playing track, if any
+fn current_track(state: &SharedState) -> Option<Track> {
+    match state.player.read().currently_playing() {
+        Some(rspotify::model::PlayableItem::Track(track)) => {
+            Track::try_from_full_track(track.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Construct a scripting engine with the application's API registered
+fn new_engine(client_pub: flume::Sender<ClientRequest>, state: SharedState) -> Engine {
+    let mut engine = Engine::new();
+    engine.on_print(|s| tracing::info!("[script] {s}"));
+
+    let send = move |request: ClientRequest| -> ScriptResult<()> {
+        client_pub
+            .send(request)
+            .map_err(|err| format!("failed to send client request: {err:#}").into())
+    };
+
+    // playback state
+    {
+        let state = state.clone();
+        engine.register_fn("playback", move || -> Dynamic {
+            let player = state.player.read();
+            let (Some(playback), Some(item)) =
+                (player.current_playback(), player.currently_playing())
+            else {
+                return Dynamic::UNIT;
+            };
+
+            let mut map = Map::new();
+            let (name, artists, album, uri, duration) = match item {
+                rspotify::model::PlayableItem::Track(track) => (
+                    track.name.clone(),
+                    crate::utils::map_join(&track.artists, |a| &a.name, ", "),
+                    track.album.name.clone(),
+                    track.id.as_ref().map(Id::uri).unwrap_or_default(),
+                    track.duration,
+                ),
+                rspotify::model::PlayableItem::Episode(episode) => (
+                    episode.name.clone(),
+                    episode.show.publisher.clone(),
+                    episode.show.name.clone(),
+                    episode.id.uri(),
+                    episode.duration,
+                ),
+                rspotify::model::PlayableItem::Unknown(_) => return Dynamic::UNIT,
+            };
+            map.insert("title".into(), name.into());
+            map.insert("artist".into(), artists.into());
+            map.insert("album".into(), album.into());
+            map.insert("uri".into(), uri.into());
+            map.insert("is_playing".into(), playback.is_playing.into());
+            map.insert(
+                "progress_ms".into(),
+                playback
+                    .progress
+                    .unwrap_or_default()
+                    .num_milliseconds()
+                    .into(),
+            );
+            map.insert("duration_ms".into(), duration.num_milliseconds().into());
+            map.insert(
+                "volume".into(),
+                i64::from(playback.device.volume_percent.unwrap_or_default()).into(),
+            );
+            map.into()
+        });
+    }
+
+    // playback control
+    for (name, request) in [
+        ("next_track", PlayerRequest::NextTrack),
+        ("previous_track", PlayerRequest::PreviousTrack),
+        ("resume_pause", PlayerRequest::ResumePause),
+        ("shuffle", PlayerRequest::Shuffle),
+        ("repeat", PlayerRequest::Repeat),
+    ] {
+        let send = send.clone();
+        engine.register_fn(name, move || send(ClientRequest::Player(request.clone())));
+    }
+    {
+        let send = send.clone();
+        engine.register_fn("set_volume", move |volume: i64| {
+            send(ClientRequest::Player(PlayerRequest::Volume(
+                volume.clamp(0, 100) as u8,
+            )))
+        });
+    }
+    {
+        let send = send.clone();
+        engine.register_fn("seek", move |position_ms: i64| {
+            send(ClientRequest::Player(PlayerRequest::SeekTrack(
+                chrono::Duration::milliseconds(position_ms),
+            )))
+        });
+    }
+    {
+        let send = send.clone();
+        engine.register_fn("add_to_queue", move |uri: &str| -> ScriptResult<()> {
+            let id = match (TrackId::from_uri(uri), EpisodeId::from_uri(uri)) {
+                (Ok(id), _) => PlayableId::Track(id.into_static()),
+                (_, Ok(id)) => PlayableId::Episode(id.into_static()),
+                _ => return Err(format!("invalid track or episode uri: {uri}").into()),
+            };
+            send(ClientRequest::AddPlayableToQueue(id))
+        });
+    }
+
+    // library
+    {
+        let state = state.clone();
+        engine.register_fn("is_liked", move || -> bool {
+            current_track(&state).is_some_and(|track| {
+                state
+                    .data
+                    .read()
+                    .user_data
+                    .saved_tracks
+                    .contains_key(&track.id.uri())
+            })
+        });
+    }
+    {
+        let (send, state) = (send.clone(), state.clone());
+        engine.register_fn("like", move || -> ScriptResult<()> {
+            let track = current_track(&state).ok_or("no track is playing")?;
+            send(ClientRequest::AddToLibrary(Item::Track(track)))
+        });
+    }
+    {
+        let (send, state) = (send.clone(), state.clone());
+        engine.register_fn("unlike", move || -> ScriptResult<()> {
+            let track = current_track(&state).ok_or("no track is playing")?;
+            send(ClientRequest::DeleteFromLibrary(ItemId::Track(track.id)))
+        });
+    }
+    {
+        let state = state.clone();
+        engine.register_fn("playlists", move || -> rhai::Array {
+            state
+                .data
+                .read()
+                .user_data
+                .playlists
+                .iter()
+                .filter_map(|item| match item {
+                    PlaylistFolderItem::Playlist(playlist) => {
+                        let mut map = Map::new();
+                        map.insert("name".into(), playlist.name.clone().into());
+                        map.insert("uri".into(), playlist.id.uri().into());
+                        map.insert("owner".into(), playlist.owner.0.clone().into());
+                        Some(map.into())
+                    }
+                    PlaylistFolderItem::Folder(_) => None,
+                })
+                .collect()
+        });
+    }
+    engine.register_fn(
+        "add_to_playlist",
+        move |name_or_uri: &str| -> ScriptResult<()> {
+            let track = current_track(&state).ok_or("no track is playing")?;
+            let playlist_id = state
+                .data
+                .read()
+                .user_data
+                .playlists
+                .iter()
+                .find_map(|item| match item {
+                    PlaylistFolderItem::Playlist(playlist)
+                        if playlist.name == name_or_uri || playlist.id.uri() == name_or_uri =>
+                    {
+                        Some(playlist.id.clone())
+                    }
+                    _ => None,
+                })
+                .ok_or_else(|| format!("no playlist {name_or_uri} found"))?;
+            send(ClientRequest::AddPlayableToPlaylist(
+                playlist_id,
+                PlayableId::Track(track.id),
+            ))
+        },
+    );
+
+    engine
+}
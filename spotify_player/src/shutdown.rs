@@ -0,0 +1,165 @@
+//! The application's graceful shutdown, triggered by the `Quit` command (which stops the UI),
+//! `SIGTERM`, `SIGHUP` or `SIGINT` (Ctrl-C on Windows).
+//!
+//! The shutdown is ordered so that no data is lost and the audio doesn't cut off abruptly:
+//! 1. cancel the in-flight client requests
+//! 2. record the played duration of the current track in the listen history
+//!    and flush the pending database writes
+//! 3. persist the UI session and the last playback (its context and position)
+//! 4. stop the integrated player's audio with a short fade-out
+//! 5. close the IPC sockets (the CLI socket, the remote control server and the MQTT client)
+//! 6. restore the terminal
+
+use anyhow::Result;
+use tokio::task::{AbortHandle, JoinHandle};
+
+use crate::{
+    client::AppClient,
+    config,
+    state::{store_cache_data, CacheKey, SharedState},
+};
+
+/// The maximum duration of waiting for the UI to stop rendering
+const UI_STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Start the application's shutdown watcher, which runs the shutdown sequence
+/// and exits the application once a shutdown is triggered.
+///
+/// `ui_task` is the task running the application's UI (if any), and `ipc_tasks` are the tasks
+/// accepting commands from other processes.
+pub async fn start_shutdown_watcher(
+    state: SharedState,
+    client: AppClient,
+    mut ui_task: Option<JoinHandle<Result<()>>>,
+    ipc_tasks: Vec<AbortHandle>,
+) {
+    let has_ui = ui_task.is_some();
+    let ui_stopped = async {
+        match ui_task.as_mut() {
+            Some(task) => log_ui_stop(task.await),
+            None => std::future::pending().await,
+        }
+    };
+    let is_ui_stopped = tokio::select! {
+        () = ui_stopped => {
+            tracing::info!("Application's UI stopped, shutting down the application...");
+            true
+        }
+        signal = wait_for_signal() => {
+            tracing::info!("Received {signal}, shutting down the application...");
+            false
+        }
+    };
+
+    if let (false, Some(task)) = (is_ui_stopped, ui_task) {
+        // stop the UI from rendering, so that it doesn't draw over the restored terminal
+        state.ui.lock().is_running = false;
+        if let Ok(result) = tokio::time::timeout(UI_STOP_TIMEOUT, task).await {
+            log_ui_stop(result);
+        } else {
+            tracing::warn!("Timed out waiting for the application's UI to stop");
+        }
+    }
+
+    shutdown(&state, &client, has_ui, ipc_tasks).await;
+    std::process::exit(0);
+}
+
+/// Log the error stopping the application's UI (if any)
+fn log_ui_stop(result: Result<Result<()>, tokio::task::JoinError>) {
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => tracing::error!("Failed to run the application's UI: {err:#}"),
+        Err(err) => tracing::error!("Application's UI task failed: {err:#}"),
+    }
+}
+
+/// Wait until the application receives a signal asking it to terminate,
+/// returning the signal's name
+#[cfg(unix)]
+async fn wait_for_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (Ok(mut sigterm), Ok(mut sighup), Ok(mut sigint)) = (
+        signal(SignalKind::terminate()),
+        signal(SignalKind::hangup()),
+        signal(SignalKind::interrupt()),
+    ) else {
+        tracing::warn!("Failed to listen to the termination signals");
+        return std::future::pending().await;
+    };
+    tokio::select! {
+        _ = sigterm.recv() => "SIGTERM",
+        _ = sighup.recv() => "SIGHUP",
+        _ = sigint.recv() => "SIGINT",
+    }
+}
+
+/// Wait until the application receives a signal asking it to terminate,
+/// returning the signal's name
+#[cfg(not(unix))]
+async fn wait_for_signal() -> &'static str {
+    if let Err(err) = tokio::signal::ctrl_c().await {
+        tracing::warn!("Failed to listen to Ctrl-C: {err:#}");
+        return std::future::pending().await;
+    }
+    "Ctrl-C"
+}
+
+/// Run the application's shutdown sequence, see the module's documentation
+async fn shutdown(
+    state: &SharedState,
+    client: &AppClient,
+    has_ui: bool,
+    ipc_tasks: Vec<AbortHandle>,
+) {
+    let configs = config::get_config();
+
+    // the in-flight requests' responses are no longer needed
+    state.requests.cancel_all();
+
+    let (current_listen, last_playback) = {
+        let player = state.player.read();
+        (
+            player.current_track_listen(),
+            player.last_playback_snapshot(),
+        )
+    };
+    if let Some((uri, progress)) = current_listen {
+        crate::db::record_listen_end(&configs.cache_folder, uri, progress.num_milliseconds());
+    }
+    if let Err(err) = tokio::task::spawn_blocking(crate::db::flush_background_writes).await {
+        tracing::error!("Failed to flush the database writes: {err:#}");
+    }
+
+    if has_ui {
+        let session = state.ui.lock().session();
+        if let Err(err) = store_cache_data(CacheKey::UISession, &configs.cache_folder, &session) {
+            tracing::error!("Failed to store the UI session: {err:#}");
+        }
+    }
+    if let Some(last_playback) = last_playback {
+        if let Err(err) = store_cache_data(
+            CacheKey::LastPlayback,
+            &configs.cache_folder,
+            &last_playback,
+        ) {
+            tracing::error!("Failed to store the last playback: {err:#}");
+        }
+    }
+
+    #[cfg(feature = "streaming")]
+    client.stop_integrated_player(state).await;
+    #[cfg(not(feature = "streaming"))]
+    let _ = client;
+
+    for task in ipc_tasks {
+        task.abort();
+    }
+
+    if has_ui {
+        if let Err(err) = crate::ui::suspend_terminal() {
+            tracing::error!("Failed to restore the terminal: {err:#}");
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Controlling the playback with the `SIGUSR1` and `SIGUSR2` signals, so that window manager
+//! keybindings can control the application without the CLI client, e.g. `pkill -USR1 spotify_player`.
+//!
+//! The signals' actions are configured by the `sigusr1_action` and `sigusr2_action` config options.
+
+use tokio::signal::unix::{signal, Signal, SignalKind};
+
+use crate::{
+    client::{ClientRequest, PlayerRequest},
+    config::{self, SignalAction},
+};
+
+/// Start the application's signal handler, which sends the player requests of the received signals' actions
+pub async fn start_signal_handler(client_pub: flume::Sender<ClientRequest>) {
+    let configs = config::get_config();
+    let (sigusr1_action, sigusr2_action) = (
+        configs.app_config.sigusr1_action,
+        configs.app_config.sigusr2_action,
+    );
+
+    let mut sigusr1 = listen(SignalKind::user_defined1(), sigusr1_action);
+    let mut sigusr2 = listen(SignalKind::user_defined2(), sigusr2_action);
+    if sigusr1.is_none() && sigusr2.is_none() {
+        return;
+    }
+
+    tracing::info!("Initializing application's signal handler...");
+    loop {
+        let (name, action) = tokio::select! {
+            () = recv(sigusr1.as_mut()) => ("SIGUSR1", sigusr1_action),
+            () = recv(sigusr2.as_mut()) => ("SIGUSR2", sigusr2_action),
+        };
+        let request = match action {
+            SignalAction::ResumePause => PlayerRequest::ResumePause,
+            SignalAction::NextTrack => PlayerRequest::NextTrack,
+            SignalAction::PreviousTrack => PlayerRequest::PreviousTrack,
+            SignalAction::None => continue,
+        };
+        tracing::info!("Received {name}, sending {request:?} request...");
+        if client_pub.send(ClientRequest::Player(request)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Listen to a signal if it triggers an action
+fn listen(kind: SignalKind, action: SignalAction) -> Option<Signal> {
+    if action == SignalAction::None {
+        return None;
+    }
+    match signal(kind) {
+        Ok(signal) => Some(signal),
+        Err(err) => {
+            tracing::warn!("Failed to listen to the signal {kind:?}: {err:#}");
+            None
+        }
+    }
+}
+
+/// Wait for the next signal, or forever if the signal isn't listened to
+async fn recv(signal: Option<&mut Signal>) {
+    if let Some(signal) = signal {
+        if signal.recv().await.is_some() {
+            return;
+        }
+    }
+    std::future::pending::<()>().await;
+}
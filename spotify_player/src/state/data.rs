@@ -1,31 +1,70 @@
-use std::io::{BufReader, BufWriter};
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
 use serde::{de::DeserializeOwned, Serialize};
 use std::sync::LazyLock;
 
 use super::model::{
-    Album, Artist, Category, Context, ContextId, Id, Playlist, PlaylistFolderItem,
-    PlaylistFolderNode, SearchResults, Show, Track,
+    Album, Artist, BlockedItem, Category, Context, ContextId, Episode, EpisodeBookmark, FeedItem,
+    Id, PartialContext, Playlist, PlaylistFolderItem, PlaylistFolderNode, PlaylistId,
+    PlaylistPreference, SearchResults, SharedPlaylistAdd, Show, Track, TrackAnnotation,
+    TrackDetails, TrackId,
 };
 use super::Lyrics;
 
 pub type DataReadGuard<'a> = parking_lot::RwLockReadGuard<'a, AppData>;
 
 #[derive(Debug, Copy, Clone)]
-pub enum FileCacheKey {
+/// the key of a user data cache entry stored in the database
+pub enum CacheKey {
     Playlists,
-    PlaylistFolders,
     FollowedArtists,
     SavedShows,
+    SavedEpisodes,
     SavedAlbums,
     SavedTracks,
+    ArtistGenres,
+    ArtistFeed,
+    UISession,
+    LastPlayback,
+    #[cfg(feature = "plugins")]
+    DisabledPlugins,
 }
 
 /// default time-to-live cache duration
 pub static TTL_CACHE_DURATION: LazyLock<std::time::Duration> =
     LazyLock::new(|| std::time::Duration::from_secs(60 * 60));
 
+/// The duration of highlighting the new tracks of a refreshed context
+const NEW_TRACK_HIGHLIGHT_DURATION: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// The difference between the tracks of two playlists
+pub struct PlaylistDiff<'a> {
+    pub only_in_first: Vec<&'a Track>,
+    pub only_in_second: Vec<&'a Track>,
+    pub in_both: Vec<&'a Track>,
+}
+
+/// Group tracks by the decades in which their albums were released, ordered by decade.
+/// Tracks without a known release date are excluded.
+pub fn group_tracks_by_decade(tracks: &[Track]) -> Vec<(i32, Vec<&Track>)> {
+    let mut decades: HashMap<i32, Vec<&Track>> = HashMap::new();
+    for track in tracks {
+        if let Some(year) = track.release_year() {
+            decades
+                .entry(year - year.rem_euclid(10))
+                .or_default()
+                .push(track);
+        }
+    }
+
+    let mut decades = decades.into_iter().collect::<Vec<_>>();
+    decades.sort_by_key(|(decade, _)| *decade);
+    decades
+}
+
 /// the application's data
 pub struct AppData {
     pub user_data: UserData,
@@ -41,16 +80,46 @@ pub struct UserData {
     pub playlist_folder_node: Option<PlaylistFolderNode>,
     pub followed_artists: Vec<Artist>,
     pub saved_shows: Vec<Show>,
+    /// the episodes saved to "Your Episodes", ordered from the most recently saved episode
+    pub saved_episodes: Vec<Episode>,
     pub saved_albums: Vec<Album>,
     pub saved_tracks: HashMap<String, Track>,
+    /// genres of the artists of the saved tracks, keyed by artist URIs
+    pub artist_genres: HashMap<String, Vec<String>>,
+    /// local bookmarks within episodes, keyed by episode URIs
+    pub episode_bookmarks: HashMap<String, Vec<EpisodeBookmark>>,
+    /// tracks and artists that are automatically skipped during playback
+    pub blocklist: Vec<BlockedItem>,
+    /// local ratings and tags of tracks, keyed by track URIs
+    pub track_annotations: HashMap<String, TrackAnnotation>,
+    /// local preferences of playlists, keyed by playlist URIs
+    pub playlist_preferences: HashMap<String, PlaylistPreference>,
+    /// the feed of the followed artists, ordered from the most recent item
+    pub artist_feed: Vec<FeedItem>,
+    /// keys of the artist feed's items that have been read
+    pub read_feed_items: HashSet<String>,
+    /// tracks recently added by other contributors to the shared playlists,
+    /// ordered from the most recently added track
+    pub shared_playlist_adds: Vec<SharedPlaylistAdd>,
 }
 
 /// the application's in-memory caches
 pub struct MemoryCaches {
     pub context: ttl_cache::TtlCache<String, Context>,
+    /// contexts being loaded, keyed by context URIs, which are moved to `context` once fully loaded
+    pub partial_contexts: HashMap<String, PartialContext>,
+    /// URIs of the cached contexts to be refreshed, which are still shown until their refresh
+    pub stale_contexts: HashSet<String>,
+    /// URIs of the tracks added to a context since its previous refresh, see `AppData::cache_context`
+    pub new_tracks: ttl_cache::TtlCache<String, ()>,
     pub search: ttl_cache::TtlCache<String, SearchResults>,
     pub lyrics: ttl_cache::TtlCache<String, Option<Lyrics>>,
     pub genres: ttl_cache::TtlCache<String, Vec<String>>,
+    /// saved (liked) status of tracks checked in the background, keyed by track URIs
+    pub saved_tracks_status: ttl_cache::TtlCache<String, bool>,
+    pub audio_analysis: ttl_cache::TtlCache<String, rspotify::model::AudioAnalysis>,
+    /// full metadata of tracks, keyed by track URIs
+    pub track_details: ttl_cache::TtlCache<String, TrackDetails>,
     #[cfg(feature = "image")]
     pub images: ttl_cache::TtlCache<String, image::DynamicImage>,
 }
@@ -66,9 +135,15 @@ impl MemoryCaches {
     pub fn new() -> Self {
         Self {
             context: ttl_cache::TtlCache::new(64),
+            partial_contexts: HashMap::new(),
+            stale_contexts: HashSet::new(),
+            new_tracks: ttl_cache::TtlCache::new(1000),
             search: ttl_cache::TtlCache::new(64),
             lyrics: ttl_cache::TtlCache::new(64),
             genres: ttl_cache::TtlCache::new(64),
+            saved_tracks_status: ttl_cache::TtlCache::new(10_000),
+            audio_analysis: ttl_cache::TtlCache::new(64),
+            track_details: ttl_cache::TtlCache::new(64),
             #[cfg(feature = "image")]
             images: ttl_cache::TtlCache::new(64),
         }
@@ -78,12 +153,45 @@ impl MemoryCaches {
 impl AppData {
     pub fn new(cache_folder: &Path) -> Self {
         Self {
-            user_data: UserData::new_from_file_caches(cache_folder),
+            user_data: UserData::new_from_db(cache_folder),
             caches: MemoryCaches::new(),
             browse: BrowseData::default(),
         }
     }
 
+    /// Check if a track is a liked track.
+    ///
+    /// The track's saved status checked in the background takes precedence over
+    /// the (possibly outdated) user's saved tracks.
+    pub fn is_liked_track(&self, track: &Track) -> bool {
+        self.caches
+            .saved_tracks_status
+            .get(&track.id.uri())
+            .copied()
+            .unwrap_or_else(|| self.user_data.is_liked_track(track))
+    }
+
+    /// Snap a position in a track to the nearest start of the track's bars.
+    ///
+    /// The position is unchanged if the track's audio analysis is not available.
+    pub fn snap_to_nearest_bar(
+        &self,
+        track_uri: &str,
+        position: chrono::Duration,
+    ) -> chrono::Duration {
+        let Some(analysis) = self.caches.audio_analysis.get(track_uri) else {
+            return position;
+        };
+
+        let position_ms = position.num_milliseconds();
+        analysis
+            .bars
+            .iter()
+            .map(|bar| (f64::from(bar.start) * 1000.0) as i64)
+            .min_by_key(|start_ms| (start_ms - position_ms).abs())
+            .map_or(position, chrono::Duration::milliseconds)
+    }
+
     /// Get a list of tracks inside a given context
     pub fn context_tracks_mut(&mut self, id: &ContextId) -> Option<&mut Vec<Track>> {
         let c = self.caches.context.get_mut(&id.uri())?;
@@ -101,44 +209,122 @@ impl AppData {
         })
     }
 
+    /// Get the difference between the tracks of two playlists (or other contexts),
+    /// returning `None` if any of the contexts is not cached yet
+    pub fn playlist_diff(&self, first: &ContextId, second: &ContextId) -> Option<PlaylistDiff<'_>> {
+        let first = self.context_tracks(first)?;
+        let second = self.context_tracks(second)?;
+
+        let first_ids = first.iter().map(|t| &t.id).collect::<HashSet<_>>();
+        let second_ids = second.iter().map(|t| &t.id).collect::<HashSet<_>>();
+
+        let (in_both, only_in_first) = first.iter().partition(|t| second_ids.contains(&t.id));
+        let only_in_second = second
+            .iter()
+            .filter(|t| !first_ids.contains(&t.id))
+            .collect();
+
+        Some(PlaylistDiff {
+            only_in_first,
+            only_in_second,
+            in_both,
+        })
+    }
+
+    /// Find a track among the cached tracks of a playlist and the user's liked tracks,
+    /// returning the found track and whether it's in the playlist and in the liked tracks
+    pub fn find_playlist_duplicate(
+        &self,
+        playlist_id: &PlaylistId<'static>,
+        track_id: &TrackId<'static>,
+    ) -> Option<(&Track, bool, bool)> {
+        let in_playlist = self
+            .context_tracks(&ContextId::Playlist(playlist_id.clone()))
+            .and_then(|tracks| tracks.iter().find(|t| t.id == *track_id));
+        let liked = self.user_data.saved_tracks.get(&track_id.uri());
+        let track = in_playlist.or(liked)?;
+        Some((track, in_playlist.is_some(), liked.is_some()))
+    }
+
     pub fn context_tracks(&self, id: &ContextId) -> Option<&Vec<Track>> {
-        let c = self.caches.context.get(&id.uri())?;
-        Some(match c {
-            Context::Album { tracks, .. }
-            | Context::Playlist { tracks, .. }
-            | Context::Tracks { tracks, .. }
-            | Context::Artist {
-                top_tracks: tracks, ..
-            } => tracks,
-            Context::Show { .. } => {
-                return None;
+        self.caches.context.get(&id.uri())?.tracks()
+    }
+
+    /// Cache a context, replacing its previously cached version (if any).
+    ///
+    /// The tracks that aren't in the previous version are highlighted as new tracks for a while.
+    /// Returns the URIs of the previous version's tracks, if any.
+    pub fn cache_context(&mut self, uri: String, context: Context) -> Option<Vec<String>> {
+        let previous_track_uris = self
+            .caches
+            .context
+            .get(&uri)
+            .and_then(Context::tracks)
+            .map(|tracks| tracks.iter().map(|t| t.id.uri()).collect::<Vec<_>>());
+
+        if let (Some(previous), Some(tracks)) = (&previous_track_uris, context.tracks()) {
+            let previous = previous.iter().collect::<HashSet<_>>();
+            for track in tracks {
+                let track_uri = track.id.uri();
+                if !previous.contains(&track_uri) {
+                    self.caches
+                        .new_tracks
+                        .insert(track_uri, (), NEW_TRACK_HIGHLIGHT_DURATION);
+                }
             }
-        })
+        }
+
+        self.caches.stale_contexts.remove(&uri);
+        self.caches
+            .context
+            .insert(uri, context, *TTL_CACHE_DURATION);
+        previous_track_uris
     }
 }
 
 impl UserData {
-    /// Construct a new user data based on file caches
-    pub fn new_from_file_caches(cache_folder: &Path) -> Self {
+    /// Construct a new user data based on the data stored in the database
+    pub fn new_from_db(cache_folder: &Path) -> Self {
         Self {
             user: None,
-            playlists: load_data_from_file_cache(FileCacheKey::Playlists, cache_folder)
+            playlists: load_cache_data(CacheKey::Playlists, cache_folder).unwrap_or_default(),
+            playlist_folder_node: load_playlist_folder_node(cache_folder),
+            followed_artists: load_cache_data(CacheKey::FollowedArtists, cache_folder)
                 .unwrap_or_default(),
-            playlist_folder_node: load_data_from_file_cache(
-                FileCacheKey::PlaylistFolders,
-                cache_folder,
-            ),
-            followed_artists: load_data_from_file_cache(
-                FileCacheKey::FollowedArtists,
-                cache_folder,
-            )
-            .unwrap_or_default(),
-            saved_shows: load_data_from_file_cache(FileCacheKey::SavedShows, cache_folder)
-                .unwrap_or_default(),
-            saved_albums: load_data_from_file_cache(FileCacheKey::SavedAlbums, cache_folder)
+            saved_shows: load_cache_data(CacheKey::SavedShows, cache_folder).unwrap_or_default(),
+            saved_episodes: load_cache_data(CacheKey::SavedEpisodes, cache_folder)
                 .unwrap_or_default(),
-            saved_tracks: load_data_from_file_cache(FileCacheKey::SavedTracks, cache_folder)
+            saved_albums: load_cache_data(CacheKey::SavedAlbums, cache_folder).unwrap_or_default(),
+            saved_tracks: load_cache_data(CacheKey::SavedTracks, cache_folder).unwrap_or_default(),
+            artist_genres: load_cache_data(CacheKey::ArtistGenres, cache_folder)
                 .unwrap_or_default(),
+            episode_bookmarks: crate::db::load_episode_bookmarks(cache_folder).unwrap_or_else(
+                |err| {
+                    tracing::error!("Failed to load episode bookmarks: {err:#}");
+                    HashMap::new()
+                },
+            ),
+            blocklist: crate::db::load_blocklist(cache_folder).unwrap_or_else(|err| {
+                tracing::error!("Failed to load the blocklist: {err:#}");
+                Vec::new()
+            }),
+            track_annotations: crate::db::load_track_annotations(cache_folder).unwrap_or_else(
+                |err| {
+                    tracing::error!("Failed to load track annotations: {err:#}");
+                    HashMap::new()
+                },
+            ),
+            playlist_preferences: crate::db::load_playlist_preferences(cache_folder)
+                .unwrap_or_else(|err| {
+                    tracing::error!("Failed to load playlist preferences: {err:#}");
+                    HashMap::new()
+                }),
+            artist_feed: load_cache_data(CacheKey::ArtistFeed, cache_folder).unwrap_or_default(),
+            read_feed_items: crate::db::load_read_feed_items(cache_folder).unwrap_or_else(|err| {
+                tracing::error!("Failed to load the artist feed's read items: {err:#}");
+                HashSet::new()
+            }),
+            shared_playlist_adds: vec![],
         }
     }
 
@@ -172,6 +358,15 @@ impl UserData {
         }
     }
 
+    /// Get the playlists in the library that are shared with other users,
+    /// i.e. collaborative playlists and Blends
+    pub fn shared_playlist_items(&self) -> Vec<&PlaylistFolderItem> {
+        self.playlists
+            .iter()
+            .filter(|item| matches!(item, PlaylistFolderItem::Playlist(p) if p.is_shared()))
+            .collect()
+    }
+
     /// Get playlists items for the given folder id
     pub fn folder_playlists_items(&self, folder_id: usize) -> Vec<&PlaylistFolderItem> {
         self.playlists
@@ -183,11 +378,173 @@ impl UserData {
             .collect()
     }
 
+    /// Group the saved (liked) tracks by the genres of their artists,
+    /// ordered by the number of tracks in each genre.
+    ///
+    /// A track belongs to every genre of its artists, the tracks in a genre are ordered
+    /// from the most recently added track.
+    pub fn saved_tracks_by_genre(&self) -> Vec<(&str, Vec<&Track>)> {
+        let mut genres: HashMap<&str, Vec<&Track>> = HashMap::new();
+        for track in self.saved_tracks.values() {
+            let track_genres = track
+                .artists
+                .iter()
+                .filter_map(|a| self.artist_genres.get(&a.id.uri()))
+                .flatten()
+                .map(String::as_str)
+                .collect::<HashSet<_>>();
+            for genre in track_genres {
+                genres.entry(genre).or_default().push(track);
+            }
+        }
+
+        let mut genres = genres.into_iter().collect::<Vec<_>>();
+        for (_, tracks) in &mut genres {
+            tracks.sort_by_key(|t| std::cmp::Reverse(t.added_at));
+        }
+        genres.sort_by(|(g1, t1), (g2, t2)| t2.len().cmp(&t1.len()).then(g1.cmp(g2)));
+        genres
+    }
+
+    /// Check if an item in the artist feed hasn't been read
+    pub fn is_unread_feed_item(&self, item: &FeedItem) -> bool {
+        !self.read_feed_items.contains(&item.key())
+    }
+
+    /// Mark items in the artist feed as read and persist the read markers into the database
+    pub fn mark_feed_items_as_read(&mut self, keys: Vec<String>, cache_folder: &Path) {
+        let keys = keys
+            .into_iter()
+            .filter(|k| !self.read_feed_items.contains(k))
+            .collect::<Vec<_>>();
+        if keys.is_empty() {
+            return;
+        }
+        self.read_feed_items.extend(keys.iter().cloned());
+        let cache_folder = cache_folder.to_path_buf();
+        crate::db::write_in_background("store read feed items into the database", move || {
+            crate::db::store_read_feed_items(&cache_folder, &keys)
+        });
+    }
+
     /// Check if a track is a liked track
     pub fn is_liked_track(&self, track: &Track) -> bool {
         self.saved_tracks.contains_key(&track.id.uri())
     }
 
+    /// Add a bookmark to an episode and persist it into the database
+    pub fn add_episode_bookmark(
+        &mut self,
+        episode_uri: String,
+        bookmark: EpisodeBookmark,
+        cache_folder: &Path,
+    ) {
+        let bookmarks = self
+            .episode_bookmarks
+            .entry(episode_uri.clone())
+            .or_default();
+        bookmarks.push(bookmark.clone());
+        bookmarks.sort_by_key(|b| b.position_ms);
+        let cache_folder = cache_folder.to_path_buf();
+        crate::db::write_in_background("store episode bookmark into the database", move || {
+            crate::db::store_episode_bookmark(&cache_folder, &episode_uri, &bookmark)
+        });
+    }
+
+    /// Check if a track or any of its artists is in the blocklist
+    pub fn is_blocked(&self, track: &rspotify::model::FullTrack) -> bool {
+        let uris = track
+            .id
+            .iter()
+            .map(Id::uri)
+            .chain(
+                track
+                    .artists
+                    .iter()
+                    .filter_map(|a| a.id.as_ref().map(Id::uri)),
+            )
+            .collect::<Vec<_>>();
+        self.blocklist
+            .iter()
+            .any(|item| uris.iter().any(|uri| uri == item.uri()))
+    }
+
+    /// Add an item to the blocklist and persist it into the database
+    pub fn add_to_blocklist(&mut self, item: BlockedItem, cache_folder: &Path) {
+        if self.blocklist.iter().any(|i| i.uri() == item.uri()) {
+            return;
+        }
+        self.blocklist.push(item.clone());
+        let cache_folder = cache_folder.to_path_buf();
+        crate::db::write_in_background("store blocked item into the database", move || {
+            crate::db::store_blocked_item(&cache_folder, &item)
+        });
+    }
+
+    /// Remove an item from the blocklist and the database
+    pub fn remove_from_blocklist(&mut self, index: usize, cache_folder: &Path) {
+        if index < self.blocklist.len() {
+            let item = self.blocklist.remove(index);
+            let cache_folder = cache_folder.to_path_buf();
+            crate::db::write_in_background("delete blocked item from the database", move || {
+                crate::db::delete_blocked_item(&cache_folder, item.uri())
+            });
+        }
+    }
+
+    /// Set the local annotation (rating and tags) of a track and persist it into the database
+    pub fn set_track_annotation(
+        &mut self,
+        track_uri: String,
+        annotation: TrackAnnotation,
+        cache_folder: &Path,
+    ) {
+        let cache_folder = cache_folder.to_path_buf();
+        let (uri, stored) = (track_uri.clone(), annotation.clone());
+        crate::db::write_in_background("store track annotation into the database", move || {
+            crate::db::store_track_annotation(&cache_folder, &uri, &stored)
+        });
+        if annotation.is_empty() {
+            self.track_annotations.remove(&track_uri);
+        } else {
+            self.track_annotations.insert(track_uri, annotation);
+        }
+    }
+
+    /// Get the local preference of a playlist
+    pub fn playlist_preference(&self, id: &PlaylistId) -> PlaylistPreference {
+        self.playlist_preferences
+            .get(&id.uri())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Set the local preference of a playlist and persist it into the database
+    pub fn set_playlist_preference(
+        &mut self,
+        id: &PlaylistId,
+        preference: PlaylistPreference,
+        cache_folder: &Path,
+    ) {
+        let uri = id.uri();
+        let (cache_folder, stored_uri) = (cache_folder.to_path_buf(), uri.clone());
+        crate::db::write_in_background("store playlist preference into the database", move || {
+            crate::db::store_playlist_preference(&cache_folder, &stored_uri, preference)
+        });
+        if preference.is_empty() {
+            self.playlist_preferences.remove(&uri);
+        } else {
+            self.playlist_preferences.insert(uri, preference);
+        }
+    }
+
+    /// Sort the tracks of a playlist context following the playlist's preferred order (if any)
+    pub fn sort_playlist_context(&self, context: &mut Context) {
+        if let Context::Playlist { playlist, tracks } = context {
+            self.playlist_preference(&playlist.id).sort_tracks(tracks);
+        }
+    }
+
     /// Check if a playlist is followed
     pub fn is_followed_playlist(&self, playlist: &Playlist) -> bool {
         self.playlists.iter().any(|x| match x {
@@ -197,36 +554,49 @@ impl UserData {
     }
 }
 
-pub fn store_data_into_file_cache<T: Serialize>(
-    key: FileCacheKey,
+/// Load the playlist folders data from the `PlaylistFolders_cache.json` file,
+/// which is generated by an external tool and is not stored in the database
+fn load_playlist_folder_node(cache_folder: &Path) -> Option<PlaylistFolderNode> {
+    let path = cache_folder.join("PlaylistFolders_cache.json");
+    if !path.exists() {
+        return None;
+    }
+    tracing::info!("Loading playlist folders data from {}...", path.display());
+    match std::fs::read_to_string(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| Ok(serde_json::from_str(&data)?))
+    {
+        Ok(node) => Some(node),
+        Err(err) => {
+            tracing::error!("Failed to load playlist folders data: {err:#}");
+            None
+        }
+    }
+}
+
+/// Store a user data cache entry into the database
+pub fn store_cache_data<T: Serialize>(
+    key: CacheKey,
     cache_folder: &Path,
     data: &T,
-) -> std::io::Result<()> {
-    let path = cache_folder.join(format!("{key:?}_cache.json"));
-    let f = BufWriter::new(std::fs::File::create(path)?);
-    serde_json::to_writer(f, data)?;
-    Ok(())
+) -> anyhow::Result<()> {
+    crate::db::store_cache_entry(cache_folder, &format!("{key:?}"), data)
 }
 
-pub fn load_data_from_file_cache<T>(key: FileCacheKey, cache_folder: &Path) -> Option<T>
+/// Load a user data cache entry from the database
+pub fn load_cache_data<T>(key: CacheKey, cache_folder: &Path) -> Option<T>
 where
     T: DeserializeOwned,
 {
-    let path = cache_folder.join(format!("{key:?}_cache.json"));
-    if path.exists() {
-        tracing::info!("Loading {key:?} data from {}...", path.display());
-        let f = BufReader::new(std::fs::File::open(path).expect("path exists"));
-        match serde_json::from_reader(f) {
-            Ok(data) => {
-                tracing::info!("Successfully loaded {key:?} data!");
-                Some(data)
-            }
-            Err(err) => {
-                tracing::error!("Failed to load {key:?} data: {err:#}");
-                None
-            }
+    tracing::info!("Loading {key:?} data from the database...");
+    match crate::db::load_cache_entry(cache_folder, &format!("{key:?}")) {
+        Ok(data) => {
+            tracing::info!("Successfully loaded {key:?} data!");
+            data
+        }
+        Err(err) => {
+            tracing::error!("Failed to load {key:?} data: {err:#}");
+            None
         }
-    } else {
-        None
     }
 }
@@ -2,18 +2,29 @@ mod constant;
 mod data;
 mod model;
 mod player;
+mod requests;
+mod stats;
 mod ui;
 
 pub use constant::*;
 pub use data::*;
 pub use model::*;
 pub use player::*;
+pub use requests::*;
+pub use stats::*;
 pub use ui::*;
 
 use crate::config;
 
 pub use parking_lot::{Mutex, RwLock};
 
+/// Indicate if the private session is enabled, in which the played tracks aren't recorded
+/// in the listen history and the hooks aren't triggered (see `Command::TogglePrivateSession`).
+///
+/// The flag is global as it's checked by the writers of the listen history and the hooks.
+static IS_PRIVATE_SESSION: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
 /// Application's shared state (wrapped inside an `std::sync::Arc`)
 pub type SharedState = std::sync::Arc<State>;
 
@@ -22,35 +33,103 @@ pub struct State {
     pub ui: Mutex<UIState>,
     pub player: RwLock<PlayerState>,
     pub data: RwLock<AppData>,
+    pub api_stats: ApiStats,
+    /// the client's requests being handled, which are cancelled when no longer needed
+    pub requests: InFlightRequests,
+    #[cfg(feature = "streaming")]
+    pub playback_stats: PlaybackStats,
 
     pub is_daemon: bool,
+    /// Indicate if the application is running without its terminal UI (`--no-ui`)
+    pub is_headless: bool,
+    /// Indicate if the compact UI mode is enabled regardless of the terminal's height (`--compact`)
+    pub is_compact: bool,
+    /// Indicate if the low-power mode reducing the refresh rates and background requests is enabled,
+    /// which is updated by the power source watcher (see `power::start_power_watcher`)
+    is_low_power: std::sync::atomic::AtomicBool,
 }
 
 impl State {
-    pub fn new(is_daemon: bool) -> Self {
+    pub fn new(is_daemon: bool, is_headless: bool, is_compact: bool) -> Self {
         let mut ui = UIState::default();
         let configs = config::get_config();
 
+        ui.is_light_background = configs.app_config.terminal_background.is_light();
+        #[cfg(feature = "fx")]
+        crate::ui::visualizations::set_light_background(ui.is_light_background);
+
         if let Some(theme) = configs.theme_config.find_theme(&configs.app_config.theme) {
             // update the UI's theme based on the `theme` config option
             ui.theme = theme;
         }
+        if ui.is_light_background && ui.theme.name == "default" {
+            // the default theme's bright colors are hard to read on light backgrounds
+            if let Some(theme) = configs.theme_config.find_theme("default_light") {
+                ui.theme = theme;
+            }
+        }
 
         let app_data = AppData::new(&configs.cache_folder);
 
+        #[allow(unused_mut)]
+        let mut player = PlayerState::default();
+        #[cfg(feature = "streaming")]
+        {
+            player.streaming_bitrate = configs.app_config.device.bitrate;
+        }
+
         Self {
             ui: Mutex::new(ui),
-            player: RwLock::new(PlayerState::default()),
+            player: RwLock::new(player),
             data: RwLock::new(app_data),
+            api_stats: ApiStats::default(),
+            requests: InFlightRequests::default(),
+            #[cfg(feature = "streaming")]
+            playback_stats: PlaybackStats::default(),
             is_daemon,
+            is_headless,
+            is_compact,
+            is_low_power: std::sync::atomic::AtomicBool::new(false),
         }
     }
 
+    pub fn is_low_power(&self) -> bool {
+        self.is_low_power.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    pub fn set_low_power(&self, is_low_power: bool) {
+        self.is_low_power
+            .store(is_low_power, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Enable or disable the private session.
+    ///
+    /// Upon enabling it, the current track's listen is ended as played so far,
+    /// as the rest of it isn't recorded.
+    pub fn set_private_session(&self, is_private_session: bool) {
+        if is_private_session {
+            let current_listen = self.player.read().current_track_listen();
+            if let Some((uri, progress)) = current_listen {
+                crate::db::record_listen_end(
+                    &config::get_config().cache_folder,
+                    uri,
+                    progress.num_milliseconds(),
+                );
+            }
+        }
+        IS_PRIVATE_SESSION.store(is_private_session, std::sync::atomic::Ordering::Relaxed);
+    }
+
     #[cfg(feature = "streaming")]
     pub fn is_streaming_enabled(&self) -> bool {
         let configs = config::get_config();
         configs.app_config.enable_streaming == config::StreamingType::Always
             || (configs.app_config.enable_streaming == config::StreamingType::DaemonOnly
-                && self.is_daemon)
+                && (self.is_daemon || self.is_headless))
     }
 }
+
+/// Check if the private session is enabled (see [`State::set_private_session`])
+pub fn is_private_session() -> bool {
+    IS_PRIVATE_SESSION.load(std::sync::atomic::Ordering::Relaxed)
+}
@@ -2,7 +2,7 @@ use crate::ui::utils::to_bidi_string;
 use crate::utils::map_join;
 use html_escape::decode_html_entities;
 pub use rspotify::model::{
-    AlbumId, ArtistId, EpisodeId, Id, PlayableId, PlaylistId, ShowId, TrackId, UserId,
+    AlbumId, ArtistId, EpisodeId, Id, PlayableId, PlaylistId, SearchType, ShowId, TrackId, UserId,
 };
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
@@ -44,6 +44,17 @@ pub enum Context {
     },
 }
 
+#[derive(Default, Clone, Debug)]
+/// The sections of a context being loaded, each of which is set once its data arrives
+pub struct PartialContext {
+    /// the context's name and its main metadata
+    pub description: Option<String>,
+    /// the tracks of a playlist or an album, or the top tracks of an artist
+    pub tracks: Option<Vec<Track>>,
+    pub albums: Option<Vec<Album>>,
+    pub related_artists: Option<Vec<Artist>>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TracksId {
     pub uri: String,
@@ -81,9 +92,145 @@ pub struct SearchResults {
     pub playlists: Vec<Playlist>,
     pub shows: Vec<Show>,
     pub episodes: Vec<Episode>,
+    #[serde(skip)]
+    pub pages: SearchResultPages,
 }
 
-#[derive(Debug)]
+#[derive(Default, Clone, Debug)]
+/// The loaded pages of search results of each type
+pub struct SearchResultPages {
+    pub tracks: ResultPage,
+    pub artists: ResultPage,
+    pub albums: ResultPage,
+    pub playlists: ResultPage,
+    pub shows: ResultPage,
+    pub episodes: ResultPage,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+/// The loaded page of a paginated remote list
+pub struct ResultPage {
+    /// the position of the page's first item in the list
+    pub offset: usize,
+    /// the total number of items in the list
+    pub total: usize,
+    /// the number of items loaded so far when loading all items of the list
+    pub loading_all: Option<usize>,
+}
+
+impl ResultPage {
+    /// Describe the loaded items' position in the list, e.g. "showing 50 of 1,342"
+    pub fn describe(&self, n_items: usize) -> String {
+        let total = crate::utils::format_count(self.total);
+        if self.offset == 0 {
+            format!("showing {} of {total}", crate::utils::format_count(n_items))
+        } else {
+            format!(
+                "showing {}-{} of {total}",
+                crate::utils::format_count(self.offset + 1),
+                crate::utils::format_count(self.offset + n_items),
+            )
+        }
+    }
+
+    /// Whether all items of the list are loaded
+    pub fn is_fully_loaded(&self, n_items: usize) -> bool {
+        self.offset == 0 && n_items >= self.total
+    }
+}
+
+impl SearchResults {
+    /// Get the loaded page of search results of a given type
+    pub fn page(&self, typ: SearchType) -> &ResultPage {
+        match typ {
+            SearchType::Track => &self.pages.tracks,
+            SearchType::Artist => &self.pages.artists,
+            SearchType::Album => &self.pages.albums,
+            SearchType::Playlist => &self.pages.playlists,
+            SearchType::Show => &self.pages.shows,
+            SearchType::Episode => &self.pages.episodes,
+        }
+    }
+
+    /// Get the loaded page of search results of a given type
+    pub fn page_mut(&mut self, typ: SearchType) -> &mut ResultPage {
+        match typ {
+            SearchType::Track => &mut self.pages.tracks,
+            SearchType::Artist => &mut self.pages.artists,
+            SearchType::Album => &mut self.pages.albums,
+            SearchType::Playlist => &mut self.pages.playlists,
+            SearchType::Show => &mut self.pages.shows,
+            SearchType::Episode => &mut self.pages.episodes,
+        }
+    }
+
+    /// Get the number of loaded search results of a given type
+    pub fn len(&self, typ: SearchType) -> usize {
+        match typ {
+            SearchType::Track => self.tracks.len(),
+            SearchType::Artist => self.artists.len(),
+            SearchType::Album => self.albums.len(),
+            SearchType::Playlist => self.playlists.len(),
+            SearchType::Show => self.shows.len(),
+            SearchType::Episode => self.episodes.len(),
+        }
+    }
+
+    /// Append a page of search results to the results of the page's type,
+    /// updating the total number of results of the type
+    pub fn append_page(&mut self, result: rspotify::model::SearchResult) {
+        use rspotify::model::SearchResult;
+
+        let (typ, total) = match result {
+            SearchResult::Tracks(p) => {
+                self.tracks
+                    .extend(p.items.into_iter().filter_map(Track::try_from_full_track));
+                (SearchType::Track, p.total)
+            }
+            SearchResult::Artists(p) => {
+                self.artists.extend(p.items.into_iter().map(Artist::from));
+                (SearchType::Artist, p.total)
+            }
+            SearchResult::Albums(p) => {
+                self.albums.extend(
+                    p.items
+                        .into_iter()
+                        .filter_map(Album::try_from_simplified_album),
+                );
+                (SearchType::Album, p.total)
+            }
+            SearchResult::Playlists(p) => {
+                self.playlists
+                    .extend(p.items.into_iter().map(Playlist::from));
+                (SearchType::Playlist, p.total)
+            }
+            SearchResult::Shows(p) => {
+                self.shows.extend(p.items.into_iter().map(Show::from));
+                (SearchType::Show, p.total)
+            }
+            SearchResult::Episodes(p) => {
+                self.episodes.extend(p.items.into_iter().map(Episode::from));
+                (SearchType::Episode, p.total)
+            }
+        };
+        self.page_mut(typ).total = total as usize;
+    }
+
+    /// Replace the search results of a given type with the results of the type in `other`
+    pub fn replace_results(&mut self, typ: SearchType, other: SearchResults) {
+        *self.page_mut(typ) = *other.page(typ);
+        match typ {
+            SearchType::Track => self.tracks = other.tracks,
+            SearchType::Artist => self.artists = other.artists,
+            SearchType::Album => self.albums = other.albums,
+            SearchType::Playlist => self.playlists = other.playlists,
+            SearchType::Show => self.shows = other.shows,
+            SearchType::Episode => self.episodes = other.episodes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 /// A track order
 pub enum TrackOrder {
     AddedAt,
@@ -101,6 +248,7 @@ pub enum Item {
     Artist(Artist),
     Playlist(Playlist),
     Show(Show),
+    Episode(Episode),
 }
 
 #[derive(Debug, Clone)]
@@ -110,6 +258,7 @@ pub enum ItemId {
     Artist(ArtistId<'static>),
     Playlist(PlaylistId<'static>),
     Show(ShowId<'static>),
+    Episode(EpisodeId<'static>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -146,6 +295,16 @@ pub struct Track {
     pub explicit: bool,
     #[serde(skip)]
     pub added_at: u64,
+    /// the track's disc number in its album
+    #[serde(default)]
+    pub disc_number: i32,
+    /// whether the track is unavailable in the user's market
+    #[serde(default)]
+    pub is_unavailable: bool,
+    /// the ID of the track that this track is relinked to in the user's market, if any.
+    /// `id` is the ID of the originally requested track, e.g. the one in a playlist.
+    #[serde(default)]
+    pub relinked_id: Option<TrackId<'static>>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -157,6 +316,12 @@ pub struct Album {
     pub artists: Vec<Artist>,
     pub typ: Option<rspotify::model::AlbumType>,
     pub added_at: u64,
+    /// the album's record label, only available for a fully fetched album
+    #[serde(default)]
+    pub label: Option<String>,
+    /// the album's copyright statements, only available for a fully fetched album
+    #[serde(default)]
+    pub copyrights: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -196,6 +361,275 @@ pub struct Episode {
     pub duration: std::time::Duration,
     pub show: Option<Show>,
     pub release_date: String,
+    /// whether the user has fully played the episode
+    #[serde(default)]
+    pub fully_played: bool,
+    /// the position the user stopped playing the episode at
+    #[serde(default)]
+    pub resume_position: std::time::Duration,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+/// A local bookmark at a timestamp within an episode
+pub struct EpisodeBookmark {
+    pub position_ms: i64,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+/// Local annotations of a track, which consist of a star rating (from 1 to 5) and freeform tags
+pub struct TrackAnnotation {
+    pub rating: Option<u8>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Local preferences of a playlist, which are applied when loading and playing the playlist
+pub struct PlaylistPreference {
+    /// the order of the playlist's tracks, which is `None` for the playlist's own order
+    pub track_order: Option<TrackOrder>,
+    /// whether the order of the playlist's tracks is reversed
+    pub reversed: bool,
+    /// the shuffle state of the playback started from the playlist
+    pub shuffle: Option<bool>,
+}
+
+impl PlaylistPreference {
+    pub fn is_empty(self) -> bool {
+        self == Self::default()
+    }
+
+    /// Check if the preference changes the order of the playlist's tracks
+    pub fn has_track_order(self) -> bool {
+        self.track_order.is_some() || self.reversed
+    }
+
+    /// Sort the playlist's tracks following the preferred order
+    pub fn sort_tracks(self, tracks: &mut [Track]) {
+        if let Some(order) = self.track_order {
+            tracks.sort_by(|x, y| order.compare(x, y));
+        }
+        if self.reversed {
+            tracks.reverse();
+        }
+    }
+}
+
+impl TrackAnnotation {
+    pub fn is_empty(&self) -> bool {
+        self.rating.is_none() && self.tags.is_empty()
+    }
+
+    /// Check if the annotation matches a search filter, which is either
+    /// `rating:N` (rated at least `N` stars) or `tag:name` (tagged with `name`)
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        if let Some(rating) = filter.strip_prefix("rating:") {
+            rating
+                .parse::<u8>()
+                .is_ok_and(|rating| self.rating.is_some_and(|r| r >= rating))
+        } else if let Some(tag) = filter.strip_prefix("tag:") {
+            self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+        } else {
+            false
+        }
+    }
+}
+
+impl std::fmt::Display for TrackAnnotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![];
+        if let Some(rating) = self.rating {
+            parts.push(format!(
+                "{}{}",
+                "★".repeat(rating.into()),
+                "☆".repeat(5_usize.saturating_sub(rating.into()))
+            ));
+        }
+        parts.extend(self.tags.iter().map(|t| format!("#{t}")));
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+/// An item in the user's blocklist, which is automatically skipped during playback
+pub enum BlockedItem {
+    Track { uri: String, name: String },
+    Artist { uri: String, name: String },
+}
+
+impl BlockedItem {
+    pub fn uri(&self) -> &str {
+        match self {
+            Self::Track { uri, .. } | Self::Artist { uri, .. } => uri,
+        }
+    }
+}
+
+impl std::fmt::Display for BlockedItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Track { name, .. } => write!(f, "[track] {name}"),
+            Self::Artist { name, .. } => write!(f, "[artist] {name}"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+/// An item in the feed of the user's followed artists
+pub enum FeedItem {
+    /// an album or a single released by a followed artist
+    Release { artist: Artist, album: Album },
+    /// an album a followed artist appears on
+    AppearsOn { artist: Artist, album: Album },
+    /// a track of a followed artist added to a playlist
+    PlaylistAdd {
+        artist: Artist,
+        track: Box<Track>,
+        playlist: Playlist,
+        /// the time the track was added, stored separately as a track's added time isn't serialized
+        added_at: u64,
+    },
+}
+
+impl FeedItem {
+    /// A key identifying the item, used to store the item's read marker
+    pub fn key(&self) -> String {
+        match self {
+            Self::Release { album, .. } | Self::AppearsOn { album, .. } => album.id.uri(),
+            Self::PlaylistAdd {
+                track, playlist, ..
+            } => format!("{}:{}", playlist.id.uri(), track.id.uri()),
+        }
+    }
+
+    /// The date of the item, which is the album's release date for a release
+    /// or the track's added date for a playlist add
+    pub fn date(&self) -> String {
+        match self {
+            Self::Release { album, .. } | Self::AppearsOn { album, .. } => {
+                album.release_date.clone()
+            }
+            Self::PlaylistAdd { added_at, .. } => i64::try_from(*added_at)
+                .ok()
+                .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl std::fmt::Display for FeedItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Release { artist, album } => {
+                let typ = album.album_type();
+                let typ = if typ.is_empty() { "release" } else { &typ };
+                write!(f, "[new {typ}] {} • {}", album.name, artist.name)
+            }
+            Self::AppearsOn { artist, album } => {
+                write!(f, "[appears on] {} • {}", album.name, artist.name)
+            }
+            Self::PlaylistAdd {
+                artist,
+                track,
+                playlist,
+                ..
+            } => write!(
+                f,
+                "[playlist add] {} • {} ▎ {}",
+                track.display_name(),
+                artist.name,
+                playlist.name
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Full metadata of a track, displayed in the track's detail popup
+pub struct TrackDetails {
+    pub name: String,
+    pub album: Option<Album>,
+    pub disc_number: i32,
+    pub track_number: u32,
+    pub isrc: Option<String>,
+    pub popularity: u32,
+    pub duration: chrono::Duration,
+    pub n_available_markets: usize,
+    pub artists: Vec<Artist>,
+}
+
+impl TrackDetails {
+    /// The number of fields before the artists' fields
+    pub const N_METADATA_FIELDS: usize = 9;
+
+    /// The track's metadata fields as pairs of labels and values,
+    /// followed by a field for each of the track's artists
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("Name", self.name.clone()),
+            (
+                "Album",
+                self.album
+                    .as_ref()
+                    .map(|a| a.name.clone())
+                    .unwrap_or_default(),
+            ),
+            (
+                "Release date",
+                self.album
+                    .as_ref()
+                    .map(|a| a.release_date.clone())
+                    .unwrap_or_default(),
+            ),
+            ("Disc", self.disc_number.to_string()),
+            ("Track", self.track_number.to_string()),
+            ("ISRC", self.isrc.clone().unwrap_or_else(|| "-".to_string())),
+            ("Popularity", format!("{}/100", self.popularity)),
+            ("Duration", crate::utils::format_duration(&self.duration)),
+            ("Available markets", self.n_available_markets.to_string()),
+        ];
+        fields.extend(self.artists.iter().map(|a| ("Artist", a.name.clone())));
+        fields
+    }
+}
+
+impl From<rspotify::model::FullTrack> for TrackDetails {
+    fn from(track: rspotify::model::FullTrack) -> Self {
+        Self {
+            name: track.name,
+            album: Album::try_from_simplified_album(track.album),
+            disc_number: track.disc_number,
+            track_number: track.track_number,
+            isrc: track.external_ids.get("isrc").cloned(),
+            popularity: track.popularity,
+            duration: track.duration,
+            n_available_markets: track.available_markets.len(),
+            artists: from_simplified_artists_to_artists(track.artists),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A track added to a shared playlist by another contributor
+pub struct SharedPlaylistAdd {
+    pub track: Track,
+    /// the name of the user who added the track
+    pub added_by: String,
+    pub playlist: Playlist,
+}
+
+impl std::fmt::Display for SharedPlaylistAdd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} • {} ▎ added by {} to {}",
+            self.track.display_name(),
+            self.track.artists_info(),
+            self.added_by,
+            self.playlist.name
+        )
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -236,6 +670,30 @@ pub struct Category {
 }
 
 impl Context {
+    /// gets the context's tracks, or the top tracks of an artist
+    pub fn tracks(&self) -> Option<&Vec<Track>> {
+        match self {
+            Context::Album { tracks, .. }
+            | Context::Playlist { tracks, .. }
+            | Context::Tracks { tracks, .. }
+            | Context::Artist {
+                top_tracks: tracks, ..
+            } => Some(tracks),
+            Context::Show { .. } => None,
+        }
+    }
+
+    /// gets the context's name
+    pub fn name(&self) -> &str {
+        match self {
+            Context::Album { ref album, .. } => &album.name,
+            Context::Playlist { ref playlist, .. } => &playlist.name,
+            Context::Artist { ref artist, .. } => &artist.name,
+            Context::Tracks { ref desc, .. } => desc,
+            Context::Show { ref show, .. } => &show.name,
+        }
+    }
+
     /// gets the context's description
     pub fn description(&self) -> String {
         match self {
@@ -297,6 +755,17 @@ fn play_time(tracks: &[Track]) -> String {
 }
 
 impl ContextId {
+    /// Get the ID of a playlist, album, artist or show context from its URI
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        Some(match uri.split(':').nth(1)? {
+            "playlist" => Self::Playlist(PlaylistId::from_uri(uri).ok()?.into_static()),
+            "album" => Self::Album(AlbumId::from_uri(uri).ok()?.into_static()),
+            "artist" => Self::Artist(ArtistId::from_uri(uri).ok()?.into_static()),
+            "show" => Self::Show(ShowId::from_uri(uri).ok()?.into_static()),
+            _ => return None,
+        })
+    }
+
     pub fn uri(&self) -> String {
         match self {
             Self::Album(id) => id.uri(),
@@ -309,8 +778,8 @@ impl ContextId {
 }
 
 impl TrackOrder {
-    pub fn compare(&self, x: &Track, y: &Track) -> std::cmp::Ordering {
-        match *self {
+    pub fn compare(self, x: &Track, y: &Track) -> std::cmp::Ordering {
+        match self {
             Self::AddedAt => x.added_at.cmp(&y.added_at),
             Self::TrackName => x.name.cmp(&y.name),
             Self::Album => x.album_info().cmp(&y.album_info()),
@@ -344,7 +813,50 @@ impl Track {
             .unwrap_or_default()
     }
 
+    /// gets the release year of the track's album
+    pub fn release_year(&self) -> Option<i32> {
+        self.album.as_ref()?.year().parse().ok()
+    }
+
+    /// Check if the track matches a release filter, which is either
+    /// `year:YYYY`, `year:YYYY-YYYY` (released in a range of years) or `decade:80s`/`decade:1980s`
+    pub fn matches_release_filter(&self, filter: &str) -> bool {
+        let Some(year) = self.release_year() else {
+            return false;
+        };
+
+        if let Some(years) = filter.strip_prefix("year:") {
+            match years.split_once('-') {
+                Some((from, to)) => match (from.parse::<i32>(), to.parse::<i32>()) {
+                    (Ok(from), Ok(to)) => (from..=to).contains(&year),
+                    _ => false,
+                },
+                None => years.parse::<i32>().is_ok_and(|y| y == year),
+            }
+        } else if let Some(decade) = filter.strip_prefix("decade:") {
+            let Ok(decade) = decade.trim_end_matches('s').parse::<i32>() else {
+                return false;
+            };
+            // a two-digit decade refers to a decade of the 20th century (e.g. `80s`),
+            // or of the 21st century for `00s`, `10s` and `20s`
+            let decade = match decade {
+                0..=29 => 2000 + decade,
+                30..=99 => 1900 + decade,
+                _ => decade,
+            };
+            year - year.rem_euclid(10) == decade
+        } else {
+            false
+        }
+    }
+
     /// gets the track's name, including an explicit label
+    /// Whether the track is identified by a given URI, which is either the track's URI
+    /// or the URI of the track it's relinked to (e.g. the playing track's URI)
+    pub fn has_uri(&self, uri: &str) -> bool {
+        self.id.uri() == uri || self.relinked_id.as_ref().is_some_and(|id| id.uri() == uri)
+    }
+
     pub fn display_name(&self) -> Cow<'_, str> {
         if self.explicit {
             Cow::Owned(format!("{} (E)", self.name))
@@ -355,23 +867,22 @@ impl Track {
 
     /// tries to convert from a `rspotify::model::SimplifiedTrack` into `Track`
     pub fn try_from_simplified_track(track: rspotify::model::SimplifiedTrack) -> Option<Self> {
-        if track.is_playable.unwrap_or(true) {
-            let id = match track.linked_from {
-                Some(d) => d.id?,
-                None => track.id?,
-            };
-            Some(Self {
-                id,
-                name: track.name,
-                artists: from_simplified_artists_to_artists(track.artists),
-                album: None,
-                duration: track.duration.to_std().expect("valid chrono duration"),
-                explicit: track.explicit,
-                added_at: 0,
-            })
-        } else {
-            None
-        }
+        let (id, relinked_id) = match track.linked_from {
+            Some(d) => (d.id?, track.id),
+            None => (track.id?, None),
+        };
+        Some(Self {
+            id,
+            name: track.name,
+            artists: from_simplified_artists_to_artists(track.artists),
+            album: None,
+            duration: track.duration.to_std().expect("valid chrono duration"),
+            explicit: track.explicit,
+            added_at: 0,
+            disc_number: track.disc_number,
+            is_unavailable: track.is_playable == Some(false),
+            relinked_id,
+        })
     }
 
     /// tries to convert from a `rspotify::model::FullTrack` into `Track` with a optional `added_at` date
@@ -379,23 +890,22 @@ impl Track {
         track: rspotify::model::FullTrack,
         added_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Option<Self> {
-        if track.is_playable.unwrap_or(true) {
-            let id = match track.linked_from {
-                Some(d) => d.id?,
-                None => track.id?,
-            };
-            Some(Self {
-                id,
-                name: track.name,
-                artists: from_simplified_artists_to_artists(track.artists),
-                album: Album::try_from_simplified_album(track.album),
-                duration: track.duration.to_std().expect("valid chrono duration"),
-                explicit: track.explicit,
-                added_at: added_at.map(|t| t.timestamp() as u64).unwrap_or_default(),
-            })
-        } else {
-            None
-        }
+        let (id, relinked_id) = match track.linked_from {
+            Some(d) => (d.id?, track.id),
+            None => (track.id?, None),
+        };
+        Some(Self {
+            id,
+            name: track.name,
+            artists: from_simplified_artists_to_artists(track.artists),
+            album: Album::try_from_simplified_album(track.album),
+            duration: track.duration.to_std().expect("valid chrono duration"),
+            explicit: track.explicit,
+            added_at: added_at.map(|t| t.timestamp() as u64).unwrap_or_default(),
+            disc_number: track.disc_number,
+            is_unavailable: track.is_playable == Some(false),
+            relinked_id,
+        })
     }
 
     /// tries to convert from a `rspotify::model::FullTrack` into `Track`
@@ -445,6 +955,8 @@ impl Album {
                     _ => None,
                 }),
             added_at: 0,
+            label: None,
+            copyrights: vec![],
         })
     }
 
@@ -457,6 +969,16 @@ impl Album {
             .to_string()
     }
 
+    /// gets the album's label and copyright statements, separated by `|`
+    pub fn credits(&self) -> String {
+        self.label
+            .iter()
+            .chain(self.copyrights.iter())
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
     /// gets the album type
     pub fn album_type(&self) -> String {
         match self.typ {
@@ -475,6 +997,8 @@ impl From<rspotify::model::FullAlbum> for Album {
             artists: from_simplified_artists_to_artists(album.artists),
             typ: Some(album.album_type),
             added_at: 0,
+            label: album.label,
+            copyrights: album.copyrights.into_iter().map(|c| c.text).collect(),
         }
     }
 }
@@ -539,6 +1063,16 @@ fn from_simplified_artists_to_artists(
 
 impl BidiDisplay for Artist {}
 
+impl Playlist {
+    /// Check if the playlist is shared with other users, i.e. a collaborative playlist or a Blend.
+    ///
+    /// Blends aren't exposed by the API, so they are recognized as Spotify-owned playlists
+    /// named after their members (e.g. "Alice + Bob").
+    pub fn is_shared(&self) -> bool {
+        self.collaborative || (self.owner.1.id() == "spotify" && self.name.contains(" + "))
+    }
+}
+
 impl From<rspotify::model::SimplifiedPlaylist> for Playlist {
     fn from(playlist: rspotify::model::SimplifiedPlaylist) -> Self {
         Self {
@@ -614,6 +1148,7 @@ impl BidiDisplay for Show {}
 
 impl From<rspotify::model::SimplifiedEpisode> for Episode {
     fn from(episode: rspotify::model::SimplifiedEpisode) -> Self {
+        let (fully_played, resume_position) = resume_point_progress(episode.resume_point);
         Self {
             id: episode.id,
             name: episode.name,
@@ -621,12 +1156,15 @@ impl From<rspotify::model::SimplifiedEpisode> for Episode {
             duration: episode.duration.to_std().expect("valid chrono duration"),
             show: None,
             release_date: episode.release_date,
+            fully_played,
+            resume_position,
         }
     }
 }
 
 impl From<rspotify::model::FullEpisode> for Episode {
     fn from(episode: rspotify::model::FullEpisode) -> Self {
+        let (fully_played, resume_position) = resume_point_progress(episode.resume_point);
         Self {
             id: episode.id,
             name: episode.name,
@@ -634,6 +1172,34 @@ impl From<rspotify::model::FullEpisode> for Episode {
             duration: episode.duration.to_std().expect("valid chrono duration"),
             show: Some(episode.show.into()),
             release_date: episode.release_date,
+            fully_played,
+            resume_position,
+        }
+    }
+}
+
+/// Get the played status and the resume position of an episode from its resume point
+fn resume_point_progress(
+    resume_point: Option<rspotify::model::ResumePoint>,
+) -> (bool, std::time::Duration) {
+    resume_point.map_or((false, std::time::Duration::ZERO), |p| {
+        (
+            p.fully_played,
+            p.resume_position.to_std().unwrap_or_default(),
+        )
+    })
+}
+
+impl Episode {
+    /// The user's progress of the episode: played, in progress at a resume position, or unplayed
+    pub fn progress(&self) -> String {
+        if self.fully_played {
+            "✓ played".to_string()
+        } else if self.resume_position.is_zero() {
+            "● unplayed".to_string()
+        } else {
+            let secs = self.resume_position.as_secs();
+            format!("◐ {}:{:02}", secs / 60, secs % 60)
         }
     }
 }
@@ -696,6 +1262,19 @@ impl TracksId {
 }
 
 impl Playback {
+    /// creates a playback of a context, track or episode from its URI
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        if let Some(id) = ContextId::from_uri(uri) {
+            return Some(Playback::Context(id, None));
+        }
+        let id = match uri.split(':').nth(1)? {
+            "track" => PlayableId::Track(TrackId::from_uri(uri).ok()?.into_static()),
+            "episode" => PlayableId::Episode(EpisodeId::from_uri(uri).ok()?.into_static()),
+            _ => return None,
+        };
+        Some(Playback::URIs(vec![id], None))
+    }
+
     /// creates new playback with a specified offset based on the current playback
     pub fn uri_offset(&self, uri: String, limit: usize) -> Self {
         match self {
@@ -764,3 +1343,69 @@ impl From<librespot_metadata::lyrics::Lyrics> for Lyrics {
         Self { lines }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The order of the tracks in a playlist merged from multiple playlists
+pub enum PlaylistMergeOrder {
+    /// the tracks of the first playlist, then the tracks of the second playlist, etc
+    Concatenate,
+    /// the first track of each playlist, then the second track of each playlist, etc
+    Interleave,
+    /// the tracks of all playlists ordered by the date they were added to their playlists
+    AddedDate,
+}
+
+impl PlaylistMergeOrder {
+    /// The next order, used to cycle through the orders
+    pub fn next(self) -> Self {
+        match self {
+            Self::Concatenate => Self::Interleave,
+            Self::Interleave => Self::AddedDate,
+            Self::AddedDate => Self::Concatenate,
+        }
+    }
+
+    /// Merge the tracks of multiple playlists, optionally removing duplicated tracks
+    /// (only the first occurrence of a track is kept)
+    pub fn merge(self, playlists: Vec<Vec<Track>>, deduplicate: bool) -> Vec<Track> {
+        let mut tracks = match self {
+            Self::Concatenate => playlists.into_iter().flatten().collect::<Vec<_>>(),
+            Self::Interleave => {
+                let mut iters = playlists
+                    .into_iter()
+                    .map(Vec::into_iter)
+                    .collect::<Vec<_>>();
+                let mut tracks = vec![];
+                loop {
+                    let n_tracks = tracks.len();
+                    tracks.extend(iters.iter_mut().filter_map(Iterator::next));
+                    if tracks.len() == n_tracks {
+                        break tracks;
+                    }
+                }
+            }
+            Self::AddedDate => {
+                let mut tracks = playlists.into_iter().flatten().collect::<Vec<_>>();
+                tracks.sort_by_key(|t| t.added_at);
+                tracks
+            }
+        };
+
+        if deduplicate {
+            let mut ids = std::collections::HashSet::new();
+            tracks.retain(|t| ids.insert(t.id.clone()));
+        }
+        tracks
+    }
+}
+
+impl std::fmt::Display for PlaylistMergeOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let desc = match self {
+            Self::Concatenate => "concatenate",
+            Self::Interleave => "interleave",
+            Self::AddedDate => "by added date",
+        };
+        write!(f, "{desc}")
+    }
+}
@@ -1,4 +1,5 @@
 use super::model::{AlbumId, ArtistId, ContextId, Device, PlaybackMetadata, PlaylistId, ShowId};
+use rspotify::model::Id;
 
 /// Player state
 #[derive(Default, Debug)]
@@ -7,11 +8,119 @@ pub struct PlayerState {
 
     pub playback: Option<rspotify::model::CurrentPlaybackContext>,
     pub playback_last_updated_time: Option<std::time::Instant>,
+    pub connection_status: ConnectionStatus,
     /// A buffered state to speedup the feedback of playback metadata update to user
     // Related issue: https://github.com/aome510/spotify-player/issues/109
     pub buffered_playback: Option<PlaybackMetadata>,
 
     pub queue: Option<rspotify::model::CurrentUserQueue>,
+
+    /// A temporary cue point (track's URI, position) in the current track
+    pub cue_point: Option<(String, chrono::Duration)>,
+    /// An ongoing preview of the next queued track
+    pub track_preview: Option<TrackPreview>,
+    /// An A-B loop within the current track
+    pub ab_loop: Option<ABLoop>,
+    /// An ongoing audition of the playing context
+    pub audition: Option<Audition>,
+    /// The last playback of the application's previous run, which can be continued
+    pub last_playback: Option<LastPlayback>,
+
+    /// IDs of the devices grouped together in the devices popup. Volume changes are applied
+    /// to every device in the group and playback can be moved between the group's devices.
+    pub device_group: Vec<String>,
+
+    /// the end behaviors of contexts (by URI) changed from the `context_end_behavior` config option
+    pub context_end_behaviors: std::collections::HashMap<String, crate::config::ContextEndBehavior>,
+
+    /// the bitrate (in kbps) of the integrated player's audio streams, which is
+    /// initialized from the `device.bitrate` config option and can be changed at runtime
+    #[cfg(feature = "streaming")]
+    pub streaming_bitrate: u16,
+    /// the format (codec and bitrate) of the current track's audio streamed by the integrated player
+    #[cfg(feature = "streaming")]
+    pub stream_quality: Option<String>,
+    /// whether the integrated player and its session are shut down after the playback
+    /// has been paused for `daemon_idle_timeout_mins` minutes in daemon mode
+    #[cfg(feature = "daemon")]
+    pub is_idle: bool,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+/// The status of the application's connection to Spotify
+pub enum ConnectionStatus {
+    #[default]
+    Online,
+    /// the last request to get the playback failed, e.g. due to a lost network connection
+    Offline,
+    /// the client's session is lost and being re-created
+    Reconnecting,
+}
+
+impl ConnectionStatus {
+    pub fn desc(self) -> &'static str {
+        match self {
+            Self::Online => "online",
+            Self::Offline => "offline",
+            Self::Reconnecting => "reconnecting…",
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A loop between two positions (A and B) of a track.
+/// The loop is active once both of its positions are set.
+pub struct ABLoop {
+    pub track_uri: String,
+    pub a: chrono::Duration,
+    pub b: Option<chrono::Duration>,
+}
+
+#[derive(Debug)]
+/// A preview of the next queued track, which can be committed (keep playing the previewed track)
+/// or reverted (go back to the previous track's position) after a while
+pub struct TrackPreview {
+    /// the track (with its context and position) to go back to upon reverting the preview
+    pub previous: LastPlayback,
+    pub started_at: std::time::Instant,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// The last played track (or episode) and its position, which is stored when the application exits
+pub struct LastPlayback {
+    /// the URI of the playing context, which is `None` for a playback of tracks without a context
+    pub context_uri: Option<String>,
+    pub item_uri: String,
+    pub item_name: String,
+    pub position_ms: i64,
+}
+
+#[derive(Debug, Default)]
+/// An audition of the playing context, which plays a part of each track back to back
+pub struct Audition {
+    /// the URI of the auditioned track, which is `None` until the first track's part starts playing
+    pub track_uri: Option<String>,
+    /// when the auditioned track's part started playing
+    pub started_at: Option<std::time::Instant>,
+    /// the volume before fading out the track's part, which is restored on the next track
+    pub faded_volume: Option<u8>,
+    /// whether the next track is requested at the end of the track's part
+    pub next_requested: bool,
+}
+
+#[cfg(any(feature = "remote", feature = "mqtt"))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+/// A serializable snapshot of the current playback, used by external integrations
+pub struct PlaybackSnapshot {
+    pub uri: String,
+    pub name: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub is_playing: bool,
+    pub progress_ms: i64,
+    pub duration_ms: i64,
+    pub volume: Option<u32>,
+    pub device: String,
 }
 
 impl PlayerState {
@@ -50,6 +159,42 @@ impl PlayerState {
         self.playback.as_ref().and_then(|p| p.item.as_ref())
     }
 
+    /// Get the URI of the currently playing track or episode
+    pub fn currently_playing_uri(&self) -> Option<String> {
+        match self.currently_playing()? {
+            rspotify::model::PlayableItem::Track(track) => track.id.as_ref().map(Id::uri),
+            rspotify::model::PlayableItem::Episode(episode) => Some(episode.id.uri()),
+            rspotify::model::PlayableItem::Unknown(_) => None,
+        }
+    }
+
+    /// Get the URI of the currently playing track (episodes excluded) and its playback progress,
+    /// which is the played duration of the track's listen
+    pub fn current_track_listen(&self) -> Option<(String, chrono::Duration)> {
+        match self.currently_playing()? {
+            rspotify::model::PlayableItem::Track(_) => {
+                self.currently_playing_uri().zip(self.playback_progress())
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the current playback as a last playback to continue in a next run
+    pub fn last_playback_snapshot(&self) -> Option<LastPlayback> {
+        let playback = self.playback.as_ref()?;
+        let (item_uri, item_name) = match playback.item.as_ref()? {
+            rspotify::model::PlayableItem::Track(track) => (track.id.as_ref()?.uri(), &track.name),
+            rspotify::model::PlayableItem::Episode(episode) => (episode.id.uri(), &episode.name),
+            rspotify::model::PlayableItem::Unknown(_) => return None,
+        };
+        Some(LastPlayback {
+            context_uri: playback.context.as_ref().map(|c| c.uri.clone()),
+            item_uri,
+            item_name: item_name.clone(),
+            position_ms: self.playback_progress()?.num_milliseconds(),
+        })
+    }
+
     pub fn playback_progress(&self) -> Option<chrono::Duration> {
         match self.playback {
             None => None,
@@ -68,6 +213,53 @@ impl PlayerState {
         }
     }
 
+    #[cfg(any(feature = "remote", feature = "mqtt"))]
+    /// Get a snapshot of the current playback
+    pub fn playback_snapshot(&self) -> Option<PlaybackSnapshot> {
+        let playback = self.current_playback()?;
+        let (uri, name, artists, album, duration) = match playback.item? {
+            rspotify::model::PlayableItem::Track(track) => (
+                track.id?.uri(),
+                track.name,
+                track.artists.into_iter().map(|a| a.name).collect(),
+                track.album.name,
+                track.duration,
+            ),
+            rspotify::model::PlayableItem::Episode(episode) => (
+                episode.id.uri(),
+                episode.name,
+                vec![episode.show.publisher],
+                episode.show.name,
+                episode.duration,
+            ),
+            rspotify::model::PlayableItem::Unknown(_) => return None,
+        };
+
+        Some(PlaybackSnapshot {
+            uri,
+            name,
+            artists,
+            album,
+            is_playing: playback.is_playing,
+            progress_ms: playback
+                .progress
+                .unwrap_or_default()
+                .min(duration)
+                .num_milliseconds(),
+            duration_ms: duration.num_milliseconds(),
+            volume: playback.device.volume_percent,
+            device: playback.device.name,
+        })
+    }
+
+    /// Get the behavior when the given context ends
+    pub fn context_end_behavior(&self, id: &ContextId) -> crate::config::ContextEndBehavior {
+        self.context_end_behaviors
+            .get(&id.uri())
+            .copied()
+            .unwrap_or(crate::config::get_config().app_config.context_end_behavior)
+    }
+
     pub fn playing_context_id(&self) -> Option<ContextId> {
         match self.playback {
             Some(ref playback) => match playback.context {
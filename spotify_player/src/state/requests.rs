@@ -0,0 +1,92 @@
+//! Cooperative cancellation of the client's in-flight requests.
+//!
+//! Every request handled by the client is registered with a [`CancellationToken`] (from `tokio-util`), which cancels
+//! the request when
+//! - the user leaves the page whose data the request loads (see [`InFlightRequests::cancel_page_requests`])
+//! - the application is shutting down (see [`InFlightRequests::cancel_all`])
+//!
+//! A cancelled request stops at its next pending API call instead of running to completion,
+//! so it doesn't update the application's data no longer needed or delay the application's shutdown.
+
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use parking_lot::Mutex;
+pub use tokio_util::sync::CancellationToken;
+
+#[derive(Debug)]
+struct InFlightRequest {
+    page_key: Option<String>,
+    token: CancellationToken,
+}
+
+#[derive(Debug, Default)]
+/// The client's requests being handled
+pub struct InFlightRequests {
+    next_id: AtomicU64,
+    requests: Mutex<HashMap<u64, InFlightRequest>>,
+}
+
+impl InFlightRequests {
+    /// Register a request, which is unregistered when the returned guard is dropped.
+    ///
+    /// `page_key` identifies the page data loaded by the request (e.g. a context's URI),
+    /// or is `None` if the request isn't bound to a page.
+    pub fn register(&self, page_key: Option<String>) -> InFlightRequestGuard<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::default();
+        self.requests.lock().insert(
+            id,
+            InFlightRequest {
+                page_key,
+                token: token.clone(),
+            },
+        );
+        InFlightRequestGuard {
+            requests: self,
+            id,
+            token,
+        }
+    }
+
+    /// Cancel the requests loading data of pages other than the pages identified by `page_keys`
+    pub fn cancel_page_requests(&self, page_keys: &[String]) {
+        for request in self.requests.lock().values() {
+            if let Some(key) = &request.page_key {
+                if !page_keys.contains(key) && !request.token.is_cancelled() {
+                    tracing::info!("Cancelling the request loading data of the left page {key}");
+                    request.token.cancel();
+                }
+            }
+        }
+    }
+
+    /// Cancel all requests
+    pub fn cancel_all(&self) {
+        for request in self.requests.lock().values() {
+            request.token.cancel();
+        }
+    }
+}
+
+/// A guard unregistering a request when dropped
+pub struct InFlightRequestGuard<'a> {
+    requests: &'a InFlightRequests,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl InFlightRequestGuard<'_> {
+    /// The token cancelling the request
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for InFlightRequestGuard<'_> {
+    fn drop(&mut self) {
+        self.requests.requests.lock().remove(&self.id);
+    }
+}
@@ -0,0 +1,168 @@
+//! Performance statistics of the application, displayed in the performance overlay
+//! to help diagnosing slowness
+
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Default)]
+/// Statistics of the client's API requests
+pub struct ApiStats {
+    n_pending: AtomicUsize,
+    /// the duration (in microseconds) of the last handled request
+    last_duration_us: AtomicU64,
+}
+
+impl ApiStats {
+    /// Record the start of a request, whose end is recorded when the returned guard is dropped,
+    /// i.e. when the request is handled or cancelled
+    pub fn track_request(&self) -> RequestGuard<'_> {
+        self.n_pending.fetch_add(1, Ordering::Relaxed);
+        RequestGuard {
+            stats: self,
+            start: Instant::now(),
+        }
+    }
+
+    /// Record the end of a request, which took `duration` to handle
+    fn finish_request(&self, duration: Duration) {
+        self.n_pending.fetch_sub(1, Ordering::Relaxed);
+        self.last_duration_us.store(
+            u64::try_from(duration.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// The number of requests being handled
+    pub fn n_pending(&self) -> usize {
+        self.n_pending.load(Ordering::Relaxed)
+    }
+
+    /// The duration of the last handled request
+    pub fn last_duration(&self) -> Duration {
+        Duration::from_micros(self.last_duration_us.load(Ordering::Relaxed))
+    }
+}
+
+/// A guard recording the end of a request when dropped
+pub struct RequestGuard<'a> {
+    stats: &'a ApiStats,
+    start: Instant,
+}
+
+impl Drop for RequestGuard<'_> {
+    fn drop(&mut self) {
+        self.stats.finish_request(self.start.elapsed());
+    }
+}
+
+#[cfg(feature = "streaming")]
+#[derive(Debug, Default)]
+/// Statistics of the integrated player's playback, which help diagnosing flaky audio
+pub struct PlaybackStats {
+    n_bufferings: AtomicUsize,
+    /// the total duration (in milliseconds) of the bufferings
+    buffering_duration_ms: AtomicU64,
+    /// the start of the ongoing buffering (if any)
+    buffering_start: parking_lot::Mutex<Option<Instant>>,
+    n_underruns: AtomicUsize,
+    n_reconnects: AtomicUsize,
+}
+
+#[cfg(feature = "streaming")]
+impl PlaybackStats {
+    /// Record the start of a buffering, i.e. when the player is delayed by loading a track
+    pub fn start_buffering(&self) {
+        let mut buffering_start = self.buffering_start.lock();
+        if buffering_start.is_none() {
+            *buffering_start = Some(Instant::now());
+            self.n_bufferings.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Record the end of the ongoing buffering (if any), i.e. when the player starts playing
+    pub fn end_buffering(&self) {
+        if let Some(start) = self.buffering_start.lock().take() {
+            self.buffering_duration_ms.fetch_add(
+                u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+                Ordering::Relaxed,
+            );
+        }
+    }
+
+    /// Record an underrun, i.e. when the playback falls behind its expected position
+    pub fn record_underrun(&self) {
+        self.n_underruns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a reconnection of the client's session
+    pub fn record_reconnect(&self) {
+        self.n_reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn n_bufferings(&self) -> usize {
+        self.n_bufferings.load(Ordering::Relaxed)
+    }
+
+    /// The total duration of the bufferings, including the ongoing buffering
+    pub fn buffering_duration(&self) -> Duration {
+        Duration::from_millis(self.buffering_duration_ms.load(Ordering::Relaxed))
+            + self
+                .buffering_start
+                .lock()
+                .map(|start| start.elapsed())
+                .unwrap_or_default()
+    }
+
+    pub fn n_underruns(&self) -> usize {
+        self.n_underruns.load(Ordering::Relaxed)
+    }
+
+    pub fn n_reconnects(&self) -> usize {
+        self.n_reconnects.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Default)]
+/// Statistics of the UI's rendered frames
+pub struct RenderStats {
+    /// the end times of the frames rendered in the last second
+    frame_times: VecDeque<Instant>,
+    /// the duration of the last frame
+    pub frame_duration: Duration,
+    /// the time spent rendering the visualization in the last frame
+    pub visualization_duration: Duration,
+    /// the time spent rendering the visualization in the ongoing frame
+    current_visualization_duration: Duration,
+}
+
+impl RenderStats {
+    /// Record the time spent rendering the visualization in the ongoing frame
+    #[cfg(feature = "fx")]
+    pub fn record_visualization(&mut self, duration: Duration) {
+        self.current_visualization_duration += duration;
+    }
+
+    /// Record the end of a frame, which took `duration` to render
+    pub fn record_frame(&mut self, duration: Duration) {
+        let now = Instant::now();
+        self.frame_times.push_back(now);
+        while self
+            .frame_times
+            .front()
+            .is_some_and(|t| now - *t > Duration::from_secs(1))
+        {
+            self.frame_times.pop_front();
+        }
+
+        self.frame_duration = duration;
+        self.visualization_duration = std::mem::take(&mut self.current_visualization_duration);
+    }
+
+    /// The number of frames rendered in the last second
+    pub fn fps(&self) -> usize {
+        self.frame_times.len()
+    }
+}
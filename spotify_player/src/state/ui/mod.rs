@@ -1,7 +1,11 @@
 use crate::{
     config::{self, Theme},
     key,
-    ui::{self, Orientation},
+    ui::{
+        self,
+        single_line_input::{InputHistory, LineInput},
+        Orientation,
+    },
     utils::filtered_items_from_query,
 };
 
@@ -9,8 +13,9 @@ pub type UIStateGuard<'a> = parking_lot::MutexGuard<'a, UIState>;
 
 mod page;
 mod popup;
+mod session;
 
-use super::TracksId;
+use super::{AppData, Id, Playlist, Track, TracksId};
 
 pub use page::*;
 pub use popup::*;
@@ -24,26 +29,85 @@ pub struct ImageRenderInfo {
     pub rendered: bool,
 }
 
+#[derive(Debug, Clone)]
+/// A change liking (or unliking) multiple tracks at once
+pub struct LikedTracksChange {
+    /// the tracks whose liked status was changed
+    pub tracks: Vec<Track>,
+    pub liked: bool,
+}
+
 /// Application's UI state
 #[derive(Debug)]
+/// A tab with its own navigation histories
+#[derive(Default)]
+pub struct Tab {
+    pub history: Vec<PageState>,
+    pub forward_history: Vec<PageState>,
+}
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct UIState {
     pub is_running: bool,
+    /// whether the terminal is handed over to another program (e.g. an editor),
+    /// during which the application's UI isn't rendered
+    pub is_terminal_suspended: bool,
     pub theme: config::Theme,
     pub input_key_sequence: key::KeySequence,
     pub orientation: ui::Orientation,
 
+    /// the navigation history of the current tab
     pub history: Vec<PageState>,
+    /// the pages of the current tab that were navigated back from
+    pub forward_history: Vec<PageState>,
+    /// the opened tabs, in which the current tab's navigation histories
+    /// are moved out into `history` and `forward_history`
+    pub tabs: Vec<Tab>,
+    pub current_tab: usize,
     pub popup: Option<PopupState>,
+    /// the history of the queries submitted in the search page
+    pub search_history: InputHistory,
 
     /// The rectangle representing the playback progress bar,
     /// which is mainly used to handle mouse click events (for seeking command)
     pub playback_progress_bar_rect: ratatui::layout::Rect,
+    /// The rectangle representing the playback controls in the compact mode,
+    /// which is mainly used to handle mouse click events
+    pub compact_controls_rect: ratatui::layout::Rect,
+    /// The rectangles representing the pages in the breadcrumb of a context page,
+    /// which are mainly used to handle mouse click events
+    pub breadcrumb_rects: Vec<ratatui::layout::Rect>,
 
     /// Count prefix for vim-style navigation (e.g., 5j, 10k)
     pub count_prefix: Option<usize>,
 
+    /// The key events of the macro being recorded (if any)
+    pub macro_recording: Option<Vec<crossterm::event::KeyEvent>>,
+    /// The key events of the last recorded macro
+    pub last_macro: Vec<crossterm::event::KeyEvent>,
+    /// The number of times the last recorded macro should be replayed after handling the current key
+    pub pending_macro_replays: usize,
+    /// Whether a macro is being replayed, which is used to prevent a macro from replaying itself
+    pub is_replaying_macro: bool,
+    /// A playlist whose tracks should be edited in an external editor after handling the current key
+    pub pending_playlist_edit: Option<Playlist>,
+    /// The last change liking or unliking all tracks of a context, which can be undone
+    pub last_liked_tracks_change: Option<LikedTracksChange>,
+
+    pub show_performance_overlay: bool,
+    /// whether the terminal has a light background, see the `terminal_background` config option
+    pub is_light_background: bool,
+    /// the color blindness simulated when rendering the application, for checking themes
+    pub color_blindness_simulation: Option<crate::ui::utils::ColorBlindness>,
+    /// a short message shown on top of the application, along with the time it was shown
+    pub toast: Option<(String, std::time::Instant)>,
+    pub render_stats: super::RenderStats,
+
     #[cfg(feature = "image")]
     pub last_cover_image_render_info: ImageRenderInfo,
+    /// the resized cover art of the now playing page, along with its image's URL and size
+    #[cfg(feature = "image")]
+    pub cover_art_thumbnail: Option<(String, (u16, u16), image::RgbImage)>,
 
     #[cfg(feature = "fx")]
     pub effects_state: crate::ui::effects::EffectsState,
@@ -51,19 +115,68 @@ pub struct UIState {
     pub enable_effects: bool,
     #[cfg(feature = "fx")]
     pub current_effect: config::ProgressBarEffect,
-    
+
     // Visualization state
+    /// the start of the "drop" animation played when a track starts
     #[cfg(feature = "fx")]
-    pub visualization_start_time: std::time::Instant,
+    pub visualization_drop_start: Option<std::time::Instant>,
     #[cfg(all(feature = "fx", feature = "image"))]
     pub current_album_color: Option<(u8, u8, u8)>,
     #[cfg(feature = "fx")]
     pub current_bpm: f64, // Track tempo/BPM
     #[cfg(feature = "fx")]
+    pub beat_clock: crate::ui::beat_clock::BeatClock,
+    /// the clock of the animations, see `set_clock`
+    #[cfg(feature = "fx")]
+    pub clock: crate::ui::clock::Clock,
+    #[cfg(feature = "fx")]
     pub enable_visualization: bool,
+    /// whether a snapshot of the visualization should be saved when it's rendered next
+    #[cfg(feature = "fx")]
+    pub visualization_snapshot_requested: bool,
+    #[cfg(feature = "fx")]
+    pub visualization_recording: Option<crate::ui::visualizations::VisualizationRecording>,
+    /// the current visualization mode and color scheme
+    #[cfg(feature = "fx")]
+    pub visualization: crate::ui::visualizations::VisualizationPreference,
+    /// the visualization picked by the user, restored when a track without
+    /// a remembered visualization preference starts
+    #[cfg(feature = "fx")]
+    pub visualization_fallback: Option<crate::ui::visualizations::VisualizationPreference>,
+    /// the visualization mode picked for the current track in the `Smart` mode
+    #[cfg(feature = "fx")]
+    pub smart_visualization_mode: crate::ui::visualizations::VisualizationMode,
+    /// the visualization's color in the last render
+    #[cfg(feature = "fx")]
+    pub visualization_color: Option<(u8, u8, u8)>,
+    /// an ongoing crossfade of the visualization's color after a track change
+    #[cfg(feature = "fx")]
+    pub visualization_crossfade: Option<crate::ui::visualizations::ColorCrossfade>,
+    /// the plugin's visualization rendered instead of the built-in ones, as its plugin's and its names
+    #[cfg(all(feature = "fx", feature = "plugins"))]
+    pub plugin_visualization: Option<(String, String)>,
 }
 
 impl UIState {
+    /// Show a short message on top of the application for a few seconds
+    pub fn show_toast(&mut self, message: String) {
+        self.toast = Some((message, std::time::Instant::now()));
+    }
+
+    /// Set the clock of the animations, restarting the ongoing animations at the clock's time
+    #[cfg(feature = "fx")]
+    pub fn set_clock(&mut self, clock: crate::ui::clock::Clock) {
+        self.clock = clock;
+        self.effects_state = crate::ui::effects::EffectsState {
+            last_update: clock.now(),
+            track_change_start: None,
+        };
+        self.beat_clock = crate::ui::beat_clock::BeatClock::default();
+        self.visualization_drop_start = None;
+        self.visualization_crossfade = None;
+        crate::ui::visualizations::set_pattern_seed(clock.seed());
+    }
+
     pub fn current_page(&self) -> &PageState {
         self.history.last().expect("non-empty history")
     }
@@ -75,15 +188,96 @@ impl UIState {
     pub fn new_search_popup(&mut self) {
         self.current_page_mut().select(0);
         self.popup = Some(PopupState::Search {
-            query: String::new(),
+            query: LineInput::default(),
         });
     }
 
     pub fn new_page(&mut self, page: PageState) {
         self.history.push(page);
+        self.forward_history.clear();
         self.popup = None;
     }
 
+    /// Go back to the previous page in the navigation history
+    pub fn previous_page(&mut self) {
+        if self.history.len() > 1 {
+            self.forward_history
+                .push(self.history.pop().expect("non-empty history"));
+            self.popup = None;
+        }
+    }
+
+    /// Go back to the `id`-th page in the navigation history
+    pub fn go_back_to_page(&mut self, id: usize) {
+        while self.history.len() > id + 1 {
+            self.previous_page();
+        }
+    }
+
+    /// Go forward to the page navigated back from
+    pub fn next_page(&mut self) {
+        if let Some(page) = self.forward_history.pop() {
+            self.history.push(page);
+            self.popup = None;
+        }
+    }
+
+    /// Open a new tab next to the current tab, starting with a given page
+    pub fn new_tab(&mut self, page: PageState) {
+        self.tabs.insert(
+            self.current_tab + 1,
+            Tab {
+                history: vec![page],
+                forward_history: vec![],
+            },
+        );
+        self.switch_tab(self.current_tab + 1);
+    }
+
+    /// Close the current tab and go to the next tab (if any), or the previous tab
+    pub fn close_tab(&mut self) {
+        if self.tabs.len() <= 1 {
+            return;
+        }
+        self.tabs.remove(self.current_tab);
+        self.current_tab = self.current_tab.min(self.tabs.len() - 1);
+        let tab = std::mem::take(&mut self.tabs[self.current_tab]);
+        self.history = tab.history;
+        self.forward_history = tab.forward_history;
+        self.popup = None;
+    }
+
+    /// Switch to the `id`-th tab
+    pub fn switch_tab(&mut self, id: usize) {
+        if id == self.current_tab || id >= self.tabs.len() {
+            return;
+        }
+        let tab = &mut self.tabs[self.current_tab];
+        std::mem::swap(&mut self.history, &mut tab.history);
+        std::mem::swap(&mut self.forward_history, &mut tab.forward_history);
+        let tab = &mut self.tabs[id];
+        std::mem::swap(&mut self.history, &mut tab.history);
+        std::mem::swap(&mut self.forward_history, &mut tab.forward_history);
+        self.current_tab = id;
+        self.popup = None;
+    }
+
+    /// Get the titles of the tabs' current pages
+    pub fn tab_titles(&self) -> Vec<String> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(id, tab)| {
+                let page = if id == self.current_tab {
+                    self.current_page()
+                } else {
+                    tab.history.last().expect("non-empty history")
+                };
+                page.title()
+            })
+            .collect()
+    }
+
     pub fn new_radio_page(&mut self, uri: &str) {
         self.new_page(PageState::Context {
             id: None,
@@ -108,10 +302,102 @@ impl UIState {
     /// Get a list of items possibly filtered by a search query if exists a search popup
     pub fn search_filtered_items<'a, T: std::fmt::Display>(&self, items: &'a [T]) -> Vec<&'a T> {
         match self.popup {
-            Some(PopupState::Search { ref query }) => filtered_items_from_query(query, items),
+            Some(PopupState::Search { ref query }) => {
+                filtered_items_from_query(&query.get_text(), items)
+            }
             _ => items.iter().collect::<Vec<_>>(),
         }
     }
+
+    /// Keep the selected track of a context's page selected after the context is refreshed,
+    /// given the URIs of the context's tracks before and after the refresh.
+    ///
+    /// The table is scrolled along with the selected track, so that it stays at the same row.
+    pub fn follow_refreshed_context(
+        &mut self,
+        context_uri: &str,
+        previous_track_uris: &[String],
+        track_uris: &[String],
+    ) {
+        // positions of a filtered table don't match the positions of the context's tracks
+        if matches!(self.popup, Some(PopupState::Search { .. })) {
+            return;
+        }
+        let PageState::Context {
+            id: Some(id),
+            state:
+                Some(
+                    ContextPageUIState::Playlist {
+                        track_table,
+                        grabbed_track: None,
+                    }
+                    | ContextPageUIState::Album { track_table }
+                    | ContextPageUIState::Tracks { track_table },
+                ),
+            ..
+        } = self.current_page_mut()
+        else {
+            return;
+        };
+        if id.uri() != context_uri {
+            return;
+        }
+        let Some(selected) = track_table.selected() else {
+            return;
+        };
+
+        match previous_track_uris
+            .get(selected)
+            .and_then(|uri| track_uris.iter().position(|u| u == uri))
+        {
+            Some(position) => {
+                *track_table.offset_mut() =
+                    (track_table.offset() + position).saturating_sub(selected);
+                track_table.select(Some(position));
+            }
+            // the selected track is removed, select the track now at its position
+            None => track_table.select(Some(selected.min(track_uris.len().saturating_sub(1)))),
+        }
+    }
+
+    /// Get a list of tracks possibly filtered by a search query if exists a search popup.
+    ///
+    /// Apart from normal search terms, the query can include `rating:N` and `tag:name` filters
+    /// to match tracks based on their local annotations, and `year:` and `decade:` filters
+    /// to match tracks based on their release dates.
+    pub fn search_filtered_tracks<'a>(
+        &self,
+        tracks: &'a [Track],
+        data: &AppData,
+    ) -> Vec<&'a Track> {
+        let Some(PopupState::Search { ref query }) = self.popup else {
+            return tracks.iter().collect::<Vec<_>>();
+        };
+        let query = query.get_text();
+
+        let (filters, terms): (Vec<_>, Vec<_>) = query
+            .split(' ')
+            .partition(|t| t.starts_with("rating:") || t.starts_with("tag:"));
+        let (release_filters, terms): (Vec<_>, Vec<_>) = terms
+            .into_iter()
+            .partition(|t| t.starts_with("year:") || t.starts_with("decade:"));
+        let tracks = tracks
+            .iter()
+            .filter(|t| {
+                filters.is_empty()
+                    || data
+                        .user_data
+                        .track_annotations
+                        .get(&t.id.uri())
+                        .is_some_and(|a| filters.iter().all(|f| a.matches_filter(f)))
+            })
+            .filter(|t| release_filters.iter().all(|f| t.matches_release_filter(f)))
+            .collect::<Vec<_>>();
+        filtered_items_from_query(&terms.join(" "), &tracks)
+            .into_iter()
+            .copied()
+            .collect()
+    }
 }
 
 use ratatui::layout::Rect;
@@ -120,6 +406,7 @@ impl Default for UIState {
     fn default() -> Self {
         Self {
             is_running: true,
+            is_terminal_suspended: false,
             theme: Theme::default(),
             input_key_sequence: key::KeySequence { keys: vec![] },
             orientation: match crossterm::terminal::size() {
@@ -133,31 +420,74 @@ impl Default for UIState {
             history: vec![PageState::Library {
                 state: LibraryPageUIState::new(),
             }],
+            forward_history: vec![],
+            tabs: vec![Tab::default()],
+            current_tab: 0,
             popup: None,
+            search_history: InputHistory::default(),
 
             playback_progress_bar_rect: Rect::default(),
+            compact_controls_rect: Rect::default(),
+            breadcrumb_rects: vec![],
 
             count_prefix: None,
 
+            macro_recording: None,
+            last_macro: vec![],
+            pending_macro_replays: 0,
+            is_replaying_macro: false,
+            pending_playlist_edit: None,
+            last_liked_tracks_change: None,
+
+            show_performance_overlay: false,
+            is_light_background: false,
+            color_blindness_simulation: None,
+            toast: None,
+            render_stats: super::RenderStats::default(),
+
             #[cfg(feature = "image")]
             last_cover_image_render_info: ImageRenderInfo::default(),
+            #[cfg(feature = "image")]
+            cover_art_thumbnail: None,
 
             #[cfg(feature = "fx")]
             effects_state: crate::ui::effects::EffectsState::default(),
             #[cfg(feature = "fx")]
-            enable_effects: config::get_config().app_config.enable_effects,
+            enable_effects: config::get_config().app_config.enable_effects
+                && !config::get_config().app_config.accessibility.reduce_motion,
             #[cfg(feature = "fx")]
             current_effect: config::get_config().app_config.progress_bar_effect.clone(),
-            
+
             // Visualization defaults
             #[cfg(feature = "fx")]
-            visualization_start_time: std::time::Instant::now(),
+            visualization_drop_start: None,
             #[cfg(all(feature = "fx", feature = "image"))]
             current_album_color: None,
             #[cfg(feature = "fx")]
             current_bpm: 120.0, // Default BPM
             #[cfg(feature = "fx")]
-            enable_visualization: config::get_config().app_config.enable_visualization,
+            beat_clock: crate::ui::beat_clock::BeatClock::default(),
+            #[cfg(feature = "fx")]
+            clock: crate::ui::clock::Clock::default(),
+            #[cfg(feature = "fx")]
+            enable_visualization: config::get_config().app_config.enable_visualization
+                && !config::get_config().app_config.accessibility.reduce_motion,
+            #[cfg(feature = "fx")]
+            visualization_snapshot_requested: false,
+            #[cfg(feature = "fx")]
+            visualization_recording: None,
+            #[cfg(feature = "fx")]
+            visualization: crate::ui::visualizations::VisualizationPreference::default(),
+            #[cfg(feature = "fx")]
+            visualization_fallback: None,
+            #[cfg(feature = "fx")]
+            smart_visualization_mode: crate::ui::visualizations::VisualizationMode::default(),
+            #[cfg(feature = "fx")]
+            visualization_color: None,
+            #[cfg(feature = "fx")]
+            visualization_crossfade: None,
+            #[cfg(all(feature = "fx", feature = "plugins"))]
+            plugin_visualization: None,
         }
     }
 }
@@ -1,8 +1,9 @@
 use crate::{
-    state::model::{Category, ContextId},
+    state::model::{Category, ContextId, Playlist, SearchType},
     ui::single_line_input::LineInput,
 };
 use ratatui::widgets::{ListState, TableState};
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug)]
 pub enum PageState {
@@ -27,12 +28,46 @@ pub enum PageState {
     Browse {
         state: BrowsePageUIState,
     },
+    Blocklist {
+        state: ListState,
+    },
+    /// A feed of the followed artists' releases and playlist adds
+    ArtistFeed {
+        state: ListState,
+    },
+    /// Collaborative playlists and Blends shared with the user
+    SharedPlaylists {
+        state: SharedPlaylistsPageUIState,
+    },
+    /// The user's saved episodes ("Your Episodes")
+    SavedEpisodes {
+        state: TableState,
+    },
     Queue {
         scroll_offset: usize,
     },
     CommandHelp {
         scroll_offset: usize,
     },
+    NowPlaying,
+    /// A comparison between the tracks of two playlists
+    PlaylistDiff {
+        first: Playlist,
+        second: Playlist,
+        state: PlaylistDiffPageUIState,
+    },
+    /// The plugin manager
+    #[cfg(feature = "plugins")]
+    Plugins {
+        state: ListState,
+    },
+    /// A page provided by a plugin
+    #[cfg(feature = "plugins")]
+    PluginPage {
+        plugin: String,
+        page: String,
+        scroll_offset: usize,
+    },
 }
 
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -41,9 +76,19 @@ pub enum PageType {
     Context,
     Search,
     Browse,
+    Blocklist,
+    ArtistFeed,
+    SharedPlaylists,
+    SavedEpisodes,
     Lyrics,
     Queue,
     CommandHelp,
+    NowPlaying,
+    PlaylistDiff,
+    #[cfg(feature = "plugins")]
+    Plugins,
+    #[cfg(feature = "plugins")]
+    PluginPage,
 }
 
 #[derive(Clone, Debug)]
@@ -66,6 +111,21 @@ pub struct SearchPageUIState {
     pub focus: SearchFocusState,
 }
 
+#[derive(Clone, Debug)]
+pub struct PlaylistDiffPageUIState {
+    pub only_in_first_list: ListState,
+    pub only_in_second_list: ListState,
+    pub in_both_list: ListState,
+    pub focus: PlaylistDiffFocusState,
+}
+
+#[derive(Clone, Debug)]
+pub struct SharedPlaylistsPageUIState {
+    pub playlist_list: ListState,
+    pub recent_track_list: ListState,
+    pub focus: SharedPlaylistsFocusState,
+}
+
 #[derive(Clone, Debug)]
 pub enum ContextPageType {
     CurrentPlaying,
@@ -76,6 +136,9 @@ pub enum ContextPageType {
 pub enum ContextPageUIState {
     Playlist {
         track_table: TableState,
+        /// the original position of the track grabbed to be moved (if any),
+        /// which is displayed at the selected position until it's dropped
+        grabbed_track: Option<usize>,
     },
     Album {
         track_table: TableState,
@@ -94,14 +157,14 @@ pub enum ContextPageUIState {
     },
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LibraryFocusState {
     Playlists,
     SavedAlbums,
     FollowedArtists,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ArtistFocusState {
     TopTracks,
     Albums,
@@ -119,6 +182,34 @@ pub enum SearchFocusState {
     Episodes,
 }
 
+impl SearchFocusState {
+    /// The type of the search results in the focused window, if any
+    pub fn search_type(self) -> Option<SearchType> {
+        match self {
+            Self::Input => None,
+            Self::Tracks => Some(SearchType::Track),
+            Self::Albums => Some(SearchType::Album),
+            Self::Artists => Some(SearchType::Artist),
+            Self::Playlists => Some(SearchType::Playlist),
+            Self::Shows => Some(SearchType::Show),
+            Self::Episodes => Some(SearchType::Episode),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlaylistDiffFocusState {
+    OnlyInFirst,
+    OnlyInSecond,
+    InBoth,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SharedPlaylistsFocusState {
+    Playlists,
+    RecentTracks,
+}
+
 #[derive(Clone, Debug)]
 pub enum BrowsePageUIState {
     CategoryList {
@@ -144,9 +235,55 @@ impl PageState {
             PageState::Context { .. } => PageType::Context,
             PageState::Search { .. } => PageType::Search,
             PageState::Browse { .. } => PageType::Browse,
+            PageState::Blocklist { .. } => PageType::Blocklist,
+            PageState::ArtistFeed { .. } => PageType::ArtistFeed,
+            PageState::SharedPlaylists { .. } => PageType::SharedPlaylists,
+            PageState::SavedEpisodes { .. } => PageType::SavedEpisodes,
             PageState::Lyrics { .. } => PageType::Lyrics,
             PageState::Queue { .. } => PageType::Queue,
             PageState::CommandHelp { .. } => PageType::CommandHelp,
+            PageState::NowPlaying => PageType::NowPlaying,
+            PageState::PlaylistDiff { .. } => PageType::PlaylistDiff,
+            #[cfg(feature = "plugins")]
+            PageState::Plugins { .. } => PageType::Plugins,
+            #[cfg(feature = "plugins")]
+            PageState::PluginPage { .. } => PageType::PluginPage,
+        }
+    }
+
+    /// The original position of the grabbed track in a playlist page (if any).
+    pub fn grabbed_track_mut(&mut self) -> Option<&mut Option<usize>> {
+        match self {
+            Self::Context {
+                state: Some(ContextPageUIState::Playlist { grabbed_track, .. }),
+                ..
+            } => Some(grabbed_track),
+            _ => None,
+        }
+    }
+
+    /// The title of the page.
+    pub fn title(&self) -> String {
+        match self {
+            PageState::Library { .. } => "Library".to_string(),
+            PageState::Context {
+                context_page_type, ..
+            } => context_page_type.title(),
+            PageState::Search { .. } => "Search".to_string(),
+            PageState::Browse { .. } => "Browse".to_string(),
+            PageState::Blocklist { .. } => "Blocklist".to_string(),
+            PageState::ArtistFeed { .. } => "Artist Feed".to_string(),
+            PageState::SharedPlaylists { .. } => "Shared Playlists".to_string(),
+            PageState::SavedEpisodes { .. } => "Your Episodes".to_string(),
+            PageState::Lyrics { .. } => "Lyrics".to_string(),
+            PageState::Queue { .. } => "Queue".to_string(),
+            PageState::CommandHelp { .. } => "Commands".to_string(),
+            PageState::NowPlaying => "Now Playing".to_string(),
+            PageState::PlaylistDiff { .. } => "Playlist Diff".to_string(),
+            #[cfg(feature = "plugins")]
+            PageState::Plugins { .. } => "Plugins".to_string(),
+            #[cfg(feature = "plugins")]
+            PageState::PluginPage { page, .. } => page.clone(),
         }
     }
 
@@ -205,7 +342,7 @@ impl PageState {
             },
             Self::Context { state, .. } => state.as_mut().map(|state| match state {
                 ContextPageUIState::Tracks { track_table }
-                | ContextPageUIState::Playlist { track_table } => {
+                | ContextPageUIState::Playlist { track_table, .. } => {
                     MutableWindowState::Table(track_table)
                 }
                 ContextPageUIState::Album { track_table } => MutableWindowState::Table(track_table),
@@ -231,7 +368,42 @@ impl PageState {
                     Some(MutableWindowState::List(state))
                 }
             },
-            Self::Lyrics { .. } => None,
+            Self::Blocklist { state } | Self::ArtistFeed { state } => {
+                Some(MutableWindowState::List(state))
+            }
+            #[cfg(feature = "plugins")]
+            Self::Plugins { state } => Some(MutableWindowState::List(state)),
+            #[cfg(feature = "plugins")]
+            Self::PluginPage { scroll_offset, .. } => {
+                Some(MutableWindowState::Scroll(scroll_offset))
+            }
+            Self::SavedEpisodes { state } => Some(MutableWindowState::Table(state)),
+            Self::PlaylistDiff {
+                state:
+                    PlaylistDiffPageUIState {
+                        only_in_first_list,
+                        only_in_second_list,
+                        in_both_list,
+                        focus,
+                    },
+                ..
+            } => Some(MutableWindowState::List(match focus {
+                PlaylistDiffFocusState::OnlyInFirst => only_in_first_list,
+                PlaylistDiffFocusState::OnlyInSecond => only_in_second_list,
+                PlaylistDiffFocusState::InBoth => in_both_list,
+            })),
+            Self::SharedPlaylists {
+                state:
+                    SharedPlaylistsPageUIState {
+                        playlist_list,
+                        recent_track_list,
+                        focus,
+                    },
+            } => Some(MutableWindowState::List(match focus {
+                SharedPlaylistsFocusState::Playlists => playlist_list,
+                SharedPlaylistsFocusState::RecentTracks => recent_track_list,
+            })),
+            Self::Lyrics { .. } | Self::NowPlaying => None,
             Self::CommandHelp { scroll_offset } | Self::Queue { scroll_offset } => {
                 Some(MutableWindowState::Scroll(scroll_offset))
             }
@@ -265,6 +437,27 @@ impl SearchPageUIState {
     }
 }
 
+impl PlaylistDiffPageUIState {
+    pub fn new() -> Self {
+        Self {
+            only_in_first_list: ListState::default(),
+            only_in_second_list: ListState::default(),
+            in_both_list: ListState::default(),
+            focus: PlaylistDiffFocusState::OnlyInFirst,
+        }
+    }
+}
+
+impl SharedPlaylistsPageUIState {
+    pub fn new() -> Self {
+        Self {
+            playlist_list: ListState::default(),
+            recent_track_list: ListState::default(),
+            focus: SharedPlaylistsFocusState::Playlists,
+        }
+    }
+}
+
 impl ContextPageType {
     pub fn title(&self) -> String {
         match self {
@@ -284,6 +477,7 @@ impl ContextPageUIState {
     pub fn new_playlist() -> Self {
         Self::Playlist {
             track_table: TableState::default(),
+            grabbed_track: None,
         }
     }
 
@@ -355,6 +549,13 @@ impl Focusable for PageState {
                 state: Some(ContextPageUIState::Artist { focus, .. }),
                 ..
             } => focus.next(),
+            Self::PlaylistDiff {
+                state: PlaylistDiffPageUIState { focus, .. },
+                ..
+            } => focus.next(),
+            Self::SharedPlaylists {
+                state: SharedPlaylistsPageUIState { focus, .. },
+            } => focus.next(),
             _ => {}
         }
 
@@ -378,6 +579,13 @@ impl Focusable for PageState {
                 state: Some(ContextPageUIState::Artist { focus, .. }),
                 ..
             } => focus.previous(),
+            Self::PlaylistDiff {
+                state: PlaylistDiffPageUIState { focus, .. },
+                ..
+            } => focus.previous(),
+            Self::SharedPlaylists {
+                state: SharedPlaylistsPageUIState { focus, .. },
+            } => focus.previous(),
             _ => {}
         }
 
@@ -424,6 +632,19 @@ impl_focusable!(
     [RelatedArtists, TopTracks]
 );
 
+impl_focusable!(
+    PlaylistDiffFocusState,
+    [OnlyInFirst, OnlyInSecond],
+    [OnlyInSecond, InBoth],
+    [InBoth, OnlyInFirst]
+);
+
+impl_focusable!(
+    SharedPlaylistsFocusState,
+    [Playlists, RecentTracks],
+    [RecentTracks, Playlists]
+);
+
 impl_focusable!(
     SearchFocusState,
     [Input, Tracks],
@@ -1,10 +1,21 @@
 use crate::{
     command,
-    state::model::{Album, Artist, Episode, EpisodeId, Playlist, Show, Track, TrackId},
-    ui::single_line_input::LineInput,
+    state::model::{
+        Album, Artist, Episode, EpisodeId, Playlist, PlaylistId, PlaylistMergeOrder, Show, Track,
+        TrackId,
+    },
+    ui::{multi_line_input::MultiLineInput, single_line_input::LineInput},
 };
 use ratatui::widgets::ListState;
 
+/// the number of options (the merge's order, de-duplication and the merge itself)
+/// listed before the playlists in the playlist merge popup
+pub const PLAYLIST_MERGE_N_OPTIONS: usize = 3;
+
+/// choices of the track rating popup, with `None` to clear a track's rating
+pub const TRACK_RATING_CHOICES: [Option<u8>; 6] =
+    [Some(5), Some(4), Some(3), Some(2), Some(1), None];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlaylistCreateCurrentField {
     Name,
@@ -14,7 +25,7 @@ pub enum PlaylistCreateCurrentField {
 #[derive(Debug)]
 pub enum PopupState {
     Search {
-        query: String,
+        query: LineInput,
     },
     UserPlaylistList(PlaylistPopupAction, ListState),
     UserFollowedArtistList(ListState),
@@ -28,6 +39,71 @@ pub enum PopupState {
         desc: LineInput,
         current_field: PlaylistCreateCurrentField,
     },
+    EpisodeBookmarkCreate {
+        episode_uri: String,
+        position: chrono::Duration,
+        note: LineInput,
+    },
+    /// A list of bookmarks within an episode, specified by the episode's URI
+    EpisodeBookmarkList(String, ListState),
+    /// A list of star ratings to rate a track, specified by the track's URI
+    TrackRating(String, ListState),
+    /// A list of a track's metadata fields, specified by the track's URI
+    TrackDetails(String, ListState),
+    TrackTagsEdit {
+        track_uri: String,
+        tags: LineInput,
+    },
+    PlaylistRename {
+        playlist_id: PlaylistId<'static>,
+        name: LineInput,
+    },
+    PlaylistDescriptionEdit {
+        playlist_id: PlaylistId<'static>,
+        desc: MultiLineInput,
+    },
+    SmartPlaylistList(ListState),
+    /// A list of genres of the user's liked tracks
+    LikedTrackGenreList(ListState),
+    /// A list of release decades of tracks, specified by the tracks' source name (e.g. a playlist)
+    TrackDecadeList {
+        name: String,
+        tracks: Vec<Track>,
+        list_state: ListState,
+    },
+    /// A confirmation of removing a track from a playlist or from the liked tracks,
+    /// which are easily confused with each other
+    TrackRemovalConfirmation(Track, TrackRemoval),
+    /// A palette to search and run commands and actions
+    CommandPalette {
+        query: LineInput,
+        entries: Vec<command::PaletteEntry>,
+        list_state: ListState,
+    },
+    /// A list of playlists to merge into a new playlist, preceded by the merge's options
+    PlaylistMerge {
+        playlists: Vec<Playlist>,
+        /// whether each playlist is chosen to be merged
+        chosen: Vec<bool>,
+        order: PlaylistMergeOrder,
+        deduplicate: bool,
+        list_state: ListState,
+    },
+    /// A list of artists and genres to remember the current visualization for
+    #[cfg(feature = "fx")]
+    VisualizationPreferenceTargets(
+        Vec<crate::ui::visualizations::VisualizationPreferenceTarget>,
+        ListState,
+    ),
+}
+
+#[derive(Debug, Clone)]
+/// Where a track is removed from
+pub enum TrackRemoval {
+    /// remove the track from a playlist, specified by its ID and name, keeping the track liked
+    Playlist(PlaylistId<'static>, String),
+    /// unlike the track, keeping it in the playlists
+    Liked,
 }
 
 #[derive(Debug, Clone)]
@@ -45,17 +121,26 @@ pub enum ActionListItem {
 pub enum PlaylistPopupAction {
     Browse {
         folder_id: usize,
-        search_query: String,
+        search_query: LineInput,
     },
     AddTrack {
         folder_id: usize,
         track_id: TrackId<'static>,
-        search_query: String,
+        search_query: LineInput,
+        /// the playlist chosen despite already having the track (or the track being liked),
+        /// to which the track is added upon choosing it again
+        confirmed_playlist: Option<PlaylistId<'static>>,
     },
     AddEpisode {
         folder_id: usize,
         episode_id: EpisodeId<'static>,
-        search_query: String,
+        search_query: LineInput,
+    },
+    /// Compare a playlist with the chosen playlist
+    Diff {
+        folder_id: usize,
+        playlist: Playlist,
+        search_query: LineInput,
     },
 }
 
@@ -76,8 +161,24 @@ impl PopupState {
             | Self::UserSavedAlbumList(list_state)
             | Self::ArtistList(.., list_state)
             | Self::ThemeList(.., list_state)
-            | Self::ActionList(.., list_state) => Some(list_state),
-            Self::Search { .. } | Self::PlaylistCreate { .. } => None,
+            | Self::ActionList(.., list_state)
+            | Self::EpisodeBookmarkList(.., list_state)
+            | Self::TrackRating(.., list_state)
+            | Self::TrackDetails(.., list_state)
+            | Self::SmartPlaylistList(list_state)
+            | Self::LikedTrackGenreList(list_state)
+            | Self::TrackDecadeList { list_state, .. }
+            | Self::CommandPalette { list_state, .. }
+            | Self::PlaylistMerge { list_state, .. } => Some(list_state),
+            #[cfg(feature = "fx")]
+            Self::VisualizationPreferenceTargets(.., list_state) => Some(list_state),
+            Self::Search { .. }
+            | Self::PlaylistCreate { .. }
+            | Self::EpisodeBookmarkCreate { .. }
+            | Self::TrackTagsEdit { .. }
+            | Self::PlaylistRename { .. }
+            | Self::PlaylistDescriptionEdit { .. }
+            | Self::TrackRemovalConfirmation(..) => None,
         }
     }
 
@@ -90,8 +191,50 @@ impl PopupState {
             | Self::UserSavedAlbumList(list_state)
             | Self::ArtistList(.., list_state)
             | Self::ThemeList(.., list_state)
-            | Self::ActionList(.., list_state) => Some(list_state),
-            Self::Search { .. } | Self::PlaylistCreate { .. } => None,
+            | Self::ActionList(.., list_state)
+            | Self::EpisodeBookmarkList(.., list_state)
+            | Self::TrackRating(.., list_state)
+            | Self::TrackDetails(.., list_state)
+            | Self::SmartPlaylistList(list_state)
+            | Self::LikedTrackGenreList(list_state)
+            | Self::TrackDecadeList { list_state, .. }
+            | Self::CommandPalette { list_state, .. }
+            | Self::PlaylistMerge { list_state, .. } => Some(list_state),
+            #[cfg(feature = "fx")]
+            Self::VisualizationPreferenceTargets(.., list_state) => Some(list_state),
+            Self::Search { .. }
+            | Self::PlaylistCreate { .. }
+            | Self::EpisodeBookmarkCreate { .. }
+            | Self::TrackTagsEdit { .. }
+            | Self::PlaylistRename { .. }
+            | Self::PlaylistDescriptionEdit { .. }
+            | Self::TrackRemovalConfirmation(..) => None,
+        }
+    }
+
+    /// gets the (mutable) text input of a popup, in which the user's typed text is entered
+    pub fn line_input_mut(&mut self) -> Option<&mut LineInput> {
+        match self {
+            Self::Search { query } | Self::CommandPalette { query, .. } => Some(query),
+            Self::UserPlaylistList(
+                PlaylistPopupAction::Browse { search_query, .. }
+                | PlaylistPopupAction::AddTrack { search_query, .. }
+                | PlaylistPopupAction::AddEpisode { search_query, .. }
+                | PlaylistPopupAction::Diff { search_query, .. },
+                _,
+            ) => Some(search_query),
+            Self::PlaylistCreate {
+                name,
+                desc,
+                current_field,
+            } => match current_field {
+                PlaylistCreateCurrentField::Name => Some(name),
+                PlaylistCreateCurrentField::Desc => Some(desc),
+            },
+            Self::EpisodeBookmarkCreate { note, .. } => Some(note),
+            Self::TrackTagsEdit { tags, .. } => Some(tags),
+            Self::PlaylistRename { name, .. } => Some(name),
+            _ => None,
         }
     }
 
@@ -143,7 +286,19 @@ impl ActionListItem {
             | ActionListItem::Playlist(.., actions)
             | ActionListItem::Show(.., actions)
             | ActionListItem::Episode(.., actions) => {
-                actions.iter().map(|a| format!("{a:?}")).collect::<Vec<_>>()
+                actions
+                    .iter()
+                    .map(|a| match a {
+                        // distinguish the actions removing a track, which are easily confused
+                        command::Action::DeleteFromPlaylist => {
+                            "DeleteFromPlaylist (only from this playlist)".to_string()
+                        }
+                        command::Action::DeleteFromLiked => {
+                            "DeleteFromLiked (unlike everywhere)".to_string()
+                        }
+                        a => format!("{a:?}"),
+                    })
+                    .collect::<Vec<_>>()
             }
         }
     }
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ArtistFocusState, BrowsePageUIState, ContextPageType, ContextPageUIState, LibraryFocusState,
+    LibraryPageUIState, PageState, SearchPageUIState, SharedPlaylistsFocusState,
+    SharedPlaylistsPageUIState, UIState,
+};
+use crate::{state::model::ContextId, ui::single_line_input::LineInput};
+use ratatui::widgets::{ListState, TableState};
+
+#[derive(Debug, Serialize, Deserialize)]
+/// A page that can be restored in a new session
+enum SessionPage {
+    Library {
+        playlist_folder_id: usize,
+        focus: LibraryFocusState,
+    },
+    /// A context page, specified by the context's URI or `None` for the currently playing context
+    Context {
+        uri: Option<String>,
+        artist_focus: Option<ArtistFocusState>,
+    },
+    Search {
+        query: String,
+    },
+    Browse,
+    Blocklist,
+    ArtistFeed,
+    SharedPlaylists {
+        focus: SharedPlaylistsFocusState,
+    },
+    SavedEpisodes,
+    Queue,
+    CommandHelp,
+    NowPlaying,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// The UI session, which is stored when the application exits and restored when it starts
+pub struct UISession {
+    page: SessionPage,
+    /// the selected position (or the scroll offset) in the page's focused window
+    selected: Option<usize>,
+    /// whether the visualization is enabled and its mode and color scheme
+    #[cfg(feature = "fx")]
+    #[serde(default)]
+    visualization: Option<(bool, crate::ui::visualizations::VisualizationPreference)>,
+}
+
+impl SessionPage {
+    fn from_page(page: &PageState) -> Option<Self> {
+        Some(match page {
+            PageState::Library { state } => Self::Library {
+                playlist_folder_id: state.playlist_folder_id,
+                focus: state.focus,
+            },
+            PageState::Context {
+                context_page_type,
+                state,
+                ..
+            } => {
+                let uri = match context_page_type {
+                    // contexts of tracks only live in the application's memory
+                    ContextPageType::Browsing(ContextId::Tracks(_)) => return None,
+                    ContextPageType::Browsing(id) => Some(id.uri()),
+                    ContextPageType::CurrentPlaying => None,
+                };
+                let artist_focus = match state {
+                    Some(ContextPageUIState::Artist { focus, .. }) => Some(*focus),
+                    _ => None,
+                };
+                Self::Context { uri, artist_focus }
+            }
+            PageState::Search { current_query, .. } => Self::Search {
+                query: current_query.clone(),
+            },
+            PageState::Browse { .. } => Self::Browse,
+            PageState::Blocklist { .. } => Self::Blocklist,
+            PageState::ArtistFeed { .. } => Self::ArtistFeed,
+            PageState::SharedPlaylists { state } => Self::SharedPlaylists { focus: state.focus },
+            PageState::SavedEpisodes { .. } => Self::SavedEpisodes,
+            PageState::Queue { .. } => Self::Queue,
+            PageState::CommandHelp { .. } => Self::CommandHelp,
+            PageState::NowPlaying => Self::NowPlaying,
+            // the lyrics and playlist diff pages depend on data which isn't stored
+            PageState::Lyrics { .. } | PageState::PlaylistDiff { .. } => return None,
+            // plugins are loaded after the session is restored
+            #[cfg(feature = "plugins")]
+            PageState::Plugins { .. } | PageState::PluginPage { .. } => return None,
+        })
+    }
+
+    fn into_page(self) -> Option<PageState> {
+        Some(match self {
+            Self::Library {
+                playlist_folder_id,
+                focus,
+            } => PageState::Library {
+                state: LibraryPageUIState {
+                    focus,
+                    playlist_folder_id,
+                    ..LibraryPageUIState::new()
+                },
+            },
+            Self::Context {
+                uri: None,
+                artist_focus: _,
+            } => PageState::Context {
+                id: None,
+                context_page_type: ContextPageType::CurrentPlaying,
+                state: None,
+            },
+            Self::Context {
+                uri: Some(uri),
+                artist_focus,
+            } => {
+                let id = ContextId::from_uri(&uri)?;
+                // the page's state is initialized along with its context ID,
+                // so that the restored selection isn't reset when the page is first rendered
+                let state = match id {
+                    ContextId::Album(_) => ContextPageUIState::new_album(),
+                    ContextId::Artist(_) => ContextPageUIState::Artist {
+                        top_track_table: TableState::default(),
+                        album_table: TableState::default(),
+                        related_artist_list: ListState::default(),
+                        focus: artist_focus.unwrap_or(ArtistFocusState::TopTracks),
+                    },
+                    ContextId::Playlist(_) => ContextPageUIState::new_playlist(),
+                    ContextId::Show(_) => ContextPageUIState::new_show(),
+                    ContextId::Tracks(_) => return None,
+                };
+                PageState::Context {
+                    id: Some(id.clone()),
+                    context_page_type: ContextPageType::Browsing(id),
+                    state: Some(state),
+                }
+            }
+            Self::Search { query } => PageState::Search {
+                line_input: LineInput::new(query.chars().collect()),
+                current_query: query,
+                state: SearchPageUIState::new(),
+            },
+            Self::Browse => PageState::Browse {
+                state: BrowsePageUIState::CategoryList {
+                    state: ListState::default(),
+                },
+            },
+            Self::Blocklist => PageState::Blocklist {
+                state: ListState::default(),
+            },
+            Self::ArtistFeed => PageState::ArtistFeed {
+                state: ListState::default(),
+            },
+            Self::SharedPlaylists { focus } => PageState::SharedPlaylists {
+                state: SharedPlaylistsPageUIState {
+                    focus,
+                    ..SharedPlaylistsPageUIState::new()
+                },
+            },
+            Self::SavedEpisodes => PageState::SavedEpisodes {
+                state: TableState::default(),
+            },
+            Self::Queue => PageState::Queue { scroll_offset: 0 },
+            Self::CommandHelp => PageState::CommandHelp { scroll_offset: 0 },
+            Self::NowPlaying => PageState::NowPlaying,
+        })
+    }
+}
+
+impl UIState {
+    /// Get the current UI session, which consists of the last page that can be restored
+    /// in the navigation history, its selection and the visualization settings
+    pub fn session(&mut self) -> UISession {
+        let (page, selected) = self
+            .history
+            .iter_mut()
+            .rev()
+            .find_map(|page| Some((SessionPage::from_page(page)?, page.selected())))
+            .unwrap_or((
+                SessionPage::Library {
+                    playlist_folder_id: 0,
+                    focus: LibraryFocusState::Playlists,
+                },
+                None,
+            ));
+
+        UISession {
+            page,
+            selected,
+            #[cfg(feature = "fx")]
+            visualization: Some((
+                self.enable_visualization,
+                self.visualization_fallback.unwrap_or(self.visualization),
+            )),
+        }
+    }
+
+    /// Restore a UI session stored in a previous run of the application
+    pub fn restore_session(&mut self, session: UISession) {
+        #[cfg(feature = "fx")]
+        if let Some((enable_visualization, visualization)) = session.visualization {
+            self.enable_visualization = enable_visualization
+                && !crate::config::get_config()
+                    .app_config
+                    .accessibility
+                    .reduce_motion;
+            self.visualization = visualization;
+        }
+
+        let Some(mut page) = session.page.into_page() else {
+            return;
+        };
+        if let Some(selected) = session.selected {
+            page.select(selected);
+        }
+        // the library page is always the first page in the navigation history
+        if matches!(page, PageState::Library { .. }) {
+            self.history = vec![page];
+        } else {
+            self.new_page(page);
+        }
+    }
+}
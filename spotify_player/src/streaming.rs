@@ -4,17 +4,27 @@ use librespot_connect::{ConnectConfig, Spirc};
 use librespot_core::authentication::Credentials;
 use librespot_core::config::DeviceType;
 use librespot_core::{spotify_uri, Session, SpotifyUri};
+use librespot_metadata::audio::{AudioFileFormat, AudioItem};
 use librespot_playback::mixer::MixerConfig;
 use librespot_playback::{
-    audio_backend,
+    audio_backend::{self, Sink, SinkResult},
     config::{AudioFormat, Bitrate, PlayerConfig},
-    mixer::{self, Mixer},
-    player,
+    convert::Converter,
+    decoder::AudioPacket,
+    mixer::{self, Mixer, VolumeGetter},
+    player, SAMPLES_PER_SECOND,
 };
+use parking_lot::Mutex;
 use rspotify::model::{EpisodeId, Id, PlayableId, TrackId};
 use serde::Serialize;
 use std::sync::Arc;
 
+/// the amplitude (-60 dBFS) under which the audio is considered silent when trimming silence
+const SILENCE_THRESHOLD: f64 = 0.001;
+/// the maximum duration (in seconds) of the silence trimmed at a track's start or end,
+/// longer silence is played as is
+const MAX_TRIMMED_SILENCE_SECS: usize = 5;
+
 #[cfg(not(any(
     feature = "rodio-backend",
     feature = "alsa-backend",
@@ -138,6 +148,56 @@ fn execute_player_event_hook_command(
     Ok(())
 }
 
+/// Get the format (codec and bitrate) in which the player streams an audio item.
+///
+/// Like `librespot`'s player, the format of the given bitrate is preferred, falling back to
+/// the nearest available bitrate, and Ogg Vorbis is preferred over MP3.
+fn stream_quality(audio_item: &AudioItem, bitrate: Bitrate) -> Option<String> {
+    let formats = match bitrate {
+        Bitrate::Bitrate96 => [
+            AudioFileFormat::OGG_VORBIS_96,
+            AudioFileFormat::MP3_96,
+            AudioFileFormat::OGG_VORBIS_160,
+            AudioFileFormat::MP3_160,
+            AudioFileFormat::MP3_256,
+            AudioFileFormat::OGG_VORBIS_320,
+            AudioFileFormat::MP3_320,
+        ],
+        Bitrate::Bitrate160 => [
+            AudioFileFormat::OGG_VORBIS_160,
+            AudioFileFormat::MP3_160,
+            AudioFileFormat::OGG_VORBIS_96,
+            AudioFileFormat::MP3_96,
+            AudioFileFormat::MP3_256,
+            AudioFileFormat::OGG_VORBIS_320,
+            AudioFileFormat::MP3_320,
+        ],
+        Bitrate::Bitrate320 => [
+            AudioFileFormat::OGG_VORBIS_320,
+            AudioFileFormat::MP3_320,
+            AudioFileFormat::MP3_256,
+            AudioFileFormat::OGG_VORBIS_160,
+            AudioFileFormat::MP3_160,
+            AudioFileFormat::OGG_VORBIS_96,
+            AudioFileFormat::MP3_96,
+        ],
+    };
+
+    let format = formats
+        .into_iter()
+        .find(|format| audio_item.files.contains_key(format))?;
+    let (codec, kbps) = match format {
+        AudioFileFormat::OGG_VORBIS_96 => ("Ogg Vorbis", 96),
+        AudioFileFormat::OGG_VORBIS_160 => ("Ogg Vorbis", 160),
+        AudioFileFormat::OGG_VORBIS_320 => ("Ogg Vorbis", 320),
+        AudioFileFormat::MP3_96 => ("MP3", 96),
+        AudioFileFormat::MP3_160 => ("MP3", 160),
+        AudioFileFormat::MP3_256 => ("MP3", 256),
+        _ => ("MP3", 320),
+    };
+    Some(format!("{codec} {kbps} kbps"))
+}
+
 /// Create a new streaming connection
 pub async fn new_connection(
     client: AppClient,
@@ -173,12 +233,15 @@ pub async fn new_connection(
     mixer.set_volume(volume);
 
     let backend = audio_backend::find(None).expect("should be able to find an audio backend");
+    let bitrate = state
+        .player
+        .read()
+        .streaming_bitrate
+        .to_string()
+        .parse::<Bitrate>()
+        .unwrap_or_default();
     let player_config = PlayerConfig {
-        bitrate: device
-            .bitrate
-            .to_string()
-            .parse::<Bitrate>()
-            .unwrap_or_default(),
+        bitrate,
         normalisation: device.normalization,
         ..Default::default()
     };
@@ -188,17 +251,44 @@ pub async fn new_connection(
         session.device_id()
     );
 
-    let player = player::Player::new(
-        player_config,
-        session.clone(),
-        mixer.get_soft_volume(),
-        move || backend(None, AudioFormat::default()),
-    );
+    let trimmed_player_events = Arc::new(Mutex::new(None));
+    let player = player::Player::new(player_config, session.clone(), mixer.get_soft_volume(), {
+        let trim_silence = device.trim_silence;
+        let volume = mixer.get_soft_volume();
+        let player_events = trimmed_player_events.clone();
+        move || {
+            let sink = backend(None, AudioFormat::default());
+            if trim_silence {
+                Box::new(SilenceTrimmingSink::new(sink, volume, player_events))
+            } else {
+                sink
+            }
+        }
+    });
+    if device.trim_silence {
+        *trimmed_player_events.lock() = Some(player.get_player_event_channel());
+    }
 
     let player_event_task = tokio::task::spawn({
         let mut channel = player.get_player_event_channel();
         async move {
             while let Some(event) = channel.recv().await {
+                match &event {
+                    player::PlayerEvent::TrackChanged { audio_item } => {
+                        state.player.write().stream_quality = stream_quality(audio_item, bitrate);
+                    }
+                    player::PlayerEvent::Loading { .. } => state.playback_stats.start_buffering(),
+                    player::PlayerEvent::Playing { .. }
+                    | player::PlayerEvent::Paused { .. }
+                    | player::PlayerEvent::Stopped { .. } => state.playback_stats.end_buffering(),
+                    // the player corrects its reported position when the playback lags behind,
+                    // e.g. because the audio couldn't be streamed or decoded fast enough
+                    player::PlayerEvent::PositionCorrection { position_ms, .. } => {
+                        tracing::warn!("Playback fell behind at position_ms={position_ms}");
+                        state.playback_stats.record_underrun();
+                    }
+                    _ => {}
+                }
                 match PlayerEvent::from_librespot_player_event(event) {
                     Err(err) => {
                         tracing::warn!("Failed to convert a `librespot` player event into `spotify_player` player event: {err:#}");
@@ -254,3 +344,113 @@ pub async fn new_connection(
 
     Ok(spirc)
 }
+
+/// An audio sink trimming the near-silence at the start and the end of tracks,
+/// which tightens the transitions between tracks, e.g. of live albums
+struct SilenceTrimmingSink {
+    sink: Box<dyn Sink>,
+    volume: Box<dyn VolumeGetter + Send>,
+    /// the player's events used to find the tracks' boundaries in the written audio, which are
+    /// set after the player is initialized. Because the events are sent from the player's thread,
+    /// which also writes the audio, the events received before a write happened before its audio.
+    player_events: Arc<Mutex<Option<player::PlayerEventChannel>>>,
+    /// whether no non-silent audio has been written since a track started
+    is_track_start: bool,
+    /// the silent audio held back from the inner sink, which is dropped if it turns out to be
+    /// the silence at a track's start or end
+    held_silence: Vec<AudioPacket>,
+    n_held_samples: usize,
+}
+
+impl SilenceTrimmingSink {
+    fn new(
+        sink: Box<dyn Sink>,
+        volume: Box<dyn VolumeGetter + Send>,
+        player_events: Arc<Mutex<Option<player::PlayerEventChannel>>>,
+    ) -> Self {
+        Self {
+            sink,
+            volume,
+            player_events,
+            is_track_start: true,
+            held_silence: Vec::new(),
+            n_held_samples: 0,
+        }
+    }
+
+    fn handle_player_events(&mut self) {
+        let player_events = self.player_events.clone();
+        let mut player_events = player_events.lock();
+        let Some(player_events) = player_events.as_mut() else {
+            return;
+        };
+        while let Ok(event) = player_events.try_recv() {
+            match event {
+                // the held silence is the end of the previous track
+                player::PlayerEvent::TrackChanged { .. } => {
+                    self.drop_held_silence();
+                    self.is_track_start = true;
+                }
+                // the held silence is before the seeked position
+                player::PlayerEvent::Seeked { .. } => {
+                    self.drop_held_silence();
+                    self.is_track_start = false;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn drop_held_silence(&mut self) {
+        self.held_silence.clear();
+        self.n_held_samples = 0;
+    }
+
+    /// Write the held silence into the inner sink
+    fn flush_held_silence(&mut self, converter: &mut Converter) -> SinkResult<()> {
+        self.n_held_samples = 0;
+        for packet in std::mem::take(&mut self.held_silence) {
+            self.sink.write(packet, converter)?;
+        }
+        Ok(())
+    }
+}
+
+impl Sink for SilenceTrimmingSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        self.handle_player_events();
+
+        let AudioPacket::Samples(samples) = &packet else {
+            self.flush_held_silence(converter)?;
+            return self.sink.write(packet, converter);
+        };
+
+        // the written samples are already attenuated by the player's volume
+        let threshold = SILENCE_THRESHOLD * self.volume.attenuation_factor();
+        if samples.iter().any(|s| s.abs() > threshold) {
+            if self.is_track_start {
+                self.drop_held_silence();
+                self.is_track_start = false;
+            } else {
+                self.flush_held_silence(converter)?;
+            }
+            return self.sink.write(packet, converter);
+        }
+
+        self.n_held_samples += samples.len();
+        self.held_silence.push(packet);
+        if self.n_held_samples > MAX_TRIMMED_SILENCE_SECS * SAMPLES_PER_SECOND as usize {
+            self.is_track_start = false;
+            self.flush_held_silence(converter)?;
+        }
+        Ok(())
+    }
+}
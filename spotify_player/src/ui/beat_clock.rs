@@ -0,0 +1,71 @@
+//! A beat clock driving the visualization's animations.
+//!
+//! Instead of counting beats from the wall clock, the clock is phase-locked to the playback's
+//! position (and the track's detected beats, if its audio analysis is available), so that
+//! animations stay on-beat after seeks and pauses.
+
+use std::time::{Duration, Instant};
+
+/// The drift (in beats) from the playback's position above which the clock jumps to the position
+/// instead of smoothly catching up with it, e.g. after a seek
+const MAX_DRIFT_BEATS: f64 = 1.0;
+/// The fraction of the drift corrected on each tick
+const DRIFT_CORRECTION: f64 = 0.1;
+
+#[derive(Debug)]
+/// A clock counting the visualization's beats, phase-locked to the playback's position
+pub struct BeatClock {
+    n_beats: f64,
+    /// the tempo (in beats per second) of the last tick
+    rate: f64,
+    last_tick: Option<Instant>,
+}
+
+impl Default for BeatClock {
+    fn default() -> Self {
+        Self {
+            n_beats: 0.0,
+            rate: 2.0,
+            last_tick: None,
+        }
+    }
+}
+
+impl BeatClock {
+    /// Advance the clock towards the number of beats (`target`) at the playback's position.
+    ///
+    /// Between two ticks, the clock advances at `rate` beats per second while playing and stops while paused.
+    /// A small drift from the target is corrected gradually to keep the animations smooth,
+    /// a large one (e.g. after a seek) is corrected at once.
+    pub fn tick(&mut self, target: f64, rate: f64, is_playing: bool, now: Instant) -> f64 {
+        if let Some(last_tick) = self.last_tick {
+            if is_playing {
+                self.n_beats += (now - last_tick).as_secs_f64() * rate;
+            }
+        } else {
+            self.n_beats = target;
+        }
+        self.last_tick = Some(now);
+        self.rate = rate;
+
+        let drift = target - self.n_beats;
+        if drift.abs() > MAX_DRIFT_BEATS {
+            self.n_beats = target;
+        } else {
+            self.n_beats += drift * DRIFT_CORRECTION;
+        }
+        self.n_beats
+    }
+
+    /// The tempo (in BPM) of the last tick
+    pub fn bpm(&self) -> f64 {
+        self.rate * 60.0
+    }
+
+    /// The time at which the first beat would have happened at the current tempo
+    pub fn beat_origin(&self, now: Instant) -> Instant {
+        let now = self.last_tick.unwrap_or(now);
+        let elapsed = Duration::from_secs_f64((self.n_beats / self.rate).max(0.0));
+        now.checked_sub(elapsed).unwrap_or(now)
+    }
+}
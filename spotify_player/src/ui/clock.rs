@@ -0,0 +1,67 @@
+//! The clock of the UI's animations.
+//!
+//! Animations read the time from the UI state's clock instead of `Instant::now`,
+//! so that they can be rendered deterministically, e.g. in snapshot tests or for reproducible recordings.
+//! A deterministic clock starts at a given timestamp and only advances when it's stepped,
+//! and its seed selects the visualizations' pseudo-random patterns.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Clock {
+    /// the system's clock
+    #[default]
+    System,
+    /// a clock advancing only when it's stepped
+    Deterministic {
+        /// the instant of the clock's zero timestamp
+        origin: Instant,
+        /// the time elapsed since the clock's zero timestamp
+        timestamp: Duration,
+        seed: u64,
+    },
+}
+
+impl Clock {
+    /// Construct a deterministic clock starting at `timestamp`
+    pub fn deterministic(seed: u64, timestamp: Duration) -> Self {
+        Self::Deterministic {
+            origin: Instant::now(),
+            timestamp,
+            seed,
+        }
+    }
+
+    /// Get the current instant
+    pub fn now(&self) -> Instant {
+        match self {
+            Self::System => Instant::now(),
+            Self::Deterministic {
+                origin, timestamp, ..
+            } => *origin + *timestamp,
+        }
+    }
+
+    /// Get the time elapsed since the clock's zero timestamp, if the clock is deterministic
+    pub fn timestamp(&self) -> Option<Duration> {
+        match self {
+            Self::System => None,
+            Self::Deterministic { timestamp, .. } => Some(*timestamp),
+        }
+    }
+
+    /// Get the seed of the visualizations' pseudo-random patterns
+    pub fn seed(&self) -> u64 {
+        match self {
+            Self::System => 0,
+            Self::Deterministic { seed, .. } => *seed,
+        }
+    }
+
+    /// Step a deterministic clock forward, the system's clock advances on its own
+    pub fn advance(&mut self, step: Duration) {
+        if let Self::Deterministic { timestamp, .. } = self {
+            *timestamp += step;
+        }
+    }
+}
@@ -1,9 +1,15 @@
 //! UI effects and animations
 
+/// The duration of the playback window's animation on track change
+#[cfg(feature = "fx")]
+pub const TRACK_CHANGE_DURATION: std::time::Duration = std::time::Duration::from_millis(800);
+
 #[cfg(feature = "fx")]
 #[derive(Debug)]
 pub struct EffectsState {
     pub last_update: std::time::Instant,
+    /// the start of the playback window's animation played when a track changes
+    pub track_change_start: Option<std::time::Instant>,
 }
 
 #[cfg(feature = "fx")]
@@ -11,6 +17,7 @@ impl Default for EffectsState {
     fn default() -> Self {
         Self {
             last_update: std::time::Instant::now(),
+            track_change_start: None,
         }
     }
 }
@@ -18,8 +25,79 @@ impl Default for EffectsState {
 #[cfg(feature = "fx")]
 impl EffectsState {
     /// Get elapsed time since last update
-    pub fn elapsed(&self) -> std::time::Duration {
-        std::time::Instant::now().duration_since(self.last_update)
+    pub fn elapsed(&self, now: std::time::Instant) -> std::time::Duration {
+        now.saturating_duration_since(self.last_update)
+    }
+
+    /// Start the playback window's track change animation
+    pub fn start_track_change(&mut self, now: std::time::Instant) {
+        self.track_change_start = Some(now);
+    }
+
+    /// Get the eased progress (from 0 to 1) of the ongoing track change animation, if any
+    pub fn track_change_progress(&self, now: std::time::Instant) -> Option<f64> {
+        let start = self.track_change_start?;
+        let t = now.saturating_duration_since(start).as_secs_f64()
+            / TRACK_CHANGE_DURATION.as_secs_f64();
+        (t < 1.0).then(|| ease_out_cubic(t))
+    }
+}
+
+/// A cubic easing curve, which starts fast and slows down towards its end
+#[cfg(feature = "fx")]
+fn ease_out_cubic(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Sweep a text in from the left: the characters after the revealed part are blanked
+/// (rather than removed) so that the text after it doesn't move during the animation
+#[cfg(feature = "fx")]
+pub fn sweep_in(text: &str, progress: f64) -> String {
+    let n_chars = text.chars().count();
+    let n_revealed = (n_chars as f64 * progress).ceil() as usize;
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| if i < n_revealed { c } else { ' ' })
+        .collect()
+}
+
+/// Flash the border of the given area, fading from the flash color to the border's color
+#[cfg(feature = "fx")]
+pub fn flash_border(
+    buf: &mut ratatui::buffer::Buffer,
+    rect: ratatui::layout::Rect,
+    flash: ratatui::style::Color,
+    border: Option<ratatui::style::Color>,
+    progress: f64,
+) {
+    use ratatui::style::Color;
+
+    let color = match (flash, border) {
+        (Color::Rgb(r1, g1, b1), Some(Color::Rgb(r2, g2, b2))) => {
+            let lerp =
+                |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * progress) as u8;
+            Color::Rgb(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2))
+        }
+        // colors that can't be blended are switched halfway through the animation
+        (_, border) if progress >= 0.5 => match border {
+            Some(border) => border,
+            None => return,
+        },
+        _ => flash,
+    };
+
+    for x in rect.left()..rect.right() {
+        for y in rect.top()..rect.bottom() {
+            let is_border = x == rect.left()
+                || x + 1 == rect.right()
+                || y == rect.top()
+                || y + 1 == rect.bottom();
+            if is_border {
+                if let Some(cell) = buf.cell_mut((x, y)) {
+                    cell.set_fg(color);
+                }
+            }
+        }
     }
 }
 
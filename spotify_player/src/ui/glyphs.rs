@@ -0,0 +1,64 @@
+//! Glyph sets used to draw the application's widgets and visualizations.
+//!
+//! The unicode glyph set relies on the terminal font having block, Braille and geometric
+//! characters. The ASCII glyph set is a fallback for limited fonts,
+//! enabled by the `accessibility.ascii_only` option or for terminals without a UTF-8 locale.
+
+/// The characters used to draw widgets and visualizations
+pub struct GlyphSet {
+    /// a point of the wave visualization's line
+    pub wave: char,
+    /// a fully filled cell of a spectrum bar
+    pub bar_full: char,
+    /// the partially filled cells of a spectrum bar, indexed by eighths of the filled height
+    pub bar_partial: [char; 8],
+    /// the particles of the particles visualization, from the brightest to the dimmest
+    pub particles: [char; 3],
+    /// the cells of the shader visualization, from the dimmest to the brightest
+    pub shades: [char; 5],
+    /// the separator between an item's name and its artists
+    pub separator: &'static str,
+    /// the marker of an unread feed item
+    pub unread_marker: &'static str,
+    /// the separator between two breadcrumbs, which should be 3 characters wide
+    pub breadcrumb_separator: &'static str,
+    /// the compact mode's playback controls (previous, play/pause, next),
+    /// each of which is a single character followed by a space
+    pub controls: &'static str,
+    /// the beat grid's ticks on an empty, a line and a filled cell of the progress bar
+    pub beat_ticks: [&'static str; 3],
+    /// whether the cover art is drawn with upper half blocks showing two pixels per cell,
+    /// instead of background colored spaces showing one
+    #[cfg(feature = "image")]
+    pub half_blocks: bool,
+}
+
+pub const UNICODE: GlyphSet = GlyphSet {
+    wave: '█',
+    bar_full: '█',
+    bar_partial: [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇'],
+    particles: ['*', '•', '·'],
+    shades: [' ', '░', '▒', '▓', '█'],
+    separator: "•",
+    unread_marker: "●",
+    breadcrumb_separator: " ▸ ",
+    controls: "⏮ ⏯ ⏭",
+    beat_ticks: ["│", "┼", "┿"],
+    #[cfg(feature = "image")]
+    half_blocks: true,
+};
+
+pub const ASCII: GlyphSet = GlyphSet {
+    wave: '*',
+    bar_full: '#',
+    bar_partial: [' ', ' ', '.', '.', 'o', 'o', '#', '#'],
+    particles: ['*', 'o', '.'],
+    shades: [' ', '.', ':', '*', '#'],
+    separator: "-",
+    unread_marker: "*",
+    breadcrumb_separator: " > ",
+    controls: "< = >",
+    beat_ticks: ["|", "+", "+"],
+    #[cfg(feature = "image")]
+    half_blocks: false,
+};
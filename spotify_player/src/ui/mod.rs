@@ -14,7 +14,7 @@ use ratatui::{
     text::{Line, Span, Text},
     widgets::{
         Block, BorderType, Borders, Cell, Gauge, LineGauge, List, ListItem, ListState, Paragraph,
-        Row, Table, TableState, Wrap,
+        Row, Table, TableState, Tabs, Wrap,
     },
     Frame,
 };
@@ -24,15 +24,26 @@ use crate::state::ImageRenderInfo;
 
 type Terminal = ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>;
 
+pub mod glyphs;
+pub mod multi_line_input;
 mod page;
-mod playback;
+pub mod playback;
 mod popup;
 pub mod single_line_input;
 pub mod utils;
 
+#[cfg(feature = "fx")]
+pub mod beat_clock;
+
+#[cfg(feature = "fx")]
+pub mod clock;
+
 #[cfg(feature = "fx")]
 pub mod effects;
 
+#[cfg(feature = "fx")]
+pub mod shader;
+
 #[cfg(feature = "fx")]
 pub mod visualizations;
 
@@ -40,17 +51,38 @@ pub mod visualizations;
 pub fn run(state: &SharedState) -> Result<()> {
     let mut terminal = init_ui().context("failed to initialize the application's UI")?;
 
-    let ui_refresh_duration = std::time::Duration::from_millis(
-        config::get_config().app_config.app_refresh_duration_in_ms,
-    );
+    let configs = config::get_config();
+    let app_refresh_duration =
+        std::time::Duration::from_millis(configs.app_config.app_refresh_duration_in_ms);
     let mut last_terminal_size = None;
+    let mut was_terminal_suspended = false;
 
     loop {
+        // the UI (including its visualization) is refreshed less frequently in the low-power mode
+        let ui_refresh_duration = if state.is_low_power() {
+            app_refresh_duration.max(crate::power::LOW_POWER_APP_REFRESH_DURATION)
+        } else {
+            app_refresh_duration
+        };
+
         {
             let mut ui = state.ui.lock();
+            if ui.is_terminal_suspended {
+                was_terminal_suspended = true;
+                drop(ui);
+                std::thread::sleep(ui_refresh_duration);
+                continue;
+            }
+            if was_terminal_suspended {
+                // the whole terminal needs to be redrawn after another program used it
+                was_terminal_suspended = false;
+                terminal.clear()?;
+            }
+
             if !ui.is_running {
-                clean_up(terminal).context("clean up UI resources")?;
-                std::process::exit(0);
+                // the terminal is restored at the end of the application's shutdown,
+                // see `shutdown::start_shutdown_watcher`
+                return Ok(());
             }
 
             let terminal_size = terminal.size()?;
@@ -63,16 +95,13 @@ pub fn run(state: &SharedState) -> Result<()> {
                 }
             }
 
-            if let Err(err) = terminal.draw(|frame| {
-                // set the background and foreground colors for the application
-                let rect = frame.area();
-                let block = Block::default().style(ui.theme.app());
-                frame.render_widget(block, rect);
-
-                render_application(frame, state, &mut ui, rect);
-            }) {
+            let start = std::time::Instant::now();
+            if let Err(err) = terminal.draw(|frame| render(frame, state, &mut ui)) {
                 tracing::error!("Failed to render the application: {err:#}");
             }
+            #[cfg(feature = "fx")]
+            ui.clock.advance(ui_refresh_duration);
+            ui.render_stats.record_frame(start.elapsed());
         }
 
         std::thread::sleep(ui_refresh_duration);
@@ -86,29 +115,96 @@ fn init_ui() -> Result<Terminal> {
     crossterm::execute!(
         stdout,
         crossterm::terminal::EnterAlternateScreen,
-        crossterm::event::EnableMouseCapture
+        crossterm::event::EnableBracketedPaste
     )?;
+    if crate::capabilities::get().mouse {
+        crossterm::execute!(stdout, crossterm::event::EnableMouseCapture)?;
+    }
     let backend = ratatui::backend::CrosstermBackend::new(stdout);
     let mut terminal = ratatui::Terminal::new(backend)?;
     terminal.clear()?;
     Ok(terminal)
 }
 
-/// Clean up UI resources before quitting the application
-fn clean_up(mut terminal: Terminal) -> Result<()> {
+/// Hand over the terminal to another program (e.g. an editor or the shell when quitting)
+/// by restoring its normal mode
+pub fn suspend_terminal() -> Result<()> {
     crossterm::terminal::disable_raw_mode()?;
     crossterm::execute!(
-        terminal.backend_mut(),
+        std::io::stdout(),
         crossterm::terminal::LeaveAlternateScreen,
         crossterm::event::DisableMouseCapture,
+        crossterm::event::DisableBracketedPaste,
+        crossterm::cursor::Show,
+    )?;
+    Ok(())
+}
+
+/// Take back the terminal from another program, see `suspend_terminal`
+pub fn resume_terminal() -> Result<()> {
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(
+        std::io::stdout(),
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableBracketedPaste,
+        crossterm::cursor::Hide,
     )?;
-    terminal.show_cursor()?;
+    if crate::capabilities::get().mouse {
+        crossterm::execute!(std::io::stdout(), crossterm::event::EnableMouseCapture)?;
+    }
     Ok(())
 }
 
+/// Render a frame of the application
+pub fn render(frame: &mut Frame, state: &SharedState, ui: &mut UIStateGuard) {
+    let configs = config::get_config();
+
+    // set the background and foreground colors for the application
+    let rect = frame.area();
+    let block = Block::default().style(ui.theme.app());
+    frame.render_widget(block, rect);
+
+    // the compact mode's controls are only clickable in frames rendering them
+    ui.compact_controls_rect = Rect::default();
+    if state.is_compact || rect.height < configs.app_config.layout.compact_mode_height_threshold {
+        playback::render_compact_playback_window(frame, state, ui, rect);
+    } else {
+        render_application(frame, state, ui, rect);
+    }
+
+    if ui.show_performance_overlay {
+        popup::render_performance_overlay(frame, state, ui, rect);
+    }
+    popup::render_toast(frame, ui, rect);
+
+    let accessibility = &configs.app_config.accessibility;
+    if accessibility.high_contrast {
+        utils::increase_contrast(frame.buffer_mut(), ui.is_light_background);
+    }
+    if accessibility.ascii_only {
+        utils::replace_unicode_symbols(frame.buffer_mut());
+    }
+    if let Some(color_blindness) = ui.color_blindness_simulation {
+        utils::simulate_color_blindness(frame.buffer_mut(), color_blindness);
+    }
+    if !crate::capabilities::get().truecolor {
+        utils::reduce_to_256_colors(frame.buffer_mut());
+    }
+}
+
 /// Render the application
 fn render_application(frame: &mut Frame, state: &SharedState, ui: &mut UIStateGuard, rect: Rect) {
-    // rendering order: playback window -> shortcut help popup -> other popups -> main layout
+    // rendering order: track announcement -> status bar -> playback window -> shortcut help popup -> other popups -> main layout
+
+    let rect = playback::render_track_announcement(frame, state, ui, rect);
+    let rect = playback::render_status_bar(frame, state, ui, rect);
+
+    // the now playing page takes the whole screen
+    if ui.current_page().page_type() == PageType::NowPlaying {
+        let (rect, _) = popup::render_popup(frame, state, ui, rect);
+        page::render_now_playing_page(frame, state, ui, rect);
+        return;
+    }
 
     // render playback window before other popups and windows to ensure nothing is rendered on top
     // of the playback window, which is to avoid "duplicated images" issue
@@ -130,15 +226,45 @@ fn render_main_layout(
     ui: &mut UIStateGuard,
     rect: Rect,
 ) {
+    ui.breadcrumb_rects.clear();
+
+    // render the tab bar if there are multiple tabs
+    let rect = if ui.tabs.len() > 1 {
+        let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(0)]).split(rect);
+        let tabs = Tabs::new(ui.tab_titles())
+            .select(ui.current_tab)
+            .highlight_style(ui.theme.selection(true));
+        frame.render_widget(tabs, chunks[0]);
+        chunks[1]
+    } else {
+        rect
+    };
+
     let page_type = ui.current_page().page_type();
     match page_type {
         PageType::Library => page::render_library_page(is_active, frame, state, ui, rect),
         PageType::Search => page::render_search_page(is_active, frame, state, ui, rect),
         PageType::Context => page::render_context_page(is_active, frame, state, ui, rect),
         PageType::Browse => page::render_browse_page(is_active, frame, state, ui, rect),
+        PageType::Blocklist => page::render_blocklist_page(is_active, frame, state, ui, rect),
+        PageType::ArtistFeed => page::render_artist_feed_page(is_active, frame, state, ui, rect),
+        PageType::SharedPlaylists => {
+            page::render_shared_playlists_page(is_active, frame, state, ui, rect);
+        }
+        PageType::SavedEpisodes => {
+            page::render_saved_episodes_page(is_active, frame, state, ui, rect);
+        }
         PageType::Lyrics => page::render_lyrics_page(is_active, frame, state, ui, rect),
         PageType::Queue => page::render_queue_page(frame, state, ui, rect),
         PageType::CommandHelp => page::render_commands_help_page(frame, ui, rect),
+        PageType::NowPlaying => page::render_now_playing_page(frame, state, ui, rect),
+        PageType::PlaylistDiff => {
+            page::render_playlist_diff_page(is_active, frame, state, ui, rect);
+        }
+        #[cfg(feature = "plugins")]
+        PageType::Plugins => page::render_plugins_page(is_active, frame, ui, rect),
+        #[cfg(feature = "plugins")]
+        PageType::PluginPage => page::render_plugin_page(frame, ui, rect),
     }
 }
 
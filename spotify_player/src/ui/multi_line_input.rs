@@ -0,0 +1,180 @@
+use crossterm::event::KeyCode;
+use ratatui::widgets::{Widget, Wrap};
+
+use super::{single_line_input::InputEffect, Line, Modifier, Paragraph, Span, Style};
+use crate::key::Key;
+
+#[derive(Debug, Clone)]
+/// A text input spanning multiple lines, in which `enter` inserts a line break
+pub struct MultiLineInput {
+    lines: Vec<Vec<char>>,
+    /// the cursor's line
+    row: usize,
+    /// the cursor's position in its line
+    col: usize,
+}
+
+impl MultiLineInput {
+    pub fn new(text: &str) -> Self {
+        let lines = text
+            .split('\n')
+            .map(|l| l.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let row = lines.len() - 1;
+        Self {
+            col: lines[row].len(),
+            row,
+            lines,
+        }
+    }
+
+    /// Handle a key pressed when the input is focused
+    pub fn input(&mut self, key: &Key) -> Option<InputEffect> {
+        let Key::None(c) = key else {
+            return None;
+        };
+        match c {
+            KeyCode::Char(c) => {
+                self.lines[self.row].insert(self.col, *c);
+                self.col += 1;
+                Some(InputEffect::TextChanged)
+            }
+            KeyCode::Enter => {
+                let rest = self.lines[self.row].split_off(self.col);
+                self.row += 1;
+                self.col = 0;
+                self.lines.insert(self.row, rest);
+                Some(InputEffect::TextChanged)
+            }
+            KeyCode::Backspace => {
+                if self.col > 0 {
+                    self.col -= 1;
+                    self.lines[self.row].remove(self.col);
+                } else if self.row > 0 {
+                    // join the line with the previous line
+                    let line = self.lines.remove(self.row);
+                    self.row -= 1;
+                    self.col = self.lines[self.row].len();
+                    self.lines[self.row].extend(line);
+                } else {
+                    return Some(InputEffect::Ack);
+                }
+                Some(InputEffect::TextChanged)
+            }
+            KeyCode::Delete => {
+                if self.col < self.lines[self.row].len() {
+                    self.lines[self.row].remove(self.col);
+                } else if self.row + 1 < self.lines.len() {
+                    // join the next line with the line
+                    let line = self.lines.remove(self.row + 1);
+                    self.lines[self.row].extend(line);
+                } else {
+                    return Some(InputEffect::Ack);
+                }
+                Some(InputEffect::TextChanged)
+            }
+            KeyCode::Left => {
+                if self.col > 0 {
+                    self.col -= 1;
+                } else if self.row > 0 {
+                    self.row -= 1;
+                    self.col = self.lines[self.row].len();
+                } else {
+                    return Some(InputEffect::Ack);
+                }
+                Some(InputEffect::CursorMoved)
+            }
+            KeyCode::Right => {
+                if self.col < self.lines[self.row].len() {
+                    self.col += 1;
+                } else if self.row + 1 < self.lines.len() {
+                    self.row += 1;
+                    self.col = 0;
+                } else {
+                    return Some(InputEffect::Ack);
+                }
+                Some(InputEffect::CursorMoved)
+            }
+            KeyCode::Up => Some(self.move_to_row(self.row.checked_sub(1))),
+            KeyCode::Down => {
+                Some(self.move_to_row(Some(self.row + 1).filter(|row| *row < self.lines.len())))
+            }
+            KeyCode::Home => {
+                self.col = 0;
+                Some(InputEffect::CursorMoved)
+            }
+            KeyCode::End => {
+                self.col = self.lines[self.row].len();
+                Some(InputEffect::CursorMoved)
+            }
+            _ => None,
+        }
+    }
+
+    /// Move the cursor to another line (if any), keeping its position in the line if possible
+    fn move_to_row(&mut self, row: Option<usize>) -> InputEffect {
+        match row {
+            None => InputEffect::Ack,
+            Some(row) => {
+                self.row = row;
+                self.col = self.col.min(self.lines[row].len());
+                InputEffect::CursorMoved
+            }
+        }
+    }
+
+    /// Insert a text (e.g. a pasted text) at the cursor
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars().filter(|c| *c != '\r') {
+            let key = match c {
+                '\n' => KeyCode::Enter,
+                c => KeyCode::Char(c),
+            };
+            self.input(&Key::None(key));
+        }
+    }
+
+    /// Get the number of characters in the text, excluding line breaks
+    pub fn n_chars(&self) -> usize {
+        self.lines.iter().map(Vec::len).sum()
+    }
+
+    pub fn get_text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|l| l.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn widget(&self, is_active: bool) -> impl Widget {
+        let cursor_style = Style::default().add_modifier(Modifier::REVERSED);
+        let lines = self
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(row, line)| {
+                if !is_active || row != self.row {
+                    return Line::from(line.iter().collect::<String>());
+                }
+
+                let before_cursor: String = line[..self.col].iter().collect();
+                let (cursor, after_cursor) = if self.col == line.len() {
+                    (" ".to_string(), String::new())
+                } else {
+                    (
+                        line[self.col].to_string(),
+                        line[self.col + 1..].iter().collect(),
+                    )
+                };
+                Line::from(vec![
+                    Span::raw(before_cursor),
+                    Span::styled(cursor, cursor_style),
+                    Span::raw(after_cursor),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        Paragraph::new(lines).wrap(Wrap { trim: false })
+    }
+}
@@ -1,29 +1,39 @@
+use crate::ui::utils::glyphs;
 use std::{
     collections::{btree_map::Entry, BTreeMap},
     fmt::Display,
+    sync::LazyLock,
 };
 
 use chrono_humanize::HumanTime;
-use ratatui::text::Line;
+use figlet_rs::FIGfont;
+use ratatui::text::{Line, Text};
 
-use crate::{state::Episode, utils::format_duration};
+use crate::{
+    state::{Episode, ResultPage, SearchType},
+    utils::{format_count, format_duration},
+};
 
 use super::{
     config, utils, utils::construct_and_render_block, Album, Artist, ArtistFocusState, Borders,
     BrowsePageUIState, Cell, Constraint, Context, ContextPageUIState, DataReadGuard, Frame, Id,
-    Layout, LibraryFocusState, MutableWindowState, Orientation, PageState, Paragraph,
-    PlaylistFolderItem, Rect, Row, SearchFocusState, SharedState, Span, Style, Table, Track,
-    UIStateGuard,
+    Layout, LibraryFocusState, LineGauge, MutableWindowState, Orientation, PageState, Paragraph,
+    PlaylistFolderItem, PopupState, Rect, Row, SearchFocusState, SharedState, Span, Style, Table,
+    Track, UIStateGuard,
+};
+use crate::state::{
+    BidiDisplay, ContextId, PartialContext, PlaylistDiffFocusState, SharedPlaylistsFocusState,
 };
-use ratatui::widgets::Block;
-use crate::state::BidiDisplay;
 use crate::ui::utils::to_bidi_string;
+use ratatui::widgets::Block;
 
 const COMMAND_TABLE_CONSTRAINTS: [Constraint; 3] = [
     Constraint::Percentage(25),
     Constraint::Percentage(25),
     Constraint::Percentage(50),
 ];
+/// The index of the title column in a track table
+const TRACK_TABLE_TITLE_COLUMN: usize = 2;
 
 // UI codes to render a page.
 // A `render_*_page` function should follow (not strictly) the below steps
@@ -32,6 +42,35 @@ const COMMAND_TABLE_CONSTRAINTS: [Constraint; 3] = [
 // 3. construct the page's widgets
 // 4. render the widgets
 
+/// Render the progress of loading all items of a paginated list (if any) at the bottom
+/// of the list's window, returning the remaining area of the window
+fn render_load_all_progress(
+    frame: &mut Frame,
+    ui: &UIStateGuard,
+    page: Option<&ResultPage>,
+    rect: Rect,
+) -> Rect {
+    let Some((loaded, total)) = page.and_then(|page| Some((page.loading_all?, page.total))) else {
+        return rect;
+    };
+
+    let chunks = Layout::vertical([Constraint::Fill(0), Constraint::Length(1)]).split(rect);
+    let ratio = (loaded as f64 / total.max(1) as f64).min(1.0);
+    frame.render_widget(
+        LineGauge::default()
+            .filled_style(ui.theme.playback_progress_bar())
+            .unfilled_style(ui.theme.playback_progress_bar_unfilled())
+            .ratio(ratio)
+            .label(format!(
+                "loading {}/{}",
+                format_count(loaded),
+                format_count(total)
+            )),
+        chunks[1],
+    );
+    chunks[0]
+}
+
 pub fn render_search_page(
     is_active: bool,
     frame: &mut Frame,
@@ -59,6 +98,12 @@ pub fn render_search_page(
     };
 
     let search_results = data.caches.search.get(current_query);
+    // the window's title with the position of the loaded results, e.g. "Tracks (showing 50 of 1,342)"
+    let title = |name: &str, typ: SearchType| match search_results {
+        Some(results) => format!("{name} ({})", results.page(typ).describe(results.len(typ))),
+        None => name.to_string(),
+    };
+    let page = |typ: SearchType| search_results.map(|results| results.page(typ));
 
     // 2. Construct the page's layout
     let rect = construct_and_render_block("Search", &ui.theme, Borders::ALL, frame, rect);
@@ -95,7 +140,7 @@ pub fn render_search_page(
     };
 
     let track_rect = construct_and_render_block(
-        "Tracks",
+        &title("Tracks", SearchType::Track),
         &ui.theme,
         if ui.orientation == Orientation::Horizontal {
             Borders::TOP | Borders::RIGHT
@@ -105,10 +150,15 @@ pub fn render_search_page(
         frame,
         chunks[0],
     );
-    let album_rect =
-        construct_and_render_block("Albums", &ui.theme, Borders::TOP, frame, chunks[1]);
+    let album_rect = construct_and_render_block(
+        &title("Albums", SearchType::Album),
+        &ui.theme,
+        Borders::TOP,
+        frame,
+        chunks[1],
+    );
     let artist_rect = construct_and_render_block(
-        "Artists",
+        &title("Artists", SearchType::Artist),
         &ui.theme,
         if ui.orientation == Orientation::Horizontal {
             Borders::TOP | Borders::RIGHT
@@ -118,10 +168,15 @@ pub fn render_search_page(
         frame,
         chunks[2],
     );
-    let playlist_rect =
-        construct_and_render_block("Playlists", &ui.theme, Borders::TOP, frame, chunks[3]);
+    let playlist_rect = construct_and_render_block(
+        &title("Playlists", SearchType::Playlist),
+        &ui.theme,
+        Borders::TOP,
+        frame,
+        chunks[3],
+    );
     let show_rect = construct_and_render_block(
-        "Shows",
+        &title("Shows", SearchType::Show),
         &ui.theme,
         if ui.orientation == Orientation::Horizontal {
             Borders::TOP | Borders::RIGHT
@@ -131,8 +186,20 @@ pub fn render_search_page(
         frame,
         chunks[4],
     );
-    let episode_rect =
-        construct_and_render_block("Episodes", &ui.theme, Borders::TOP, frame, chunks[5]);
+    let episode_rect = construct_and_render_block(
+        &title("Episodes", SearchType::Episode),
+        &ui.theme,
+        Borders::TOP,
+        frame,
+        chunks[5],
+    );
+    let track_rect = render_load_all_progress(frame, ui, page(SearchType::Track), track_rect);
+    let album_rect = render_load_all_progress(frame, ui, page(SearchType::Album), album_rect);
+    let artist_rect = render_load_all_progress(frame, ui, page(SearchType::Artist), artist_rect);
+    let playlist_rect =
+        render_load_all_progress(frame, ui, page(SearchType::Playlist), playlist_rect);
+    let show_rect = render_load_all_progress(frame, ui, page(SearchType::Show), show_rect);
+    let episode_rect = render_load_all_progress(frame, ui, page(SearchType::Episode), episode_rect);
 
     // 3. Construct the page's widgets
     let (track_list, n_tracks) = {
@@ -287,13 +354,21 @@ pub fn render_context_page(
     #[cfg(not(feature = "fx"))]
     let rect = rect;
 
-    let rect = construct_and_render_block(
-        &context_page_type.title(),
-        &ui.theme,
-        Borders::ALL,
-        frame,
-        rect,
-    );
+    let title = match ui.current_page() {
+        PageState::Context {
+            state:
+                Some(ContextPageUIState::Playlist {
+                    grabbed_track: Some(_),
+                    ..
+                }),
+            ..
+        } => format!(
+            "{} (moving a track: drop with space/enter, cancel with esc)",
+            context_page_type.title()
+        ),
+        _ => context_page_type.title(),
+    };
+    let rect = construct_and_render_block(&title, &ui.theme, Borders::ALL, frame, rect);
 
     // 3+4. Construct and render the page's widgets
     let Some(id) = id else {
@@ -305,13 +380,17 @@ pub fn render_context_page(
     };
 
     let data = state.data.read();
-    match data.caches.context.get(&id.uri()) {
+    let context = data.caches.context.get(&id.uri());
+    let partial_context = data.caches.partial_contexts.get(&id.uri());
+    let is_artist = matches!(id, ContextId::Artist(_));
+    let rect = render_breadcrumb(frame, ui, &data, rect);
+    match context {
         Some(context) => {
             // render context description
             let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(0)]).split(rect);
 
-            let description = if let Context::Playlist { playlist, .. } = context {
-                format!(
+            let description = match context {
+                Context::Playlist { playlist, .. } => format!(
                     "{} | {}",
                     context.description(),
                     if data.user_data.is_followed_playlist(playlist) {
@@ -319,9 +398,17 @@ pub fn render_context_page(
                     } else {
                         "Not Followed"
                     }
-                )
-            } else {
-                context.description()
+                ),
+                Context::Album { album, .. } => format!(
+                    "{} | {}",
+                    context.description(),
+                    if data.user_data.saved_albums.iter().any(|a| a.id == album.id) {
+                        "Saved"
+                    } else {
+                        "Not Saved"
+                    }
+                ),
+                _ => context.description(),
             };
 
             frame.render_widget(
@@ -360,25 +447,61 @@ pub fn render_context_page(
                         chunks[1]
                     };
 
+                    let mut tracks = ui.search_filtered_tracks(tracks, &data);
+                    // display the grabbed track (if any) at the selected position
+                    if let PageState::Context {
+                        state:
+                            Some(ContextPageUIState::Playlist {
+                                track_table,
+                                grabbed_track: Some(from),
+                            }),
+                        ..
+                    } = ui.current_page()
+                    {
+                        let to = track_table.selected().unwrap_or_default();
+                        if *from < tracks.len() && to < tracks.len() {
+                            let track = tracks.remove(*from);
+                            tracks.insert(to, track);
+                        }
+                    }
+
+                    render_track_table(frame, rect, is_active, state, &tracks, ui, &data, false);
+                }
+                Context::Album { album, tracks } => {
+                    let credits = album.credits();
+                    let rect = if credits.is_empty() {
+                        rect
+                    } else {
+                        let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(0)])
+                            .split(rect);
+                        frame.render_widget(
+                            Paragraph::new(credits).style(ui.theme.playlist_desc()),
+                            chunks[0],
+                        );
+                        chunks[1]
+                    };
+
                     render_track_table(
                         frame,
                         rect,
                         is_active,
                         state,
-                        ui.search_filtered_items(tracks),
+                        &ui.search_filtered_tracks(tracks, &data),
                         ui,
                         &data,
+                        true,
                     );
                 }
-                Context::Tracks { tracks, .. } | Context::Album { tracks, .. } => {
+                Context::Tracks { tracks, .. } => {
                     render_track_table(
                         frame,
                         rect,
                         is_active,
                         state,
-                        ui.search_filtered_items(tracks),
+                        &ui.search_filtered_tracks(tracks, &data),
                         ui,
                         &data,
+                        false,
                     );
                 }
                 Context::Show { episodes, .. } => {
@@ -394,17 +517,115 @@ pub fn render_context_page(
             }
         }
         None => {
-            frame.render_widget(Paragraph::new("Loading..."), rect);
+            render_partial_context(frame, ui, is_artist, partial_context, rect);
         }
     }
 
-    // Render ASCII text banner if enabled
+    // Render visualization if enabled
     #[cfg(feature = "fx")]
     if let Some(viz_rect) = viz_rect {
-        render_ascii_banner(frame, ui, viz_rect);
+        render_visualization(frame, state, ui, viz_rect);
     }
 }
 
+/// Render a context being loaded, whose sections are filled in as they arrive.
+///
+/// A section that hasn't arrived yet is rendered as a skeleton placeholder.
+fn render_partial_context(
+    frame: &mut Frame,
+    ui: &UIStateGuard,
+    is_artist: bool,
+    context: Option<&PartialContext>,
+    rect: Rect,
+) {
+    // 1. Get data
+    let context = context.cloned().unwrap_or_default();
+    let description = context.description.map(|d| vec![d]);
+    let tracks = context.tracks.map(|tracks| {
+        tracks
+            .iter()
+            .map(|t| format!("{} • {}", t.display_name(), t.artists_info()))
+            .collect::<Vec<_>>()
+    });
+
+    // 2. Construct the page's layout
+    let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(0)]).split(rect);
+    render_partial_section(frame, ui, description, chunks[0]);
+
+    if is_artist {
+        let chunks =
+            Layout::vertical([Constraint::Length(12), Constraint::Fill(0)]).split(chunks[1]);
+        let top_tracks_rect = chunks[0];
+        let chunks = Layout::horizontal([Constraint::Ratio(1, 2); 2]).split(chunks[1]);
+        let albums_rect = construct_and_render_block(
+            "Albums",
+            &ui.theme,
+            Borders::TOP | Borders::RIGHT,
+            frame,
+            chunks[0],
+        );
+        let related_artists_rect = construct_and_render_block(
+            "Related Artists",
+            &ui.theme,
+            Borders::TOP,
+            frame,
+            chunks[1],
+        );
+
+        // 3+4. Construct and render the page's widgets
+        render_partial_section(frame, ui, tracks, top_tracks_rect);
+        render_partial_section(
+            frame,
+            ui,
+            context.albums.map(|albums| {
+                albums
+                    .iter()
+                    .map(|a| format!("{}  {}", a.release_date, a.name))
+                    .collect()
+            }),
+            albums_rect,
+        );
+        render_partial_section(
+            frame,
+            ui,
+            context
+                .related_artists
+                .map(|artists| artists.iter().map(|a| a.name.clone()).collect()),
+            related_artists_rect,
+        );
+    } else {
+        // 3+4. Construct and render the page's widgets
+        render_partial_section(frame, ui, tracks, chunks[1]);
+    }
+}
+
+/// Render a section of a context being loaded, or its skeleton if its data hasn't arrived yet
+fn render_partial_section(
+    frame: &mut Frame,
+    ui: &UIStateGuard,
+    lines: Option<Vec<String>>,
+    rect: Rect,
+) {
+    /// the widths (in percentage of the section's width) of the skeleton's rows
+    const SKELETON_ROW_WIDTHS: [u16; 5] = [70, 45, 85, 60, 35];
+
+    let text = match lines {
+        Some(lines) => Text::from(lines.into_iter().map(Line::from).collect::<Vec<_>>()),
+        None => Text::from(
+            (0..rect.height)
+                .map(|i| {
+                    let width = rect.width
+                        * SKELETON_ROW_WIDTHS[usize::from(i) % SKELETON_ROW_WIDTHS.len()]
+                        / 100;
+                    Line::from("░".repeat(width.into()))
+                })
+                .collect::<Vec<_>>(),
+        )
+        .style(ui.theme.playlist_desc()),
+    };
+    frame.render_widget(Paragraph::new(text), rect);
+}
+
 pub fn render_library_page(
     is_active: bool,
     frame: &mut Frame,
@@ -437,7 +658,7 @@ pub fn render_library_page(
     };
     #[cfg(not(feature = "fx"))]
     let rect = rect;
-    
+
     // Split the library page into 3 windows:
     // - a playlists window
     // - a saved albums window
@@ -526,87 +747,599 @@ pub fn render_library_page(
 
     utils::render_list_window(
         frame,
-        playlist_list,
-        playlist_rect,
-        n_playlists,
-        &mut page_state.playlist_list,
+        playlist_list,
+        playlist_rect,
+        n_playlists,
+        &mut page_state.playlist_list,
+    );
+    utils::render_list_window(
+        frame,
+        album_list,
+        album_rect,
+        n_albums,
+        &mut page_state.saved_album_list,
+    );
+    utils::render_list_window(
+        frame,
+        artist_list,
+        artist_rect,
+        n_artists,
+        &mut page_state.followed_artist_list,
+    );
+
+    // Render visualization if enabled
+    #[cfg(feature = "fx")]
+    if let Some(viz_rect) = viz_rect {
+        render_visualization(frame, state, ui, viz_rect);
+    }
+}
+
+pub fn render_browse_page(
+    is_active: bool,
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    mut rect: Rect,
+) {
+    // 1. Get data
+    let data = state.data.read();
+
+    // 2+3. Construct the page's layout and widgets
+    let (list, len) = match ui.current_page() {
+        PageState::Browse { state: ui_state } => match ui_state {
+            BrowsePageUIState::CategoryList { .. } => {
+                rect =
+                    construct_and_render_block("Categories", &ui.theme, Borders::ALL, frame, rect);
+
+                utils::construct_list_widget(
+                    &ui.theme,
+                    ui.search_filtered_items(&data.browse.categories)
+                        .into_iter()
+                        .map(|c| (c.name.clone(), false))
+                        .collect(),
+                    is_active,
+                )
+            }
+            BrowsePageUIState::CategoryPlaylistList { category, .. } => {
+                let title = format!("{} Playlists", category.name);
+                rect = construct_and_render_block(&title, &ui.theme, Borders::ALL, frame, rect);
+
+                let Some(playlists) = data.browse.category_playlists.get(&category.id) else {
+                    frame.render_widget(Paragraph::new("Loading..."), rect);
+                    return;
+                };
+
+                utils::construct_list_widget(
+                    &ui.theme,
+                    ui.search_filtered_items(playlists)
+                        .into_iter()
+                        .map(|c| (c.name.clone(), false))
+                        .collect(),
+                    is_active,
+                )
+            }
+        },
+        _ => return,
+    };
+
+    // 4. Render the page's widget
+    let Some(MutableWindowState::List(list_state)) = ui.current_page_mut().focus_window_state_mut()
+    else {
+        return;
+    };
+    utils::render_list_window(frame, list, rect, len, list_state);
+}
+
+pub fn render_blocklist_page(
+    is_active: bool,
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    // 1. Get data
+    let data = state.data.read();
+
+    // 2. Construct the page's layout
+    let rect = construct_and_render_block(
+        "Blocklist (press enter to unblock)",
+        &ui.theme,
+        Borders::ALL,
+        frame,
+        rect,
+    );
+
+    // 3. Construct the page's widgets
+    if data.user_data.blocklist.is_empty() {
+        frame.render_widget(Paragraph::new("No blocked tracks or artists"), rect);
+        return;
+    }
+    let (list, len) = utils::construct_list_widget(
+        &ui.theme,
+        data.user_data
+            .blocklist
+            .iter()
+            .map(|item| (item.to_string(), false))
+            .collect(),
+        is_active,
+    );
+
+    // 4. Render the page's widget
+    let Some(MutableWindowState::List(list_state)) = ui.current_page_mut().focus_window_state_mut()
+    else {
+        return;
+    };
+    utils::render_list_window(frame, list, rect, len, list_state);
+}
+
+#[cfg(feature = "plugins")]
+pub fn render_plugins_page(is_active: bool, frame: &mut Frame, ui: &mut UIStateGuard, rect: Rect) {
+    use crate::plugins::PluginItem;
+
+    // 1. Get data
+    let items = crate::plugins::items();
+
+    // 2. Construct the page's layout
+    let rect = construct_and_render_block(
+        "Plugins (press enter to enable/disable a plugin or to use its item)",
+        &ui.theme,
+        Borders::ALL,
+        frame,
+        rect,
+    );
+
+    // 3. Construct the page's widgets
+    if items.is_empty() {
+        let folder = config::get_config().config_folder.join("plugins");
+        frame.render_widget(
+            Paragraph::new(format!("No plugins found in {}", folder.display())),
+            rect,
+        );
+        return;
+    }
+    let (list, len) = utils::construct_list_widget(
+        &ui.theme,
+        items
+            .into_iter()
+            .map(|item| match item {
+                PluginItem::Plugin(plugin) => {
+                    let status = if plugin.is_enabled() { "x" } else { " " };
+                    let description = &plugin.manifest.description;
+                    if description.is_empty() {
+                        (format!("[{status}] {}", plugin.name), false)
+                    } else {
+                        (format!("[{status}] {} - {description}", plugin.name), false)
+                    }
+                }
+                PluginItem::Page(_, page) => (format!("      page: {page}"), false),
+                PluginItem::Action(_, action) => (format!("      action: {action}"), false),
+                PluginItem::Visualization(plugin, visualization) => {
+                    #[cfg(feature = "fx")]
+                    let is_active =
+                        ui.plugin_visualization
+                            .as_ref()
+                            .is_some_and(|(name, current)| {
+                                *name == plugin.name && current == visualization
+                            });
+                    #[cfg(not(feature = "fx"))]
+                    let is_active = {
+                        let _ = plugin;
+                        false
+                    };
+                    (format!("      visualization: {visualization}"), is_active)
+                }
+            })
+            .collect(),
+        is_active,
+    );
+
+    // 4. Render the page's widget
+    let Some(MutableWindowState::List(list_state)) = ui.current_page_mut().focus_window_state_mut()
+    else {
+        return;
+    };
+    utils::render_list_window(frame, list, rect, len, list_state);
+}
+
+#[cfg(feature = "plugins")]
+pub fn render_plugin_page(frame: &mut Frame, ui: &mut UIStateGuard, rect: Rect) {
+    // 1. Get data
+    let PageState::PluginPage {
+        plugin,
+        page,
+        scroll_offset,
+    } = ui.current_page()
+    else {
+        return;
+    };
+    let (plugin, page, scroll_offset) = (plugin.clone(), page.clone(), *scroll_offset);
+
+    // 2. Construct the page's layout
+    let rect = construct_and_render_block(
+        &format!("{page} ({plugin})"),
+        &ui.theme,
+        Borders::ALL,
+        frame,
+        rect,
+    );
+
+    // 3. Construct the page's widget
+    let text = match crate::plugins::get(&plugin) {
+        Some(p) if p.is_enabled() => {
+            match p.rendered("page", &page, (rect.width, rect.height), 0.0) {
+                None => Text::from("Loading..."),
+                Some(Ok(lines)) => {
+                    let scroll_offset = scroll_offset.min(lines.len().saturating_sub(1));
+                    if let PageState::PluginPage {
+                        scroll_offset: offset,
+                        ..
+                    } = ui.current_page_mut()
+                    {
+                        *offset = scroll_offset;
+                    }
+                    lines
+                        .into_iter()
+                        .skip(scroll_offset)
+                        .map(Line::from)
+                        .collect()
+                }
+                Some(Err(err)) => Text::from(format!("Failed to render the page: {err:#}")),
+            }
+        }
+        _ => Text::from(format!("Plugin {plugin} is disabled")),
+    };
+
+    // 4. Render the page's widget
+    frame.render_widget(Paragraph::new(text), rect);
+}
+
+pub fn render_artist_feed_page(
+    is_active: bool,
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    // 1. Get data
+    let data = state.data.read();
+    let feed = &data.user_data.artist_feed;
+    let n_unread = feed
+        .iter()
+        .filter(|item| data.user_data.is_unread_feed_item(item))
+        .count();
+
+    // 2. Construct the page's layout
+    let rect = construct_and_render_block(
+        &format!("Artist Feed ({n_unread} unread)"),
+        &ui.theme,
+        Borders::ALL,
+        frame,
+        rect,
+    );
+
+    // 3. Construct the page's widgets
+    if feed.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No recent releases or playlist adds of the followed artists"),
+            rect,
+        );
+        return;
+    }
+    let (list, len) = utils::construct_list_widget(
+        &ui.theme,
+        feed.iter()
+            .map(|item| {
+                let is_unread = data.user_data.is_unread_feed_item(item);
+                (
+                    format!(
+                        "{} {:<10}  {item}",
+                        if is_unread {
+                            glyphs().unread_marker
+                        } else {
+                            " "
+                        },
+                        item.date()
+                    ),
+                    is_unread,
+                )
+            })
+            .collect(),
+        is_active,
+    );
+
+    // 4. Render the page's widget
+    let Some(MutableWindowState::List(list_state)) = ui.current_page_mut().focus_window_state_mut()
+    else {
+        return;
+    };
+    utils::render_list_window(frame, list, rect, len, list_state);
+}
+
+pub fn render_saved_episodes_page(
+    is_active: bool,
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    // 1. Get data
+    let data = state.data.read();
+    let episodes = ui.search_filtered_items(&data.user_data.saved_episodes);
+    let n_unplayed = episodes.iter().filter(|e| !e.fully_played).count();
+
+    // 2. Construct the page's layout
+    let rect = construct_and_render_block(
+        &format!("Your Episodes ({n_unplayed} unplayed)"),
+        &ui.theme,
+        Borders::ALL,
+        frame,
+        rect,
+    );
+
+    // 3. Construct the page's widgets
+    if episodes.is_empty() {
+        frame.render_widget(Paragraph::new("No saved episodes"), rect);
+        return;
+    }
+    let n_episodes = episodes.len();
+    let rows = episodes
+        .into_iter()
+        .enumerate()
+        .map(|(id, e)| {
+            Row::new(vec![
+                Cell::from((id + 1).to_string()),
+                Cell::from(to_bidi_string(&e.name)),
+                Cell::from(e.show.as_ref().map(|s| s.name.clone()).unwrap_or_default()),
+                Cell::from(e.release_date.clone()),
+                Cell::from(e.progress()),
+                Cell::from(format!(
+                    "{}:{:02}",
+                    e.duration.as_secs() / 60,
+                    e.duration.as_secs() % 60,
+                )),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let episode_table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Fill(5),
+            Constraint::Fill(3),
+            Constraint::Fill(2),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            Cell::from("#"),
+            Cell::from("Title"),
+            Cell::from("Show"),
+            Cell::from("Date"),
+            Cell::from("Progress"),
+            Cell::from("Duration"),
+        ])
+        .style(ui.theme.table_header()),
+    )
+    .column_spacing(2)
+    .row_highlight_style(ui.theme.selection(is_active));
+
+    // 4. Render the page's widget
+    let Some(MutableWindowState::Table(table_state)) =
+        ui.current_page_mut().focus_window_state_mut()
+    else {
+        return;
+    };
+    utils::render_table_window(frame, episode_table, rect, n_episodes, table_state);
+}
+
+pub fn render_shared_playlists_page(
+    is_active: bool,
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    // 1. Get data
+    let data = state.data.read();
+    let focus_state = match ui.current_page() {
+        PageState::SharedPlaylists { state } => state.focus,
+        _ => return,
+    };
+    let playlists = data.user_data.shared_playlist_items();
+    let recent_adds = &data.user_data.shared_playlist_adds;
+
+    // 2. Construct the page's layout
+    // Split the page into 2 windows:
+    // - a window of the shared playlists
+    // - a window of the tracks recently added by the other contributors
+    let chunks = ui
+        .orientation
+        .layout([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(rect);
+    let borders = match ui.orientation {
+        Orientation::Horizontal => Borders::TOP | Borders::LEFT | Borders::BOTTOM,
+        Orientation::Vertical => Borders::ALL,
+    };
+
+    let playlist_rect = construct_and_render_block(
+        &format!("Shared Playlists ({})", playlists.len()),
+        &ui.theme,
+        borders,
+        frame,
+        chunks[0],
+    );
+    let recent_track_rect = construct_and_render_block(
+        "Recently Added by Others",
+        &ui.theme,
+        Borders::ALL,
+        frame,
+        chunks[1],
+    );
+
+    // 3. Construct the page's widgets
+    let (playlist_list, n_playlists) = utils::construct_list_widget(
+        &ui.theme,
+        playlists
+            .iter()
+            .map(|item| (item.to_bidi_string(), false))
+            .collect(),
+        is_active && focus_state == SharedPlaylistsFocusState::Playlists,
+    );
+    let (recent_track_list, n_recent_tracks) = utils::construct_list_widget(
+        &ui.theme,
+        recent_adds
+            .iter()
+            .map(|add| (add.to_string(), false))
+            .collect(),
+        is_active && focus_state == SharedPlaylistsFocusState::RecentTracks,
+    );
+
+    // 4. Render the page's widgets
+    let PageState::SharedPlaylists { state: page_state } = ui.current_page_mut() else {
+        return;
+    };
+
+    utils::render_list_window(
+        frame,
+        playlist_list,
+        playlist_rect,
+        n_playlists,
+        &mut page_state.playlist_list,
+    );
+    utils::render_list_window(
+        frame,
+        recent_track_list,
+        recent_track_rect,
+        n_recent_tracks,
+        &mut page_state.recent_track_list,
+    );
+}
+
+pub fn render_playlist_diff_page(
+    is_active: bool,
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    // 1. Get data
+    let data = state.data.read();
+
+    let (first, second, focus_state) = match ui.current_page() {
+        PageState::PlaylistDiff {
+            first,
+            second,
+            state,
+        } => (first, second, state.focus),
+        _ => return,
+    };
+    let Some(diff) = data.playlist_diff(
+        &ContextId::Playlist(first.id.clone()),
+        &ContextId::Playlist(second.id.clone()),
+    ) else {
+        let rect = construct_and_render_block(
+            &format!("{} vs {}", first.name, second.name),
+            &ui.theme,
+            Borders::ALL,
+            frame,
+            rect,
+        );
+        frame.render_widget(Paragraph::new("Loading..."), rect);
+        return;
+    };
+
+    // 2. Construct the page's layout
+    // Split the page into 3 windows:
+    // - a window of tracks only in the first playlist
+    // - a window of tracks only in the second playlist
+    // - a window of tracks in both playlists
+    let chunks = ui
+        .orientation
+        .layout([
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .split(rect);
+    let borders = match ui.orientation {
+        Orientation::Horizontal => Borders::TOP | Borders::LEFT | Borders::BOTTOM,
+        Orientation::Vertical => Borders::ALL,
+    };
+
+    let first_rect = construct_and_render_block(
+        &format!("Only in {} ({})", first.name, diff.only_in_first.len()),
+        &ui.theme,
+        borders,
+        frame,
+        chunks[0],
+    );
+    let second_rect = construct_and_render_block(
+        &format!("Only in {} ({})", second.name, diff.only_in_second.len()),
+        &ui.theme,
+        borders,
+        frame,
+        chunks[1],
+    );
+    let both_rect = construct_and_render_block(
+        &format!("In both ({})", diff.in_both.len()),
+        &ui.theme,
+        Borders::ALL,
+        frame,
+        chunks[2],
+    );
+
+    // 3. Construct the page's widgets
+    let construct_track_list = |tracks: &[&Track], is_active: bool| {
+        utils::construct_list_widget(
+            &ui.theme,
+            tracks.iter().map(|t| (t.to_bidi_string(), false)).collect(),
+            is_active,
+        )
+    };
+    let (first_list, n_first) = construct_track_list(
+        &diff.only_in_first,
+        is_active && focus_state == PlaylistDiffFocusState::OnlyInFirst,
+    );
+    let (second_list, n_second) = construct_track_list(
+        &diff.only_in_second,
+        is_active && focus_state == PlaylistDiffFocusState::OnlyInSecond,
+    );
+    let (both_list, n_both) = construct_track_list(
+        &diff.in_both,
+        is_active && focus_state == PlaylistDiffFocusState::InBoth,
+    );
+
+    // 4. Render the page's widgets
+    let PageState::PlaylistDiff {
+        state: page_state, ..
+    } = ui.current_page_mut()
+    else {
+        return;
+    };
+
+    utils::render_list_window(
+        frame,
+        first_list,
+        first_rect,
+        n_first,
+        &mut page_state.only_in_first_list,
     );
     utils::render_list_window(
         frame,
-        album_list,
-        album_rect,
-        n_albums,
-        &mut page_state.saved_album_list,
+        second_list,
+        second_rect,
+        n_second,
+        &mut page_state.only_in_second_list,
     );
     utils::render_list_window(
         frame,
-        artist_list,
-        artist_rect,
-        n_artists,
-        &mut page_state.followed_artist_list,
+        both_list,
+        both_rect,
+        n_both,
+        &mut page_state.in_both_list,
     );
-    
-    // Render ASCII text banner if enabled
-    #[cfg(feature = "fx")]
-    if let Some(viz_rect) = viz_rect {
-        render_ascii_banner(frame, ui, viz_rect);
-    }
-}
-
-pub fn render_browse_page(
-    is_active: bool,
-    frame: &mut Frame,
-    state: &SharedState,
-    ui: &mut UIStateGuard,
-    mut rect: Rect,
-) {
-    // 1. Get data
-    let data = state.data.read();
-
-    // 2+3. Construct the page's layout and widgets
-    let (list, len) = match ui.current_page() {
-        PageState::Browse { state: ui_state } => match ui_state {
-            BrowsePageUIState::CategoryList { .. } => {
-                rect =
-                    construct_and_render_block("Categories", &ui.theme, Borders::ALL, frame, rect);
-
-                utils::construct_list_widget(
-                    &ui.theme,
-                    ui.search_filtered_items(&data.browse.categories)
-                        .into_iter()
-                        .map(|c| (c.name.clone(), false))
-                        .collect(),
-                    is_active,
-                )
-            }
-            BrowsePageUIState::CategoryPlaylistList { category, .. } => {
-                let title = format!("{} Playlists", category.name);
-                rect = construct_and_render_block(&title, &ui.theme, Borders::ALL, frame, rect);
-
-                let Some(playlists) = data.browse.category_playlists.get(&category.id) else {
-                    frame.render_widget(Paragraph::new("Loading..."), rect);
-                    return;
-                };
-
-                utils::construct_list_widget(
-                    &ui.theme,
-                    ui.search_filtered_items(playlists)
-                        .into_iter()
-                        .map(|c| (c.name.clone(), false))
-                        .collect(),
-                    is_active,
-                )
-            }
-        },
-        _ => return,
-    };
-
-    // 4. Render the page's widget
-    let Some(MutableWindowState::List(list_state)) = ui.current_page_mut().focus_window_state_mut()
-    else {
-        return;
-    };
-    utils::render_list_window(frame, list, rect, len, list_state);
 }
 
 pub fn render_lyrics_page(
@@ -690,6 +1423,209 @@ pub fn render_lyrics_page(
     frame.render_widget(paragraph, chunks[1]);
 }
 
+/// Render a text in a big figlet font, one string per row.
+/// The text itself is returned if it can't be rendered in the font or doesn't fit into `width` columns.
+fn render_big_text(text: &str, width: u16) -> Vec<String> {
+    static FONT: LazyLock<Option<FIGfont>> = LazyLock::new(|| FIGfont::standard().ok());
+
+    // the standard font only supports printable ASCII characters
+    if text.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        if let Some(figure) = FONT.as_ref().and_then(|font| font.convert(text)) {
+            let mut rows = figure
+                .to_string()
+                .lines()
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            while rows.last().is_some_and(|row| row.trim().is_empty()) {
+                rows.pop();
+            }
+            if rows.iter().all(|row| row.chars().count() <= width as usize) {
+                return rows;
+            }
+        }
+    }
+    vec![text.to_string()]
+}
+
+/// Render the cover art with upper half blocks (`▀`), each of which shows two pixels,
+/// or with background colored spaces if the glyph set has no half blocks.
+///
+/// The cover image is only resized when it or the rendered area changes.
+#[cfg(feature = "image")]
+fn render_cover_art(
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    url: &str,
+    rect: Rect,
+) {
+    let size = rect.width.min(rect.height * 2);
+    if size < 2 {
+        return;
+    }
+    let rect = Rect {
+        x: rect.x + (rect.width - size) / 2,
+        y: rect.y + (rect.height - size / 2) / 2,
+        width: size,
+        height: size / 2,
+    };
+    let size = (rect.width, rect.height);
+    if ui
+        .cover_art_thumbnail
+        .as_ref()
+        .is_none_or(|(u, s, _)| u != url || *s != size)
+    {
+        let Some(thumbnail) = state.data.read().caches.images.get(url).map(|image| {
+            image
+                .thumbnail_exact(u32::from(rect.width), u32::from(rect.height) * 2)
+                .to_rgb8()
+        }) else {
+            return;
+        };
+        ui.cover_art_thumbnail = Some((url.to_string(), size, thumbnail));
+    }
+    let Some((_, _, image)) = &ui.cover_art_thumbnail else {
+        return;
+    };
+
+    let half_blocks = glyphs().half_blocks;
+    let buffer = frame.buffer_mut();
+    for y in 0..rect.height {
+        for x in 0..rect.width {
+            let [tr, tg, tb] = image.get_pixel(u32::from(x), u32::from(y) * 2).0;
+            let [br, bg, bb] = image.get_pixel(u32::from(x), u32::from(y) * 2 + 1).0;
+            let Some(cell) = buffer.cell_mut((rect.x + x, rect.y + y)) else {
+                continue;
+            };
+            if half_blocks {
+                cell.set_symbol("▀")
+                    .set_fg(ratatui::style::Color::Rgb(tr, tg, tb))
+                    .set_bg(ratatui::style::Color::Rgb(br, bg, bb));
+            } else {
+                cell.set_symbol(" ").set_bg(ratatui::style::Color::Rgb(
+                    u8::midpoint(tr, br),
+                    u8::midpoint(tg, bg),
+                    u8::midpoint(tb, bb),
+                ));
+            }
+        }
+    }
+}
+
+/// Render a full-screen page showing the current playback, intended for a dedicated monitor or TV
+pub fn render_now_playing_page(
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    // 1. Get data
+    let player = state.player.read();
+    let Some(item) = player.currently_playing() else {
+        frame.render_widget(Paragraph::new("No playback available").centered(), rect);
+        return;
+    };
+    let (title, artists, album, duration) = match item {
+        rspotify::model::PlayableItem::Track(track) => (
+            &track.name,
+            crate::utils::map_join(&track.artists, |a| &a.name, ", "),
+            &track.album.name,
+            track.duration,
+        ),
+        rspotify::model::PlayableItem::Episode(episode) => (
+            &episode.name,
+            episode.show.publisher.clone(),
+            &episode.show.name,
+            episode.duration,
+        ),
+        rspotify::model::PlayableItem::Unknown(_) => {
+            frame.render_widget(Paragraph::new("Unknown playback item").centered(), rect);
+            return;
+        }
+    };
+    let progress = std::cmp::min(player.playback_progress().unwrap_or_default(), duration);
+
+    // 2. Construct the page's layout
+    let title_rows = render_big_text(title, rect.width);
+
+    #[cfg(feature = "fx")]
+    let viz_height = if ui.enable_visualization {
+        (config::get_config().app_config.visualization_height as u16).min(rect.height / 4)
+    } else {
+        0
+    };
+    #[cfg(not(feature = "fx"))]
+    let viz_height = 0;
+
+    let chunks = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(title_rows.len() as u16),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(viz_height),
+    ])
+    .spacing(1)
+    .split(rect);
+    let progress_bar_rect = Layout::horizontal([
+        Constraint::Percentage(20),
+        Constraint::Percentage(60),
+        Constraint::Percentage(20),
+    ])
+    .split(chunks[5])[1];
+
+    // 3+4. Construct and render the page's widgets
+    #[cfg(feature = "image")]
+    {
+        let url = match item {
+            rspotify::model::PlayableItem::Track(track) => {
+                crate::utils::get_track_album_image_url(track)
+            }
+            rspotify::model::PlayableItem::Episode(episode) => {
+                crate::utils::get_episode_show_image_url(episode)
+            }
+            rspotify::model::PlayableItem::Unknown(_) => None,
+        };
+        if let Some(url) = url {
+            render_cover_art(frame, state, ui, url, chunks[0]);
+        }
+    }
+
+    frame.render_widget(
+        Paragraph::new(
+            title_rows
+                .into_iter()
+                .map(|row| Line::styled(row, ui.theme.playback_track()))
+                .collect::<Vec<_>>(),
+        )
+        .centered(),
+        chunks[1],
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            to_bidi_string(&artists),
+            ui.theme.playback_artists(),
+        ))
+        .centered(),
+        chunks[2],
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            to_bidi_string(album),
+            ui.theme.playback_album(),
+        ))
+        .centered(),
+        chunks[3],
+    );
+    super::playback::render_playback_progress_bar(frame, ui, progress, duration, progress_bar_rect);
+
+    #[cfg(feature = "fx")]
+    if viz_height > 0 {
+        render_visualization(frame, state, ui, chunks[6]);
+    }
+}
+
 pub fn render_commands_help_page(frame: &mut Frame, ui: &mut UIStateGuard, rect: Rect) {
     // 1. Get data
     let configs = config::get_config();
@@ -865,7 +1801,7 @@ fn render_artist_context_page_windows(
 ) {
     // 1. Get data
     let (tracks, albums, artists) = (
-        ui.search_filtered_items(artist_data.0),
+        ui.search_filtered_tracks(artist_data.0, data),
         ui.search_filtered_items(artist_data.1),
         ui.search_filtered_items(artist_data.2),
     );
@@ -951,9 +1887,10 @@ fn render_artist_context_page_windows(
         top_tracks_rect,
         is_active && focus_state == ArtistFocusState::TopTracks,
         state,
-        tracks,
+        &tracks,
         ui,
         data,
+        false,
     );
 
     let PageState::Context {
@@ -979,14 +1916,82 @@ fn render_artist_context_page_windows(
     );
 }
 
+/// Render the breadcrumb of the pages in the navigation history and the current search filter,
+/// returning the remaining rectangle
+fn render_breadcrumb(
+    frame: &mut Frame,
+    ui: &mut UIStateGuard,
+    data: &DataReadGuard,
+    rect: Rect,
+) -> Rect {
+    let mut crumbs = ui
+        .history
+        .iter()
+        .map(|page| match page {
+            PageState::Context {
+                id: Some(id),
+                context_page_type,
+                ..
+            } => match data.caches.context.get(&id.uri()) {
+                Some(context) => format!("{} \"{}\"", context_page_type.title(), context.name()),
+                None => page.title(),
+            },
+            _ => page.title(),
+        })
+        .collect::<Vec<_>>();
+    let n_pages = crumbs.len();
+    if let Some(PopupState::Search { query }) = &ui.popup {
+        let query = query.get_text();
+        if !query.is_empty() {
+            crumbs.push(format!("Search filter \"{query}\""));
+        }
+    }
+
+    let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(0)]).split(rect);
+    let n_crumbs = crumbs.len();
+    let mut spans = vec![];
+    let mut x = chunks[0].x;
+    for (i, crumb) in crumbs.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(
+                glyphs().breadcrumb_separator,
+                ui.theme.page_desc(),
+            ));
+            x = x.saturating_add(3);
+        }
+        let width = u16::try_from(Span::raw(&crumb).width()).unwrap_or(u16::MAX);
+        // only the pages in the navigation history can be clicked to go back to
+        if i < n_pages {
+            ui.breadcrumb_rects
+                .push(Rect::new(x, chunks[0].y, width, 1).intersection(chunks[0]));
+        }
+        x = x.saturating_add(width);
+        let style = if i + 1 == n_crumbs {
+            ui.theme.block_title()
+        } else {
+            ui.theme.page_desc()
+        };
+        spans.push(Span::styled(crumb, style));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), chunks[0]);
+
+    chunks[1]
+}
+
+/// Render a table of tracks.
+///
+/// If `group_by_disc` is true and the tracks span multiple discs (in order),
+/// the first track of each disc is preceded by a header line of the disc.
+#[allow(clippy::too_many_arguments)]
 fn render_track_table(
     frame: &mut Frame,
     rect: Rect,
     is_active: bool,
     state: &SharedState,
-    tracks: Vec<&Track>,
+    tracks: &[&Track],
     ui: &mut UIStateGuard,
     data: &DataReadGuard,
+    group_by_disc: bool,
 ) {
     let configs = config::get_config();
     // get the current playing track's URI to decorate such track (if exists) in the track table
@@ -1010,47 +2015,83 @@ fn render_track_table(
 
     // enable Added column if any track in the table has added_at field specified
     let added_at_enabled = tracks.iter().any(|t| t.added_at > 0);
+    // enable Rating/Tags column if any track in the table has a local annotation
+    let annotation_enabled = tracks
+        .iter()
+        .any(|t| data.user_data.track_annotations.contains_key(&t.id.uri()));
+
+    let group_by_disc = group_by_disc
+        && tracks.first().map(|t| t.disc_number) != tracks.last().map(|t| t.disc_number)
+        && tracks.is_sorted_by_key(|t| t.disc_number);
 
     let n_tracks = tracks.len();
     let rows = tracks
-        .into_iter()
+        .iter()
         .enumerate()
         .map(|(id, t)| {
-            let (id, style) = if playing_track_uri == t.id.uri() {
+            let disc_header = (group_by_disc
+                && (id == 0 || tracks[id - 1].disc_number != t.disc_number))
+                .then(|| format!("Disc {}", t.disc_number));
+            let (id, style) = if t.has_uri(&playing_track_uri) {
                 (playing_id.to_string(), ui.theme.current_playing())
+            } else if t.is_unavailable {
+                ((id + 1).to_string(), ui.theme.unavailable_track())
+            } else if data.caches.new_tracks.contains_key(&t.id.uri()) {
+                // highlight the tracks added since the context's previous refresh
+                ((id + 1).to_string(), ui.theme.new_track())
             } else {
                 ((id + 1).to_string(), Style::default())
             };
-            Row::new(vec![
-                if data.user_data.is_liked_track(t) {
-                    Cell::from(&configs.app_config.liked_icon as &str).style(ui.theme.like())
+            let cells = vec![
+                if data.is_liked_track(t) {
+                    Line::styled(configs.app_config.liked_icon.clone(), ui.theme.like())
                 } else {
-                    Cell::from("")
+                    Line::default()
                 },
-                Cell::from(id),
-                Cell::from(to_bidi_string(&t.display_name())),
-                Cell::from(to_bidi_string(&t.artists_info())),
-                Cell::from(to_bidi_string(&t.album_info())),
+                Line::from(id),
+                Line::from(to_bidi_string(&t.display_name())),
+                Line::from(to_bidi_string(&t.artists_info())),
+                Line::from(to_bidi_string(&t.album_info())),
+                Line::from(
+                    data.user_data
+                        .track_annotations
+                        .get(&t.id.uri())
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                ),
                 if added_at_enabled {
                     // added_at is in seconds resolution
                     let time =
                         chrono::DateTime::from_timestamp_nanos(t.added_at as i64 * 1_000_000_000);
                     // use absolute date format if the track is added more than a month ago, otherwise use relative date
-                    Cell::from(if chrono::Utc::now() > time + chrono::Duration::days(30) {
+                    Line::from(if chrono::Utc::now() > time + chrono::Duration::days(30) {
                         time.format("%b %d, %Y").to_string()
                     } else {
                         HumanTime::from(time).to_string()
                     })
                 } else {
-                    Cell::from("")
+                    Line::default()
                 },
-                Cell::from(format!(
+                Line::from(format!(
                     "{}:{:02}",
                     t.duration.as_secs() / 60,
                     t.duration.as_secs() % 60,
                 )),
-            ])
-            .style(style)
+            ];
+            let row = match disc_header {
+                // the first track of a disc is rendered with the disc's header above its title
+                Some(header) => Row::new(cells.into_iter().enumerate().map(|(i, line)| {
+                    let header = if i == TRACK_TABLE_TITLE_COLUMN {
+                        Line::styled(header.clone(), ui.theme.table_header())
+                    } else {
+                        Line::default()
+                    };
+                    Cell::from(Text::from(vec![header, line]))
+                }))
+                .height(2),
+                None => Row::new(cells),
+            };
+            row.style(style)
         })
         .collect::<Vec<_>>();
     let track_table = Table::new(
@@ -1061,6 +2102,11 @@ fn render_track_table(
             Constraint::Fill(4),
             Constraint::Fill(3),
             Constraint::Fill(5),
+            if annotation_enabled {
+                Constraint::Fill(2)
+            } else {
+                Constraint::Fill(0)
+            },
             if added_at_enabled {
                 Constraint::Fill(2)
             } else {
@@ -1076,6 +2122,11 @@ fn render_track_table(
             Cell::from("Title"),
             Cell::from("Artists"),
             Cell::from("Album"),
+            if annotation_enabled {
+                Cell::from("Rating/Tags")
+            } else {
+                Cell::from("")
+            },
             if added_at_enabled {
                 Cell::from("Added")
             } else {
@@ -1096,7 +2147,7 @@ fn render_track_table(
             ContextPageUIState::Artist {
                 top_track_table, ..
             } => top_track_table,
-            ContextPageUIState::Playlist { track_table }
+            ContextPageUIState::Playlist { track_table, .. }
             | ContextPageUIState::Album { track_table }
             | ContextPageUIState::Tracks { track_table } => track_table,
             ContextPageUIState::Show { .. } => {
@@ -1187,41 +2238,262 @@ fn render_episode_table(
     }
 }
 
-/// Render ASCII animated cats and dogs
+/// Render the visualization in the current mode, saving a snapshot of it or recording it if requested
+#[cfg(feature = "fx")]
+fn render_visualization(frame: &mut Frame, state: &SharedState, ui: &mut UIStateGuard, rect: Rect) {
+    use super::visualizations::{self, VisualizationMode};
+
+    let start = std::time::Instant::now();
+    let now = ui.clock.now();
+
+    #[cfg(feature = "image")]
+    let album_color = ui.current_album_color;
+    #[cfg(not(feature = "image"))]
+    let album_color = None;
+    let target_color = ui.visualization.color_scheme.color(album_color);
+    let crossfade_duration = std::time::Duration::from_millis(
+        config::get_config().app_config.visualization_crossfade_ms,
+    );
+    let color = match (ui.visualization_crossfade, target_color) {
+        (Some(crossfade), Some(to)) => crossfade.color(to, crossfade_duration, now),
+        _ => None,
+    };
+    if color.is_none() {
+        ui.visualization_crossfade = None;
+    }
+    let color = color.or(target_color);
+    ui.visualization_color = color;
+
+    let (n_beats, position, (loudness, bands)) = {
+        let player = state.player.read();
+        // a deterministic clock plays the visualization from its timestamp instead of the playback's position
+        let (position, is_playing) = match ui.clock.timestamp() {
+            Some(timestamp) => (timestamp.as_secs_f64(), true),
+            None => (
+                player
+                    .playback_progress()
+                    .map_or(0.0, |p| p.num_milliseconds() as f64 / 1000.0),
+                player.playback.as_ref().is_some_and(|p| p.is_playing),
+            ),
+        };
+        let data = state.data.read();
+        let analysis = player
+            .currently_playing_uri()
+            .and_then(|uri| data.caches.audio_analysis.get(&uri));
+        let (target, rate) = crate::utils::beats_at(analysis, position, ui.current_bpm);
+        let n_beats = ui.beat_clock.tick(target, rate, is_playing, now);
+        (
+            n_beats,
+            position,
+            visualizations::audio_levels(analysis, position, n_beats),
+        )
+    };
+    let mode = match ui.visualization.mode {
+        VisualizationMode::Smart => ui.smart_visualization_mode,
+        mode => mode,
+    };
+
+    // a visualization picked from a plugin replaces the built-in ones
+    #[cfg(feature = "plugins")]
+    let is_rendered_by_plugin = match ui.plugin_visualization.clone() {
+        Some((plugin, visualization)) => {
+            match render_plugin_visualization(
+                frame,
+                ui,
+                rect,
+                (&plugin, &visualization),
+                color,
+                n_beats,
+            ) {
+                Ok(()) => true,
+                Err(err) => {
+                    tracing::error!(
+                        "Failed to render visualization {visualization} of plugin {plugin}: {err:#}"
+                    );
+                    ui.plugin_visualization = None;
+                    false
+                }
+            }
+        }
+        None => false,
+    };
+    #[cfg(not(feature = "plugins"))]
+    let is_rendered_by_plugin = false;
+
+    if !is_rendered_by_plugin {
+        match mode {
+            VisualizationMode::Banner | VisualizationMode::Smart => {
+                render_ascii_banner(frame, ui, rect, color, n_beats);
+            }
+            VisualizationMode::Waves => {
+                visualizations::render_concentric_waves(
+                    frame,
+                    rect,
+                    n_beats * 10.0,
+                    color,
+                    glyphs(),
+                    true,
+                );
+            }
+            VisualizationMode::Bars => {
+                visualizations::render_spectrum_bars(frame, rect, n_beats, color, glyphs(), true);
+            }
+            VisualizationMode::Particles => {
+                visualizations::render_particles(frame, rect, n_beats, color, glyphs(), true);
+            }
+            VisualizationMode::Shader => match configured_shader() {
+                Some(shader) => {
+                    let inputs = super::shader::Inputs {
+                        t: position,
+                        beat: n_beats,
+                        loudness,
+                        bands,
+                        ..Default::default()
+                    };
+                    visualizations::render_shader(
+                        frame,
+                        rect,
+                        shader,
+                        inputs,
+                        color,
+                        glyphs(),
+                        true,
+                    );
+                }
+                None => render_ascii_banner(frame, ui, rect, color, n_beats),
+            },
+        }
+    }
+
+    if let Some(start) = ui.visualization_drop_start {
+        let progress = now.saturating_duration_since(start).as_secs_f64()
+            / visualizations::DROP_DURATION.as_secs_f64();
+        if progress < 1.0 {
+            visualizations::apply_drop(frame.buffer_mut(), rect, progress);
+        } else {
+            ui.visualization_drop_start = None;
+        }
+    }
+
+    ui.render_stats.record_visualization(start.elapsed());
+
+    if std::mem::take(&mut ui.visualization_snapshot_requested) {
+        let palette = visualizations::album_palette(album_color);
+        let cache_folder = &config::get_config().cache_folder;
+        match visualizations::save_snapshot(cache_folder, frame.buffer_mut(), rect, &palette) {
+            Ok(path) => tracing::info!("Saved visualization snapshot to {}", path.display()),
+            Err(err) => tracing::error!("Failed to save visualization snapshot: {err:#}"),
+        }
+    }
+
+    if let Some(recording) = ui.visualization_recording.as_mut() {
+        let finished = recording
+            .record(frame.buffer_mut(), rect, now)
+            .unwrap_or_else(|err| {
+                tracing::error!("Failed to record visualization: {err:#}");
+                true
+            });
+        if let Some(recording) = ui.visualization_recording.take_if(|_| finished) {
+            match recording.save(&config::get_config().cache_folder) {
+                Ok(path) => tracing::info!("Saved visualization recording to {}", path.display()),
+                Err(err) => tracing::error!("Failed to save visualization recording: {err:#}"),
+            }
+        }
+    }
+}
+
+/// Render ASCII animated cats and dogs, in the given color or the theme's color
+#[cfg(feature = "fx")]
+/// Get the compiled shader of the `visualization_shader` config option, if any
 #[cfg(feature = "fx")]
+fn configured_shader() -> Option<&'static super::shader::Shader> {
+    static SHADER: std::sync::OnceLock<Option<super::shader::Shader>> = std::sync::OnceLock::new();
+
+    SHADER
+        .get_or_init(|| {
+            let source = config::get_config()
+                .app_config
+                .visualization_shader
+                .as_ref()?;
+            // the shader is validated when the config is parsed
+            super::shader::Shader::compile(source).ok()
+        })
+        .as_ref()
+}
+
+/// Render a plugin's visualization, whose text lines are drawn in the visualization's color
+#[cfg(all(feature = "fx", feature = "plugins"))]
+fn render_plugin_visualization(
+    frame: &mut Frame,
+    ui: &UIStateGuard,
+    rect: Rect,
+    (plugin, visualization): (&str, &str),
+    color: Option<(u8, u8, u8)>,
+    n_beats: f64,
+) -> anyhow::Result<()> {
+    use anyhow::Context as _;
+
+    let plugin = crate::plugins::get(plugin)
+        .filter(|p| p.is_enabled())
+        .context("plugin isn't enabled")?;
+    let Some(lines) = plugin.rendered(
+        "visualization",
+        visualization,
+        (rect.width, rect.height),
+        n_beats,
+    ) else {
+        return Ok(());
+    };
+    let lines = lines?;
+
+    let text_color = match color {
+        Some((r, g, b)) => ratatui::style::Color::Rgb(r, g, b),
+        None => ui.theme.app().fg.unwrap_or(ratatui::style::Color::Cyan),
+    };
+    let text = lines.into_iter().map(Line::from).collect::<Vec<_>>();
+    frame.render_widget(
+        Paragraph::new(text).style(Style::default().fg(text_color)),
+        rect,
+    );
+    Ok(())
+}
+
 fn render_ascii_banner(
     frame: &mut Frame,
     ui: &mut UIStateGuard,
     rect: Rect,
+    color: Option<(u8, u8, u8)>,
+    n_beats: f64,
 ) {
-    // Use elapsed time to animate
-    let elapsed = ui.visualization_start_time.elapsed().as_secs_f64();
+    // animate by the beats, so that the animals move twice per second at 120 BPM,
+    // stop while paused and jump on seeks
+    let elapsed = n_beats.max(0.0) / 2.0;
     let frame_num = (elapsed * 2.0) as usize; // 2 FPS animation
-    
+
     let width = rect.width as usize;
-    
+
     // Calculate positions for animals moving across the screen
     let cat_pos = ((elapsed * 8.0) as usize) % (width + 20);
     let dog_pos = ((elapsed * 5.0 + 30.0) as usize) % (width + 20);
     let ball_pos = ((elapsed * 12.0 + 15.0) as usize) % (width + 20);
-    
+
     // Cat animation frames
     let cat = if frame_num % 2 == 0 {
         [" /\\_/\\  ", "( o.o ) ", " > ^ <  "]
     } else {
         [" /\\_/\\  ", "( ^.^ ) ", " > ~ <  "]
     };
-    
+
     // Dog animation frames
     let dog = if frame_num % 2 == 0 {
         [" /\\_/\\ ", "( o o )", "  \\-/  "]
     } else {
         [" /\\_/\\ ", "( O O )", "  \\=/  "]
     };
-    
+
     // Ball
     let ball = "  o  ";
-    
+
     // Build 5 lines of animation
     let mut lines = vec![
         vec![' '; width],
@@ -1230,7 +2502,7 @@ fn render_ascii_banner(
         vec![' '; width],
         vec![' '; width],
     ];
-    
+
     // Place cat
     if cat_pos < width {
         for (line_idx, cat_line) in cat.iter().enumerate() {
@@ -1243,7 +2515,7 @@ fn render_ascii_banner(
             }
         }
     }
-    
+
     // Place dog
     if dog_pos < width {
         for (line_idx, dog_line) in dog.iter().enumerate() {
@@ -1256,7 +2528,7 @@ fn render_ascii_banner(
             }
         }
     }
-    
+
     // Place ball
     if ball_pos < width && ball_pos < width - ball.len() {
         for (i, ch) in ball.chars().enumerate() {
@@ -1265,9 +2537,12 @@ fn render_ascii_banner(
             }
         }
     }
-    
+
     // Convert to styled text
-    let text_color = ui.theme.app().fg.unwrap_or(ratatui::style::Color::Cyan);
+    let text_color = match color {
+        Some((r, g, b)) => ratatui::style::Color::Rgb(r, g, b),
+        None => ui.theme.app().fg.unwrap_or(ratatui::style::Color::Cyan),
+    };
     let mut text = vec![];
     for line in lines {
         let line_str: String = line.into_iter().collect();
@@ -1276,9 +2551,14 @@ fn render_ascii_banner(
             Style::default().fg(text_color),
         )));
     }
-    
-    let widget = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("🐱 kluless' korner 🐶"));
-    
+
+    let widget = Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(
+        if crate::capabilities::get().wide_emoji {
+            "🐱 kluless' korner 🐶"
+        } else {
+            "kluless' korner"
+        },
+    ));
+
     frame.render_widget(widget, rect);
 }
@@ -5,6 +5,8 @@ use super::{
 };
 #[cfg(feature = "image")]
 use crate::state::ImageRenderInfo;
+use crate::state::{ABLoop, ConnectionStatus};
+use crate::ui::utils::glyphs;
 use crate::ui::utils::{format_genres, to_bidi_string};
 #[cfg(feature = "image")]
 use anyhow::{Context, Result};
@@ -15,6 +17,7 @@ use rspotify::model::Id;
 /// - playback metadata (playing state, repeat state, shuffle state, volume, device, etc)
 /// - cover image (if `image` feature is enabled)
 /// - playback progress bar
+/// - the next queued tracks (if `up_next_num` is positive)
 pub fn render_playback_window(
     frame: &mut Frame,
     state: &SharedState,
@@ -22,11 +25,56 @@ pub fn render_playback_window(
     rect: Rect,
 ) -> Rect {
     let (rect, other_rect) = split_rect_for_playback_window(rect);
-    let rect = construct_and_render_block("Playback", &ui.theme, Borders::ALL, frame, rect);
-
     let player = state.player.read();
+    let mut title = match player.connection_status {
+        ConnectionStatus::Online => "Playback".to_string(),
+        status => format!("Playback ({})", status.desc()),
+    };
+    if crate::state::is_private_session() {
+        title.push_str(" [private]");
+    }
+    if ui.macro_recording.is_some() {
+        title.push_str(" [recording macro]");
+    }
+    #[cfg(feature = "fx")]
+    let block_rect = rect;
+    let rect = construct_and_render_block(&title, &ui.theme, Borders::ALL, frame, rect);
+
+    // flash the window's border on track change
+    #[cfg(feature = "fx")]
+    if let Some(progress) = ui
+        .enable_effects
+        .then(|| ui.effects_state.track_change_progress(ui.clock.now()))
+        .flatten()
+    {
+        #[cfg(feature = "image")]
+        let album_color = ui
+            .current_album_color
+            .map(|(r, g, b)| ratatui::style::Color::Rgb(r, g, b));
+        #[cfg(not(feature = "image"))]
+        let album_color = None;
+        let flash = album_color
+            .or(ui.theme.playback_track().fg)
+            .unwrap_or(ratatui::style::Color::Cyan);
+        crate::ui::effects::flash_border(
+            frame.buffer_mut(),
+            block_rect,
+            flash,
+            ui.theme.border().fg,
+            progress,
+        );
+    }
+
     if let Some(ref playback) = player.playback {
         if let Some(item) = &playback.item {
+            let rect = if config::get_config().app_config.up_next_num > 0 {
+                let (rect, up_next_rect) = split_rect_for_progress_bar(rect);
+                render_up_next_strip(frame, ui, &player, up_next_rect);
+                rect
+            } else {
+                rect
+            };
+
             let (metadata_rect, progress_bar_rect) = {
                 // Render the track's cover image if `image` feature is enabled
                 #[cfg(feature = "image")]
@@ -133,6 +181,14 @@ pub fn render_playback_window(
                 duration,
             );
             render_playback_progress_bar(frame, ui, progress, duration, progress_bar_rect);
+            render_beat_grid(frame, state, item, duration, progress_bar_rect);
+            render_ab_loop_markers(
+                frame,
+                ui,
+                player.ab_loop.as_ref(),
+                duration,
+                progress_bar_rect,
+            );
             return other_rect;
         }
     }
@@ -164,6 +220,290 @@ pub fn render_playback_window(
     other_rect
 }
 
+/// Render a line announcing the currently playing item at the bottom of the application.
+///
+/// The line only changes with the playing item, so screen readers following the terminal's
+/// output can read it out on track changes.
+/// Returns the remaining rectangle to render other widgets.
+pub fn render_track_announcement(
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &UIStateGuard,
+    rect: Rect,
+) -> Rect {
+    if !config::get_config()
+        .app_config
+        .accessibility
+        .announce_track_changes
+    {
+        return rect;
+    }
+    let chunks = Layout::vertical([Constraint::Fill(0), Constraint::Length(1)]).split(rect);
+
+    let player = state.player.read();
+    let announcement = match player.currently_playing() {
+        Some(rspotify::model::PlayableItem::Track(track)) => format!(
+            "Now playing: {} by {}, from {}",
+            track.name,
+            crate::utils::map_join(&track.artists, |a| &a.name, ", "),
+            track.album.name
+        ),
+        Some(rspotify::model::PlayableItem::Episode(episode)) => {
+            format!("Now playing: {} from {}", episode.name, episode.show.name)
+        }
+        Some(rspotify::model::PlayableItem::Unknown(_)) | None => "Nothing is playing".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            to_bidi_string(&announcement),
+            ui.theme.playback_metadata(),
+        )),
+        chunks[1],
+    );
+
+    chunks[0]
+}
+
+/// Render the status bar (if any segment is configured) at the bottom of the given rectangle,
+/// returning the remaining rectangle
+pub fn render_status_bar(
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &UIStateGuard,
+    rect: Rect,
+) -> Rect {
+    use config::StatusBarSegmentType;
+    use std::fmt::Write;
+
+    let segments = &config::get_config().app_config.status_bar_segments;
+    if segments.is_empty() {
+        return rect;
+    }
+    let chunks = Layout::vertical([Constraint::Fill(0), Constraint::Length(1)]).split(rect);
+
+    let player = state.player.read();
+    let now = chrono::Local::now();
+    let texts = segments
+        .iter()
+        .filter_map(|segment| {
+            let format = segment
+                .format
+                .as_deref()
+                .unwrap_or_else(|| segment.typ.default_format());
+            let value = match segment.typ {
+                StatusBarSegmentType::Clock | StatusBarSegmentType::Date => {
+                    let mut text = String::new();
+                    // formatting fails if the format is an invalid `strftime` format
+                    if write!(text, "{}", now.format(format)).is_err() {
+                        return Some(format!("invalid format: {format}"));
+                    }
+                    return Some(text);
+                }
+                StatusBarSegmentType::Network => player.connection_status.desc().to_string(),
+                StatusBarSegmentType::Shuffle => {
+                    let playback = player.buffered_playback.as_ref()?;
+                    if playback.shuffle_state { "on" } else { "off" }.to_string()
+                }
+                StatusBarSegmentType::Repeat => {
+                    let playback = player.buffered_playback.as_ref()?;
+                    if playback.fake_track_repeat_state {
+                        "track (fake)".to_string()
+                    } else {
+                        <&'static str>::from(playback.repeat_state).to_string()
+                    }
+                }
+                StatusBarSegmentType::Volume => {
+                    let playback = player.buffered_playback.as_ref()?;
+                    match playback.mute_state {
+                        Some(volume) => format!("{volume}% (muted)"),
+                        None => format!("{}%", playback.volume.unwrap_or_default()),
+                    }
+                }
+            };
+            Some(format.replace("{}", &value))
+        })
+        .collect::<Vec<_>>();
+
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            texts.join(" | "),
+            ui.theme.playback_metadata(),
+        ))
+        .right_aligned(),
+        chunks[1],
+    );
+    chunks[0]
+}
+
+/// Render a compact single-row playback window, which is used when the terminal is too small
+/// (e.g. a 2-3 lines tmux pane) or when the application is run with `--compact`.
+///
+/// The window consists of the playing status, a scrolling (marquee) title, the playback progress bar
+/// and minimal mouse-clickable playback controls.
+pub fn render_compact_playback_window(
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    // the cover image is not rendered in the compact mode, clear the previously rendered one
+    #[cfg(feature = "image")]
+    {
+        if ui.last_cover_image_render_info.rendered {
+            clear_area(
+                frame,
+                ui.last_cover_image_render_info.render_area,
+                &ui.theme,
+            );
+            ui.last_cover_image_render_info = ImageRenderInfo::default();
+        }
+    }
+
+    let rect = Rect { height: 1, ..rect };
+
+    let player = state.player.read();
+    let (Some(item), Some(playback)) = (
+        player.currently_playing(),
+        player.buffered_playback.as_ref(),
+    ) else {
+        frame.render_widget(Paragraph::new("No playback found"), rect);
+        return;
+    };
+
+    let (title, duration) = match item {
+        rspotify::model::PlayableItem::Track(track) => (
+            format!(
+                "{} {} {}",
+                to_bidi_string(&track.name),
+                glyphs().separator,
+                to_bidi_string(&crate::utils::map_join(&track.artists, |a| &a.name, ", "))
+            ),
+            track.duration,
+        ),
+        rspotify::model::PlayableItem::Episode(episode) => (
+            format!(
+                "{} {} {}",
+                to_bidi_string(&episode.name),
+                glyphs().separator,
+                to_bidi_string(&episode.show.publisher)
+            ),
+            episode.duration,
+        ),
+        rspotify::model::PlayableItem::Unknown(_) => {
+            frame.render_widget(Paragraph::new("Unknown playback item"), rect);
+            return;
+        }
+    };
+    let title = if crate::state::is_private_session() {
+        format!("[private] {title}")
+    } else {
+        title
+    };
+    let progress = std::cmp::min(player.playback_progress().unwrap_or_default(), duration);
+
+    let configs = config::get_config();
+    let status = if playback.is_playing {
+        &configs.app_config.play_icon
+    } else {
+        &configs.app_config.pause_icon
+    };
+    let controls = glyphs().controls;
+
+    let chunks = Layout::horizontal([
+        Constraint::Length(status.chars().count() as u16),
+        Constraint::Fill(3),
+        Constraint::Fill(2),
+        Constraint::Length(controls.chars().count() as u16),
+    ])
+    .spacing(1)
+    .split(rect);
+
+    frame.render_widget(
+        Paragraph::new(Span::styled(status.clone(), ui.theme.playback_status())),
+        chunks[0],
+    );
+    // scroll the title by one character every 250ms of the playback's progress
+    let offset = if config::get_config().app_config.accessibility.reduce_motion {
+        0
+    } else {
+        usize::try_from(progress.num_milliseconds() / 250).unwrap_or_default()
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            marquee(&title, chunks[1].width as usize, offset),
+            ui.theme.playback_track(),
+        )),
+        chunks[1],
+    );
+    render_playback_progress_bar(frame, ui, progress, duration, chunks[2]);
+    render_beat_grid(frame, state, item, duration, chunks[2]);
+    render_ab_loop_markers(frame, ui, player.ab_loop.as_ref(), duration, chunks[2]);
+    frame.render_widget(
+        Paragraph::new(Span::styled(controls, ui.theme.playback_metadata())),
+        chunks[3],
+    );
+    ui.compact_controls_rect = chunks[3];
+}
+
+/// Return a `width`-character window of a text scrolled by `offset` characters.
+/// The text is not scrolled if it fits into the window.
+fn marquee(text: &str, width: usize, offset: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+
+    // add a gap between the end of the text and its next repetition
+    let text = format!("{text}   ");
+    text.chars()
+        .cycle()
+        .skip(offset % (len + 3))
+        .take(width)
+        .collect()
+}
+
+/// Render a single line listing the next queued tracks under the playback progress bar
+fn render_up_next_strip(
+    frame: &mut Frame,
+    ui: &UIStateGuard,
+    player: &crate::state::PlayerState,
+    rect: Rect,
+) {
+    let Some(queue) = player.queue.as_ref() else {
+        return;
+    };
+    let n = config::get_config().app_config.up_next_num;
+    let items = queue
+        .queue
+        .iter()
+        .take(n)
+        .filter_map(|item| match item {
+            rspotify::model::PlayableItem::Track(track) => Some(format!(
+                "{} {} {}",
+                track.name,
+                glyphs().separator,
+                crate::utils::map_join(&track.artists, |a| &a.name, ", ")
+            )),
+            rspotify::model::PlayableItem::Episode(episode) => Some(format!(
+                "{} {} {}",
+                episode.name,
+                glyphs().separator,
+                episode.show.name
+            )),
+            rspotify::model::PlayableItem::Unknown(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        return;
+    }
+
+    let text = format!("Up next: {}", items.join("  |  "));
+    frame.render_widget(
+        Paragraph::new(to_bidi_string(&text)).style(ui.theme.playback_up_next()),
+        rect,
+    );
+}
+
 fn split_rect_for_progress_bar(rect: Rect) -> (Rect, Rect) {
     let chunks = Layout::vertical([Constraint::Fill(0), Constraint::Length(1)]).split(rect);
     (chunks[0], chunks[1])
@@ -345,6 +685,22 @@ fn construct_playback_text(
                         "shuffle" => parts.push(format!("shuffle: {}", playback.shuffle_state)),
                         "volume" => parts.push(format!("volume: {volume_value}")),
                         "device" => parts.push(format!("device: {}", playback.device_name)),
+                        #[cfg(feature = "streaming")]
+                        // the stream quality is only known for the integrated player's playback
+                        "quality" if playback.device_name == configs.app_config.device.name => {
+                            if let Some(quality) = &state.player.read().stream_quality {
+                                parts.push(format!("quality: {quality}"));
+                            }
+                        }
+                        "context_end" => {
+                            let player = state.player.read();
+                            if let Some(id) = player.playing_context_id() {
+                                parts.push(format!(
+                                    "on end: {}",
+                                    player.context_end_behavior(&id).desc()
+                                ));
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -355,6 +711,19 @@ fn construct_playback_text(
             _ => continue,
         };
 
+        // sweep the new track's title in on track change
+        #[cfg(feature = "fx")]
+        let text = match ui
+            .enable_effects
+            .then(|| ui.effects_state.track_change_progress(ui.clock.now()))
+            .flatten()
+        {
+            Some(progress) if m.as_str() == "{track}" => {
+                crate::ui::effects::sweep_in(&text, progress)
+            }
+            _ => text,
+        };
+
         spans.push(Span::styled(text, style));
     }
     if ptr < format_str.len() {
@@ -367,7 +736,7 @@ fn construct_playback_text(
     playback_text
 }
 
-fn render_playback_progress_bar(
+pub(super) fn render_playback_progress_bar(
     frame: &mut Frame,
     ui: &mut UIStateGuard,
     progress: chrono::Duration,
@@ -381,14 +750,9 @@ fn render_playback_progress_bar(
     // Get animated color if effects are enabled
     #[cfg(feature = "fx")]
     let animated_style = if ui.enable_effects {
-        if let Some(color) = ui
-            .current_effect
-            .get_animated_color(ui.effects_state.elapsed(), ratio)
-        {
-            Some(Style::default().fg(color))
-        } else {
-            None
-        }
+        ui.current_effect
+            .get_animated_color(ui.effects_state.elapsed(ui.clock.now()), ratio)
+            .map(|color| Style::default().fg(color))
     } else {
         None
     };
@@ -426,10 +790,85 @@ fn render_playback_progress_bar(
             rect,
         ),
     }
-    
+
     ui.playback_progress_bar_rect = rect;
 }
 
+/// Render tick marks of the current track's bars on top of the playback progress bar
+/// if `enable_beat_grid` is enabled and the track's audio analysis is available
+fn render_beat_grid(
+    frame: &mut Frame,
+    state: &SharedState,
+    item: &rspotify::model::PlayableItem,
+    duration: chrono::Duration,
+    rect: Rect,
+) {
+    if !config::get_config().app_config.enable_beat_grid || rect.width == 0 {
+        return;
+    }
+    let rspotify::model::PlayableItem::Track(track) = item else {
+        return;
+    };
+    let Some(uri) = track.id.as_ref().map(Id::uri) else {
+        return;
+    };
+    let data = state.data.read();
+    let Some(analysis) = data.caches.audio_analysis.get(&uri) else {
+        return;
+    };
+
+    // only render every `step` bars, so that ticks are at least two cells apart
+    let mut step = 1;
+    while analysis.bars.len() / step > usize::from(rect.width) / 2 {
+        step *= 2;
+    }
+
+    let duration_secs = duration.num_milliseconds() as f64 / 1000.0;
+    for bar in analysis.bars.iter().step_by(step) {
+        let x = rect.x + (f64::from(bar.start) / duration_secs * f64::from(rect.width)) as u16;
+        if x >= rect.right() {
+            continue;
+        }
+        if let Some(cell) = frame.buffer_mut().cell_mut((x, rect.y)) {
+            // only draw ticks on the bar's line/filled cells, not on the progress label
+            let [empty_tick, line_tick, filled_tick] = glyphs().beat_ticks;
+            let tick = match cell.symbol() {
+                " " => empty_tick,
+                "─" => line_tick,
+                "━" => filled_tick,
+                _ => continue,
+            };
+            cell.set_symbol(tick);
+        }
+    }
+}
+
+/// Render markers of the A-B loop's points (if any) on top of the playback progress bar
+fn render_ab_loop_markers(
+    frame: &mut Frame,
+    ui: &UIStateGuard,
+    ab_loop: Option<&ABLoop>,
+    duration: chrono::Duration,
+    rect: Rect,
+) {
+    let Some(ab_loop) = ab_loop else {
+        return;
+    };
+    if rect.width == 0 || duration <= chrono::Duration::zero() {
+        return;
+    }
+
+    let markers = std::iter::once((ab_loop.a, "A")).chain(ab_loop.b.map(|b| (b, "B")));
+    for (position, marker) in markers {
+        let ratio = position.num_milliseconds() as f64 / duration.num_milliseconds() as f64;
+        let x = rect.x + (ratio.clamp(0.0, 1.0) * f64::from(rect.width - 1)) as u16;
+        if let Some(cell) = frame.buffer_mut().cell_mut((x, rect.y)) {
+            cell.set_symbol(marker)
+                .set_style(ui.theme.playback_status().add_modifier(Modifier::BOLD));
+        }
+    }
+}
+
 #[cfg(feature = "image")]
 fn render_playback_cover_image(state: &SharedState, ui: &mut UIStateGuard) -> Result<()> {
     fn remove_temp_files() -> Result<()> {
@@ -492,10 +931,14 @@ fn split_rect_for_playback_window(rect: Rect) -> (Rect, Rect) {
     let playback_width = std::cmp::max(configs.app_config.cover_img_width + 1, playback_width);
 
     // add lines for top/bottom borders depending on the progress bar's position
-    let num_lines = match configs.app_config.progress_bar_position {
+    let mut num_lines = match configs.app_config.progress_bar_position {
         config::ProgressBarPosition::Bottom => 2,
         config::ProgressBarPosition::Right => 1,
     };
+    // add a line for the "up next" strip
+    if configs.app_config.up_next_num > 0 {
+        num_lines += 1;
+    }
     let playback_width = (playback_width + num_lines) as u16;
 
     match configs.app_config.layout.playback_window_position {
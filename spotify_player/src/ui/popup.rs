@@ -1,15 +1,33 @@
-use crate::utils::filtered_items_from_query;
+use crate::{
+    state::{group_tracks_by_decade, TrackAnnotation, TrackRemoval, TRACK_RATING_CHOICES},
+    utils::filtered_items_from_query,
+};
 
 use super::{
     config, utils, utils::construct_and_render_block, Borders, Cell, Constraint, Frame, Layout,
     Paragraph, PlaylistCreateCurrentField, PlaylistPopupAction, PopupState, Rect, Row, SharedState,
-    Table, UIStateGuard,
+    Span, Table, UIStateGuard,
 };
+use crate::ui::single_line_input::LineInput;
+
+/// the maximum length of a playlist's description allowed by Spotify
+const PLAYLIST_DESCRIPTION_MAX_LENGTH: usize = 300;
+
+/// the duration for which a toast message is shown
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(4);
 
 const SHORTCUT_TABLE_N_COLUMNS: usize = 3;
 const SHORTCUT_TABLE_CONSTRAINS: [Constraint; SHORTCUT_TABLE_N_COLUMNS] =
     [Constraint::Ratio(1, 3); 3];
 
+/// Render a text input preceded by a prompt
+fn render_prompted_line_input(frame: &mut Frame, prompt: &str, input: &LineInput, rect: Rect) {
+    let width = u16::try_from(Span::raw(prompt).width()).unwrap_or(u16::MAX);
+    let chunks = Layout::horizontal([Constraint::Length(width), Constraint::Fill(0)]).split(rect);
+    frame.render_widget(Paragraph::new(prompt), chunks[0]);
+    frame.render_widget(input.widget(true), chunks[1]);
+}
+
 /// Render a popup (if any) to handle a command or show additional information
 /// depending on the current popup state.
 ///
@@ -62,6 +80,258 @@ pub fn render_popup(
                 );
                 (chunks[0], true)
             }
+            PopupState::EpisodeBookmarkCreate { position, note, .. } => {
+                let chunks =
+                    Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(rect);
+
+                let note_input = construct_and_render_block(
+                    &format!(
+                        "Enter Note for Bookmark at {}:",
+                        crate::utils::format_duration(position)
+                    ),
+                    &ui.theme,
+                    Borders::ALL,
+                    frame,
+                    chunks[1],
+                );
+
+                frame.render_widget(note.widget(true), note_input);
+                (chunks[0], true)
+            }
+            PopupState::TrackRemovalConfirmation(track, removal) => {
+                let chunks =
+                    Layout::vertical([Constraint::Min(0), Constraint::Length(4)]).split(rect);
+
+                let (title, message) = match removal {
+                    TrackRemoval::Playlist(_, name) => (
+                        format!("Remove From Playlist {name}?"),
+                        format!(
+                            "{} will be removed from this playlist only, it stays in your {}.",
+                            track.name,
+                            if state.data.read().is_liked_track(track) {
+                                "liked songs and other playlists"
+                            } else {
+                                "other playlists"
+                            }
+                        ),
+                    ),
+                    TrackRemoval::Liked => (
+                        "Unlike Track?".to_string(),
+                        format!(
+                            "{} will be removed from your liked songs everywhere, it stays in your playlists.",
+                            track.name
+                        ),
+                    ),
+                };
+                let rect =
+                    construct_and_render_block(&title, &ui.theme, Borders::ALL, frame, chunks[1]);
+                frame.render_widget(
+                    Paragraph::new(format!("{message}\n[y] confirm  [n] cancel")),
+                    rect,
+                );
+                (chunks[0], false)
+            }
+            PopupState::PlaylistRename { name, .. } => {
+                let chunks =
+                    Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(rect);
+
+                let name_input = construct_and_render_block(
+                    "Enter New Name for Playlist:",
+                    &ui.theme,
+                    Borders::ALL,
+                    frame,
+                    chunks[1],
+                );
+
+                frame.render_widget(name.widget(true), name_input);
+                (chunks[0], true)
+            }
+            PopupState::PlaylistDescriptionEdit { desc, .. } => {
+                let chunks =
+                    Layout::vertical([Constraint::Min(0), Constraint::Length(10)]).split(rect);
+
+                let desc_input = construct_and_render_block(
+                    &format!(
+                        "Edit Playlist Description ({}/{PLAYLIST_DESCRIPTION_MAX_LENGTH}, C-s to save, esc to cancel):",
+                        desc.n_chars()
+                    ),
+                    &ui.theme,
+                    Borders::ALL,
+                    frame,
+                    chunks[1],
+                );
+
+                frame.render_widget(desc.widget(true), desc_input);
+                (chunks[0], true)
+            }
+            PopupState::EpisodeBookmarkList(episode_uri, _) => {
+                let items = state
+                    .data
+                    .read()
+                    .user_data
+                    .episode_bookmarks
+                    .get(episode_uri)
+                    .map(|bookmarks| {
+                        bookmarks
+                            .iter()
+                            .map(|b| {
+                                (
+                                    format!(
+                                        "{} | {}",
+                                        crate::utils::format_duration(
+                                            &chrono::Duration::milliseconds(b.position_ms)
+                                        ),
+                                        b.note
+                                    ),
+                                    false,
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let rect = render_list_popup(frame, rect, "Episode Bookmarks", items, 7, ui);
+                (rect, false)
+            }
+            PopupState::TrackTagsEdit { tags, .. } => {
+                let chunks =
+                    Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(rect);
+
+                let tags_input = construct_and_render_block(
+                    "Enter Tags for Track (comma-separated):",
+                    &ui.theme,
+                    Borders::ALL,
+                    frame,
+                    chunks[1],
+                );
+
+                frame.render_widget(tags.widget(true), tags_input);
+                (chunks[0], true)
+            }
+            PopupState::TrackRating(track_uri, _) => {
+                let current_rating = state
+                    .data
+                    .read()
+                    .user_data
+                    .track_annotations
+                    .get(track_uri)
+                    .and_then(|a| a.rating);
+                let items = TRACK_RATING_CHOICES
+                    .iter()
+                    .map(|&rating| match rating {
+                        Some(_) => (
+                            TrackAnnotation {
+                                rating,
+                                tags: vec![],
+                            }
+                            .to_string(),
+                            rating == current_rating,
+                        ),
+                        None => ("No rating".to_string(), current_rating.is_none()),
+                    })
+                    .collect();
+
+                let rect = render_list_popup(frame, rect, "Rate Track", items, 8, ui);
+                (rect, false)
+            }
+            PopupState::TrackDetails(track_uri, _) => {
+                let data = state.data.read();
+                let (title, items) = match data.caches.track_details.get(track_uri) {
+                    Some(details) => (
+                        format!("Details of {}", details.name),
+                        details
+                            .fields()
+                            .into_iter()
+                            .map(|(label, value)| (format!("{label}: {value}"), false))
+                            .collect(),
+                    ),
+                    None => ("Loading Track Details...".to_string(), vec![]),
+                };
+
+                let rect = render_list_popup(frame, rect, &title, items, 14, ui);
+                (rect, false)
+            }
+            PopupState::PlaylistMerge {
+                playlists,
+                chosen,
+                order,
+                deduplicate,
+                ..
+            } => {
+                let n_chosen = chosen.iter().filter(|c| **c).count();
+                let mut items = vec![
+                    (format!("Order: {order}"), false),
+                    (
+                        format!(
+                            "Remove duplicated tracks: {}",
+                            if *deduplicate { "yes" } else { "no" }
+                        ),
+                        false,
+                    ),
+                    (
+                        format!("Merge {n_chosen} playlists into a new playlist"),
+                        false,
+                    ),
+                ];
+                items.extend(playlists.iter().zip(chosen).map(|(p, chosen)| {
+                    (
+                        format!("[{}] {}", if *chosen { "x" } else { " " }, p.name),
+                        *chosen,
+                    )
+                }));
+
+                let rect = render_list_popup(
+                    frame,
+                    rect,
+                    "Merge Playlists (press enter to choose)",
+                    items,
+                    12,
+                    ui,
+                );
+                (rect, false)
+            }
+            PopupState::LikedTrackGenreList(_) => {
+                let items = state
+                    .data
+                    .read()
+                    .user_data
+                    .saved_tracks_by_genre()
+                    .into_iter()
+                    .map(|(genre, tracks)| (format!("{genre} ({})", tracks.len()), false))
+                    .collect();
+
+                let rect = render_list_popup(frame, rect, "Liked Tracks by Genre", items, 10, ui);
+                (rect, false)
+            }
+            PopupState::TrackDecadeList { name, tracks, .. } => {
+                let items = group_tracks_by_decade(tracks)
+                    .into_iter()
+                    .map(|(decade, tracks)| (format!("{decade}s ({})", tracks.len()), false))
+                    .collect();
+
+                let rect =
+                    render_list_popup(frame, rect, &format!("{name} by Decade"), items, 10, ui);
+                (rect, false)
+            }
+            PopupState::SmartPlaylistList(_) => {
+                let items = config::get_config()
+                    .app_config
+                    .smart_playlists
+                    .iter()
+                    .map(|p| (p.name.clone(), false))
+                    .collect();
+
+                let rect = render_list_popup(frame, rect, "Smart Playlists", items, 10, ui);
+                (rect, false)
+            }
+            #[cfg(feature = "fx")]
+            PopupState::VisualizationPreferenceTargets(targets, _) => {
+                let items = targets.iter().map(|t| (t.to_string(), false)).collect();
+
+                let rect =
+                    render_list_popup(frame, rect, "Remember Visualization For", items, 10, ui);
+                (rect, false)
+            }
             PopupState::Search { query } => {
                 let chunks =
                     Layout::vertical([Constraint::Fill(0), Constraint::Length(3)]).split(rect);
@@ -69,7 +339,7 @@ pub fn render_popup(
                 let rect =
                     construct_and_render_block("Search", &ui.theme, Borders::ALL, frame, chunks[1]);
 
-                frame.render_widget(Paragraph::new(format!("/{query}")), rect);
+                render_prompted_line_input(frame, "/", query, rect);
                 (chunks[0], true)
             }
             PopupState::ActionList(item, _) => {
@@ -97,10 +367,25 @@ pub fn render_popup(
                 let items = player
                     .devices
                     .iter()
-                    .map(|d| (format!("{} | {}", d.name, d.id), current_device_id == d.id))
+                    .map(|d| {
+                        let marker = if player.device_group.contains(&d.id) {
+                            "[G] "
+                        } else {
+                            ""
+                        };
+                        (
+                            format!("{marker}{} | {}", d.name, d.id),
+                            current_device_id == d.id,
+                        )
+                    })
                     .collect();
 
-                let rect = render_list_popup(frame, rect, "Devices", items, 5, ui);
+                let title = if player.device_group.is_empty() {
+                    "Devices".to_string()
+                } else {
+                    format!("Devices (group: {})", player.device_group.len())
+                };
+                let rect = render_list_popup(frame, rect, &title, items, 5, ui);
                 (rect, false)
             }
             PopupState::ThemeList(themes, ..) => {
@@ -115,6 +400,11 @@ pub fn render_popup(
                     PlaylistPopupAction::Browse {
                         folder_id,
                         search_query,
+                    }
+                    | PlaylistPopupAction::Diff {
+                        folder_id,
+                        search_query,
+                        ..
                     } => (
                         data.user_data.folder_playlists_items(*folder_id),
                         search_query,
@@ -135,7 +425,7 @@ pub fn render_popup(
                 };
 
                 // Filter items based on search query if present
-                let filtered_items = filtered_items_from_query(search_query, &items);
+                let filtered_items = filtered_items_from_query(&search_query.get_text(), &items);
 
                 let display_items = filtered_items
                     .iter()
@@ -157,11 +447,49 @@ pub fn render_popup(
                     frame,
                     chunks[0],
                 );
-                frame.render_widget(Paragraph::new(format!("🔍 {search_query}")), search_rect);
+                let prompt = if crate::capabilities::get().wide_emoji {
+                    "🔍 "
+                } else {
+                    "/ "
+                };
+                render_prompted_line_input(frame, prompt, search_query, search_rect);
 
                 // Render filtered playlist list
-                let rect =
-                    render_list_popup(frame, chunks[2], "User Playlists", display_items, 10, ui);
+                let title = match action {
+                    PlaylistPopupAction::Diff { playlist, .. } => {
+                        format!("Compare \"{}\" With", playlist.name)
+                    }
+                    _ => "User Playlists".to_string(),
+                };
+                let rect = render_list_popup(frame, chunks[2], &title, display_items, 10, ui);
+                (rect, false)
+            }
+            PopupState::CommandPalette { query, entries, .. } => {
+                let text = query.get_text();
+                let items = if crate::utils::is_spotify_link(&text) {
+                    vec![(format!("Open Spotify link: {}", text.trim()), false)]
+                } else if let Some(file) = crate::utils::theme_import_path(&text) {
+                    vec![(format!("Import theme from {}", file.display()), false)]
+                } else {
+                    filtered_items_from_query(&text, entries)
+                        .into_iter()
+                        .map(|e| (e.to_string(), false))
+                        .collect()
+                };
+
+                let chunks =
+                    Layout::vertical([Constraint::Length(3), Constraint::Fill(0)]).split(rect);
+
+                let query_rect = construct_and_render_block(
+                    "Command Palette (type to search, enter to run)",
+                    &ui.theme,
+                    Borders::ALL,
+                    frame,
+                    chunks[0],
+                );
+                render_prompted_line_input(frame, "> ", query, query_rect);
+
+                let rect = render_list_popup(frame, chunks[1], "Commands", items, 15, ui);
                 (rect, false)
             }
             PopupState::UserFollowedArtistList { .. } => {
@@ -272,3 +600,127 @@ pub fn render_shortcut_help_popup(frame: &mut Frame, ui: &mut UIStateGuard, rect
         chunks[0]
     }
 }
+
+/// Render an overlay showing the application's performance statistics at the top-right corner
+/// Render the toast message (if any) at the bottom of the application, hiding it after it expires
+pub fn render_toast(frame: &mut Frame, ui: &mut UIStateGuard, rect: Rect) {
+    let Some((message, shown_at)) = &ui.toast else {
+        return;
+    };
+    if shown_at.elapsed() > TOAST_DURATION {
+        ui.toast = None;
+        return;
+    }
+
+    let width = (message.chars().count() as u16 + 2).min(rect.width);
+    let height = 3.min(rect.height);
+    let rect = Rect {
+        x: rect.x + (rect.width - width) / 2,
+        y: rect.bottom().saturating_sub(height),
+        width,
+        height,
+    };
+    frame.render_widget(ratatui::widgets::Clear, rect);
+    let rect = construct_and_render_block("", &ui.theme, Borders::ALL, frame, rect);
+    frame.render_widget(Paragraph::new(message.as_str()), rect);
+}
+
+pub fn render_performance_overlay(
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &UIStateGuard,
+    rect: Rect,
+) {
+    let stats = &ui.render_stats;
+    let mut lines = vec![
+        format!("FPS: {}", stats.fps()),
+        format!("frame: {:.1?}", stats.frame_duration),
+        format!(
+            "  layout & drawing: {:.1?}",
+            stats
+                .frame_duration
+                .saturating_sub(stats.visualization_duration)
+        ),
+        format!("  visualization: {:.1?}", stats.visualization_duration),
+        format!("last API request: {:.1?}", state.api_stats.last_duration()),
+        format!("pending API requests: {}", state.api_stats.n_pending()),
+    ];
+
+    #[cfg(feature = "streaming")]
+    {
+        let stats = &state.playback_stats;
+        lines.extend([
+            "playback:".to_string(),
+            format!(
+                "  bufferings: {} ({:.1?})",
+                stats.n_bufferings(),
+                stats.buffering_duration()
+            ),
+            format!("  underruns: {}", stats.n_underruns()),
+            format!("  reconnects: {}", stats.n_reconnects()),
+        ]);
+    }
+
+    let capabilities = crate::capabilities::get();
+    lines.extend([
+        "terminal:".to_string(),
+        format!(
+            "  truecolor: {}, unicode: {}, wide emoji: {}",
+            capabilities.truecolor, capabilities.unicode, capabilities.wide_emoji
+        ),
+        format!(
+            "  OSC 52: {}, mouse: {}, light background: {}",
+            capabilities.osc52, capabilities.mouse, capabilities.light_background
+        ),
+    ]);
+    #[cfg(feature = "image")]
+    lines.push(format!("  images: {:?}", capabilities.image_protocol));
+
+    lines.push("cache entries:".to_string());
+
+    {
+        // iterating a cache requires mutable access to clean up its expired entries
+        let mut data = state.data.write();
+        let caches = &mut data.caches;
+        lines.push(format!("  contexts: {}", caches.context.iter().count()));
+        lines.push(format!("  searches: {}", caches.search.iter().count()));
+        lines.push(format!("  lyrics: {}", caches.lyrics.iter().count()));
+        lines.push(format!("  genres: {}", caches.genres.iter().count()));
+        lines.push(format!(
+            "  saved statuses: {}",
+            caches.saved_tracks_status.iter().count()
+        ));
+        lines.push(format!(
+            "  audio analyses: {}",
+            caches.audio_analysis.iter().count()
+        ));
+        #[cfg(feature = "image")]
+        {
+            let (n_images, n_bytes) =
+                caches.images.iter().fold((0, 0), |(n, bytes), (_, image)| {
+                    (n + 1, bytes + image.as_bytes().len())
+                });
+            lines.push(format!(
+                "  images: {n_images} ({:.1} MB)",
+                n_bytes as f64 / 1_000_000.0
+            ));
+        }
+    }
+
+    let width = lines
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or_default() as u16
+        + 2;
+    let height = lines.len() as u16 + 2;
+    let rect = Rect {
+        x: rect.right().saturating_sub(width),
+        y: rect.y,
+        width: width.min(rect.width),
+        height: height.min(rect.height),
+    };
+    frame.render_widget(ratatui::widgets::Clear, rect);
+    let rect = construct_and_render_block("Performance", &ui.theme, Borders::ALL, frame, rect);
+    frame.render_widget(Paragraph::new(lines.join("\n")), rect);
+}
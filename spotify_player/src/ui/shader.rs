@@ -0,0 +1,384 @@
+//! A small expression language for user-defined visualizations (the `visualization_shader` config option).
+//!
+//! A shader is an expression computing the intensity (from 0 to 1) of each cell of the visualization
+//! from the cell's position, the playback's time and beats, and the audio bands,
+//! e.g. `0.5 + 0.5 * sin(10 * x - 2 * t) * (1 - fract(beat))`.
+//! It's compiled once into a syntax tree, which is evaluated for each cell when rendering.
+
+use anyhow::{bail, Result};
+
+/// The number of audio bands, which are the pitch classes of the track's audio analysis
+pub const N_BANDS: usize = 12;
+
+/// The values of a shader's variables for a cell
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Inputs {
+    /// the cell's column, from 0 (left) to 1 (right)
+    pub x: f64,
+    /// the cell's row, from 0 (top) to 1 (bottom)
+    pub y: f64,
+    /// the playback's position in seconds
+    pub t: f64,
+    /// the number of beats played in the track
+    pub beat: f64,
+    /// the track's current loudness, from 0 to 1
+    pub loudness: f64,
+    /// the strength of each audio band, from 0 to 1
+    pub bands: [f64; N_BANDS],
+}
+
+/// A compiled shader
+#[derive(Debug, Clone)]
+pub struct Shader(Expr);
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Num(f64),
+    Var(Var),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Var {
+    X,
+    Y,
+    T,
+    Beat,
+    Loudness,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Func {
+    Sin,
+    Cos,
+    Tan,
+    Abs,
+    Sqrt,
+    Exp,
+    Floor,
+    Fract,
+    Min,
+    Max,
+    Clamp,
+    Pow,
+    Mix,
+    Hash,
+    Band,
+}
+
+impl Func {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "tan" => Self::Tan,
+            "abs" => Self::Abs,
+            "sqrt" => Self::Sqrt,
+            "exp" => Self::Exp,
+            "floor" => Self::Floor,
+            "fract" => Self::Fract,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "clamp" => Self::Clamp,
+            "pow" => Self::Pow,
+            "mix" => Self::Mix,
+            "hash" => Self::Hash,
+            "band" => Self::Band,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Self::Sin
+            | Self::Cos
+            | Self::Tan
+            | Self::Abs
+            | Self::Sqrt
+            | Self::Exp
+            | Self::Floor
+            | Self::Fract
+            | Self::Hash
+            | Self::Band => 1,
+            Self::Min | Self::Max | Self::Pow => 2,
+            Self::Clamp | Self::Mix => 3,
+        }
+    }
+}
+
+impl Shader {
+    /// Compile a shader from its source expression
+    pub fn compile(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+        };
+        let expr = parser.expr()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            bail!("unexpected `{token}` after the expression");
+        }
+        Ok(Self(expr))
+    }
+
+    /// Compute the intensity of a cell, from 0 to 1
+    pub fn intensity(&self, inputs: &Inputs) -> f64 {
+        let value = self.0.eval(inputs);
+        if value.is_finite() {
+            value.clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, inputs: &Inputs) -> f64 {
+        match self {
+            Self::Num(n) => *n,
+            Self::Var(var) => match var {
+                Var::X => inputs.x,
+                Var::Y => inputs.y,
+                Var::T => inputs.t,
+                Var::Beat => inputs.beat,
+                Var::Loudness => inputs.loudness,
+            },
+            Self::Neg(expr) => -expr.eval(inputs),
+            Self::Binary(op, lhs, rhs) => {
+                let (a, b) = (lhs.eval(inputs), rhs.eval(inputs));
+                let bool_value = |b: bool| if b { 1.0 } else { 0.0 };
+                match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                    Op::Rem => a.rem_euclid(b),
+                    Op::Pow => a.powf(b),
+                    Op::Lt => bool_value(a < b),
+                    Op::Gt => bool_value(a > b),
+                    Op::Le => bool_value(a <= b),
+                    Op::Ge => bool_value(a >= b),
+                }
+            }
+            Self::Call(func, args) => {
+                let arg = |i: usize| args[i].eval(inputs);
+                match func {
+                    Func::Sin => arg(0).sin(),
+                    Func::Cos => arg(0).cos(),
+                    Func::Tan => arg(0).tan(),
+                    Func::Abs => arg(0).abs(),
+                    Func::Sqrt => arg(0).sqrt(),
+                    Func::Exp => arg(0).exp(),
+                    Func::Floor => arg(0).floor(),
+                    Func::Fract => arg(0).rem_euclid(1.0),
+                    Func::Min => arg(0).min(arg(1)),
+                    Func::Max => arg(0).max(arg(1)),
+                    Func::Clamp => arg(0).clamp(arg(1).min(arg(2)), arg(2).max(arg(1))),
+                    Func::Pow => arg(0).powf(arg(1)),
+                    Func::Mix => {
+                        let (a, b, t) = (arg(0), arg(1), arg(2));
+                        a + (b - a) * t
+                    }
+                    Func::Hash => super::visualizations::hash01(arg(0)),
+                    Func::Band => {
+                        let i = arg(0);
+                        if i.is_finite() {
+                            inputs.bands[(i.floor() as i64).rem_euclid(N_BANDS as i64) as usize]
+                        } else {
+                            0.0
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Num(n) => write!(f, "{n}"),
+            Self::Ident(name) => write!(f, "{name}"),
+            Self::Symbol(symbol) => write!(f, "{symbol}"),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 13] = [
+    "<=", ">=", "+", "-", "*", "/", "%", "^", "<", ">", "(", ")", ",",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_ascii_digit() || c == '.' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = &rest[..len];
+            match number.parse() {
+                Ok(n) => tokens.push(Token::Num(n)),
+                Err(_) => bail!("invalid number `{number}`"),
+            }
+            len
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].to_string()));
+            len
+        } else if let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(*s)) {
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        } else {
+            bail!("unexpected character `{c}`");
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser of a shader's expression, from the lowest to the highest precedence:
+/// comparisons, sums, products, negations and powers
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_symbol(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Symbol(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<()> {
+        if self.peek_symbol() == Some(symbol) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            bail!("expected `{symbol}`")
+        }
+    }
+
+    fn binary(&mut self, ops: &[(&str, Op)], next: fn(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        let mut lhs = next(self)?;
+        while let Some(&(_, op)) = ops.iter().find(|(s, _)| self.peek_symbol() == Some(s)) {
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(next(self)?));
+        }
+        Ok(lhs)
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.binary(
+            &[("<", Op::Lt), (">", Op::Gt), ("<=", Op::Le), (">=", Op::Ge)],
+            Self::sum,
+        )
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        self.binary(&[("+", Op::Add), ("-", Op::Sub)], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        self.binary(
+            &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.peek_symbol() == Some("-") {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr> {
+        let base = self.atom()?;
+        if self.peek_symbol() == Some("^") {
+            self.pos += 1;
+            // powers are right-associative
+            return Ok(Expr::Binary(
+                Op::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            bail!("unexpected end of the expression");
+        };
+        self.pos += 1;
+        match token {
+            Token::Num(n) => Ok(Expr::Num(n)),
+            Token::Symbol("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Ident(name) if self.peek_symbol() == Some("(") => {
+                let Some(func) = Func::from_name(&name) else {
+                    bail!("unknown function `{name}`");
+                };
+                self.pos += 1;
+                let mut args = vec![];
+                if self.peek_symbol() != Some(")") {
+                    args.push(self.expr()?);
+                    while self.peek_symbol() == Some(",") {
+                        self.pos += 1;
+                        args.push(self.expr()?);
+                    }
+                }
+                self.expect(")")?;
+                if args.len() != func.arity() {
+                    bail!(
+                        "function `{name}` takes {} argument(s), got {}",
+                        func.arity(),
+                        args.len()
+                    );
+                }
+                Ok(Expr::Call(func, args))
+            }
+            Token::Ident(name) => Ok(match name.as_str() {
+                "x" => Expr::Var(Var::X),
+                "y" => Expr::Var(Var::Y),
+                "t" => Expr::Var(Var::T),
+                "beat" => Expr::Var(Var::Beat),
+                "loudness" => Expr::Var(Var::Loudness),
+                "pi" => Expr::Num(std::f64::consts::PI),
+                _ => bail!("unknown variable `{name}`"),
+            }),
+            Token::Symbol(symbol) => bail!("unexpected `{symbol}`"),
+        }
+    }
+}
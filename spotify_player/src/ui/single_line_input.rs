@@ -4,6 +4,9 @@ use ratatui::widgets::Widget;
 use super::{Line, Modifier, Paragraph, Span, Style};
 use crate::key::Key;
 
+/// the maximum number of entries kept in an input history
+const MAX_HISTORY_SIZE: usize = 100;
+
 #[derive(Debug, Clone)]
 pub struct LineInput {
     // This is less space-efficient than String, but it's easier to work with text manipulation at the
@@ -29,55 +32,130 @@ impl Default for LineInput {
 impl LineInput {
     pub fn new(str: Vec<char>) -> Self {
         Self {
+            cursor: str.len(),
             line: str,
-            cursor: 0,
         }
     }
 
+    /// Handle a key pressed when the input is focused.
+    ///
+    /// Besides inserting characters, the input supports readline-like editing keys:
+    /// - `left`/`right` (`M-b`/`M-f` for words), `home`/`end` (`C-a`/`C-e`) to move the cursor
+    /// - `backspace`/`delete` to delete a character, `C-w`/`M-backspace` to delete a word
+    /// - `C-u`/`C-k` to delete the text before/after the cursor
     pub fn input(&mut self, key: &Key) -> Option<InputEffect> {
         match key {
             Key::None(c) => match c {
                 KeyCode::Char(c) => {
-                    if self.cursor == self.line.len() {
-                        self.line.push(*c);
-                    } else {
-                        self.line.insert(self.cursor, *c);
-                    }
+                    self.line.insert(self.cursor, *c);
                     self.cursor += 1;
                     Some(InputEffect::TextChanged)
                 }
-                KeyCode::Backspace => {
-                    if self.line.is_empty() || self.cursor == 0 {
+                KeyCode::Backspace => Some(self.delete_before(self.cursor.saturating_sub(1))),
+                KeyCode::Delete => {
+                    if self.cursor == self.line.len() {
                         Some(InputEffect::Ack)
                     } else {
-                        // Perform the decrement first.
-                        self.cursor -= 1;
                         self.line.remove(self.cursor);
                         Some(InputEffect::TextChanged)
                     }
                 }
-                KeyCode::Left => {
-                    if self.cursor == 0 {
-                        Some(InputEffect::Ack)
-                    } else {
-                        self.cursor -= 1;
-                        Some(InputEffect::CursorMoved)
-                    }
-                }
-                KeyCode::Right => {
+                KeyCode::Left => Some(self.move_cursor(self.cursor.saturating_sub(1))),
+                KeyCode::Right => Some(self.move_cursor(self.cursor + 1)),
+                KeyCode::Home => Some(self.move_cursor(0)),
+                KeyCode::End => Some(self.move_cursor(self.line.len())),
+                _ => None,
+            },
+            Key::Ctrl(c) => match c {
+                KeyCode::Char('a') => Some(self.move_cursor(0)),
+                KeyCode::Char('e') => Some(self.move_cursor(self.line.len())),
+                KeyCode::Char('w') => Some(self.delete_before(self.previous_word_start())),
+                KeyCode::Char('u') => Some(self.delete_before(0)),
+                KeyCode::Char('k') => {
                     if self.cursor == self.line.len() {
                         Some(InputEffect::Ack)
                     } else {
-                        self.cursor += 1;
-                        Some(InputEffect::CursorMoved)
+                        self.line.truncate(self.cursor);
+                        Some(InputEffect::TextChanged)
                     }
                 }
                 _ => None,
             },
-            _ => None,
+            Key::Alt(c) => match c {
+                KeyCode::Char('b') | KeyCode::Left => {
+                    Some(self.move_cursor(self.previous_word_start()))
+                }
+                KeyCode::Char('f') | KeyCode::Right => Some(self.move_cursor(self.next_word_end())),
+                KeyCode::Backspace => Some(self.delete_before(self.previous_word_start())),
+                _ => None,
+            },
+            Key::Unknown => None,
+        }
+    }
+
+    /// Move the cursor to a position, which is clamped to the text's length
+    fn move_cursor(&mut self, position: usize) -> InputEffect {
+        let position = position.min(self.line.len());
+        if position == self.cursor {
+            InputEffect::Ack
+        } else {
+            self.cursor = position;
+            InputEffect::CursorMoved
         }
     }
 
+    /// Delete the text between a position and the cursor
+    fn delete_before(&mut self, position: usize) -> InputEffect {
+        if position >= self.cursor {
+            InputEffect::Ack
+        } else {
+            self.line.drain(position..self.cursor);
+            self.cursor = position;
+            InputEffect::TextChanged
+        }
+    }
+
+    /// Get the start position of the word before the cursor
+    fn previous_word_start(&self) -> usize {
+        let mut position = self.cursor;
+        while position > 0 && !self.line[position - 1].is_alphanumeric() {
+            position -= 1;
+        }
+        while position > 0 && self.line[position - 1].is_alphanumeric() {
+            position -= 1;
+        }
+        position
+    }
+
+    /// Get the end position of the word after the cursor
+    fn next_word_end(&self) -> usize {
+        let mut position = self.cursor;
+        while position < self.line.len() && !self.line[position].is_alphanumeric() {
+            position += 1;
+        }
+        while position < self.line.len() && self.line[position].is_alphanumeric() {
+            position += 1;
+        }
+        position
+    }
+
+    /// Insert a text (e.g. a pasted text) at the cursor, in which line breaks are replaced by spaces
+    pub fn insert_str(&mut self, text: &str) {
+        let chars = text
+            .trim_end_matches(['\r', '\n'])
+            .chars()
+            .map(|c| if c == '\r' || c == '\n' { ' ' } else { c })
+            .collect::<Vec<_>>();
+        let n_chars = chars.len();
+        self.line.splice(self.cursor..self.cursor, chars);
+        self.cursor += n_chars;
+    }
+
+    /// Replace the input's text, moving the cursor to the end
+    pub fn set_text(&mut self, text: &str) {
+        *self = Self::new(text.chars().collect());
+    }
+
     pub fn widget(&self, is_active: bool) -> impl Widget {
         if !is_active {
             let converted_str: String = self.line.iter().collect();
@@ -115,3 +193,49 @@ impl LineInput {
         self.line.iter().collect()
     }
 }
+
+#[derive(Debug, Default)]
+/// A history of the texts submitted in an input, which can be browsed from the latest text
+pub struct InputHistory {
+    entries: Vec<String>,
+    /// the position of the browsed entry, or `None` if the history isn't being browsed
+    position: Option<usize>,
+}
+
+impl InputHistory {
+    /// Add a submitted text to the history, stopping the browsing
+    pub fn push(&mut self, text: String) {
+        self.position = None;
+        if text.is_empty() || self.entries.last() == Some(&text) {
+            return;
+        }
+        self.entries.retain(|e| *e != text);
+        self.entries.push(text);
+        if self.entries.len() > MAX_HISTORY_SIZE {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Browse to an older entry in the history
+    pub fn previous(&mut self) -> Option<&str> {
+        let position = match self.position {
+            None => self.entries.len().checked_sub(1)?,
+            Some(position) => position.saturating_sub(1),
+        };
+        self.position = Some(position);
+        Some(&self.entries[position])
+    }
+
+    /// Browse to a newer entry in the history, returning an empty text when browsing past
+    /// the latest entry or `None` if the history isn't being browsed
+    pub fn next(&mut self) -> Option<&str> {
+        let position = self.position? + 1;
+        if position < self.entries.len() {
+            self.position = Some(position);
+            Some(&self.entries[position])
+        } else {
+            self.position = None;
+            Some("")
+        }
+    }
+}
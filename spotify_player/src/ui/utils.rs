@@ -1,9 +1,21 @@
+use super::glyphs::{self, GlyphSet};
 use super::{
     config, Block, BorderType, Borders, Frame, List, ListItem, ListState, Rect, Span, Style, Table,
     TableState,
 };
 use unicode_bidi::BidiInfo;
 
+/// Get the configured glyph set
+pub fn glyphs() -> &'static GlyphSet {
+    if config::get_config().app_config.accessibility.ascii_only
+        || !crate::capabilities::get().unicode
+    {
+        &glyphs::ASCII
+    } else {
+        &glyphs::UNICODE
+    }
+}
+
 /// Construct and render a block.
 ///
 /// This function should only be used to render a window's borders and its title.
@@ -162,3 +174,236 @@ pub fn format_genres(genres: &[String], genre_num: u8) -> String {
 
     genre_str
 }
+
+/// Increase the contrast of a rendered buffer by removing the dim modifier
+/// and turning gray foreground colors and colors close to the background into
+/// white (or black for a light background)
+pub fn increase_contrast(buf: &mut ratatui::buffer::Buffer, light_background: bool) {
+    use ratatui::style::{Color, Modifier};
+
+    for cell in &mut buf.content {
+        cell.modifier.remove(Modifier::DIM);
+        cell.fg = match cell.fg {
+            Color::DarkGray | Color::Gray => {
+                if light_background {
+                    Color::Black
+                } else {
+                    Color::White
+                }
+            }
+            Color::Black if !light_background => Color::White,
+            Color::White if light_background => Color::Black,
+            // colors with a perceived luminance close to the background's
+            Color::Rgb(r, g, b) => {
+                let is_dark =
+                    299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b) < 128_000;
+                match (is_dark, light_background) {
+                    (true, false) => Color::White,
+                    (false, true) => Color::Black,
+                    _ => cell.fg,
+                }
+            }
+            color => color,
+        };
+    }
+}
+
+/// Replace the unicode symbols in a rendered buffer (box drawings, blocks, Braille patterns and icons)
+/// with plain ASCII characters. Other characters, e.g. in track names, are left unchanged.
+pub fn replace_unicode_symbols(buf: &mut ratatui::buffer::Buffer) {
+    for cell in &mut buf.content {
+        let mut chars = cell.symbol().chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            continue;
+        };
+        if let Some(c) = ascii_symbol(c) {
+            cell.set_char(c);
+        }
+    }
+}
+
+/// Return the ASCII replacement of a unicode symbol, if any
+// the specific symbols need to be matched before the unicode blocks containing them
+#[allow(clippy::match_same_arms)]
+fn ascii_symbol(c: char) -> Option<char> {
+    let c = match c {
+        '─' | '━' | '═' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' | '╴' | '╶' | '╸' | '╺' => {
+            '-'
+        }
+        '│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '╵' | '╷' | '╹' | '╻' => {
+            '|'
+        }
+        // the remaining box drawings are corners and junctions
+        '\u{2500}'..='\u{257F}' => '+',
+        '░' => '.',
+        '▒' => ':',
+        '▁' => '_',
+        '▌' | '▐' | '▏' | '▕' => '|',
+        '\u{2580}'..='\u{259F}' => '#',
+        '\u{2800}' => ' ',
+        '\u{2801}'..='\u{28FF}' => '.',
+        '▶' | '►' | '▸' | '⏵' | '⏭' => '>',
+        '◀' | '◄' | '◂' | '⏴' | '⏮' => '<',
+        '⏸' | '⏯' => '=',
+        '■' | '□' | '▪' | '▫' => '#',
+        '●' | '○' | '•' | '◆' | '◇' | '★' | '☆' | '♥' | '♡' | '❤' => '*',
+        '…' => '.',
+        '✓' | '✔' => 'v',
+        '✗' | '✘' | '×' => 'x',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// A type of color blindness, simulated to check the application's colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorBlindness {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl ColorBlindness {
+    /// Get the next simulated color blindness (`None` for no simulation), for cycling through the simulations
+    pub fn next(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(Self::Protanopia),
+            Some(Self::Protanopia) => Some(Self::Deuteranopia),
+            Some(Self::Deuteranopia) => Some(Self::Tritanopia),
+            Some(Self::Tritanopia) => None,
+        }
+    }
+
+    /// The matrix transforming linear RGB colors into the colors perceived with the color blindness,
+    /// from "A Physiologically-based Model for Simulation of Color Vision Deficiency" (Machado et al., 2009)
+    fn matrix(self) -> [[f64; 3]; 3] {
+        match self {
+            Self::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            Self::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            Self::Tritanopia => [
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for ColorBlindness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Protanopia => write!(f, "protanopia"),
+            Self::Deuteranopia => write!(f, "deuteranopia"),
+            Self::Tritanopia => write!(f, "tritanopia"),
+        }
+    }
+}
+
+/// Simulate a color blindness on the colors of a rendered buffer.
+/// The terminal's default colors are left unchanged, as their values are unknown.
+pub fn simulate_color_blindness(
+    buf: &mut ratatui::buffer::Buffer,
+    color_blindness: ColorBlindness,
+) {
+    let matrix = color_blindness.matrix();
+    for cell in &mut buf.content {
+        cell.fg = simulate_color(cell.fg, &matrix);
+        cell.bg = simulate_color(cell.bg, &matrix);
+    }
+}
+
+fn simulate_color(color: ratatui::style::Color, matrix: &[[f64; 3]; 3]) -> ratatui::style::Color {
+    use ratatui::style::Color;
+
+    // the ANSI colors are approximated with xterm's default values
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (127, 127, 127),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Reset | Color::Indexed(_) => return color,
+    };
+
+    let to_linear = |c: u8| {
+        let c = f64::from(c) / 255.0;
+        if c <= 0.040_45 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let to_srgb = |c: f64| {
+        let c = c.clamp(0.0, 1.0);
+        let c = if c <= 0.003_130_8 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (c * 255.0).round() as u8
+    };
+
+    let rgb = [to_linear(r), to_linear(g), to_linear(b)];
+    let [r, g, b] = matrix.map(|row| to_srgb(row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]));
+    Color::Rgb(r, g, b)
+}
+
+/// Replace the RGB colors of a rendered buffer with the closest colors of the 256-color palette,
+/// for terminals without RGB colors support
+pub fn reduce_to_256_colors(buf: &mut ratatui::buffer::Buffer) {
+    for cell in &mut buf.content {
+        cell.fg = to_256_color(cell.fg);
+        cell.bg = to_256_color(cell.bg);
+    }
+}
+
+fn to_256_color(color: ratatui::style::Color) -> ratatui::style::Color {
+    use ratatui::style::Color;
+
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+
+    // the 6x6x6 color cube (indices 16-231), whose levels are 0, 95, 135, 175, 215 and 255
+    let level = |c: u8| if c < 48 { 0 } else { (c.max(75) - 35) / 40 };
+    let value = |l: u8| if l == 0 { 0 } else { 55 + 40 * l };
+    let (lr, lg, lb) = (level(r), level(g), level(b));
+    let cube = (value(lr), value(lg), value(lb));
+
+    // the grayscale ramp (indices 232-255), whose levels are 8, 18, ..., 238
+    let avg = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_index = u8::try_from((avg.saturating_sub(3) / 10).min(23)).unwrap_or(23);
+    let gray = 8 + 10 * gray_index;
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        [(r, cr), (g, cg), (b, cb)]
+            .iter()
+            .map(|&(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+    if distance((gray, gray, gray)) < distance(cube) {
+        Color::Indexed(232 + gray_index)
+    } else {
+        Color::Indexed(16 + 36 * lr + 6 * lg + lb)
+    }
+}
@@ -3,13 +3,31 @@
 //! Renders slow BPM-reactive visualization with multi-color gradients from album art
 //! and periodic pixelated album cover scatter/reassemble effect
 
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use super::{
+    glyphs::GlyphSet,
+    shader::{Inputs, Shader, N_BANDS},
+};
+use anyhow::Result;
 use ratatui::{
+    buffer::Buffer,
+    crossterm::{
+        style::{ResetColor, SetBackgroundColor, SetForegroundColor},
+        Command,
+    },
     layout::Rect,
     style::{Color, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
+use rspotify::prelude::Id;
+use serde::{Deserialize, Serialize};
 
 /// Color palette extracted from album art
 #[derive(Debug, Clone)]
@@ -31,17 +49,176 @@ impl AlbumPalette {
     }
 }
 
+/// Linearly interpolate between two colors, `t` ranging from 0.0 (`from`) to 1.0 (`to`)
+pub fn lerp_color(from: (u8, u8, u8), to: (u8, u8, u8), t: f64) -> (u8, u8, u8) {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+    (lerp(from.0, to.0), lerp(from.1, to.1), lerp(from.2, to.2))
+}
+
+/// A crossfade of the visualization's color from the color shown before a track change
+#[derive(Debug, Clone, Copy)]
+pub struct ColorCrossfade {
+    from: (u8, u8, u8),
+    start: Instant,
+}
+
+impl ColorCrossfade {
+    pub fn new(from: (u8, u8, u8), now: Instant) -> Self {
+        Self { from, start: now }
+    }
+
+    /// Get the color of the crossfade towards `to` at `now`, or `None` if the crossfade has finished
+    pub fn color(
+        &self,
+        to: (u8, u8, u8),
+        duration: Duration,
+        now: Instant,
+    ) -> Option<(u8, u8, u8)> {
+        let elapsed = now.saturating_duration_since(self.start);
+        if elapsed >= duration {
+            return None;
+        }
+        Some(lerp_color(
+            self.from,
+            to,
+            elapsed.as_secs_f64() / duration.as_secs_f64(),
+        ))
+    }
+}
+
 /// Color schemes for visualization when no album art is available
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum ColorScheme {
-    Cyan,    // Default cyan/blue
-    Warm,    // Orange/red warm colors
-    Purple,  // Purple/magenta
-    Green,   // Green/emerald
-    Sunset,  // Orange to pink gradient
-    Ocean,   // Deep blue to cyan
-    Custom,  // From album art
+    Cyan,         // Default cyan/blue
+    Warm,         // Orange/red warm colors
+    Purple,       // Purple/magenta
+    Green,        // Green/emerald
+    Sunset,       // Orange to pink gradient
+    Ocean,        // Deep blue to cyan
+    Deuteranopia, // Sky blue, safe for red-green color blindness (green-weak)
+    Protanopia,   // Yellow to orange, safe for red-green color blindness (red-weak)
+    Tritanopia,   // Pink to red, safe for blue-yellow color blindness
+    #[default]
+    Custom, // From album art
+}
+
+impl ColorScheme {
+    /// Get the next color scheme, for cycling through the color schemes
+    pub fn next(self) -> Self {
+        match self {
+            Self::Cyan => Self::Warm,
+            Self::Warm => Self::Purple,
+            Self::Purple => Self::Green,
+            Self::Green => Self::Sunset,
+            Self::Sunset => Self::Ocean,
+            Self::Ocean => Self::Deuteranopia,
+            Self::Deuteranopia => Self::Protanopia,
+            Self::Protanopia => Self::Tritanopia,
+            Self::Tritanopia => Self::Custom,
+            Self::Custom => Self::Cyan,
+        }
+    }
+
+    /// Get the scheme's main color, or `None` for the `Custom` scheme without an album color
+    pub fn color(self, album_color: Option<(u8, u8, u8)>) -> Option<(u8, u8, u8)> {
+        if self == Self::Custom && album_color.is_none() {
+            return None;
+        }
+        match get_color_for_scheme(self, 1.0, 0, album_color) {
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+            _ => None,
+        }
+    }
+}
+
+/// Visualization modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VisualizationMode {
+    /// ASCII animated cats and dogs
+    #[default]
+    Banner,
+    /// A horizontal sine wave
+    Waves,
+    /// Beat-reactive spectrum bars
+    Bars,
+    /// Particles drifting upwards, flashing on beats
+    Particles,
+    /// A user-defined visualization computed by the `visualization_shader` config option
+    Shader,
+    /// A mode picked for each track by the `visualization_rules` config option
+    Smart,
+}
+
+impl VisualizationMode {
+    /// Get the next mode, for cycling through the visualization modes.
+    /// The `Shader` mode is skipped if no shader is configured.
+    pub fn next(self, has_shader: bool) -> Self {
+        match self {
+            Self::Banner => Self::Waves,
+            Self::Waves => Self::Bars,
+            Self::Bars => Self::Particles,
+            Self::Particles if has_shader => Self::Shader,
+            Self::Particles | Self::Shader => Self::Smart,
+            Self::Smart => Self::Banner,
+        }
+    }
+}
+
+/// The visualization mode and color scheme picked for an artist or a genre
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VisualizationPreference {
+    pub mode: VisualizationMode,
+    pub color_scheme: ColorScheme,
+}
+
+/// An artist or a genre to remember a visualization preference for
+#[derive(Debug, Clone)]
+pub enum VisualizationPreferenceTarget {
+    Artist { uri: String, name: String },
+    Genre(String),
+}
+
+impl VisualizationPreferenceTarget {
+    /// The key of the target's preference in the database
+    pub fn key(&self) -> String {
+        match self {
+            Self::Artist { uri, .. } => format!("artist:{uri}"),
+            Self::Genre(genre) => format!("genre:{genre}"),
+        }
+    }
+}
+
+impl std::fmt::Display for VisualizationPreferenceTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Artist { name, .. } => write!(f, "Artist: {name}"),
+            Self::Genre(genre) => write!(f, "Genre: {genre}"),
+        }
+    }
+}
+
+/// Get the targets of a visualization preference for a track: the track's artists,
+/// followed by the genres of its first artist
+pub fn visualization_preference_targets(
+    track: &rspotify::model::FullTrack,
+    genres: &[String],
+) -> Vec<VisualizationPreferenceTarget> {
+    track
+        .artists
+        .iter()
+        .filter_map(|artist| {
+            Some(VisualizationPreferenceTarget::Artist {
+                uri: artist.id.as_ref()?.uri(),
+                name: artist.name.clone(),
+            })
+        })
+        .chain(
+            genres
+                .iter()
+                .map(|genre| VisualizationPreferenceTarget::Genre(genre.clone())),
+        )
+        .collect()
 }
 
 /// Render simple horizontal sine wave with album colors
@@ -51,12 +228,14 @@ pub enum ColorScheme {
 /// - `area`: The rectangular area to render the visualization
 /// - `beat_progress`: Progress through current beat (0.0-1.0)
 /// - `album_color`: Optional RGB color extracted from album art
+/// - `glyphs`: The glyph set to draw with
 /// - `show_border`: Whether to show a border around the visualization
 pub fn render_concentric_waves(
     frame: &mut Frame,
     area: Rect,
     beat_progress: f64,
     album_color: Option<(u8, u8, u8)>,
+    glyphs: &GlyphSet,
     show_border: bool,
 ) {
     // Use album color only if available (when image feature works), otherwise use theme contrast
@@ -66,17 +245,17 @@ pub fn render_concentric_waves(
         // Default to bright cyan for good contrast with dark themes
         (0, 255, 255)
     };
-    
+
     // Very slow wave animation - tempo between 0.01 and 0.1
     let wave_phase = beat_progress * 0.05; // Ultra slow movement for smooth left-to-right flow
-    let center_y = area.height as f64 / 2.0;
+    let center_y = f64::from(area.height) / 2.0;
     // Flatter wave - reduced amplitude from 1/3 to 1/5 of height
-    let amplitude = (area.height as f64 / 5.0).max(1.5);
-    
+    let amplitude = (f64::from(area.height) / 5.0).max(1.5);
+
     // Pre-calculate the wave y-position for each x coordinate
     let mut wave_positions: Vec<usize> = Vec::with_capacity(area.width as usize);
     for x in 0..area.width {
-        let x_pos = x as f64;
+        let x_pos = f64::from(x);
         // Calculate sine wave - moves left to right as wave_phase increases
         let wave_y = center_y + (x_pos * 0.2 + wave_phase).sin() * amplitude;
         wave_positions.push(wave_y.round() as usize);
@@ -85,83 +264,406 @@ pub fn render_concentric_waves(
     // Build the visualization by drawing the continuous wave line
     let mut text = vec![];
     for y in 0..area.height {
-        let mut line_spans = vec![];
-        for x in 0..area.width {
+        text.push(coalesce_spans((0..area.width).map(|x| {
             // Check if this position is on the wave line
             let wave_y = wave_positions[x as usize];
-            
+
             // Use smooth line characters - horizontal line segments and block characters
             let (char, color) = if y as usize == wave_y {
                 // Use full block for smooth continuous line
-                ('█', Color::Rgb(wave_color.0, wave_color.1, wave_color.2))
+                (
+                    glyphs.wave,
+                    Color::Rgb(wave_color.0, wave_color.1, wave_color.2),
+                )
             } else {
                 (' ', Color::Black)
             };
+            (char, Style::default().fg(color))
+        })));
+    }
+
+    let widget = if show_border {
+        Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Visualization"),
+        )
+    } else {
+        Paragraph::new(text)
+    };
+
+    frame.render_widget(widget, area);
+}
+
+/// Get the palette of the current album, or the default palette if no album color is available
+pub fn album_palette(album_color: Option<(u8, u8, u8)>) -> AlbumPalette {
+    album_color.map_or_else(AlbumPalette::default, |(r, g, b)| {
+        generate_color_palette(r, g, b)
+    })
+}
+
+/// The seed of the visualizations' pseudo-random patterns, set by a deterministic clock
+static PATTERN_SEED: AtomicU64 = AtomicU64::new(0);
+
+/// Set the seed of the visualizations' pseudo-random patterns
+pub fn set_pattern_seed(seed: u64) {
+    PATTERN_SEED.store(seed, Ordering::Relaxed);
+}
+
+/// A pseudo-random number in `[0, 1)` from a seed
+pub fn hash01(seed: f64) -> f64 {
+    // the pattern's seed shifts the input far enough apart for different patterns
+    let seed = seed + (PATTERN_SEED.load(Ordering::Relaxed) % 100_000) as f64 * 1_000.0;
+    ((seed * 12.9898).sin() * 43_758.545_3).rem_euclid(1.0)
+}
 
-            line_spans.push(Span::styled(char.to_string(), Style::default().fg(color)));
+/// Build a line from styled cells, coalescing adjacent cells of the same style into a single span
+pub fn coalesce_spans(cells: impl IntoIterator<Item = (char, Style)>) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = vec![];
+    let mut text = String::new();
+    let mut style = None;
+    for (c, cell_style) in cells {
+        if style.is_some_and(|s| s != cell_style) {
+            spans.push(Span::styled(
+                std::mem::take(&mut text),
+                style.unwrap_or_default(),
+            ));
         }
-        text.push(Line::from(line_spans));
+        style = Some(cell_style);
+        text.push(c);
     }
+    if let Some(style) = style {
+        spans.push(Span::styled(text, style));
+    }
+    Line::from(spans)
+}
 
+/// Render a block with the visualization's lines, optionally with a border
+fn render_lines(frame: &mut Frame, area: Rect, text: Vec<Line>, show_border: bool) {
     let widget = if show_border {
-        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Visualization"))
+        Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Visualization"),
+        )
     } else {
         Paragraph::new(text)
     };
-    
     frame.render_widget(widget, area);
 }
 
+/// Render beat-reactive spectrum bars
+///
+/// # Parameters
+/// - `frame`: The ratatui frame to render into
+/// - `area`: The rectangular area to render the visualization
+/// - `n_beats`: The number of beats elapsed since the visualization started
+/// - `color`: Optional RGB color of the bars
+/// - `glyphs`: The glyph set to draw with
+/// - `show_border`: Whether to show a border around the visualization
+pub fn render_spectrum_bars(
+    frame: &mut Frame,
+    area: Rect,
+    n_beats: f64,
+    color: Option<(u8, u8, u8)>,
+    glyphs: &GlyphSet,
+    show_border: bool,
+) {
+    let (r, g, b) = color.unwrap_or((0, 255, 255));
+    // bars jump on each beat, then decay until the next one
+    let pulse = 0.4 + 0.6 * (1.0 - n_beats.fract());
+    let beat = n_beats.floor();
+    let heights = (0..area.width)
+        .map(|x| {
+            let level = 0.2 + 0.8 * hash01(f64::from(x) + beat * 97.0);
+            level * pulse * f64::from(area.height)
+        })
+        .collect::<Vec<_>>();
+
+    let text = (0..area.height)
+        .map(|y| {
+            // the number of cells between the row's bottom and the area's bottom
+            let row = f64::from(area.height - 1 - y);
+            coalesce_spans(heights.iter().map(|&height| {
+                let fill = (height - row).clamp(0.0, 1.0);
+                let c = if fill >= 1.0 {
+                    glyphs.bar_full
+                } else {
+                    glyphs.bar_partial[(fill * 8.0) as usize]
+                };
+                let intensity = 0.5 + 0.5 * row / f64::from(area.height);
+                (
+                    c,
+                    Style::default().fg(apply_intensity((r, g, b), intensity)),
+                )
+            }))
+        })
+        .collect();
+
+    render_lines(frame, area, text, show_border);
+}
+
+/// Render particles drifting upwards and flashing on beats
+///
+/// # Parameters
+/// - `frame`: The ratatui frame to render into
+/// - `area`: The rectangular area to render the visualization
+/// - `n_beats`: The number of beats elapsed since the visualization started
+/// - `color`: Optional RGB color of the particles
+/// - `glyphs`: The glyph set to draw with
+/// - `show_border`: Whether to show a border around the visualization
+pub fn render_particles(
+    frame: &mut Frame,
+    area: Rect,
+    n_beats: f64,
+    color: Option<(u8, u8, u8)>,
+    glyphs: &GlyphSet,
+    show_border: bool,
+) {
+    let (width, height) = (usize::from(area.width), usize::from(area.height));
+    if width == 0 || height == 0 {
+        return;
+    }
+    let color = color.unwrap_or((0, 255, 255));
+    let flash = 1.0 - n_beats.fract();
+    let [bright, medium, dim] = glyphs.particles;
+
+    let mut cells = vec![vec![(' ', 0.0); width]; height];
+    for i in 0..(width * height / 12).max(1) {
+        let seed = i as f64;
+        let speed = 0.5 + hash01(seed + 0.5);
+        let x = (hash01(seed) * width as f64) as usize;
+        // particles move up by `speed` rows per beat, wrapping around the area
+        let y = (hash01(seed + 0.25) * height as f64 - n_beats * speed).rem_euclid(height as f64);
+        let brightness = 0.3 + 0.7 * flash * hash01(seed + 0.75);
+        let symbol = match brightness {
+            b if b > 0.8 => bright,
+            b if b > 0.5 => medium,
+            _ => dim,
+        };
+        cells[(y as usize).min(height - 1)][x.min(width - 1)] = (symbol, brightness);
+    }
+
+    let text = cells
+        .into_iter()
+        .map(|row| {
+            coalesce_spans(row.into_iter().map(|(symbol, brightness)| {
+                (
+                    symbol,
+                    Style::default().fg(apply_intensity(color, brightness)),
+                )
+            }))
+        })
+        .collect();
+
+    render_lines(frame, area, text, show_border);
+}
+
+/// Get the track's loudness and the strength of its audio bands (from 0 to 1) at a position (in seconds).
+///
+/// The Web API doesn't provide a track's spectrum, so the bands are the pitch classes of the audio analysis'
+/// segment at the position. Without an audio analysis, they're pseudo-random levels jumping on each beat.
+pub fn audio_levels(
+    analysis: Option<&rspotify::model::AudioAnalysis>,
+    position: f64,
+    n_beats: f64,
+) -> (f64, [f64; N_BANDS]) {
+    let mut bands = [0.0; N_BANDS];
+    let segment = analysis.and_then(|analysis| {
+        let segments = &analysis.segments;
+        let n = segments.partition_point(|s| f64::from(s.time_interval.start) <= position);
+        segments.get(n.checked_sub(1)?)
+    });
+    if let Some(segment) = segment {
+        for (band, pitch) in bands.iter_mut().zip(&segment.pitches) {
+            *band = f64::from(*pitch).clamp(0.0, 1.0);
+        }
+        // loudness is in decibels, usually between -60 and 0
+        let loudness = ((f64::from(segment.loudness_max) + 60.0) / 60.0).clamp(0.0, 1.0);
+        return (loudness, bands);
+    }
+
+    let pulse = 1.0 - n_beats.rem_euclid(1.0);
+    let beat = n_beats.floor();
+    for (i, band) in bands.iter_mut().enumerate() {
+        *band = (0.2 + 0.8 * hash01(i as f64 + beat * 97.0)) * pulse;
+    }
+    (pulse, bands)
+}
+
+/// Render a user-defined visualization, whose cells' intensities are computed by a shader
+///
+/// # Parameters
+/// - `frame`: The ratatui frame to render into
+/// - `area`: The rectangular area to render the visualization
+/// - `shader`: The shader computing the cells' intensities
+/// - `inputs`: The shader's inputs other than the cells' positions
+/// - `color`: Optional RGB color of the cells
+/// - `glyphs`: The glyph set to draw with
+/// - `show_border`: Whether to show a border around the visualization
+pub fn render_shader(
+    frame: &mut Frame,
+    area: Rect,
+    shader: &Shader,
+    inputs: Inputs,
+    color: Option<(u8, u8, u8)>,
+    glyphs: &GlyphSet,
+    show_border: bool,
+) {
+    let color = color.unwrap_or((0, 255, 255));
+    let (width, height) = (
+        f64::from(area.width.saturating_sub(1).max(1)),
+        f64::from(area.height.saturating_sub(1).max(1)),
+    );
+    let n_shades = glyphs.shades.len() - 1;
+
+    let mut cell = inputs;
+    let text = (0..area.height)
+        .map(|y| {
+            cell.y = f64::from(y) / height;
+            coalesce_spans((0..area.width).map(|x| {
+                cell.x = f64::from(x) / width;
+                let intensity = shader.intensity(&cell);
+                (
+                    glyphs.shades[(intensity * n_shades as f64).round() as usize],
+                    Style::default().fg(apply_intensity(color, 0.4 + 0.6 * intensity)),
+                )
+            }))
+        })
+        .collect();
+
+    render_lines(frame, area, text, show_border);
+}
+
 /// Generate a vibrant color palette from a base album color
 fn generate_color_palette(r: u8, g: u8, b: u8) -> AlbumPalette {
-    let mut colors = vec![];
-    
     // Base color
-    colors.push((r, g, b));
-    
+    let mut colors = vec![(r, g, b)];
+
     // Complementary color (opposite on color wheel)
     colors.push((
         255u8.saturating_sub(r),
         255u8.saturating_sub(g),
         255u8.saturating_sub(b),
     ));
-    
+
     // Analogous colors (rotate hue)
     colors.push((
-        ((r as f64 * 0.8 + g as f64 * 0.2) as u8).min(255),
-        ((g as f64 * 0.8 + b as f64 * 0.2) as u8).min(255),
-        ((b as f64 * 0.8 + r as f64 * 0.2) as u8).min(255),
+        (f64::from(r) * 0.8 + f64::from(g) * 0.2) as u8,
+        (f64::from(g) * 0.8 + f64::from(b) * 0.2) as u8,
+        (f64::from(b) * 0.8 + f64::from(r) * 0.2) as u8,
     ));
-    
+
     colors.push((
-        ((r as f64 * 0.2 + b as f64 * 0.8) as u8).min(255),
-        ((g as f64 * 0.2 + r as f64 * 0.8) as u8).min(255),
-        ((b as f64 * 0.2 + g as f64 * 0.8) as u8).min(255),
+        (f64::from(r) * 0.2 + f64::from(b) * 0.8) as u8,
+        (f64::from(g) * 0.2 + f64::from(r) * 0.8) as u8,
+        (f64::from(b) * 0.2 + f64::from(g) * 0.8) as u8,
     ));
-    
+
     // Lighter variant
     colors.push((
         r.saturating_add(60),
         g.saturating_add(60),
         b.saturating_add(60),
     ));
-    
+
     // Darker variant
     colors.push((
         r.saturating_sub(60),
         g.saturating_sub(60),
         b.saturating_sub(60),
     ));
-    
+
     AlbumPalette { colors }
 }
 
+#[cfg(feature = "image")]
+/// Construct the colors of a theme's palette (hex colors keyed by the palette's fields)
+/// from an album palette.
+///
+/// The album's colors replace the palette's colors used by the default component styles
+/// (e.g. magenta for block titles and cyan for the playback's track), and are lightened (or darkened
+/// on a light background) to stay readable on a background tinted with the album's base color.
+pub fn album_theme_colors(
+    palette: &AlbumPalette,
+    light_background: bool,
+) -> Vec<(&'static str, String)> {
+    let base = palette.colors[0];
+    let color = |i: usize| palette.colors.get(i).copied().unwrap_or(base);
+    let (bg_end, fg_end) = if light_background {
+        ((255, 255, 255), (0, 0, 0))
+    } else {
+        ((0, 0, 0), (255, 255, 255))
+    };
+    let readable = |c: (u8, u8, u8)| {
+        let luminance = 0.2126 * f64::from(c.0) + 0.7152 * f64::from(c.1) + 0.0722 * f64::from(c.2);
+        if light_background && luminance > 110.0 {
+            lerp_color(c, fg_end, (luminance - 110.0) / luminance)
+        } else if !light_background && luminance < 140.0 {
+            lerp_color(c, fg_end, (140.0 - luminance) / (255.0 - luminance))
+        } else {
+            c
+        }
+    };
+    let hex = |(r, g, b): (u8, u8, u8)| format!("#{r:02x}{g:02x}{b:02x}");
+
+    // the album's colors: the base, complementary, analogous, lighter and darker colors
+    let red = readable(lerp_color((220, 60, 60), base, 0.25));
+    let normal = [
+        ("red", red),
+        ("green", readable(color(2))),
+        ("yellow", readable(color(1))),
+        ("blue", readable(color(3))),
+        ("magenta", readable(base)),
+        ("cyan", readable(color(4))),
+    ];
+    let bright = [
+        "bright_red",
+        "bright_green",
+        "bright_yellow",
+        "bright_blue",
+        "bright_magenta",
+        "bright_cyan",
+    ];
+
+    let mut colors = vec![
+        ("background", hex(lerp_color(base, bg_end, 0.88))),
+        ("foreground", hex(lerp_color(base, fg_end, 0.85))),
+        ("black", hex(lerp_color(color(5), bg_end, 0.6))),
+        ("white", hex(lerp_color(base, fg_end, 0.7))),
+        ("bright_black", hex(lerp_color(base, (128, 128, 128), 0.7))),
+        ("bright_white", hex(lerp_color(base, fg_end, 0.92))),
+    ];
+    for ((field, c), bright_field) in normal.into_iter().zip(bright) {
+        colors.push((field, hex(c)));
+        colors.push((bright_field, hex(lerp_color(c, fg_end, 0.3))));
+    }
+    colors
+}
+
+/// Whether the terminal has a light background, on which colors are dimmed towards white instead of black
+static LIGHT_BACKGROUND: AtomicBool = AtomicBool::new(false);
+
+/// Set whether the terminal has a light background
+pub fn set_light_background(light: bool) {
+    LIGHT_BACKGROUND.store(light, Ordering::Relaxed);
+}
+
 /// Apply intensity to a color
 fn apply_intensity(color: (u8, u8, u8), intensity: f64) -> Color {
+    if LIGHT_BACKGROUND.load(Ordering::Relaxed) {
+        // darken the color to stand out from the background, then fade it towards white
+        let (r, g, b) = lerp_color(
+            (255, 255, 255),
+            lerp_color(color, (0, 0, 0), 0.4),
+            intensity,
+        );
+        return Color::Rgb(r, g, b);
+    }
     Color::Rgb(
-        (color.0 as f64 * intensity) as u8,
-        (color.1 as f64 * intensity) as u8,
-        (color.2 as f64 * intensity) as u8,
+        (f64::from(color.0) * intensity) as u8,
+        (f64::from(color.1) * intensity) as u8,
+        (f64::from(color.2) * intensity) as u8,
     )
 }
 
@@ -172,115 +674,315 @@ fn get_color_for_scheme(
     level: u8,
     album_color: Option<(u8, u8, u8)>,
 ) -> Color {
-    match scheme {
+    let base: (u8, u8, u8) = match scheme {
         ColorScheme::Custom => {
-            if let Some((r, g, b)) = album_color {
-                // Generate gradient from album color
-                let base = match level {
-                    0 => (r, g, b),
-                    1 => (
-                        (r as f64 * 0.85) as u8,
-                        (g as f64 * 0.85) as u8,
-                        (b as f64 * 0.85) as u8,
-                    ),
-                    2 => (
-                        (r as f64 * 0.7) as u8,
-                        (g as f64 * 0.7) as u8,
-                        (b as f64 * 0.7) as u8,
-                    ),
-                    _ => (
-                        (r as f64 * 0.5) as u8,
-                        (g as f64 * 0.5) as u8,
-                        (b as f64 * 0.5) as u8,
-                    ),
-                };
-                Color::Rgb(
-                    (base.0 as f64 * intensity) as u8,
-                    (base.1 as f64 * intensity) as u8,
-                    (base.2 as f64 * intensity) as u8,
-                )
-            } else {
+            let Some((r, g, b)) = album_color else {
                 // Fallback to cyan if custom but no color provided
-                get_color_for_scheme(ColorScheme::Cyan, intensity, level, None)
-            }
-        }
-        ColorScheme::Cyan => {
-            let base = match level {
-                0 => (0, 255, 255),
-                1 => (0, 200, 255),
-                2 => (0, 150, 200),
-                _ => (0, 100, 150),
+                return get_color_for_scheme(ColorScheme::Cyan, intensity, level, None);
             };
-            Color::Rgb(
-                (base.0 as f64 * intensity) as u8,
-                (base.1 as f64 * intensity) as u8,
-                (base.2 as f64 * intensity) as u8,
-            )
-        }
-        ColorScheme::Warm => {
-            let base = match level {
-                0 => (255, 100, 0),
-                1 => (255, 150, 50),
-                2 => (200, 100, 0),
-                _ => (150, 70, 0),
+            // Generate gradient from album color
+            let factor = match level {
+                0 => 1.0,
+                1 => 0.85,
+                2 => 0.7,
+                _ => 0.5,
             };
-            Color::Rgb(
-                (base.0 as f64 * intensity) as u8,
-                (base.1 as f64 * intensity) as u8,
-                (base.2 as f64 * intensity) as u8,
-            )
+            return apply_intensity((r, g, b), factor * intensity);
         }
-        ColorScheme::Purple => {
-            let base = match level {
-                0 => (200, 50, 255),
-                1 => (180, 80, 230),
-                2 => (150, 50, 200),
-                _ => (100, 30, 150),
+        ColorScheme::Cyan => match level {
+            0 => (0, 255, 255),
+            1 => (0, 200, 255),
+            2 => (0, 150, 200),
+            _ => (0, 100, 150),
+        },
+        ColorScheme::Warm => match level {
+            0 => (255, 100, 0),
+            1 => (255, 150, 50),
+            2 => (200, 100, 0),
+            _ => (150, 70, 0),
+        },
+        ColorScheme::Purple => match level {
+            0 => (200, 50, 255),
+            1 => (180, 80, 230),
+            2 => (150, 50, 200),
+            _ => (100, 30, 150),
+        },
+        ColorScheme::Green => match level {
+            0 => (50, 255, 150),
+            1 => (50, 220, 120),
+            2 => (30, 180, 100),
+            _ => (20, 120, 70),
+        },
+        ColorScheme::Sunset => match level {
+            0 => (255, 100, 150),
+            1 => (255, 150, 100),
+            2 => (200, 100, 100),
+            _ => (150, 70, 80),
+        },
+        ColorScheme::Ocean => match level {
+            0 => (0, 150, 255),
+            1 => (20, 120, 220),
+            2 => (10, 80, 180),
+            _ => (5, 50, 120),
+        },
+        ColorScheme::Deuteranopia => match level {
+            0 => (86, 180, 233),
+            1 => (40, 150, 210),
+            2 => (0, 114, 178),
+            _ => (0, 80, 130),
+        },
+        ColorScheme::Protanopia => match level {
+            0 => (240, 228, 66),
+            1 => (240, 190, 40),
+            2 => (230, 159, 0),
+            _ => (160, 110, 0),
+        },
+        ColorScheme::Tritanopia => match level {
+            0 => (242, 138, 179),
+            1 => (224, 78, 138),
+            2 => (213, 94, 0),
+            _ => (150, 60, 40),
+        },
+    };
+    apply_intensity(base, intensity)
+}
+
+/// Render the cells of a buffer's area as ANSI text
+fn snapshot_ansi(buffer: &Buffer, area: Rect) -> Result<String> {
+    let mut out = String::new();
+    for y in area.top()..area.bottom() {
+        // the number of cells to skip, as they are covered by a previous wide character
+        let mut skip = 0;
+        for x in area.left()..area.right() {
+            let Some(cell) = buffer.cell((x, y)) else {
+                continue;
             };
-            Color::Rgb(
-                (base.0 as f64 * intensity) as u8,
-                (base.1 as f64 * intensity) as u8,
-                (base.2 as f64 * intensity) as u8,
-            )
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            SetForegroundColor(cell.fg.into()).write_ansi(&mut out)?;
+            SetBackgroundColor(cell.bg.into()).write_ansi(&mut out)?;
+            out.push_str(cell.symbol());
+            skip = Span::raw(cell.symbol()).width().saturating_sub(1);
         }
-        ColorScheme::Green => {
-            let base = match level {
-                0 => (50, 255, 150),
-                1 => (50, 220, 120),
-                2 => (30, 180, 100),
-                _ => (20, 120, 70),
+        ResetColor.write_ansi(&mut out)?;
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Get the RGB value of a color, approximating named colors with the standard terminal palette
+#[cfg(feature = "image")]
+fn color_to_rgb(color: Color, default: [u8; 3]) -> [u8; 3] {
+    match color {
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Black => [0, 0, 0],
+        Color::Red => [128, 0, 0],
+        Color::Green => [0, 128, 0],
+        Color::Yellow => [128, 128, 0],
+        Color::Blue => [0, 0, 128],
+        Color::Magenta => [128, 0, 128],
+        Color::Cyan => [0, 128, 128],
+        Color::Gray => [192, 192, 192],
+        Color::DarkGray => [128, 128, 128],
+        Color::LightRed => [255, 0, 0],
+        Color::LightGreen => [0, 255, 0],
+        Color::LightYellow => [255, 255, 0],
+        Color::LightBlue => [0, 0, 255],
+        Color::LightMagenta => [255, 0, 255],
+        Color::LightCyan => [0, 255, 255],
+        Color::White => [255, 255, 255],
+        Color::Reset | Color::Indexed(_) => default,
+    }
+}
+
+/// Render the cells of a buffer's area as an image, in which each non-blank cell is a block
+/// of its foreground color and each blank cell is a block of its background color
+#[cfg(feature = "image")]
+fn snapshot_image(buffer: &Buffer, area: Rect) -> image::RgbImage {
+    const CELL_WIDTH: u32 = 4;
+    const CELL_HEIGHT: u32 = 8;
+
+    image::RgbImage::from_fn(
+        u32::from(area.width) * CELL_WIDTH,
+        u32::from(area.height) * CELL_HEIGHT,
+        |px, py| {
+            let (x, y) = (
+                area.left() + (px / CELL_WIDTH) as u16,
+                area.top() + (py / CELL_HEIGHT) as u16,
+            );
+            let rgb = match buffer.cell((x, y)) {
+                Some(cell) if !cell.symbol().trim().is_empty() => {
+                    color_to_rgb(cell.fg, [255, 255, 255])
+                }
+                Some(cell) => color_to_rgb(cell.bg, [0, 0, 0]),
+                None => [0, 0, 0],
             };
-            Color::Rgb(
-                (base.0 as f64 * intensity) as u8,
-                (base.1 as f64 * intensity) as u8,
-                (base.2 as f64 * intensity) as u8,
-            )
-        }
-        ColorScheme::Sunset => {
-            let base = match level {
-                0 => (255, 100, 150),
-                1 => (255, 150, 100),
-                2 => (200, 100, 100),
-                _ => (150, 70, 80),
+            image::Rgb(rgb)
+        },
+    )
+}
+
+/// Get the path (without extension) of a new snapshot or recording in the `snapshots` folder
+/// of the application's cache folder
+fn new_snapshot_path(cache_folder: &Path, now: chrono::DateTime<chrono::Local>) -> Result<PathBuf> {
+    let folder = cache_folder.join("snapshots");
+    std::fs::create_dir_all(&folder)?;
+    Ok(folder.join(format!("visualization-{}", now.format("%Y%m%d-%H%M%S"))))
+}
+
+/// The duration of the "drop" animation played when a track starts
+pub const DROP_DURATION: Duration = Duration::from_millis(800);
+
+/// Apply a frame of the "drop" animation, in which the visualization rendered in the `area`
+/// of a buffer falls into place from above, `progress` ranging from 0.0 (start) to 1.0 (end)
+pub fn apply_drop(buffer: &mut Buffer, area: Rect, progress: f64) {
+    let remaining = 1.0 - progress.clamp(0.0, 1.0);
+    // ease out: fall quickly, then slow down into place
+    let offset = (remaining * remaining * f64::from(area.height)).round() as u16;
+    if offset == 0 {
+        return;
+    }
+
+    for y in (area.top()..area.bottom()).rev() {
+        for x in area.left()..area.right() {
+            let above = if y >= area.top() + offset {
+                buffer.cell((x, y - offset)).cloned()
+            } else {
+                None
             };
-            Color::Rgb(
-                (base.0 as f64 * intensity) as u8,
-                (base.1 as f64 * intensity) as u8,
-                (base.2 as f64 * intensity) as u8,
-            )
+            if let Some(cell) = buffer.cell_mut((x, y)) {
+                match above {
+                    Some(above) => *cell = above,
+                    None => {
+                        cell.set_symbol(" ");
+                    }
+                }
+            }
         }
-        ColorScheme::Ocean => {
-            let base = match level {
-                0 => (0, 150, 255),
-                1 => (20, 120, 220),
-                2 => (10, 80, 180),
-                _ => (5, 50, 120),
-            };
-            Color::Rgb(
-                (base.0 as f64 * intensity) as u8,
-                (base.1 as f64 * intensity) as u8,
-                (base.2 as f64 * intensity) as u8,
-            )
+    }
+}
+
+/// Save a snapshot of the visualization rendered in the `area` of a buffer into the `snapshots`
+/// folder of the application's cache folder.
+///
+/// The snapshot consists of an ANSI text file, a PNG image (with the `image` feature)
+/// and a JSON file with the album palette. Returns the snapshot's path without extension.
+pub fn save_snapshot(
+    cache_folder: &Path,
+    buffer: &Buffer,
+    area: Rect,
+    palette: &AlbumPalette,
+) -> Result<PathBuf> {
+    let now = chrono::Local::now();
+    let path = new_snapshot_path(cache_folder, now)?;
+
+    std::fs::write(path.with_extension("ansi"), snapshot_ansi(buffer, area)?)?;
+    #[cfg(feature = "image")]
+    snapshot_image(buffer, area).save(path.with_extension("png"))?;
+
+    let metadata = serde_json::json!({
+        "captured_at": now.to_rfc3339(),
+        "width": area.width,
+        "height": area.height,
+        "palette": palette
+            .colors
+            .iter()
+            .map(|(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}"))
+            .collect::<Vec<_>>(),
+    });
+    std::fs::write(
+        path.with_extension("json"),
+        serde_json::to_string_pretty(&metadata)?,
+    )?;
+
+    Ok(path)
+}
+
+#[derive(Debug)]
+/// A recording of the visualization as an [asciinema](https://asciinema.org) cast.
+///
+/// The recording starts and ends on a beat of the visualization's tempo.
+pub struct VisualizationRecording {
+    start: Instant,
+    end: Instant,
+    size: (u16, u16),
+    /// the recorded frames, along with their times (in seconds) relative to the recording's start
+    frames: Vec<(f64, String)>,
+}
+
+impl VisualizationRecording {
+    /// Create a recording of about `duration`, starting on the next beat after `now` of a tempo
+    /// whose first beat happened at `beat_origin`
+    pub fn new(bpm: f64, beat_origin: Instant, duration: Duration, now: Instant) -> Self {
+        let beat = Duration::from_secs_f64(60.0 / bpm);
+        let n_elapsed_beats =
+            (now.saturating_duration_since(beat_origin).as_secs_f64() / beat.as_secs_f64()).ceil();
+        let n_beats = (duration.as_secs_f64() / beat.as_secs_f64())
+            .round()
+            .max(1.0);
+
+        let start = beat_origin + beat.mul_f64(n_elapsed_beats);
+        Self {
+            start,
+            end: start + beat.mul_f64(n_beats),
+            size: (0, 0),
+            frames: vec![],
+        }
+    }
+
+    /// Stop the recording at the next recorded frame
+    pub fn stop(&mut self, now: Instant) {
+        self.end = self.end.min(now);
+    }
+
+    /// Record the visualization rendered at `now` in the `area` of a buffer.
+    ///
+    /// Returns whether the recording has finished.
+    pub fn record(&mut self, buffer: &Buffer, area: Rect, now: Instant) -> Result<bool> {
+        if now >= self.end {
+            return Ok(true);
+        }
+        if now < self.start {
+            return Ok(false);
+        }
+
+        // move the cursor to the top-left corner before drawing the frame
+        let frame = format!(
+            "\x1b[H{}",
+            snapshot_ansi(buffer, area)?.replace('\n', "\r\n")
+        );
+        if self.frames.last().is_none_or(|(_, last)| *last != frame) {
+            self.frames.push(((now - self.start).as_secs_f64(), frame));
         }
+        self.size = (area.width, area.height);
+        Ok(false)
+    }
+
+    /// Save the recording into the `snapshots` folder of the application's cache folder,
+    /// returning the path of the cast file
+    pub fn save(&self, cache_folder: &Path) -> Result<PathBuf> {
+        if self.frames.is_empty() {
+            anyhow::bail!("no frame was recorded");
+        }
+
+        let now = chrono::Local::now();
+        let path = new_snapshot_path(cache_folder, now)?.with_extension("cast");
+        let mut cast = serde_json::to_string(&serde_json::json!({
+            "version": 2,
+            "width": self.size.0,
+            "height": self.size.1,
+            "timestamp": now.timestamp(),
+            "title": "spotify_player visualization",
+        }))?;
+        for (time, frame) in &self.frames {
+            cast.push('\n');
+            cast.push_str(&serde_json::to_string(&(time, "o", frame))?);
+        }
+        cast.push('\n');
+        std::fs::write(&path, cast)?;
+
+        Ok(path)
     }
 }
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::LazyLock};
 
 /// formats a time duration into a "{minutes}:{seconds}" format
 pub fn format_duration(duration: &chrono::Duration) -> String {
@@ -6,6 +6,19 @@ pub fn format_duration(duration: &chrono::Duration) -> String {
     format!("{}:{:02}", secs / 60, secs % 60)
 }
 
+/// formats a count with thousands separators, e.g. "1,342"
+pub fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(c);
+    }
+    formatted
+}
+
 pub fn map_join<T, F>(v: &[T], f: F, sep: &str) -> String
 where
     F: Fn(&T) -> &str,
@@ -50,6 +63,49 @@ pub fn parse_uri(uri: &str) -> Cow<'_, str> {
     }
 }
 
+/// Get the URI (`spotify:{type}:{id}`) of the item referred by a Spotify link
+/// (`https://open.spotify.com/{type}/{id}`) or URI in the given text, if any
+pub fn spotify_uri_from_link(text: &str) -> Option<String> {
+    static LINK_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+        regex::Regex::new(
+            r"https?://open\.spotify\.com/(?:intl-[[:alpha:]-]+/)?(?:embed/)?(?P<type>[a-z]+)/(?P<id>[[:alnum:]]+)",
+        )
+        .expect("valid regex")
+    });
+    static URI_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+        regex::Regex::new(r"spotify(?::user:[^:\s]+)?:(?P<type>[a-z]+):(?P<id>[[:alnum:]]+)")
+            .expect("valid regex")
+    });
+
+    let cap = LINK_RE.captures(text).or_else(|| URI_RE.captures(text))?;
+    Some(format!("spotify:{}:{}", &cap["type"], &cap["id"]))
+}
+
+/// Check if the given text is a shortened Spotify link (`https://spotify.link/...`),
+/// which needs to be resolved into a full link
+pub fn is_short_spotify_link(text: &str) -> bool {
+    let text = text.trim();
+    text.starts_with("https://spotify.link/") || text.starts_with("https://spotify.app.link/")
+}
+
+/// Check if the given text contains a Spotify link or URI
+pub fn is_spotify_link(text: &str) -> bool {
+    is_short_spotify_link(text) || spotify_uri_from_link(text).is_some()
+}
+
+/// Get the scheme file of a `theme import <file>` text typed into the command palette,
+/// in which a leading `~` is expanded to the home folder
+pub fn theme_import_path(text: &str) -> Option<std::path::PathBuf> {
+    let file = text.trim().strip_prefix("theme import ")?.trim();
+    if file.is_empty() {
+        return None;
+    }
+    match (file.strip_prefix("~/"), dirs_next::home_dir()) {
+        (Some(file), Some(home)) => Some(home.join(file)),
+        _ => Some(file.into()),
+    }
+}
+
 #[cfg(feature = "fzf")]
 use fuzzy_matcher::skim::SkimMatcherV2;
 
@@ -96,38 +152,75 @@ pub fn filtered_items_from_query<'a, T: std::fmt::Display>(
         .collect::<Vec<_>>()
 }
 
+/// Get the number of beats elapsed at a position (in seconds) of a track, along with
+/// the tempo (in beats per second) at the position.
+///
+/// The track's detected beats are used if its audio analysis is available,
+/// otherwise, beats are assumed to be evenly spaced at `bpm`.
+pub fn beats_at(
+    analysis: Option<&rspotify::model::AudioAnalysis>,
+    position: f64,
+    bpm: f64,
+) -> (f64, f64) {
+    let mut rate = bpm / 60.0;
+    let Some(analysis) = analysis else {
+        return (position * rate, rate);
+    };
+    if analysis.track.tempo > 0.0 {
+        rate = f64::from(analysis.track.tempo) / 60.0;
+    }
+
+    let beats = &analysis.beats;
+    // the number of beats starting at or before the position
+    let n = beats.partition_point(|beat| f64::from(beat.start) <= position);
+    match n.checked_sub(1).map(|i| &beats[i]) {
+        Some(beat) if beat.duration > 0.0 => {
+            let duration = f64::from(beat.duration);
+            (
+                (n - 1) as f64 + (position - f64::from(beat.start)) / duration,
+                1.0 / duration,
+            )
+        }
+        // before the first beat
+        _ => match beats.first() {
+            Some(first) => ((position - f64::from(first.start)) * rate, rate),
+            None => (position * rate, rate),
+        },
+    }
+}
+
 /// Extract dominant RGB color from album art image
 #[cfg(feature = "image")]
 pub fn extract_dominant_color(img: &image::DynamicImage) -> (u8, u8, u8) {
     let img = img.resize(64, 64, image::imageops::FilterType::Nearest); // Resize for faster processing
     let rgb_img = img.to_rgb8();
-    
+
     let mut r_sum: u64 = 0;
     let mut g_sum: u64 = 0;
     let mut b_sum: u64 = 0;
     let mut count: u64 = 0;
-    
+
     // Sample every 4th pixel for performance
     for (x, y, pixel) in rgb_img.enumerate_pixels() {
         if x % 4 == 0 && y % 4 == 0 {
             let rgb = pixel.0;
-            
+
             // Skip very dark or very light pixels (often background)
-            let brightness = (rgb[0] as u16 + rgb[1] as u16 + rgb[2] as u16) / 3;
+            let brightness = (u16::from(rgb[0]) + u16::from(rgb[1]) + u16::from(rgb[2])) / 3;
             if brightness > 20 && brightness < 235 {
-                r_sum += rgb[0] as u64;
-                g_sum += rgb[1] as u64;
-                b_sum += rgb[2] as u64;
+                r_sum += u64::from(rgb[0]);
+                g_sum += u64::from(rgb[1]);
+                b_sum += u64::from(rgb[2]);
                 count += 1;
             }
         }
     }
-    
+
     if count == 0 {
         // Fallback to cyan if no pixels sampled
         return (0, 200, 255);
     }
-    
+
     (
         (r_sum / count) as u8,
         (g_sum / count) as u8,
@@ -0,0 +1,80 @@
+//! Advertising the integrated player on the local network via zeroconf (mDNS/DNS-SD),
+//! so that other Spotify clients (e.g. phones) can discover it as a Spotify Connect device
+//! and hand off their playback to it, sending their user's credentials in the process.
+
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
+use librespot_core::{authentication::Credentials, config::DeviceType};
+use librespot_discovery::Discovery;
+
+use crate::{client::AppClient, config, state::SharedState};
+
+/// Start advertising the integrated player on the local network
+pub fn start_discovery(client: &AppClient) -> Result<Discovery> {
+    let configs = config::get_config();
+    let device = &configs.app_config.device;
+    let session_config = &client.auth_config().session_config;
+
+    tracing::info!(
+        "Advertising the integrated player (name={}) via zeroconf...",
+        device.name
+    );
+    Discovery::builder(
+        session_config.device_id.clone(),
+        session_config.client_id.clone(),
+    )
+    .name(device.name.clone())
+    .device_type(device.device_type.parse::<DeviceType>().unwrap_or_default())
+    .launch()
+    .context("launch the zeroconf discovery")
+}
+
+/// Wait for a Spotify client to hand off its playback to the integrated player,
+/// storing the received credentials to authenticate the application
+pub async fn wait_for_credentials(discovery: &mut Discovery, client: &AppClient) -> Result<()> {
+    let configs = config::get_config();
+    println!(
+        "No cached credentials found, waiting for a Spotify client to connect to the `{}` device...",
+        configs.app_config.device.name
+    );
+    let creds = discovery
+        .next()
+        .await
+        .context("zeroconf discovery stopped before receiving credentials")?;
+    store_credentials(client, &creds);
+    Ok(())
+}
+
+/// Handle the credentials received from the Spotify clients connecting to the integrated player.
+///
+/// Only the connections of the current session's user are accepted. Credentials of another user
+/// (e.g. a friend's phone connecting to the player) are ignored, as switching the session's user
+/// would mix the other user's data with the current user's cached data and state.
+pub async fn handle_discovered_credentials(
+    mut discovery: Discovery,
+    client: AppClient,
+    state: SharedState,
+) {
+    while let Some(creds) = discovery.next().await {
+        let current_user = client.session().await.username();
+        if creds.username.as_deref() == Some(current_user.as_str()) {
+            // the current session already handles the user's connections
+            continue;
+        }
+
+        tracing::warn!(
+            "Ignored credentials of another user (username={:?}) received via zeroconf",
+            creds.username
+        );
+        state.ui.lock().show_toast(format!(
+            "Ignored a connection from another Spotify account ({})",
+            creds.username.as_deref().unwrap_or("unknown user")
+        ));
+    }
+    tracing::warn!("The zeroconf discovery stopped, the integrated player is no longer advertised");
+}
+
+/// Store credentials into the application's cache, from which they're used to create new sessions
+fn store_credentials(client: &AppClient, creds: &Credentials) {
+    client.auth_config().cache.save_credentials(creds);
+}
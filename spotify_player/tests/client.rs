@@ -0,0 +1,323 @@
+//! Integration tests of the client layer against a mock Spotify Web API server, run with `cargo test`.
+//!
+//! Each test starts its own mock server, drives the client with requests as the application's
+//! event loop would, and snapshots the resulting application state.
+//!
+//! The client's access token normally comes from a `librespot` session, which can't be mocked,
+//! so the tests inject a token that doesn't expire during a test.
+
+use std::{fmt::Write, sync::OnceLock};
+
+use rspotify::{clients::BaseClient, prelude::Id};
+use wiremock::{
+    matchers::{header, method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use spotify_player::client::{AppClient, ClientRequest, PlayerRequest};
+use spotify_player::{config, state};
+use state::{ConnectionStatus, PlaylistFolderItem, SharedState};
+
+/// The access token sent by the client in the tests
+const ACCESS_TOKEN: &str = "test-access-token";
+
+/// Initialize the application's configs, which are shared by all tests of the process
+fn init_configs() {
+    static INIT: OnceLock<()> = OnceLock::new();
+
+    INIT.get_or_init(|| {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let folder = std::env::temp_dir().join(format!(
+            "spotify_player-client-tests-{}",
+            std::process::id()
+        ));
+        let config_folder = folder.join("config");
+        let cache_folder = folder.join("cache");
+        for dir in [
+            &config_folder,
+            &cache_folder.join("audio"),
+            &cache_folder.join("image"),
+        ] {
+            std::fs::create_dir_all(dir).expect("create test folder");
+        }
+
+        let configs = config::Configs::new(&config_folder, &cache_folder).expect("default configs");
+        config::set_config(configs);
+    });
+}
+
+/// Read a JSON fixture, replacing the `{{server}}` placeholders with the mock server's address
+fn fixture(server: &MockServer, name: &str) -> serde_json::Value {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let text = std::fs::read_to_string(&path).expect("read fixture");
+    serde_json::from_str(&text.replace("{{server}}", &server.uri())).expect("parse fixture")
+}
+
+/// Construct a client sending requests to the mock server with an authenticated token,
+/// and a fresh application state
+async fn setup(server: &MockServer) -> (AppClient, SharedState) {
+    init_configs();
+
+    let client = AppClient::with_api_endpoint(&format!("{}/v1", server.uri()))
+        .await
+        .expect("construct client");
+    let now = chrono::Utc::now();
+    *client.get_token().lock().await.unwrap() = Some(rspotify::Token {
+        access_token: ACCESS_TOKEN.to_string(),
+        expires_in: chrono::Duration::hours(1),
+        expires_at: Some(now + chrono::Duration::hours(1)),
+        refresh_token: None,
+        scopes: std::collections::HashSet::new(),
+    });
+
+    let state = std::sync::Arc::new(state::State::new(false, false, false));
+    (client, state)
+}
+
+/// Mount a mock responding to requests matching the method and the path with a JSON fixture
+async fn mount_fixture(server: &MockServer, http_method: &str, url_path: &str, name: &str) {
+    Mock::given(method(http_method))
+        .and(path(url_path))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture(server, name)))
+        .mount(server)
+        .await;
+}
+
+/// Mount a mock responding to requests of the album covers
+async fn mount_images(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(wiremock::matchers::path_regex("^/images/"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(Vec::new()))
+        .mount(server)
+        .await;
+}
+
+/// Describe the player's state as shown by the playback window
+fn playback_summary(state: &SharedState) -> String {
+    let player = state.player.read();
+    let mut summary = String::new();
+
+    writeln!(summary, "connection: {:?}", player.connection_status).unwrap();
+    match player.currently_playing() {
+        Some(rspotify::model::PlayableItem::Track(track)) => writeln!(
+            summary,
+            "track: {} by {}",
+            track.name,
+            track
+                .artists
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .unwrap(),
+        Some(item) => writeln!(summary, "item: {item:?}").unwrap(),
+        None => writeln!(summary, "item: none").unwrap(),
+    }
+    match &player.buffered_playback {
+        Some(playback) => writeln!(
+            summary,
+            "device: {} ({})\nplaying: {}\nvolume: {:?}\nrepeat: {:?}\nshuffle: {}",
+            playback.device_name,
+            playback.device_id.as_deref().unwrap_or("-"),
+            playback.is_playing,
+            playback.volume,
+            playback.repeat_state,
+            playback.shuffle_state,
+        )
+        .unwrap(),
+        None => writeln!(summary, "playback: none").unwrap(),
+    }
+    summary
+}
+
+/// Describe the user's library as shown by the library page
+fn library_summary(state: &SharedState) -> String {
+    let data = state.data.read();
+    let mut summary = String::new();
+
+    writeln!(summary, "playlists:").unwrap();
+    for item in &data.user_data.playlists {
+        match item {
+            PlaylistFolderItem::Playlist(playlist) => {
+                writeln!(summary, "- {} ({})", playlist.name, playlist.owner.0).unwrap();
+            }
+            PlaylistFolderItem::Folder(folder) => {
+                writeln!(summary, "- folder {}", folder.name).unwrap();
+            }
+        }
+    }
+
+    writeln!(summary, "saved tracks:").unwrap();
+    let mut tracks = data.user_data.saved_tracks.values().collect::<Vec<_>>();
+    tracks.sort_by(|a, b| a.name.cmp(&b.name));
+    for track in tracks {
+        writeln!(
+            summary,
+            "- {} by {} ({}s)",
+            track.name,
+            track.artists_info(),
+            track.duration.as_secs()
+        )
+        .unwrap();
+    }
+    summary
+}
+
+#[tokio::test]
+async fn auth_sends_the_bearer_token() {
+    let server = MockServer::start().await;
+    let (client, state) = setup(&server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/"))
+        .and(header("authorization", format!("Bearer {ACCESS_TOKEN}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture(&server, "me.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .handle_request(&state, ClientRequest::GetCurrentUser)
+        .await
+        .expect("get the current user");
+
+    let data = state.data.read();
+    let user = data.user_data.user.as_ref().expect("current user");
+    assert_eq!(user.id.uri(), "spotify:user:test-user");
+    assert_eq!(user.display_name.as_deref(), Some("Test User"));
+}
+
+#[tokio::test]
+async fn auth_failure_marks_the_player_offline() {
+    let server = MockServer::start().await;
+    let (client, state) = setup(&server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/player"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": { "status": 401, "message": "Invalid access token" }
+        })))
+        .mount(&server)
+        .await;
+
+    let result = client
+        .handle_request(&state, ClientRequest::GetCurrentPlayback)
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(
+        state.player.read().connection_status,
+        ConnectionStatus::Offline
+    );
+}
+
+#[tokio::test]
+async fn playback_state_transitions() {
+    let server = MockServer::start().await;
+    let (client, state) = setup(&server).await;
+
+    mount_fixture(&server, "GET", "/v1/me/player", "playback.json").await;
+    mount_images(&server).await;
+    insta::assert_snapshot!("playback_initial", playback_summary(&state));
+
+    client
+        .handle_request(&state, ClientRequest::GetCurrentPlayback)
+        .await
+        .expect("get the current playback");
+    insta::assert_snapshot!("playback_playing", playback_summary(&state));
+
+    Mock::given(method("PUT"))
+        .and(path("/v1/me/player/pause"))
+        .and(query_param("device_id", "device-1"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/v1/me/player/volume"))
+        .and(query_param("volume_percent", "75"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    for request in [PlayerRequest::Pause, PlayerRequest::Volume(75)] {
+        client
+            .handle_request(&state, ClientRequest::Player(request))
+            .await
+            .expect("handle the player request");
+    }
+    insta::assert_snapshot!("playback_paused", playback_summary(&state));
+}
+
+#[tokio::test]
+async fn library_is_loaded_into_the_state() {
+    let server = MockServer::start().await;
+    let (client, state) = setup(&server).await;
+
+    // the playlists are paginated, the second page being linked from the first one
+    Mock::given(method("GET"))
+        .and(path("/v1/me/playlists"))
+        .and(query_param("offset", "1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(fixture(&server, "playlists_page_2.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/me/playlists"))
+        .and(query_param("limit", "50"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(fixture(&server, "playlists_page_1.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    mount_fixture(&server, "GET", "/v1/me/tracks", "saved_tracks.json").await;
+
+    for request in [
+        ClientRequest::GetUserPlaylists,
+        ClientRequest::GetUserSavedTracks,
+    ] {
+        client
+            .handle_request(&state, request)
+            .await
+            .expect("load the library");
+    }
+
+    insta::assert_snapshot!("library", library_summary(&state));
+}
+
+#[tokio::test]
+async fn leaving_a_page_cancels_its_requests() {
+    init_configs();
+    let state = state::State::new(false, false, false);
+
+    let left_page = state
+        .requests
+        .register(Some("spotify:album:left".to_string()));
+    let current_page = state
+        .requests
+        .register(Some("spotify:album:current".to_string()));
+    let unscoped = state.requests.register(None);
+
+    state
+        .requests
+        .cancel_page_requests(&["spotify:album:current".to_string()]);
+
+    tokio::time::timeout(
+        std::time::Duration::from_secs(1),
+        left_page.token().cancelled(),
+    )
+    .await
+    .expect("cancel the left page's request");
+    assert!(!current_page.token().is_cancelled());
+    assert!(!unscoped.token().is_cancelled());
+
+    state.requests.cancel_all();
+    assert!(current_page.token().is_cancelled());
+    assert!(unscoped.token().is_cancelled());
+}
@@ -0,0 +1,366 @@
+//! Snapshot tests of the application's rendering, run with `cargo test`.
+//!
+//! Pages and widgets are rendered with fixed data into a `TestBackend`, whose content is compared
+//! to the golden files in `tests/snapshots`, so rendering changes are reviewable as diffs of these files.
+//! After an intended change, the golden files are updated with `INSTA_UPDATE=always cargo test`
+//! (or `cargo insta review`).
+
+use std::sync::OnceLock;
+
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+use rspotify::prelude::Id;
+
+use spotify_player::{config, state, ui};
+use state::{
+    Context, ContextId, ContextPageType, ContextPageUIState, PageState, PartialContext,
+    PlaylistFolderItem, SharedState, Track, TTL_CACHE_DURATION, USER_LIKED_TRACKS_ID,
+};
+
+/// The size of the rendered terminal, similar to a small terminal window
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 30;
+
+/// Initialize the application's configs, which are shared by all tests of the process
+fn init_configs() {
+    static INIT: OnceLock<()> = OnceLock::new();
+
+    INIT.get_or_init(|| {
+        let folder =
+            std::env::temp_dir().join(format!("spotify_player-ui-tests-{}", std::process::id()));
+        let config_folder = folder.join("config");
+        let cache_folder = folder.join("cache");
+        for dir in [
+            &config_folder,
+            &cache_folder.join("audio"),
+            &cache_folder.join("image"),
+        ] {
+            std::fs::create_dir_all(dir).expect("create test folder");
+        }
+
+        let configs = config::Configs::new(&config_folder, &cache_folder).expect("default configs");
+        config::set_config(configs);
+    });
+}
+
+/// Read a JSON fixture shared with the client tests
+fn fixture<T: serde::de::DeserializeOwned>(name: &str) -> T {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let text = std::fs::read_to_string(&path).expect("read fixture");
+    serde_json::from_str(&text.replace("{{server}}", "http://localhost")).expect("parse fixture")
+}
+
+/// Construct an application state with a paused playback and the user's library
+fn setup() -> SharedState {
+    init_configs();
+    let state = std::sync::Arc::new(state::State::new(false, false, false));
+    // the visualizations are animated, so they are tested separately at fixed phases
+    #[cfg(feature = "fx")]
+    {
+        state.ui.lock().enable_visualization = false;
+    }
+
+    {
+        // a paused playback's progress doesn't depend on the time of the rendering
+        let mut playback: rspotify::model::CurrentPlaybackContext = fixture("playback.json");
+        playback.is_playing = false;
+        let mut player = state.player.write();
+        player.buffered_playback = Some(state::PlaybackMetadata::from_playback(&playback));
+        player.playback = Some(playback);
+        player.playback_last_updated_time = Some(std::time::Instant::now());
+    }
+
+    {
+        let mut data = state.data.write();
+        for page in ["playlists_page_1.json", "playlists_page_2.json"] {
+            let page: rspotify::model::Page<rspotify::model::SimplifiedPlaylist> = fixture(page);
+            data.user_data.playlists.extend(
+                page.items
+                    .into_iter()
+                    .map(|p| PlaylistFolderItem::Playlist(p.into())),
+            );
+        }
+
+        let page: rspotify::model::Page<rspotify::model::SavedTrack> = fixture("saved_tracks.json");
+        let tracks = page
+            .items
+            .into_iter()
+            .filter_map(|t| Track::try_from_full_track(t.track))
+            .collect::<Vec<_>>();
+        for track in &tracks {
+            data.user_data
+                .saved_tracks
+                .insert(track.id.uri(), track.clone());
+        }
+        data.caches.context.insert(
+            USER_LIKED_TRACKS_ID.uri.clone(),
+            Context::Tracks {
+                tracks,
+                desc: "User's liked tracks".to_string(),
+            },
+            *TTL_CACHE_DURATION,
+        );
+    }
+
+    state
+}
+
+/// Render the whole application into a terminal of the given size
+fn render_application(state: &SharedState, width: u16, height: u16) -> Terminal<TestBackend> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).expect("test terminal");
+    let mut ui = state.ui.lock();
+    terminal
+        .draw(|frame| ui::render(frame, state, &mut ui))
+        .expect("render the application");
+    drop(ui);
+    terminal
+}
+
+#[test]
+fn library_page() {
+    let state = setup();
+
+    let terminal = render_application(&state, WIDTH, HEIGHT);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn liked_tracks_page() {
+    let state = setup();
+    let id = ContextId::Tracks(USER_LIKED_TRACKS_ID.to_owned());
+    state.ui.lock().new_page(PageState::Context {
+        id: Some(id.clone()),
+        context_page_type: ContextPageType::Browsing(id),
+        state: Some(ContextPageUIState::new_tracks()),
+    });
+
+    let terminal = render_application(&state, WIDTH, HEIGHT);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn unlike_track_confirmation_popup() {
+    let state = setup();
+    let id = ContextId::Tracks(USER_LIKED_TRACKS_ID.to_owned());
+    let track = state
+        .data
+        .read()
+        .context_tracks(&id)
+        .and_then(|tracks| tracks.first().cloned())
+        .expect("a liked track");
+    {
+        let mut ui = state.ui.lock();
+        ui.new_page(PageState::Context {
+            id: Some(id.clone()),
+            context_page_type: ContextPageType::Browsing(id),
+            state: Some(ContextPageUIState::new_tracks()),
+        });
+        ui.popup = Some(state::PopupState::TrackRemovalConfirmation(
+            track,
+            state::TrackRemoval::Liked,
+        ));
+    }
+
+    let terminal = render_application(&state, WIDTH, HEIGHT);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn remove_unliked_track_from_playlist_confirmation_popup() {
+    let state = setup();
+    let id = ContextId::Tracks(USER_LIKED_TRACKS_ID.to_owned());
+    let (track, playlist) = {
+        let mut data = state.data.write();
+        let track = data
+            .context_tracks(&id)
+            .and_then(|tracks| tracks.first().cloned())
+            .expect("a track");
+        // the track is only in the playlist, not in the liked songs
+        data.user_data.saved_tracks.remove(&track.id.uri());
+        let playlist = data
+            .user_data
+            .playlists
+            .iter()
+            .find_map(|item| match item {
+                PlaylistFolderItem::Playlist(p) => Some(p.clone()),
+                PlaylistFolderItem::Folder(_) => None,
+            })
+            .expect("a playlist");
+        (track, playlist)
+    };
+    state.ui.lock().popup = Some(state::PopupState::TrackRemovalConfirmation(
+        track,
+        state::TrackRemoval::Playlist(playlist.id, playlist.name),
+    ));
+
+    let terminal = render_application(&state, WIDTH, HEIGHT);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn artist_page_while_loading() {
+    let state = setup();
+    let id = ContextId::Artist(
+        rspotify::model::ArtistId::from_id("0OdUWJ0sBjDrqHygGUXeCF")
+            .expect("valid artist id")
+            .into_static(),
+    );
+    {
+        // the artist's top tracks have arrived, its albums and related artists haven't
+        let mut data = state.data.write();
+        let top_tracks = match data.caches.context.get(&USER_LIKED_TRACKS_ID.uri) {
+            Some(Context::Tracks { tracks, .. }) => tracks.iter().take(3).cloned().collect(),
+            _ => vec![],
+        };
+        data.caches.partial_contexts.insert(
+            id.uri(),
+            PartialContext {
+                description: Some("Band of Horses".to_string()),
+                tracks: Some(top_tracks),
+                ..PartialContext::default()
+            },
+        );
+    }
+    state.ui.lock().new_page(PageState::Context {
+        id: Some(id.clone()),
+        context_page_type: ContextPageType::Browsing(id),
+        state: Some(ContextPageUIState::new_artist()),
+    });
+
+    let terminal = render_application(&state, WIDTH, HEIGHT);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn compact_playback_window() {
+    let state = setup();
+
+    let terminal = render_application(&state, WIDTH, 4);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn compact_controls_cleared_after_resize() {
+    let state = setup();
+
+    render_application(&state, WIDTH, 4);
+    assert_ne!(state.ui.lock().compact_controls_rect, Rect::default());
+
+    // growing back to the normal layout must not leave the compact controls clickable
+    render_application(&state, WIDTH, HEIGHT);
+    assert_eq!(state.ui.lock().compact_controls_rect, Rect::default());
+}
+
+#[test]
+fn playback_window() {
+    let state = setup();
+    let mut terminal = Terminal::new(TestBackend::new(60, 8)).expect("test terminal");
+
+    let mut ui = state.ui.lock();
+    terminal
+        .draw(|frame| {
+            ui::playback::render_playback_window(frame, &state, &mut ui, frame.area());
+        })
+        .expect("render the playback window");
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[cfg(feature = "fx")]
+#[test]
+fn visualizations_at_fixed_phases() {
+    use ui::{glyphs, visualizations};
+
+    type Render = fn(&mut ratatui::Frame, Rect, f64, Option<(u8, u8, u8)>, &glyphs::GlyphSet, bool);
+
+    let renders: [(&str, Render); 3] = [
+        ("waves", visualizations::render_concentric_waves),
+        ("bars", visualizations::render_spectrum_bars),
+        ("particles", visualizations::render_particles),
+    ];
+    for (name, render) in renders {
+        for (glyph_set, glyphs) in [("unicode", &glyphs::UNICODE), ("ascii", &glyphs::ASCII)] {
+            for phase in [0.0, 0.25, 2.5] {
+                let mut terminal = Terminal::new(TestBackend::new(40, 12)).expect("test terminal");
+                terminal
+                    .draw(|frame| render(frame, frame.area(), phase, None, glyphs, true))
+                    .expect("render the visualization");
+                insta::assert_snapshot!(
+                    format!("{name}_{glyph_set}_at_{phase}"),
+                    terminal.backend()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "fx")]
+#[test]
+fn shader_visualization_at_fixed_phases() {
+    use ui::{
+        glyphs,
+        shader::{Inputs, Shader},
+        visualizations,
+    };
+
+    // ripples from the center, pulsing with the beats and the first audio band
+    let shader = Shader::compile(
+        "(0.5 + 0.5 * sin(20 * sqrt((x - 0.5) ^ 2 + (y - 0.5) ^ 2) - 2 * t)) * (0.5 + 0.5 * band(0)) * (1 - fract(beat) / 2)",
+    )
+    .expect("compile the shader");
+    for (glyph_set, glyphs) in [("unicode", &glyphs::UNICODE), ("ascii", &glyphs::ASCII)] {
+        for phase in [0.0, 2.5] {
+            let (loudness, bands) = visualizations::audio_levels(None, phase / 2.0, phase);
+            let inputs = Inputs {
+                t: phase / 2.0,
+                beat: phase,
+                loudness,
+                bands,
+                ..Default::default()
+            };
+            let mut terminal = Terminal::new(TestBackend::new(40, 12)).expect("test terminal");
+            terminal
+                .draw(|frame| {
+                    visualizations::render_shader(
+                        frame,
+                        frame.area(),
+                        &shader,
+                        inputs,
+                        None,
+                        glyphs,
+                        true,
+                    );
+                })
+                .expect("render the visualization");
+            insta::assert_snapshot!(format!("shader_{glyph_set}_at_{phase}"), terminal.backend());
+        }
+    }
+}
+
+#[cfg(feature = "fx")]
+#[test]
+fn now_playing_page_with_deterministic_clock() {
+    use ui::{clock::Clock, visualizations::VisualizationMode};
+
+    let state = setup();
+    {
+        let mut ui = state.ui.lock();
+        ui.enable_visualization = true;
+        ui.visualization.mode = VisualizationMode::Bars;
+        ui.set_clock(Clock::deterministic(
+            0,
+            std::time::Duration::from_millis(2500),
+        ));
+        ui.new_page(PageState::NowPlaying);
+    }
+
+    let terminal = render_application(&state, WIDTH, HEIGHT);
+    insta::assert_snapshot!("now_playing_page_at_2.5s", terminal.backend());
+
+    // stepping the clock animates the visualization as if the track was playing
+    state
+        .ui
+        .lock()
+        .clock
+        .advance(std::time::Duration::from_millis(250));
+    let terminal = render_application(&state, WIDTH, HEIGHT);
+    insta::assert_snapshot!("now_playing_page_at_2.75s", terminal.backend());
+}